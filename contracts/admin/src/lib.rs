@@ -2,7 +2,10 @@
 use escrow::EscrowContractClient;
 use payments::PaymentContractClient;
 use refund::RefundContractClient;
-use soroban_sdk::{contract, contracterror, contractimpl, contracttype, Address, Env, String};
+use soroban_sdk::{
+    contract, contracterror, contractevent, contractimpl, contracttype, vec, Address, BytesN, Env,
+    IntoVal, InvokeError, String, Symbol, Val, Vec,
+};
 
 #[contracterror]
 #[derive(Clone, Debug, PartialEq)]
@@ -10,6 +13,22 @@ pub enum Error {
     AlreadyInitialized = 1,
     NotInitialized = 2,
     Unauthorized = 3,
+    GovernanceNotInitialized = 4,
+    InvalidGovernanceConfig = 5,
+    NotSigner = 6,
+    ProposalNotFound = 7,
+    InvalidProposalStatus = 8,
+    AlreadyApproved = 9,
+    ProposalExpired = 10,
+    TimelockNotElapsed = 11,
+    CallNotAllowed = 12,
+    ForwardedCallFailed = 13,
+    GovernanceRequired = 14,
+    UpgradeFailed = 15,
+    CapabilityNotFound = 16,
+    CapabilityPaused = 17,
+    MigrationPending = 18,
+    NoMigrationPending = 19,
 }
 
 #[contracttype]
@@ -19,8 +38,244 @@ pub enum DataKey {
     PaymentContract,
     EscrowContract,
     RefundContract,
+    Governance,
+    ProposalCount,
+    Proposal(u64),
+    /// IDs of proposals that are still pending or queued.
+    OpenProposals,
+    AllowedCall(ChildContract, Symbol),
+    Capability(Symbol),
+    Capabilities,
+    PausedCapabilities,
+    EscrowMigration,
 }
 
+/// Child contract a governance proposal can forward a call to.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChildContract {
+    Payment,
+    Escrow,
+    Refund,
+}
+
+/// A child contract function covered by a pause capability.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapabilityFunction {
+    pub target: ChildContract,
+    /// Name passed to the child's `pause_function` / `unpause_function`.
+    pub function: String,
+}
+
+/// A child's `get_pause_state` together with its `get_paused_until`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChildPauseState {
+    pub globally_paused: bool,
+    pub paused_functions: Vec<String>,
+    pub paused_at: u64,
+    pub paused_by: Address,
+    pub pause_reason: String,
+    /// When a timed global pause lifts on its own; `None` for an indefinite pause.
+    pub paused_until: Option<u64>,
+}
+
+/// Pause state across the platform, as returned by `get_platform_pause_status`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlatformPauseStatus {
+    pub payment: ChildPauseState,
+    pub escrow: ChildPauseState,
+    pub refund: ChildPauseState,
+    /// Capabilities currently paused through `pause_capability`.
+    pub paused_capabilities: Vec<Symbol>,
+}
+
+/// M-of-N signer set and timing rules for governance proposals.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceConfig {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+    /// Delay between a proposal reaching its threshold and becoming executable.
+    pub timelock_seconds: u64,
+    /// How long a proposal can collect approvals before it expires.
+    pub proposal_ttl: u64,
+}
+
+/// A call forwarded to a child contract. The admin contract's own address is
+/// passed as the first (`admin`) argument, followed by `args`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ForwardedCall {
+    pub target: ChildContract,
+    pub function: Symbol,
+    pub args: Vec<Val>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum GovernanceAction {
+    /// Invoke a whitelisted configuration function on a child contract.
+    Forward(ForwardedCall),
+    /// Add (`true`) or remove (`false`) a child function from the whitelist.
+    SetCallAllowed(ChildContract, Symbol, bool),
+    /// Replace the signer set, threshold and timing rules.
+    UpdateConfig(GovernanceConfig),
+    /// Upgrade and migrate child contracts in order (see `upgrade_all`).
+    UpgradeAll(Vec<ChildUpgrade>),
+    /// Point the admin contract at a new child contract address.
+    SetChildContract(ChildContract, Address),
+}
+
+/// One step of a staged `upgrade_all` rollout.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChildUpgrade {
+    pub target: ChildContract,
+    /// Hash of WASM already uploaded to the network.
+    pub wasm_hash: BytesN<32>,
+    /// Schema version to migrate to after the upgrade; `0` skips the migration.
+    /// For escrow any non-zero value starts its batched `begin_migration`, which
+    /// `continue_escrow_migration` then drives to completion.
+    pub schema_version: u32,
+}
+
+/// An escrow schema migration started by an upgrade and not yet completed.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowMigrationProgress {
+    pub schema_version: u32,
+    /// Next escrow ID to migrate.
+    pub next_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProposalStatus {
+    /// Collecting approvals.
+    Pending,
+    /// Threshold reached; executable once `eta` has passed.
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GovernanceProposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: GovernanceAction,
+    pub description: String,
+    pub approvals: Vec<Address>,
+    pub status: ProposalStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    /// Earliest execution time; `0` until the proposal is queued.
+    pub eta: u64,
+    /// Execution or cancellation time; `0` while the proposal is open.
+    pub closed_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceProposalApproved {
+    pub proposal_id: u64,
+    pub approver: Address,
+    pub approval_count: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceProposalQueued {
+    pub proposal_id: u64,
+    pub eta: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceProposalExecuted {
+    pub proposal_id: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceProposalCancelled {
+    pub proposal_id: u64,
+    pub cancelled_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChildContractUpgraded {
+    pub target: ChildContract,
+    pub wasm_hash: BytesN<32>,
+    pub schema_version: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowMigrationCompleted {
+    pub schema_version: u32,
+    pub migrated_count: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapabilityPaused {
+    pub capability: Symbol,
+    pub paused_by: Address,
+    pub reason: String,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapabilityUnpaused {
+    pub capability: Symbol,
+    pub unpaused_by: Address,
+}
+
+const LEDGERS_PER_DAY: u32 = 17_280;
+const PROPOSAL_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+const PROPOSAL_TTL_EXTEND_TO: u32 = 120 * LEDGERS_PER_DAY;
+
+/// Child functions governance may call out of the box. All of them take the
+/// calling admin as their first argument.
+const DEFAULT_ALLOWED_CALLS: [(ChildContract, &str); 13] = [
+    (ChildContract::Payment, "set_fee_config"),
+    (ChildContract::Payment, "set_rate_limit_config"),
+    (ChildContract::Payment, "set_merchant_rate_limit"),
+    (ChildContract::Payment, "pause_function"),
+    (ChildContract::Payment, "unpause_function"),
+    (ChildContract::Escrow, "set_escrow_fee_config"),
+    (ChildContract::Escrow, "pause_function"),
+    (ChildContract::Escrow, "unpause_function"),
+    (ChildContract::Refund, "set_customer_rate_limit"),
+    (ChildContract::Refund, "set_global_refund_rate_limit"),
+    (ChildContract::Refund, "set_arbitration_fee_config"),
+    (ChildContract::Refund, "pause_function"),
+    (ChildContract::Refund, "unpause_function"),
+];
+
+/// Capability seeded by `initialize`: every function that pays money back out.
+const REFUNDS_CAPABILITY: &str = "refunds";
+const REFUNDS_CAPABILITY_FUNCTIONS: [(ChildContract, &str); 5] = [
+    (ChildContract::Payment, "refund_payment"),
+    (ChildContract::Payment, "partial_refund"),
+    (ChildContract::Refund, "process_refund"),
+    (ChildContract::Refund, "process_refund_batch"),
+    (ChildContract::Escrow, "release_escrow"),
+];
+
 #[contract]
 pub struct AdminContract;
 
@@ -66,6 +321,15 @@ impl AdminContract {
             .instance()
             .set(&DataKey::RefundContract, &refund_contract);
 
+        let mut functions = Vec::new(&env);
+        for (target, function) in REFUNDS_CAPABILITY_FUNCTIONS.iter() {
+            functions.push_back(CapabilityFunction {
+                target: *target,
+                function: String::from_str(&env, function),
+            });
+        }
+        Self::store_capability(&env, Symbol::new(&env, REFUNDS_CAPABILITY), functions);
+
         Ok(())
     }
 
@@ -74,6 +338,8 @@ impl AdminContract {
     /// # Parameters
     /// - `pauser`: the pauser address that must be authorized.
     /// - `reason`: a human-readable explanation for the emergency pause.
+    /// - `duration_seconds`: how long the pause lasts before each child lifts it
+    ///   on its own; `None` pauses until `emergency_unpause_all`.
     ///
     /// # Returns
     /// Returns `Ok(())` when all child contracts are paused successfully.
@@ -82,7 +348,12 @@ impl AdminContract {
     /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
     /// and `Error::Unauthorized` if the provided pauser address does not match the
     /// stored pauser.
-    pub fn emergency_pause_all(env: Env, pauser: Address, reason: String) -> Result<(), Error> {
+    pub fn emergency_pause_all(
+        env: Env,
+        pauser: Address,
+        reason: String,
+        duration_seconds: Option<u64>,
+    ) -> Result<(), Error> {
         pauser.require_auth();
 
         let stored_pauser: Address = env
@@ -110,9 +381,21 @@ impl AdminContract {
             .get(&DataKey::RefundContract)
             .ok_or(Error::NotInitialized)?;
 
-        PaymentContractClient::new(&env, &payment_contract).pause_contract(&pauser, &reason);
-        EscrowContractClient::new(&env, &escrow_contract).pause_contract(&pauser, &reason);
-        RefundContractClient::new(&env, &refund_contract).pause_contract(&pauser, &reason);
+        PaymentContractClient::new(&env, &payment_contract).pause_contract(
+            &pauser,
+            &reason,
+            &duration_seconds,
+        );
+        EscrowContractClient::new(&env, &escrow_contract).pause_contract(
+            &pauser,
+            &reason,
+            &duration_seconds,
+        );
+        RefundContractClient::new(&env, &refund_contract).pause_contract(
+            &pauser,
+            &reason,
+            &duration_seconds,
+        );
 
         Ok(())
     }
@@ -164,16 +447,191 @@ impl AdminContract {
         Ok(())
     }
 
+    /// Defines or replaces a named pause capability: a set of child contract
+    /// functions that `pause_capability` pauses together. An empty `functions`
+    /// list removes the capability.
+    ///
+    /// # Parameters
+    /// - `admin`: the admin address that must be authorized.
+    /// - `capability`: the capability name, e.g. `refunds`.
+    /// - `functions`: the child functions the capability covers.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
+    /// `Error::Unauthorized` if `admin` does not match the stored admin, and
+    /// `Error::CapabilityPaused` if the capability is currently paused.
+    pub fn set_capability(
+        env: Env,
+        admin: Address,
+        capability: Symbol,
+        functions: Vec<CapabilityFunction>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        if admin != stored_admin {
+            return Err(Error::Unauthorized);
+        }
+        if Self::paused_capabilities(&env).contains(&capability) {
+            return Err(Error::CapabilityPaused);
+        }
+
+        Self::store_capability(&env, capability, functions);
+        Ok(())
+    }
+
+    /// Returns the child functions covered by `capability`.
+    ///
+    /// # Errors
+    /// Returns `Error::CapabilityNotFound` if no such capability is defined.
+    pub fn get_capability(env: Env, capability: Symbol) -> Result<Vec<CapabilityFunction>, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Capability(capability))
+            .ok_or(Error::CapabilityNotFound)
+    }
+
+    /// Returns the names of all defined capabilities.
+    pub fn get_capabilities(env: Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Capabilities)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Pauses every child function covered by `capability`, across all three
+    /// child contracts, in one Soroban call.
+    ///
+    /// # Parameters
+    /// - `pauser`: the pauser address that must be authorized.
+    /// - `capability`: the capability to pause.
+    /// - `reason`: a human-readable explanation recorded by each child contract.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
+    /// `Error::Unauthorized` if `pauser` does not match the stored pauser, and
+    /// `Error::CapabilityNotFound` if the capability is not defined.
+    pub fn pause_capability(
+        env: Env,
+        pauser: Address,
+        capability: Symbol,
+        reason: String,
+    ) -> Result<(), Error> {
+        Self::require_pauser(&env, &pauser)?;
+        let functions = Self::get_capability(env.clone(), capability.clone())?;
+
+        for entry in functions.iter() {
+            let contract = Self::child_contract(&env, entry.target)?;
+            match entry.target {
+                ChildContract::Payment => PaymentContractClient::new(&env, &contract)
+                    .pause_function(&pauser, &entry.function, &reason),
+                ChildContract::Escrow => EscrowContractClient::new(&env, &contract).pause_function(
+                    &pauser,
+                    &entry.function,
+                    &reason,
+                ),
+                ChildContract::Refund => RefundContractClient::new(&env, &contract).pause_function(
+                    &pauser,
+                    &entry.function,
+                    &reason,
+                ),
+            }
+        }
+
+        let mut paused = Self::paused_capabilities(&env);
+        if !paused.contains(&capability) {
+            paused.push_back(capability.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::PausedCapabilities, &paused);
+        }
+
+        CapabilityPaused {
+            capability,
+            paused_by: pauser,
+            reason,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Unpauses the child functions covered by `capability`. Functions that are
+    /// also covered by another paused capability stay paused.
+    ///
+    /// # Parameters
+    /// - `pauser`: the pauser address that must be authorized.
+    /// - `capability`: the capability to unpause.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
+    /// `Error::Unauthorized` if `pauser` does not match the stored pauser, and
+    /// `Error::CapabilityNotFound` if the capability is not defined.
+    pub fn unpause_capability(env: Env, pauser: Address, capability: Symbol) -> Result<(), Error> {
+        Self::require_pauser(&env, &pauser)?;
+        let functions = Self::get_capability(env.clone(), capability.clone())?;
+
+        let mut paused = Self::paused_capabilities(&env);
+        if let Some(index) = paused.first_index_of(&capability) {
+            paused.remove(index);
+            env.storage()
+                .instance()
+                .set(&DataKey::PausedCapabilities, &paused);
+        }
+
+        for entry in functions.iter() {
+            if Self::covered_by_paused_capability(&env, &paused, &entry) {
+                continue;
+            }
+            let contract = Self::child_contract(&env, entry.target)?;
+            match entry.target {
+                ChildContract::Payment => PaymentContractClient::new(&env, &contract)
+                    .unpause_function(&pauser, &entry.function),
+                ChildContract::Escrow => EscrowContractClient::new(&env, &contract)
+                    .unpause_function(&pauser, &entry.function),
+                ChildContract::Refund => RefundContractClient::new(&env, &contract)
+                    .unpause_function(&pauser, &entry.function),
+            }
+        }
+
+        CapabilityUnpaused {
+            capability,
+            unpaused_by: pauser,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the pause state of the payment, escrow, and refund contracts
+    /// together with the capabilities currently paused from this contract.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized.
+    pub fn get_platform_pause_status(env: Env) -> Result<PlatformPauseStatus, Error> {
+        Ok(PlatformPauseStatus {
+            payment: Self::child_pause_state(&env, ChildContract::Payment)?,
+            escrow: Self::child_pause_state(&env, ChildContract::Escrow)?,
+            refund: Self::child_pause_state(&env, ChildContract::Refund)?,
+            paused_capabilities: Self::paused_capabilities(&env),
+        })
+    }
+
     /// Updates the stored payment contract address.
     ///
     /// # Parameters
     /// - `admin`: the admin address that must be authorized.
     /// - `payment_contract`: the new payment contract address.
     ///
+    /// Once governance is initialized the address can only be changed through a
+    /// `GovernanceAction::SetChildContract` proposal.
+    ///
     /// # Errors
     /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
-    /// and `Error::Unauthorized` if the provided admin address does not match the
-    /// stored admin.
+    /// `Error::Unauthorized` if the provided admin address does not match the
+    /// stored admin, and `Error::GovernanceRequired` once governance is initialized.
     pub fn set_payment_contract(
         env: Env,
         admin: Address,
@@ -189,10 +647,14 @@ impl AdminContract {
         if admin != stored_admin {
             return Err(Error::Unauthorized);
         }
+        if env.storage().instance().has(&DataKey::Governance) {
+            return Err(Error::GovernanceRequired);
+        }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::PaymentContract, &payment_contract);
+        env.storage().instance().set(
+            &Self::child_contract_key(ChildContract::Payment),
+            &payment_contract,
+        );
 
         Ok(())
     }
@@ -203,10 +665,13 @@ impl AdminContract {
     /// - `admin`: the admin address that must be authorized.
     /// - `escrow_contract`: the new escrow contract address.
     ///
+    /// Once governance is initialized the address can only be changed through a
+    /// `GovernanceAction::SetChildContract` proposal.
+    ///
     /// # Errors
     /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
-    /// and `Error::Unauthorized` if the provided admin address does not match the
-    /// stored admin.
+    /// `Error::Unauthorized` if the provided admin address does not match the
+    /// stored admin, and `Error::GovernanceRequired` once governance is initialized.
     pub fn set_escrow_contract(
         env: Env,
         admin: Address,
@@ -222,10 +687,14 @@ impl AdminContract {
         if admin != stored_admin {
             return Err(Error::Unauthorized);
         }
+        if env.storage().instance().has(&DataKey::Governance) {
+            return Err(Error::GovernanceRequired);
+        }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::EscrowContract, &escrow_contract);
+        env.storage().instance().set(
+            &Self::child_contract_key(ChildContract::Escrow),
+            &escrow_contract,
+        );
 
         Ok(())
     }
@@ -236,10 +705,13 @@ impl AdminContract {
     /// - `admin`: the admin address that must be authorized.
     /// - `refund_contract`: the new refund contract address.
     ///
+    /// Once governance is initialized the address can only be changed through a
+    /// `GovernanceAction::SetChildContract` proposal.
+    ///
     /// # Errors
     /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
-    /// and `Error::Unauthorized` if the provided admin address does not match the
-    /// stored admin.
+    /// `Error::Unauthorized` if the provided admin address does not match the
+    /// stored admin, and `Error::GovernanceRequired` once governance is initialized.
     pub fn set_refund_contract(
         env: Env,
         admin: Address,
@@ -255,13 +727,725 @@ impl AdminContract {
         if admin != stored_admin {
             return Err(Error::Unauthorized);
         }
+        if env.storage().instance().has(&DataKey::Governance) {
+            return Err(Error::GovernanceRequired);
+        }
+
+        env.storage().instance().set(
+            &Self::child_contract_key(ChildContract::Refund),
+            &refund_contract,
+        );
+
+        Ok(())
+    }
+
+    /// Upgrades child contracts in the given order, running each one's schema
+    /// migration right after its upgrade. The rollout is atomic: if any upgrade or
+    /// migration fails, every step is rolled back.
+    ///
+    /// The admin contract must be an admin of each child (with a multisig threshold
+    /// of one on payment and escrow). Once governance is initialized, upgrades must
+    /// go through a `GovernanceAction::UpgradeAll` proposal instead.
+    ///
+    /// # Parameters
+    /// - `admin`: the admin address that must be authorized.
+    /// - `upgrades`: the steps to run, in order.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
+    /// `Error::Unauthorized` if `admin` does not match the stored admin,
+    /// `Error::GovernanceRequired` once governance is initialized, and
+    /// `Error::MigrationPending` if an escrow step runs while an earlier escrow
+    /// migration is still in progress, and `Error::UpgradeFailed` if a child
+    /// rejected its upgrade or migration.
+    pub fn upgrade_all(env: Env, admin: Address, upgrades: Vec<ChildUpgrade>) -> Result<(), Error> {
+        admin.require_auth();
+
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        if admin != stored_admin {
+            return Err(Error::Unauthorized);
+        }
+        if env.storage().instance().has(&DataKey::Governance) {
+            return Err(Error::GovernanceRequired);
+        }
+
+        Self::run_upgrades(&env, &upgrades)
+    }
+
+    /// Migrates the next `batch_size` escrows of a migration started by an escrow
+    /// upgrade, and completes the migration once every escrow has been migrated.
+    /// Callable by anyone; until it reports completion the escrow upgrade stays
+    /// pending and further escrow upgrades are rejected.
+    ///
+    /// # Returns
+    /// `true` once the migration has completed and the escrow schema version is set.
+    ///
+    /// # Errors
+    /// Returns `Error::NoMigrationPending` if no escrow migration is in progress and
+    /// `Error::UpgradeFailed` if the escrow contract rejected a batch or the completion.
+    pub fn continue_escrow_migration(env: Env, batch_size: u32) -> Result<bool, Error> {
+        let mut progress: EscrowMigrationProgress = env
+            .storage()
+            .instance()
+            .get(&DataKey::EscrowMigration)
+            .ok_or(Error::NoMigrationPending)?;
+        let contract = Self::child_contract(&env, ChildContract::Escrow)?;
+        let this: Val = env.current_contract_address().into_val(&env);
+
+        let total = EscrowContractClient::new(&env, &contract)
+            .get_migration_status()
+            .total_count;
+        let end = progress
+            .next_id
+            .saturating_add(batch_size as u64)
+            .min(total + 1);
+        if progress.next_id < end {
+            let mut ids: Vec<u64> = Vec::new(&env);
+            for escrow_id in progress.next_id..end {
+                ids.push_back(escrow_id);
+            }
+            let args = vec![&env, this, ids.into_val(&env)];
+            let batch = Symbol::new(&env, "migrate_escrow_batch");
+            if !Self::invoke_child(&env, &contract, &batch, args) {
+                return Err(Error::UpgradeFailed);
+            }
+            progress.next_id = end;
+        }
+
+        if progress.next_id <= total {
+            env.storage()
+                .instance()
+                .set(&DataKey::EscrowMigration, &progress);
+            return Ok(false);
+        }
+
+        let complete = Symbol::new(&env, "complete_migration");
+        if !Self::invoke_child(&env, &contract, &complete, vec![&env, this]) {
+            return Err(Error::UpgradeFailed);
+        }
+        env.storage().instance().remove(&DataKey::EscrowMigration);
+        EscrowMigrationCompleted {
+            schema_version: progress.schema_version,
+            migrated_count: EscrowContractClient::new(&env, &contract)
+                .get_migration_status()
+                .migrated_count,
+        }
+        .publish(&env);
+        Ok(true)
+    }
+
+    /// Returns the escrow migration an upgrade left pending, if any.
+    pub fn get_escrow_migration(env: Env) -> Option<EscrowMigrationProgress> {
+        env.storage().instance().get(&DataKey::EscrowMigration)
+    }
+
+    // ── Governance ───────────────────────────────────────────────────────────
+
+    /// Sets up M-of-N governance over the child contracts.
+    ///
+    /// Proposals need `config.threshold` signer approvals and then wait out
+    /// `config.timelock_seconds` before they can be executed. The default set of
+    /// child configuration functions (fee configs, rate limits, `pause_function`
+    /// and `unpause_function`) is whitelisted for forwarding. For forwarded calls
+    /// to succeed, this contract's address must be an admin of each child contract.
+    ///
+    /// # Parameters
+    /// - `admin`: the stored admin address, which must be authorized.
+    /// - `config`: the signer set, threshold, timelock and proposal TTL.
+    ///
+    /// # Errors
+    /// Returns `Error::NotInitialized` if the admin contract has not been initialized,
+    /// `Error::Unauthorized` if `admin` does not match the stored admin,
+    /// `Error::AlreadyInitialized` if governance is already set up, and
+    /// `Error::InvalidGovernanceConfig` if the threshold is zero, exceeds the number
+    /// of signers, or the signer list contains duplicates.
+    pub fn init_governance(
+        env: Env,
+        admin: Address,
+        config: GovernanceConfig,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        if admin != stored_admin {
+            return Err(Error::Unauthorized);
+        }
+        if env.storage().instance().has(&DataKey::Governance) {
+            return Err(Error::AlreadyInitialized);
+        }
+        Self::validate_config(&config)?;
 
+        env.storage().instance().set(&DataKey::Governance, &config);
+        for (target, function) in DEFAULT_ALLOWED_CALLS.iter() {
+            env.storage().instance().set(
+                &DataKey::AllowedCall(*target, Symbol::new(&env, function)),
+                &true,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current governance configuration.
+    ///
+    /// # Errors
+    /// Returns `Error::GovernanceNotInitialized` if `init_governance` has not been called.
+    pub fn get_governance_config(env: Env) -> Result<GovernanceConfig, Error> {
+        Self::governance_config(&env)
+    }
+
+    /// Creates a governance proposal. The proposer's approval is counted
+    /// immediately, so with a threshold of one the proposal is queued at once.
+    ///
+    /// # Parameters
+    /// - `proposer`: a governance signer, which must be authorized.
+    /// - `action`: the action to run once the proposal is executed.
+    /// - `description`: a human-readable summary kept in the proposal history.
+    ///
+    /// # Returns
+    /// The new proposal ID.
+    ///
+    /// # Errors
+    /// Returns `Error::GovernanceNotInitialized`, `Error::NotSigner` if `proposer`
+    /// is not a signer, `Error::CallNotAllowed` if a forwarded call is not
+    /// whitelisted, and `Error::InvalidGovernanceConfig` for an invalid config update.
+    pub fn propose_action(
+        env: Env,
+        proposer: Address,
+        action: GovernanceAction,
+        description: String,
+    ) -> Result<u64, Error> {
+        proposer.require_auth();
+
+        let config = Self::governance_config(&env)?;
+        if !config.signers.contains(&proposer) {
+            return Err(Error::NotSigner);
+        }
+        Self::validate_action(&env, &action)?;
+
+        let proposal_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0)
+            + 1;
         env.storage()
             .instance()
-            .set(&DataKey::RefundContract, &refund_contract);
+            .set(&DataKey::ProposalCount, &proposal_id);
+
+        let now = env.ledger().timestamp();
+        let mut proposal = GovernanceProposal {
+            id: proposal_id,
+            proposer: proposer.clone(),
+            action,
+            description,
+            approvals: Vec::from_array(&env, [proposer.clone()]),
+            status: ProposalStatus::Pending,
+            created_at: now,
+            expires_at: now.saturating_add(config.proposal_ttl),
+            eta: 0,
+            closed_at: 0,
+        };
+
+        GovernanceProposalCreated {
+            proposal_id,
+            proposer,
+        }
+        .publish(&env);
+        Self::queue_if_ready(&env, &config, &mut proposal);
+        Self::save_proposal(&env, &proposal);
+        let mut open = Self::open_proposals(&env);
+        open.push_back(proposal_id);
+        env.storage().instance().set(&DataKey::OpenProposals, &open);
+
+        Ok(proposal_id)
+    }
+
+    /// Approves a pending proposal. Once the threshold is reached the proposal is
+    /// queued and becomes executable after the timelock.
+    ///
+    /// # Errors
+    /// Returns `Error::NotSigner`, `Error::ProposalNotFound`,
+    /// `Error::InvalidProposalStatus` if the proposal is no longer pending,
+    /// `Error::ProposalExpired` once its approval window has closed, and
+    /// `Error::AlreadyApproved` if `approver` has already approved it.
+    pub fn approve_action(env: Env, approver: Address, proposal_id: u64) -> Result<(), Error> {
+        approver.require_auth();
+
+        let config = Self::governance_config(&env)?;
+        if !config.signers.contains(&approver) {
+            return Err(Error::NotSigner);
+        }
+
+        let mut proposal = Self::load_proposal(&env, proposal_id)?;
+        if proposal.status != ProposalStatus::Pending {
+            return Err(Error::InvalidProposalStatus);
+        }
+        if env.ledger().timestamp() > proposal.expires_at {
+            return Err(Error::ProposalExpired);
+        }
+        if proposal.approvals.contains(&approver) {
+            return Err(Error::AlreadyApproved);
+        }
+
+        proposal.approvals.push_back(approver.clone());
+        GovernanceProposalApproved {
+            proposal_id,
+            approver,
+            approval_count: proposal.approvals.len(),
+        }
+        .publish(&env);
+        Self::queue_if_ready(&env, &config, &mut proposal);
+        Self::save_proposal(&env, &proposal);
+
+        Ok(())
+    }
+
+    /// Executes a queued proposal whose timelock has elapsed. Callable by anyone.
+    ///
+    /// If a forwarded call fails, the whole execution is rolled back and the
+    /// proposal stays queued.
+    ///
+    /// # Errors
+    /// Returns `Error::ProposalNotFound`, `Error::InvalidProposalStatus` if the
+    /// proposal is not queued, `Error::TimelockNotElapsed` before its `eta`,
+    /// `Error::CallNotAllowed` if a forwarded call was removed from the whitelist
+    /// after proposal, and `Error::ForwardedCallFailed` if the child contract
+    /// rejected the call.
+    pub fn execute_action(env: Env, proposal_id: u64) -> Result<(), Error> {
+        let mut proposal = Self::load_proposal(&env, proposal_id)?;
+        if proposal.status != ProposalStatus::Queued {
+            return Err(Error::InvalidProposalStatus);
+        }
+        let now = env.ledger().timestamp();
+        if now < proposal.eta {
+            return Err(Error::TimelockNotElapsed);
+        }
+
+        proposal.status = ProposalStatus::Executed;
+        proposal.closed_at = now;
+        Self::save_proposal(&env, &proposal);
+        Self::close_proposal(&env, proposal_id);
+
+        Self::dispatch_action(&env, &proposal.action)?;
+
+        GovernanceProposalExecuted { proposal_id }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Cancels a pending or queued proposal. Any signer can cancel.
+    ///
+    /// # Errors
+    /// Returns `Error::NotSigner`, `Error::ProposalNotFound`, and
+    /// `Error::InvalidProposalStatus` if the proposal was already executed or cancelled.
+    pub fn cancel_action(env: Env, signer: Address, proposal_id: u64) -> Result<(), Error> {
+        signer.require_auth();
+
+        let config = Self::governance_config(&env)?;
+        if !config.signers.contains(&signer) {
+            return Err(Error::NotSigner);
+        }
+
+        let mut proposal = Self::load_proposal(&env, proposal_id)?;
+        if !matches!(
+            proposal.status,
+            ProposalStatus::Pending | ProposalStatus::Queued
+        ) {
+            return Err(Error::InvalidProposalStatus);
+        }
+
+        proposal.status = ProposalStatus::Cancelled;
+        proposal.closed_at = env.ledger().timestamp();
+        Self::save_proposal(&env, &proposal);
+        Self::close_proposal(&env, proposal_id);
+
+        GovernanceProposalCancelled {
+            proposal_id,
+            cancelled_by: signer,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Returns a proposal by ID.
+    ///
+    /// # Errors
+    /// Returns `Error::ProposalNotFound` if no proposal has that ID.
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Result<GovernanceProposal, Error> {
+        Self::load_proposal(&env, proposal_id)
+    }
+
+    /// Returns the number of proposals created so far (the highest proposal ID).
+    pub fn get_proposal_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ProposalCount)
+            .unwrap_or(0)
+    }
+
+    /// Returns up to `limit` proposals in creation order, starting at `start_id`.
+    pub fn get_proposal_history(env: Env, start_id: u64, limit: u32) -> Vec<GovernanceProposal> {
+        let count = Self::get_proposal_count(env.clone());
+        let mut proposals = Vec::new(&env);
+        let mut id = start_id.max(1);
+        while id <= count && proposals.len() < limit {
+            if let Ok(proposal) = Self::load_proposal(&env, id) {
+                proposals.push_back(proposal);
+            }
+            id += 1;
+        }
+        proposals
+    }
+
+    /// Returns `true` if governance may forward calls to `function` on `target`.
+    pub fn is_call_allowed(env: Env, target: ChildContract, function: Symbol) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowedCall(target, function))
+            .unwrap_or(false)
+    }
+
+    fn require_pauser(env: &Env, pauser: &Address) -> Result<(), Error> {
+        pauser.require_auth();
+
+        let stored_pauser: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Pauser)
+            .ok_or(Error::NotInitialized)?;
+        if *pauser != stored_pauser {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    fn store_capability(env: &Env, capability: Symbol, functions: Vec<CapabilityFunction>) {
+        let mut names = Self::get_capabilities(env.clone());
+        let index = names.first_index_of(&capability);
+        if functions.is_empty() {
+            env.storage()
+                .instance()
+                .remove(&DataKey::Capability(capability));
+            if let Some(index) = index {
+                names.remove(index);
+            }
+        } else {
+            env.storage()
+                .instance()
+                .set(&DataKey::Capability(capability.clone()), &functions);
+            if index.is_none() {
+                names.push_back(capability);
+            }
+        }
+        env.storage().instance().set(&DataKey::Capabilities, &names);
+    }
+
+    fn paused_capabilities(env: &Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&DataKey::PausedCapabilities)
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn covered_by_paused_capability(
+        env: &Env,
+        paused: &Vec<Symbol>,
+        function: &CapabilityFunction,
+    ) -> bool {
+        paused.iter().any(|capability| {
+            Self::get_capability(env.clone(), capability)
+                .map(|functions| functions.contains(function))
+                .unwrap_or(false)
+        })
+    }
+
+    fn child_pause_state(env: &Env, target: ChildContract) -> Result<ChildPauseState, Error> {
+        let contract = Self::child_contract(env, target)?;
+        let status = match target {
+            ChildContract::Payment => {
+                let client = PaymentContractClient::new(env, &contract);
+                let state = client.get_pause_state();
+                ChildPauseState {
+                    globally_paused: state.globally_paused,
+                    paused_functions: state.paused_functions,
+                    paused_at: state.paused_at,
+                    paused_by: state.paused_by,
+                    pause_reason: state.pause_reason,
+                    paused_until: client.get_paused_until(),
+                }
+            }
+            ChildContract::Escrow => {
+                let client = EscrowContractClient::new(env, &contract);
+                let state = client.get_pause_state();
+                ChildPauseState {
+                    globally_paused: state.globally_paused,
+                    paused_functions: state.paused_functions,
+                    paused_at: state.paused_at,
+                    paused_by: state.paused_by,
+                    pause_reason: state.pause_reason,
+                    paused_until: client.get_paused_until(),
+                }
+            }
+            ChildContract::Refund => {
+                let client = RefundContractClient::new(env, &contract);
+                let state = client.get_pause_state();
+                ChildPauseState {
+                    globally_paused: state.globally_paused,
+                    paused_functions: state.paused_functions,
+                    paused_at: state.paused_at,
+                    paused_by: state.paused_by,
+                    pause_reason: state.pause_reason,
+                    paused_until: client.get_paused_until(),
+                }
+            }
+        };
+        Ok(status)
+    }
+
+    fn governance_config(env: &Env) -> Result<GovernanceConfig, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Governance)
+            .ok_or(Error::GovernanceNotInitialized)
+    }
+
+    fn validate_config(config: &GovernanceConfig) -> Result<(), Error> {
+        if config.threshold == 0 || config.threshold > config.signers.len() {
+            return Err(Error::InvalidGovernanceConfig);
+        }
+        for (i, signer) in config.signers.iter().enumerate() {
+            if config.signers.first_index_of(&signer) != Some(i as u32) {
+                return Err(Error::InvalidGovernanceConfig);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_action(env: &Env, action: &GovernanceAction) -> Result<(), Error> {
+        match action {
+            GovernanceAction::Forward(call) => {
+                if !Self::is_call_allowed(env.clone(), call.target, call.function.clone()) {
+                    return Err(Error::CallNotAllowed);
+                }
+                Ok(())
+            }
+            GovernanceAction::SetCallAllowed(..)
+            | GovernanceAction::UpgradeAll(_)
+            | GovernanceAction::SetChildContract(..) => Ok(()),
+            GovernanceAction::UpdateConfig(config) => Self::validate_config(config),
+        }
+    }
+
+    fn dispatch_action(env: &Env, action: &GovernanceAction) -> Result<(), Error> {
+        Self::validate_action(env, action)?;
+        match action {
+            GovernanceAction::Forward(call) => {
+                let contract = Self::child_contract(env, call.target)?;
+                let mut args: Vec<Val> = vec![env, env.current_contract_address().into_val(env)];
+                args.append(&call.args);
+                if !Self::invoke_child(env, &contract, &call.function, args) {
+                    return Err(Error::ForwardedCallFailed);
+                }
+                Ok(())
+            }
+            GovernanceAction::SetCallAllowed(target, function, allowed) => {
+                let key = DataKey::AllowedCall(*target, function.clone());
+                if *allowed {
+                    env.storage().instance().set(&key, &true);
+                } else {
+                    env.storage().instance().remove(&key);
+                }
+                Ok(())
+            }
+            GovernanceAction::UpdateConfig(config) => {
+                env.storage().instance().set(&DataKey::Governance, config);
+                Self::recount_open_proposals(env, config);
+                Ok(())
+            }
+            GovernanceAction::UpgradeAll(upgrades) => Self::run_upgrades(env, upgrades),
+            GovernanceAction::SetChildContract(target, contract) => {
+                env.storage()
+                    .instance()
+                    .set(&Self::child_contract_key(*target), contract);
+                Ok(())
+            }
+        }
+    }
+
+    fn run_upgrades(env: &Env, upgrades: &Vec<ChildUpgrade>) -> Result<(), Error> {
+        let this: Val = env.current_contract_address().into_val(env);
+        for step in upgrades.iter() {
+            let contract = Self::child_contract(env, step.target)?;
+            if step.target == ChildContract::Escrow
+                && env.storage().instance().has(&DataKey::EscrowMigration)
+            {
+                return Err(Error::MigrationPending);
+            }
+
+            let args = vec![env, this, step.wasm_hash.into_val(env)];
+            if !Self::invoke_child(env, &contract, &Symbol::new(env, "upgrade"), args) {
+                return Err(Error::UpgradeFailed);
+            }
 
+            if step.schema_version != 0 {
+                let migrated = match step.target {
+                    ChildContract::Escrow => Self::invoke_child(
+                        env,
+                        &contract,
+                        &Symbol::new(env, "begin_migration"),
+                        vec![env, this],
+                    ),
+                    ChildContract::Payment | ChildContract::Refund => Self::invoke_child(
+                        env,
+                        &contract,
+                        &Symbol::new(env, "migrate_schema"),
+                        vec![env, this, step.schema_version.into_val(env)],
+                    ),
+                };
+                if !migrated {
+                    return Err(Error::UpgradeFailed);
+                }
+                if step.target == ChildContract::Escrow {
+                    env.storage().instance().set(
+                        &DataKey::EscrowMigration,
+                        &EscrowMigrationProgress {
+                            schema_version: step.schema_version,
+                            next_id: 1,
+                        },
+                    );
+                }
+            }
+
+            ChildContractUpgraded {
+                target: step.target,
+                wasm_hash: step.wasm_hash,
+                schema_version: step.schema_version,
+            }
+            .publish(env);
+        }
         Ok(())
     }
+
+    fn invoke_child(env: &Env, contract: &Address, function: &Symbol, args: Vec<Val>) -> bool {
+        matches!(
+            env.try_invoke_contract::<Val, InvokeError>(contract, function, args),
+            Ok(Ok(_))
+        )
+    }
+
+    fn child_contract(env: &Env, target: ChildContract) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&Self::child_contract_key(target))
+            .ok_or(Error::NotInitialized)
+    }
+
+    fn child_contract_key(target: ChildContract) -> DataKey {
+        match target {
+            ChildContract::Payment => DataKey::PaymentContract,
+            ChildContract::Escrow => DataKey::EscrowContract,
+            ChildContract::Refund => DataKey::RefundContract,
+        }
+    }
+
+    fn queue_if_ready(env: &Env, config: &GovernanceConfig, proposal: &mut GovernanceProposal) {
+        if proposal.approvals.len() < config.threshold {
+            return;
+        }
+        proposal.status = ProposalStatus::Queued;
+        proposal.eta = env
+            .ledger()
+            .timestamp()
+            .saturating_add(config.timelock_seconds);
+        GovernanceProposalQueued {
+            proposal_id: proposal.id,
+            eta: proposal.eta,
+        }
+        .publish(env);
+    }
+
+    fn open_proposals(env: &Env) -> Vec<u64> {
+        env.storage()
+            .instance()
+            .get(&DataKey::OpenProposals)
+            .unwrap_or(Vec::new(env))
+    }
+
+    fn close_proposal(env: &Env, proposal_id: u64) {
+        let mut open = Self::open_proposals(env);
+        if let Some(index) = open.first_index_of(proposal_id) {
+            open.remove(index);
+            env.storage().instance().set(&DataKey::OpenProposals, &open);
+        }
+    }
+
+    /// Re-applies a new signer set to every open proposal: approvals from removed
+    /// signers are dropped, queued proposals that fall below the new threshold go
+    /// back to pending, and pending proposals that now meet it are queued.
+    /// Expired pending proposals are dropped from the open list.
+    fn recount_open_proposals(env: &Env, config: &GovernanceConfig) {
+        let now = env.ledger().timestamp();
+        let mut still_open = Vec::new(env);
+        for proposal_id in Self::open_proposals(env).iter() {
+            let Ok(mut proposal) = Self::load_proposal(env, proposal_id) else {
+                continue;
+            };
+            if proposal.status == ProposalStatus::Pending && now > proposal.expires_at {
+                continue;
+            }
+            let mut approvals = Vec::new(env);
+            for approver in proposal.approvals.iter() {
+                if config.signers.contains(&approver) {
+                    approvals.push_back(approver);
+                }
+            }
+            proposal.approvals = approvals;
+            if proposal.status == ProposalStatus::Queued
+                && proposal.approvals.len() < config.threshold
+            {
+                proposal.status = ProposalStatus::Pending;
+                proposal.eta = 0;
+            } else if proposal.status == ProposalStatus::Pending {
+                Self::queue_if_ready(env, config, &mut proposal);
+            }
+            Self::save_proposal(env, &proposal);
+            still_open.push_back(proposal_id);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::OpenProposals, &still_open);
+    }
+
+    fn load_proposal(env: &Env, proposal_id: u64) -> Result<GovernanceProposal, Error> {
+        let key = DataKey::Proposal(proposal_id);
+        let proposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::ProposalNotFound)?;
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROPOSAL_TTL_THRESHOLD, PROPOSAL_TTL_EXTEND_TO);
+        Ok(proposal)
+    }
+
+    fn save_proposal(env: &Env, proposal: &GovernanceProposal) {
+        let key = DataKey::Proposal(proposal.id);
+        env.storage().persistent().set(&key, proposal);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PROPOSAL_TTL_THRESHOLD, PROPOSAL_TTL_EXTEND_TO);
+    }
 }
 
 #[cfg(test)]
@@ -307,7 +1491,16 @@ mod test {
         client.initialize(&admin, &pauser, &payment_contract, &escrow_contract, &refund_contract);
 
         let reason = String::from_str(&env, "security incident");
-        client.emergency_pause_all(&pauser, &reason);
+        client.emergency_pause_all(&pauser, &reason, &None);
         client.emergency_unpause_all(&pauser);
     }
 }
+
+#[cfg(test)]
+mod test_governance;
+
+#[cfg(test)]
+mod test_upgrade;
+
+#[cfg(test)]
+mod test_pause_capability;
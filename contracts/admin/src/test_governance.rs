@@ -0,0 +1,456 @@
+#![cfg(test)]
+
+use super::*;
+use payments::FeeConfig;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::vec;
+
+const TIMELOCK: u64 = 3_600;
+const PROPOSAL_TTL: u64 = 86_400;
+
+struct Setup<'a> {
+    env: Env,
+    client: AdminContractClient<'a>,
+    admin: Address,
+    signers: [Address; 3],
+    payment: PaymentContractClient<'a>,
+    refund: RefundContractClient<'a>,
+}
+
+/// Registers the admin contract and the three child contracts with the admin
+/// contract as their admin, and sets up 2-of-3 governance.
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let client = AdminContractClient::new(&env, &env.register(AdminContract, ()));
+
+    let payment = PaymentContractClient::new(&env, &env.register(payments::PaymentContract, ()));
+    payment.initialize(&client.address);
+    let escrow = EscrowContractClient::new(&env, &env.register(escrow::EscrowContract, ()));
+    escrow.initialize(&client.address);
+    let refund = RefundContractClient::new(&env, &env.register(refund::RefundContract, ()));
+    refund.initialize(&client.address);
+
+    client.initialize(
+        &admin,
+        &Address::generate(&env),
+        &payment.address,
+        &escrow.address,
+        &refund.address,
+    );
+
+    let signers = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    client.init_governance(&admin, &config(&env, &signers, 2));
+
+    Setup {
+        env,
+        client,
+        admin,
+        signers,
+        payment,
+        refund,
+    }
+}
+
+fn config(env: &Env, signers: &[Address], threshold: u32) -> GovernanceConfig {
+    let mut list = Vec::new(env);
+    for signer in signers {
+        list.push_back(signer.clone());
+    }
+    GovernanceConfig {
+        signers: list,
+        threshold,
+        timelock_seconds: TIMELOCK,
+        proposal_ttl: PROPOSAL_TTL,
+    }
+}
+
+fn forward(target: ChildContract, function: Symbol, args: Vec<Val>) -> GovernanceAction {
+    GovernanceAction::Forward(ForwardedCall {
+        target,
+        function,
+        args,
+    })
+}
+
+fn propose(s: &Setup, action: &GovernanceAction) -> u64 {
+    s.client.propose_action(
+        &s.signers[0],
+        action,
+        &String::from_str(&s.env, "config change"),
+    )
+}
+
+fn advance(env: &Env, seconds: u64) {
+    env.ledger().with_mut(|li| li.timestamp += seconds);
+}
+
+fn pause_refunds_action(s: &Setup) -> GovernanceAction {
+    forward(
+        ChildContract::Refund,
+        Symbol::new(&s.env, "pause_function"),
+        vec![
+            &s.env,
+            String::from_str(&s.env, "request_refund").into_val(&s.env),
+            String::from_str(&s.env, "incident").into_val(&s.env),
+        ],
+    )
+}
+
+#[test]
+fn test_forwarded_fee_config_needs_threshold_and_timelock() {
+    let s = setup();
+    let fee_config = FeeConfig {
+        fee_bps: 150,
+        min_fee: 1,
+        max_fee: 1_000,
+        treasury: Address::generate(&s.env),
+        fee_token: Address::generate(&s.env),
+        active: true,
+    };
+    let action = forward(
+        ChildContract::Payment,
+        Symbol::new(&s.env, "set_fee_config"),
+        vec![&s.env, fee_config.into_val(&s.env)],
+    );
+
+    let id = propose(&s, &action);
+    assert_eq!(
+        s.client.try_execute_action(&id),
+        Err(Ok(Error::InvalidProposalStatus))
+    );
+
+    s.client.approve_action(&s.signers[1], &id);
+    let proposal = s.client.get_proposal(&id);
+    assert_eq!(proposal.status, ProposalStatus::Queued);
+    assert_eq!(proposal.eta, s.env.ledger().timestamp() + TIMELOCK);
+    assert_eq!(
+        s.client.try_execute_action(&id),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+
+    assert_eq!(s.payment.get_fee_config().fee_bps, 150);
+    let proposal = s.client.get_proposal(&id);
+    assert_eq!(proposal.status, ProposalStatus::Executed);
+    assert_eq!(proposal.closed_at, s.env.ledger().timestamp());
+    assert_eq!(
+        s.client.try_execute_action(&id),
+        Err(Ok(Error::InvalidProposalStatus))
+    );
+}
+
+#[test]
+fn test_forwarded_pause_function_reaches_refund_contract() {
+    let s = setup();
+    let id = propose(&s, &pause_refunds_action(&s));
+    s.client.approve_action(&s.signers[2], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+
+    assert!(s
+        .refund
+        .is_function_paused(&String::from_str(&s.env, "request_refund")));
+}
+
+#[test]
+fn test_call_outside_whitelist_is_rejected() {
+    let s = setup();
+    let action = forward(
+        ChildContract::Payment,
+        Symbol::new(&s.env, "set_refund_contract"),
+        vec![&s.env, Address::generate(&s.env).into_val(&s.env)],
+    );
+
+    let result =
+        s.client
+            .try_propose_action(&s.signers[0], &action, &String::from_str(&s.env, "relink"));
+    assert_eq!(result, Err(Ok(Error::CallNotAllowed)));
+}
+
+#[test]
+fn test_whitelist_is_managed_through_governance() {
+    let s = setup();
+    let function = Symbol::new(&s.env, "set_refund_contract");
+    assert!(!s.client.is_call_allowed(&ChildContract::Payment, &function));
+
+    let id = propose(
+        &s,
+        &GovernanceAction::SetCallAllowed(ChildContract::Payment, function.clone(), true),
+    );
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+    assert!(s.client.is_call_allowed(&ChildContract::Payment, &function));
+
+    let refund_contract = Address::generate(&s.env);
+    let id = propose(
+        &s,
+        &forward(
+            ChildContract::Payment,
+            function,
+            vec![&s.env, refund_contract.into_val(&s.env)],
+        ),
+    );
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+    assert_eq!(s.payment.get_refund_contract(), Some(refund_contract));
+}
+
+#[test]
+fn test_only_signers_propose_and_approve_once() {
+    let s = setup();
+    let outsider = Address::generate(&s.env);
+
+    let result = s.client.try_propose_action(
+        &outsider,
+        &pause_refunds_action(&s),
+        &String::from_str(&s.env, "pause"),
+    );
+    assert_eq!(result, Err(Ok(Error::NotSigner)));
+
+    let id = propose(&s, &pause_refunds_action(&s));
+    assert_eq!(
+        s.client.try_approve_action(&outsider, &id),
+        Err(Ok(Error::NotSigner))
+    );
+    assert_eq!(
+        s.client.try_approve_action(&s.signers[0], &id),
+        Err(Ok(Error::AlreadyApproved))
+    );
+}
+
+#[test]
+fn test_proposal_expires_without_threshold() {
+    let s = setup();
+    let id = propose(&s, &pause_refunds_action(&s));
+
+    advance(&s.env, PROPOSAL_TTL + 1);
+    assert_eq!(
+        s.client.try_approve_action(&s.signers[1], &id),
+        Err(Ok(Error::ProposalExpired))
+    );
+}
+
+#[test]
+fn test_cancelled_proposal_cannot_execute() {
+    let s = setup();
+    let id = propose(&s, &pause_refunds_action(&s));
+    s.client.approve_action(&s.signers[1], &id);
+
+    s.client.cancel_action(&s.signers[2], &id);
+    advance(&s.env, TIMELOCK);
+
+    assert_eq!(
+        s.client.try_execute_action(&id),
+        Err(Ok(Error::InvalidProposalStatus))
+    );
+    assert_eq!(s.client.get_proposal(&id).status, ProposalStatus::Cancelled);
+    assert!(!s
+        .refund
+        .is_function_paused(&String::from_str(&s.env, "request_refund")));
+}
+
+#[test]
+fn test_rejected_forward_leaves_proposal_queued() {
+    let s = setup();
+    // The refund contract rejects a zero-sized rate-limit window.
+    let action = forward(
+        ChildContract::Refund,
+        Symbol::new(&s.env, "set_global_refund_rate_limit"),
+        vec![&s.env, 0u32.into_val(&s.env), 0u64.into_val(&s.env)],
+    );
+    let id = propose(&s, &action);
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+
+    assert_eq!(
+        s.client.try_execute_action(&id),
+        Err(Ok(Error::ForwardedCallFailed))
+    );
+    assert_eq!(s.client.get_proposal(&id).status, ProposalStatus::Queued);
+}
+
+#[test]
+fn test_update_config_changes_threshold() {
+    let s = setup();
+    let new_signer = Address::generate(&s.env);
+    let new_config = config(&s.env, &[s.signers[0].clone(), new_signer.clone()], 1);
+
+    let id = propose(&s, &GovernanceAction::UpdateConfig(new_config.clone()));
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+    assert_eq!(s.client.get_governance_config(), new_config);
+
+    // A single approval now queues the proposal; removed signers lose their vote.
+    let id = s.client.propose_action(
+        &new_signer,
+        &pause_refunds_action(&s),
+        &String::from_str(&s.env, "pause"),
+    );
+    assert_eq!(s.client.get_proposal(&id).status, ProposalStatus::Queued);
+    assert_eq!(
+        s.client.try_cancel_action(&s.signers[2], &id),
+        Err(Ok(Error::NotSigner))
+    );
+}
+
+#[test]
+fn test_update_config_drops_removed_signer_approvals() {
+    let s = setup();
+    // Pending with only the soon-removed signer's approval
+    let pending = s.client.propose_action(
+        &s.signers[1],
+        &pause_refunds_action(&s),
+        &String::from_str(&s.env, "pause"),
+    );
+    // Queued on the soon-removed signer's second approval
+    let queued = propose(&s, &pause_refunds_action(&s));
+    s.client.approve_action(&s.signers[1], &queued);
+    assert_eq!(
+        s.client.get_proposal(&queued).status,
+        ProposalStatus::Queued
+    );
+
+    let new_config = config(
+        &s.env,
+        &[
+            s.signers[0].clone(),
+            s.signers[2].clone(),
+            Address::generate(&s.env),
+        ],
+        2,
+    );
+    let id = propose(&s, &GovernanceAction::UpdateConfig(new_config));
+    s.client.approve_action(&s.signers[2], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+
+    assert_eq!(s.client.get_proposal(&pending).approvals.len(), 0);
+    let requeue = s.client.get_proposal(&queued);
+    assert_eq!(requeue.status, ProposalStatus::Pending);
+    assert_eq!(requeue.approvals, vec![&s.env, s.signers[0].clone()]);
+    assert_eq!(requeue.eta, 0);
+    assert_eq!(
+        s.client.try_execute_action(&queued),
+        Err(Ok(Error::InvalidProposalStatus))
+    );
+
+    s.client.approve_action(&s.signers[2], &queued);
+    assert_eq!(
+        s.client.get_proposal(&queued).status,
+        ProposalStatus::Queued
+    );
+}
+
+#[test]
+fn test_child_contracts_are_relinked_through_governance() {
+    let s = setup();
+    let refund = RefundContractClient::new(&s.env, &s.env.register(refund::RefundContract, ()));
+    refund.initialize(&s.client.address);
+
+    assert_eq!(
+        s.client.try_set_refund_contract(&s.admin, &refund.address),
+        Err(Ok(Error::GovernanceRequired))
+    );
+
+    let id = propose(
+        &s,
+        &GovernanceAction::SetChildContract(ChildContract::Refund, refund.address.clone()),
+    );
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+
+    // Forwarded calls now reach the new refund contract
+    let id = propose(&s, &pause_refunds_action(&s));
+    s.client.approve_action(&s.signers[1], &id);
+    advance(&s.env, TIMELOCK);
+    s.client.execute_action(&id);
+    let function = String::from_str(&s.env, "request_refund");
+    assert!(refund.is_function_paused(&function));
+    assert!(!s.refund.is_function_paused(&function));
+}
+
+#[test]
+fn test_init_governance_validation() {
+    let s = setup();
+    assert_eq!(
+        s.client
+            .try_init_governance(&s.admin, &config(&s.env, &s.signers, 2)),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = AdminContractClient::new(&env, &env.register(AdminContract, ()));
+    let admin = Address::generate(&env);
+    let signer = Address::generate(&env);
+    client.initialize(
+        &admin,
+        &admin,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &Address::generate(&env),
+    );
+
+    let one = core::slice::from_ref(&signer);
+    assert_eq!(
+        client.try_init_governance(&signer, &config(&env, one, 1)),
+        Err(Ok(Error::Unauthorized))
+    );
+    for bad in [
+        config(&env, one, 0),
+        config(&env, one, 2),
+        config(&env, &[signer.clone(), signer.clone()], 1),
+    ] {
+        assert_eq!(
+            client.try_init_governance(&admin, &bad),
+            Err(Ok(Error::InvalidGovernanceConfig))
+        );
+    }
+    assert_eq!(
+        client.try_propose_action(
+            &signer,
+            &GovernanceAction::SetCallAllowed(
+                ChildContract::Escrow,
+                Symbol::new(&env, "pause_function"),
+                false
+            ),
+            &String::from_str(&env, ""),
+        ),
+        Err(Ok(Error::GovernanceNotInitialized))
+    );
+}
+
+#[test]
+fn test_proposal_history_is_paginated() {
+    let s = setup();
+    for _ in 0..3 {
+        propose(&s, &pause_refunds_action(&s));
+    }
+    s.client.cancel_action(&s.signers[1], &2);
+
+    assert_eq!(s.client.get_proposal_count(), 3);
+    let history = s.client.get_proposal_history(&1, &10);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(1).unwrap().status, ProposalStatus::Cancelled);
+
+    let page = s.client.get_proposal_history(&2, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().id, 2);
+    assert_eq!(
+        s.client.try_get_proposal(&4).err(),
+        Some(Ok(Error::ProposalNotFound))
+    );
+}
@@ -0,0 +1,261 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+
+struct Setup<'a> {
+    env: Env,
+    client: AdminContractClient<'a>,
+    admin: Address,
+    pauser: Address,
+    payment: PaymentContractClient<'a>,
+    escrow: EscrowContractClient<'a>,
+    refund: RefundContractClient<'a>,
+}
+
+/// Registers the admin contract and the three child contracts with the pauser
+/// as their admin, the same arrangement `emergency_pause_all` relies on.
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let client = AdminContractClient::new(&env, &env.register(AdminContract, ()));
+
+    let payment = PaymentContractClient::new(&env, &env.register(payments::PaymentContract, ()));
+    payment.initialize(&pauser);
+    let escrow = EscrowContractClient::new(&env, &env.register(escrow::EscrowContract, ()));
+    escrow.initialize(&pauser);
+    let refund = RefundContractClient::new(&env, &env.register(refund::RefundContract, ()));
+    refund.initialize(&pauser);
+
+    client.initialize(
+        &admin,
+        &pauser,
+        &payment.address,
+        &escrow.address,
+        &refund.address,
+    );
+
+    Setup {
+        env,
+        client,
+        admin,
+        pauser,
+        payment,
+        escrow,
+        refund,
+    }
+}
+
+fn refunds(env: &Env) -> Symbol {
+    Symbol::new(env, "refunds")
+}
+
+fn reason(env: &Env) -> String {
+    String::from_str(env, "refund exploit")
+}
+
+fn function(env: &Env, target: ChildContract, name: &str) -> CapabilityFunction {
+    CapabilityFunction {
+        target,
+        function: String::from_str(env, name),
+    }
+}
+
+#[test]
+fn test_pause_refunds_capability_blocks_refund_paths() {
+    let s = setup();
+    s.client
+        .pause_capability(&s.pauser, &refunds(&s.env), &reason(&s.env));
+
+    assert_eq!(
+        s.payment.try_refund_payment(&s.pauser, &1),
+        Err(Ok(payments::Error::Basic(
+            payments::BasicError::FunctionPaused
+        )))
+    );
+    assert_eq!(
+        s.payment.try_partial_refund(&s.pauser, &1, &10),
+        Err(Ok(payments::Error::Basic(
+            payments::BasicError::FunctionPaused
+        )))
+    );
+    assert_eq!(
+        s.escrow.try_release_escrow(&s.pauser, &1, &false),
+        Err(Ok(escrow::Error::Basic(escrow::BasicError::ContractPaused)))
+    );
+    assert_eq!(
+        s.refund.try_process_refund(&s.pauser, &1),
+        Err(Ok(refund::Error::Core(refund::CoreError::FunctionPaused)))
+    );
+    let batch = s.refund.process_refund_batch(&s.pauser, &vec![&s.env, 1]);
+    assert_eq!(batch.len(), 1);
+    assert_eq!(
+        batch.get(0).unwrap().error_code,
+        refund::Error::Core(refund::CoreError::FunctionPaused).to_u32()
+    );
+
+    // Other functions keep working.
+    assert!(!s
+        .payment
+        .is_function_paused(&String::from_str(&s.env, "create_payment")));
+}
+
+#[test]
+fn test_platform_pause_status_aggregates_children() {
+    let s = setup();
+    let status = s.client.get_platform_pause_status();
+    assert!(status.paused_capabilities.is_empty());
+    assert!(status.payment.paused_functions.is_empty());
+
+    s.client
+        .pause_capability(&s.pauser, &refunds(&s.env), &reason(&s.env));
+    let status = s.client.get_platform_pause_status();
+    assert_eq!(status.paused_capabilities, vec![&s.env, refunds(&s.env)]);
+    assert_eq!(
+        status.payment.paused_functions,
+        vec![
+            &s.env,
+            String::from_str(&s.env, "refund_payment"),
+            String::from_str(&s.env, "partial_refund"),
+        ]
+    );
+    assert_eq!(
+        status.escrow.paused_functions,
+        vec![&s.env, String::from_str(&s.env, "release_escrow")]
+    );
+    assert_eq!(status.refund.paused_functions.len(), 2);
+    assert!(!status.payment.globally_paused);
+
+    s.client
+        .emergency_pause_all(&s.pauser, &String::from_str(&s.env, "incident"), &None);
+    assert!(s.client.get_platform_pause_status().refund.globally_paused);
+}
+
+#[test]
+fn test_unpause_keeps_functions_shared_with_paused_capability() {
+    let s = setup();
+    let escrow_releases = Symbol::new(&s.env, "escrow_releases");
+    s.client.set_capability(
+        &s.admin,
+        &escrow_releases,
+        &vec![
+            &s.env,
+            function(&s.env, ChildContract::Escrow, "release_escrow"),
+        ],
+    );
+
+    s.client
+        .pause_capability(&s.pauser, &refunds(&s.env), &reason(&s.env));
+    s.client
+        .pause_capability(&s.pauser, &escrow_releases, &reason(&s.env));
+    s.client.unpause_capability(&s.pauser, &refunds(&s.env));
+
+    let release = String::from_str(&s.env, "release_escrow");
+    assert!(s.escrow.is_function_paused(&release));
+    assert!(!s
+        .payment
+        .is_function_paused(&String::from_str(&s.env, "refund_payment")));
+    assert!(!s
+        .refund
+        .is_function_paused(&String::from_str(&s.env, "process_refund")));
+
+    s.client.unpause_capability(&s.pauser, &escrow_releases);
+    assert!(!s.escrow.is_function_paused(&release));
+    assert!(s
+        .client
+        .get_platform_pause_status()
+        .paused_capabilities
+        .is_empty());
+}
+
+#[test]
+fn test_capability_map_management() {
+    let s = setup();
+    assert_eq!(s.client.get_capabilities(), vec![&s.env, refunds(&s.env)]);
+    assert_eq!(s.client.get_capability(&refunds(&s.env)).len(), 5);
+
+    let installments = Symbol::new(&s.env, "installments");
+    let functions = vec![
+        &s.env,
+        function(&s.env, ChildContract::Payment, "pay_installment"),
+    ];
+    assert_eq!(
+        s.client
+            .try_set_capability(&s.pauser, &installments, &functions),
+        Err(Ok(Error::Unauthorized))
+    );
+    s.client.set_capability(&s.admin, &installments, &functions);
+    assert_eq!(s.client.get_capability(&installments), functions);
+
+    s.client
+        .pause_capability(&s.pauser, &installments, &reason(&s.env));
+    assert_eq!(
+        s.client
+            .try_set_capability(&s.admin, &installments, &Vec::new(&s.env)),
+        Err(Ok(Error::CapabilityPaused))
+    );
+
+    s.client.unpause_capability(&s.pauser, &installments);
+    s.client
+        .set_capability(&s.admin, &installments, &Vec::new(&s.env));
+    assert_eq!(s.client.get_capabilities(), vec![&s.env, refunds(&s.env)]);
+    assert_eq!(
+        s.client.try_get_capability(&installments),
+        Err(Ok(Error::CapabilityNotFound))
+    );
+}
+
+#[test]
+fn test_pause_capability_rejects_non_pauser_and_unknown_capability() {
+    let s = setup();
+    assert_eq!(
+        s.client
+            .try_pause_capability(&s.admin, &refunds(&s.env), &reason(&s.env)),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        s.client
+            .try_pause_capability(&s.pauser, &Symbol::new(&s.env, "unknown"), &reason(&s.env)),
+        Err(Ok(Error::CapabilityNotFound))
+    );
+    assert!(s
+        .client
+        .get_platform_pause_status()
+        .paused_capabilities
+        .is_empty());
+}
+
+#[test]
+fn test_timed_emergency_pause_lifts_on_its_own() {
+    let s = setup();
+    s.env.ledger().set_timestamp(1_000);
+    s.client.emergency_pause_all(
+        &s.pauser,
+        &String::from_str(&s.env, "mistaken pause"),
+        &Some(3_600),
+    );
+
+    let status = s.client.get_platform_pause_status();
+    for child in [&status.payment, &status.escrow, &status.refund] {
+        assert!(child.globally_paused);
+        assert_eq!(child.paused_until, Some(4_600));
+    }
+    assert_eq!(
+        s.refund.try_process_refund(&s.pauser, &1),
+        Err(Ok(refund::Error::Core(refund::CoreError::ContractPaused)))
+    );
+
+    s.env.ledger().set_timestamp(4_600);
+    let status = s.client.get_platform_pause_status();
+    for child in [&status.payment, &status.escrow, &status.refund] {
+        assert!(!child.globally_paused);
+        assert_eq!(child.paused_until, None);
+    }
+    assert_ne!(
+        s.refund.try_process_refund(&s.pauser, &1),
+        Err(Ok(refund::Error::Core(refund::CoreError::ContractPaused)))
+    );
+}
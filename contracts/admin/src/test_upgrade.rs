@@ -0,0 +1,211 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::vec;
+
+const UPGRADE_WASM: &[u8] = include_bytes!("../../test_fixtures/add_u64.wasm");
+
+struct Setup<'a> {
+    env: Env,
+    client: AdminContractClient<'a>,
+    admin: Address,
+    payment: PaymentContractClient<'a>,
+    escrow: EscrowContractClient<'a>,
+    refund: RefundContractClient<'a>,
+    wasm_hash: BytesN<32>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let client = AdminContractClient::new(&env, &env.register(AdminContract, ()));
+    let payment = PaymentContractClient::new(&env, &env.register(payments::PaymentContract, ()));
+    payment.initialize(&client.address);
+    let escrow = EscrowContractClient::new(&env, &env.register(escrow::EscrowContract, ()));
+    escrow.initialize(&client.address);
+    let refund = RefundContractClient::new(&env, &env.register(refund::RefundContract, ()));
+    refund.initialize(&client.address);
+
+    client.initialize(
+        &admin,
+        &admin,
+        &payment.address,
+        &escrow.address,
+        &refund.address,
+    );
+    let wasm_hash = env.deployer().upload_contract_wasm(UPGRADE_WASM);
+
+    Setup {
+        env,
+        client,
+        admin,
+        payment,
+        escrow,
+        refund,
+        wasm_hash,
+    }
+}
+
+fn step(s: &Setup, target: ChildContract, schema_version: u32) -> ChildUpgrade {
+    ChildUpgrade {
+        target,
+        wasm_hash: s.wasm_hash.clone(),
+        schema_version,
+    }
+}
+
+/// Calls `add` from the fixture WASM, which only succeeds once the code was replaced.
+fn runs_upgraded_code(env: &Env, contract: &Address) -> bool {
+    let sum: u64 = env.invoke_contract(
+        contract,
+        &Symbol::new(env, "add"),
+        vec![env, 1u64.into_val(env), 2u64.into_val(env)],
+    );
+    sum == 3
+}
+
+#[test]
+fn test_upgrade_all_upgrades_children_in_order() {
+    let s = setup();
+    let plan = vec![
+        &s.env,
+        step(&s, ChildContract::Payment, 0),
+        step(&s, ChildContract::Escrow, 0),
+        step(&s, ChildContract::Refund, 0),
+    ];
+
+    s.client.upgrade_all(&s.admin, &plan);
+
+    assert!(runs_upgraded_code(&s.env, &s.payment.address));
+    assert!(runs_upgraded_code(&s.env, &s.escrow.address));
+    assert!(runs_upgraded_code(&s.env, &s.refund.address));
+}
+
+#[test]
+fn test_upgrade_all_rolls_back_when_a_migration_fails() {
+    let s = setup();
+    // The fixture has no `migrate_schema`, so the refund step fails after its upgrade.
+    let plan = vec![
+        &s.env,
+        step(&s, ChildContract::Payment, 0),
+        step(&s, ChildContract::Refund, 2),
+    ];
+
+    let result = s.client.try_upgrade_all(&s.admin, &plan);
+    assert_eq!(result, Err(Ok(Error::UpgradeFailed)));
+
+    // Both children still run their original code.
+    assert_eq!(s.payment.get_wasm_hash(), None);
+    assert_eq!(s.refund.get_wasm_hash(), None);
+    assert_eq!(s.refund.get_schema_version(), 1);
+}
+
+#[test]
+fn test_upgrade_all_aborts_on_unknown_wasm() {
+    let s = setup();
+    let mut missing = step(&s, ChildContract::Escrow, 0);
+    missing.wasm_hash = BytesN::from_array(&s.env, &[9; 32]);
+    let plan = vec![&s.env, step(&s, ChildContract::Payment, 0), missing];
+
+    let result = s.client.try_upgrade_all(&s.admin, &plan);
+    assert_eq!(result, Err(Ok(Error::UpgradeFailed)));
+    assert_eq!(s.payment.get_wasm_hash(), None);
+}
+
+#[test]
+fn test_upgrade_all_requires_admin() {
+    let s = setup();
+    let plan = vec![&s.env, step(&s, ChildContract::Payment, 0)];
+
+    let result = s.client.try_upgrade_all(&Address::generate(&s.env), &plan);
+    assert_eq!(result, Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_upgrade_all_goes_through_governance_once_initialized() {
+    let s = setup();
+    let signer = Address::generate(&s.env);
+    s.client.init_governance(
+        &s.admin,
+        &GovernanceConfig {
+            signers: vec![&s.env, signer.clone()],
+            threshold: 1,
+            timelock_seconds: 600,
+            proposal_ttl: 86_400,
+        },
+    );
+    let plan = vec![&s.env, step(&s, ChildContract::Refund, 0)];
+
+    assert_eq!(
+        s.client.try_upgrade_all(&s.admin, &plan),
+        Err(Ok(Error::GovernanceRequired))
+    );
+
+    let id = s.client.propose_action(
+        &signer,
+        &GovernanceAction::UpgradeAll(plan),
+        &String::from_str(&s.env, "refund v2"),
+    );
+    s.env.ledger().with_mut(|li| li.timestamp += 600);
+    s.client.execute_action(&id);
+
+    assert!(runs_upgraded_code(&s.env, &s.refund.address));
+}
+
+#[test]
+fn test_escrow_migration_is_driven_to_completion() {
+    let s = setup();
+    let customer = Address::generate(&s.env);
+    let token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    soroban_sdk::token::StellarAssetClient::new(&s.env, &token).mint(&customer, &3_000);
+    for _ in 0..3 {
+        s.escrow.create_escrow(
+            &customer,
+            &Address::generate(&s.env),
+            &1_000,
+            &token,
+            &0,
+            &0,
+            &0,
+            &false,
+        );
+    }
+
+    // The state `run_upgrades` leaves behind after upgrading escrow to schema 2
+    s.escrow.begin_migration(&s.client.address);
+    s.env.as_contract(&s.client.address, || {
+        s.env.storage().instance().set(
+            &DataKey::EscrowMigration,
+            &EscrowMigrationProgress {
+                schema_version: 2,
+                next_id: 1,
+            },
+        );
+    });
+
+    assert!(!s.client.continue_escrow_migration(&2));
+    assert_eq!(s.client.get_escrow_migration().unwrap().next_id, 3);
+    assert_eq!(s.escrow.get_schema_version(), 1);
+
+    // Escrow cannot be upgraded again mid-migration
+    let plan = vec![&s.env, step(&s, ChildContract::Escrow, 0)];
+    assert_eq!(
+        s.client.try_upgrade_all(&s.admin, &plan),
+        Err(Ok(Error::MigrationPending))
+    );
+
+    assert!(s.client.continue_escrow_migration(&2));
+    assert_eq!(s.client.get_escrow_migration(), None);
+    assert_eq!(s.escrow.get_schema_version(), 2);
+    assert_eq!(s.escrow.get_migration_status().migrated_count, 3);
+    assert_eq!(
+        s.client.try_continue_escrow_migration(&2),
+        Err(Ok(Error::NoMigrationPending))
+    );
+}
@@ -24,6 +24,7 @@ pub enum ConfigKey {
     WatchdogConfig,
     BatchLimit,
     PauseStateKey,
+    PausedUntil,
     PauseHistoryEntry(u64),
     PauseHistoryCount,
     ActivePauseIndex(String),
@@ -36,7 +37,10 @@ pub enum ConfigKey {
     AdminClawbackEscrow(u64),
     SchemaVersion,
     TrustedBridge(Address),
+    SwapAdapter(Address),
     EvidenceDeadlineConfig,
+    WasmHash,
+    PreviousWasmHash,
 }
 
 #[derive(Clone)]
@@ -198,6 +202,8 @@ pub enum ActionError {
     EvidenceDeadlinePassed = 313,
     ApprovalsThresholdNotMet = 314,
     InsufficientCollateral = 315,
+    SwapFailed = 316,
+    SwapAdapterNotAllowed = 317,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -244,7 +250,7 @@ impl TryFrom<soroban_sdk::Error> for Error {
     fn try_from(error: soroban_sdk::Error) -> Result<Self, Self::Error> {
         if error.is_type(soroban_sdk::xdr::ScErrorType::Contract) {
             let code = error.get_code();
-            if code >= 300 && code <= 315 {
+            if code >= 300 && code <= 317 {
                 return Ok(Error::Action(unsafe { core::mem::transmute(code) }));
             }
             if code >= 200 && code <= 229 {
@@ -1088,7 +1094,7 @@ pub struct EscrowSwapConfig {
     pub source_token: Address,
     pub target_token: Address,
     pub min_output_amount: i128,
-    pub oracle: Address,
+    pub adapter: Address,
     pub executed: bool,
 }
 
@@ -1113,6 +1119,7 @@ pub enum ActionType {
     RemoveAdmin,
     UpdateRequiredSignatures,
     UpdateThreshold(u32),
+    UpgradeContract(BytesN<32>),
 }
 
 #[derive(Clone)]
@@ -1310,6 +1317,13 @@ pub struct AdminAdded {
     pub admin: Address,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUpgraded {
+    pub previous_wasm_hash: Option<BytesN<32>>,
+    pub new_wasm_hash: BytesN<32>,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AdminRemoved {
@@ -1395,6 +1409,7 @@ pub struct ContractPausedEvent {
     pub paused_by: Address,
     pub reason: String,
     pub paused_at: u64,
+    pub paused_until: Option<u64>,
 }
 
 #[contractevent]
@@ -1572,10 +1587,7 @@ fn sum_approved_weight(env: &Env, escrow_id: u64, participants: &Vec<Participant
     let mut total: u32 = 0;
     for p in participants.iter() {
         if p.approved {
-            let weight: u32 = env
-                .storage()
-                .instance()
-                .get(&DataKey::VoteWeight(escrow_id, p.address.clone()))
+            let weight: u32 = record_get(env, &DataKey::VoteWeight(escrow_id, p.address.clone()))
                 .unwrap_or(p.weight_bps);
             total += weight;
         }
@@ -1583,6 +1595,108 @@ fn sum_approved_weight(env: &Env, escrow_id: u64, participants: &Vec<Participant
     total
 }
 
+// ── Per-record persistent storage ───────────────────────────────────────────
+//
+// Escrow records, evidence, vesting schedules, dispute side records and the
+// customer/merchant escrow indexes are kept in persistent storage with a TTL
+// that is bumped whenever they are read or written. Records left in instance
+// storage by earlier releases are moved across on first touch, or eagerly by
+// `migrate_escrow` / `migrate_escrow_batch`.
+
+const LEDGERS_PER_DAY: u32 = 17_280;
+const RECORD_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+const RECORD_TTL_EXTEND_TO: u32 = 120 * LEDGERS_PER_DAY;
+
+fn bump_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+}
+
+/// Moves a record written by a pre-persistent release out of instance storage.
+/// Returns `true` when the record now lives in persistent storage.
+fn promote_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if env.storage().persistent().has(key) {
+        return true;
+    }
+    match env.storage().instance().get::<K, Val>(key) {
+        Some(value) => {
+            env.storage().instance().remove(key);
+            env.storage().persistent().set(key, &value);
+            true
+        }
+        None => false,
+    }
+}
+
+fn record_get<K: IntoVal<Env, Val>, V: TryFromVal<Env, Val>>(env: &Env, key: &K) -> Option<V> {
+    if !promote_record(env, key) {
+        return None;
+    }
+    let value = env.storage().persistent().get(key);
+    bump_record(env, key);
+    value
+}
+
+fn record_set<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(env: &Env, key: &K, value: &V) {
+    if env.storage().instance().has(key) {
+        env.storage().instance().remove(key);
+    }
+    env.storage().persistent().set(key, value);
+    bump_record(env, key);
+}
+
+fn record_has<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    env.storage().persistent().has(key) || env.storage().instance().has(key)
+}
+
+fn record_remove<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage().persistent().remove(key);
+    env.storage().instance().remove(key);
+}
+
+/// Bumps a record's TTL, promoting it from instance storage first if needed.
+/// Returns `false` when no such record exists.
+fn touch_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if !promote_record(env, key) {
+        return false;
+    }
+    bump_record(env, key);
+    true
+}
+
+/// Touches an escrow record and every side record keyed only by its id.
+/// Returns `false` when the escrow itself does not exist.
+fn touch_escrow_records(env: &Env, escrow_id: u64) -> bool {
+    if !touch_record(env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
+        return false;
+    }
+    let side_records = [
+        DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id)),
+        DataKey::Escrow(EscrowKey::EvidencePageCount(escrow_id)),
+        DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)),
+        DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        DataKey::Escrow(EscrowKey::Conditional(escrow_id)),
+        DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)),
+        DataKey::Escrow(EscrowKey::Hierarchy(escrow_id)),
+        DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
+        DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)),
+        DataKey::Dispute(DisputeKey::Collateral(escrow_id)),
+        DataKey::Dispute(DisputeKey::Round(escrow_id)),
+        DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
+        DataKey::Dispute(DisputeKey::EscrowRenewalCount(escrow_id)),
+        DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
+        DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)),
+        DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)),
+        DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
+        DataKey::ReleaseThresholdBps(escrow_id),
+    ];
+    for key in side_records.iter() {
+        touch_record(env, key);
+    }
+    true
+}
+
 const INITIAL_SCHEMA_VERSION: u32 = 1;
 const MIGRATION_TARGET_SCHEMA_VERSION: u32 = 2;
 const MIN_CLAWBACK_DELAY: u64 = 86_400;
@@ -1641,6 +1755,87 @@ impl EscrowContract {
             .unwrap_or(INITIAL_SCHEMA_VERSION)
     }
 
+    /// Replaces the contract code with the uploaded WASM identified by `new_wasm_hash`.
+    ///
+    /// With a multisig threshold above one, the upgrade must instead go through
+    /// `propose_action` with `ActionType::UpgradeContract(new_wasm_hash)`. The hash
+    /// being replaced is kept as the rollback target. Escrow records are not
+    /// touched; start `begin_migration` afterwards if the new code needs it.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    /// * `admin` - Multisig admin authorizing the upgrade.
+    /// * `new_wasm_hash` - Hash of WASM already uploaded to the network.
+    ///
+    /// # Errors
+    /// Returns `NotAnAdmin` if `admin` is not a multisig admin, and
+    /// `ApprovalsThresholdNotMet` if more than one signature is required.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        admin.require_auth();
+        let multisig = Self::get_multisig_config(env.clone());
+        if !multisig.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+        if multisig.required_signatures > 1 {
+            return Err(Error::Action(ActionError::ApprovalsThresholdNotMet));
+        }
+
+        Self::do_upgrade(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Returns the WASM hash installed by the last upgrade.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    ///
+    /// # Returns
+    /// `None` if the contract still runs the code it was deployed with.
+    pub fn get_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::WasmHash))
+    }
+
+    /// Returns the WASM hash replaced by the last upgrade (the rollback target).
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    ///
+    /// # Returns
+    /// `None` until the contract has been upgraded twice, since the hash it was
+    /// deployed with is not known on-chain.
+    pub fn get_previous_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::PreviousWasmHash))
+    }
+
+    /// Extends the TTL of an escrow record and its per-escrow side records.
+    ///
+    /// Anyone may call this, so keepers can keep long-running or settled escrows
+    /// readable. Records still held in instance storage are moved to persistent
+    /// storage first.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    /// * `escrow_id` - Identifier for the requested object.
+    ///
+    /// # Returns
+    /// Results in `Ok(())` on success or `Err(Error)` on failure.
+    ///
+    /// # Errors
+    /// Returns `EscrowError::NotFound` if the escrow does not exist.
+    pub fn extend_record_ttl(env: Env, escrow_id: u64) -> Result<(), Error> {
+        if !touch_escrow_records(&env, escrow_id) {
+            return Err(Error::Escrow(EscrowError::NotFound));
+        }
+        env.storage()
+            .instance()
+            .extend_ttl(RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+        Ok(())
+    }
+
     /// Sets escrow fee config.
     ///
     /// # Arguments
@@ -2385,11 +2580,7 @@ impl EscrowContract {
             return Err(Error::Escrow(EscrowError::ClawbackDelayTooShort));
         }
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -2492,7 +2683,8 @@ impl EscrowContract {
 
         let mut updated_escrow = escrow;
         updated_escrow.status = EscrowStatus::Resolved;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Data(request.escrow_id)),
             &updated_escrow,
         );
@@ -2748,9 +2940,7 @@ impl EscrowContract {
             evidence_deadline: None,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::Counter), &escrow_id);
@@ -2762,7 +2952,8 @@ impl EscrowContract {
                     threshold: config.threshold,
                     approvals: Vec::new(&env),
                 };
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)),
                     &release_multisig,
                 );
@@ -2770,31 +2961,35 @@ impl EscrowContract {
         }
 
         // Index by customer
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::CustomerList(customer.clone(), customer_count)),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())),
             &(customer_count + 1),
         );
 
         // Index by merchant
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let merchant_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::MerchantList(merchant.clone(), merchant_count)),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())),
             &(merchant_count + 1),
         );
@@ -2933,22 +3128,23 @@ impl EscrowContract {
             release_timestamp,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::MultiPartyCounter), &escrow_id);
 
         for p in escrow.participants.iter() {
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::VoteWeight(escrow_id, p.address.clone()),
                 &p.share_bps,
             );
         }
-        env.storage()
-            .instance()
-            .set(&DataKey::ReleaseThresholdBps(escrow_id), &10000u32);
+        record_set(&env, &DataKey::ReleaseThresholdBps(escrow_id), &10000u32);
 
         MultiPartyEscrowCreated {
             escrow_id,
@@ -2974,19 +3170,12 @@ impl EscrowContract {
     pub fn approve_release(env: Env, caller: Address, escrow_id: u64) -> Result<(), Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .unwrap();
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))).unwrap();
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -3005,10 +3194,7 @@ impl EscrowContract {
         let mut found_voter = false;
         for p in escrow.participants.iter() {
             if p.address == caller {
-                let weight = env
-                    .storage()
-                    .instance()
-                    .get(&DataKey::VoteWeight(escrow_id, caller.clone()))
+                let weight = record_get(&env, &DataKey::VoteWeight(escrow_id, caller.clone()))
                     .unwrap_or(p.weight_bps);
                 if weight == 0 {
                     return Err(Error::Basic(BasicError::Unauthorized));
@@ -3036,9 +3222,11 @@ impl EscrowContract {
 
         escrow.participants = updated_participants;
         escrow.approvals.push_back(caller.clone());
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
         ParticipantApproved {
             escrow_id,
@@ -3061,19 +3249,12 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn release_multi_party_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .unwrap();
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))).unwrap();
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -3081,10 +3262,7 @@ impl EscrowContract {
 
         // Check if cumulative approved weight meets the threshold
         let approved_weight = sum_approved_weight(&env, escrow_id, &escrow.participants);
-        let threshold = env
-            .storage()
-            .instance()
-            .get(&DataKey::ReleaseThresholdBps(escrow_id))
+        let threshold = record_get(&env, &DataKey::ReleaseThresholdBps(escrow_id))
             .unwrap_or(escrow.threshold_bps);
         if approved_weight < threshold {
             return Err(Error::Action(ActionError::ApprovalsThresholdNotMet));
@@ -3109,9 +3287,11 @@ impl EscrowContract {
         }
 
         escrow.status = EscrowStatus::Released;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
         MultiPartyEscrowReleased { escrow_id }.publish(&env);
 
@@ -3130,32 +3310,19 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_multi_party_escrow(env: Env, escrow_id: u64) -> Result<MultiPartyEscrow, Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
-        Ok(env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .unwrap())
+        Ok(record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id))).unwrap())
     }
 
     /// Returns (current cumulative approved weight, required threshold) in basis points.
     pub fn get_approval_weight(env: Env, escrow_id: u64) -> Result<(u32, u32), Error> {
-        let escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
         let approved = sum_approved_weight(&env, escrow_id, &escrow.participants);
-        let threshold = env
-            .storage()
-            .instance()
-            .get(&DataKey::ReleaseThresholdBps(escrow_id))
+        let threshold = record_get(&env, &DataKey::ReleaseThresholdBps(escrow_id))
             .unwrap_or(escrow.threshold_bps);
         Ok((approved, threshold))
     }
@@ -3175,11 +3342,9 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -3221,11 +3386,14 @@ impl EscrowContract {
         }
 
         escrow.participants = updated;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::VoteWeight(escrow_id, participant.clone()),
             &weight_bps,
         );
@@ -3257,24 +3425,26 @@ impl EscrowContract {
             return Err(Error::Escrow(EscrowError::InvalidThreshold));
         }
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
         }
 
         escrow.threshold_bps = threshold_bps;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
-        env.storage()
-            .instance()
-            .set(&DataKey::ReleaseThresholdBps(escrow_id), &threshold_bps);
+        record_set(
+            &env,
+            &DataKey::ReleaseThresholdBps(escrow_id),
+            &threshold_bps,
+        );
 
         ThresholdUpdated {
             escrow_id,
@@ -3354,9 +3524,11 @@ impl EscrowContract {
             release_timestamp,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)),
+            &escrow,
+        );
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::MultiTokenCounter), &escrow_id);
@@ -3385,11 +3557,9 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn release_multi_token_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let mut escrow: MultiTokenEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiTokenEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -3425,9 +3595,11 @@ impl EscrowContract {
 
         let token_count = escrow.tokens.len();
         escrow.status = EscrowStatus::Released;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)),
+            &escrow,
+        );
 
         MultiTokenEscrowReleased {
             escrow_id,
@@ -3451,9 +3623,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_multi_token_escrow(env: Env, escrow_id: u64) -> Result<MultiTokenEscrow, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
+        record_get(&env, &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::NotFound))
     }
 
@@ -3481,11 +3651,9 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -3496,9 +3664,11 @@ impl EscrowContract {
         }
 
         escrow.status = EscrowStatus::Cancelled;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
         let token_client = token::Client::new(&env, &escrow.token);
         token_client.transfer(
@@ -3533,11 +3703,9 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        let mut escrow: MultiTokenEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiTokenEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.customer != customer {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -3552,9 +3720,11 @@ impl EscrowContract {
         }
 
         escrow.status = EscrowStatus::Cancelled;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiToken(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiToken(escrow_id)),
+            &escrow,
+        );
 
         for entry in escrow.tokens.iter() {
             let token_client = token::Client::new(&env, &entry.token);
@@ -3580,11 +3750,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_escrow(env: &Env, escrow_id: u64) -> Escrow {
-        env.storage()
-        let escrow: Escrow = env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-            .expect("Escrow not found")
+        record_get(env, &DataKey::Escrow(EscrowKey::Data(escrow_id))).expect("Escrow not found")
     }
 
     /// Approves escrow release.
@@ -3602,11 +3768,7 @@ impl EscrowContract {
     pub fn approve_escrow_release(env: Env, signer: Address, escrow_id: u64) -> Result<(), Error> {
         signer.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -3615,11 +3777,11 @@ impl EscrowContract {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
         }
 
-        let mut release_multisig: EscrowReleaseMultisig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut release_multisig: EscrowReleaseMultisig = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if !release_multisig.signers.contains(&signer) {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -3629,7 +3791,8 @@ impl EscrowContract {
         }
 
         release_multisig.approvals.push_back(signer.clone());
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)),
             &release_multisig,
         );
@@ -3663,6 +3826,7 @@ impl EscrowContract {
         early_release: bool,
     ) -> Result<(), Error> {
         admin.require_auth();
+        Self::require_not_paused(&env, "release_escrow")?;
 
         if EscrowContract::verify_observer_access(env.clone(), escrow_id, admin.clone()) {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -3693,11 +3857,7 @@ impl EscrowContract {
     fn can_release_escrow(env: Env, escrow_id: u64, early_release: bool) -> Result<(), Error> {
         let current_time: u64 = env.ledger().timestamp();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -3709,19 +3869,16 @@ impl EscrowContract {
         }
 
         // Guard: block full release if any sub-accounts remain unreleased
-        let sub_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)))
-            .unwrap_or(0);
+        let sub_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
+        )
+        .unwrap_or(0);
         for sub_id in 1..=sub_count {
-            if let Some(sub) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowSubAccount>(&DataKey::Escrow(EscrowKey::SubAccount(
-                        escrow_id, sub_id,
-                    )))
-            {
+            if let Some(sub) = record_get::<DataKey, EscrowSubAccount>(
+                &env,
+                &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+            ) {
                 if !sub.released {
                     return Err(Error::Escrow(EscrowError::InvalidStatus));
                 }
@@ -3759,13 +3916,10 @@ impl EscrowContract {
 
         Self::can_release_escrow(env.clone(), escrow_id, early_release)?;
 
-        if let Some(release_multisig) = env
-            .storage()
-            .instance()
-            .get::<DataKey, EscrowReleaseMultisig>(&DataKey::Escrow(EscrowKey::ReleaseMultisig(
-                escrow_id,
-            )))
-        {
+        if let Some(release_multisig) = record_get::<DataKey, EscrowReleaseMultisig>(
+            &env,
+            &DataKey::Escrow(EscrowKey::ReleaseMultisig(escrow_id)),
+        ) {
             if (release_multisig.approvals.len() as u32) < release_multisig.threshold {
                 return Err(Error::Action(ActionError::ApprovalsThresholdNotMet));
             }
@@ -3776,9 +3930,7 @@ impl EscrowContract {
             escrow.status = EscrowStatus::Released;
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         let fee_amount = (escrow.amount * escrow.fee_bps) / 10000;
         let merchant_amount = escrow.amount - fee_amount;
@@ -3899,11 +4051,7 @@ impl EscrowContract {
     pub fn refund_escrow(env: Env, caller: Address, escrow_id: u64) -> Result<(), Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -3927,9 +4075,7 @@ impl EscrowContract {
             }
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         EscrowContract::transfer_if_token_contract(
             &env,
@@ -3964,11 +4110,7 @@ impl EscrowContract {
         caller.require_auth();
 
         // Check if escrow exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -4002,7 +4144,8 @@ impl EscrowContract {
                 token: config.collateral_token.clone(),
                 deposited_at: env.ledger().timestamp(),
             };
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Dispute(DisputeKey::Collateral(escrow_id)),
                 &collateral,
             );
@@ -4039,9 +4182,7 @@ impl EscrowContract {
             EscrowStatus::Cancelled => return Err(Error::Escrow(EscrowError::AlreadyProcessed)),
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         // Update global analytics
         let mut analytics: EscrowAnalytics = env
@@ -4098,11 +4239,7 @@ impl EscrowContract {
         ipfs_hash: String,
     ) -> Result<(), Error> {
         caller.require_auth();
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
@@ -4138,11 +4275,7 @@ impl EscrowContract {
         merkle_root: BytesN<32>,
     ) -> Result<(), Error> {
         caller.require_auth();
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
@@ -4152,11 +4285,10 @@ impl EscrowContract {
         if escrow.customer != caller && escrow.merchant != caller {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
-        if env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)))
-        {
+        if record_has(
+            &env,
+            &DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)),
+        ) {
             return Err(Error::Basic(BasicError::RootAlreadyCommitted));
         }
         let commitment = EvidenceCommitment {
@@ -4165,7 +4297,8 @@ impl EscrowContract {
             committed_at: env.ledger().timestamp(),
             committed_by: caller,
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)),
             &commitment,
         );
@@ -4188,11 +4321,7 @@ impl EscrowContract {
         leaf_index: u32,
     ) -> Result<(), Error> {
         caller.require_auth();
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow_check = EscrowContract::get_escrow(&env, escrow_id);
@@ -4203,12 +4332,10 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let commitment_opt =
-            env.storage()
-                .instance()
-                .get::<DataKey, EvidenceCommitment>(&DataKey::Escrow(
-                    EscrowKey::EvidenceCommitment(escrow_id),
-                ));
+        let commitment_opt = record_get::<DataKey, EvidenceCommitment>(
+            &env,
+            &DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)),
+        );
 
         if commitment_opt.is_none() {
             let ipfs_hash = EscrowContract::evidence_bytes_to_label_string(&env, evidence);
@@ -4243,19 +4370,14 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_evidence_commitment(env: Env, escrow_id: u64) -> Result<EvidenceCommitment, Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
-        env.storage()
-            .instance()
-            .get::<DataKey, EvidenceCommitment>(&DataKey::Escrow(EscrowKey::EvidenceCommitment(
-                escrow_id,
-            )))
-            .ok_or(Error::Basic(BasicError::RootAlreadyCommitted))
+        record_get::<DataKey, EvidenceCommitment>(
+            &env,
+            &DataKey::Escrow(EscrowKey::EvidenceCommitment(escrow_id)),
+        )
+        .ok_or(Error::Basic(BasicError::RootAlreadyCommitted))
     }
 
     fn verify_keccak_merkle_proof(
@@ -4309,29 +4431,26 @@ impl EscrowContract {
         caller: Address,
         ipfs_hash: String,
     ) -> Result<(), Error> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id)))
-            .unwrap_or(0);
+        let count: u64 =
+            record_get(env, &DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id))).unwrap_or(0);
         let evidence = Evidence {
             submitter: caller.clone(),
             ipfs_hash: ipfs_hash.clone(),
             submitted_at: env.ledger().timestamp(),
         };
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::Evidence(escrow_id, count)),
             &evidence,
         );
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id)),
             &(count + 1),
         );
         let mut escrow = EscrowContract::get_escrow(env, escrow_id);
         escrow.last_activity_at = env.ledger().timestamp();
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         EvidenceSubmitted {
             escrow_id,
             submitter: caller,
@@ -4353,10 +4472,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_evidence_count(env: &Env, escrow_id: u64) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id)))
-            .unwrap_or(0)
+        record_get(env, &DataKey::Escrow(EscrowKey::EvidenceCount(escrow_id))).unwrap_or(0)
     }
 
     /// Returns evIDence.
@@ -4381,11 +4497,10 @@ impl EscrowContract {
         let end = core::cmp::min(total, offset.saturating_add(limit));
         let mut i = offset;
         while i < end {
-            if let Some(ev) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Evidence>(&DataKey::Escrow(EscrowKey::Evidence(escrow_id, i)))
-            {
+            if let Some(ev) = record_get::<DataKey, Evidence>(
+                &env,
+                &DataKey::Escrow(EscrowKey::Evidence(escrow_id, i)),
+            ) {
                 items.push_back(ev);
             }
             i += 1;
@@ -4414,11 +4529,7 @@ impl EscrowContract {
     ) -> Result<u32, Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -4449,11 +4560,11 @@ impl EscrowContract {
                 return Err(Error::Action(ActionError::EvidenceDeadlinePassed));
             }
         }
-        let page_num: u32 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::EvidencePageCount(escrow_id)))
-            .unwrap_or(0);
+        let page_num: u32 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::EvidencePageCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let mut page: Vec<Evidence> = Vec::new(&env);
         for item in evidence_items.iter() {
@@ -4465,11 +4576,13 @@ impl EscrowContract {
             });
         }
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::EvidencePage(escrow_id, page_num)),
             &page,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::EvidencePageCount(escrow_id)),
             &(page_num + 1),
         );
@@ -4490,10 +4603,11 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_evidence_page(env: Env, escrow_id: u64, page: u32) -> Vec<Evidence> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::EvidencePage(escrow_id, page)))
-            .unwrap_or(Vec::new(&env))
+        record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::EvidencePage(escrow_id, page)),
+        )
+        .unwrap_or(Vec::new(&env))
     }
 
     /// Escalates a dispute for the specified escrow.
@@ -4510,11 +4624,7 @@ impl EscrowContract {
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn escalate_dispute(env: Env, caller: Address, escrow_id: u64) -> Result<(), Error> {
         caller.require_auth();
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let mut escrow = EscrowContract::get_escrow(&env, escrow_id);
@@ -4528,9 +4638,7 @@ impl EscrowContract {
         let now = env.ledger().timestamp();
         escrow.escalated_at = Some(now);
         escrow.last_activity_at = now;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         let deadline = now.saturating_add(escrow.escalation_timeout);
         Self::enqueue_escalation(&env, escrow_id, deadline);
@@ -4555,11 +4663,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn auto_resolve_dispute(env: Env, escrow_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let mut escrow = EscrowContract::get_escrow(&env, escrow_id);
@@ -4587,9 +4691,7 @@ impl EscrowContract {
         } else {
             EscrowStatus::Resolved
         };
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         EscrowContract::update_reputation_on_dispute_outcome(&env, &winner, &loser);
         EscrowResolved {
             escrow_id,
@@ -4646,10 +4748,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if the contract state is invalid.
     pub fn check_escalation_timeout(env: Env, escrow_id: u64) -> bool {
-        let escrow: Option<Escrow> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Data(escrow_id)));
+        let escrow: Option<Escrow> = record_get(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)));
         let escrow = match escrow {
             Some(e) => e,
             None => return false,
@@ -4674,11 +4773,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn trigger_timeout_resolution(env: Env, escrow_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -4702,9 +4797,7 @@ impl EscrowContract {
         match &favor {
             AutoResolveFavor::Customer => {
                 escrow.status = EscrowStatus::Resolved;
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
                 EscrowContract::transfer_if_token_contract(
                     &env,
                     &escrow.token,
@@ -4714,9 +4807,7 @@ impl EscrowContract {
             }
             AutoResolveFavor::Merchant => {
                 escrow.status = EscrowStatus::Released;
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
                 EscrowContract::transfer_if_token_contract(
                     &env,
                     &escrow.token,
@@ -4728,9 +4819,7 @@ impl EscrowContract {
                 let half = escrow.amount / 2;
                 let remainder = escrow.amount - half;
                 escrow.status = EscrowStatus::Resolved;
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
                 EscrowContract::transfer_if_token_contract(
                     &env,
                     &escrow.token,
@@ -4869,11 +4958,7 @@ impl EscrowContract {
         release_to_merchant: bool,
     ) -> Result<(), Error> {
         // Check if escrow exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -4891,9 +4976,7 @@ impl EscrowContract {
             _ => return Err(Error::Action(ActionError::NotDisputed)),
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         Self::dequeue_escalation(&env, escrow_id);
 
@@ -4906,11 +4989,10 @@ impl EscrowContract {
         Self::transfer_if_token_contract(&env, &escrow.token, recipient, escrow.amount)?;
 
         // Handle collateral distribution
-        if let Some(collateral) = env
-            .storage()
-            .instance()
-            .get::<DataKey, DisputeCollateral>(&DataKey::Dispute(DisputeKey::Collateral(escrow_id)))
-        {
+        if let Some(collateral) = record_get::<DataKey, DisputeCollateral>(
+            &env,
+            &DataKey::Dispute(DisputeKey::Collateral(escrow_id)),
+        ) {
             let winner = if release_to_merchant {
                 escrow.merchant.clone()
             } else {
@@ -4935,9 +5017,7 @@ impl EscrowContract {
                 }
                 .publish(&env);
             }
-            env.storage()
-                .instance()
-                .remove(&DataKey::Dispute(DisputeKey::Collateral(escrow_id)));
+            record_remove(&env, &DataKey::Dispute(DisputeKey::Collateral(escrow_id)));
         }
 
         let (winner, loser) = if release_to_merchant {
@@ -5045,11 +5125,7 @@ impl EscrowContract {
         appellant.require_auth();
 
         // Check if escrow exists and is in Resolved or Released status from initial dispute
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -5085,10 +5161,8 @@ impl EscrowContract {
             .unwrap_or(0);
 
         for i in 0..appeals_count {
-            if let Some(appeal) = env
-                .storage()
-                .instance()
-                .get::<DataKey, DisputeAppeal>(&DataKey::Dispute(DisputeKey::Appeal(i)))
+            if let Some(appeal) =
+                record_get::<DataKey, DisputeAppeal>(&env, &DataKey::Dispute(DisputeKey::Appeal(i)))
             {
                 if appeal.escrow_id == escrow_id && !appeal.resolved {
                     return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -5119,21 +5193,25 @@ impl EscrowContract {
             status: AppealStatus::Pending,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::AppealRecord(escrow_id, appeal_id)),
             &appeal_record,
         );
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)), &appeal);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Appeal(appeal_id)),
+            &appeal,
+        );
         env.storage().instance().set(
             &DataKey::Dispute(DisputeKey::AppealCounter),
             &(appeals_count + 1),
         );
 
         // Update dispute round to Appeal
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::Round(escrow_id)),
             &DisputeRound::Appeal,
         );
@@ -5162,9 +5240,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_dispute_round(env: Env, escrow_id: u64) -> DisputeRound {
-        env.storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::Round(escrow_id)))
+        record_get(&env, &DataKey::Dispute(DisputeKey::Round(escrow_id)))
             .unwrap_or(DisputeRound::Initial)
     }
 
@@ -5202,11 +5278,11 @@ impl EscrowContract {
         }
 
         // Get the appeal
-        let mut appeal = env
-            .storage()
-            .instance()
-            .get::<DataKey, DisputeAppeal>(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut appeal = record_get::<DataKey, DisputeAppeal>(
+            &env,
+            &DataKey::Dispute(DisputeKey::Appeal(appeal_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if appeal.resolved {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
@@ -5215,11 +5291,7 @@ impl EscrowContract {
         let escrow_id = appeal.escrow_id;
 
         // Get the escrow
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -5239,12 +5311,15 @@ impl EscrowContract {
 
         // Resolve appeal
         appeal.resolved = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)), &appeal);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Appeal(appeal_id)),
+            &appeal,
+        );
 
         // Update dispute round to Final
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::Round(escrow_id)),
             &DisputeRound::Final,
         );
@@ -5259,9 +5334,11 @@ impl EscrowContract {
         let mut escrow_mut = escrow;
         escrow_mut.status = EscrowStatus::Resolved;
         escrow_mut.last_activity_at = now;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow_mut);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::Data(escrow_id)),
+            &escrow_mut,
+        );
 
         // Transfer funds to the party in favor
         Self::transfer_if_token_contract(
@@ -5272,11 +5349,10 @@ impl EscrowContract {
         )?;
 
         // Handle collateral distribution if any
-        if let Some(collateral) = env
-            .storage()
-            .instance()
-            .get::<DataKey, DisputeCollateral>(&DataKey::Dispute(DisputeKey::Collateral(escrow_id)))
-        {
+        if let Some(collateral) = record_get::<DataKey, DisputeCollateral>(
+            &env,
+            &DataKey::Dispute(DisputeKey::Collateral(escrow_id)),
+        ) {
             let token_client = token::Client::new(&env, &collateral.token);
             token_client.transfer(
                 &env.current_contract_address(),
@@ -5299,9 +5375,7 @@ impl EscrowContract {
                 }
                 .publish(&env);
             }
-            env.storage()
-                .instance()
-                .remove(&DataKey::Dispute(DisputeKey::Collateral(escrow_id)));
+            record_remove(&env, &DataKey::Dispute(DisputeKey::Collateral(escrow_id)));
         }
 
         // Update analytics
@@ -5357,11 +5431,11 @@ impl EscrowContract {
     /// * `AlreadyProcessed` - the appeal has already been resolved or expired.
     /// * `TimeoutNotReached` - the appeal deadline has not yet passed.
     pub fn expire_appeal(env: Env, appeal_id: u64) -> Result<(), Error> {
-        let mut appeal = env
-            .storage()
-            .instance()
-            .get::<DataKey, DisputeAppeal>(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut appeal = record_get::<DataKey, DisputeAppeal>(
+            &env,
+            &DataKey::Dispute(DisputeKey::Appeal(appeal_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if appeal.resolved {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
@@ -5374,36 +5448,33 @@ impl EscrowContract {
 
         let escrow_id = appeal.escrow_id;
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
         // Mark the appeal resolved and advance the round to Final so the dispute state
         // machine can no longer be blocked in the Appeal round.
         appeal.resolved = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)), &appeal);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Appeal(appeal_id)),
+            &appeal,
+        );
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::Round(escrow_id)),
             &DisputeRound::Final,
         );
 
         // Reflect the timeout in the persisted audit record.
-        if let Some(mut record) = env
-            .storage()
-            .instance()
-            .get::<DataKey, AppealRecord>(&DataKey::Dispute(DisputeKey::AppealRecord(
-                escrow_id, appeal_id,
-            )))
-        {
+        if let Some(mut record) = record_get::<DataKey, AppealRecord>(
+            &env,
+            &DataKey::Dispute(DisputeKey::AppealRecord(escrow_id, appeal_id)),
+        ) {
             record.status = AppealStatus::Rejected;
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Dispute(DisputeKey::AppealRecord(escrow_id, appeal_id)),
                 &record,
             );
@@ -5420,9 +5491,7 @@ impl EscrowContract {
         };
 
         escrow.last_activity_at = now;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         AppealExpired {
             appeal_id,
@@ -5444,9 +5513,7 @@ impl EscrowContract {
     /// # Returns
     /// Some result when found or None when no value exists.
     pub fn get_appeal(env: Env, appeal_id: u64) -> Option<DisputeAppeal> {
-        env.storage()
-            .instance()
-            .get::<DataKey, DisputeAppeal>(&DataKey::Dispute(DisputeKey::Appeal(appeal_id)))
+        record_get::<DataKey, DisputeAppeal>(&env, &DataKey::Dispute(DisputeKey::Appeal(appeal_id)))
     }
 
     /// Returns escrows by customer.
@@ -5468,30 +5535,25 @@ impl EscrowContract {
         limit: u64,
         offset: u64,
     ) -> Vec<Escrow> {
-        let total_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())))
-            .unwrap_or(0);
+        let total_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())),
+        )
+        .unwrap_or(0);
 
         let mut escrows = Vec::new(&env);
         let start = offset;
         let end = (offset + limit).min(total_count);
 
         for i in start..end {
-            if let Some(escrow_id) = env
-                .storage()
-                .instance()
-                .get::<DataKey, u64>(&DataKey::Escrow(EscrowKey::CustomerList(
-                    customer.clone(),
-                    i,
-                )))
-            {
-                if let Some(escrow) = env
-                    .storage()
-                    .instance()
-                    .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-                {
+            if let Some(escrow_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Escrow(EscrowKey::CustomerList(customer.clone(), i)),
+            ) {
+                if let Some(escrow) = record_get::<DataKey, Escrow>(
+                    &env,
+                    &DataKey::Escrow(EscrowKey::Data(escrow_id)),
+                ) {
                     escrows.push_back(escrow);
                 }
             }
@@ -5512,10 +5574,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_escrow_count_by_customer(env: Env, customer: Address) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::CustomerCount(customer)))
-            .unwrap_or(0)
+        record_get(&env, &DataKey::Escrow(EscrowKey::CustomerCount(customer))).unwrap_or(0)
     }
 
     /// Returns escrows by merchant.
@@ -5537,30 +5596,25 @@ impl EscrowContract {
         limit: u64,
         offset: u64,
     ) -> Vec<Escrow> {
-        let total_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())))
-            .unwrap_or(0);
+        let total_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())),
+        )
+        .unwrap_or(0);
 
         let mut escrows = Vec::new(&env);
         let start = offset;
         let end = (offset + limit).min(total_count);
 
         for i in start..end {
-            if let Some(escrow_id) = env
-                .storage()
-                .instance()
-                .get::<DataKey, u64>(&DataKey::Escrow(EscrowKey::MerchantList(
-                    merchant.clone(),
-                    i,
-                )))
-            {
-                if let Some(escrow) = env
-                    .storage()
-                    .instance()
-                    .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-                {
+            if let Some(escrow_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Escrow(EscrowKey::MerchantList(merchant.clone(), i)),
+            ) {
+                if let Some(escrow) = record_get::<DataKey, Escrow>(
+                    &env,
+                    &DataKey::Escrow(EscrowKey::Data(escrow_id)),
+                ) {
                     escrows.push_back(escrow);
                 }
             }
@@ -5581,55 +5635,42 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_escrow_count_by_merchant(env: Env, merchant: Address) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MerchantCount(merchant)))
-            .unwrap_or(0)
+        record_get(&env, &DataKey::Escrow(EscrowKey::MerchantCount(merchant))).unwrap_or(0)
     }
 
     // ── STATE VERIFICATION INTERFACE ──────────────────────────────────────────
 
     /// Returns true if the escrow exists and its status is Released.
     pub fn is_escrow_released(env: Env, escrow_id: u64) -> bool {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| e.status == EscrowStatus::Released)
             .unwrap_or(false)
     }
 
     /// Returns true if the escrow exists and its status is Disputed.
     pub fn is_escrow_disputed(env: Env, escrow_id: u64) -> bool {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| e.status == EscrowStatus::Disputed)
             .unwrap_or(false)
     }
 
     /// Returns the current status of an escrow, or EscrowNotFound if it does not exist.
     pub fn get_escrow_status(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| e.status)
             .ok_or(Error::Escrow(EscrowError::NotFound))
     }
 
     /// Returns the (customer, merchant) address pair for an escrow, or EscrowNotFound.
     pub fn get_escrow_parties(env: Env, escrow_id: u64) -> Result<(Address, Address), Error> {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| (e.customer, e.merchant))
             .ok_or(Error::Escrow(EscrowError::NotFound))
     }
 
     /// Returns the locked amount for an escrow, or EscrowNotFound.
     pub fn get_escrow_amount(env: Env, escrow_id: u64) -> Result<i128, Error> {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| e.amount)
             .ok_or(Error::Escrow(EscrowError::NotFound))
     }
@@ -5637,9 +5678,7 @@ impl EscrowContract {
     /// Returns true if `address` is the customer or merchant of the given escrow.
     /// Returns false for non-existent escrow IDs or unrelated addresses.
     pub fn verify_escrow_participant(env: Env, escrow_id: u64, address: Address) -> bool {
-        env.storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .map(|e| e.customer == address || e.merchant == address)
             .unwrap_or(false)
     }
@@ -5649,10 +5688,7 @@ impl EscrowContract {
     pub fn get_escrow_details(env: Env, caller: Address, escrow_id: u64) -> Result<Escrow, Error> {
         caller.require_auth();
 
-        let escrow: Escrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        let escrow: Escrow = record_get(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if caller == escrow.customer || caller == escrow.merchant {
@@ -5677,11 +5713,7 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         granter.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -5701,21 +5733,18 @@ impl EscrowContract {
         }
 
         let now = env.ledger().timestamp();
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let mut i = 0u64;
         while i < count {
-            if let Some(existing) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowObserver>(&DataKey::Dispute(DisputeKey::Observer(
-                        escrow_id, i,
-                    )))
-            {
+            if let Some(existing) = record_get::<DataKey, EscrowObserver>(
+                &env,
+                &DataKey::Dispute(DisputeKey::Observer(escrow_id, i)),
+            ) {
                 if existing.observer == observer && existing.expires_at > now {
                     return Err(Error::Action(ActionError::ObserverAlreadyAdded));
                 }
@@ -5731,11 +5760,13 @@ impl EscrowContract {
             expires_at: now.saturating_add(duration_seconds),
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::Observer(escrow_id, count)),
             &obs,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
             &(count + 1),
         );
@@ -5752,11 +5783,7 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         granter.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -5775,38 +5802,38 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let mut i = 0u64;
         while i < count {
-            if let Some(existing) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowObserver>(&DataKey::Dispute(DisputeKey::Observer(
-                        escrow_id, i,
-                    )))
-            {
+            if let Some(existing) = record_get::<DataKey, EscrowObserver>(
+                &env,
+                &DataKey::Dispute(DisputeKey::Observer(escrow_id, i)),
+            ) {
                 if existing.observer == observer {
                     let last_index = count.saturating_sub(1);
                     if i != last_index {
-                        if let Some(last) = env.storage().instance().get::<DataKey, EscrowObserver>(
+                        if let Some(last) = record_get::<DataKey, EscrowObserver>(
+                            &env,
                             &DataKey::Dispute(DisputeKey::Observer(escrow_id, last_index)),
                         ) {
-                            env.storage()
-                                .instance()
-                                .set(&DataKey::Dispute(DisputeKey::Observer(escrow_id, i)), &last);
+                            record_set(
+                                &env,
+                                &DataKey::Dispute(DisputeKey::Observer(escrow_id, i)),
+                                &last,
+                            );
                         }
                     }
-                    env.storage()
-                        .instance()
-                        .remove(&DataKey::Dispute(DisputeKey::Observer(
-                            escrow_id, last_index,
-                        )));
-                    env.storage().instance().set(
+                    record_remove(
+                        &env,
+                        &DataKey::Dispute(DisputeKey::Observer(escrow_id, last_index)),
+                    );
+                    record_set(
+                        &env,
                         &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
                         &last_index,
                     );
@@ -5821,21 +5848,18 @@ impl EscrowContract {
 
     /// Returns all observers (including expired ones) for an escrow.
     pub fn get_observers(env: Env, escrow_id: u64) -> Vec<EscrowObserver> {
-        let total: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)))
-            .unwrap_or(0);
+        let total: u64 = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
+        )
+        .unwrap_or(0);
         let mut items: Vec<EscrowObserver> = Vec::new(&env);
         let mut i = 0u64;
         while i < total {
-            if let Some(o) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowObserver>(&DataKey::Dispute(DisputeKey::Observer(
-                        escrow_id, i,
-                    )))
-            {
+            if let Some(o) = record_get::<DataKey, EscrowObserver>(
+                &env,
+                &DataKey::Dispute(DisputeKey::Observer(escrow_id, i)),
+            ) {
                 items.push_back(o);
             }
             i += 1;
@@ -5847,20 +5871,17 @@ impl EscrowContract {
     /// Returns false for expired observers without removing them.
     pub fn verify_observer_access(env: Env, escrow_id: u64, observer: Address) -> bool {
         let now = env.ledger().timestamp();
-        let total: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)))
-            .unwrap_or(0);
+        let total: u64 = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::ObserverCount(escrow_id)),
+        )
+        .unwrap_or(0);
         let mut i = 0u64;
         while i < total {
-            if let Some(o) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowObserver>(&DataKey::Dispute(DisputeKey::Observer(
-                        escrow_id, i,
-                    )))
-            {
+            if let Some(o) = record_get::<DataKey, EscrowObserver>(
+                &env,
+                &DataKey::Dispute(DisputeKey::Observer(escrow_id, i)),
+            ) {
                 if o.observer == observer {
                     return o.expires_at > now;
                 }
@@ -6125,11 +6146,10 @@ impl EscrowContract {
 
         let mut i: u64 = 0;
         while i < total {
-            if let Some(ev) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Evidence>(&DataKey::Escrow(EscrowKey::Evidence(escrow_id, i)))
-            {
+            if let Some(ev) = record_get::<DataKey, Evidence>(
+                env,
+                &DataKey::Escrow(EscrowKey::Evidence(escrow_id, i)),
+            ) {
                 let rep = EscrowContract::get_or_default_reputation(env, &ev.submitter);
                 if ev.submitter == escrow.customer {
                     customer_weight = customer_weight.saturating_add(rep.score as i128);
@@ -6239,9 +6259,7 @@ impl EscrowContract {
             evidence_deadline: None,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::Counter), &escrow_id);
@@ -6266,37 +6284,42 @@ impl EscrowContract {
             milestones: indexed_milestones,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
             &vesting_schedule,
         );
 
         // Index by customer
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::CustomerList(customer.clone(), customer_count)),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::CustomerCount(customer.clone())),
             &(customer_count + 1),
         );
 
         // Index by merchant
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let merchant_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::MerchantList(merchant.clone(), merchant_count)),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::MerchantCount(merchant.clone())),
             &(merchant_count + 1),
         );
@@ -6322,23 +6345,21 @@ impl EscrowContract {
     /// # Errors
     /// * EscrowNotFound - If the escrow does not exist or has no vesting schedule
     pub fn get_vesting_schedule(env: Env, escrow_id: u64) -> Result<VestingSchedule, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))
+        record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))
     }
 
     fn get_base_vested_amount(env: &Env, escrow_id: u64) -> i128 {
-        let vesting_schedule =
-            match env
-                .storage()
-                .instance()
-                .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                    escrow_id,
-                ))) {
-                Some(schedule) => schedule,
-                None => return 0,
-            };
+        let vesting_schedule = match record_get::<DataKey, VestingSchedule>(
+            env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        ) {
+            Some(schedule) => schedule,
+            None => return 0,
+        };
 
         let current_timestamp = env.ledger().timestamp();
 
@@ -6389,16 +6410,13 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_vested_amount(env: Env, escrow_id: u64) -> i128 {
-        let vesting_schedule =
-            match env
-                .storage()
-                .instance()
-                .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                    escrow_id,
-                ))) {
-                Some(schedule) => schedule,
-                None => return 0,
-            };
+        let vesting_schedule = match record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        ) {
+            Some(schedule) => schedule,
+            None => return 0,
+        };
 
         let base_vested = Self::get_base_vested_amount(&env, escrow_id);
         let accelerated_amount = Self::calculate_accelerated_amount(env.clone(), escrow_id);
@@ -6446,13 +6464,11 @@ impl EscrowContract {
             return Err(Error::Escrow(EscrowError::InvalidVestingSchedule));
         }
 
-        if env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                schedule_id,
-            )))
-            .is_none()
+        if record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(schedule_id)),
+        )
+        .is_none()
         {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
@@ -6464,7 +6480,8 @@ impl EscrowContract {
             total_accelerated_bps: 0,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingAccelerationConfig(schedule_id)),
             &acceleration_config,
         );
@@ -6495,13 +6512,11 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut acceleration_config = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingAccelerationConfig>(&DataKey::Escrow(
-                EscrowKey::VestingAccelerationConfig(schedule_id),
-            ))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut acceleration_config = record_get::<DataKey, VestingAccelerationConfig>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingAccelerationConfig(schedule_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if acceleration_config.total_accelerated_bps >= acceleration_config.max_acceleration_bps {
             return Err(Error::Escrow(EscrowError::MilestoneAlreadyReleased));
@@ -6515,7 +6530,8 @@ impl EscrowContract {
         }
 
         acceleration_config.total_accelerated_bps = next_total;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingAccelerationConfig(schedule_id)),
             &acceleration_config,
         );
@@ -6535,11 +6551,10 @@ impl EscrowContract {
         env: Env,
         schedule_id: u64,
     ) -> Option<VestingAccelerationConfig> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::VestingAccelerationConfig(
-                schedule_id,
-            )))
+        record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingAccelerationConfig(schedule_id)),
+        )
     }
 
     /// Calculates accelerated amount.
@@ -6554,23 +6569,18 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn calculate_accelerated_amount(env: Env, schedule_id: u64) -> i128 {
-        let vesting_schedule =
-            match env
-                .storage()
-                .instance()
-                .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                    schedule_id,
-                ))) {
-                Some(schedule) => schedule,
-                None => return 0,
-            };
+        let vesting_schedule = match record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(schedule_id)),
+        ) {
+            Some(schedule) => schedule,
+            None => return 0,
+        };
 
-        let acceleration_config = match env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingAccelerationConfig>(&DataKey::Escrow(
-                EscrowKey::VestingAccelerationConfig(schedule_id),
-            )) {
+        let acceleration_config = match record_get::<DataKey, VestingAccelerationConfig>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingAccelerationConfig(schedule_id)),
+        ) {
             Some(config) => config,
             None => return 0,
         };
@@ -6586,13 +6596,11 @@ impl EscrowContract {
     /// # Errors
     /// * `EscrowNotFound` - No vesting schedule for this escrow
     pub fn get_cliff_status(env: Env, escrow_id: u64) -> Result<CliffStatus, Error> {
-        let vesting_schedule = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                escrow_id,
-            )))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let vesting_schedule = record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         let now = env.ledger().timestamp();
         let cliff_ts = vesting_schedule.cliff_timestamp;
@@ -6619,16 +6627,13 @@ impl EscrowContract {
     /// # Returns
     /// The amount that can be released
     pub fn get_releasable_amount(env: Env, escrow_id: u64) -> i128 {
-        let vesting_schedule =
-            match env
-                .storage()
-                .instance()
-                .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                    escrow_id,
-                ))) {
-                Some(schedule) => schedule,
-                None => return 0,
-            };
+        let vesting_schedule = match record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        ) {
+            Some(schedule) => schedule,
+            None => return 0,
+        };
 
         let vested_amount = EscrowContract::get_vested_amount(env, escrow_id);
         vested_amount.saturating_sub(vesting_schedule.released_amount)
@@ -6653,21 +6658,15 @@ impl EscrowContract {
         admin.require_auth();
 
         // Check if escrow exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
-        let mut vesting_schedule = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                escrow_id,
-            )))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut vesting_schedule = record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         let current_timestamp = env.ledger().timestamp();
 
@@ -6715,7 +6714,8 @@ impl EscrowContract {
         }
 
         // Update storage
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
             &vesting_schedule,
         );
@@ -6750,13 +6750,11 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut vesting_schedule = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                escrow_id,
-            )))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut vesting_schedule = record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         let mut found = false;
         let mut milestones = vesting_schedule.milestones.clone();
@@ -6780,7 +6778,8 @@ impl EscrowContract {
         }
 
         vesting_schedule.milestones = milestones;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
             &vesting_schedule,
         );
@@ -6804,18 +6803,13 @@ impl EscrowContract {
     /// * `MilestoneAlreadyReleased` - Milestone was already released
     /// * `MilestoneOverflow` - Release would exceed the escrow's locked amount
     pub fn release_milestone(env: Env, escrow_id: u64, milestone_id: u64) -> Result<i128, Error> {
-        let mut vesting_schedule = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                escrow_id,
-            )))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut vesting_schedule = record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
-        let escrow: Escrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        let escrow: Escrow = record_get(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         let mut found = false;
@@ -6853,7 +6847,8 @@ impl EscrowContract {
             .released_amount
             .saturating_add(release_amount);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
             &vesting_schedule,
         );
@@ -6878,16 +6873,13 @@ impl EscrowContract {
 
     /// Returns all milestones that have not yet been released.
     pub fn get_pending_milestones(env: Env, escrow_id: u64) -> Vec<VestingMilestone> {
-        let vesting_schedule =
-            match env
-                .storage()
-                .instance()
-                .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                    escrow_id,
-                ))) {
-                Some(s) => s,
-                None => return Vec::new(&env),
-            };
+        let vesting_schedule = match record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        ) {
+            Some(s) => s,
+            None => return Vec::new(&env),
+        };
 
         let mut pending = Vec::new(&env);
         for m in vesting_schedule.milestones.iter() {
@@ -6919,13 +6911,11 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut vesting_schedule = env
-            .storage()
-            .instance()
-            .get::<DataKey, VestingSchedule>(&DataKey::Escrow(EscrowKey::VestingSchedule(
-                escrow_id,
-            )))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut vesting_schedule = record_get::<DataKey, VestingSchedule>(
+            &env,
+            &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         // Sum existing milestone amounts
         let mut existing_total: i128 = 0;
@@ -6951,7 +6941,8 @@ impl EscrowContract {
         }
 
         vesting_schedule.milestones.push_back(new_milestone);
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::VestingSchedule(escrow_id)),
             &vesting_schedule,
         );
@@ -6998,29 +6989,50 @@ impl EscrowContract {
         for i in 0..count {
             buf[i] = digits[count - 1 - i];
         }
-        String::from_bytes(env, &buf[..count])
-    }
+        String::from_bytes(env, &buf[..count])
+    }
+
+    fn read_u64_from_bytes(data: &Bytes, offset: u32) -> u64 {
+        let mut result: u64 = 0;
+        for i in 0..8u32 {
+            let byte = data.get(offset + i).unwrap_or(0) as u64;
+            result = (result << 8) | byte;
+        }
+        result
+    }
+
+    fn do_upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
+        let previous_wasm_hash: Option<BytesN<32>> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::WasmHash));
+        if let Some(previous) = &previous_wasm_hash {
+            env.storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::PreviousWasmHash), previous);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::WasmHash), &new_wasm_hash);
 
-    fn read_u64_from_bytes(data: &Bytes, offset: u32) -> u64 {
-        let mut result: u64 = 0;
-        for i in 0..8u32 {
-            let byte = data.get(offset + i).unwrap_or(0) as u64;
-            result = (result << 8) | byte;
+        ContractUpgraded {
+            previous_wasm_hash,
+            new_wasm_hash: new_wasm_hash.clone(),
         }
-        result
+        .publish(env);
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
     }
 
     fn dispatch_action(env: &Env, proposal: &AdminProposal) -> Result<(), Error> {
         match proposal.action_type {
+            ActionType::UpgradeContract(ref new_wasm_hash) => {
+                EscrowContract::do_upgrade(env, new_wasm_hash.clone());
+            }
             ActionType::ReleaseEscrow => {
                 let escrow_id = EscrowContract::read_u64_from_bytes(&proposal.data, 0);
                 let early_release = proposal.data.get(8).unwrap_or(0) != 0;
 
-                if !env
-                    .storage()
-                    .instance()
-                    .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-                {
+                if !record_has(env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
                     return Err(Error::Escrow(EscrowError::NotFound));
                 }
 
@@ -7053,9 +7065,7 @@ impl EscrowContract {
                     }
                 }
 
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                record_set(env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
                 let fee_amount = (escrow.amount * escrow.fee_bps) / 10000;
                 let merchant_amount = escrow.amount - fee_amount;
@@ -7147,11 +7157,7 @@ impl EscrowContract {
                 let escrow_id = EscrowContract::read_u64_from_bytes(&proposal.data, 0);
                 let release_to_merchant = proposal.data.get(8).unwrap_or(0) != 0;
 
-                if !env
-                    .storage()
-                    .instance()
-                    .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-                {
+                if !record_has(env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
                     return Err(Error::Escrow(EscrowError::NotFound));
                 }
 
@@ -7168,9 +7174,7 @@ impl EscrowContract {
                     _ => return Err(Error::Action(ActionError::NotDisputed)),
                 }
 
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                record_set(env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
                 let (winner, loser) = if release_to_merchant {
                     (escrow.merchant.clone(), escrow.customer.clone())
@@ -7358,9 +7362,11 @@ impl EscrowContract {
             data,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Action(action_id)), &action);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Action(action_id)),
+            &action,
+        );
 
         TimeLockActionQueued {
             action_id,
@@ -7384,11 +7390,9 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn execute_queued_action(env: Env, action_id: u64) -> Result<(), Error> {
-        let mut action: TimeLockAction = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::Action(action_id)))
-            .ok_or(Error::Basic(BasicError::Unauthorized))?;
+        let mut action: TimeLockAction =
+            record_get(&env, &DataKey::Dispute(DisputeKey::Action(action_id)))
+                .ok_or(Error::Basic(BasicError::Unauthorized))?;
 
         if action.executed {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
@@ -7406,9 +7410,11 @@ impl EscrowContract {
         }
 
         action.executed = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Action(action_id)), &action);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Action(action_id)),
+            &action,
+        );
 
         match action.action_type {
             EscrowActionType::ResolveDispute(release_to_merchant) => {
@@ -7461,11 +7467,9 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut action: TimeLockAction = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::Action(action_id)))
-            .ok_or(Error::Basic(BasicError::Unauthorized))?;
+        let mut action: TimeLockAction =
+            record_get(&env, &DataKey::Dispute(DisputeKey::Action(action_id)))
+                .ok_or(Error::Basic(BasicError::Unauthorized))?;
 
         if action.executed {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -7480,9 +7484,11 @@ impl EscrowContract {
         }
 
         action.cancelled = true;
-        env.storage()
-            .instance()
-            .set(&DataKey::Dispute(DisputeKey::Action(action_id)), &action);
+        record_set(
+            &env,
+            &DataKey::Dispute(DisputeKey::Action(action_id)),
+            &action,
+        );
 
         TimeLockActionCancelled {
             action_id,
@@ -7505,9 +7511,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_queued_action(env: Env, action_id: u64) -> Result<TimeLockAction, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::Action(action_id)))
+        record_get(&env, &DataKey::Dispute(DisputeKey::Action(action_id)))
             .ok_or(Error::Basic(BasicError::Unauthorized))
     }
 
@@ -7645,17 +7649,28 @@ impl EscrowContract {
 
     /// Executes pause contract.
     ///
+    /// Pausing an already-paused contract only updates when the pause lifts.
+    ///
     /// # Arguments
     /// * `env` - Soroban environment.
     /// * `admin` - Address of the signer or participant.
     /// * `reason` - Reason description.
+    /// * `duration_seconds` - How long the pause lasts before it lifts on its
+    ///   own; `None` pauses until `unpause_contract` is called. Re-pausing a
+    ///   paused contract keeps the later expiry, so an indefinite pause stays
+    ///   indefinite.
     ///
     /// # Returns
     /// Results in `Ok(())` on success or `Err(Error)` on failure.
     ///
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
-    pub fn pause_contract(env: Env, admin: Address, reason: String) -> Result<(), Error> {
+    pub fn pause_contract(
+        env: Env,
+        admin: Address,
+        reason: String,
+        duration_seconds: Option<u64>,
+    ) -> Result<(), Error> {
         admin.require_auth();
         let config: MultiSigConfig = env
             .storage()
@@ -7666,6 +7681,25 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
         let global_key = String::from_str(&env, "global");
+        let mut already_paused = false;
+        if let Some(state) = env
+            .storage()
+            .instance()
+            .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
+        {
+            if state.globally_paused && Self::global_pause_expired(&env) {
+                Self::lift_expired_pause(&env, state);
+            } else {
+                already_paused = state.globally_paused;
+            }
+        }
+        let now = env.ledger().timestamp();
+        let paused_until = Self::merged_paused_until(
+            &env,
+            already_paused,
+            duration_seconds.map(|duration| now.saturating_add(duration)),
+        );
+        Self::set_paused_until(&env, paused_until);
         if env
             .storage()
             .instance()
@@ -7675,7 +7709,6 @@ impl EscrowContract {
         {
             return Ok(());
         }
-        let now = env.ledger().timestamp();
         let pause_state = if let Some(mut state) = env
             .storage()
             .instance()
@@ -7727,6 +7760,7 @@ impl EscrowContract {
             paused_by: admin,
             reason,
             paused_at: now,
+            paused_until,
         })
         .publish(&env);
         Ok(())
@@ -7763,6 +7797,7 @@ impl EscrowContract {
                 .instance()
                 .set(&DataKey::Config(ConfigKey::PauseStateKey), &state);
         }
+        Self::set_paused_until(&env, None);
         let now = env.ledger().timestamp();
         let global_key = String::from_str(&env, "global");
         if let Some(active_idx) = env
@@ -8053,7 +8088,8 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_pause_state(env: Env) -> PauseState {
-        env.storage()
+        let mut state = env
+            .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::PauseStateKey))
             .unwrap_or(PauseState {
@@ -8062,7 +8098,27 @@ impl EscrowContract {
                 paused_at: 0,
                 paused_by: env.current_contract_address(),
                 pause_reason: String::from_str(&env, ""),
-            })
+            });
+        if state.globally_paused && Self::global_pause_expired(&env) {
+            state.globally_paused = false;
+        }
+        state
+    }
+
+    /// Returns the timestamp at which the current global pause lifts on its own.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    ///
+    /// # Returns
+    /// `None` if the contract is not paused or was paused without a duration.
+    pub fn get_paused_until(env: Env) -> Option<u64> {
+        if Self::global_pause_expired(&env) {
+            return None;
+        }
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::PausedUntil))
     }
 
     /// Returns true if function paused.
@@ -8082,7 +8138,7 @@ impl EscrowContract {
             .instance()
             .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
         {
-            if state.globally_paused {
+            if state.globally_paused && !Self::global_pause_expired(&env) {
                 return true;
             }
             for fn_name in state.paused_functions.iter() {
@@ -8150,6 +8206,9 @@ impl EscrowContract {
 
     /// Executes migrate escrow.
     ///
+    /// Re-writes the escrow record and moves it, along with its per-escrow side
+    /// records, from instance storage into persistent storage.
+    ///
     /// # Arguments
     /// * `env` - Soroban environment.
     /// * `admin` - Address of the signer or participant.
@@ -8177,26 +8236,24 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::MigrationNotStarted));
         }
 
-        if env
-            .storage()
-            .instance()
-            .get::<DataKey, bool>(&DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)))
-            .unwrap_or(false)
+        if record_get::<DataKey, bool>(
+            &env,
+            &DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)),
+        )
+        .unwrap_or(false)
         {
             return Err(Error::Basic(BasicError::AlreadyMigrated));
         }
 
         // Read and re-write the escrow record (applies any new-format defaults)
-        let escrow: Escrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        let escrow: Escrow = record_get(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::NotFound))?;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        // Move the remaining per-escrow records out of instance storage
+        touch_escrow_records(&env, escrow_id);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)),
             &true,
         );
@@ -8246,24 +8303,22 @@ impl EscrowContract {
         let mut migrated: u32 = 0;
         for escrow_id in escrow_ids.iter() {
             // Skip already-migrated entries silently in batch mode
-            if env
-                .storage()
-                .instance()
-                .get::<DataKey, bool>(&DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)))
-                .unwrap_or(false)
+            if record_get::<DataKey, bool>(
+                &env,
+                &DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)),
+            )
+            .unwrap_or(false)
             {
                 continue;
             }
 
-            if let Some(escrow) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+            if let Some(escrow) =
+                record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
             {
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
-                env.storage().instance().set(
+                record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+                touch_escrow_records(&env, escrow_id);
+                record_set(
+                    &env,
                     &DataKey::Dispute(DisputeKey::EscrowMigrated(escrow_id)),
                     &true,
                 );
@@ -8355,6 +8410,77 @@ impl EscrowContract {
             })
     }
 
+    fn set_paused_until(env: &Env, paused_until: Option<u64>) {
+        match paused_until {
+            Some(timestamp) => env
+                .storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::PausedUntil), &timestamp),
+            None => env
+                .storage()
+                .instance()
+                .remove(&DataKey::Config(ConfigKey::PausedUntil)),
+        }
+    }
+
+    /// Returns the expiry of a global pause requested while the contract may
+    /// already be paused. A live pause keeps the later of the two expiries, and
+    /// an indefinite pause (`None`) outlasts any timed one.
+    fn merged_paused_until(env: &Env, already_paused: bool, requested: Option<u64>) -> Option<u64> {
+        if !already_paused {
+            return requested;
+        }
+        match (
+            env.storage()
+                .instance()
+                .get::<DataKey, u64>(&DataKey::Config(ConfigKey::PausedUntil)),
+            requested,
+        ) {
+            (Some(current), Some(requested)) => Some(current.max(requested)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` once a timed global pause has reached its `paused_until` time.
+    fn global_pause_expired(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get::<DataKey, u64>(&DataKey::Config(ConfigKey::PausedUntil))
+            .is_some_and(|paused_until| env.ledger().timestamp() >= paused_until)
+    }
+
+    /// Persists the unpause of a global pause whose duration has run out and
+    /// closes its history entry, with the contract itself as the unpauser.
+    fn lift_expired_pause(env: &Env, mut state: PauseState) {
+        let now = env.ledger().timestamp();
+        let contract = env.current_contract_address();
+        state.globally_paused = false;
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::PauseStateKey), &state);
+        Self::set_paused_until(env, None);
+        let index_key =
+            DataKey::Config(ConfigKey::ActivePauseIndex(String::from_str(env, "global")));
+        if let Some(active_idx) = env.storage().instance().get::<DataKey, u64>(&index_key) {
+            let entry_key = DataKey::Config(ConfigKey::PauseHistoryEntry(active_idx));
+            if let Some(mut entry) = env
+                .storage()
+                .instance()
+                .get::<DataKey, PauseHistory>(&entry_key)
+            {
+                entry.unpaused_by = Some(contract.clone());
+                entry.unpaused_at = Some(now);
+                env.storage().instance().set(&entry_key, &entry);
+            }
+            env.storage().instance().remove(&index_key);
+        }
+        (ContractUnpausedEvent {
+            unpaused_by: contract,
+            unpaused_at: now,
+        })
+        .publish(env);
+    }
+
     fn require_not_paused(env: &Env, function_name: &str) -> Result<(), Error> {
         if let Some(state) = env
             .storage()
@@ -8362,7 +8488,10 @@ impl EscrowContract {
             .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
         {
             if state.globally_paused {
-                return Err(Error::Basic(BasicError::ContractPaused));
+                if !Self::global_pause_expired(env) {
+                    return Err(Error::Basic(BasicError::ContractPaused));
+                }
+                Self::lift_expired_pause(env, state.clone());
             }
             let fn_str = String::from_str(env, function_name);
             for fn_name in state.paused_functions.iter() {
@@ -8505,9 +8634,7 @@ impl EscrowContract {
         }
 
         escrow.amount -= premium;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         let mut pool = Self::get_insurance_pool(env.clone(), escrow.token.clone());
         pool.balance += premium;
@@ -8576,7 +8703,8 @@ impl EscrowContract {
             paid_at: None,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::InsuranceClaim(counter)),
             &claim,
         );
@@ -8607,11 +8735,11 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let mut claim: InsuranceClaim = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::InsuranceClaim(claim_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut claim: InsuranceClaim = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::InsuranceClaim(claim_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::NotFound))?;
         if claim.approved {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
         }
@@ -8632,7 +8760,8 @@ impl EscrowContract {
 
         claim.approved = true;
         claim.paid_at = Some(env.ledger().timestamp());
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::InsuranceClaim(claim_id)),
             &claim,
         );
@@ -8678,11 +8807,7 @@ impl EscrowContract {
             return false;
         }
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return false;
         }
 
@@ -8730,9 +8855,7 @@ impl EscrowContract {
             EscrowStatus::Released
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         Self::transfer_if_token_contract(&env, &escrow.token, &released_to, escrow.amount)?;
 
@@ -8886,9 +9009,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_dispute_collateral(env: Env, escrow_id: u64) -> Result<DisputeCollateral, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::Collateral(escrow_id)))
+        record_get(&env, &DataKey::Dispute(DisputeKey::Collateral(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::InvalidStatus))
     }
 
@@ -9009,18 +9130,15 @@ impl EscrowContract {
         if !config.admins.contains(&admin) {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
         }
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)),
             &condition,
         );
@@ -9039,10 +9157,11 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_oracle_condition(env: Env, escrow_id: u64) -> Result<OracleCondition, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::InvalidStatus))
+        record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::InvalidStatus))
     }
 
     /// Automatically resolves a dispute or action when conditions are met.
@@ -9057,22 +9176,18 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn auto_resolve_with_oracle(env: Env, escrow_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
         if escrow.status != EscrowStatus::Disputed {
             return Err(Error::Action(ActionError::NotDisputed));
         }
-        let condition: OracleCondition = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::InvalidStatus))?;
+        let condition: OracleCondition = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::OracleCondition(escrow_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::InvalidStatus))?;
 
         let mut args: Vec<soroban_sdk::Val> = Vec::new(&env);
         args.push_back(condition.oracle.price_feed_id.clone().into());
@@ -9168,9 +9283,7 @@ impl EscrowContract {
             evidence_deadline: None,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::Counter), &escrow_id);
@@ -9181,7 +9294,8 @@ impl EscrowContract {
             evaluated: false,
             result: false,
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Conditional(escrow_id)),
             &conditional,
         );
@@ -9211,21 +9325,15 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn evaluate_and_release(env: Env, escrow_id: u64) -> Result<bool, Error> {
-        let mut conditional: ConditionalEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Conditional(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::ConditionalEscrowNotFound))?;
+        let mut conditional: ConditionalEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::Conditional(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::ConditionalEscrowNotFound))?;
 
         if conditional.evaluated {
             return Err(Error::Action(ActionError::ConditionAlreadyEvaluated));
         }
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let mut escrow = EscrowContract::get_escrow(&env, escrow_id);
@@ -9245,7 +9353,8 @@ impl EscrowContract {
 
         conditional.evaluated = true;
         conditional.result = met;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Conditional(escrow_id)),
             &conditional,
         );
@@ -9254,9 +9363,7 @@ impl EscrowContract {
 
         if met {
             escrow.status = EscrowStatus::Released;
-            env.storage()
-                .instance()
-                .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+            record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
             let fee_amount = (escrow.amount * escrow.fee_bps) / 10000;
             let merchant_amount = escrow.amount - fee_amount;
@@ -9324,9 +9431,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_conditional_escrow(env: Env, escrow_id: u64) -> Result<ConditionalEscrow, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Conditional(escrow_id)))
+        record_get(&env, &DataKey::Escrow(EscrowKey::Conditional(escrow_id)))
             .ok_or(Error::Escrow(EscrowError::ConditionalEscrowNotFound))
     }
 
@@ -9353,11 +9458,7 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -9382,13 +9483,11 @@ impl EscrowContract {
         let old_merchant = escrow.merchant.clone();
         let now = env.ledger().timestamp();
 
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Participant(
-                ParticipantKey::BeneficiaryTransferCount(escrow_id),
-            ))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let transfer = BeneficiaryTransfer {
             escrow_id,
@@ -9398,19 +9497,19 @@ impl EscrowContract {
             authorised_by: caller,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Participant(ParticipantKey::BeneficiaryTransferHistory(escrow_id, count)),
             &transfer,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
             &(count + 1),
         );
 
         escrow.merchant = new_merchant.clone();
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         BeneficiaryTransferred {
             escrow_id,
@@ -9434,23 +9533,18 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn get_transfer_history(env: Env, escrow_id: u64) -> Vec<BeneficiaryTransfer> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Participant(
-                ParticipantKey::BeneficiaryTransferCount(escrow_id),
-            ))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let mut history = Vec::new(&env);
         for i in 0..count {
-            if let Some(transfer) = env
-                .storage()
-                .instance()
-                .get::<DataKey, BeneficiaryTransfer>(&DataKey::Participant(
-                    ParticipantKey::BeneficiaryTransferHistory(escrow_id, i),
-                ))
-            {
+            if let Some(transfer) = record_get::<DataKey, BeneficiaryTransfer>(
+                &env,
+                &DataKey::Participant(ParticipantKey::BeneficiaryTransferHistory(escrow_id, i)),
+            ) {
                 history.push_back(transfer);
             }
         }
@@ -9464,11 +9558,7 @@ impl EscrowContract {
         escrow_id: u64,
         new_beneficiary: Address,
     ) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -9491,13 +9581,11 @@ impl EscrowContract {
         let old_beneficiary = escrow.merchant.clone();
         let now = env.ledger().timestamp();
 
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Participant(
-                ParticipantKey::BeneficiaryTransferCount(escrow_id),
-            ))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
+        )
+        .unwrap_or(0);
 
         let transfer = BeneficiaryTransfer {
             escrow_id,
@@ -9507,19 +9595,19 @@ impl EscrowContract {
             authorised_by: old_beneficiary.clone(),
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Participant(ParticipantKey::BeneficiaryTransferHistory(escrow_id, count)),
             &transfer,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Participant(ParticipantKey::BeneficiaryTransferCount(escrow_id)),
             &(count + 1),
         );
 
         escrow.merchant = new_beneficiary.clone();
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         BeneficiaryTransferred {
             escrow_id,
@@ -9552,11 +9640,9 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         caller.require_auth();
 
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
         if escrow.status != EscrowStatus::Locked {
             return Err(Error::Escrow(EscrowError::InvalidStatus));
@@ -9586,15 +9672,18 @@ impl EscrowContract {
             resolved: false,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
             &dispute,
         );
 
         escrow.status = EscrowStatus::Disputed;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
         MultiPartyDisputeRaised {
             escrow_id,
@@ -9626,17 +9715,15 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         voter.require_auth();
 
-        let escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
+        let escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
 
-        let mut dispute: MultiPartyDispute = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)))
-            .ok_or(Error::Action(ActionError::NotDisputed))?;
+        let mut dispute: MultiPartyDispute = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
+        )
+        .ok_or(Error::Action(ActionError::NotDisputed))?;
 
         if dispute.resolved {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
@@ -9670,7 +9757,8 @@ impl EscrowContract {
             dispute.votes_for_customer.push_back(voter.clone());
         }
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
             &dispute,
         );
@@ -9697,17 +9785,15 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn resolve_multi_party_dispute(env: Env, escrow_id: u64) -> Result<(), Error> {
-        let mut escrow: MultiPartyEscrow = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
-            .ok_or(Error::Escrow(EscrowError::NotFound))?;
-
-        let mut dispute: MultiPartyDispute = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)))
-            .ok_or(Error::Action(ActionError::NotDisputed))?;
+        let mut escrow: MultiPartyEscrow =
+            record_get(&env, &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)))
+                .ok_or(Error::Escrow(EscrowError::NotFound))?;
+
+        let mut dispute: MultiPartyDispute = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
+        )
+        .ok_or(Error::Action(ActionError::NotDisputed))?;
 
         if dispute.resolved {
             return Err(Error::Escrow(EscrowError::AlreadyProcessed));
@@ -9730,7 +9816,8 @@ impl EscrowContract {
         }
 
         dispute.resolved = true;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
             &dispute,
         );
@@ -9773,9 +9860,11 @@ impl EscrowContract {
             escrow.status = EscrowStatus::Resolved;
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::MultiParty(escrow_id)), &escrow);
+        record_set(
+            &env,
+            &DataKey::Escrow(EscrowKey::MultiParty(escrow_id)),
+            &escrow,
+        );
 
         MultiPartyDisputeResolved {
             escrow_id,
@@ -9799,10 +9888,11 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_multi_party_dispute(env: Env, escrow_id: u64) -> Result<MultiPartyDispute, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)))
-            .ok_or(Error::Action(ActionError::NotDisputed))
+        record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::MultiPartyDispute(escrow_id)),
+        )
+        .ok_or(Error::Action(ActionError::NotDisputed))
     }
 
     // ── BATCH ESCROW CREATION ─────────────────────────────────────────────
@@ -9936,49 +10026,47 @@ impl EscrowContract {
             evidence_deadline: None,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
         env.storage()
             .instance()
             .set(&DataKey::Escrow(EscrowKey::Counter), &escrow_id);
 
         // Index by customer
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::CustomerCount(
-                entry.customer.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 = record_get(
+            env,
+            &DataKey::Escrow(EscrowKey::CustomerCount(entry.customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::CustomerList(
                 entry.customer.clone(),
                 customer_count,
             )),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::CustomerCount(entry.customer.clone())),
             &(customer_count + 1),
         );
 
         // Index by merchant
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::MerchantCount(
-                entry.merchant.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let merchant_count: u64 = record_get(
+            env,
+            &DataKey::Escrow(EscrowKey::MerchantCount(entry.merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::MerchantList(
                 entry.merchant.clone(),
                 merchant_count,
             )),
             &escrow_id,
         );
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::Escrow(EscrowKey::MerchantCount(entry.merchant.clone())),
             &(merchant_count + 1),
         );
@@ -10107,11 +10195,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if the contract state is invalid.
     pub fn is_escrow_expired(env: Env, escrow_id: u64) -> bool {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return false;
         }
         let escrow = Self::get_escrow(&env, escrow_id);
@@ -10133,11 +10217,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn expire_escrow(env: Env, escrow_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let mut escrow = Self::get_escrow(&env, escrow_id);
@@ -10155,9 +10235,7 @@ impl EscrowContract {
         }
 
         escrow.status = EscrowStatus::Cancelled;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         EscrowContract::transfer_if_token_contract(
             &env,
@@ -10212,11 +10290,7 @@ impl EscrowContract {
     ) -> Result<(), Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
 
@@ -10251,11 +10325,11 @@ impl EscrowContract {
                 return Err(Error::Escrow(EscrowError::RenewalDisabled));
             }
 
-            renewal_count = env
-                .storage()
-                .instance()
-                .get(&DataKey::Dispute(DisputeKey::EscrowRenewalCount(escrow_id)))
-                .unwrap_or(0);
+            renewal_count = record_get(
+                &env,
+                &DataKey::Dispute(DisputeKey::EscrowRenewalCount(escrow_id)),
+            )
+            .unwrap_or(0);
 
             if renewal_count >= config.max_renewals {
                 return Err(Error::Escrow(EscrowError::MaxRenewalsReached));
@@ -10278,7 +10352,8 @@ impl EscrowContract {
             renewal_fee = (escrow.amount * config.renewal_fee_bps as i128) / 10_000;
             renewal_count += 1;
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Dispute(DisputeKey::EscrowRenewal(escrow_id)),
                 &EscrowRenewal {
                     renewal_id: renewal_count as u64,
@@ -10290,7 +10365,8 @@ impl EscrowContract {
                     renewal_count,
                 },
             );
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Dispute(DisputeKey::EscrowRenewalCount(escrow_id)),
                 &renewal_count,
             );
@@ -10298,9 +10374,7 @@ impl EscrowContract {
 
         escrow.expiry_timestamp = new_expiry_timestamp;
         escrow.last_activity_at = now;
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
         EscrowRenewed {
             escrow_id,
@@ -10357,7 +10431,8 @@ impl EscrowContract {
             active: true,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Template(template_id)),
             &template,
         );
@@ -10389,11 +10464,9 @@ impl EscrowContract {
     ) -> Result<u64, Error> {
         customer.require_auth();
 
-        let template: EscrowTemplate = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Template(template_id)))
-            .ok_or(Error::Escrow(EscrowError::TemplateNotFound))?;
+        let template: EscrowTemplate =
+            record_get(&env, &DataKey::Escrow(EscrowKey::Template(template_id)))
+                .ok_or(Error::Escrow(EscrowError::TemplateNotFound))?;
 
         if !template.active {
             return Err(Error::Escrow(EscrowError::TemplateInactive));
@@ -10493,14 +10566,13 @@ impl EscrowContract {
     pub fn deactivate_template(env: Env, owner: Address, template_id: u64) -> Result<(), Error> {
         owner.require_auth();
 
-        let mut template: EscrowTemplate = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Template(template_id)))
-            .ok_or(Error::Escrow(EscrowError::TemplateNotFound))?;
+        let mut template: EscrowTemplate =
+            record_get(&env, &DataKey::Escrow(EscrowKey::Template(template_id)))
+                .ok_or(Error::Escrow(EscrowError::TemplateNotFound))?;
 
         template.active = false;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Template(template_id)),
             &template,
         );
@@ -10522,9 +10594,7 @@ impl EscrowContract {
     /// # Errors
     /// Returns `Err(Error)` when the operation cannot be completed.
     pub fn get_template(env: Env, template_id: u64) -> Result<EscrowTemplate, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::Template(template_id)))
+        record_get(&env, &DataKey::Escrow(EscrowKey::Template(template_id)))
             .ok_or(Error::Escrow(EscrowError::TemplateNotFound))
     }
 
@@ -10599,11 +10669,7 @@ impl EscrowContract {
     /// Panics with `EscrowNotFound` if the escrow does not exist, or
     /// `StaleThresholdNotConfigured` if `set_stale_threshold` was never called.
     pub fn get_escrow_health(env: Env, escrow_id: u64) -> EscrowHealthReport {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             panic_with_error!(&env, Error::Escrow(EscrowError::NotFound));
         }
         let config = Self::require_stale_threshold(&env);
@@ -10639,10 +10705,8 @@ impl EscrowContract {
         let mut result = Vec::new(&env);
         let mut id: u64 = 1;
         while id <= counter && (result.len() as u32) < limit {
-            if let Some(escrow) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(id)))
+            if let Some(escrow) =
+                record_get::<DataKey, Escrow>(&env, &DataKey::Escrow(EscrowKey::Data(id)))
             {
                 if Self::classify_health(&escrow, now, &config) == EscrowHealth::Stale {
                     result.push_back(id);
@@ -10718,20 +10782,17 @@ impl EscrowContract {
 
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
 
-        let sub_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)))
-            .unwrap_or(0);
+        let sub_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
+        )
+        .unwrap_or(0);
         let mut allocated: i128 = 0;
         for sub_id in 1..=sub_count {
-            if let Some(sub) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowSubAccount>(&DataKey::Escrow(EscrowKey::SubAccount(
-                        escrow_id, sub_id,
-                    )))
-            {
+            if let Some(sub) = record_get::<DataKey, EscrowSubAccount>(
+                &env,
+                &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+            ) {
                 allocated += sub.amount;
             }
         }
@@ -10751,11 +10812,13 @@ impl EscrowContract {
             fee_bps_override,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
             &sub,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
             &sub_id,
         );
@@ -10791,18 +10854,19 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let mut sub: EscrowSubAccount = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)))
-            .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
+        let mut sub: EscrowSubAccount = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
 
         if sub.released {
             return Err(Error::Escrow(EscrowError::SubAccountAlreadyReleased));
         }
 
         sub.fee_bps_override = fee_bps_override;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
             &sub,
         );
@@ -10835,29 +10899,26 @@ impl EscrowContract {
 
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
 
-        let mut sub: EscrowSubAccount = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)))
-            .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
+        let mut sub: EscrowSubAccount = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
 
-        let sub_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)))
-            .unwrap_or(0);
+        let sub_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
+        )
+        .unwrap_or(0);
         let mut allocated: i128 = 0;
         for id in 1..=sub_count {
             if id == sub_id {
                 continue;
             }
-            if let Some(s) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowSubAccount>(&DataKey::Escrow(EscrowKey::SubAccount(
-                        escrow_id, id,
-                    )))
-            {
+            if let Some(s) = record_get::<DataKey, EscrowSubAccount>(
+                &env,
+                &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, id)),
+            ) {
                 allocated += s.amount;
             }
         }
@@ -10867,7 +10928,8 @@ impl EscrowContract {
         }
 
         sub.amount += amount;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
             &sub,
         );
@@ -10898,11 +10960,11 @@ impl EscrowContract {
 
         let escrow = EscrowContract::get_escrow(&env, escrow_id);
 
-        let mut sub: EscrowSubAccount = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)))
-            .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
+        let mut sub: EscrowSubAccount = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+        )
+        .ok_or(Error::Escrow(EscrowError::SubAccountNotFound))?;
 
         if sub.released {
             return Err(Error::Escrow(EscrowError::SubAccountAlreadyReleased));
@@ -10930,7 +10992,8 @@ impl EscrowContract {
         )?;
 
         sub.released = true;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
             &sub,
         );
@@ -10948,9 +11011,10 @@ impl EscrowContract {
     /// # Returns
     /// Some result when found or None when no value exists.
     pub fn get_sub_account(env: Env, escrow_id: u64, sub_id: u64) -> Option<EscrowSubAccount> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)))
+        record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+        )
     }
 
     /// Executes list sub accounts.
@@ -10965,35 +11029,91 @@ impl EscrowContract {
     /// # Panics
     /// Panics if required state is missing.
     pub fn list_sub_accounts(env: Env, escrow_id: u64) -> Vec<EscrowSubAccount> {
-        let sub_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)))
-            .unwrap_or(0);
+        let sub_count: u64 = record_get(
+            &env,
+            &DataKey::Escrow(EscrowKey::SubAccountCounter(escrow_id)),
+        )
+        .unwrap_or(0);
         let mut result = Vec::new(&env);
         for sub_id in 1..=sub_count {
-            if let Some(sub) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, EscrowSubAccount>(&DataKey::Escrow(EscrowKey::SubAccount(
-                        escrow_id, sub_id,
-                    )))
-            {
+            if let Some(sub) = record_get::<DataKey, EscrowSubAccount>(
+                &env,
+                &DataKey::Escrow(EscrowKey::SubAccount(escrow_id, sub_id)),
+            ) {
                 result.push_back(sub);
             }
         }
         result
     }
 
+    /// Adds `adapter` to, or removes it from, the swap adapters escrows may
+    /// trade through.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    /// * `caller` - Multi-sig admin (must authorize).
+    /// * `adapter` - Swap adapter contract.
+    /// * `allowed` - Whether `configure_escrow_swap` and `execute_escrow_swap`
+    ///   accept the adapter.
+    ///
+    /// # Returns
+    /// Results in `Ok(())` on success or `Err(Error)` on failure.
+    ///
+    /// # Errors
+    /// Returns `Err(Error)` when the caller is not an admin.
+    pub fn set_swap_adapter_allowed(
+        env: Env,
+        caller: Address,
+        adapter: Address,
+        allowed: bool,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::AdminMultiSig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&caller) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        let key = DataKey::Config(ConfigKey::SwapAdapter(adapter));
+        if allowed {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+        Ok(())
+    }
+
+    /// Returns whether escrow swaps may trade through `adapter`.
+    ///
+    /// # Arguments
+    /// * `env` - Soroban environment.
+    /// * `adapter` - Swap adapter contract.
+    ///
+    /// # Returns
+    /// `true` if an admin has allowed the adapter.
+    pub fn is_swap_adapter_allowed(env: Env, adapter: Address) -> bool {
+        env.storage()
+            .instance()
+            .has(&DataKey::Config(ConfigKey::SwapAdapter(adapter)))
+    }
+
     /// Executes configure escrow swap.
     ///
+    /// The escrow's customer must authorize the call as well, since the target
+    /// token and minimum output decide what the escrow will pay out.
+    ///
     /// # Arguments
     /// * `env` - Soroban environment.
     /// * `merchant` - Address of the signer or participant.
     /// * `escrow_id` - Identifier for the requested object.
     /// * `target_token` - Parameter value.
-    /// * `min_output` - Parameter value.
-    /// * `oracle` - Parameter value.
+    /// * `min_output` - Minimum amount of `target_token` the swap must deliver.
+    /// * `adapter` - Swap adapter contract that trades the escrowed token; must
+    ///   be allowed through `set_swap_adapter_allowed`.
     ///
     /// # Returns
     /// Results in `Ok(())` on success or `Err(Error)` on failure.
@@ -11006,15 +11126,11 @@ impl EscrowContract {
         escrow_id: u64,
         target_token: Address,
         min_output: i128,
-        oracle: Address,
+        adapter: Address,
     ) -> Result<(), Error> {
         merchant.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let escrow = Self::get_escrow(&env, escrow_id);
@@ -11026,17 +11142,22 @@ impl EscrowContract {
         if escrow.merchant != merchant && !config.admins.contains(&merchant) {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
+        escrow.customer.require_auth();
+        if !Self::is_swap_adapter_allowed(env.clone(), adapter.clone()) {
+            return Err(Error::Action(ActionError::SwapAdapterNotAllowed));
+        }
 
         let swap_config = EscrowSwapConfig {
             escrow_id,
             source_token: escrow.token.clone(),
             target_token,
             min_output_amount: min_output,
-            oracle,
+            adapter,
             executed: false,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)),
             &swap_config,
         );
@@ -11060,11 +11181,7 @@ impl EscrowContract {
         // Authenticate the caller to follow standard signature verification pattern.
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id))) {
             return Err(Error::Escrow(EscrowError::NotFound));
         }
         let mut escrow = Self::get_escrow(&env, escrow_id);
@@ -11077,42 +11194,74 @@ impl EscrowContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let mut swap_config: EscrowSwapConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)))
-            .ok_or(Error::Action(ActionError::SwapConfigNotFound))?;
+        let mut swap_config: EscrowSwapConfig = record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)),
+        )
+        .ok_or(Error::Action(ActionError::SwapConfigNotFound))?;
 
         if swap_config.executed {
             return Err(Error::Action(ActionError::SwapAlreadyExecuted));
         }
+        if !Self::is_swap_adapter_allowed(env.clone(), swap_config.adapter.clone()) {
+            return Err(Error::Action(ActionError::SwapAdapterNotAllowed));
+        }
 
-        // Call oracle to get the rate (get_rate symbol takes no arguments and returns i128)
-        let rate: i128 = env.invoke_contract(
-            &swap_config.oracle,
-            &Symbol::new(&env, "get_rate"),
-            Vec::new(&env),
+        // The adapter implements `swap(sender, token_in, token_out, amount_in, min_amount_out)`:
+        // it pulls `amount_in` from the escrow with `transfer_from` against a one-off
+        // allowance and sends the output back, so a failed swap leaves the escrow untouched.
+        let contract_address = env.current_contract_address();
+        let source = token::Client::new(&env, &escrow.token);
+        let target = token::Client::new(&env, &swap_config.target_token);
+        let balance_before = target.balance(&contract_address);
+
+        let expiration_ledger = env.ledger().sequence();
+        source.approve(
+            &contract_address,
+            &swap_config.adapter,
+            &escrow.amount,
+            &expiration_ledger,
         );
+        let args = (
+            contract_address.clone(),
+            escrow.token.clone(),
+            swap_config.target_token.clone(),
+            escrow.amount,
+            swap_config.min_output_amount,
+        )
+            .into_val(&env);
+        let result = env.try_invoke_contract::<i128, Error>(
+            &swap_config.adapter,
+            &Symbol::new(&env, "swap"),
+            args,
+        );
+        // Revoke whatever the adapter did not pull.
+        source.approve(
+            &contract_address,
+            &swap_config.adapter,
+            &0,
+            &expiration_ledger,
+        );
+        if !matches!(result, Ok(Ok(_))) {
+            return Err(Error::Action(ActionError::SwapFailed));
+        }
 
-        // Output amount scaled by Stellar/Soroban standard 1e7 fixed-point rate representation.
-        // The mock oracle and implementation use a 1e7 rate because the issue does not specify oracle decimals.
-        let output_amount = (escrow.amount * rate) / 10_000_000;
-
-        if output_amount < swap_config.min_output_amount {
+        // Trust the balance change rather than the adapter's reported output.
+        let output_amount = target.balance(&contract_address) - balance_before;
+        if output_amount <= 0 || output_amount < swap_config.min_output_amount {
             return Err(Error::Action(ActionError::SwapOutputBelowMinimum));
         }
 
-        // Update escrow state (token swap is modeled as metadata update, which automatically redirects settlement)
+        // The escrow now holds the target token, so settlement pays it out.
         escrow.token = swap_config.target_token.clone();
         escrow.amount = output_amount;
         escrow.last_activity_at = env.ledger().timestamp();
         swap_config.executed = true;
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
+        record_set(&env, &DataKey::Escrow(EscrowKey::Data(escrow_id)), &escrow);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)),
             &swap_config,
         );
@@ -11129,9 +11278,10 @@ impl EscrowContract {
     /// # Returns
     /// Some result when found or None when no value exists.
     pub fn get_swap_config(env: Env, escrow_id: u64) -> Option<EscrowSwapConfig> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)))
+        record_get(
+            &env,
+            &DataKey::Dispute(DisputeKey::EscrowSwapConfig(escrow_id)),
+        )
     }
 
     /// Creates child escrow.
@@ -11166,25 +11316,21 @@ impl EscrowContract {
         }
 
         // Verify parent escrow exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(parent_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(parent_id))) {
             return Err(Error::Escrow(EscrowError::ParentEscrowNotFound));
         }
 
         // Get or initialize parent hierarchy node to find parent's depth
-        let mut parent_node = env
-            .storage()
-            .instance()
-            .get::<DataKey, EscrowHierarchyNode>(&DataKey::Escrow(EscrowKey::Hierarchy(parent_id)))
-            .unwrap_or(EscrowHierarchyNode {
-                escrow_id: parent_id,
-                parent_id: None,
-                children: Vec::new(&env),
-                depth: 0, // Root depth is 0
-            });
+        let mut parent_node = record_get::<DataKey, EscrowHierarchyNode>(
+            &env,
+            &DataKey::Escrow(EscrowKey::Hierarchy(parent_id)),
+        )
+        .unwrap_or(EscrowHierarchyNode {
+            escrow_id: parent_id,
+            parent_id: None,
+            children: Vec::new(&env),
+            depth: 0, // Root depth is 0
+        });
 
         if parent_node.depth + 1 > 3 {
             return Err(Error::Escrow(EscrowError::MaxHierarchyDepth));
@@ -11208,7 +11354,8 @@ impl EscrowContract {
 
         // Update parent children list
         parent_node.children.push_back(child_id);
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Hierarchy(parent_id)),
             &parent_node,
         );
@@ -11220,7 +11367,8 @@ impl EscrowContract {
             children: Vec::new(&env),
             depth: child_depth,
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Escrow(EscrowKey::Hierarchy(child_id)),
             &child_node,
         );
@@ -11241,11 +11389,7 @@ impl EscrowContract {
     /// Panics if required state is missing.
     pub fn get_escrow_hierarchy(env: Env, root_id: u64) -> Vec<EscrowHierarchyNode> {
         let mut result = Vec::new(&env);
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(root_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(root_id))) {
             return result;
         }
 
@@ -11257,18 +11401,16 @@ impl EscrowContract {
             let current_id = queue.get(index).unwrap();
             index += 1;
 
-            let node = env
-                .storage()
-                .instance()
-                .get::<DataKey, EscrowHierarchyNode>(&DataKey::Escrow(EscrowKey::Hierarchy(
-                    current_id,
-                )))
-                .unwrap_or(EscrowHierarchyNode {
-                    escrow_id: current_id,
-                    parent_id: None,
-                    children: Vec::new(&env),
-                    depth: 0,
-                });
+            let node = record_get::<DataKey, EscrowHierarchyNode>(
+                &env,
+                &DataKey::Escrow(EscrowKey::Hierarchy(current_id)),
+            )
+            .unwrap_or(EscrowHierarchyNode {
+                escrow_id: current_id,
+                parent_id: None,
+                children: Vec::new(&env),
+                depth: 0,
+            });
 
             result.push_back(node.clone());
 
@@ -11291,11 +11433,7 @@ impl EscrowContract {
     /// # Panics
     /// Panics if the contract state is invalid.
     pub fn can_parent_release(env: Env, parent_id: u64) -> bool {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Escrow(EscrowKey::Data(parent_id)))
-        {
+        if !record_has(&env, &DataKey::Escrow(EscrowKey::Data(parent_id))) {
             return false;
         }
 
@@ -11307,13 +11445,10 @@ impl EscrowContract {
             let current_id = queue.get(index).unwrap();
             index += 1;
 
-            if let Some(node) = env
-                .storage()
-                .instance()
-                .get::<DataKey, EscrowHierarchyNode>(&DataKey::Escrow(EscrowKey::Hierarchy(
-                    current_id,
-                )))
-            {
+            if let Some(node) = record_get::<DataKey, EscrowHierarchyNode>(
+                &env,
+                &DataKey::Escrow(EscrowKey::Hierarchy(current_id)),
+            ) {
                 for child_id in node.children.iter() {
                     // Check if child is resolved
                     if !Self::is_escrow_resolved(&env, child_id) {
@@ -11327,10 +11462,8 @@ impl EscrowContract {
     }
 
     fn is_escrow_resolved(env: &Env, escrow_id: u64) -> bool {
-        if let Some(escrow) = env
-            .storage()
-            .instance()
-            .get::<DataKey, Escrow>(&DataKey::Escrow(EscrowKey::Data(escrow_id)))
+        if let Some(escrow) =
+            record_get::<DataKey, Escrow>(env, &DataKey::Escrow(EscrowKey::Data(escrow_id)))
         {
             match escrow.status {
                 EscrowStatus::Released | EscrowStatus::Resolved | EscrowStatus::Cancelled => true,
@@ -11546,6 +11679,13 @@ mod expiry_test;
 mod bulk_evidence_test;
 #[cfg(test)]
 mod migration_test;
+#[cfg(test)]
+mod record_storage_test;
+#[cfg(test)]
+mod upgrade_test;
+
+#[cfg(test)]
+mod timed_pause_test;
 //
 // #[cfg(test)]
 // mod multi_party_rollback_test;
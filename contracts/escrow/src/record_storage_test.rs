@@ -0,0 +1,107 @@
+#![cfg(test)]
+
+use crate::*;
+use soroban_sdk::testutils::storage::Persistent as _;
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::{testutils::Address as _, token, Address, Env};
+
+fn setup(env: &Env) -> (EscrowContractClient<'_>, Address, u64) {
+    env.mock_all_auths();
+    let contract_id = env.register(EscrowContract, ());
+    let client = EscrowContractClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let token_addr = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let token_admin = token::StellarAssetClient::new(env, &token_addr);
+    let customer = Address::generate(env);
+    token_admin.mint(&customer, &10_000);
+
+    let escrow_id = client.create_escrow(
+        &customer,
+        &Address::generate(env),
+        &500_i128,
+        &token_addr,
+        &2000_u64,
+        &0_u64,
+        &0_u64,
+        &false,
+    );
+    (client, admin, escrow_id)
+}
+
+/// Moves an escrow record back into instance storage, as a pre-v2 release stored it.
+fn demote_to_instance(env: &Env, client: &EscrowContractClient, escrow_id: u64) {
+    let key = DataKey::Escrow(EscrowKey::Data(escrow_id));
+    env.as_contract(&client.address, || {
+        let escrow: Escrow = env.storage().persistent().get(&key).unwrap();
+        env.storage().persistent().remove(&key);
+        env.storage().instance().set(&key, &escrow);
+    });
+}
+
+fn is_persistent(env: &Env, client: &EscrowContractClient, escrow_id: u64) -> bool {
+    let key = DataKey::Escrow(EscrowKey::Data(escrow_id));
+    env.as_contract(&client.address, || {
+        env.storage().persistent().has(&key) && !env.storage().instance().has(&key)
+    })
+}
+
+#[test]
+fn test_escrow_record_stored_in_persistent_storage() {
+    let env = Env::default();
+    let (client, _admin, escrow_id) = setup(&env);
+
+    assert!(is_persistent(&env, &client, escrow_id));
+}
+
+#[test]
+fn test_extend_record_ttl_refreshes_escrow_record() {
+    let env = Env::default();
+    let (client, _admin, escrow_id) = setup(&env);
+    let key = DataKey::Escrow(EscrowKey::Data(escrow_id));
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += RECORD_TTL_EXTEND_TO - RECORD_TTL_THRESHOLD + 1);
+    client.extend_record_ttl(&escrow_id);
+
+    let ttl = env.as_contract(&client.address, || env.storage().persistent().get_ttl(&key));
+    assert_eq!(ttl, RECORD_TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_extend_record_ttl_unknown_escrow_fails() {
+    let env = Env::default();
+    let (client, _admin, _escrow_id) = setup(&env);
+
+    let result = client.try_extend_record_ttl(&42);
+    assert_eq!(result, Err(Ok(Error::Escrow(EscrowError::NotFound))));
+}
+
+#[test]
+fn test_migrate_escrow_moves_legacy_record_to_persistent_storage() {
+    let env = Env::default();
+    let (client, admin, escrow_id) = setup(&env);
+    demote_to_instance(&env, &client, escrow_id);
+    assert!(!is_persistent(&env, &client, escrow_id));
+
+    client.begin_migration(&admin);
+    client.migrate_escrow(&admin, &escrow_id);
+    client.complete_migration(&admin);
+
+    assert!(is_persistent(&env, &client, escrow_id));
+    assert_eq!(client.get_escrow(&escrow_id).id, escrow_id);
+    assert_eq!(client.get_schema_version(), 2);
+}
+
+#[test]
+fn test_legacy_escrow_record_promoted_on_read() {
+    let env = Env::default();
+    let (client, _admin, escrow_id) = setup(&env);
+    demote_to_instance(&env, &client, escrow_id);
+
+    assert_eq!(client.get_escrow(&escrow_id).id, escrow_id);
+    assert!(is_persistent(&env, &client, escrow_id));
+}
@@ -1,140 +1,176 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{contract, contractimpl, testutils::Address as _, Address, Env};
-
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Ledger},
+    token, Address, Env,
+};
+
+/// Constant-rate AMM implementing the swap adapter interface. The rate is
+/// scaled by 1e7; `shortfall` makes it deliver less than it reports.
 #[contract]
-pub struct MockSwapOracle;
+pub struct MockAmm;
 
 #[contractimpl]
-impl MockSwapOracle {
-    pub fn get_rate(env: Env) -> i128 {
-        env.storage()
-            .instance()
-            .get::<u32, i128>(&0u32)
-            .unwrap_or(10_000_000) // Default 1.0 rate (1e7)
-    }
-
+impl MockAmm {
     pub fn set_rate(env: Env, rate: i128) {
         env.storage().instance().set(&0u32, &rate);
     }
+
+    pub fn set_shortfall(env: Env, shortfall: i128) {
+        env.storage().instance().set(&1u32, &shortfall);
+    }
+
+    pub fn swap(
+        env: Env,
+        sender: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        _min_amount_out: i128,
+    ) -> i128 {
+        let this = env.current_contract_address();
+        token::Client::new(&env, &token_in).transfer_from(&this, &sender, &this, &amount_in);
+
+        let rate: i128 = env.storage().instance().get(&0u32).unwrap_or(10_000_000);
+        let shortfall: i128 = env.storage().instance().get(&1u32).unwrap_or(0);
+        let amount_out = amount_in * rate / 10_000_000;
+        token::Client::new(&env, &token_out).transfer(&this, &sender, &(amount_out - shortfall));
+        amount_out
+    }
 }
 
-#[test]
-fn test_escrow_swap_successful() {
+struct Setup<'a> {
+    env: Env,
+    client: EscrowContractClient<'a>,
+    admin: Address,
+    customer: Address,
+    merchant: Address,
+    token: token::Client<'a>,
+    target_token: token::Client<'a>,
+    amm: MockAmmClient<'a>,
+    escrow_id: u64,
+}
+
+/// Locks 1_000 of `token` in an escrow releasable at t=1_000, gives the AMM
+/// 10_000 of `target_token` to trade with and allows it as a swap adapter.
+fn setup<'a>() -> Setup<'a> {
     let env = Env::default();
     env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
+    let client = EscrowContractClient::new(&env, &env.register(EscrowContract, ()));
 
     let admin = Address::generate(&env);
     let customer = Address::generate(&env);
     let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let target_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let amm = MockAmmClient::new(&env, &env.register(MockAmm, ()));
+    token::StellarAssetClient::new(&env, &token_id).mint(&customer, &1_000);
+    token::StellarAssetClient::new(&env, &target_id).mint(&amm.address, &10_000);
 
     client.initialize(&admin);
-
-    // Create escrow (8 arguments as defined in lib.rs)
+    client.set_swap_adapter_allowed(&admin, &amm.address, &true);
     let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+        &customer, &merchant, &1000_i128, &token_id, &1000_u64, &0_u64, &0_u64, &false,
     );
 
+    Setup {
+        token: token::Client::new(&env, &token_id),
+        target_token: token::Client::new(&env, &target_id),
+        env,
+        client,
+        admin,
+        customer,
+        merchant,
+        amm,
+        escrow_id,
+    }
+}
+
+#[test]
+fn test_escrow_swap_successful() {
+    let s = setup();
     // Set rate to 1.5 (15_000_000)
-    oracle_client.set_rate(&15_000_000_i128);
+    s.amm.set_rate(&15_000_000_i128);
 
     // Configure swap by merchant (min_output = 1400)
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1400_i128, &oracle);
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
+    );
 
     // Verify config is retrieved correctly
-    let config = client.get_swap_config(&escrow_id).unwrap();
-    assert_eq!(config.escrow_id, escrow_id);
-    assert_eq!(config.source_token, token);
-    assert_eq!(config.target_token, target_token);
+    let config = s.client.get_swap_config(&s.escrow_id).unwrap();
+    assert_eq!(config.escrow_id, s.escrow_id);
+    assert_eq!(config.source_token, s.token.address);
+    assert_eq!(config.target_token, s.target_token.address);
     assert_eq!(config.min_output_amount, 1400_i128);
-    assert_eq!(config.oracle, oracle);
+    assert_eq!(config.adapter, s.amm.address);
     assert_eq!(config.executed, false);
 
     // Execute swap
-    let output = client.execute_escrow_swap(&merchant, &escrow_id);
+    let output = s.client.execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(output, 1500_i128); // 1000 * 15_000_000 / 10_000_000 = 1500
 
-    // Verify updated escrow
-    let escrow = client.get_escrow(&escrow_id);
-    assert_eq!(escrow.token, target_token);
+    // The escrow now holds the target token instead of the source token
+    let escrow = s.client.get_escrow(&s.escrow_id);
+    assert_eq!(escrow.token, s.target_token.address);
     assert_eq!(escrow.amount, 1500_i128);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+    assert_eq!(s.target_token.balance(&s.client.address), 1500);
+    assert_eq!(s.token.allowance(&s.client.address, &s.amm.address), 0);
 
     // Verify config executed
-    let config = client.get_swap_config(&escrow_id).unwrap();
+    let config = s.client.get_swap_config(&s.escrow_id).unwrap();
     assert_eq!(config.executed, true);
 }
 
 #[test]
 fn test_escrow_swap_successful_by_admin() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
-
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+    let s = setup();
+    s.amm.set_rate(&15_000_000_i128);
+
+    // Configure and execute swap by admin
+    s.client.configure_escrow_swap(
+        &s.admin,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
-
-    oracle_client.set_rate(&15_000_000_i128);
-
-    // Configure swap by admin
-    client.configure_escrow_swap(&admin, &escrow_id, &target_token, &1400_i128, &oracle);
-
-    // Execute swap by admin
-    let output = client.execute_escrow_swap(&admin, &escrow_id);
+    let output = s.client.execute_escrow_swap(&s.admin, &s.escrow_id);
     assert_eq!(output, 1500_i128);
 
-    let escrow = client.get_escrow(&escrow_id);
-    assert_eq!(escrow.token, target_token);
+    let escrow = s.client.get_escrow(&s.escrow_id);
+    assert_eq!(escrow.token, s.target_token.address);
     assert_eq!(escrow.amount, 1500_i128);
 }
 
 #[test]
 fn test_escrow_swap_below_minimum_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
-
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
-    );
-
+    let s = setup();
     // Set rate to 1.2 (12_000_000) -> output = 1200
-    oracle_client.set_rate(&12_000_000_i128);
+    s.amm.set_rate(&12_000_000_i128);
 
     // Configure swap (min_output = 1300)
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1300_i128, &oracle);
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1300_i128,
+        &s.amm.address,
+    );
 
     // Executing should fail with SwapOutputBelowMinimum
-    let res = client.try_execute_escrow_swap(&merchant, &escrow_id);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(
         res,
         Err(Ok(Error::Action(ActionError::SwapOutputBelowMinimum)))
@@ -142,36 +178,68 @@ fn test_escrow_swap_below_minimum_fails() {
 }
 
 #[test]
-fn test_escrow_swap_double_execution_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
+fn test_escrow_swap_minimum_checked_against_received_balance() {
+    let s = setup();
+    // The AMM reports 1500 but only delivers 1350.
+    s.amm.set_rate(&15_000_000_i128);
+    s.amm.set_shortfall(&150_i128);
+
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
+    );
 
-    client.initialize(&admin);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
+    assert_eq!(
+        res,
+        Err(Ok(Error::Action(ActionError::SwapOutputBelowMinimum)))
+    );
+    assert_eq!(s.token.balance(&s.client.address), 1000);
+    assert_eq!(s.target_token.balance(&s.client.address), 0);
+}
 
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+#[test]
+fn test_escrow_swap_adapter_failure_fails() {
+    let s = setup();
+    // Rate 20 -> output 20_000, more than the AMM holds.
+    s.amm.set_rate(&200_000_000_i128);
+
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
 
-    oracle_client.set_rate(&15_000_000_i128);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
+    assert_eq!(res, Err(Ok(Error::Action(ActionError::SwapFailed))));
+    assert_eq!(s.token.balance(&s.client.address), 1000);
+    assert_eq!(s.client.get_escrow(&s.escrow_id).token, s.token.address);
+}
+
+#[test]
+fn test_escrow_swap_double_execution_fails() {
+    let s = setup();
+    s.amm.set_rate(&15_000_000_i128);
 
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1400_i128, &oracle);
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
+    );
 
     // Execute first time: success
-    let output = client.execute_escrow_swap(&merchant, &escrow_id);
+    let output = s.client.execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(output, 1500_i128);
 
     // Execute second time: fails with SwapAlreadyExecuted
-    let res = client.try_execute_escrow_swap(&merchant, &escrow_id);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(
         res,
         Err(Ok(Error::Action(ActionError::SwapAlreadyExecuted)))
@@ -180,201 +248,177 @@ fn test_escrow_swap_double_execution_fails() {
 
 #[test]
 fn test_escrow_swap_unauthorized_config_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let unauthorized_caller = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
-    );
+    let s = setup();
+    let unauthorized_caller = Address::generate(&s.env);
 
     // Non-merchant, non-admin tries to configure swap: fails with Unauthorized
-    let res = client.try_configure_escrow_swap(
+    let res = s.client.try_configure_escrow_swap(
         &unauthorized_caller,
-        &escrow_id,
-        &target_token,
+        &s.escrow_id,
+        &s.target_token.address,
         &1400_i128,
-        &oracle,
+        &s.amm.address,
     );
     assert_eq!(res, Err(Ok(Error::Basic(BasicError::Unauthorized))));
 }
 
 #[test]
 fn test_escrow_swap_unauthorized_execute_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let unauthorized_caller = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
+    let s = setup();
+    let unauthorized_caller = Address::generate(&s.env);
 
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
 
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1400_i128, &oracle);
-
     // Non-merchant, non-admin tries to execute swap: fails with Unauthorized
-    let res = client.try_execute_escrow_swap(&unauthorized_caller, &escrow_id);
+    let res = s
+        .client
+        .try_execute_escrow_swap(&unauthorized_caller, &s.escrow_id);
     assert_eq!(res, Err(Ok(Error::Basic(BasicError::Unauthorized))));
 }
 
 #[test]
-fn test_escrow_swap_config_not_found_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
+fn test_escrow_swap_config_requires_customer_auth() {
+    let s = setup();
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
+    );
 
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
+    let auths = s.env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == s.merchant));
+    assert!(auths.iter().any(|(address, _)| *address == s.customer));
+}
 
-    client.initialize(&admin);
+#[test]
+fn test_escrow_swap_adapter_must_be_allowed() {
+    let s = setup();
+    let unlisted = MockAmmClient::new(&s.env, &s.env.register(MockAmm, ()));
+    assert_eq!(
+        s.client.try_configure_escrow_swap(
+            &s.merchant,
+            &s.escrow_id,
+            &s.target_token.address,
+            &1400_i128,
+            &unlisted.address,
+        ),
+        Err(Ok(Error::Action(ActionError::SwapAdapterNotAllowed)))
+    );
 
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+    s.amm.set_rate(&15_000_000_i128);
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
+    s.client
+        .set_swap_adapter_allowed(&s.admin, &s.amm.address, &false);
+    assert!(!s.client.is_swap_adapter_allowed(&s.amm.address));
+    assert_eq!(
+        s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id),
+        Err(Ok(Error::Action(ActionError::SwapAdapterNotAllowed)))
+    );
+    assert_eq!(
+        s.client
+            .try_set_swap_adapter_allowed(&s.merchant, &s.amm.address, &true),
+        Err(Ok(Error::Basic(BasicError::NotAnAdmin)))
+    );
+}
+
+#[test]
+fn test_escrow_swap_config_not_found_fails() {
+    let s = setup();
 
     // Execute swap directly without configuring first: fails with SwapConfigNotFound
-    let res = client.try_execute_escrow_swap(&merchant, &escrow_id);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(res, Err(Ok(Error::Action(ActionError::SwapConfigNotFound))));
 }
 
 #[test]
 fn swap_rolls_back_when_second_leg_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
-
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
-    );
-
+    let s = setup();
     // Set rate to 1.2 (12_000_000) -> output = 1200
-    oracle_client.set_rate(&12_000_000_i128);
+    s.amm.set_rate(&12_000_000_i128);
 
     // Configure swap (min_output = 1300)
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1300_i128, &oracle);
-
-    // Get initial escrow state
-    let escrow_before = client.get_escrow(&escrow_id);
-    assert_eq!(escrow_before.token, token);
-    assert_eq!(escrow_before.amount, 1000_i128);
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1300_i128,
+        &s.amm.address,
+    );
 
     // Executing should fail with SwapOutputBelowMinimum
-    let res = client.try_execute_escrow_swap(&merchant, &escrow_id);
+    let res = s.client.try_execute_escrow_swap(&s.merchant, &s.escrow_id);
     assert_eq!(
         res,
         Err(Ok(Error::Action(ActionError::SwapOutputBelowMinimum)))
     );
 
-    // Verify escrow state is unchanged (rollback)
-    let escrow_after = client.get_escrow(&escrow_id);
-    assert_eq!(escrow_after.token, token);
+    // Verify escrow state and balances are unchanged (rollback)
+    let escrow_after = s.client.get_escrow(&s.escrow_id);
+    assert_eq!(escrow_after.token, s.token.address);
     assert_eq!(escrow_after.amount, 1000_i128);
+    assert_eq!(s.token.balance(&s.client.address), 1000);
+    assert_eq!(s.token.balance(&s.amm.address), 0);
+    assert_eq!(s.target_token.balance(&s.amm.address), 10_000);
 
     // Verify swap config is not marked as executed
-    let config = client.get_swap_config(&escrow_id).unwrap();
+    let config = s.client.get_swap_config(&s.escrow_id).unwrap();
     assert_eq!(config.executed, false);
 }
 
 #[test]
 fn both_legs_succeed_funds_exchanged_correctly() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
-    let oracle_client = MockSwapOracleClient::new(&env, &oracle);
-
-    client.initialize(&admin);
+    let s = setup();
+    s.amm.set_rate(&15_000_000_i128);
 
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
-
-    // Set rate to 1.5 (15_000_000)
-    oracle_client.set_rate(&15_000_000_i128);
-
-    // Configure swap by merchant (min_output = 1400)
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1400_i128, &oracle);
-
-    // Execute swap
-    let output = client.execute_escrow_swap(&merchant, &escrow_id);
-    assert_eq!(output, 1500_i128); // 1000 * 15_000_000 / 10_000_000 = 1500
-
-    // Verify updated escrow - funds exchanged correctly
-    let escrow = client.get_escrow(&escrow_id);
-    assert_eq!(escrow.token, target_token);
-    assert_eq!(escrow.amount, 1500_i128);
-
-    // Verify config executed
-    let config = client.get_swap_config(&escrow_id).unwrap();
-    assert_eq!(config.executed, true);
+    s.client.execute_escrow_swap(&s.merchant, &s.escrow_id);
+    assert_eq!(s.token.balance(&s.amm.address), 1000);
+    assert_eq!(s.target_token.balance(&s.amm.address), 8500);
+
+    // Release pays the merchant in the target token
+    s.env.ledger().set_timestamp(1000);
+    s.client.release_escrow(&s.admin, &s.escrow_id, &false);
+    assert_eq!(s.target_token.balance(&s.merchant), 1500);
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert_eq!(s.target_token.balance(&s.client.address), 0);
 }
 
 #[test]
 fn swap_cannot_be_claimed_by_non_participant() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let contract_id = env.register(EscrowContract, ());
-    let client = EscrowContractClient::new(&env, &contract_id);
-
-    let admin = Address::generate(&env);
-    let customer = Address::generate(&env);
-    let merchant = Address::generate(&env);
-    let non_participant = Address::generate(&env);
-    let token = Address::generate(&env);
-    let target_token = Address::generate(&env);
-    let oracle = env.register(MockSwapOracle, ());
+    let s = setup();
+    let non_participant = Address::generate(&s.env);
 
-    client.initialize(&admin);
-
-    let escrow_id = client.create_escrow(
-        &customer, &merchant, &1000_i128, &token, &1000_u64, &0_u64, &0_u64, &false,
+    s.client.configure_escrow_swap(
+        &s.merchant,
+        &s.escrow_id,
+        &s.target_token.address,
+        &1400_i128,
+        &s.amm.address,
     );
 
-    client.configure_escrow_swap(&merchant, &escrow_id, &target_token, &1400_i128, &oracle);
-
     // Non-participant tries to execute swap: fails with Unauthorized
-    let res = client.try_execute_escrow_swap(&non_participant, &escrow_id);
+    let res = s
+        .client
+        .try_execute_escrow_swap(&non_participant, &s.escrow_id);
     assert_eq!(res, Err(Ok(Error::Basic(BasicError::Unauthorized))));
 }
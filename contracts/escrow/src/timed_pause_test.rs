@@ -0,0 +1,68 @@
+use crate::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+
+fn setup(env: &Env) -> (EscrowContractClient<'_>, Address) {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    let admin = Address::generate(env);
+    let client = EscrowContractClient::new(env, &env.register(EscrowContract, ()));
+    client.initialize(&admin);
+    (client, admin)
+}
+
+fn reason(env: &Env) -> String {
+    String::from_str(env, "mistaken pause")
+}
+
+#[test]
+fn test_timed_pause_lifts_after_duration() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let release = String::from_str(&env, "release_escrow");
+
+    client.pause_contract(&admin, &reason(&env), &Some(3_600));
+    assert!(client.is_function_paused(&release));
+    assert_eq!(client.get_paused_until(), Some(4_600));
+    assert_eq!(
+        client.try_release_escrow(&admin, &1, &false),
+        Err(Ok(Error::Basic(BasicError::ContractPaused)))
+    );
+
+    env.ledger().set_timestamp(4_600);
+    assert!(!client.is_function_paused(&release));
+    assert!(!client.get_pause_state().globally_paused);
+    assert_eq!(client.get_paused_until(), None);
+}
+
+#[test]
+fn test_repause_after_expiry_closes_previous_history_entry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(100));
+    env.ledger().set_timestamp(2_000);
+    client.pause_contract(&admin, &reason(&env), &None);
+
+    assert!(client.get_pause_state().globally_paused);
+    let history = client.get_pause_history(&10, &0);
+    assert_eq!(history.len(), 2);
+    let expired = history.get(0).unwrap();
+    assert_eq!(expired.unpaused_by, Some(client.address.clone()));
+    assert_eq!(expired.unpaused_at, Some(2_000));
+    assert_eq!(history.get(1).unwrap().unpaused_by, None);
+}
+
+#[test]
+fn test_timed_repause_keeps_indefinite_pause() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &None);
+    client.pause_contract(&admin, &reason(&env), &Some(500));
+
+    assert_eq!(client.get_paused_until(), None);
+    assert_eq!(client.get_pause_history(&10, &0).len(), 1);
+
+    client.unpause_contract(&admin);
+    assert_eq!(client.get_paused_until(), None);
+}
@@ -0,0 +1,85 @@
+#![cfg(test)]
+
+use crate::*;
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol};
+
+const UPGRADE_WASM: &[u8] = include_bytes!("../../test_fixtures/add_u64.wasm");
+
+fn setup(env: &Env) -> (EscrowContractClient<'_>, Address, BytesN<32>) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let client = EscrowContractClient::new(env, &env.register(EscrowContract, ()));
+    client.initialize(&admin);
+    let wasm_hash = env.deployer().upload_contract_wasm(UPGRADE_WASM);
+    (client, admin, wasm_hash)
+}
+
+fn runs_upgraded_code(env: &Env, contract: &Address) -> bool {
+    let sum: u64 = env.invoke_contract(
+        contract,
+        &Symbol::new(env, "add"),
+        vec![env, 4u64.into_val(env), 5u64.into_val(env)],
+    );
+    sum == 9
+}
+
+#[test]
+fn test_upgrade_replaces_code_and_records_hash() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+
+    client.upgrade(&admin, &wasm_hash);
+
+    assert!(runs_upgraded_code(&env, &client.address));
+    let stored: Option<BytesN<32>> = env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::WasmHash))
+    });
+    assert_eq!(stored, Some(wasm_hash));
+}
+
+#[test]
+fn test_upgrade_rejects_non_admin() {
+    let env = Env::default();
+    let (client, _admin, wasm_hash) = setup(&env);
+
+    let result = client.try_upgrade(&Address::generate(&env), &wasm_hash);
+    assert_eq!(result, Err(Ok(Error::Basic(BasicError::NotAnAdmin))));
+    assert_eq!(client.get_wasm_hash(), None);
+}
+
+#[test]
+fn test_upgrade_requires_proposal_above_threshold_one() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+    let admin2 = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        let config = MultiSigConfig {
+            admins: vec![&env, admin.clone(), admin2.clone()],
+            required_signatures: 2,
+            total_admins: 2,
+            proposal_ttl: 604800,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::AdminMultiSig), &config);
+    });
+
+    let result = client.try_upgrade(&admin, &wasm_hash);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Action(ActionError::ApprovalsThresholdNotMet)))
+    );
+
+    let proposal_id = client.propose_action(
+        &admin,
+        &ActionType::UpgradeContract(wasm_hash),
+        &client.address,
+        &Bytes::new(&env),
+    );
+    client.approve_action(&admin2, &proposal_id);
+    client.execute_action(&proposal_id);
+
+    assert!(runs_upgraded_code(&env, &client.address));
+}
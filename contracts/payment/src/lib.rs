@@ -4,7 +4,7 @@
 use escrow::EscrowContractClient;
 use soroban_sdk::{
     contract, contracterror, contractevent, contractimpl, contracttype, token, xdr::ToXdr, Address,
-    Bytes, BytesN, Env, FromVal, IntoVal, InvokeError, String, Symbol, TryFromVal, Val, Vec,
+    Bytes, BytesN, Env, FromVal, IntoVal, InvokeError, Map, String, Symbol, TryFromVal, Val, Vec,
 };
 
 #[derive(Clone, Debug, PartialEq)]
@@ -42,10 +42,17 @@ pub enum ConfigKey {
     LargePaymentThreshold,
     GlobalMerchantCount,
     PauseStateKey,
+    PausedUntil,
     MinSplitAmount,
     SchemaVersion,
     AllowedTokens,
     MaxForwardDepth,
+    MaxRouteHops,
+    RefundContract,
+    WasmHash,
+    PreviousWasmHash,
+    ChargebackWindow,
+    ReferralConfig,
 }
 
 #[derive(Clone)]
@@ -66,6 +73,17 @@ pub enum PaymentKey {
     AccumulatedFees,
     LargePaymentCounter,
     Discount(u64),
+    InvoiceHash(u64),
+    IssuedInvoice(u64),
+    IssuedInvoiceCounter,
+    TaxRule(Symbol, BytesN<32>),
+    MerchantTaxRule(Address, Symbol, BytesN<32>),
+    InvoiceTaxBreakdown(u64),
+    IssuedInvoiceTaxContext(u64),
+    IssuedInvoiceTaxBreakdown(u64),
+    Chargeback(u64),
+    PlatformPayment(u64),
+    CompletedAt(u64),
 }
 
 pub const MAX_MEMO_VERSIONS: u32 = 10;
@@ -80,6 +98,14 @@ pub enum SubscriptionKey {
     Group(u64),
     GroupCounter,
     GroupMembership(u64),
+    Plan(u64),
+    PlanCounter,
+    MerchantPlans(Address),
+    PlanLink(u64),
+    PlanDunning(u64),
+    PendingPlanChange(u64),
+    ProrationCredit(u64),
+    BillingAnchor(u64),
 }
 
 #[derive(Clone)]
@@ -95,11 +121,23 @@ pub enum FeatureKey {
     CustomerSpendLimit(Address),
     PaymentChannel(u64),
     PaymentChannelCounter,
+    BidirectionalChannel(u64),
+    BidirectionalChannelCounter,
     SplitConfig(u64),
     SweepRecipient,
     SweepCounter,
     SweepHistory(u64),
     RouteOptions(Address, Address),
+    SwapVenues,
+    Referral(Address),
+    ReferralBalance(Address, Address),
+    ReferrerAnalytics(Address),
+    Campaign(u64),
+    CampaignCounter,
+    MerchantCampaigns(Address),
+    CampaignRewards(u64, Address),
+    CustomerPurchases(Address, Address),
+    HeldCashback(u64),
 }
 
 #[derive(Clone)]
@@ -176,6 +214,25 @@ pub enum PaymentError {
     InvalidLineItem = 222,
     InvalidScheduleTime = 223,
     TokenNotAllowed = 224,
+    InvoiceNotFound = 225,
+    InvoiceNotOpen = 226,
+    TokenNotAccepted = 227,
+    InvalidDueDate = 228,
+    TaxRuleNotFound = 229,
+    InvalidTaxRule = 230,
+    ChargebackWindowClosed = 231,
+    ChargebackNotFound = 232,
+    SubMerchantNotFound = 233,
+    InsufficientWalletBalance = 234,
+    DelegationNotFound = 235,
+    DelegationExpired = 236,
+    DelegationScopeViolation = 237,
+    DelegationCapExceeded = 238,
+    CampaignNotFound = 239,
+    CampaignNotEnded = 240,
+    CampaignClosed = 241,
+    ChargebackPending = 242,
+    RefundMismatch = 243,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -188,6 +245,8 @@ pub enum SubscriptionError {
     BillingCapExceeded = 311, GroupNotFound = 312, AlreadyInGroup = 313,
     GroupSizeLimitExceeded = 314, TrialExpired = 315, MaxTrialDurationExceeded = 316,
     MerchantPaused = 317, UsageCapExceeded = 318,
+    PlanNotFound = 319, PlanArchived = 320, PriceNoticeTooShort = 321,
+    PriceScheduleConflict = 322, IncompatiblePlan = 323,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -250,6 +309,38 @@ pub enum FeatureError {
     BelowMinSplitAmount = 539,
     // Issue #385: claimed settlement amounts must sum exactly to the channel deposit.
     BalanceSumMismatch = 541,
+    ChannelNotClosing = 542,
+    ChallengePeriodActive = 543,
+    ChallengePeriodOver = 544,
+    ReferralProgramNotFound = 545,
+    ReferralNotEligible = 546,
+    NoReferralCommission = 547,
+    ChannelStateNotFinal = 548,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u32)]
+#[contracterror]
+pub enum RoutingError {
+    SwapVenueNotFound = 600,
+    InvalidRoute = 601,
+    TooManyHops = 602,
+    SwapFailed = 603,
+    SlippageExceeded = 604,
+    RateUnavailable = 605,
+    QuoteOverflow = 606,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u32)]
+#[contracterror]
+pub enum SignatureError {
+    SigningKeyNotRegistered = 700,
+    IntentExpired = 701,
+    IntentNonceUsed = 702,
+    KeyTypeMismatch = 703,
+    InvalidWebAuthnAssertion = 704,
+    ChallengeMismatch = 705,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -259,6 +350,8 @@ pub enum Error {
     Subscription(SubscriptionError),
     Proposal(ProposalError),
     Feature(FeatureError),
+    Routing(RoutingError),
+    Signature(SignatureError),
 }
 
 impl Error {
@@ -274,6 +367,8 @@ impl Error {
             Error::Subscription(e) => *e as u32,
             Error::Proposal(e) => *e as u32,
             Error::Feature(e) => *e as u32,
+            Error::Routing(e) => *e as u32,
+            Error::Signature(e) => *e as u32,
         }
     }
 }
@@ -295,16 +390,26 @@ impl TryFrom<soroban_sdk::Error> for Error {
     fn try_from(error: soroban_sdk::Error) -> Result<Self, Self::Error> {
         if error.is_type(soroban_sdk::xdr::ScErrorType::Contract) {
             let code = error.get_code();
-            if code >= 500 && code <= 540 {
+            if (700..=705).contains(&code) {
+                return Ok(Error::Signature(unsafe {
+                    core::mem::transmute::<u32, SignatureError>(code)
+                }));
+            }
+            if (600..=606).contains(&code) {
+                return Ok(Error::Routing(unsafe {
+                    core::mem::transmute::<u32, RoutingError>(code)
+                }));
+            }
+            if code >= 500 && code <= 548 {
                 return Ok(Error::Feature(unsafe { core::mem::transmute(code) }));
             }
             if code >= 400 && code <= 406 {
                 return Ok(Error::Proposal(unsafe { core::mem::transmute(code) }));
             }
-            if code >= 300 && code <= 318 {
+            if code >= 300 && code <= 323 {
                 return Ok(Error::Subscription(unsafe { core::mem::transmute(code) }));
             }
-            if code >= 200 && code <= 224 {
+            if code >= 200 && code <= 243 {
                 return Ok(Error::Payment(unsafe { core::mem::transmute(code) }));
             }
             if code >= 100 && code <= 126 {
@@ -350,6 +455,13 @@ pub enum CustomerDataKey {
     MerchantCount(Address),
     MonthlyVolume(Address, u64),
     HourCount(Address, u32),
+    IntentKey(Address),
+    IntentNonceUsed(Address, u64),
+    WalletBalance(Address, Address),
+    Delegation(Address, Address),
+    Delegates(Address),
+    DelegateSpends(Address, Address, u64),
+    DelegateSpendCount(Address, Address),
 }
 
 // Merchant-specific data keys
@@ -375,6 +487,16 @@ pub enum MerchantDataKey {
     MerchantActiveSubscriptions(Address, u64),
     MerchantActiveSubscriptionCount(Address),
     ActiveSubscriptionIndex(u64),
+    SettlementPreference(Address),
+    PayoutAnchor(Address),
+    ChargebackDebts(Address),
+    ReserveTier(MerchantVerificationLevel),
+    Category(Address),
+    ReserveDays(Address),
+    ReserveBucket(Address, u64),
+    ReserveBalance(Address, Address),
+    SubMerchant(Address),
+    PlatformSubMerchants(Address),
 }
 
 // State and proposal data keys
@@ -398,6 +520,40 @@ pub enum StateDataKey {
     ScheduledPaymentCounter,
 }
 
+/// Token a merchant wants to be paid in. Payouts in other tokens are converted
+/// through the best single swap venue, accepting at most `max_slippage_bps`
+/// below the quoted output.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SettlementPreference {
+    pub token: Address,
+    pub max_slippage_bps: u32,
+}
+
+/// When a recurring charge or payout falls due. Calendar anchors fall at 00:00 UTC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum BillingAnchor {
+    /// A fixed number of seconds after the previous due date.
+    FixedSeconds(u64),
+    /// Day 1–31 of every month, clamped to the last day of shorter months.
+    MonthlyOnDay(u32),
+    /// Every year on the month and day of the schedule's start; 29 February
+    /// falls on 28 February in common years.
+    Yearly,
+    /// Every week on a weekday, 0 = Monday to 6 = Sunday.
+    Weekly(u32),
+}
+
+/// A payout schedule's `BillingAnchor` and the time it was set, which
+/// `BillingAnchor::Yearly` takes its anniversary from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct PayoutAnchor {
+    pub anchor: BillingAnchor,
+    pub origin: u64,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct PayoutSchedule {
@@ -485,6 +641,59 @@ pub struct Subscription {
     pub pause_data: SubscriptionPauseData,
 }
 
+/// A plan price and the time it starts applying to subscribers.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PlanPrice {
+    pub version: u32,
+    pub amount: i128,
+    pub effective_at: u64,
+}
+
+/// Subscription terms a merchant publishes for customers to subscribe to.
+#[derive(Clone)]
+#[contracttype]
+pub struct SubscriptionPlan {
+    pub id: u64,
+    pub merchant: Address,
+    pub token: Address,
+    pub currency: Currency,
+    pub prices: Vec<PlanPrice>, // ordered by effective_at; the first is the launch price
+    pub interval: u64,
+    pub duration: u64,
+    pub trial_period_seconds: u64,
+    pub max_retries: u64,
+    pub metadata: String,
+    pub archived: bool,
+    pub created_at: u64,
+}
+
+/// The terms a subscription moves to in `change_subscription_plan`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum PlanChange {
+    /// A new per-cycle amount; the subscription stops following any plan price.
+    Amount(i128),
+    /// Another plan from the same merchant with the same token and interval.
+    Plan(u64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum PlanChangeTiming {
+    /// Switch now, prorating the rest of the paid cycle.
+    Immediate,
+    /// Switch at the next charge, without proration.
+    NextCycle,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PendingPlanChange {
+    pub change: PlanChange,
+    pub effective_at: u64,
+}
+
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TestError {
@@ -741,6 +950,16 @@ pub struct PaymentRefunded {
     pub amount: i128,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundSettled {
+    pub payment_id: u64,
+    pub refund_contract: Address,
+    pub amount: i128,
+    pub refunded_amount: i128,
+    pub released_from_custody: i128,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PaymentCancelled {
@@ -777,6 +996,61 @@ pub struct PaymentFullyPaid {
     pub completed_at: u64,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceCreated {
+    pub invoice_id: u64,
+    pub merchant: Address,
+    pub customer: Address,
+    pub total: i128,
+    pub due_date: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceIssued {
+    pub invoice_id: u64,
+    pub customer: Address,
+    pub total: i128,
+    pub due_date: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoicePaymentReceived {
+    pub invoice_id: u64,
+    pub payment_id: u64,
+    pub amount: i128,
+    pub late_fee: i128,
+    pub remaining: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoicePaid {
+    pub invoice_id: u64,
+    pub payment_id: u64,
+    pub amount_paid: i128,
+    pub paid_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvoiceVoided {
+    pub invoice_id: u64,
+    pub refunded: i128,
+    pub voided_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaxRuleSet {
+    pub merchant: Option<Address>,
+    pub jurisdiction: Symbol,
+    pub category: BytesN<32>,
+    pub inclusive: bool,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EscrowedPaymentCreated {
@@ -815,1146 +1089,4025 @@ pub struct PaymentDisputeResolved {
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionCreated {
-    pub subscription_id: u64,
+pub struct ChargebackOpened {
+    pub payment_id: u64,
     pub customer: Address,
-    pub merchant: Address,
     pub amount: i128,
-    pub interval: u64,
+    pub held: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RecurringPaymentExecuted {
-    pub subscription_id: u64,
-    pub payment_count: u64,
-    pub amount: i128,
-    pub next_payment_at: u64,
+pub struct ChargebackRepresented {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub evidence_hash: BytesN<32>,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RecurringPaymentFailed {
-    pub subscription_id: u64,
-    pub retry_count: u64,
+pub struct ChargebackResolved {
+    pub payment_id: u64,
+    pub favor_customer: bool,
+    pub refunded: i128,
+    pub merchant_debt: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionCancelled {
-    pub subscription_id: u64,
-    pub cancelled_by: Address,
+pub struct ChargebackDebtRecovered {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub amount: i128,
+    pub outstanding: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RiskFeeApplied {
+pub struct ReserveWithheld {
     pub payment_id: u64,
-    pub base_fee_bps: u32,
-    pub risk_surcharge_bps: u32,
-    pub total_fee_bps: u32,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentChannel {
-    pub channel_id: u64,
-    pub customer: Address,
-    pub merchant: Address,
-    pub token: Address,
-    pub deposited: i128,
-    pub settled: i128,
-    pub settled_nonce: u64,
-    pub open: bool,
-    pub expires_at: u64,
-    pub customer_pk: BytesN<32>,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct MeteredSubscription {
-    pub subscription_id: u64,
     pub merchant: Address,
-    pub customer: Address,
-    pub token: Address,
-    pub price_per_unit: i128,
-    pub unit_name: String,
-    pub accumulated_units: u64,
-    pub billing_cap: Option<i128>,
-    pub last_reset_at: u64,
-    pub max_units_per_period: Option<u64>,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct SplitRecipient {
-    pub address: Address,
-    pub share_bps: u32,
+    pub amount: i128,
+    pub release_at: u64,
 }
 
-#[derive(Clone)]
-#[contracttype]
-pub struct PaymentSplitConfig {
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveReleased {
     pub payment_id: u64,
-    pub recipients: Vec<SplitRecipient>,
-    pub executed: bool,
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-#[contracttype]
-pub struct PaymentForwardConfig {
     pub merchant: Address,
-    pub forward_to: Address,
-    pub forward_bps: u32,
-    pub active: bool,
+    pub amount: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ChannelOpened {
-    pub channel_id: u64,
-    pub customer: Address,
+pub struct ReserveDrawn {
+    pub payment_id: u64,
     pub merchant: Address,
     pub amount: i128,
+    pub to: Address,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ChannelToppedUp {
-    pub channel_id: u64,
-    pub amount: i128,
-    pub new_deposited: i128,
+pub struct SubMerchantRegistered {
+    pub platform: Address,
+    pub sub_merchant: Address,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ChannelSettled {
-    pub channel_id: u64,
-    pub merchant_amount: i128,
-    pub customer_refund: i128,
+pub struct SubMerchantRemoved {
+    pub platform: Address,
+    pub sub_merchant: Address,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ChannelExpiredClosed {
-    pub channel_id: u64,
-    pub refunded_to: Address,
+pub struct PlatformPaymentCreated {
+    pub payment_id: u64,
+    pub platform: Address,
+    pub sub_merchant: Address,
+    pub application_fee: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct UsageReported {
-    pub subscription_id: u64,
-    pub units: u64,
-    pub accumulated: u64,
+pub struct ApplicationFeeCollected {
+    pub payment_id: u64,
+    pub platform: Address,
+    pub amount: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct MeteredBillingExecuted {
-    pub subscription_id: u64,
+pub struct PlatformRefunded {
+    pub payment_id: u64,
+    pub platform: Address,
     pub amount: i128,
-    pub units_billed: u64,
+    pub from_platform: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct BillingCapReached {
+pub struct SubscriptionCreated {
     pub subscription_id: u64,
-    pub cap: i128,
+    pub customer: Address,
+    pub merchant: Address,
+    pub amount: i128,
+    pub interval: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionPaused {
+pub struct RecurringPaymentExecuted {
     pub subscription_id: u64,
+    pub payment_count: u64,
+    pub amount: i128,
+    pub next_payment_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionResumed {
+pub struct RecurringPaymentFailed {
     pub subscription_id: u64,
-    pub next_payment_at: u64,
+    pub retry_count: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionResumedProrated {
+pub struct SubscriptionCancelled {
     pub subscription_id: u64,
-    pub pause_duration: u64,
-    pub new_next_billing_date: u64,
-    pub prorated_amount: i128,
+    pub cancelled_by: Address,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TrialStarted {
-    pub subscription_id: u64,
-    pub trial_ends_at: u64,
+pub struct SubscriptionPlanCreated {
+    pub plan_id: u64,
+    pub merchant: Address,
+    pub price: i128,
+    pub interval: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TrialConverted {
-    pub subscription_id: u64,
-    pub converted_at: u64,
+pub struct PlanPriceScheduled {
+    pub plan_id: u64,
+    pub version: u32,
+    pub amount: i128,
+    pub effective_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TrialCancelled {
-    pub subscription_id: u64,
-    pub cancelled_at: u64,
+pub struct SubscriptionPlanArchived {
+    pub plan_id: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AddressFlagged {
-    pub address: Address,
-    pub reason: String,
+pub struct SubscribedToPlan {
+    pub subscription_id: u64,
+    pub plan_id: u64,
+    pub version: u32,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AddressUnflagged {
-    pub address: Address,
+pub struct SubscriptionUpgraded {
+    pub subscription_id: u64,
+    pub old_amount: i128,
+    pub new_amount: i128,
+    pub prorated_charge: i128,
+    pub effective_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RateLimitBreached {
-    pub address: Address,
-    pub payment_count: u32,
+pub struct SubscriptionDowngraded {
+    pub subscription_id: u64,
+    pub old_amount: i128,
+    pub new_amount: i128,
+    pub prorated_credit: i128,
+    pub effective_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionEnteredDunning {
+pub struct SubscriptionBillingAnchorSet {
     pub subscription_id: u64,
-    pub attempt: u32,
-    pub next_retry_at: u64,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct DunningRetryScheduled {
-    pub subscription_id: u64,
-    pub retry_at: u64,
+    pub anchor: BillingAnchor,
+    pub next_payment_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct SubscriptionSuspended {
+pub struct SubscriptionPriceUpdated {
     pub subscription_id: u64,
-    pub reason: String,
+    pub plan_id: u64,
+    pub version: u32,
+    pub amount: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct DunningResolved {
-    pub subscription_id: u64,
-    pub resolved_at: u64,
+pub struct RiskFeeApplied {
+    pub payment_id: u64,
+    pub base_fee_bps: u32,
+    pub risk_surcharge_bps: u32,
+    pub total_fee_bps: u32,
 }
 
-#[derive(Clone)]
 #[contracttype]
-pub struct RateLimitConfig {
-    pub max_payments_per_window: u32,
-    pub window_duration: u64,
-    pub max_payment_amount: i128,
-    pub max_daily_volume: i128,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentChannel {
+    pub channel_id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub token: Address,
+    pub deposited: i128,
+    pub settled: i128,
+    pub settled_nonce: u64,
+    pub open: bool,
+    pub expires_at: u64,
+    pub customer_key: SignerKey,
 }
 
-#[derive(Clone)]
+/// Public key a channel participant signs off-chain states with.
 #[contracttype]
-pub struct AddressRateLimit {
-    pub address: Address,
-    pub payment_count: u32,
-    pub window_start: u64,
-    pub daily_volume: i128,
-    pub last_payment_at: u64,
-    pub flagged: bool,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignerKey {
+    Ed25519(BytesN<32>),
+    /// WebAuthn passkey, as an uncompressed SEC1 secp256r1 public key.
+    Secp256r1(BytesN<65>),
 }
 
-#[derive(Clone)]
+/// A passkey's WebAuthn assertion over a signed state. The state is bound through
+/// the `challenge` in `client_data_json`, which must be the unpadded base64url
+/// encoding of `sha256(message)`.
 #[contracttype]
-pub struct MerchantRateLimit {
-    pub merchant: Address,
-    pub max_transactions_per_hour: u32,
-    pub max_amount_per_hour: i128,
-    pub current_transactions: u32,
-    pub current_amount: i128,
-    pub window_start: u64,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WebAuthnAssertion {
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    /// `r || s`, with `s` normalized to the low half of the curve order.
+    pub signature: BytesN<64>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Signature over an off-chain state, matching the signer's `SignerKey` type.
 #[contracttype]
-pub enum MerchantVerificationLevel {
-    Unverified,
-    Basic,
-    Standard,
-    Premium,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StateSignature {
+    Ed25519(BytesN<64>),
+    WebAuthn(WebAuthnAssertion),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-pub struct VerificationTierLimits {
-    pub level: MerchantVerificationLevel,
-    pub tx_per_period: u32,
-    pub volume_limit: i128,
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BidirectionalChannelStatus {
+    Open,
+    /// A unilateral close is waiting out the challenge period.
+    Closing,
+    Closed,
 }
 
-#[derive(Clone)]
+/// Payment channel in which either side can pay the other. Every off-chain
+/// state is signed by both parties' keys.
 #[contracttype]
-pub struct DunningConfig {
-    pub initial_backoff_seconds: u64,
-    pub max_retries: u32,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BidirectionalChannel {
+    pub channel_id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub token: Address,
+    pub customer_key: SignerKey,
+    pub merchant_key: SignerKey,
+    pub customer_deposit: i128,
+    pub merchant_deposit: i128,
+    /// Seconds a unilaterally submitted state can be superseded by a newer one.
+    pub challenge_period: u64,
+    pub status: BidirectionalChannelStatus,
+    /// Latest state accepted on-chain; nonce `0` is the opening balances.
+    pub nonce: u64,
+    pub customer_balance: i128,
+    pub merchant_balance: i128,
+    /// When a unilateral close can be finalized; `0` while open.
+    pub close_deadline: u64,
 }
 
-#[derive(Clone)]
+/// Off-chain balance update for a `BidirectionalChannel`. The balances must
+/// always add up to the two deposits.
 #[contracttype]
-pub struct DunningState {
-    pub subscription_id: u64,
-    pub retry_count: u32,
-    pub next_retry_at: u64,
-    pub backoff_seconds: u64,
-    pub max_retries: u32,
-    pub last_failed_at: u64,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelState {
+    pub channel_id: u64,
+    pub nonce: u64,
+    pub customer_balance: i128,
+    pub merchant_balance: i128,
+    /// Set only on the state both parties sign to close the channel; a
+    /// mutual close accepts nothing else.
+    pub is_final: bool,
 }
 
-#[derive(Clone)]
+/// A `ChannelState` with both parties' signatures over
+/// `contract_address.to_xdr() || state.to_xdr()`.
 #[contracttype]
-pub struct PartialPaymentRecord {
-    pub payment_id: u64,
-    pub installment_number: u32,
-    pub amount_paid: i128,
-    pub total_amount: i128,
-    pub remaining: i128,
-    pub paid_at: u64,
-    pub payer: Address,
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedChannelState {
+    pub state: ChannelState,
+    pub customer_signature: StateSignature,
+    pub merchant_signature: StateSignature,
 }
 
-#[derive(Clone)]
+/// A payment the customer authorizes off-chain by signing
+/// `network_id || contract_address.to_xdr() || intent.to_xdr()` with the key
+/// registered through `register_intent_key`: an ed25519 key, or a passkey whose
+/// WebAuthn challenge is the message's hash, as for channel states.
 #[contracttype]
-pub struct Payment {
-    pub id: u64,
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentIntent {
     pub customer: Address,
     pub merchant: Address,
-    pub amount: i128,
     pub token: Address,
+    pub amount: i128,
     pub currency: Currency,
-    pub status: PaymentStatus,
-    pub created_at: u64,
-    pub expires_at: u64,
-    pub metadata: String,
-    pub notes: String,
-    pub refunded_amount: i128,
+    /// Ledger timestamp after which the intent can no longer be executed.
+    pub expiry: u64,
+    pub nonce: u64,
+    pub invoice_hash: Option<BytesN<32>>,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct EscrowedPayment {
-    pub payment_id: u64,
-    pub escrow_id: u64,
-    pub escrow_contract: Address,
-    pub auto_release_on_complete: bool,
+pub struct MeteredSubscription {
+    pub subscription_id: u64,
+    pub merchant: Address,
+    pub customer: Address,
+    pub token: Address,
+    pub price_per_unit: i128,
+    pub unit_name: String,
+    pub accumulated_units: u64,
+    pub billing_cap: Option<i128>,
+    pub last_reset_at: u64,
+    pub max_units_per_period: Option<u64>,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct EscrowedPaymentDispute {
-    pub payment_id: u64,
-    pub raised_by: Address,
-    pub reason: String,
-    pub raised_at: u64,
-    pub resolved: bool,
-    pub resolved_at: Option<u64>,
-    pub favor_customer: Option<bool>,
+pub struct SplitRecipient {
+    pub address: Address,
+    pub share_bps: u32,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct ConditionalPayment {
+pub struct PaymentSplitConfig {
     pub payment_id: u64,
-    pub condition: ConditionType,
-    pub condition_met: bool,
-    pub evaluated_at: Option<u64>,
+    pub recipients: Vec<SplitRecipient>,
+    pub executed: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-pub struct AutoEscrowRule {
+pub struct PaymentForwardConfig {
     pub merchant: Address,
-    pub escrow_bps: u32,
-    pub min_amount: i128,
-    pub token: Address,
+    pub forward_to: Address,
+    pub forward_bps: u32,
     pub active: bool,
-    pub escrow_contract: Address,
 }
 
-#[derive(Clone)]
-#[contracttype]
-pub struct ScheduledPayment {
-    pub payment_id: u64,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelOpened {
+    pub channel_id: u64,
     pub customer: Address,
     pub merchant: Address,
-    pub token: Address,
     pub amount: i128,
-    pub scheduled_at: u64,
-    pub executed: bool,
-    pub cancelled: bool,
 }
 
-#[derive(Clone)]
-#[contracttype]
-pub struct OracleRateConfig {
-    pub oracle_address: Address,
-    pub currency: Currency,
-    pub price_feed_id: BytesN<32>,
-    pub max_staleness_seconds: u64,
-    pub enabled: bool,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelToppedUp {
+    pub channel_id: u64,
+    pub amount: i128,
+    pub new_deposited: i128,
 }
 
-// Dynamic fee calculation structures (#124)
-#[derive(Clone)]
-#[contracttype]
-pub struct RiskFeeConfig {
-    pub base_fee_bps: u32,
-    pub large_amount_threshold: i128,
-    pub large_amount_surcharge_bps: u32,
-    pub new_customer_surcharge_bps: u32,
-    pub high_risk_currency_surcharge: u32,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelSettled {
+    pub channel_id: u64,
+    pub merchant_amount: i128,
+    pub customer_refund: i128,
 }
 
-#[derive(Clone)]
-#[contracttype]
-pub struct AnalyticsBucket {
-    pub bucket_start: u64,
-    pub bucket_end: u64,
-    pub total_payments: u64,
-    pub total_volume: i128,
-    pub total_refunds: i128,
-    pub failed_count: u64,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChannelExpiredClosed {
+    pub channel_id: u64,
+    pub refunded_to: Address,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-#[contracttype]
-pub struct LoyaltyConfig {
-    pub points_per_unit: u32,
-    pub redemption_rate: u32,
-    pub expiry_seconds: u64,
-    pub active: bool,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwapVenueRegistered {
+    pub venue_id: u32,
+    pub adapter: Address,
+    pub token_a: Address,
+    pub token_b: Address,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-#[contracttype]
-pub struct CustomerLoyaltyBalance {
-    pub customer: Address,
-    pub points: u64,
-    pub last_updated: u64,
-    pub expires_at: u64,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoutedPaymentExecuted {
+    pub payment_id: u64,
+    pub input_token: Address,
+    pub output_token: Address,
+    pub input_amount: i128,
+    pub output_amount: i128,
+    pub hops: u32,
 }
 
+#[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-#[contracttype]
-pub enum ActionType {
-    ReleaseEscrow,
-    ResolveDispute,
-    CompletePayment,
-    RefundPayment,
-    AddAdmin,
-    RemoveAdmin,
-    UpdateRequiredSignatures,
+pub struct SettlementPreferenceSet {
+    pub merchant: Address,
+    pub token: Address,
+    pub max_slippage_bps: u32,
 }
 
-#[derive(Clone)]
-#[contracttype]
-pub struct MultiSigConfig {
-    pub admins: Vec<Address>,
-    pub required_signatures: u32,
-    pub total_admins: u32,
-    pub proposal_ttl: u64,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct AdminProposal {
-    pub id: String,
-    pub proposer: Address,
-    pub action_type: ActionType,
-    pub target: Address,
-    pub data: Bytes,
-    pub approvals: Vec<Address>,
-    pub approval_count: u32,
-    pub executed: bool,
-    pub rejected: bool,
-    pub created_at: u64,
-    pub expires_at: u64,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct LargePaymentProposal {
-    pub payment_id: u64,
-    pub approvals: Vec<Address>,
-    pub required: u32,
-    pub proposed_at: u64,
-    pub expires_at: u64,
-    pub executed: bool,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct BatchPaymentEntry {
-    pub customer: Address,
-    pub merchant: Address,
-    pub amount: i128,
-    pub token: Address,
-    pub currency: Currency,
-    pub expiration_duration: u64,
-    pub metadata: String,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct BatchResult {
-    pub payment_id: u64,
-    pub success: bool,
-    pub error_code: Option<u32>,
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-#[contracttype]
-pub enum FeeTier {
-    Standard,
-    Premium,    // >= configured premium volume
-    Enterprise, // >= configured enterprise volume
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct FeeConfig {
-    pub fee_bps: u32,
-    pub min_fee: i128,
-    pub max_fee: i128,
-    pub treasury: Address,
-    pub fee_token: Address,
-    pub active: bool,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct MerchantFeeRecord {
-    pub merchant: Address,
-    pub total_fees_paid: i128,
-    pub total_volume: i128,
-    pub fee_tier: FeeTier,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct FeeWaiver {
-    pub merchant: Address,
-    pub waiver_bps: u32, // reduction in basis points
-    pub valid_until: u64,
-    pub reason: String,
-    pub granted_by: Address,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct FeeRebateConfig {
-    pub threshold_volume: i128,
-    pub rebate_bps: u32,
-    pub rebate_period_seconds: u64,
-    pub active: bool,
-}
-
-#[derive(Clone)]
-#[contracttype]
-pub struct MerchantRebateAccrual {
-    pub merchant: Address,
-    pub accrued_rebate: i128,
-    pub period_start: u64,
-    pub period_volume: i128,
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementConverted {
+    pub payee: Address,
+    pub from_token: Address,
+    pub to_token: Address,
+    pub amount_in: i128,
+    pub amount_out: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ActionProposed {
-    pub proposal_id: String,
-    pub proposer: Address,
-    pub action_type: ActionType,
+pub struct SettlementConversionFailed {
+    pub payee: Address,
+    pub token: Address,
+    pub amount: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ActionApproved {
-    pub proposal_id: String,
-    pub approver: Address,
-    pub approval_count: u32,
+pub struct IntentKeyRegistered {
+    pub customer: Address,
+    pub key: SignerKey,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ActionExecuted {
-    pub proposal_id: String,
+pub struct PaymentIntentExecuted {
+    pub payment_id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub nonce: u64,
+    pub invoice_hash: Option<BytesN<32>>,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ActionRejected {
-    pub proposal_id: String,
-    pub rejected_by: Address,
+pub struct PaymentIntentCancelled {
+    pub customer: Address,
+    pub nonce: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AdminAdded {
-    pub admin: Address,
+pub struct BidirectionalChannelOpened {
+    pub channel_id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub customer_deposit: i128,
+    pub merchant_deposit: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AdminRemoved {
-    pub admin: Address,
+pub struct ChannelCloseInitiated {
+    pub channel_id: u64,
+    pub initiated_by: Address,
+    pub nonce: u64,
+    pub close_deadline: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeeCollected {
-    pub payment_id: u64,
-    pub fee_amount: i128,
-    pub merchant: Address,
+pub struct ChannelStateChallenged {
+    pub channel_id: u64,
+    pub nonce: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeesWithdrawn {
-    pub amount: i128,
-    pub treasury: Address,
+pub struct BidirectionalChannelClosed {
+    pub channel_id: u64,
+    pub nonce: u64,
+    pub customer_amount: i128,
+    pub merchant_amount: i128,
+    pub mutual: bool,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct MerchantTierUpgraded {
-    pub merchant: Address,
-    pub old_tier: FeeTier,
-    pub new_tier: FeeTier,
+pub struct UsageReported {
+    pub subscription_id: u64,
+    pub units: u64,
+    pub accumulated: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeeWaiverGranted {
-    pub merchant: Address,
-    pub waiver_bps: u32,
-    pub valid_until: u64,
+pub struct MeteredBillingExecuted {
+    pub subscription_id: u64,
+    pub amount: i128,
+    pub units_billed: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeeWaiverRevoked {
-    pub merchant: Address,
-    pub revoked_by: Address,
+pub struct BillingCapReached {
+    pub subscription_id: u64,
+    pub cap: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeeWaiverExpired {
-    pub merchant: Address,
+pub struct SubscriptionPaused {
+    pub subscription_id: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ConditionEvaluated {
-    pub payment_id: u64,
-    pub met: bool,
-    pub evaluated_at: u64,
+pub struct SubscriptionResumed {
+    pub subscription_id: u64,
+    pub next_payment_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ConditionalPaymentCreated {
-    pub payment_id: u64,
-    pub condition_type: String,
+pub struct SubscriptionResumedProrated {
+    pub subscription_id: u64,
+    pub pause_duration: u64,
+    pub new_next_billing_date: u64,
+    pub prorated_amount: i128,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FeeConfigUpdated {
-    pub fee_bps: u32,
-    pub treasury: Address,
+pub struct TrialStarted {
+    pub subscription_id: u64,
+    pub trial_ends_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ContractPausedEvent {
-    pub paused_by: Address,
-    pub reason: String,
-    pub paused_at: u64,
+pub struct TrialConverted {
+    pub subscription_id: u64,
+    pub converted_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ContractUnpausedEvent {
-    pub unpaused_by: Address,
-    pub unpaused_at: u64,
+pub struct TrialCancelled {
+    pub subscription_id: u64,
+    pub cancelled_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FunctionPausedEvent {
-    pub function_name: String,
-    pub paused_by: Address,
+pub struct AddressFlagged {
+    pub address: Address,
     pub reason: String,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct FunctionUnpausedEvent {
-    pub function_name: String,
-    pub unpaused_by: Address,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct AutoEscrowTriggered {
-    pub payment_id: u64,
-    pub merchant: Address,
-    pub escrow_id: u64,
-    pub amount: i128,
-    pub escrow_amount: i128,
+pub struct AddressUnflagged {
+    pub address: Address,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LargePaymentProposed {
-    pub payment_id: u64,
-    pub proposer: Address,
-    pub required_approvals: u32,
-    pub expires_at: u64,
+pub struct RateLimitBreached {
+    pub address: Address,
+    pub payment_count: u32,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LargePaymentApproved {
-    pub payment_id: u64,
-    pub approver: Address,
-    pub approval_count: u32,
+pub struct SubscriptionEnteredDunning {
+    pub subscription_id: u64,
+    pub attempt: u32,
+    pub next_retry_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LargePaymentExecuted {
-    pub payment_id: u64,
+pub struct DunningRetryScheduled {
+    pub subscription_id: u64,
+    pub retry_at: u64,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LargePaymentThresholdUpdated {
-    pub threshold: i128,
-    pub updated_by: Address,
+pub struct SubscriptionSuspended {
+    pub subscription_id: u64,
+    pub reason: String,
 }
 
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentMetadataSet {
-    pub payment_id: u64,
-    pub content_ref: String,
-    pub encrypted: bool,
-    pub set_by: Address,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentMetadataUpdated {
-    pub payment_id: u64,
-    pub content_ref: String,
-    pub updated_by: Address,
-    pub version: u32,
+pub struct DunningResolved {
+    pub subscription_id: u64,
+    pub resolved_at: u64,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct PaymentMetadata {
-    pub payment_id: u64,
-    pub content_ref: String,      // IPFS CID or similar
-    pub content_hash: BytesN<32>, // SHA-256 of plaintext for verification
-    pub encrypted: bool,
-    pub updated_at: u64,
-    pub version: u32,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentMemoSet {
-    pub payment_id: u64,
-    pub memo_ref: String,
-    pub set_by: Address,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentMemoUpdated {
-    pub payment_id: u64,
-    pub memo_ref: String,
-    pub updated_by: Address,
-    pub version: u32,
-}
-
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentMemoVerified {
-    pub payment_id: u64,
-    pub memo_hash: BytesN<32>,
-    pub verified_at: u64,
-    pub verified_by: Address,
+pub struct RateLimitConfig {
+    pub max_payments_per_window: u32,
+    pub window_duration: u64,
+    pub max_payment_amount: i128,
+    pub max_daily_volume: i128,
 }
 
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentForwardConfigSet {
-    pub merchant: Address,
-    pub forward_to: Address,
-    pub forward_bps: u32,
+#[derive(Clone)]
+#[contracttype]
+pub struct AddressRateLimit {
+    pub address: Address,
+    pub payment_count: u32,
+    pub window_start: u64,
+    pub daily_volume: i128,
+    pub last_payment_at: u64,
+    pub flagged: bool,
 }
 
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentForwardConfigRemoved {
+#[derive(Clone)]
+#[contracttype]
+pub struct MerchantRateLimit {
     pub merchant: Address,
+    pub max_transactions_per_hour: u32,
+    pub max_amount_per_hour: i128,
+    pub current_transactions: u32,
+    pub current_amount: i128,
+    pub window_start: u64,
 }
 
-#[contractevent]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PaymentForwarded {
-    pub payment_id: u64,
-    pub merchant: Address,
-    pub forward_to: Address,
-    pub forward_amount: i128,
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum MerchantVerificationLevel {
+    Unverified,
+    Basic,
+    Standard,
+    Premium,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-pub struct PaymentMemo {
-    pub payment_id: u64,
-    pub memo_ref: String,      // Reference to memo content (IPFS CID, URL, etc.)
-    pub memo_hash: BytesN<32>, // SHA-256 hash of memo plaintext (immutable)
-    pub reference_hash: BytesN<32>, // Hash linking memo to payment (for integrity)
-    pub created_at: u64,
-    pub updated_at: u64,
-    pub version: u32,
-    pub created_by: Address,
+pub struct VerificationTierLimits {
+    pub level: MerchantVerificationLevel,
+    pub tx_per_period: u32,
+    pub volume_limit: i128,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct PaymentAnalytics {
-    pub total_payments_created: u64,
-    pub total_payments_completed: u64,
-    pub total_payments_cancelled: u64,
-    pub total_payments_refunded: u64,
-    pub total_volume: i128,
-    pub total_refunded_volume: i128,
-    pub unique_customers: u64,
-    pub unique_merchants: u64,
+pub struct DunningConfig {
+    pub initial_backoff_seconds: u64,
+    pub max_retries: u32,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct MerchantAnalytics {
-    pub total_payments: u64,
-    pub total_volume: i128,
-    pub total_completed: u64,
-    pub total_cancelled: u64,
-    pub total_refunded: u64,
-    pub total_refunded_volume: i128,
+pub struct DunningState {
+    pub subscription_id: u64,
+    pub retry_count: u32,
+    pub next_retry_at: u64,
+    pub backoff_seconds: u64,
+    pub max_retries: u32,
+    pub last_failed_at: u64,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct CustomerAnalytics {
-    pub total_payments: u64,
-    pub total_volume: i128,
-    pub total_refunds: i128,
-    pub avg_transaction_size: i128,
-    pub peak_hour: u32,
-    pub top_merchant: Option<Address>,
-    pub top_merchant_volume: i128,
-    pub first_payment_at: u64,
-    pub last_payment_at: u64,
+pub struct PartialPaymentRecord {
+    pub payment_id: u64,
+    pub installment_number: u32,
+    pub amount_paid: i128,
+    pub total_amount: i128,
+    pub remaining: i128,
+    pub paid_at: u64,
+    pub payer: Address,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct PauseState {
-    pub globally_paused: bool,
-    pub paused_functions: Vec<String>,
-    pub paused_at: u64,
-    pub paused_by: Address,
-    pub pause_reason: String,
+pub struct Payment {
+    pub id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub currency: Currency,
+    pub status: PaymentStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub metadata: String,
+    pub notes: String,
+    pub refunded_amount: i128,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct PauseHistory {
-    pub index: u64,
-    pub function_name: String,
-    pub paused: bool,
-    pub changed_by: Address,
-    pub changed_at: u64,
-    pub reason: String,
+pub struct EscrowedPayment {
+    pub payment_id: u64,
+    pub escrow_id: u64,
+    pub escrow_contract: Address,
+    pub auto_release_on_complete: bool,
 }
 
+pub const DEFAULT_CHARGEBACK_WINDOW: u64 = 120 * SECONDS_PER_DAY;
+pub const CHARGEBACK_RESPONSE_PERIOD: u64 = 14 * SECONDS_PER_DAY;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[contracttype]
-#[derive(Clone)]
-pub struct FeeSweepRecord {
-    pub sweep_id: u64,
-    pub amount: i128,
-    pub token: Address,
-    pub recipient: Address,
-    pub swept_at: u64,
+pub enum ChargebackStatus {
+    Open,
+    Represented,
+    CustomerWon,
+    MerchantWon,
 }
 
+/// A customer dispute on a completed payment. The disputed amount is held back
+/// from the payment's pending settlement first, then from the merchant's
+/// accumulated payout balance; `held_from_settlement` and `held_from_balance`
+/// record where it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-#[derive(Clone)]
-pub struct CustomerSpendLimit {
+pub struct Chargeback {
+    pub payment_id: u64,
     pub customer: Address,
-    pub limit_amount: i128,
-    pub period_seconds: u64,
-    pub used: i128,
-    pub period_start: u64,
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub reason: String,
+    pub status: ChargebackStatus,
+    pub opened_at: u64,
+    pub evidence_hash: Option<BytesN<32>>,
+    pub held_from_settlement: i128,
+    pub held_from_balance: i128,
+    pub resolved_at: u64,
 }
 
+/// The part of a lost chargeback that could not be covered by held funds. It
+/// is recovered from the merchant's later settlements in `token`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-#[derive(Clone)]
-pub struct SubscriptionGroup {
-    pub group_id: u64,
-    pub owner: Address,
-    pub subscription_ids: Vec<u64>,
-    pub discount_bps: u32,
-    pub active: bool,
+pub struct ChargebackDebt {
+    pub payment_id: u64,
+    pub customer: Address,
+    pub token: Address,
+    pub amount: i128,
 }
 
+/// Rolling reserve parameters for a verification tier. Each completed payment
+/// withholds `reserve_bps` of the merchant's settlement, plus
+/// `risk_weight_pct` percent of the payment's `calculate_risk_score`, for
+/// `hold_seconds` before it is released.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-#[derive(Clone)]
-pub struct FinalityConfig {
-    pub delay_seconds: u64,
-    pub min_amount_threshold: i128,
-    pub active: bool,
+pub struct ReserveTier {
+    pub level: MerchantVerificationLevel,
+    pub reserve_bps: u32,
+    pub risk_weight_pct: u32,
+    pub hold_seconds: u64,
 }
 
+/// One payment's slice of a merchant's rolling reserve. `amount` shrinks as
+/// the reserve is drawn on to cover refunds and chargebacks.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-#[derive(Clone)]
-pub struct PendingSettlement {
+pub struct ReserveEntry {
     pub payment_id: u64,
-    pub merchant: Address,
-    pub amount: i128,
     pub token: Address,
+    pub amount: i128,
     pub release_at: u64,
 }
 
-// Issue #118: Payment routing optimization
-#[derive(Clone)]
+/// A marketplace platform's application fee: `fixed` plus `bps` of the payment amount.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-pub struct RouteOption {
-    pub input_token: Address,
-    pub output_token: Address,
-    pub input_amount: i128,
-    pub output_amount: i128,
-    pub fee_bps: u32,
-    pub effective_cost: i128,
+pub struct ApplicationFee {
+    pub fixed: i128,
+    pub bps: u32,
 }
 
-// Issue #210: Payment tagging system
-#[derive(Clone)]
+/// A merchant onboarded by a marketplace platform.
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
-pub struct PaymentTagSet {
+pub struct SubMerchant {
+    pub platform: Address,
+    pub sub_merchant: Address,
+    pub registered_at: u64,
+}
+
+/// The marketplace side of a payment created with `create_platform_payment`.
+/// The payment's `merchant` is the sub-merchant; `application_fee` is routed to
+/// `platform` when it completes and `fee_collected` records what was paid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct PlatformPayment {
     pub payment_id: u64,
-    pub tags: Vec<BytesN<32>>,
+    pub platform: Address,
+    pub application_fee: i128,
+    pub fee_collected: i128,
 }
 
-// Issue #205: Invoice-based payment with line items
 #[derive(Clone)]
 #[contracttype]
-pub struct LineItem {
-    pub description_hash: BytesN<32>,
-    pub quantity: u32,
-    pub unit_price: i128,
-    pub amount: i128,
+pub struct EscrowedPaymentDispute {
+    pub payment_id: u64,
+    pub raised_by: Address,
+    pub reason: String,
+    pub raised_at: u64,
+    pub resolved: bool,
+    pub resolved_at: Option<u64>,
+    pub favor_customer: Option<bool>,
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct PaymentInvoice {
-    pub invoice_id: u64,
+pub struct ConditionalPayment {
     pub payment_id: u64,
-    pub items: Vec<LineItem>,
-    pub subtotal: i128,
-    pub tax: i128,
-    pub total: i128,
-    pub issued_at: u64,
+    pub condition: ConditionType,
+    pub condition_met: bool,
+    pub evaluated_at: Option<u64>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AutoEscrowRule {
+    pub merchant: Address,
+    pub escrow_bps: u32,
+    pub min_amount: i128,
+    pub token: Address,
+    pub active: bool,
+    pub escrow_contract: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ScheduledPayment {
+    pub payment_id: u64,
+    pub customer: Address,
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub scheduled_at: u64,
+    pub executed: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct OracleRateConfig {
+    pub oracle_address: Address,
+    pub currency: Currency,
+    pub price_feed_id: BytesN<32>,
+    pub max_staleness_seconds: u64,
+    pub enabled: bool,
+}
+
+// Dynamic fee calculation structures (#124)
+#[derive(Clone)]
+#[contracttype]
+pub struct RiskFeeConfig {
+    pub base_fee_bps: u32,
+    pub large_amount_threshold: i128,
+    pub large_amount_surcharge_bps: u32,
+    pub new_customer_surcharge_bps: u32,
+    pub high_risk_currency_surcharge: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AnalyticsBucket {
+    pub bucket_start: u64,
+    pub bucket_end: u64,
+    pub total_payments: u64,
+    pub total_volume: i128,
+    pub total_refunds: i128,
+    pub failed_count: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct LoyaltyConfig {
+    pub points_per_unit: u32,
+    pub redemption_rate: u32,
+    pub expiry_seconds: u64,
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct CustomerLoyaltyBalance {
+    pub customer: Address,
+    pub points: u64,
+    pub last_updated: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum CampaignReward {
+    Cashback,
+    Points,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum CampaignStatus {
+    Active,
+    Exhausted,
+    Closed,
+}
+
+/// The rules of a merchant campaign. A qualifying payment in `token` between
+/// `starts_at` and `ends_at` of at least `min_amount` earns `fixed_reward` plus
+/// `reward_bps` of its amount, up to `per_customer_cap` per customer (0 for no
+/// cap). `first_purchase_only` limits it to a customer's first purchase from
+/// the merchant. Rewards are token units for `Cashback` and points for `Points`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct CampaignTerms {
+    pub token: Address,
+    pub reward: CampaignReward,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub reward_bps: u32,
+    pub fixed_reward: i128,
+    pub min_amount: i128,
+    pub per_customer_cap: i128,
+    pub first_purchase_only: bool,
+}
+
+/// A merchant campaign and its remaining budget, in the campaign's reward unit.
+/// Cashback budgets are held by the contract in the campaign token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Campaign {
+    pub id: u64,
+    pub merchant: Address,
+    pub terms: CampaignTerms,
+    pub status: CampaignStatus,
+    pub budget: i128,
+    pub total_funded: i128,
+    pub total_rewarded: i128,
+}
+
+/// Cashback a campaign awarded on a payment, held by the contract until the
+/// payment's chargeback window has closed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct HeldCashback {
+    pub campaign_id: u64,
+    pub amount: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ActionType {
+    ReleaseEscrow,
+    ResolveDispute,
+    CompletePayment,
+    RefundPayment,
+    AddAdmin,
+    RemoveAdmin,
+    UpdateRequiredSignatures,
+    UpgradeContract(BytesN<32>),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MultiSigConfig {
+    pub admins: Vec<Address>,
+    pub required_signatures: u32,
+    pub total_admins: u32,
+    pub proposal_ttl: u64,
 }
 
-#[contract]
-pub struct PaymentContract;
+#[derive(Clone)]
+#[contracttype]
+pub struct AdminProposal {
+    pub id: String,
+    pub proposer: Address,
+    pub action_type: ActionType,
+    pub target: Address,
+    pub data: Bytes,
+    pub approvals: Vec<Address>,
+    pub approval_count: u32,
+    pub executed: bool,
+    pub rejected: bool,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct LargePaymentProposal {
+    pub payment_id: u64,
+    pub approvals: Vec<Address>,
+    pub required: u32,
+    pub proposed_at: u64,
+    pub expires_at: u64,
+    pub executed: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BatchPaymentEntry {
+    pub customer: Address,
+    pub merchant: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub currency: Currency,
+    pub expiration_duration: u64,
+    pub metadata: String,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BatchResult {
+    pub payment_id: u64,
+    pub success: bool,
+    pub error_code: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum FeeTier {
+    Standard,
+    Premium,    // >= configured premium volume
+    Enterprise, // >= configured enterprise volume
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeConfig {
+    pub fee_bps: u32,
+    pub min_fee: i128,
+    pub max_fee: i128,
+    pub treasury: Address,
+    pub fee_token: Address,
+    pub active: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MerchantFeeRecord {
+    pub merchant: Address,
+    pub total_fees_paid: i128,
+    pub total_volume: i128,
+    pub fee_tier: FeeTier,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeWaiver {
+    pub merchant: Address,
+    pub waiver_bps: u32, // reduction in basis points
+    pub valid_until: u64,
+    pub reason: String,
+    pub granted_by: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeRebateConfig {
+    pub threshold_volume: i128,
+    pub rebate_bps: u32,
+    pub rebate_period_seconds: u64,
+    pub active: bool,
+}
+
+/// Referral programme settings. A referrer earns `commission_bps` of the
+/// platform fee on each payment made by or to a referee, for `duration_seconds`
+/// after the referee was attached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ReferralConfig {
+    pub commission_bps: u32,
+    pub duration_seconds: u64,
+    pub active: bool,
+}
+
+/// Links a referee (a merchant or customer) to the referrer who onboarded them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct Referral {
+    pub referrer: Address,
+    pub referee: Address,
+    pub attached_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct ReferrerAnalytics {
+    pub total_referrals: u32,
+    pub commissioned_payments: u64,
+    pub total_earned: i128,
+    pub total_claimed: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MerchantRebateAccrual {
+    pub merchant: Address,
+    pub accrued_rebate: i128,
+    pub period_start: u64,
+    pub period_volume: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionProposed {
+    pub proposal_id: String,
+    pub proposer: Address,
+    pub action_type: ActionType,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionApproved {
+    pub proposal_id: String,
+    pub approver: Address,
+    pub approval_count: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionExecuted {
+    pub proposal_id: String,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionRejected {
+    pub proposal_id: String,
+    pub rejected_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminAdded {
+    pub admin: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUpgraded {
+    pub previous_wasm_hash: Option<BytesN<32>>,
+    pub new_wasm_hash: BytesN<32>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminRemoved {
+    pub admin: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeCollected {
+    pub payment_id: u64,
+    pub fee_amount: i128,
+    pub merchant: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesWithdrawn {
+    pub amount: i128,
+    pub treasury: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralAttached {
+    pub referee: Address,
+    pub referrer: Address,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralCommissionAccrued {
+    pub payment_id: u64,
+    pub referrer: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralCommissionClaimed {
+    pub referrer: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletDeposited {
+    pub customer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletWithdrawn {
+    pub customer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalletDebited {
+    pub customer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub to: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegateGranted {
+    pub customer: Address,
+    pub delegate: Address,
+    pub period_cap: i128,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegateRevoked {
+    pub customer: Address,
+    pub delegate: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegatedPaymentCreated {
+    pub payment_id: u64,
+    pub customer: Address,
+    pub delegate: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignCreated {
+    pub campaign_id: u64,
+    pub merchant: Address,
+    pub budget: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignFunded {
+    pub campaign_id: u64,
+    pub amount: i128,
+    pub budget: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignRewardPaid {
+    pub campaign_id: u64,
+    pub payment_id: u64,
+    pub customer: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignCashbackHeld {
+    pub campaign_id: u64,
+    pub payment_id: u64,
+    pub customer: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignCashbackForfeited {
+    pub campaign_id: u64,
+    pub payment_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignExhausted {
+    pub campaign_id: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CampaignBudgetReclaimed {
+    pub campaign_id: u64,
+    pub merchant: Address,
+    pub amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MerchantTierUpgraded {
+    pub merchant: Address,
+    pub old_tier: FeeTier,
+    pub new_tier: FeeTier,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeWaiverGranted {
+    pub merchant: Address,
+    pub waiver_bps: u32,
+    pub valid_until: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeWaiverRevoked {
+    pub merchant: Address,
+    pub revoked_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeWaiverExpired {
+    pub merchant: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionEvaluated {
+    pub payment_id: u64,
+    pub met: bool,
+    pub evaluated_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConditionalPaymentCreated {
+    pub payment_id: u64,
+    pub condition_type: String,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeConfigUpdated {
+    pub fee_bps: u32,
+    pub treasury: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractPausedEvent {
+    pub paused_by: Address,
+    pub reason: String,
+    pub paused_at: u64,
+    pub paused_until: Option<u64>,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUnpausedEvent {
+    pub unpaused_by: Address,
+    pub unpaused_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionPausedEvent {
+    pub function_name: String,
+    pub paused_by: Address,
+    pub reason: String,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionUnpausedEvent {
+    pub function_name: String,
+    pub unpaused_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoEscrowTriggered {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub escrow_id: u64,
+    pub amount: i128,
+    pub escrow_amount: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargePaymentProposed {
+    pub payment_id: u64,
+    pub proposer: Address,
+    pub required_approvals: u32,
+    pub expires_at: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargePaymentApproved {
+    pub payment_id: u64,
+    pub approver: Address,
+    pub approval_count: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargePaymentExecuted {
+    pub payment_id: u64,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LargePaymentThresholdUpdated {
+    pub threshold: i128,
+    pub updated_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMetadataSet {
+    pub payment_id: u64,
+    pub content_ref: String,
+    pub encrypted: bool,
+    pub set_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMetadataUpdated {
+    pub payment_id: u64,
+    pub content_ref: String,
+    pub updated_by: Address,
+    pub version: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PaymentMetadata {
+    pub payment_id: u64,
+    pub content_ref: String,      // IPFS CID or similar
+    pub content_hash: BytesN<32>, // SHA-256 of plaintext for verification
+    pub encrypted: bool,
+    pub updated_at: u64,
+    pub version: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMemoSet {
+    pub payment_id: u64,
+    pub memo_ref: String,
+    pub set_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMemoUpdated {
+    pub payment_id: u64,
+    pub memo_ref: String,
+    pub updated_by: Address,
+    pub version: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMemoVerified {
+    pub payment_id: u64,
+    pub memo_hash: BytesN<32>,
+    pub verified_at: u64,
+    pub verified_by: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentForwardConfigSet {
+    pub merchant: Address,
+    pub forward_to: Address,
+    pub forward_bps: u32,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentForwardConfigRemoved {
+    pub merchant: Address,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentForwarded {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub forward_to: Address,
+    pub forward_amount: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PaymentMemo {
+    pub payment_id: u64,
+    pub memo_ref: String,      // Reference to memo content (IPFS CID, URL, etc.)
+    pub memo_hash: BytesN<32>, // SHA-256 hash of memo plaintext (immutable)
+    pub reference_hash: BytesN<32>, // Hash linking memo to payment (for integrity)
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub version: u32,
+    pub created_by: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PaymentAnalytics {
+    pub total_payments_created: u64,
+    pub total_payments_completed: u64,
+    pub total_payments_cancelled: u64,
+    pub total_payments_refunded: u64,
+    pub total_volume: i128,
+    pub total_refunded_volume: i128,
+    pub unique_customers: u64,
+    pub unique_merchants: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MerchantAnalytics {
+    pub total_payments: u64,
+    pub total_volume: i128,
+    pub total_completed: u64,
+    pub total_cancelled: u64,
+    pub total_refunded: u64,
+    pub total_refunded_volume: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CustomerAnalytics {
+    pub total_payments: u64,
+    pub total_volume: i128,
+    pub total_refunds: i128,
+    pub avg_transaction_size: i128,
+    pub peak_hour: u32,
+    pub top_merchant: Option<Address>,
+    pub top_merchant_volume: i128,
+    pub first_payment_at: u64,
+    pub last_payment_at: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PauseState {
+    pub globally_paused: bool,
+    pub paused_functions: Vec<String>,
+    pub paused_at: u64,
+    pub paused_by: Address,
+    pub pause_reason: String,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PauseHistory {
+    pub index: u64,
+    pub function_name: String,
+    pub paused: bool,
+    pub changed_by: Address,
+    pub changed_at: u64,
+    pub reason: String,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FeeSweepRecord {
+    pub sweep_id: u64,
+    pub amount: i128,
+    pub token: Address,
+    pub recipient: Address,
+    pub swept_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct CustomerSpendLimit {
+    pub customer: Address,
+    pub limit_amount: i128,
+    pub period_seconds: u64,
+    pub used: i128,
+    pub period_start: u64,
+}
+
+/// What a delegate may spend on a customer's behalf. Empty `merchants`,
+/// `categories` or `tokens` lists allow any; `expires_at` of 0 never expires.
+/// `categories` are matched against the category an admin registered for the
+/// merchant with `set_merchant_category`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegateScope {
+    pub period_cap: i128,
+    pub period_seconds: u64,
+    pub merchants: Vec<Address>,
+    pub categories: Vec<BytesN<32>>,
+    pub tokens: Vec<Address>,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpendDelegation {
+    pub customer: Address,
+    pub delegate: Address,
+    pub scope: DelegateScope,
+    pub granted_at: u64,
+    pub period_start: u64,
+    pub spent_in_period: i128,
+    pub total_spent: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelegateSpend {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub category: Option<BytesN<32>>,
+    pub spent_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SubscriptionGroup {
+    pub group_id: u64,
+    pub owner: Address,
+    pub subscription_ids: Vec<u64>,
+    pub discount_bps: u32,
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FinalityConfig {
+    pub delay_seconds: u64,
+    pub min_amount_threshold: i128,
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PendingSettlement {
+    pub payment_id: u64,
+    pub merchant: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub release_at: u64,
+}
+
+// Issue #118: Payment routing optimization
+/// A liquidity venue that converts between `token_a` and `token_b`.
+///
+/// `adapter` is a contract exposing
+/// `swap(sender, token_in, token_out, amount_in, min_amount_out) -> i128`.
+/// It pulls `amount_in` from `sender` with `transfer_from` (the payment contract
+/// approves it just before the call), sends the output back to `sender`, and
+/// fails if the output would be below `min_amount_out`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SwapVenue {
+    pub venue_id: u32,
+    pub adapter: Address,
+    pub token_a: Address,
+    pub currency_a: Currency,
+    pub token_b: Address,
+    pub currency_b: Currency,
+    pub fee_bps: u32,
+    pub active: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RouteHop {
+    pub venue_id: u32,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+/// A candidate path from `input_token` to `output_token`. `output_amount` is
+/// estimated from conversion rates; `effective_cost` is the output lost to
+/// venue fees. An empty `hops` list means the tokens are the same.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RouteOption {
+    pub input_token: Address,
+    pub output_token: Address,
+    pub input_amount: i128,
+    pub output_amount: i128,
+    pub fee_bps: u32,
+    pub effective_cost: i128,
+    pub hops: Vec<RouteHop>,
+}
+
+// Issue #210: Payment tagging system
+#[derive(Clone)]
+#[contracttype]
+pub struct PaymentTagSet {
+    pub payment_id: u64,
+    pub tags: Vec<BytesN<32>>,
+}
+
+// Issue #205: Invoice-based payment with line items
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LineItem {
+    pub description_hash: BytesN<32>,
+    pub quantity: u32,
+    pub unit_price: i128,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PaymentInvoice {
+    pub invoice_id: u64,
+    pub payment_id: u64,
+    pub items: Vec<LineItem>,
+    pub subtotal: i128,
+    pub tax: i128,
+    pub total: i128,
+    pub issued_at: u64,
+}
+
+/// Lifecycle of a standalone invoice. `Overdue` is never stored: an `Open`
+/// invoice reads as `Overdue` once its due date has passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum InvoiceStatus {
+    Draft,
+    Open,
+    Paid,
+    Overdue,
+    Void,
+}
+
+/// Late fee accrued on an unpaid invoice: `bps_per_period` of the invoice
+/// total for every started `period_seconds` past the due date, capped at
+/// `max_fee` (0 for no cap). A zero `bps_per_period` disables late fees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct LateFeePolicy {
+    pub bps_per_period: u32,
+    pub period_seconds: u64,
+    pub max_fee: i128,
+}
+
+/// A merchant-issued invoice settled by the customer via `pay_invoice`.
+/// `payment_id` is 0 until the first payment creates the backing payment.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct Invoice {
+    pub id: u64,
+    pub merchant: Address,
+    pub customer: Address,
+    pub currency: Currency,
+    pub items: Vec<LineItem>,
+    pub subtotal: i128,
+    pub tax: i128,
+    pub total: i128,
+    pub accepted_tokens: Vec<Address>,
+    pub due_date: u64,
+    pub late_fee: LateFeePolicy,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+    pub issued_at: u64,
+    pub payment_id: u64,
+    pub late_fee_charged: i128,
+    pub paid_at: u64,
+}
+
+pub const MAX_TAX_RATES: u32 = 4;
+
+/// One tax in a `TaxRule`. A compounding rate is charged on the line amount
+/// plus the taxes before it in the rule; otherwise on the line amount alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct TaxRate {
+    pub rate_bps: u32,
+    pub compound: bool,
+}
+
+/// Taxes applied, in order, to line items of one product category in one
+/// jurisdiction. Inclusive rules treat the line amount as already containing
+/// the tax; exclusive rules add the tax on top. An empty `rates` list marks the
+/// category as exempt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct TaxRule {
+    pub inclusive: bool,
+    pub rates: Vec<TaxRate>,
+}
+
+/// The jurisdiction and per-line product categories `issue_invoice` computes
+/// an invoice's tax from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct InvoiceTaxContext {
+    pub jurisdiction: Symbol,
+    pub categories: Vec<BytesN<32>>,
+}
+
+/// The ID space an invoice ID belongs to: invoices attached to a payment with
+/// `attach_invoice`, or standalone invoices from `create_invoice`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum InvoiceKind {
+    Attached,
+    Issued,
+}
+
+/// One rate applied to one line item. `line` indexes the invoice's items.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct TaxLine {
+    pub line: u32,
+    pub jurisdiction: Symbol,
+    pub category: BytesN<32>,
+    pub rate_bps: u32,
+    pub compound: bool,
+    pub inclusive: bool,
+    pub taxable_amount: i128,
+    pub tax: i128,
+}
+
+// ── Per-record persistent storage ───────────────────────────────────────────
+//
+// Payments, subscriptions, their side records and the customer/merchant
+// indexes grow with volume, so they live in persistent storage rather than
+// the instance map that is loaded on every call. Each read or write bumps the
+// entry's TTL, and `extend_record_ttl` lets keepers refresh idle records.
+// Entries written by releases before schema version 2 are moved out of
+// instance storage the first time they are touched.
+
+const LEDGERS_PER_DAY: u32 = 17_280;
+const RECORD_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+const RECORD_TTL_EXTEND_TO: u32 = 120 * LEDGERS_PER_DAY;
+
+fn bump_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+}
+
+/// Moves a record written by a pre-persistent release out of instance storage.
+/// Returns `true` when the record now lives in persistent storage.
+fn promote_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if env.storage().persistent().has(key) {
+        return true;
+    }
+    match env.storage().instance().get::<K, Val>(key) {
+        Some(value) => {
+            env.storage().instance().remove(key);
+            env.storage().persistent().set(key, &value);
+            true
+        }
+        None => false,
+    }
+}
+
+fn record_get<K: IntoVal<Env, Val>, V: TryFromVal<Env, Val>>(env: &Env, key: &K) -> Option<V> {
+    if !promote_record(env, key) {
+        return None;
+    }
+    let value = env.storage().persistent().get(key);
+    bump_record(env, key);
+    value
+}
+
+fn record_set<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(env: &Env, key: &K, value: &V) {
+    if env.storage().instance().has(key) {
+        env.storage().instance().remove(key);
+    }
+    env.storage().persistent().set(key, value);
+    bump_record(env, key);
+}
+
+fn record_has<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    env.storage().persistent().has(key) || env.storage().instance().has(key)
+}
+
+fn record_remove<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage().persistent().remove(key);
+    env.storage().instance().remove(key);
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// Writes the unpadded base64url encoding of a SHA-256 digest (43 characters),
+/// the form WebAuthn uses for the challenge in `clientDataJSON`.
+fn base64url_encode_digest(digest: &[u8; 32], out: &mut [u8]) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut written = 0;
+    for byte in digest {
+        bits = (bits << 8) | *byte as u32;
+        bit_count += 8;
+        while bit_count >= 6 {
+            bit_count -= 6;
+            out[written] = ALPHABET[((bits >> bit_count) & 0x3f) as usize];
+            written += 1;
+        }
+    }
+    out[written] = ALPHABET[((bits << (6 - bit_count)) & 0x3f) as usize];
+}
+
+// ── UTC CALENDAR ────────────────────────────────────────────────────────────
+// Proleptic Gregorian dates from days since 1970-01-01, after Howard Hinnant's
+// `civil_from_days` / `days_from_civil` algorithms.
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `(year, month, day)` of the day `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Days from 1970-01-01 to `(year, month, day)`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Midnight UTC of `(year, month, day)`, with `day` clamped to the month's length.
+fn midnight_on(year: i64, month: u32, day: u32) -> u64 {
+    let day = day.min(days_in_month(year, month));
+    days_from_civil(year, month, day) as u64 * SECONDS_PER_DAY
+}
+
+/// The first time `anchor` falls due strictly after `after`. `origin` is when the
+/// schedule started; only `BillingAnchor::Yearly` reads it.
+fn next_billing_time(anchor: &BillingAnchor, origin: u64, after: u64) -> u64 {
+    let today = (after / SECONDS_PER_DAY) as i64;
+    match anchor {
+        BillingAnchor::FixedSeconds(seconds) => after.saturating_add(*seconds),
+        BillingAnchor::MonthlyOnDay(day) => {
+            let (mut year, mut month, _) = civil_from_days(today);
+            loop {
+                let due = midnight_on(year, month, *day);
+                if due > after {
+                    return due;
+                }
+                if month == 12 {
+                    year += 1;
+                    month = 1;
+                } else {
+                    month += 1;
+                }
+            }
+        }
+        BillingAnchor::Yearly => {
+            let (_, month, day) = civil_from_days((origin / SECONDS_PER_DAY) as i64);
+            let (mut year, _, _) = civil_from_days(today);
+            loop {
+                let due = midnight_on(year, month, day);
+                if due > after {
+                    return due;
+                }
+                year += 1;
+            }
+        }
+        BillingAnchor::Weekly(weekday) => {
+            // 1970-01-01 was a Thursday (3 with Monday = 0).
+            let current = ((today + 3) % 7) as u32;
+            let ahead = match (weekday + 7 - current) % 7 {
+                0 => 7,
+                n => n,
+            };
+            (today as u64 + ahead as u64) * SECONDS_PER_DAY
+        }
+    }
+}
+
+fn is_valid_billing_anchor(anchor: &BillingAnchor) -> bool {
+    match anchor {
+        BillingAnchor::FixedSeconds(seconds) => *seconds > 0,
+        BillingAnchor::MonthlyOnDay(day) => (1..=31).contains(day),
+        BillingAnchor::Yearly => true,
+        BillingAnchor::Weekly(weekday) => *weekday <= 6,
+    }
+}
+
+// ── TAX ─────────────────────────────────────────────────────────────────────
+
+/// `num / den` rounded half to even, for `num >= 0` and `den > 0`.
+fn div_round_half_even(num: i128, den: i128) -> i128 {
+    let quotient = num / den;
+    let twice_remainder = (num % den) * 2;
+    if twice_remainder > den || (twice_remainder == den && quotient % 2 == 1) {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+/// Applies `rule` to one line item, appending a `TaxLine` per rate. Returns the
+/// tax to add on top of the line amount, which is zero for inclusive rules.
+fn apply_tax_rule(
+    line: u32,
+    amount: i128,
+    jurisdiction: &Symbol,
+    category: &BytesN<32>,
+    rule: &TaxRule,
+    breakdown: &mut Vec<TaxLine>,
+) -> Result<i128, Error> {
+    let overflow = Error::Payment(PaymentError::BillingOverflow);
+
+    // For inclusive rules, find the net amount that grows to `amount` once
+    // taxed: (base + taxes) / base is tracked exactly as `gross / scale`.
+    let base = if rule.inclusive {
+        let mut gross: i128 = 1;
+        let mut scale: i128 = 1;
+        for rate in rule.rates.iter() {
+            let taxed_on = if rate.compound { gross } else { scale };
+            gross = gross
+                .checked_mul(10_000)
+                .and_then(|g| g.checked_add(taxed_on.checked_mul(rate.rate_bps as i128)?))
+                .ok_or(overflow)?;
+            scale = scale.checked_mul(10_000).ok_or(overflow)?;
+        }
+        div_round_half_even(amount.checked_mul(scale).ok_or(overflow)?, gross)
+    } else {
+        amount
+    };
+
+    let first = breakdown.len();
+    let mut taxes: i128 = 0;
+    for rate in rule.rates.iter() {
+        let taxable_amount = if rate.compound { base + taxes } else { base };
+        let tax = div_round_half_even(
+            taxable_amount
+                .checked_mul(rate.rate_bps as i128)
+                .ok_or(overflow)?,
+            10_000,
+        );
+        taxes += tax;
+        breakdown.push_back(TaxLine {
+            line,
+            jurisdiction: jurisdiction.clone(),
+            category: category.clone(),
+            rate_bps: rate.rate_bps,
+            compound: rate.compound,
+            inclusive: rule.inclusive,
+            taxable_amount,
+            tax,
+        });
+    }
+
+    if !rule.inclusive {
+        return Ok(taxes);
+    }
+    // Rounding each rate separately can leave the parts a unit off the
+    // amount; the last rate absorbs the difference.
+    let drift = amount - base - taxes;
+    if drift != 0 && breakdown.len() > first {
+        let last = breakdown.len() - 1;
+        let mut tax_line = breakdown.get(last).unwrap();
+        tax_line.tax += drift;
+        breakdown.set(last, tax_line);
+    }
+    Ok(0)
+}
+
+/// Bumps a record's TTL, promoting it from instance storage first if needed.
+/// Returns `false` when no such record exists.
+fn touch_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if !promote_record(env, key) {
+        return false;
+    }
+    bump_record(env, key);
+    true
+}
+
+#[contract]
+pub struct PaymentContract;
+
+// Constants for size limits
+const MAX_METADATA_SIZE: u32 = 512;
+const MAX_NOTES_SIZE: u32 = 1024;
+const DEFAULT_MAX_RETRIES: u64 = 3;
+const SECONDS_PER_DAY: u64 = 86400;
+const MAX_TRIAL_DURATION: u64 = 90 * SECONDS_PER_DAY; // 90 days max trial
+const PLAN_PRICE_NOTICE_PERIOD: u64 = 30 * SECONDS_PER_DAY; // notice before a plan price change applies
+const MAX_ROUTE_HOPS: u32 = 4;
+const MAX_CLIENT_DATA_JSON_LEN: u32 = 1024;
+
+// Fee tier volume thresholds (raw token units)
+const PREMIUM_VOLUME_THRESHOLD: i128 = 10_000;
+const ENTERPRISE_VOLUME_THRESHOLD: i128 = 100_000;
+const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+#[contractimpl]
+impl PaymentContract {
+    /// Initializes the payment contract with a single admin and default multisig config.
+    ///
+    /// # Arguments
+    /// * `admin` - The initial admin address
+    ///
+    /// # Panics
+    /// Panics if the contract has already been initialized.
+    pub fn initialize(env: Env, admin: Address) {
+        if env
+            .storage()
+            .instance()
+            .has(&DataKey::Config(ConfigKey::MultiSigConfig))
+        {
+            panic!("already initialized");
+        }
+        let config = MultiSigConfig {
+            admins: Vec::from_array(&env, [admin.clone()]),
+            required_signatures: 1,
+            total_admins: 1,
+            proposal_ttl: 604800,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
+        // Keep Admin key for backward compat
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::Admin), &admin);
+        env.storage().instance().set(
+            &DataKey::Config(ConfigKey::SchemaVersion),
+            &INITIAL_SCHEMA_VERSION,
+        );
+        (AdminAdded { admin }).publish(&env);
+    }
+
+    /// Returns the current schema version of the contract storage.
+    ///
+    /// # Returns
+    /// The current schema version as a `u32`.
+    pub fn get_schema_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::SchemaVersion))
+            .unwrap_or(INITIAL_SCHEMA_VERSION)
+    }
+
+    /// Migrates the contract storage schema to a target version.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing the migration (must be in the multisig admin list)
+    /// * `target_version` - The schema version to migrate to
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin, the target version
+    /// is not greater than the current version, or multisig is not initialized.
+    pub fn migrate_schema(env: Env, admin: Address, target_version: u32) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        let current = Self::get_schema_version(env.clone());
+        if current >= target_version {
+            return Err(Error::Basic(BasicError::SchemaAlreadyAtTarget));
+        }
+
+        env.storage().instance().set(
+            &DataKey::Config(ConfigKey::SchemaVersion),
+            &target_version,
+        );
+        Ok(())
+    }
+
+    /// Replaces the contract code with the uploaded WASM identified by `new_wasm_hash`.
+    ///
+    /// With a multisig threshold above one, the upgrade must instead go through
+    /// `propose_action` with `ActionType::UpgradeContract(new_wasm_hash)`. The hash
+    /// being replaced is kept as the rollback target (see `get_previous_wasm_hash`).
+    /// Storage is left untouched; run `migrate_schema` afterwards if the new code
+    /// expects a newer schema.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing the upgrade (must be in the multisig admin list)
+    /// * `new_wasm_hash` - Hash of WASM already uploaded to the network
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `BasicError::NotAnAdmin` if the caller is not an admin, or
+    /// `ProposalError::RequiresMultiSig` if more than one signature is required.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+        if config.required_signatures > 1 {
+            return Err(Error::Proposal(ProposalError::RequiresMultiSig));
+        }
+
+        Self::do_upgrade(&env, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Returns the WASM hash installed by the last `upgrade`, or `None` if the
+    /// contract still runs the code it was deployed with.
+    pub fn get_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::WasmHash))
+    }
+
+    /// Returns the WASM hash replaced by the last `upgrade`, i.e. the rollback target.
+    /// `None` until the contract has been upgraded twice, since the hash it was
+    /// deployed with is not known on-chain.
+    pub fn get_previous_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::PreviousWasmHash))
+    }
+
+    /// Extends the TTL of a payment record and the side records stored under its id.
+    ///
+    /// Permissionless so that keepers can keep old payments readable. A record still
+    /// held in instance storage by a pre-v2 release is moved to persistent storage first,
+    /// which makes this the per-payment step of the schema version 2 migration.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The payment whose records should be kept alive
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `PaymentError::NotFound` if the payment does not exist.
+    pub fn extend_record_ttl(env: Env, payment_id: u64) -> Result<(), Error> {
+        if !touch_record(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
+
+        let side_records = [
+            DataKey::Payment(PaymentKey::Metadata(payment_id)),
+            DataKey::Payment(PaymentKey::Memo(payment_id)),
+            DataKey::Payment(PaymentKey::MemoVersion(payment_id)),
+            DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+            DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
+            DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+            DataKey::Payment(PaymentKey::Discount(payment_id)),
+            DataKey::State(StateDataKey::EscrowedPayment(payment_id)),
+            DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
+            DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+            DataKey::State(StateDataKey::AutoEscrowTriggered(payment_id)),
+        ];
+        for key in side_records.iter() {
+            touch_record(&env, key);
+        }
+
+        env.storage()
+            .instance()
+            .extend_ttl(RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+        Ok(())
+    }
+
+    /// Sets the verification level for a specific merchant.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `merchant` - The merchant whose verification level is being set
+    /// * `level` - The new verification level to assign
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
+    pub fn set_merchant_verification_level(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        level: MerchantVerificationLevel,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::VerificationLevel(merchant)),
+            &level,
+        );
+
+        Ok(())
+    }
+
+    /// Retrieves the verification level for a given merchant.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant address to query
+    ///
+    /// # Returns
+    /// The merchant's verification level, defaulting to `Unverified` if not set.
+    pub fn get_merchant_verification_level(
+        env: Env,
+        merchant: Address,
+    ) -> MerchantVerificationLevel {
+        env.storage()
+            .instance()
+            .get(&DataKey::Merchant(MerchantDataKey::VerificationLevel(
+                merchant,
+            )))
+            .unwrap_or(MerchantVerificationLevel::Unverified)
+    }
+
+    /// Registers the category hash of a merchant's business, which delegate
+    /// scopes restricted to categories are checked against.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `merchant` - The merchant being categorized
+    /// * `category` - The category hash, or `None` to clear it
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
+    pub fn set_merchant_category(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        category: Option<BytesN<32>>,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+
+        let key = DataKey::Merchant(MerchantDataKey::Category(merchant));
+        match category {
+            Some(category) => record_set(&env, &key, &category),
+            None => record_remove(&env, &key),
+        }
+        Ok(())
+    }
+
+    /// Returns the category registered for a merchant, if any.
+    pub fn get_merchant_category(env: Env, merchant: Address) -> Option<BytesN<32>> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::Category(merchant)),
+        )
+    }
+
+    /// Configures the transaction limits for a specific verification tier.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `limits` - The tier limits to store, including the tier level and its associated limits
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
+    pub fn set_verification_tier_limits(
+        env: Env,
+        admin: Address,
+        limits: VerificationTierLimits,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::VerificationTierLimit(limits.level.clone())),
+            &limits,
+        );
+
+        Ok(())
+    }
+
+    /// Retrieves the transaction limits configured for a given verification tier.
+    ///
+    /// # Arguments
+    /// * `level` - The verification tier level to query
+    ///
+    /// # Returns
+    /// `Some(VerificationTierLimits)` if configured for the tier, or `None` otherwise.
+    pub fn get_tier_limits(
+        env: Env,
+        level: MerchantVerificationLevel,
+    ) -> Option<VerificationTierLimits> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Merchant(MerchantDataKey::VerificationTierLimit(
+                level,
+            )))
+    }
+
+    /// Returns the current multisig configuration.
+    ///
+    /// # Returns
+    /// The `MultiSigConfig` containing admin list, required signatures, and proposal TTL.
+    ///
+    /// # Panics
+    /// Panics if the multisig configuration has not been initialized.
+    pub fn get_multisig_config(env: Env) -> MultiSigConfig {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .expect("MultiSig not initialized")
+    }
+
+    /// Proposes a new admin action for multisig approval.
+    ///
+    /// # Arguments
+    /// * `proposer` - The admin proposing the action (must be in the multisig admin list)
+    /// * `action_type` - The type of action being proposed
+    /// * `target` - The target address for the action
+    /// * `data` - Arbitrary data payload for the action
+    ///
+    /// # Returns
+    /// `Ok(proposal_id)` on success, or an error if the proposer is not an admin
+    /// or multisig is not initialized.
+    pub fn propose_action(
+        env: Env,
+        proposer: Address,
+        action_type: ActionType,
+        target: Address,
+        data: Bytes,
+    ) -> Result<String, Error> {
+        proposer.require_auth();
+
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&proposer) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Payment(PaymentKey::LargePaymentCounter))
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::Payment(PaymentKey::LargePaymentCounter), &counter);
+
+        let proposal_id = PaymentContract::u64_to_string(&env, counter);
+        let now = env.ledger().timestamp();
+
+        let mut approvals = Vec::new(&env);
+        approvals.push_back(proposer.clone());
+
+        let proposal = AdminProposal {
+            id: proposal_id.clone(),
+            proposer: proposer.clone(),
+            action_type: action_type.clone(),
+            target,
+            data,
+            approvals,
+            approval_count: 1,
+            executed: false,
+            rejected: false,
+            created_at: now,
+            expires_at: now + config.proposal_ttl,
+        };
+
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
+            &proposal,
+        );
+
+        (ActionProposed {
+            proposal_id: proposal_id.clone(),
+            proposer,
+            action_type,
+        })
+        .publish(&env);
+
+        Ok(proposal_id)
+    }
+
+    /// Approves a pending admin proposal.
+    ///
+    /// # Arguments
+    /// * `approver` - The admin approving the proposal (must be in the multisig admin list)
+    /// * `proposal_id` - The ID of the proposal to approve
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the approver is not an admin, the proposal
+    /// is not found, has already been executed/rejected, is expired, or was already approved
+    /// by this admin.
+    pub fn approve_action(env: Env, approver: Address, proposal_id: String) -> Result<(), Error> {
+        approver.require_auth();
+
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&approver) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        let mut proposal: AdminProposal = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::AdminProposal(
+                proposal_id.clone(),
+            )))
+            .ok_or(Error::Proposal(ProposalError::NotFound))?;
+
+        if proposal.executed || proposal.rejected {
+            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
+        }
+
+        if env.ledger().timestamp() > proposal.expires_at {
+            return Err(Error::Proposal(ProposalError::Expired));
+        }
+
+        if proposal.approvals.contains(&approver) {
+            return Err(Error::Basic(BasicError::AlreadyApproved));
+        }
+
+        proposal.approvals.push_back(approver.clone());
+        proposal.approval_count += 1;
+
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
+            &proposal,
+        );
+
+        (ActionApproved {
+            proposal_id,
+            approver,
+            approval_count: proposal.approval_count,
+        })
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Executes a fully approved admin proposal.
+    ///
+    /// # Arguments
+    /// * `proposal_id` - The ID of the proposal to execute
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the proposal is not found, has already been
+    /// executed/rejected, is expired, or has not reached the required signature threshold.
+    pub fn execute_action(env: Env, proposal_id: String) -> Result<(), Error> {
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        let mut proposal: AdminProposal = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::AdminProposal(
+                proposal_id.clone(),
+            )))
+            .ok_or(Error::Proposal(ProposalError::NotFound))?;
+
+        if proposal.executed || proposal.rejected {
+            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
+        }
+
+        if env.ledger().timestamp() > proposal.expires_at {
+            return Err(Error::Proposal(ProposalError::Expired));
+        }
+
+        if proposal.approval_count < config.required_signatures {
+            return Err(Error::Proposal(ProposalError::ThresholdNotMet));
+        }
+
+        proposal.executed = true;
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
+            &proposal,
+        );
+
+        PaymentContract::dispatch_action(&env, &proposal)?;
+
+        (ActionExecuted { proposal_id }).publish(&env);
+
+        Ok(())
+    }
+
+    /// Rejects a pending admin proposal, preventing its execution.
+    ///
+    /// # Arguments
+    /// * `rejecter` - The admin rejecting the proposal (must be in the multisig admin list)
+    /// * `proposal_id` - The ID of the proposal to reject
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the rejecter is not an admin, the proposal
+    /// is not found, or has already been executed/rejected.
+    pub fn reject_action(env: Env, rejecter: Address, proposal_id: String) -> Result<(), Error> {
+        rejecter.require_auth();
+
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&rejecter) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        let mut proposal: AdminProposal = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::AdminProposal(
+                proposal_id.clone(),
+            )))
+            .ok_or(Error::Proposal(ProposalError::NotFound))?;
+
+        if proposal.executed || proposal.rejected {
+            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
+        }
+
+        proposal.rejected = true;
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
+            &proposal,
+        );
+
+        (ActionRejected {
+            proposal_id,
+            rejected_by: rejecter,
+        })
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Adds a new admin to the multisig configuration.
+    ///
+    /// # Arguments
+    /// * `caller` - The existing admin adding the new admin (must be in the multisig admin list)
+    /// * `new_admin` - The address of the new admin to add
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
+    /// No-op if the address is already an admin.
+    pub fn add_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let mut config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&caller) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        if !config.admins.contains(&new_admin) {
+            config.admins.push_back(new_admin.clone());
+            config.total_admins += 1;
+            env.storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
+            (AdminAdded { admin: new_admin }).publish(&env);
+        }
+
+        Ok(())
+    }
+
+    /// Removes an admin from the multisig configuration.
+    ///
+    /// # Arguments
+    /// * `caller` - The existing admin performing the removal (must be in the multisig admin list)
+    /// * `admin` - The address of the admin to remove
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin, the target is not
+    /// an admin, removing them would leave fewer admins than required signatures, or multisig
+    /// is not initialized.
+    pub fn remove_admin(env: Env, caller: Address, admin: Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let mut config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&caller) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        if config.total_admins <= config.required_signatures {
+            return Err(Error::Basic(BasicError::InsufficientAdmins));
+        }
+
+        let mut new_admins = Vec::new(&env);
+        for a in config.admins.iter() {
+            if a != admin {
+                new_admins.push_back(a);
+            }
+        }
+
+        if new_admins.len() == config.admins.len() {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        config.admins = new_admins;
+        config.total_admins -= 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
+        (AdminRemoved { admin }).publish(&env);
+
+        Ok(())
+    }
+
+    /// Updates the required number of admin signatures for multisig proposals.
+    ///
+    /// # Arguments
+    /// * `caller` - The admin updating the requirement (must be in the multisig admin list)
+    /// * `required` - The new number of required signatures (must be > 0 and <= total admins)
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the caller is not an admin, the required count
+    /// is invalid, or multisig is not initialized.
+    pub fn update_required_signatures(
+        env: Env,
+        caller: Address,
+        required: u32,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
+        let mut config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+
+        if !config.admins.contains(&caller) {
+            return Err(Error::Basic(BasicError::NotAnAdmin));
+        }
+
+        if required == 0 || required > config.total_admins {
+            return Err(Error::Basic(BasicError::InsufficientAdmins));
+        }
+
+        config.required_signatures = required;
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
+
+        Ok(())
+    }
+
+    /// Creates a new payment from a customer to a merchant.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer making the payment (must authorize)
+    /// * `merchant` - The merchant receiving the payment
+    /// * `amount` - The payment amount in base token units
+    /// * `token` - The SPL token address for the payment
+    /// * `currency` - The fiat currency associated with the payment
+    /// * `expiration_duration` - Seconds until the payment expires (0 for no expiration)
+    /// * `metadata` - Arbitrary metadata string for the payment
+    ///
+    /// # Returns
+    /// `Ok(payment_id)` on success, or an error if the token is not allowed, currency is
+    /// invalid, metadata is too large, the customer is flagged, rate limits are exceeded,
+    /// or the contract is paused.
+    pub fn create_payment(
+        env: Env,
+        customer: Address,
+        merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        expiration_duration: u64,
+        metadata: String,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "create_payment")?;
+        Self::require_merchant_not_paused(&env, &merchant)?;
+        customer.require_auth();
+        PaymentContract::do_create_payment(
+            &env,
+            customer,
+            merchant,
+            amount,
+            token,
+            currency,
+            expiration_duration,
+            metadata,
+        )
+    }
+
+    /// Registers the key the customer signs payment intents with, replacing any
+    /// previous key. Intents signed with the old key stop verifying.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer registering the key (must authorize)
+    /// * `key` - An ed25519 key or a WebAuthn passkey, as for channels
+    pub fn register_intent_key(env: Env, customer: Address, key: SignerKey) {
+        customer.require_auth();
+        record_set(
+            &env,
+            &DataKey::Customer(CustomerDataKey::IntentKey(customer.clone())),
+            &key,
+        );
+        (IntentKeyRegistered { customer, key }).publish(&env);
+    }
+
+    /// Returns the key registered for payment intents, if any.
+    pub fn get_intent_key(env: Env, customer: Address) -> Option<SignerKey> {
+        Self::load_intent_key(&env, &customer)
+    }
+
+    /// Loads a customer's intent key. Keys registered before intents accepted a
+    /// `SignerKey` were stored as a bare ed25519 key.
+    fn load_intent_key(env: &Env, customer: &Address) -> Option<SignerKey> {
+        let key: Val = record_get(
+            env,
+            &DataKey::Customer(CustomerDataKey::IntentKey(customer.clone())),
+        )?;
+        Some(match BytesN::<32>::try_from_val(env, &key) {
+            Ok(public_key) => SignerKey::Ed25519(public_key),
+            Err(_) => SignerKey::from_val(env, &key),
+        })
+    }
+
+    /// Creates a payment from an intent signed off-chain by the customer, so the
+    /// merchant or a relayer can submit it without the customer's authorization
+    /// in the transaction.
+    ///
+    /// The signature covers the network ID and this contract's address, so an
+    /// intent cannot be replayed on another network or contract. Each nonce can be
+    /// used once per customer; the payment is then completed like any other
+    /// `Pending` payment.
+    ///
+    /// # Arguments
+    /// * `intent` - The signed payment intent
+    /// * `signature` - The customer's signature over the intent, of the type
+    ///   matching their registered key
+    ///
+    /// # Returns
+    /// `Ok(payment_id)` on success, or an error if the customer has no registered
+    /// key, the intent has expired, its nonce was used or cancelled, the
+    /// signature does not match the key type or is not a valid WebAuthn
+    /// assertion, or any `create_payment` check fails. Panics if the ed25519 or
+    /// secp256r1 signature itself is invalid.
+    pub fn execute_payment_intent(
+        env: Env,
+        intent: PaymentIntent,
+        signature: StateSignature,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "execute_payment_intent")?;
+        if env.ledger().timestamp() > intent.expiry {
+            return Err(Error::Signature(SignatureError::IntentExpired));
+        }
+        let key = Self::load_intent_key(&env, &intent.customer)
+            .ok_or(Error::Signature(SignatureError::SigningKeyNotRegistered))?;
+        let nonce_key = DataKey::Customer(CustomerDataKey::IntentNonceUsed(
+            intent.customer.clone(),
+            intent.nonce,
+        ));
+        if record_has(&env, &nonce_key) {
+            return Err(Error::Signature(SignatureError::IntentNonceUsed));
+        }
+
+        let mut msg = Bytes::new(&env);
+        msg.append(&env.ledger().network_id().into());
+        msg.append(&env.current_contract_address().to_xdr(&env));
+        msg.append(&intent.clone().to_xdr(&env));
+        Self::verify_state_signature(&env, &key, &msg, &signature)?;
+        record_set(&env, &nonce_key, &true);
+
+        let payment_id = PaymentContract::do_create_payment(
+            &env,
+            intent.customer.clone(),
+            intent.merchant.clone(),
+            intent.amount,
+            intent.token,
+            intent.currency,
+            0,
+            String::from_str(&env, ""),
+        )?;
+        if let Some(ref invoice_hash) = intent.invoice_hash {
+            record_set(
+                &env,
+                &DataKey::Payment(PaymentKey::InvoiceHash(payment_id)),
+                invoice_hash,
+            );
+        }
+
+        (PaymentIntentExecuted {
+            payment_id,
+            customer: intent.customer,
+            merchant: intent.merchant,
+            nonce: intent.nonce,
+            invoice_hash: intent.invoice_hash,
+        })
+        .publish(&env);
+        Ok(payment_id)
+    }
+
+    /// Cancels an outstanding payment intent by consuming its nonce.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer who signed the intent (must authorize)
+    /// * `nonce` - The nonce of the intent to cancel
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `IntentNonceUsed` if the intent was already executed
+    /// or cancelled.
+    pub fn cancel_payment_intent(env: Env, customer: Address, nonce: u64) -> Result<(), Error> {
+        customer.require_auth();
+        let nonce_key =
+            DataKey::Customer(CustomerDataKey::IntentNonceUsed(customer.clone(), nonce));
+        if record_has(&env, &nonce_key) {
+            return Err(Error::Signature(SignatureError::IntentNonceUsed));
+        }
+        record_set(&env, &nonce_key, &true);
+        (PaymentIntentCancelled { customer, nonce }).publish(&env);
+        Ok(())
+    }
+
+    /// Returns `true` if the customer's intent nonce has been executed or cancelled.
+    pub fn is_intent_nonce_used(env: Env, customer: Address, nonce: u64) -> bool {
+        record_has(
+            &env,
+            &DataKey::Customer(CustomerDataKey::IntentNonceUsed(customer, nonce)),
+        )
+    }
+
+    /// Returns the invoice hash attached to a payment created from an intent.
+    pub fn get_payment_invoice_hash(env: Env, payment_id: u64) -> Option<BytesN<32>> {
+        record_get(&env, &DataKey::Payment(PaymentKey::InvoiceHash(payment_id)))
+    }
+
+    /// Schedules a future payment by escrowing tokens until the scheduled time.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer scheduling the payment (must authorize)
+    /// * `merchant` - The merchant who will receive the payment
+    /// * `token` - The SPL token address for the payment
+    /// * `amount` - The payment amount in base token units
+    /// * `scheduled_at` - The ledger timestamp at which the payment should be executed
+    ///
+    /// # Returns
+    /// `Ok(payment_id)` on success, or an error if the amount is non-positive, the scheduled
+    /// time is in the past, or the contract is paused.
+    pub fn schedule_payment(
+        env: Env,
+        customer: Address,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        scheduled_at: u64,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "schedule_payment")?;
+        customer.require_auth();
+        if amount <= 0 {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        let now = env.ledger().timestamp();
+        if scheduled_at <= now {
+            return Err(Error::Payment(PaymentError::InvalidScheduleTime));
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        token_client.transfer_from(&contract_address, &customer, &contract_address, &amount);
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::ScheduledPaymentCounter))
+            .unwrap_or(0);
+        let payment_id = counter + 1;
+        let scheduled = ScheduledPayment {
+            payment_id,
+            customer: customer.clone(),
+            merchant: merchant.clone(),
+            token,
+            amount,
+            scheduled_at,
+            executed: false,
+            cancelled: false,
+        };
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+            &scheduled,
+        );
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::ScheduledPaymentCounter),
+            &payment_id,
+        );
+
+        (PaymentCreated {
+            payment_id,
+            customer,
+            merchant,
+            amount,
+        })
+        .publish(&env);
+
+        Ok(payment_id)
+    }
+
+    /// Executes a previously scheduled payment once its scheduled time has passed.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the scheduled payment to execute
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the payment is not found, has been cancelled,
+    /// was already executed, is not yet due, the customer is flagged, spend limits are
+    /// exceeded, or the contract is paused.
+    pub fn execute_scheduled_payment(env: Env, payment_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, "execute_scheduled_payment")?;
+        let mut scheduled: ScheduledPayment = record_get(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
+        if scheduled.cancelled {
+            return Err(Error::Payment(PaymentError::ScheduledPaymentCancelled));
+        }
+        if scheduled.executed {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        }
+        if env.ledger().timestamp() < scheduled.scheduled_at {
+            return Err(Error::Payment(PaymentError::NotYetDue));
+        }
+
+        // Check customer spend limit (#282)
+        PaymentContract::check_and_update_spend_limit(&env, &scheduled.customer, scheduled.amount)?;
+
+        Self::settle_or_accumulate(
+            &env,
+            scheduled.merchant.clone(),
+            scheduled.token.clone(),
+            scheduled.amount,
+        )?;
+        scheduled.executed = true;
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+            &scheduled,
+        );
+        Ok(())
+    }
+
+    /// Cancels a scheduled payment and refunds the escrowed tokens to the customer.
+    ///
+    /// # Arguments
+    /// * `caller` - The customer who scheduled the payment, or an admin (must authorize)
+    /// * `payment_id` - The ID of the scheduled payment to cancel
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the payment is not found, has already been
+    /// executed or cancelled, the caller is unauthorized, or the contract is paused.
+    pub fn cancel_scheduled_payment(
+        env: Env,
+        caller: Address,
+        payment_id: u64,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, "cancel_scheduled_payment")?;
+        caller.require_auth();
+        let mut scheduled: ScheduledPayment = record_get(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
+        if scheduled.executed {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        }
+        if scheduled.cancelled {
+            return Err(Error::Payment(PaymentError::ScheduledPaymentCancelled));
+        }
+        if caller != scheduled.customer {
+            let stored_admin: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::Config(ConfigKey::Admin))
+                .ok_or(Error::Basic(BasicError::Unauthorized))?;
+            if caller != stored_admin {
+                return Err(Error::Basic(BasicError::Unauthorized));
+            }
+        }
+
+        let token_client = token::Client::new(&env, &scheduled.token);
+        let contract_address = env.current_contract_address();
+        token_client.transfer(&contract_address, &scheduled.customer, &scheduled.amount);
+        scheduled.cancelled = true;
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+            &scheduled,
+        );
+        Ok(())
+    }
+
+    /// Retrieves the details of a scheduled payment.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the scheduled payment to retrieve
+    ///
+    /// # Returns
+    /// `Ok(ScheduledPayment)` on success, or `Error::Payment(NotFound)` if not found.
+    pub fn get_scheduled_payment(env: Env, payment_id: u64) -> Result<ScheduledPayment, Error> {
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))
+    }
+
+    fn settle_or_accumulate(
+        env: &Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let amount = Self::recover_chargeback_debts(env, &merchant, &token, amount);
+        if amount == 0 {
+            return Ok(());
+        }
+
+        // If merchant has a payout schedule for this token and it's not Immediate, accumulate
+        if let Some(mut schedule) =
+            env.storage()
+                .instance()
+                .get::<DataKey, PayoutSchedule>(&DataKey::Merchant(
+                    MerchantDataKey::PayoutSchedule(merchant.clone()),
+                ))
+        {
+            if schedule.token == token && schedule.frequency != PayoutFrequency::Immediate {
+                schedule.accumulated += amount;
+                env.storage().instance().set(
+                    &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant.clone())),
+                    &schedule,
+                );
+                return Ok(());
+            }
+        }
+
+        // Otherwise perform immediate transfer
+        Self::pay_out(env, &merchant, &token, amount)?;
+        Ok(())
+    }
+
+    /// Configures a merchant's payout schedule for accumulating payments before settlement.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant configuring the payout schedule (must authorize)
+    /// * `frequency` - How often payouts should occur (Immediate, Daily, Weekly, Monthly)
+    /// * `token` - The SPL token address to accumulate for payouts
+    ///
+    /// # Returns
+    /// `Ok(())` on success.
+    pub fn set_payout_schedule(
+        env: Env,
+        merchant: Address,
+        frequency: PayoutFrequency,
+        token: Address,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        let now = env.ledger().timestamp();
+        let anchor = match frequency {
+            PayoutFrequency::Immediate | PayoutFrequency::Daily => {
+                BillingAnchor::FixedSeconds(SECONDS_PER_DAY)
+            }
+            PayoutFrequency::Weekly => BillingAnchor::FixedSeconds(SECONDS_PER_DAY * 7),
+            PayoutFrequency::Monthly => {
+                BillingAnchor::MonthlyOnDay(civil_from_days((now / SECONDS_PER_DAY) as i64).2)
+            }
+        };
+        let next = match frequency {
+            PayoutFrequency::Immediate => now,
+            _ => next_billing_time(&anchor, now, now),
+        };
+        record_set(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PayoutAnchor(merchant.clone())),
+            &PayoutAnchor {
+                anchor,
+                origin: now,
+            },
+        );
+        let schedule = PayoutSchedule {
+            merchant: merchant.clone(),
+            token: token.clone(),
+            frequency,
+            next_payout_at: next,
+            accumulated: 0,
+        };
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant)),
+            &schedule,
+        );
+        Ok(())
+    }
+
+    /// Retrieves the payout schedule configuration for a merchant.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant address to query
+    ///
+    /// # Returns
+    /// `Some(PayoutSchedule)` if configured, or `None` otherwise.
+    pub fn get_payout_schedule(env: Env, merchant: Address) -> Option<PayoutSchedule> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
+                merchant,
+            )))
+    }
+
+    /// Pins a merchant's payout schedule to a `BillingAnchor`, e.g. the 1st of each
+    /// month, replacing the cadence derived from its `PayoutFrequency`. The next payout
+    /// moves to the anchor's first due date after now.
+    ///
+    /// # Returns
+    /// The new `next_payout_at`.
+    pub fn set_payout_anchor(
+        env: Env,
+        merchant: Address,
+        anchor: BillingAnchor,
+    ) -> Result<u64, Error> {
+        merchant.require_auth();
+        if !is_valid_billing_anchor(&anchor) {
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+        let schedule_key = DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant.clone()));
+        let mut schedule: PayoutSchedule = env
+            .storage()
+            .instance()
+            .get(&schedule_key)
+            .ok_or(Error::Payment(PaymentError::PayoutScheduleNotFound))?;
+
+        let now = env.ledger().timestamp();
+        schedule.next_payout_at = next_billing_time(&anchor, now, now);
+        env.storage().instance().set(&schedule_key, &schedule);
+        record_set(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PayoutAnchor(merchant)),
+            &PayoutAnchor {
+                anchor,
+                origin: now,
+            },
+        );
+        Ok(schedule.next_payout_at)
+    }
+
+    /// Returns the anchor a merchant's payouts fall due on, if one has been recorded.
+    /// Schedules set before anchors existed step by their `PayoutFrequency` in fixed seconds.
+    pub fn get_payout_anchor(env: Env, merchant: Address) -> Option<PayoutAnchor> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PayoutAnchor(merchant)),
+        )
+    }
+
+    /// Sets the token a merchant wants to be paid in.
+    ///
+    /// Payouts from `complete_payment`, payout schedules, split settlements and
+    /// finality-delay settlements in any other token are converted through the
+    /// swap venue quoting the best output. If no venue can price the pair or the
+    /// swap fails, the merchant is paid in the original token instead.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant setting the preference (must authorize).
+    /// * `token` - The token to settle in.
+    /// * `max_slippage_bps` - How far a conversion may fall below the venue quote.
+    ///
+    /// # Errors
+    /// Returns `InvalidBps` if `max_slippage_bps` exceeds 10_000.
+    pub fn set_settlement_preference(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        max_slippage_bps: u32,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        if max_slippage_bps > 10_000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        record_set(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::SettlementPreference(merchant.clone())),
+            &SettlementPreference {
+                token: token.clone(),
+                max_slippage_bps,
+            },
+        );
+        (SettlementPreferenceSet {
+            merchant,
+            token,
+            max_slippage_bps,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Removes a merchant's settlement preference so payouts stay in the
+    /// payment token.
+    pub fn clear_settlement_preference(env: Env, merchant: Address) {
+        merchant.require_auth();
+        record_remove(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::SettlementPreference(merchant)),
+        );
+    }
+
+    /// Returns a merchant's settlement preference, if set.
+    pub fn get_settlement_preference(env: Env, merchant: Address) -> Option<SettlementPreference> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::SettlementPreference(merchant)),
+        )
+    }
+
+    /// Pays `amount` of `token` held by the contract to `payee`, converting it to
+    /// the payee's settlement token when they have set one. Returns the token and
+    /// amount actually paid.
+    ///
+    /// Conversion uses a single swap so that a failed swap leaves `token` in place
+    /// and the payee is paid in it instead. An adapter that reports success but
+    /// under-delivers cannot be unwound, so that case is an error.
+    fn pay_out(
+        env: &Env,
+        payee: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(Address, i128), Error> {
+        let contract_address = env.current_contract_address();
+        let preference = Self::get_settlement_preference(env.clone(), payee.clone())
+            .filter(|preference| preference.token != *token && amount > 0);
+        if let Some(preference) = preference {
+            let mut quotes = Vec::new(env);
+            let mut visited = Vec::new(env);
+            visited.push_back(token.clone());
+            Self::search_routes(
+                env,
+                &Self::get_swap_venues(env.clone()),
+                &preference.token,
+                1,
+                &mut visited,
+                &mut Vec::new(env),
+                (amount, amount, 0),
+                &mut quotes,
+            );
+            let best = quotes.iter().max_by_key(|route| route.output_amount);
+            if let Some(route) = best {
+                let hop = route.hops.get(0).unwrap();
+                let venue = Self::get_swap_venue(env.clone(), hop.venue_id)?;
+                let min_output =
+                    route.output_amount * (10_000 - preference.max_slippage_bps as i128) / 10_000;
+                match Self::swap_through_venue(env, &venue, &hop, amount, min_output) {
+                    Ok(received) => {
+                        token::Client::new(env, &preference.token).transfer(
+                            &contract_address,
+                            payee,
+                            &received,
+                        );
+                        (SettlementConverted {
+                            payee: payee.clone(),
+                            from_token: token.clone(),
+                            to_token: preference.token.clone(),
+                            amount_in: amount,
+                            amount_out: received,
+                        })
+                        .publish(env);
+                        return Ok((preference.token, received));
+                    }
+                    Err(Error::Routing(RoutingError::SwapFailed)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            (SettlementConversionFailed {
+                payee: payee.clone(),
+                token: token.clone(),
+                amount,
+            })
+            .publish(env);
+        }
+
+        token::Client::new(env, token).transfer(&contract_address, payee, &amount);
+        Ok((token.clone(), amount))
+    }
+
+    /// Triggers an accumulated payout for a merchant, transferring held funds.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant requesting the payout (must authorize)
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the payout schedule is not found, the payout
+    /// period has not elapsed, or there is nothing to settle.
+    pub fn trigger_scheduled_payout(env: Env, merchant: Address) -> Result<(), Error> {
+        merchant.require_auth();
+        let mut schedule: PayoutSchedule = env
+            .storage()
+            .instance()
+            .get(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
+                merchant.clone(),
+            )))
+            .ok_or(Error::Payment(PaymentError::PayoutScheduleNotFound))?;
+        let now = env.ledger().timestamp();
+        if now < schedule.next_payout_at {
+            return Err(Error::Payment(PaymentError::PayoutNotYetDue));
+        }
+        if schedule.accumulated == 0 {
+            return Err(Error::Payment(PaymentError::NothingToSettle));
+        }
+        Self::pay_out(&env, &merchant, &schedule.token, schedule.accumulated)?;
+        schedule.accumulated = 0;
+        let anchor: PayoutAnchor = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PayoutAnchor(merchant.clone())),
+        )
+            .unwrap_or(PayoutAnchor {
+                anchor: BillingAnchor::FixedSeconds(match schedule.frequency {
+                    PayoutFrequency::Immediate => SECONDS_PER_DAY,
+                    PayoutFrequency::Daily => SECONDS_PER_DAY,
+                    PayoutFrequency::Weekly => SECONDS_PER_DAY * 7,
+                    PayoutFrequency::Monthly => SECONDS_PER_DAY * 30,
+                }),
+                origin: schedule.next_payout_at,
+            });
+        schedule.next_payout_at =
+            next_billing_time(&anchor.anchor, anchor.origin, schedule.next_payout_at);
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant)),
+            &schedule,
+        );
+        Ok(())
+    }
+
+    /// Returns the accumulated token balance for a merchant's payout schedule.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant address to query
+    ///
+    /// # Returns
+    /// The accumulated balance in base token units, or 0 if no payout schedule exists.
+    pub fn get_accumulated_balance(env: Env, merchant: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get::<DataKey, PayoutSchedule>(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
+                merchant,
+            )))
+            .map(|s: PayoutSchedule| s.accumulated)
+            .unwrap_or(0)
+    }
+
+    fn do_create_payment(
+        env: &Env,
+        customer: Address,
+        merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        expiration_duration: u64,
+        metadata: String,
+    ) -> Result<u64, Error> {
+        Self::require_merchant_not_paused(env, &merchant)?;
+        if !PaymentContract::is_token_allowed(env, &token) {
+            return Err(Error::Payment(PaymentError::TokenNotAllowed));
+        }
+
+        // Validate currency
+        if !PaymentContract::is_valid_currency(&currency) {
+            return Err(Error::Basic(BasicError::InvalidCurrency));
+        }
+
+        // Validate metadata size
+        if metadata.len() > MAX_METADATA_SIZE {
+            return Err(Error::Basic(BasicError::MetadataTooLarge));
+        }
+
+        // Enforce sanctions/flag checks at creation entry point.
+        if PaymentContract::is_address_flagged(env.clone(), customer.clone())
+            && !PaymentContract::is_allowlisted(env, &customer)
+        {
+            return Err(Error::Basic(BasicError::AddressFlagged));
+        }
+
+        // Check rate limits and anti-fraud before processing
+        PaymentContract::check_rate_limit_internal(env, &customer, amount)?;
+
+        // Check merchant rate limits
+        PaymentContract::check_merchant_rate_limit(env, &merchant, amount)?;
+
+        // Check customer spend limit (#217)
+        PaymentContract::check_and_update_spend_limit(env, &customer, amount)?;
+
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Payment(PaymentKey::Counter))
+            .unwrap_or(0);
+        let payment_id = counter + 1;
+
+        let current_timestamp = env.ledger().timestamp();
+        let expires_at = if expiration_duration > 0 {
+            current_timestamp + expiration_duration
+        } else {
+            0
+        };
+
+        let payment = Payment {
+            id: payment_id,
+            customer: customer.clone(),
+            merchant: merchant.clone(),
+            amount,
+            token,
+            currency,
+            status: PaymentStatus::Pending,
+            created_at: current_timestamp,
+            expires_at,
+            metadata,
+            notes: String::from_str(&env, ""),
+            refunded_amount: 0,
+        };
+
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::Payment(PaymentKey::Counter), &payment_id);
+
+        // Index by customer
+        let customer_count: u64 = record_get(
+            env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
+            &DataKey::Customer(CustomerDataKey::Payments(customer.clone(), customer_count)),
+            &payment_id,
+        );
+        record_set(
+            env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer)),
+            &(customer_count + 1),
+        );
 
-// Constants for size limits
-const MAX_METADATA_SIZE: u32 = 512;
-const MAX_NOTES_SIZE: u32 = 1024;
-const DEFAULT_MAX_RETRIES: u64 = 3;
-const SECONDS_PER_DAY: u64 = 86400;
-const MAX_TRIAL_DURATION: u64 = 90 * SECONDS_PER_DAY; // 90 days max trial
+        // Index by merchant
+        let merchant_count: u64 = record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::Payments(merchant.clone(), merchant_count)),
+            &payment_id,
+        );
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
+            &(merchant_count + 1),
+        );
 
-// Fee tier volume thresholds (raw token units)
-const PREMIUM_VOLUME_THRESHOLD: i128 = 10_000;
-const ENTERPRISE_VOLUME_THRESHOLD: i128 = 100_000;
-const INITIAL_SCHEMA_VERSION: u32 = 1;
+        // Paged merchant payment index (100 entries per page)
+        {
+            const PAGE_SIZE: u64 = 100;
+            let page_num = merchant_count / PAGE_SIZE;
+            let mut page: Vec<u64> = record_get(
+                env,
+                &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
+                    merchant.clone(),
+                    page_num,
+                )))
+                .unwrap_or_else(|| Vec::new(&env));
+            page.push_back(payment_id);
+            record_set(
+                env,
+                &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(merchant, page_num)),
+                &page,
+            );
+        }
 
-#[contractimpl]
-impl PaymentContract {
-    /// Initializes the payment contract with a single admin and default multisig config.
-    ///
-    /// # Arguments
-    /// * `admin` - The initial admin address
-    ///
-    /// # Panics
-    /// Panics if the contract has already been initialized.
-    pub fn initialize(env: Env, admin: Address) {
-        if env
+        // Update global analytics
+        let mut analytics: PaymentAnalytics = env
             .storage()
             .instance()
-            .has(&DataKey::Config(ConfigKey::MultiSigConfig))
-        {
-            panic!("already initialized");
+            .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
+            .unwrap_or(PaymentAnalytics {
+                total_payments_created: 0,
+                total_payments_completed: 0,
+                total_payments_cancelled: 0,
+                total_payments_refunded: 0,
+                total_volume: 0,
+                total_refunded_volume: 0,
+                unique_customers: 0,
+                unique_merchants: 0,
+            });
+        analytics.total_payments_created += 1;
+        analytics.total_volume += amount;
+        if customer_count == 0 {
+            analytics.unique_customers += 1;
+        }
+        if merchant_count == 0 {
+            analytics.unique_merchants += 1;
+            let global_count: u64 = env
+                .storage()
+                .instance()
+                .get(&DataKey::Config(ConfigKey::GlobalMerchantCount))
+                .unwrap_or(0);
+            record_set(
+                env,
+                &DataKey::Merchant(MerchantDataKey::GlobalList(global_count)),
+                &payment.merchant.clone(),
+            );
+            env.storage().instance().set(
+                &DataKey::Config(ConfigKey::GlobalMerchantCount),
+                &(global_count + 1),
+            );
         }
-        let config = MultiSigConfig {
-            admins: Vec::from_array(&env, [admin.clone()]),
-            required_signatures: 1,
-            total_admins: 1,
-            proposal_ttl: 604800,
-        };
         env.storage()
             .instance()
-            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
-        // Keep Admin key for backward compat
-        env.storage()
+            .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
+
+        // Update merchant analytics
+        let mut m_analytics: MerchantAnalytics = env
+            .storage()
             .instance()
-            .set(&DataKey::Config(ConfigKey::Admin), &admin);
+            .get(&DataKey::Merchant(MerchantDataKey::Analytics(
+                payment.merchant.clone(),
+            )))
+            .unwrap_or(MerchantAnalytics {
+                total_payments: 0,
+                total_volume: 0,
+                total_completed: 0,
+                total_cancelled: 0,
+                total_refunded: 0,
+                total_refunded_volume: 0,
+            });
+        m_analytics.total_payments += 1;
+        m_analytics.total_volume += amount;
         env.storage().instance().set(
-            &DataKey::Config(ConfigKey::SchemaVersion),
-            &INITIAL_SCHEMA_VERSION,
+            &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
+            &m_analytics,
         );
-        (AdminAdded { admin }).publish(&env);
-    }
 
-    /// Returns the current schema version of the contract storage.
-    ///
-    /// # Returns
-    /// The current schema version as a `u32`.
-    pub fn get_schema_version(env: Env) -> u32 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::SchemaVersion))
-            .unwrap_or(INITIAL_SCHEMA_VERSION)
+        // Update customer analytics
+        let mut c_analytics: CustomerAnalytics = env
+            .storage()
+            .instance()
+            .get(&DataKey::Customer(CustomerDataKey::Analytics(
+                payment.customer.clone(),
+            )))
+            .unwrap_or(CustomerAnalytics {
+                total_payments: 0,
+                total_volume: 0,
+                total_refunds: 0,
+                avg_transaction_size: 0,
+                peak_hour: 0,
+                top_merchant: None,
+                top_merchant_volume: 0,
+                first_payment_at: 0,
+                last_payment_at: 0,
+            });
+        c_analytics.total_payments += 1;
+        c_analytics.total_volume += amount;
+        c_analytics.avg_transaction_size =
+            c_analytics.total_volume / (c_analytics.total_payments as i128);
+        if c_analytics.first_payment_at == 0 {
+            c_analytics.first_payment_at = current_timestamp;
+        }
+        c_analytics.last_payment_at = current_timestamp;
+
+        // Track peak hour (UTC hour 0-23)
+        let hour = ((current_timestamp / 3600) % 24) as u32;
+        let hour_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Customer(CustomerDataKey::HourCount(
+                payment.customer.clone(),
+                hour,
+            )))
+            .unwrap_or(0)
+            + 1;
+        env.storage().instance().set(
+            &DataKey::Customer(CustomerDataKey::HourCount(payment.customer.clone(), hour)),
+            &hour_count,
+        );
+        let peak_hour_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Customer(CustomerDataKey::HourCount(
+                payment.customer.clone(),
+                c_analytics.peak_hour,
+            )))
+            .unwrap_or(0);
+        if hour_count > peak_hour_count
+            || (hour_count == peak_hour_count && hour == c_analytics.peak_hour)
+        {
+            c_analytics.peak_hour = hour;
+        }
+
+        // Track per-merchant volume and update top merchant
+        let prev_merchant_vol: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Customer(CustomerDataKey::MerchantVolume(
+                payment.customer.clone(),
+                payment.merchant.clone(),
+            )))
+            .unwrap_or(0);
+        if prev_merchant_vol == 0 {
+            // New merchant for this customer — add to list
+            let m_count: u64 = record_get(
+                env,
+                &DataKey::Customer(CustomerDataKey::MerchantCount(payment.customer.clone())),
+            )
+            .unwrap_or(0);
+            record_set(
+                env,
+                &DataKey::Customer(CustomerDataKey::MerchantList(
+                    payment.customer.clone(),
+                    m_count,
+                )),
+                &payment.merchant,
+            );
+            record_set(
+                env,
+                &DataKey::Customer(CustomerDataKey::MerchantCount(payment.customer.clone())),
+                &(m_count + 1),
+            );
+        }
+        let new_merchant_vol = prev_merchant_vol + amount;
+        env.storage().instance().set(
+            &DataKey::Customer(CustomerDataKey::MerchantVolume(
+                payment.customer.clone(),
+                payment.merchant.clone(),
+            )),
+            &new_merchant_vol,
+        );
+        if new_merchant_vol > c_analytics.top_merchant_volume {
+            c_analytics.top_merchant = Some(payment.merchant.clone());
+            c_analytics.top_merchant_volume = new_merchant_vol;
+        }
+
+        // Track monthly volume (30-day bucket)
+        let month_bucket = (current_timestamp / 2_592_000) * 2_592_000;
+        let prev_monthly: i128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Customer(CustomerDataKey::MonthlyVolume(
+                payment.customer.clone(),
+                month_bucket,
+            )))
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::Customer(CustomerDataKey::MonthlyVolume(
+                payment.customer.clone(),
+                month_bucket,
+            )),
+            &(prev_monthly + amount),
+        );
+
+        env.storage().instance().set(
+            &DataKey::Customer(CustomerDataKey::Analytics(payment.customer.clone())),
+            &c_analytics,
+        );
+
+        PaymentContract::update_merchant_bucket(
+            env,
+            payment.merchant.clone(),
+            current_timestamp,
+            1,
+            amount,
+            0,
+            0,
+        );
+        PaymentContract::update_platform_daily_bucket(env, current_timestamp, amount, 0, 0);
+
+        (PaymentCreated {
+            payment_id,
+            customer: payment.customer,
+            merchant: payment.merchant.clone(),
+            amount: payment.amount,
+        })
+        .publish(&env);
+
+        Ok(payment_id)
     }
 
-    /// Migrates the contract storage schema to a target version.
+    /// Retrieves a payment by its ID.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing the migration (must be in the multisig admin list)
-    /// * `target_version` - The schema version to migrate to
+    /// * `payment_id` - The ID of the payment to retrieve
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin, the target version
-    /// is not greater than the current version, or multisig is not initialized.
-    pub fn migrate_schema(env: Env, admin: Address, target_version: u32) -> Result<(), Error> {
-        admin.require_auth();
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
-        }
+    /// The `Payment` record.
+    ///
+    /// # Panics
+    /// Panics if the payment is not found.
+    pub fn get_payment(env: &Env, payment_id: u64) -> Payment {
+        record_get(env, &DataKey::Payment(PaymentKey::Data(payment_id))).expect("Payment not found")
+    }
 
-        let current = Self::get_schema_version(env.clone());
-        if current >= target_version {
-            return Err(Error::Basic(BasicError::SchemaAlreadyAtTarget));
-        }
+    /// Returns when the payment completed, or `None` if its funds were never collected.
+    ///
+    /// A payment keeps this timestamp after it is refunded, so a `PartialRefunded` or
+    /// `Refunded` payment without one was refunded while still pending.
+    pub fn get_payment_completed_at(env: Env, payment_id: u64) -> Option<u64> {
+        record_get(&env, &DataKey::Payment(PaymentKey::CompletedAt(payment_id)))
+    }
 
-        env.storage().instance().set(
-            &DataKey::Config(ConfigKey::SchemaVersion),
-            &target_version,
+    fn mark_completed(env: &Env, payment_id: u64) {
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::CompletedAt(payment_id)),
+            &env.ledger().timestamp(),
         );
+    }
+
+    /// Fails with `InvalidStatus` unless the payment's funds were collected.
+    fn require_completed(env: &Env, payment_id: u64) -> Result<(), Error> {
+        if !record_has(env, &DataKey::Payment(PaymentKey::CompletedAt(payment_id))) {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
         Ok(())
     }
 
-    /// Sets the verification level for a specific merchant.
+    /// Used by the refund contract for cross-contract ownership verification (#143).
+    /// Returns true if the payment exists, belongs to `customer`, is Completed or
+    /// PartialRefunded, and its funds were collected.
+    pub fn check_payment_customer(env: Env, payment_id: u64, customer: Address) -> bool {
+        let payment: Option<Payment> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)));
+        match payment {
+            Some(p) => {
+                p.customer == customer
+                    && matches!(
+                        p.status,
+                        PaymentStatus::Completed | PaymentStatus::PartialRefunded
+                    )
+                    && Self::require_completed(&env, payment_id).is_ok()
+            }
+            None => false,
+        }
+    }
+
+    /// Creates a payment backed by an escrow contract for dispute protection.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
-    /// * `merchant` - The merchant whose verification level is being set
-    /// * `level` - The new verification level to assign
+    /// * `customer` - The customer making the payment
+    /// * `merchant` - The merchant receiving the payment
+    /// * `amount` - The payment amount in base token units
+    /// * `token` - The SPL token address for the payment
+    /// * `currency` - The fiat currency associated with the payment
+    /// * `escrow_contract` - The address of the escrow contract to hold funds
+    /// * `release_timestamp` - The timestamp when funds can be released from escrow
+    /// * `min_hold_period` - Minimum time funds must remain in escrow
+    /// * `metadata` - Arbitrary metadata string for the payment
+    /// * `auto_release_on_complete` - Whether to auto-release escrow when payment completes
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
-    pub fn set_merchant_verification_level(
+    /// `Ok((payment_id, escrow_id))` on success, or an error if the underlying payment
+    /// creation fails, the escrow contract invocation fails, or the token transfer fails.
+    pub fn create_escrowed_payment(
         env: Env,
-        admin: Address,
+        customer: Address,
         merchant: Address,
-        level: MerchantVerificationLevel,
-    ) -> Result<(), Error> {
-        admin.require_auth();
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
-        }
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        escrow_contract: Address,
+        release_timestamp: u64,
+        min_hold_period: u64,
+        metadata: String,
+        auto_release_on_complete: bool,
+    ) -> Result<(u64, u64), Error> {
+        Self::require_merchant_not_paused(&env, &merchant)?;
+        let payment_id = PaymentContract::create_payment(
+            env.clone(),
+            customer.clone(),
+            merchant.clone(),
+            amount,
+            token.clone(),
+            currency,
+            0,
+            metadata,
+        )?;
 
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::VerificationLevel(merchant)),
-            &level,
+        let escrow_id = PaymentContract::invoke_escrow_create(
+            &env,
+            &escrow_contract,
+            &customer,
+            &merchant,
+            amount,
+            &token,
+            release_timestamp,
+            min_hold_period,
+        )?;
+
+        let bridge = EscrowedPayment {
+            payment_id,
+            escrow_id,
+            escrow_contract: escrow_contract.clone(),
+            auto_release_on_complete,
+        };
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::EscrowedPayment(payment_id)),
+            &bridge,
         );
 
-        Ok(())
-    }
+        (EscrowedPaymentCreated {
+            payment_id,
+            escrow_id,
+            escrow_contract,
+        })
+        .publish(&env);
 
-    /// Retrieves the verification level for a given merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
-    ///
-    /// # Returns
-    /// The merchant's verification level, defaulting to `Unverified` if not set.
-    pub fn get_merchant_verification_level(
-        env: Env,
-        merchant: Address,
-    ) -> MerchantVerificationLevel {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::VerificationLevel(
-                merchant,
-            )))
-            .unwrap_or(MerchantVerificationLevel::Unverified)
+        Ok((payment_id, escrow_id))
     }
 
-    /// Configures the transaction limits for a specific verification tier.
+    /// Completes an escrowed payment by releasing funds from the escrow contract.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
-    /// * `limits` - The tier limits to store, including the tier level and its associated limits
+    /// * `admin` - The admin authorizing the completion (must be in the multisig admin list)
+    /// * `payment_id` - The ID of the escrowed payment to complete
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
-    pub fn set_verification_tier_limits(
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
+    /// found, is not in Pending status, has an unresolved dispute, or the escrow release fails.
+    pub fn complete_escrowed_payment(
         env: Env,
         admin: Address,
-        limits: VerificationTierLimits,
+        payment_id: u64,
     ) -> Result<(), Error> {
         admin.require_auth();
         let config: MultiSigConfig = env
@@ -1965,889 +5118,1516 @@ impl PaymentContract {
         if !config.admins.contains(&admin) {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
 
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::VerificationTierLimit(limits.level.clone())),
-            &limits,
+        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        Self::require_merchant_not_paused(&env, &payment.merchant)?;
+        PaymentContract::require_no_unresolved_escrowed_payment_dispute(&env, payment_id)?;
+
+        // Released as this contract's own identity (not the human `admin`),
+        // so the escrow contract can recognize it as a registered trusted
+        // bridge and allow an immediate release. See
+        // EscrowContract::add_trusted_bridge / is_trusted_bridge.
+        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
+        if escrow_client
+            .try_release_escrow(
+                &env.current_contract_address(),
+                &bridge.escrow_id,
+                &bridge.auto_release_on_complete,
+            )
+            .is_err()
+        {
+            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+        }
+
+        payment.status = PaymentStatus::Completed;
+        Self::mark_completed(&env, payment_id);
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
         );
 
+        (EscrowedPaymentCompleted {
+            payment_id,
+            escrow_id: bridge.escrow_id,
+        })
+        .publish(&env);
         Ok(())
     }
 
-    /// Retrieves the transaction limits configured for a given verification tier.
+    /// Cancels an escrowed payment and refunds the escrowed funds to the customer.
     ///
     /// # Arguments
-    /// * `level` - The verification tier level to query
+    /// * `caller` - The customer or merchant who authorized this payment (must authorize)
+    /// * `payment_id` - The ID of the escrowed payment to cancel
     ///
     /// # Returns
-    /// `Some(VerificationTierLimits)` if configured for the tier, or `None` otherwise.
-    pub fn get_tier_limits(
+    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
+    /// status, the caller is not the customer or merchant, has an unresolved dispute,
+    /// or the escrow refund fails.
+    pub fn cancel_escrowed_payment(
         env: Env,
-        level: MerchantVerificationLevel,
-    ) -> Option<VerificationTierLimits> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::VerificationTierLimit(
-                level,
-            )))
-    }
+        caller: Address,
+        payment_id: u64,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
+
+        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        if payment.customer != caller && payment.merchant != caller {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        PaymentContract::require_no_unresolved_escrowed_payment_dispute(&env, payment_id)?;
+
+        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
+        if escrow_client
+            .try_refund_escrow(&caller, &bridge.escrow_id)
+            .is_err()
+        {
+            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+        }
 
-    /// Returns the current multisig configuration.
-    ///
-    /// # Returns
-    /// The `MultiSigConfig` containing admin list, required signatures, and proposal TTL.
-    ///
-    /// # Panics
-    /// Panics if the multisig configuration has not been initialized.
-    pub fn get_multisig_config(env: Env) -> MultiSigConfig {
-        env.storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .expect("MultiSig not initialized")
+        payment.status = PaymentStatus::Cancelled;
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+
+        (EscrowedPaymentCancelled {
+            payment_id,
+            escrow_id: bridge.escrow_id,
+        })
+        .publish(&env);
+        Ok(())
     }
 
-    /// Proposes a new admin action for multisig approval.
+    /// Raises a dispute on an escrowed payment, freezing the escrow funds.
     ///
     /// # Arguments
-    /// * `proposer` - The admin proposing the action (must be in the multisig admin list)
-    /// * `action_type` - The type of action being proposed
-    /// * `target` - The target address for the action
-    /// * `data` - Arbitrary data payload for the action
+    /// * `caller` - The customer or merchant raising the dispute (must authorize)
+    /// * `payment_id` - The ID of the escrowed payment to dispute
+    /// * `reason` - A text description of the dispute reason
     ///
     /// # Returns
-    /// `Ok(proposal_id)` on success, or an error if the proposer is not an admin
-    /// or multisig is not initialized.
-    pub fn propose_action(
+    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
+    /// status, the caller is not the customer or merchant, there is already an unresolved
+    /// dispute, or the escrow dispute invocation fails.
+    pub fn dispute_escrowed_payment(
         env: Env,
-        proposer: Address,
-        action_type: ActionType,
-        target: Address,
-        data: Bytes,
-    ) -> Result<String, Error> {
-        proposer.require_auth();
-
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        if !config.admins.contains(&proposer) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+        caller: Address,
+        payment_id: u64,
+        reason: String,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
         }
 
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::LargePaymentCounter))
-            .unwrap_or(0)
-            + 1;
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::LargePaymentCounter), &counter);
-
-        let proposal_id = PaymentContract::u64_to_string(&env, counter);
-        let now = env.ledger().timestamp();
+        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        if payment.customer != caller && payment.merchant != caller {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if let Some(dispute) =
+            PaymentContract::get_escrowed_payment_dispute(env.clone(), payment_id)
+        {
+            if !dispute.resolved {
+                return Err(Error::Payment(PaymentError::AlreadyProcessed));
+            }
+        }
 
-        let mut approvals = Vec::new(&env);
-        approvals.push_back(proposer.clone());
+        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
+        if escrow_client
+            .try_dispute_escrow(&caller, &bridge.escrow_id)
+            .is_err()
+        {
+            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+        }
 
-        let proposal = AdminProposal {
-            id: proposal_id.clone(),
-            proposer: proposer.clone(),
-            action_type: action_type.clone(),
-            target,
-            data,
-            approvals,
-            approval_count: 1,
-            executed: false,
-            rejected: false,
-            created_at: now,
-            expires_at: now + config.proposal_ttl,
+        let dispute = EscrowedPaymentDispute {
+            payment_id,
+            raised_by: caller.clone(),
+            reason,
+            raised_at: env.ledger().timestamp(),
+            resolved: false,
+            resolved_at: None,
+            favor_customer: None,
         };
-
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
-            &proposal,
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
+            &dispute,
         );
 
-        (ActionProposed {
-            proposal_id: proposal_id.clone(),
-            proposer,
-            action_type,
+        (EscrowedPaymentDisputed {
+            payment_id,
+            raised_by: caller,
         })
         .publish(&env);
-
-        Ok(proposal_id)
+        Ok(())
     }
 
-    /// Approves a pending admin proposal.
+    /// Resolves an escrowed payment dispute by ruling in favor of the customer or merchant.
     ///
     /// # Arguments
-    /// * `approver` - The admin approving the proposal (must be in the multisig admin list)
-    /// * `proposal_id` - The ID of the proposal to approve
+    /// * `admin` - The admin resolving the dispute (must be in the multisig admin list)
+    /// * `payment_id` - The ID of the escrowed payment with the dispute
+    /// * `favor_customer` - If true, funds are returned to the customer; otherwise, released to merchant
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the approver is not an admin, the proposal
-    /// is not found, has already been executed/rejected, is expired, or was already approved
-    /// by this admin.
-    pub fn approve_action(env: Env, approver: Address, proposal_id: String) -> Result<(), Error> {
-        approver.require_auth();
-
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
+    /// found, the dispute is already resolved, the payment is not in Pending status,
+    /// or the escrow resolution fails.
+    pub fn resolve_escrowed_payment_dispute(
+        env: Env,
+        admin: Address,
+        payment_id: u64,
+        favor_customer: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
         let config: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        if !config.admins.contains(&approver) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
         }
 
-        let mut proposal: AdminProposal = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::AdminProposal(
-                proposal_id.clone(),
-            )))
-            .ok_or(Error::Proposal(ProposalError::NotFound))?;
-
-        if proposal.executed || proposal.rejected {
-            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
+        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
+        let mut dispute = PaymentContract::get_escrowed_payment_dispute(env.clone(), payment_id)
+            .ok_or(Error::Payment(PaymentError::InvalidStatus))?;
+        if dispute.resolved {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
         }
 
-        if env.ledger().timestamp() > proposal.expires_at {
-            return Err(Error::Proposal(ProposalError::Expired));
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
         }
+        Self::require_merchant_not_paused(&env, &payment.merchant)?;
 
-        if proposal.approvals.contains(&approver) {
-            return Err(Error::Basic(BasicError::AlreadyApproved));
+        let release_to_merchant = !favor_customer;
+        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
+        if escrow_client
+            .try_resolve_dispute(&admin, &bridge.escrow_id, &release_to_merchant)
+            .is_err()
+        {
+            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
         }
 
-        proposal.approvals.push_back(approver.clone());
-        proposal.approval_count += 1;
+        if favor_customer {
+            payment.status = PaymentStatus::Refunded;
+            payment.refunded_amount = payment.amount;
+        } else {
+            payment.status = PaymentStatus::Completed;
+            Self::mark_completed(&env, payment_id);
+        }
+        dispute.resolved = true;
+        dispute.resolved_at = Some(env.ledger().timestamp());
+        dispute.favor_customer = Some(favor_customer);
 
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
-            &proposal,
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+        record_set(
+            &env,
+            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
+            &dispute,
         );
 
-        (ActionApproved {
-            proposal_id,
-            approver,
-            approval_count: proposal.approval_count,
+        (PaymentDisputeResolved {
+            payment_id,
+            favor_customer,
         })
         .publish(&env);
+        Ok(())
+    }
+
+    /// Retrieves the escrowed payment bridge record for a given payment.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the payment to look up
+    ///
+    /// # Returns
+    /// `Ok(EscrowedPayment)` on success, or an error if the escrow mapping is not found.
+    pub fn get_escrowed_payment(env: Env, payment_id: u64) -> Result<EscrowedPayment, Error> {
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::EscrowedPayment(payment_id)),
+        )
+        .ok_or(Error::Feature(FeatureError::EscrowMappingNotFound))
+    }
+
+    /// Retrieves the dispute record for an escrowed payment, if one exists.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the payment to check for disputes
+    ///
+    /// # Returns
+    /// `Some(EscrowedPaymentDispute)` if a dispute record exists, or `None` otherwise.
+    pub fn get_escrowed_payment_dispute(
+        env: Env,
+        payment_id: u64,
+    ) -> Option<EscrowedPaymentDispute> {
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
+        )
+    }
 
+    fn require_no_unresolved_escrowed_payment_dispute(
+        env: &Env,
+        payment_id: u64,
+    ) -> Result<(), Error> {
+        if let Some(dispute) = record_get::<DataKey, EscrowedPaymentDispute>(
+            env,
+            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
+        ) {
+            if !dispute.resolved {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+        }
         Ok(())
     }
 
-    /// Executes a fully approved admin proposal.
-    ///
-    /// # Arguments
-    /// * `proposal_id` - The ID of the proposal to execute
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the proposal is not found, has already been
-    /// executed/rejected, is expired, or has not reached the required signature threshold.
-    pub fn execute_action(env: Env, proposal_id: String) -> Result<(), Error> {
+    /// Sets how long after a payment is created its customer can open a
+    /// chargeback. Defaults to `DEFAULT_CHARGEBACK_WINDOW`.
+    pub fn set_chargeback_window(
+        env: Env,
+        admin: Address,
+        window_seconds: u64,
+    ) -> Result<(), Error> {
+        admin.require_auth();
         let config: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        let mut proposal: AdminProposal = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::AdminProposal(
-                proposal_id.clone(),
-            )))
-            .ok_or(Error::Proposal(ProposalError::NotFound))?;
-
-        if proposal.executed || proposal.rejected {
-            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
-        }
-
-        if env.ledger().timestamp() > proposal.expires_at {
-            return Err(Error::Proposal(ProposalError::Expired));
-        }
-
-        if proposal.approval_count < config.required_signatures {
-            return Err(Error::Proposal(ProposalError::ThresholdNotMet));
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
-
-        proposal.executed = true;
         env.storage().instance().set(
-            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
-            &proposal,
+            &DataKey::Config(ConfigKey::ChargebackWindow),
+            &window_seconds,
         );
-
-        PaymentContract::dispatch_action(&env, &proposal)?;
-
-        (ActionExecuted { proposal_id }).publish(&env);
-
         Ok(())
     }
 
-    /// Rejects a pending admin proposal, preventing its execution.
+    /// Returns the chargeback window in seconds.
+    pub fn get_chargeback_window(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::ChargebackWindow))
+            .unwrap_or(DEFAULT_CHARGEBACK_WINDOW)
+    }
+
+    /// Opens a chargeback on a completed payment.
+    ///
+    /// The disputed amount is held back from the payment's pending settlement
+    /// and then from the merchant's accumulated payout balance, as far as
+    /// those cover it, until an admin resolves the chargeback.
     ///
     /// # Arguments
-    /// * `rejecter` - The admin rejecting the proposal (must be in the multisig admin list)
-    /// * `proposal_id` - The ID of the proposal to reject
+    /// * `customer` - The payment's customer (must authorize)
+    /// * `payment_id` - The completed payment to dispute
+    /// * `amount` - The disputed amount, at most the amount not yet refunded
+    /// * `reason` - A text description of the dispute reason
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the rejecter is not an admin, the proposal
-    /// is not found, or has already been executed/rejected.
-    pub fn reject_action(env: Env, rejecter: Address, proposal_id: String) -> Result<(), Error> {
-        rejecter.require_auth();
-
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        if !config.admins.contains(&rejecter) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+    /// `Ok(())` on success, or an error if the payment never completed, the
+    /// caller is not its customer, the chargeback window has closed, the
+    /// amount is invalid, or the payment already has a chargeback.
+    pub fn open_chargeback(
+        env: Env,
+        customer: Address,
+        payment_id: u64,
+        amount: i128,
+        reason: String,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, "open_chargeback")?;
+        customer.require_auth();
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.customer != customer {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if !matches!(
+            payment.status,
+            PaymentStatus::Completed | PaymentStatus::PartialRefunded
+        ) {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        Self::require_completed(&env, payment_id)?;
+        let now = env.ledger().timestamp();
+        if now > payment.created_at + Self::get_chargeback_window(env.clone()) {
+            return Err(Error::Payment(PaymentError::ChargebackWindowClosed));
+        }
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if amount > payment.amount - payment.refunded_amount {
+            return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+        }
+        if reason.len() > MAX_METADATA_SIZE {
+            return Err(Error::Basic(BasicError::MetadataTooLarge));
+        }
+        if record_has(&env, &DataKey::Payment(PaymentKey::Chargeback(payment_id))) {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
         }
 
-        let mut proposal: AdminProposal = env
+        let mut held_from_settlement = 0;
+        if !record_has(
+            &env,
+            &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+        ) {
+            if let Some(mut settlement) = record_get::<DataKey, PendingSettlement>(
+                &env,
+                &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+            ) {
+                held_from_settlement = settlement.amount.min(amount);
+                settlement.amount -= held_from_settlement;
+                record_set(
+                    &env,
+                    &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                    &settlement,
+                );
+            }
+        }
+        let mut held_from_balance = 0;
+        let schedule_key =
+            DataKey::Merchant(MerchantDataKey::PayoutSchedule(payment.merchant.clone()));
+        if let Some(mut schedule) = env
             .storage()
             .instance()
-            .get(&DataKey::State(StateDataKey::AdminProposal(
-                proposal_id.clone(),
-            )))
-            .ok_or(Error::Proposal(ProposalError::NotFound))?;
-
-        if proposal.executed || proposal.rejected {
-            return Err(Error::Proposal(ProposalError::AlreadyExecuted));
+            .get::<DataKey, PayoutSchedule>(&schedule_key)
+        {
+            if schedule.token == payment.token {
+                held_from_balance = schedule
+                    .accumulated
+                    .min(amount - held_from_settlement)
+                    .max(0);
+                schedule.accumulated -= held_from_balance;
+                env.storage().instance().set(&schedule_key, &schedule);
+            }
         }
 
-        proposal.rejected = true;
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::AdminProposal(proposal_id.clone())),
-            &proposal,
+        let chargeback = Chargeback {
+            payment_id,
+            customer: customer.clone(),
+            merchant: payment.merchant,
+            token: payment.token,
+            amount,
+            reason,
+            status: ChargebackStatus::Open,
+            opened_at: now,
+            evidence_hash: None,
+            held_from_settlement,
+            held_from_balance,
+            resolved_at: 0,
+        };
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Chargeback(payment_id)),
+            &chargeback,
         );
 
-        (ActionRejected {
-            proposal_id,
-            rejected_by: rejecter,
+        (ChargebackOpened {
+            payment_id,
+            customer,
+            amount,
+            held: held_from_settlement + held_from_balance,
         })
         .publish(&env);
-
         Ok(())
     }
 
-    /// Adds a new admin to the multisig configuration.
+    /// Submits the merchant's representment evidence for an open chargeback.
     ///
     /// # Arguments
-    /// * `caller` - The existing admin adding the new admin (must be in the multisig admin list)
-    /// * `new_admin` - The address of the new admin to add
+    /// * `merchant` - The payment's merchant (must authorize)
+    /// * `payment_id` - The disputed payment
+    /// * `evidence_hash` - Hash of the off-chain evidence bundle
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
-    /// No-op if the address is already an admin.
-    pub fn add_admin(env: Env, caller: Address, new_admin: Address) -> Result<(), Error> {
-        caller.require_auth();
-
-        let mut config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        if !config.admins.contains(&caller) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+    /// `Ok(())` on success, or an error if there is no chargeback, the caller
+    /// is not the merchant, the chargeback is resolved, or
+    /// `CHARGEBACK_RESPONSE_PERIOD` has passed since it was opened.
+    pub fn submit_chargeback_evidence(
+        env: Env,
+        merchant: Address,
+        payment_id: u64,
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        let mut chargeback = Self::get_chargeback(env.clone(), payment_id)
+            .ok_or(Error::Payment(PaymentError::ChargebackNotFound))?;
+        if chargeback.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
-
-        if !config.admins.contains(&new_admin) {
-            config.admins.push_back(new_admin.clone());
-            config.total_admins += 1;
-            env.storage()
-                .instance()
-                .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
-            (AdminAdded { admin: new_admin }).publish(&env);
+        if !matches!(
+            chargeback.status,
+            ChargebackStatus::Open | ChargebackStatus::Represented
+        ) {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        if env.ledger().timestamp() > chargeback.opened_at + CHARGEBACK_RESPONSE_PERIOD {
+            return Err(Error::Payment(PaymentError::Expired));
         }
 
+        chargeback.status = ChargebackStatus::Represented;
+        chargeback.evidence_hash = Some(evidence_hash.clone());
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Chargeback(payment_id)),
+            &chargeback,
+        );
+
+        (ChargebackRepresented {
+            payment_id,
+            merchant,
+            evidence_hash,
+        })
+        .publish(&env);
         Ok(())
     }
 
-    /// Removes an admin from the multisig configuration.
+    /// Decides a chargeback.
+    ///
+    /// In the customer's favour, the held funds are returned to the customer
+    /// and the payment's `refunded_amount` grows by the disputed amount, capped
+    /// at what is still unrefunded; any held funds above that cap go back to the
+    /// merchant. Any part not covered by held funds is drawn from the merchant's
+    /// rolling reserve, and what is still missing becomes a `ChargebackDebt`
+    /// recovered from the merchant's later settlements. In the merchant's favour, the held
+    /// funds go back to the pending settlement or accumulated balance.
     ///
     /// # Arguments
-    /// * `caller` - The existing admin performing the removal (must be in the multisig admin list)
-    /// * `admin` - The address of the admin to remove
+    /// * `admin` - The admin deciding (must be in the multisig admin list)
+    /// * `payment_id` - The disputed payment
+    /// * `favor_customer` - Whether the customer wins
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin, the target is not
-    /// an admin, removing them would leave fewer admins than required signatures, or multisig
-    /// is not initialized.
-    pub fn remove_admin(env: Env, caller: Address, admin: Address) -> Result<(), Error> {
-        caller.require_auth();
-
-        let mut config: MultiSigConfig = env
+    /// `Ok(())` on success, or an error if the admin is unauthorized, there is
+    /// no chargeback, or it is already resolved.
+    pub fn resolve_chargeback(
+        env: Env,
+        admin: Address,
+        payment_id: u64,
+        favor_customer: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        let mut chargeback = Self::get_chargeback(env.clone(), payment_id)
+            .ok_or(Error::Payment(PaymentError::ChargebackNotFound))?;
+        if !matches!(
+            chargeback.status,
+            ChargebackStatus::Open | ChargebackStatus::Represented
+        ) {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        }
 
-        if !config.admins.contains(&caller) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+        let held = chargeback.held_from_settlement + chargeback.held_from_balance;
+        let mut refunded = 0;
+        let mut merchant_debt = 0;
+        if favor_customer {
+            let mut payment = PaymentContract::get_payment(&env, payment_id);
+            let payable = chargeback
+                .amount
+                .min(payment.amount - payment.refunded_amount)
+                .max(0);
+            let returned = held.min(payable);
+            if returned > 0 {
+                token::Client::new(&env, &chargeback.token).transfer(
+                    &env.current_contract_address(),
+                    &chargeback.customer,
+                    &returned,
+                );
+            }
+            if held > returned {
+                Self::settle_or_accumulate(
+                    &env,
+                    chargeback.merchant.clone(),
+                    chargeback.token.clone(),
+                    held - returned,
+                )?;
+            }
+            let drawn = Self::draw_reserve(
+                &env,
+                &chargeback.merchant,
+                &chargeback.token,
+                payable - returned,
+                &chargeback.customer,
+            );
+            refunded = returned + drawn;
+            merchant_debt = payable - refunded;
+            if merchant_debt > 0 {
+                let key = DataKey::Merchant(MerchantDataKey::ChargebackDebts(
+                    chargeback.merchant.clone(),
+                ));
+                let mut debts: Vec<ChargebackDebt> =
+                    record_get(&env, &key).unwrap_or_else(|| Vec::new(&env));
+                debts.push_back(ChargebackDebt {
+                    payment_id,
+                    customer: chargeback.customer.clone(),
+                    token: chargeback.token.clone(),
+                    amount: merchant_debt,
+                });
+                record_set(&env, &key, &debts);
+            }
+
+            payment.refunded_amount += payable;
+            payment.status = if payment.refunded_amount >= payment.amount {
+                PaymentStatus::Refunded
+            } else {
+                PaymentStatus::PartialRefunded
+            };
+            record_set(
+                &env,
+                &DataKey::Payment(PaymentKey::Data(payment_id)),
+                &payment,
+            );
+            chargeback.status = ChargebackStatus::CustomerWon;
+        } else {
+            let mut release = chargeback.held_from_balance;
+            if chargeback.held_from_settlement > 0 {
+                let finalized = record_has(
+                    &env,
+                    &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+                );
+                let settlement = record_get::<DataKey, PendingSettlement>(
+                    &env,
+                    &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                );
+                match settlement {
+                    Some(mut settlement) if !finalized => {
+                        settlement.amount += chargeback.held_from_settlement;
+                        record_set(
+                            &env,
+                            &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                            &settlement,
+                        );
+                    }
+                    _ => release += chargeback.held_from_settlement,
+                }
+            }
+            if release > 0 {
+                Self::settle_or_accumulate(
+                    &env,
+                    chargeback.merchant.clone(),
+                    chargeback.token.clone(),
+                    release,
+                )?;
+            }
+            chargeback.status = ChargebackStatus::MerchantWon;
         }
 
-        if config.total_admins <= config.required_signatures {
-            return Err(Error::Basic(BasicError::InsufficientAdmins));
+        chargeback.resolved_at = env.ledger().timestamp();
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Chargeback(payment_id)),
+            &chargeback,
+        );
+
+        (ChargebackResolved {
+            payment_id,
+            favor_customer,
+            refunded,
+            merchant_debt,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the chargeback on a payment, if one was opened.
+    pub fn get_chargeback(env: Env, payment_id: u64) -> Option<Chargeback> {
+        record_get(&env, &DataKey::Payment(PaymentKey::Chargeback(payment_id)))
+    }
+
+    /// Fails with `ChargebackPending` while the payment has an undecided
+    /// chargeback, so the disputed funds cannot also be refunded.
+    fn require_no_pending_chargeback(env: &Env, payment_id: u64) -> Result<(), Error> {
+        match record_get::<DataKey, Chargeback>(
+            env,
+            &DataKey::Payment(PaymentKey::Chargeback(payment_id)),
+        ) {
+            Some(chargeback)
+                if matches!(
+                    chargeback.status,
+                    ChargebackStatus::Open | ChargebackStatus::Represented
+                ) =>
+            {
+                Err(Error::Payment(PaymentError::ChargebackPending))
+            }
+            _ => Ok(()),
         }
+    }
 
-        let mut new_admins = Vec::new(&env);
-        for a in config.admins.iter() {
-            if a != admin {
-                new_admins.push_back(a);
+    /// Returns the merchant's outstanding chargeback debts.
+    pub fn get_chargeback_debts(env: Env, merchant: Address) -> Vec<ChargebackDebt> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::ChargebackDebts(merchant)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Pays the merchant's chargeback debts in `token` out of a settlement of
+    /// `amount`, oldest first. Returns what is left for the merchant.
+    fn recover_chargeback_debts(
+        env: &Env,
+        merchant: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> i128 {
+        let key = DataKey::Merchant(MerchantDataKey::ChargebackDebts(merchant.clone()));
+        let debts: Vec<ChargebackDebt> = match record_get(env, &key) {
+            Some(debts) => debts,
+            None => return amount,
+        };
+
+        let mut available = amount;
+        let mut outstanding = Vec::new(env);
+        for mut debt in debts.iter() {
+            if debt.token == *token && available > 0 {
+                let recovered = debt.amount.min(available);
+                token::Client::new(env, token).transfer(
+                    &env.current_contract_address(),
+                    &debt.customer,
+                    &recovered,
+                );
+                available -= recovered;
+                debt.amount -= recovered;
+                (ChargebackDebtRecovered {
+                    payment_id: debt.payment_id,
+                    merchant: merchant.clone(),
+                    amount: recovered,
+                    outstanding: debt.amount,
+                })
+                .publish(env);
+            }
+            if debt.amount > 0 {
+                outstanding.push_back(debt);
             }
         }
-
-        if new_admins.len() == config.admins.len() {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+        if outstanding.is_empty() {
+            record_remove(env, &key);
+        } else {
+            record_set(env, &key, &outstanding);
         }
-
-        config.admins = new_admins;
-        config.total_admins -= 1;
-        env.storage()
-            .instance()
-            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
-        (AdminRemoved { admin }).publish(&env);
-
-        Ok(())
+        available
     }
 
-    /// Updates the required number of admin signatures for multisig proposals.
+    /// Configures the rolling reserve withheld from merchants at a verification tier.
     ///
     /// # Arguments
-    /// * `caller` - The admin updating the requirement (must be in the multisig admin list)
-    /// * `required` - The new number of required signatures (must be > 0 and <= total admins)
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `tier` - The reserve parameters, keyed by `tier.level`
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the caller is not an admin, the required count
-    /// is invalid, or multisig is not initialized.
-    pub fn update_required_signatures(
-        env: Env,
-        caller: Address,
-        required: u32,
-    ) -> Result<(), Error> {
-        caller.require_auth();
-
-        let mut config: MultiSigConfig = env
+    /// `Ok(())` on success, or an error if the caller is not an admin, `reserve_bps`
+    /// exceeds 10000, or a non-zero reserve has no hold period.
+    pub fn set_reserve_tier(env: Env, admin: Address, tier: ReserveTier) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-
-        if !config.admins.contains(&caller) {
-            return Err(Error::Basic(BasicError::NotAnAdmin));
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
-
-        if required == 0 || required > config.total_admins {
-            return Err(Error::Basic(BasicError::InsufficientAdmins));
+        if tier.reserve_bps > 10000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        if tier.hold_seconds == 0 && (tier.reserve_bps > 0 || tier.risk_weight_pct > 0) {
+            return Err(Error::Basic(BasicError::InvalidInterval));
         }
 
-        config.required_signatures = required;
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::ReserveTier(tier.level.clone())),
+            &tier,
+        );
+        Ok(())
+    }
+
+    /// Returns the rolling reserve configured for a verification tier, if any.
+    pub fn get_reserve_tier(env: Env, level: MerchantVerificationLevel) -> Option<ReserveTier> {
         env.storage()
             .instance()
-            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
-
-        Ok(())
+            .get(&DataKey::Merchant(MerchantDataKey::ReserveTier(level)))
     }
 
-    /// Creates a new payment from a customer to a merchant.
+    /// Releases the merchant's reserve entries whose hold period has ended.
+    ///
+    /// Anyone may call this; released funds are settled to the merchant like a
+    /// completed payment, so they also repay chargeback debts and respect the
+    /// merchant's payout schedule.
     ///
     /// # Arguments
-    /// * `customer` - The customer making the payment (must authorize)
-    /// * `merchant` - The merchant receiving the payment
-    /// * `amount` - The payment amount in base token units
-    /// * `token` - The SPL token address for the payment
-    /// * `currency` - The fiat currency associated with the payment
-    /// * `expiration_duration` - Seconds until the payment expires (0 for no expiration)
-    /// * `metadata` - Arbitrary metadata string for the payment
+    /// * `merchant` - The merchant whose reserve to release
     ///
     /// # Returns
-    /// `Ok(payment_id)` on success, or an error if the token is not allowed, currency is
-    /// invalid, metadata is too large, the customer is flagged, rate limits are exceeded,
-    /// or the contract is paused.
-    pub fn create_payment(
-        env: Env,
-        customer: Address,
-        merchant: Address,
-        amount: i128,
-        token: Address,
-        currency: Currency,
-        expiration_duration: u64,
-        metadata: String,
-    ) -> Result<u64, Error> {
-        Self::require_not_paused(&env, "create_payment")?;
-        Self::require_merchant_not_paused(&env, &merchant)?;
-        customer.require_auth();
-        PaymentContract::do_create_payment(
+    /// `Ok(released)` with the total amount released across all tokens.
+    pub fn release_reserves(env: Env, merchant: Address) -> Result<i128, Error> {
+        let now = env.ledger().timestamp();
+        let mut days = Self::get_reserve_release_days(env.clone(), merchant.clone());
+        let mut released = 0;
+        while let Some(day) = days.first() {
+            if day * SECONDS_PER_DAY > now {
+                break;
+            }
+            let mut held = Vec::new(&env);
+            for entry in Self::get_reserve_entries(env.clone(), merchant.clone(), day).iter() {
+                if entry.release_at > now {
+                    held.push_back(entry);
+                    continue;
+                }
+                Self::adjust_reserve_balance(&env, &merchant, &entry.token, -entry.amount);
+                Self::settle_or_accumulate(&env, merchant.clone(), entry.token, entry.amount)?;
+                released += entry.amount;
+                (ReserveReleased {
+                    payment_id: entry.payment_id,
+                    merchant: merchant.clone(),
+                    amount: entry.amount,
+                })
+                .publish(&env);
+            }
+            let partly_held = !held.is_empty();
+            Self::store_reserve_bucket(&env, &merchant, &mut days, day, &held);
+            if partly_held {
+                break;
+            }
+        }
+        Ok(released)
+    }
+
+    /// Returns the release days, in days since the Unix epoch, on which the
+    /// merchant has unreleased reserve entries, soonest first.
+    pub fn get_reserve_release_days(env: Env, merchant: Address) -> Vec<u64> {
+        record_get(
             &env,
-            customer,
-            merchant,
-            amount,
-            token,
-            currency,
-            expiration_duration,
-            metadata,
+            &DataKey::Merchant(MerchantDataKey::ReserveDays(merchant)),
         )
+        .unwrap_or_else(|| Vec::new(&env))
     }
 
-    /// Schedules a future payment by escrowing tokens until the scheduled time.
-    ///
-    /// # Arguments
-    /// * `customer` - The customer scheduling the payment (must authorize)
-    /// * `merchant` - The merchant who will receive the payment
-    /// * `token` - The SPL token address for the payment
-    /// * `amount` - The payment amount in base token units
-    /// * `scheduled_at` - The ledger timestamp at which the payment should be executed
-    ///
-    /// # Returns
-    /// `Ok(payment_id)` on success, or an error if the amount is non-positive, the scheduled
-    /// time is in the past, or the contract is paused.
-    pub fn schedule_payment(
-        env: Env,
-        customer: Address,
-        merchant: Address,
-        token: Address,
-        amount: i128,
-        scheduled_at: u64,
-    ) -> Result<u64, Error> {
-        Self::require_not_paused(&env, "schedule_payment")?;
-        customer.require_auth();
-        if amount <= 0 {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
-        }
-        let now = env.ledger().timestamp();
-        if scheduled_at <= now {
-            return Err(Error::Payment(PaymentError::InvalidScheduleTime));
+    /// Returns the merchant's unreleased reserve entries that release on `release_day`.
+    pub fn get_reserve_entries(env: Env, merchant: Address, release_day: u64) -> Vec<ReserveEntry> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::ReserveBucket(merchant, release_day)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns the total the merchant currently has held in reserve in `token`.
+    pub fn get_reserve_balance(env: Env, merchant: Address, token: Address) -> i128 {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::ReserveBalance(merchant, token)),
+        )
+        .unwrap_or(0)
+    }
+
+    fn adjust_reserve_balance(env: &Env, merchant: &Address, token: &Address, delta: i128) {
+        let key = DataKey::Merchant(MerchantDataKey::ReserveBalance(
+            merchant.clone(),
+            token.clone(),
+        ));
+        let balance = record_get::<DataKey, i128>(env, &key).unwrap_or(0) + delta;
+        if balance == 0 {
+            record_remove(env, &key);
+        } else {
+            record_set(env, &key, &balance);
         }
+    }
 
-        let token_client = token::Client::new(&env, &token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer_from(&contract_address, &customer, &contract_address, &amount);
+    /// Stores the reserve entries releasing on `day`, dropping the day from the
+    /// merchant's release days once its bucket is empty.
+    fn store_reserve_bucket(
+        env: &Env,
+        merchant: &Address,
+        days: &mut Vec<u64>,
+        day: u64,
+        entries: &Vec<ReserveEntry>,
+    ) {
+        let key = DataKey::Merchant(MerchantDataKey::ReserveBucket(merchant.clone(), day));
+        if !entries.is_empty() {
+            record_set(env, &key, entries);
+            return;
+        }
+        record_remove(env, &key);
+        if let Some(index) = days.first_index_of(day) {
+            days.remove(index);
+        }
+        let days_key = DataKey::Merchant(MerchantDataKey::ReserveDays(merchant.clone()));
+        if days.is_empty() {
+            record_remove(env, &days_key);
+        } else {
+            record_set(env, &days_key, days);
+        }
+    }
 
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ScheduledPaymentCounter))
-            .unwrap_or(0);
-        let payment_id = counter + 1;
-        let scheduled = ScheduledPayment {
-            payment_id,
-            customer: customer.clone(),
-            merchant: merchant.clone(),
-            token,
-            amount,
-            scheduled_at,
-            executed: false,
-            cancelled: false,
+    /// Withholds the rolling reserve for a completed payment out of `settled`, the
+    /// amount about to be settled to the merchant. The reserve rate comes from the
+    /// merchant's verification tier plus a share of the payment's risk score.
+    /// Returns the amount withheld, which stays in the contract.
+    ///
+    /// Entries are bucketed by release day, so no single record grows with the
+    /// merchant's lifetime payment count.
+    fn withhold_reserve(env: &Env, payment: &Payment, settled: i128) -> i128 {
+        let level =
+            PaymentContract::get_merchant_verification_level(env.clone(), payment.merchant.clone());
+        let tier = match PaymentContract::get_reserve_tier(env.clone(), level) {
+            Some(tier) => tier,
+            None => return 0,
         };
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
-            &scheduled,
-        );
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::ScheduledPaymentCounter),
-            &payment_id,
+        let risk_score = PaymentContract::calculate_risk_score(
+            env.clone(),
+            payment.customer.clone(),
+            payment.merchant.clone(),
+            payment.amount,
+            payment.currency.clone(),
         );
+        let reserve_bps = (tier.reserve_bps as i128
+            + risk_score as i128 * tier.risk_weight_pct as i128 / 100)
+            .min(10000);
+        let amount = settled * reserve_bps / 10000;
+        if amount <= 0 {
+            return 0;
+        }
+
+        let release_at = env.ledger().timestamp() + tier.hold_seconds;
+        let day = release_at / SECONDS_PER_DAY;
+        let mut days =
+            PaymentContract::get_reserve_release_days(env.clone(), payment.merchant.clone());
+        if let Err(index) = days.binary_search(day) {
+            days.insert(index, day);
+            record_set(
+                env,
+                &DataKey::Merchant(MerchantDataKey::ReserveDays(payment.merchant.clone())),
+                &days,
+            );
+        }
+        let mut entries =
+            PaymentContract::get_reserve_entries(env.clone(), payment.merchant.clone(), day);
+        entries.push_back(ReserveEntry {
+            payment_id: payment.id,
+            token: payment.token.clone(),
+            amount,
+            release_at,
+        });
+        Self::store_reserve_bucket(env, &payment.merchant, &mut days, day, &entries);
+        Self::adjust_reserve_balance(env, &payment.merchant, &payment.token, amount);
 
-        (PaymentCreated {
-            payment_id,
-            customer,
-            merchant,
+        (ReserveWithheld {
+            payment_id: payment.id,
+            merchant: payment.merchant.clone(),
             amount,
+            release_at,
         })
-        .publish(&env);
+        .publish(env);
+        amount
+    }
 
-        Ok(payment_id)
+    /// Moves up to `amount` of a merchant's accumulated payout balance in `token`
+    /// to `to`, returning the amount moved.
+    fn draw_payout_balance(
+        env: &Env,
+        merchant: &Address,
+        token: &Address,
+        amount: i128,
+        to: &Address,
+    ) -> i128 {
+        let key = DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant.clone()));
+        let mut schedule: PayoutSchedule = match env.storage().instance().get(&key) {
+            Some(schedule) => schedule,
+            None => return 0,
+        };
+        if schedule.token != *token {
+            return 0;
+        }
+        let drawn = schedule.accumulated.min(amount).max(0);
+        if drawn > 0 {
+            schedule.accumulated -= drawn;
+            env.storage().instance().set(&key, &schedule);
+            token::Client::new(env, token).transfer(&env.current_contract_address(), to, &drawn);
+        }
+        drawn
     }
 
-    /// Executes a previously scheduled payment once its scheduled time has passed.
+    /// Draws up to `amount` in `token` from the merchant's reserve, soonest
+    /// release day first, and transfers it to `to`. Returns the amount drawn.
+    fn draw_reserve(
+        env: &Env,
+        merchant: &Address,
+        token: &Address,
+        amount: i128,
+        to: &Address,
+    ) -> i128 {
+        let available =
+            PaymentContract::get_reserve_balance(env.clone(), merchant.clone(), token.clone());
+        let target = amount.min(available);
+        if target <= 0 {
+            return 0;
+        }
+
+        let mut days = PaymentContract::get_reserve_release_days(env.clone(), merchant.clone());
+        let mut drawn = 0;
+        for day in days.clone().iter() {
+            if drawn >= target {
+                break;
+            }
+            let mut remaining = Vec::new(env);
+            let mut changed = false;
+            for mut entry in
+                PaymentContract::get_reserve_entries(env.clone(), merchant.clone(), day).iter()
+            {
+                if entry.token == *token && drawn < target {
+                    let take = entry.amount.min(target - drawn);
+                    entry.amount -= take;
+                    drawn += take;
+                    changed = true;
+                    (ReserveDrawn {
+                        payment_id: entry.payment_id,
+                        merchant: merchant.clone(),
+                        amount: take,
+                        to: to.clone(),
+                    })
+                    .publish(env);
+                }
+                if entry.amount > 0 {
+                    remaining.push_back(entry);
+                }
+            }
+            if changed {
+                Self::store_reserve_bucket(env, merchant, &mut days, day, &remaining);
+            }
+        }
+        if drawn > 0 {
+            Self::adjust_reserve_balance(env, merchant, token, -drawn);
+            token::Client::new(env, token).transfer(&env.current_contract_address(), to, &drawn);
+        }
+        drawn
+    }
+
+    /// Updates the notes field on a payment.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the scheduled payment to execute
+    /// * `merchant` - The merchant who owns the payment (must authorize)
+    /// * `payment_id` - The ID of the payment to update
+    /// * `notes` - The new notes text to set on the payment
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, has been cancelled,
-    /// was already executed, is not yet due, the customer is flagged, spend limits are
-    /// exceeded, or the contract is paused.
-    pub fn execute_scheduled_payment(env: Env, payment_id: u64) -> Result<(), Error> {
-        Self::require_not_paused(&env, "execute_scheduled_payment")?;
-        let mut scheduled: ScheduledPayment = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ScheduledPayment(payment_id)))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
-        if scheduled.cancelled {
-            return Err(Error::Payment(PaymentError::ScheduledPaymentCancelled));
+    /// `Ok(())` on success, or an error if the notes are too large, the payment is not
+    /// found, or the caller is not the payment's merchant.
+    pub fn update_payment_notes(
+        env: Env,
+        merchant: Address,
+        payment_id: u64,
+        notes: String,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+
+        // Validate notes size
+        if notes.len() > MAX_NOTES_SIZE {
+            return Err(Error::Basic(BasicError::NotesTooLarge));
         }
-        if scheduled.executed {
-            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+
+        // Check if payment exists
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
         }
-        if env.ledger().timestamp() < scheduled.scheduled_at {
-            return Err(Error::Payment(PaymentError::NotYetDue));
+
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+
+        // Verify caller is the merchant
+        if payment.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        // Check customer spend limit (#282)
-        PaymentContract::check_and_update_spend_limit(&env, &scheduled.customer, scheduled.amount)?;
+        // Update notes
+        payment.notes = notes;
 
-        Self::settle_or_accumulate(
+        // Save updated payment
+        record_set(
             &env,
-            scheduled.merchant.clone(),
-            scheduled.token.clone(),
-            scheduled.amount,
-        )?;
-        scheduled.executed = true;
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
-            &scheduled,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
         );
+
         Ok(())
     }
 
-    /// Cancels a scheduled payment and refunds the escrowed tokens to the customer.
+    /// Checks whether a payment has expired based on its expiration timestamp.
     ///
     /// # Arguments
-    /// * `caller` - The customer who scheduled the payment, or an admin (must authorize)
-    /// * `payment_id` - The ID of the scheduled payment to cancel
+    /// * `payment_id` - The ID of the payment to check
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, has already been
-    /// executed or cancelled, the caller is unauthorized, or the contract is paused.
-    pub fn cancel_scheduled_payment(
-        env: Env,
-        caller: Address,
-        payment_id: u64,
-    ) -> Result<(), Error> {
-        Self::require_not_paused(&env, "cancel_scheduled_payment")?;
-        caller.require_auth();
-        let mut scheduled: ScheduledPayment = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ScheduledPayment(payment_id)))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
-        if scheduled.executed {
-            return Err(Error::Payment(PaymentError::AlreadyProcessed));
-        }
-        if scheduled.cancelled {
-            return Err(Error::Payment(PaymentError::ScheduledPaymentCancelled));
-        }
-        if caller != scheduled.customer {
-            let stored_admin: Address = env
-                .storage()
-                .instance()
-                .get(&DataKey::Config(ConfigKey::Admin))
-                .ok_or(Error::Basic(BasicError::Unauthorized))?;
-            if caller != stored_admin {
-                return Err(Error::Basic(BasicError::Unauthorized));
-            }
+    /// `true` if the payment exists, has a non-zero expiration, and the current time
+    /// exceeds the expiration; `false` otherwise.
+    pub fn is_payment_expired(env: &Env, payment_id: u64) -> bool {
+        if !record_has(env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return false;
         }
-
-        let token_client = token::Client::new(&env, &scheduled.token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer(&contract_address, &scheduled.customer, &scheduled.amount);
-        scheduled.cancelled = true;
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::ScheduledPayment(payment_id)),
-            &scheduled,
-        );
-        Ok(())
+        let payment = PaymentContract::get_payment(env, payment_id);
+        payment.expires_at > 0 && env.ledger().timestamp() > payment.expires_at
     }
 
-    /// Retrieves the details of a scheduled payment.
+    /// Expires a payment that has passed its expiration time, refunding the customer.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the scheduled payment to retrieve
+    /// * `payment_id` - The ID of the payment to expire
     ///
     /// # Returns
-    /// `Ok(ScheduledPayment)` on success, or `Error::Payment(NotFound)` if not found.
-    pub fn get_scheduled_payment(env: Env, payment_id: u64) -> Result<ScheduledPayment, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ScheduledPayment(payment_id)))
-            .ok_or(Error::Payment(PaymentError::NotFound))
-    }
+    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
+    /// status, has no expiration set, or has not yet expired.
+    pub fn expire_payment(env: Env, payment_id: u64) -> Result<(), Error> {
+        // Retrieve payment from storage
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
 
-    fn settle_or_accumulate(
-        env: &Env,
-        merchant: Address,
-        token: Address,
-        amount: i128,
-    ) -> Result<(), Error> {
-        // If merchant has a payout schedule for this token and it's not Immediate, accumulate
-        if let Some(mut schedule) =
-            env.storage()
-                .instance()
-                .get::<DataKey, PayoutSchedule>(&DataKey::Merchant(
-                    MerchantDataKey::PayoutSchedule(merchant.clone()),
-                ))
-        {
-            if schedule.token == token && schedule.frequency != PayoutFrequency::Immediate {
-                schedule.accumulated += amount;
-                env.storage().instance().set(
-                    &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant.clone())),
-                    &schedule,
-                );
-                return Ok(());
+        // Check payment status allows expiry (only allow Pending)
+        match payment.status {
+            PaymentStatus::Pending => {
+                // Allow expiry
+            }
+            PaymentStatus::Refunded | PaymentStatus::PartialRefunded | PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+            PaymentStatus::Completed => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
             }
         }
 
-        // Otherwise perform immediate transfer
-        let token_client = token::Client::new(env, &token);
+        // Check payment has expiration set
+        if payment.expires_at == 0 {
+            return Err(Error::Payment(PaymentError::NoExpiration));
+        }
+
+        // Check current time is past expires_at
+        if env.ledger().timestamp() <= payment.expires_at {
+            return Err(Error::Payment(PaymentError::NotExpired));
+        }
+
+        // Transfer token amount back to customer (automatic refund)
+        let token_client = token::Client::new(&env, &payment.token);
         let contract_address = env.current_contract_address();
-        token_client.transfer(&contract_address, &merchant, &amount);
-        Ok(())
-    }
 
-    /// Configures a merchant's payout schedule for accumulating payments before settlement.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant configuring the payout schedule (must authorize)
-    /// * `frequency` - How often payouts should occur (Immediate, Daily, Weekly, Monthly)
-    /// * `token` - The SPL token address to accumulate for payouts
-    ///
-    /// # Returns
-    /// `Ok(())` on success.
-    pub fn set_payout_schedule(
-        env: Env,
-        merchant: Address,
-        frequency: PayoutFrequency,
-        token: Address,
-    ) -> Result<(), Error> {
-        merchant.require_auth();
-        let now = env.ledger().timestamp();
-        let next = match frequency {
-            PayoutFrequency::Immediate => now,
-            PayoutFrequency::Daily => now + SECONDS_PER_DAY,
-            PayoutFrequency::Weekly => now + SECONDS_PER_DAY * 7,
-            PayoutFrequency::Monthly => now + SECONDS_PER_DAY * 30,
-        };
-        let schedule = PayoutSchedule {
-            merchant: merchant.clone(),
-            token: token.clone(),
-            frequency,
-            next_payout_at: next,
-            accumulated: 0,
+        // Check if contract has sufficient balance (in case of partial payments)
+        let contract_balance = token_client.balance(&contract_address);
+        let refund_amount = if contract_balance >= payment.amount {
+            payment.amount
+        } else {
+            contract_balance
         };
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant)),
-            &schedule,
+
+        if refund_amount > 0 {
+            token_client.transfer(&contract_address, &payment.customer, &refund_amount);
+        }
+
+        // Update payment status to Cancelled
+        payment.status = PaymentStatus::Cancelled;
+
+        // Store updated payment back to storage
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
         );
+
+        // Emit PaymentExpired event with refund info
+        (PaymentExpired {
+            payment_id,
+            customer: payment.customer,
+            refunded_amount: refund_amount,
+            expired_at: env.ledger().timestamp(),
+        })
+        .publish(&env);
+
         Ok(())
     }
 
-    /// Retrieves the payout schedule configuration for a merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
+    /// Completes a pending payment, transferring funds from the customer to the merchant.
     ///
-    /// # Returns
-    /// `Some(PayoutSchedule)` if configured, or `None` otherwise.
-    pub fn get_payout_schedule(env: Env, merchant: Address) -> Option<PayoutSchedule> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
-                merchant,
-            )))
-    }
-
-    /// Triggers an accumulated payout for a merchant, transferring held funds.
+    /// If the payment exceeds the large payment threshold, a multisig proposal is
+    /// automatically created instead of completing immediately.
     ///
     /// # Arguments
-    /// * `merchant` - The merchant requesting the payout (must authorize)
+    /// * `admin` - The admin authorizing the completion (must be in the multisig admin list)
+    /// * `payment_id` - The ID of the payment to complete
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payout schedule is not found, the payout
-    /// period has not elapsed, or there is nothing to settle.
-    pub fn trigger_scheduled_payout(env: Env, merchant: Address) -> Result<(), Error> {
-        merchant.require_auth();
-        let mut schedule: PayoutSchedule = env
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
+    /// found, is not in Pending status, is expired, or the multisig threshold is not met.
+    /// Returns `ProposalError::RequiresMultiSig` if a multisig proposal was auto-created.
+    pub fn complete_payment(env: Env, admin: Address, payment_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, "complete_payment")?;
+        admin.require_auth();
+
+        // Verify caller is in the multisig admin list
+        let config: MultiSigConfig = env
             .storage()
             .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
-                merchant.clone(),
-            )))
-            .ok_or(Error::Payment(PaymentError::PayoutScheduleNotFound))?;
-        let now = env.ledger().timestamp();
-        if now < schedule.next_payout_at {
-            return Err(Error::Payment(PaymentError::PayoutNotYetDue));
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
-        if schedule.accumulated == 0 {
-            return Err(Error::Payment(PaymentError::NothingToSettle));
+
+        // Check if payment requires multi-sig approval
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        let threshold = PaymentContract::get_large_payment_threshold(env.clone());
+
+        if threshold > 0 && payment.amount > threshold {
+            // Check if there's already a proposal for this payment
+            if record_get::<DataKey, LargePaymentProposal>(
+                &env,
+                &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+            )
+            .is_some()
+            {
+                return Err(Error::Proposal(ProposalError::RequiresMultiSig));
+            }
+
+            // Auto-create proposal for large payment
+            let now = env.ledger().timestamp();
+            let expires_at = now + config.proposal_ttl;
+
+            let mut approvals = Vec::new(&env);
+            approvals.push_back(admin.clone());
+
+            let proposal = LargePaymentProposal {
+                payment_id,
+                approvals,
+                required: config.required_signatures,
+                proposed_at: now,
+                expires_at,
+                executed: false,
+            };
+
+            record_set(
+                &env,
+                &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+                &proposal,
+            );
+
+            (LargePaymentProposed {
+                payment_id,
+                proposer: admin,
+                required_approvals: config.required_signatures,
+                expires_at,
+            })
+            .publish(&env);
+
+            return Err(Error::Proposal(ProposalError::RequiresMultiSig));
         }
-        let token_client = token::Client::new(&env, &schedule.token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer(&contract_address, &merchant, &schedule.accumulated);
-        schedule.accumulated = 0;
-        let period = match schedule.frequency {
-            PayoutFrequency::Immediate => SECONDS_PER_DAY,
-            PayoutFrequency::Daily => SECONDS_PER_DAY,
-            PayoutFrequency::Weekly => SECONDS_PER_DAY * 7,
-            PayoutFrequency::Monthly => SECONDS_PER_DAY * 30,
-        };
-        schedule.next_payout_at = schedule.next_payout_at + period;
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::PayoutSchedule(merchant)),
-            &schedule,
-        );
-        Ok(())
+
+        PaymentContract::do_complete_payment(&env, payment_id)
     }
 
-    /// Returns the accumulated token balance for a merchant's payout schedule.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
-    ///
-    /// # Returns
-    /// The accumulated balance in base token units, or 0 if no payout schedule exists.
-    pub fn get_accumulated_balance(env: Env, merchant: Address) -> i128 {
-        env.storage()
-            .instance()
-            .get::<DataKey, PayoutSchedule>(&DataKey::Merchant(MerchantDataKey::PayoutSchedule(
-                merchant,
-            )))
-            .map(|s: PayoutSchedule| s.accumulated)
-            .unwrap_or(0)
+    fn do_complete_payment(env: &Env, payment_id: u64) -> Result<(), Error> {
+        PaymentContract::settle_payment(env, payment_id, None).map(|_| ())
     }
 
-    fn do_create_payment(
+    /// Completes a pending payment and returns the amount the merchant received
+    /// in the settled token, or `0` while a finality delay holds the funds.
+    ///
+    /// With `route` set to `(route, venues, max_slippage_bps)`, the merchant's
+    /// share is swapped along the route instead of being converted to their
+    /// settlement preference.
+    fn settle_payment(
         env: &Env,
-        customer: Address,
-        merchant: Address,
-        amount: i128,
-        token: Address,
-        currency: Currency,
-        expiration_duration: u64,
-        metadata: String,
-    ) -> Result<u64, Error> {
-        Self::require_merchant_not_paused(env, &merchant)?;
-        if !PaymentContract::is_token_allowed(env, &token) {
-            return Err(Error::Payment(PaymentError::TokenNotAllowed));
+        payment_id: u64,
+        route: Option<(&RouteOption, &Vec<SwapVenue>, u32)>,
+    ) -> Result<i128, Error> {
+        // Check if payment exists
+        if !record_has(env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
         }
 
-        // Validate currency
-        if !PaymentContract::is_valid_currency(&currency) {
-            return Err(Error::Basic(BasicError::InvalidCurrency));
-        }
+        let mut payment = PaymentContract::get_payment(env, payment_id);
 
-        // Validate metadata size
-        if metadata.len() > MAX_METADATA_SIZE {
-            return Err(Error::Basic(BasicError::MetadataTooLarge));
+        // Before updating status, check if payment is expired
+        if PaymentContract::is_payment_expired(env, payment_id) {
+            return Err(Error::Payment(PaymentError::Expired));
         }
 
-        // Enforce sanctions/flag checks at creation entry point.
-        if PaymentContract::is_address_flagged(env.clone(), customer.clone())
-            && !PaymentContract::is_allowlisted(env, &customer)
-        {
-            return Err(Error::Basic(BasicError::AddressFlagged));
+        match payment.status {
+            PaymentStatus::Pending => {
+                payment.status = PaymentStatus::Completed;
+                PaymentContract::mark_completed(env, payment_id);
+            }
+            PaymentStatus::Completed => {
+                return Err(Error::Payment(PaymentError::AlreadyProcessed));
+            }
+            PaymentStatus::Refunded | PaymentStatus::PartialRefunded => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+            PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
         }
 
-        // Check rate limits and anti-fraud before processing
-        PaymentContract::check_rate_limit_internal(env, &customer, amount)?;
-
-        // Check merchant rate limits
-        PaymentContract::check_merchant_rate_limit(env, &merchant, amount)?;
-
-        // Check customer spend limit (#217)
-        PaymentContract::check_and_update_spend_limit(env, &customer, amount)?;
-
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Counter))
-            .unwrap_or(0);
-        let payment_id = counter + 1;
-
-        let current_timestamp = env.ledger().timestamp();
-        let expires_at = if expiration_duration > 0 {
-            current_timestamp + expiration_duration
+        // Subtract any loyalty-point discount redeemed against this payment (#490)
+        let discount: i128 =
+            record_get(env, &DataKey::Payment(PaymentKey::Discount(payment_id))).unwrap_or(0);
+        let charge_amount = if discount > 0 {
+            record_remove(env, &DataKey::Payment(PaymentKey::Discount(payment_id)));
+            let capped_discount = if discount > payment.amount {
+                payment.amount
+            } else {
+                discount
+            };
+            payment.amount - capped_discount
         } else {
-            0
+            payment.amount
         };
 
-        let payment = Payment {
-            id: payment_id,
-            customer: customer.clone(),
-            merchant: merchant.clone(),
-            amount,
-            token,
-            currency,
-            status: PaymentStatus::Pending,
-            created_at: current_timestamp,
-            expires_at,
-            metadata,
-            notes: String::from_str(&env, ""),
-            refunded_amount: 0,
-        };
+        // Deduct platform fee (if configured) and get net amount for merchant
+        let (net_amount, fee_amount) = PaymentContract::deduct_fee(
+            env,
+            payment_id,
+            charge_amount,
+            payment.merchant.clone(),
+            &payment.token,
+            &payment.customer,
+            payment.currency.clone(),
+        );
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Counter), &payment_id);
+        // Route the marketplace application fee, if any, straight to the platform
+        let net_amount = net_amount
+            - PaymentContract::collect_application_fee(
+                env,
+                &payment,
+                net_amount,
+                &payment.customer,
+            );
 
-        // Index by customer
-        let customer_count: u64 = env
+        // Check finality delay config (#219)
+        let finality: Option<FinalityConfig> = env
             .storage()
             .instance()
-            .get(&DataKey::Customer(CustomerDataKey::PaymentCount(
-                customer.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::Payments(customer.clone(), customer_count)),
-            &payment_id,
-        );
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::PaymentCount(customer)),
-            &(customer_count + 1),
-        );
+            .get(&DataKey::Config(ConfigKey::FinalityConfig));
+        let reserve = PaymentContract::withhold_reserve(env, &payment, net_amount);
+        if let Some(ref fc) = finality {
+            if fc.active && payment.amount >= fc.min_amount_threshold {
+                // The reserve is collected now, as on the immediate path; only the
+                // merchant's share waits for the settlement to be finalized
+                if reserve > 0 {
+                    PaymentContract::charge_customer(
+                        env,
+                        &payment.customer,
+                        &payment.token,
+                        &env.current_contract_address(),
+                        reserve,
+                    );
+                }
+                // Hold funds — create PendingSettlement instead of transferring
+                let release_at = env.ledger().timestamp() + fc.delay_seconds;
+                let settlement = PendingSettlement {
+                    payment_id,
+                    merchant: payment.merchant.clone(),
+                    amount: net_amount - reserve,
+                    token: payment.token.clone(),
+                    release_at,
+                };
+                record_set(
+                    env,
+                    &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                    &settlement,
+                );
+                let idx: u64 = record_get(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::PendingSettlementCount(
+                        payment.merchant.clone(),
+                    )),
+                )
+                .unwrap_or(0);
+                record_set(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::PendingSettlementIndex(
+                        payment.merchant.clone(),
+                        idx,
+                    )),
+                    &payment_id,
+                );
+                record_set(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::PendingSettlementCount(
+                        payment.merchant.clone(),
+                    )),
+                    &(idx + 1),
+                );
+                record_set(
+                    env,
+                    &DataKey::Payment(PaymentKey::Data(payment_id)),
+                    &payment,
+                );
+                PaymentContract::update_merchant_fee_record_post_completion(
+                    env,
+                    payment.merchant.clone(),
+                    payment.amount,
+                    fee_amount,
+                );
+                let mut analytics: PaymentAnalytics = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
+                    .unwrap_or(PaymentAnalytics {
+                        total_payments_created: 0,
+                        total_payments_completed: 0,
+                        total_payments_cancelled: 0,
+                        total_payments_refunded: 0,
+                        total_volume: 0,
+                        total_refunded_volume: 0,
+                        unique_customers: 0,
+                        unique_merchants: 0,
+                    });
+                analytics.total_payments_completed += 1;
+                env.storage()
+                    .instance()
+                    .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
+                (PaymentCompleted {
+                    payment_id,
+                    merchant: payment.merchant.clone(),
+                    amount: payment.amount,
+                })
+                .publish(env);
+                return Ok(0);
+            }
+        }
 
-        // Index by merchant
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PaymentCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::Payments(merchant.clone(), merchant_count)),
-            &payment_id,
-        );
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
-            &(merchant_count + 1),
-        );
+        // Token transfer: net amount from customer to merchant, swapped along the
+        // payment's route or converted to the merchant's settlement token if they
+        // have chosen a different one
+        let contract_address = env.current_contract_address();
+        let converts =
+            PaymentContract::get_settlement_preference(env.clone(), payment.merchant.clone())
+                .is_some_and(|preference| preference.token != payment.token);
+        let (settled_token, settled_amount) = if let Some((route, venues, max_slippage_bps)) = route
+        {
+            PaymentContract::charge_customer(
+                env,
+                &payment.customer,
+                &payment.token,
+                &contract_address,
+                net_amount,
+            );
+            PaymentContract::pay_out_along_route(
+                env,
+                &payment.merchant,
+                route,
+                venues,
+                net_amount - reserve,
+                max_slippage_bps,
+            )?
+        } else if converts {
+            PaymentContract::charge_customer(
+                env,
+                &payment.customer,
+                &payment.token,
+                &contract_address,
+                net_amount,
+            );
+            PaymentContract::pay_out(env, &payment.merchant, &payment.token, net_amount - reserve)?
+        } else {
+            if reserve > 0 {
+                PaymentContract::charge_customer(
+                    env,
+                    &payment.customer,
+                    &payment.token,
+                    &contract_address,
+                    reserve,
+                );
+            }
+            PaymentContract::charge_customer(
+                env,
+                &payment.customer,
+                &payment.token,
+                &payment.merchant,
+                net_amount - reserve,
+            );
+            (payment.token.clone(), net_amount - reserve)
+        };
+        let token_client = token::Client::new(env, &settled_token);
 
-        // Paged merchant payment index (100 entries per page)
+        // Check if merchant has an active payment forward config
+        if let Ok(forward_config) =
+            PaymentContract::get_forward_config(env.clone(), payment.merchant.clone())
         {
-            const PAGE_SIZE: u64 = 100;
-            let page_num = merchant_count / PAGE_SIZE;
-            let mut page: Vec<u64> = env
-                .storage()
-                .instance()
-                .get(&DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
-                    merchant.clone(),
-                    page_num,
-                )))
-                .unwrap_or_else(|| Vec::new(&env));
-            page.push_back(payment_id);
-            env.storage().instance().set(
-                &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(merchant, page_num)),
-                &page,
-            );
+            if forward_config.active {
+                // Calculate the forward amount based on forward_bps
+                let forward_amount =
+                    (settled_amount * (forward_config.forward_bps as i128)) / 10000;
+
+                // Transfer the forward amount from merchant to forward_to address
+                if forward_amount > 0 {
+                    token_client.transfer(
+                        &payment.merchant,
+                        &forward_config.forward_to,
+                        &forward_amount,
+                    );
+
+                    (PaymentForwarded {
+                        payment_id,
+                        merchant: payment.merchant.clone(),
+                        forward_to: forward_config.forward_to,
+                        forward_amount,
+                    })
+                    .publish(env);
+                }
+            }
         }
 
-        // Update global analytics
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+        PaymentContract::update_merchant_fee_record_post_completion(
+            env,
+            payment.merchant.clone(),
+            payment.amount,
+            fee_amount,
+        );
+
+        // Update analytics
         let mut analytics: PaymentAnalytics = env
             .storage()
             .instance()
@@ -2862,32 +6642,10 @@ impl PaymentContract {
                 unique_customers: 0,
                 unique_merchants: 0,
             });
-        analytics.total_payments_created += 1;
-        analytics.total_volume += amount;
-        if customer_count == 0 {
-            analytics.unique_customers += 1;
-        }
-        if merchant_count == 0 {
-            analytics.unique_merchants += 1;
-            let global_count: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::Config(ConfigKey::GlobalMerchantCount))
-                .unwrap_or(0);
-            env.storage().instance().set(
-                &DataKey::Merchant(MerchantDataKey::GlobalList(global_count)),
-                &payment.merchant.clone(),
-            );
-            env.storage().instance().set(
-                &DataKey::Config(ConfigKey::GlobalMerchantCount),
-                &(global_count + 1),
-            );
-        }
+        analytics.total_payments_completed += 1;
         env.storage()
             .instance()
             .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
-
-        // Update merchant analytics
         let mut m_analytics: MerchantAnalytics = env
             .storage()
             .instance()
@@ -2902,1006 +6660,1112 @@ impl PaymentContract {
                 total_refunded: 0,
                 total_refunded_volume: 0,
             });
-        m_analytics.total_payments += 1;
-        m_analytics.total_volume += amount;
+        m_analytics.total_completed += 1;
         env.storage().instance().set(
             &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
             &m_analytics,
         );
+        (PaymentCompleted {
+            payment_id,
+            merchant: payment.merchant.clone(),
+            amount: payment.amount,
+        })
+        .publish(env);
 
-        // Update customer analytics
-        let mut c_analytics: CustomerAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::Analytics(
-                payment.customer.clone(),
-            )))
-            .unwrap_or(CustomerAnalytics {
-                total_payments: 0,
-                total_volume: 0,
-                total_refunds: 0,
-                avg_transaction_size: 0,
-                peak_hour: 0,
-                top_merchant: None,
-                top_merchant_volume: 0,
-                first_payment_at: 0,
-                last_payment_at: 0,
-            });
-        c_analytics.total_payments += 1;
-        c_analytics.total_volume += amount;
-        c_analytics.avg_transaction_size =
-            c_analytics.total_volume / (c_analytics.total_payments as i128);
-        if c_analytics.first_payment_at == 0 {
-            c_analytics.first_payment_at = current_timestamp;
-        }
-        c_analytics.last_payment_at = current_timestamp;
-
-        // Track peak hour (UTC hour 0-23)
-        let hour = ((current_timestamp / 3600) % 24) as u32;
-        let hour_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::HourCount(
-                payment.customer.clone(),
-                hour,
-            )))
-            .unwrap_or(0)
-            + 1;
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::HourCount(payment.customer.clone(), hour)),
-            &hour_count,
-        );
-        let peak_hour_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::HourCount(
-                payment.customer.clone(),
-                c_analytics.peak_hour,
-            )))
-            .unwrap_or(0);
-        if hour_count > peak_hour_count
-            || (hour_count == peak_hour_count && hour == c_analytics.peak_hour)
-        {
-            c_analytics.peak_hour = hour;
-        }
-
-        // Track per-merchant volume and update top merchant
-        let prev_merchant_vol: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::MerchantVolume(
-                payment.customer.clone(),
-                payment.merchant.clone(),
-            )))
-            .unwrap_or(0);
-        if prev_merchant_vol == 0 {
-            // New merchant for this customer — add to list
-            let m_count: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::Customer(CustomerDataKey::MerchantCount(
-                    payment.customer.clone(),
-                )))
-                .unwrap_or(0);
-            env.storage().instance().set(
-                &DataKey::Customer(CustomerDataKey::MerchantList(
-                    payment.customer.clone(),
-                    m_count,
-                )),
-                &payment.merchant,
-            );
-            env.storage().instance().set(
-                &DataKey::Customer(CustomerDataKey::MerchantCount(payment.customer.clone())),
-                &(m_count + 1),
-            );
-        }
-        let new_merchant_vol = prev_merchant_vol + amount;
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::MerchantVolume(
-                payment.customer.clone(),
-                payment.merchant.clone(),
-            )),
-            &new_merchant_vol,
-        );
-        if new_merchant_vol > c_analytics.top_merchant_volume {
-            c_analytics.top_merchant = Some(payment.merchant.clone());
-            c_analytics.top_merchant_volume = new_merchant_vol;
-        }
-
-        // Track monthly volume (30-day bucket)
-        let month_bucket = (current_timestamp / 2_592_000) * 2_592_000;
-        let prev_monthly: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::MonthlyVolume(
-                payment.customer.clone(),
-                month_bucket,
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::MonthlyVolume(
-                payment.customer.clone(),
-                month_bucket,
-            )),
-            &(prev_monthly + amount),
+        // Accrue loyalty points for completed payments if loyalty is configured.
+        PaymentContract::maybe_accrue_loyalty_points(
+            &env,
+            payment.customer.clone(),
+            payment.amount,
         );
 
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::Analytics(payment.customer.clone())),
-            &c_analytics,
-        );
+        // Reward the customer from any of the merchant's running campaigns
+        PaymentContract::apply_campaigns(env, &payment);
 
-        PaymentContract::update_merchant_bucket(
+        // Accrue fee rebate for merchant if rebate programme is active
+        PaymentContract::maybe_accrue_fee_rebate(
             env,
             payment.merchant.clone(),
-            current_timestamp,
-            1,
-            amount,
-            0,
-            0,
+            payment.amount,
+            fee_amount,
         );
-        PaymentContract::update_platform_daily_bucket(env, current_timestamp, amount, 0, 0);
-
-        (PaymentCreated {
-            payment_id,
-            customer: payment.customer,
-            merchant: payment.merchant.clone(),
-            amount: payment.amount,
-        })
-        .publish(&env);
-
-        Ok(payment_id)
-    }
-
-    /// Retrieves a payment by its ID.
-    ///
-    /// # Arguments
-    /// * `payment_id` - The ID of the payment to retrieve
-    ///
-    /// # Returns
-    /// The `Payment` record.
-    ///
-    /// # Panics
-    /// Panics if the payment is not found.
-    pub fn get_payment(env: &Env, payment_id: u64) -> Payment {
-        env.storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Data(payment_id)))
-            .expect("Payment not found")
-    }
 
-    /// Used by the refund contract for cross-contract ownership verification (#143).
-    /// Returns true if the payment exists, belongs to `customer`, and is Completed.
-    pub fn check_payment_customer(env: Env, payment_id: u64, customer: Address) -> bool {
-        let payment: Option<Payment> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Data(payment_id)));
-        match payment {
-            Some(p) => p.customer == customer && p.status == PaymentStatus::Completed,
-            None => false,
-        }
+        // Attempt to trigger auto-escrow if a rule exists
+        // Ignore errors - if there's no rule, payment is below minimum, or already triggered,
+        // we just skip the auto-escrow (the payment completion still succeeds)
+        let _ = PaymentContract::trigger_auto_escrow(env, payment_id);
+        let now = env.ledger().timestamp();
+        PaymentContract::update_merchant_bucket(env, payment.merchant.clone(), now, 0, 0, 0, 0);
+        PaymentContract::update_platform_daily_bucket(env, now, 0, 0, 0);
+
+        Ok(settled_amount)
     }
 
-    /// Creates a payment backed by an escrow contract for dispute protection.
+    /// Configures automatic payment forwarding for a merchant.
     ///
     /// # Arguments
-    /// * `customer` - The customer making the payment
-    /// * `merchant` - The merchant receiving the payment
-    /// * `amount` - The payment amount in base token units
-    /// * `token` - The SPL token address for the payment
-    /// * `currency` - The fiat currency associated with the payment
-    /// * `escrow_contract` - The address of the escrow contract to hold funds
-    /// * `release_timestamp` - The timestamp when funds can be released from escrow
-    /// * `min_hold_period` - Minimum time funds must remain in escrow
-    /// * `metadata` - Arbitrary metadata string for the payment
-    /// * `auto_release_on_complete` - Whether to auto-release escrow when payment completes
+    /// * `merchant` - The merchant setting up forwarding (must authorize)
+    /// * `forward_to` - The address to forward a portion of payments to
+    /// * `forward_bps` - The percentage to forward in basis points (1-10000)
     ///
     /// # Returns
-    /// `Ok((payment_id, escrow_id))` on success, or an error if the underlying payment
-    /// creation fails, the escrow contract invocation fails, or the token transfer fails.
-    pub fn create_escrowed_payment(
+    /// `Ok(())` on success, or an error if the forward_bps is invalid, a forwarding loop
+    /// is detected, or the contract is paused.
+    pub fn set_payment_forward(
         env: Env,
-        customer: Address,
         merchant: Address,
-        amount: i128,
-        token: Address,
-        currency: Currency,
-        escrow_contract: Address,
-        release_timestamp: u64,
-        min_hold_period: u64,
-        metadata: String,
-        auto_release_on_complete: bool,
-    ) -> Result<(u64, u64), Error> {
-        Self::require_merchant_not_paused(&env, &merchant)?;
-        let payment_id = PaymentContract::create_payment(
-            env.clone(),
-            customer.clone(),
-            merchant.clone(),
-            amount,
-            token.clone(),
-            currency,
-            0,
-            metadata,
-        )?;
+        forward_to: Address,
+        forward_bps: u32,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, "set_payment_forward")?;
+        merchant.require_auth();
 
-        let escrow_id = PaymentContract::invoke_escrow_create(
-            &env,
-            &escrow_contract,
-            &customer,
-            &merchant,
-            amount,
-            &token,
-            release_timestamp,
-            min_hold_period,
-        )?;
+        // Validate forward_bps: must be between 1 and 10000
+        if let Err(_) = Self::validate_bps(forward_bps) {
+            return Err(Error::Feature(FeatureError::InvalidForwardBps));
+        }
 
-        let bridge = EscrowedPayment {
-            payment_id,
-            escrow_id,
-            escrow_contract: escrow_contract.clone(),
-            auto_release_on_complete,
+        // Get configured max depth (default 5)
+        let max_depth: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MaxForwardDepth))
+            .unwrap_or(5);
+
+        // Detect cycles and enforce depth limit by walking the forward chain
+        {
+            let mut current = forward_to.clone();
+            for _depth in 0..max_depth {
+                if current == merchant {
+                    return Err(Error::Feature(FeatureError::ForwardLoop));
+                }
+                match env
+                    .storage()
+                    .instance()
+                    .get::<DataKey, PaymentForwardConfig>(&DataKey::Feature(
+                        FeatureKey::PaymentForwardConfig(current.clone()),
+                    )) {
+                    Some(next) => current = next.forward_to,
+                    None => break,
+                }
+            }
+            // If we exhausted max_depth iterations, the chain is too long
+            if env
+                .storage()
+                .instance()
+                .has(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
+                    current.clone(),
+                )))
+            {
+                return Err(Error::Feature(FeatureError::ForwardLoop));
+            }
+        }
+
+        // Create and store the forward config
+        let config = PaymentForwardConfig {
+            merchant: merchant.clone(),
+            forward_to: forward_to.clone(),
+            forward_bps,
+            active: true,
         };
+
         env.storage().instance().set(
-            &DataKey::State(StateDataKey::EscrowedPayment(payment_id)),
-            &bridge,
+            &DataKey::Feature(FeatureKey::PaymentForwardConfig(merchant.clone())),
+            &config,
         );
 
-        // Custody is shifted to escrow contract account on creation.
-        let token_client = token::Client::new(&env, &token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer_from(&contract_address, &customer, &escrow_contract, &amount);
-
-        (EscrowedPaymentCreated {
-            payment_id,
-            escrow_id,
-            escrow_contract,
+        (PaymentForwardConfigSet {
+            merchant: merchant.clone(),
+            forward_to,
+            forward_bps,
         })
         .publish(&env);
 
-        Ok((payment_id, escrow_id))
+        Ok(())
     }
 
-    /// Completes an escrowed payment by releasing funds from the escrow contract.
+    /// Removes the payment forwarding configuration for a merchant.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing the completion (must be in the multisig admin list)
-    /// * `payment_id` - The ID of the escrowed payment to complete
+    /// * `merchant` - The merchant removing forwarding (must authorize)
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
-    /// found, is not in Pending status, has an unresolved dispute, or the escrow release fails.
-    pub fn complete_escrowed_payment(
-        env: Env,
-        admin: Address,
-        payment_id: u64,
-    ) -> Result<(), Error> {
-        admin.require_auth();
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
-        }
+    /// `Ok(())` on success, or an error if no forwarding config exists or the contract is paused.
+    pub fn remove_payment_forward(env: Env, merchant: Address) -> Result<(), Error> {
+        Self::require_not_paused(&env, "remove_payment_forward")?;
+        merchant.require_auth();
+
+        // Check if the forward config exists
         if !env
             .storage()
             .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
-
-        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
-        }
-        Self::require_merchant_not_paused(&env, &payment.merchant)?;
-        PaymentContract::require_no_unresolved_escrowed_payment_dispute(&env, payment_id)?;
-
-        // Released as this contract's own identity (not the human `admin`),
-        // so the escrow contract can recognize it as a registered trusted
-        // bridge and allow an immediate release. See
-        // EscrowContract::add_trusted_bridge / is_trusted_bridge.
-        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
-        if escrow_client
-            .try_release_escrow(
-                &env.current_contract_address(),
-                &bridge.escrow_id,
-                &bridge.auto_release_on_complete,
-            )
-            .is_err()
+            .has(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
+                merchant.clone(),
+            )))
         {
-            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+            return Err(Error::Feature(FeatureError::ForwardConfigNotFound));
         }
 
-        payment.status = PaymentStatus::Completed;
+        // Remove the forward config
         env.storage()
             .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+            .remove(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
+                merchant.clone(),
+            )));
 
-        (EscrowedPaymentCompleted {
-            payment_id,
-            escrow_id: bridge.escrow_id,
+        (PaymentForwardConfigRemoved {
+            merchant: merchant.clone(),
         })
         .publish(&env);
+
         Ok(())
     }
 
-    /// Cancels an escrowed payment and refunds the escrowed funds to the customer.
+    /// Retrieves the payment forwarding configuration for a merchant.
     ///
     /// # Arguments
-    /// * `caller` - The customer or merchant who authorized this payment (must authorize)
-    /// * `payment_id` - The ID of the escrowed payment to cancel
+    /// * `merchant` - The merchant address to query
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
-    /// status, the caller is not the customer or merchant, has an unresolved dispute,
-    /// or the escrow refund fails.
-    pub fn cancel_escrowed_payment(
-        env: Env,
-        caller: Address,
-        payment_id: u64,
-    ) -> Result<(), Error> {
-        caller.require_auth();
-        if !env
-            .storage()
+    /// `Ok(PaymentForwardConfig)` if configured, or an error if no config is found.
+    pub fn get_forward_config(env: Env, merchant: Address) -> Result<PaymentForwardConfig, Error> {
+        env.storage()
             .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
+            .get(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
+                merchant,
+            )))
+            .ok_or(Error::Feature(FeatureError::ForwardConfigNotFound))
+    }
 
-        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
-        }
-        if payment.customer != caller && payment.merchant != caller {
+    /// Configures the loyalty points program settings.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `config` - The loyalty configuration including points per unit, redemption rate,
+    ///   and expiry duration
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the config values
+    /// are invalid (zero points_per_unit or zero expiry_seconds), or the contract is paused.
+    pub fn configure_loyalty(env: Env, admin: Address, config: LoyaltyConfig) -> Result<(), Error> {
+        Self::require_not_paused(&env, "configure_loyalty")?;
+        admin.require_auth();
+
+        let multisig_config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !multisig_config.admins.contains(&admin) {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
-        PaymentContract::require_no_unresolved_escrowed_payment_dispute(&env, payment_id)?;
-
-        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
-        if escrow_client
-            .try_refund_escrow(&caller, &bridge.escrow_id)
-            .is_err()
-        {
-            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+
+        if config.points_per_unit == 0 || config.expiry_seconds == 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
         }
 
-        payment.status = PaymentStatus::Cancelled;
         env.storage()
             .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-
-        (EscrowedPaymentCancelled {
-            payment_id,
-            escrow_id: bridge.escrow_id,
-        })
-        .publish(&env);
+            .set(&DataKey::Config(ConfigKey::LoyaltyConfig), &config);
         Ok(())
     }
 
-    /// Raises a dispute on an escrowed payment, freezing the escrow funds.
+    /// Returns the loyalty points balance for a customer.
     ///
     /// # Arguments
-    /// * `caller` - The customer or merchant raising the dispute (must authorize)
-    /// * `payment_id` - The ID of the escrowed payment to dispute
-    /// * `reason` - A text description of the dispute reason
+    /// * `customer` - The customer address to query
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
-    /// status, the caller is not the customer or merchant, there is already an unresolved
-    /// dispute, or the escrow dispute invocation fails.
-    pub fn dispute_escrowed_payment(
+    /// `Some(CustomerLoyaltyBalance)` if the customer has a loyalty balance, or `None` otherwise.
+    pub fn get_loyalty_balance(env: Env, customer: Address) -> Option<CustomerLoyaltyBalance> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
+                customer,
+            )))
+    }
+
+    /// Redeems loyalty points as a discount on a specific payment.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer redeeming points (must authorize)
+    /// * `points` - The number of loyalty points to redeem
+    /// * `payment_id` - The payment to apply the discount to
+    ///
+    /// # Returns
+    /// `Ok(discount_amount)` on success, where `discount_amount` is the token amount
+    /// to deduct from the payment (capped at 50% of the payment amount), or an error
+    /// if loyalty is not configured, points are insufficient or expired, the payment is
+    /// not found, or the caller is not the payment's customer.
+    pub fn redeem_points(
         env: Env,
-        caller: Address,
+        customer: Address,
+        points: u64,
         payment_id: u64,
-        reason: String,
-    ) -> Result<(), Error> {
-        caller.require_auth();
-        if !env
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "redeem_points")?;
+        customer.require_auth();
+
+        let config: LoyaltyConfig = env
             .storage()
             .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
+            .get(&DataKey::Config(ConfigKey::LoyaltyConfig))
+            .ok_or(Error::Feature(FeatureError::LoyaltyNotConfigured))?;
+        if !config.active {
+            return Err(Error::Feature(FeatureError::LoyaltyNotConfigured));
         }
 
-        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
-        let payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
+        let mut balance: CustomerLoyaltyBalance = env
+            .storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
+                customer.clone(),
+            )))
+            .ok_or(Error::Feature(FeatureError::InsufficientPoints))?;
+
+        let now = env.ledger().timestamp();
+        if balance.expires_at != 0 && now > balance.expires_at {
+            return Err(Error::Feature(FeatureError::PointsExpired));
         }
-        if payment.customer != caller && payment.merchant != caller {
-            return Err(Error::Basic(BasicError::Unauthorized));
+
+        if balance.points < points {
+            return Err(Error::Feature(FeatureError::InsufficientPoints));
         }
-        if let Some(dispute) =
-            PaymentContract::get_escrowed_payment_dispute(env.clone(), payment_id)
-        {
-            if !dispute.resolved {
-                return Err(Error::Payment(PaymentError::AlreadyProcessed));
-            }
+
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.customer != customer {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
-        if escrow_client
-            .try_dispute_escrow(&caller, &bridge.escrow_id)
-            .is_err()
-        {
-            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+        let mut discount = (points as i128) * (config.redemption_rate as i128);
+        let max_discount = payment.amount / 2;
+        if discount > max_discount {
+            discount = max_discount;
         }
 
-        let dispute = EscrowedPaymentDispute {
-            payment_id,
-            raised_by: caller.clone(),
-            reason,
-            raised_at: env.ledger().timestamp(),
-            resolved: false,
-            resolved_at: None,
-            favor_customer: None,
-        };
+        balance.points -= points;
+        balance.last_updated = now;
         env.storage().instance().set(
-            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
-            &dispute,
+            &DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(customer.clone())),
+            &balance,
         );
 
-        (EscrowedPaymentDisputed {
-            payment_id,
-            raised_by: caller,
-        })
-        .publish(&env);
-        Ok(())
+        let existing_discount: i128 =
+            record_get(&env, &DataKey::Payment(PaymentKey::Discount(payment_id))).unwrap_or(0);
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Discount(payment_id)),
+            &(existing_discount + discount),
+        );
+
+        Ok(discount)
     }
 
-    /// Resolves an escrowed payment dispute by ruling in favor of the customer or merchant.
-    ///
-    /// # Arguments
-    /// * `admin` - The admin resolving the dispute (must be in the multisig admin list)
-    /// * `payment_id` - The ID of the escrowed payment with the dispute
-    /// * `favor_customer` - If true, funds are returned to the customer; otherwise, released to merchant
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
-    /// found, the dispute is already resolved, the payment is not in Pending status,
-    /// or the escrow resolution fails.
-    pub fn resolve_escrowed_payment_dispute(
-        env: Env,
-        admin: Address,
-        payment_id: u64,
-        favor_customer: bool,
-    ) -> Result<(), Error> {
-        admin.require_auth();
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
-        }
-        if !env
-            .storage()
+    fn get_loyalty_config(env: &Env) -> Option<LoyaltyConfig> {
+        env.storage()
             .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
+            .get(&DataKey::Config(ConfigKey::LoyaltyConfig))
+    }
 
-        let bridge = PaymentContract::get_escrowed_payment(env.clone(), payment_id)?;
-        let mut dispute = PaymentContract::get_escrowed_payment_dispute(env.clone(), payment_id)
-            .ok_or(Error::Payment(PaymentError::InvalidStatus))?;
-        if dispute.resolved {
-            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+    fn maybe_accrue_loyalty_points(env: &Env, customer: Address, amount: i128) {
+        if amount <= 0 {
+            return;
         }
+        let config = match PaymentContract::get_loyalty_config(env) {
+            Some(c) if c.active && c.points_per_unit > 0 => c,
+            _ => return,
+        };
 
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
-        }
-        Self::require_merchant_not_paused(&env, &payment.merchant)?;
+        let points = (amount / i128::from(config.points_per_unit)) as u64;
+        PaymentContract::credit_loyalty_points(env, customer, points, config.expiry_seconds);
+    }
 
-        let release_to_merchant = !favor_customer;
-        let escrow_client = EscrowContractClient::new(&env, &bridge.escrow_contract);
-        if escrow_client
-            .try_resolve_dispute(&admin, &bridge.escrow_id, &release_to_merchant)
-            .is_err()
-        {
-            return Err(Error::Feature(FeatureError::EscrowBridgeFailed));
+    fn credit_loyalty_points(env: &Env, customer: Address, points: u64, expiry_seconds: u64) {
+        if points == 0 {
+            return;
         }
 
-        if favor_customer {
-            payment.status = PaymentStatus::Refunded;
-            payment.refunded_amount = payment.amount;
+        let now = env.ledger().timestamp();
+        let mut balance: CustomerLoyaltyBalance = env
+            .storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
+                customer.clone(),
+            )))
+            .unwrap_or(CustomerLoyaltyBalance {
+                customer: customer.clone(),
+                points: 0,
+                last_updated: 0,
+                expires_at: 0,
+            });
+
+        if balance.expires_at != 0 && now > balance.expires_at {
+            balance.points = points;
         } else {
-            payment.status = PaymentStatus::Completed;
+            balance.points = balance.points.saturating_add(points);
         }
-        dispute.resolved = true;
-        dispute.resolved_at = Some(env.ledger().timestamp());
-        dispute.favor_customer = Some(favor_customer);
+        balance.last_updated = now;
+        balance.expires_at = now + expiry_seconds;
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
         env.storage().instance().set(
-            &DataKey::State(StateDataKey::EscrowedPaymentDispute(payment_id)),
-            &dispute,
+            &DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(customer)),
+            &balance,
         );
-
-        (PaymentDisputeResolved {
-            payment_id,
-            favor_customer,
-        })
-        .publish(&env);
-        Ok(())
     }
 
-    /// Retrieves the escrowed payment bridge record for a given payment.
+    // ── MERCHANT CAMPAIGNS ───────────────────────────────────────────────────
+
+    /// Creates a time-boxed cashback or points campaign funded by the merchant.
+    ///
+    /// Every completed payment to the merchant is checked against its running
+    /// campaigns, and qualifying customers are rewarded from the budget until it
+    /// runs out. A cashback budget is transferred from the merchant to the
+    /// contract; a points budget is the number of loyalty points to award.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the payment to look up
+    /// * `merchant` - The merchant running the campaign (must authorize).
+    /// * `terms` - Who qualifies, when, and how the reward is computed.
+    /// * `budget` - The initial budget, in the campaign's reward unit.
     ///
     /// # Returns
-    /// `Ok(EscrowedPayment)` on success, or an error if the escrow mapping is not found.
-    pub fn get_escrowed_payment(env: Env, payment_id: u64) -> Result<EscrowedPayment, Error> {
+    /// The new campaign ID, or an error if the terms or budget are invalid, or a
+    /// points campaign is created while the loyalty programme is inactive.
+    pub fn create_campaign(
+        env: Env,
+        merchant: Address,
+        terms: CampaignTerms,
+        budget: i128,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "create_campaign")?;
+        merchant.require_auth();
+        let now = env.ledger().timestamp();
+        if terms.ends_at <= terms.starts_at || terms.ends_at <= now {
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+        if terms.reward_bps > 10_000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        if budget <= 0
+            || terms.fixed_reward < 0
+            || terms.min_amount < 0
+            || terms.per_customer_cap < 0
+            || (terms.reward_bps == 0 && terms.fixed_reward == 0)
+        {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if terms.reward == CampaignReward::Points {
+            match PaymentContract::get_loyalty_config(&env) {
+                Some(c) if c.active => {}
+                _ => return Err(Error::Feature(FeatureError::LoyaltyNotConfigured)),
+            }
+        } else {
+            token::Client::new(&env, &terms.token).transfer(
+                &merchant,
+                env.current_contract_address(),
+                &budget,
+            );
+        }
+
+        let campaign_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::CampaignCounter))
+            .unwrap_or(0)
+            + 1;
         env.storage()
             .instance()
-            .get(&DataKey::State(StateDataKey::EscrowedPayment(payment_id)))
-            .ok_or(Error::Feature(FeatureError::EscrowMappingNotFound))
+            .set(&DataKey::Feature(FeatureKey::CampaignCounter), &campaign_id);
+
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::Campaign(campaign_id)),
+            &Campaign {
+                id: campaign_id,
+                merchant: merchant.clone(),
+                terms,
+                status: CampaignStatus::Active,
+                budget,
+                total_funded: budget,
+                total_rewarded: 0,
+            },
+        );
+        let list_key = DataKey::Feature(FeatureKey::MerchantCampaigns(merchant.clone()));
+        let mut campaigns: Vec<u64> = record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        campaigns.push_back(campaign_id);
+        record_set(&env, &list_key, &campaigns);
+
+        (CampaignCreated {
+            campaign_id,
+            merchant,
+            budget,
+        })
+        .publish(&env);
+        Ok(campaign_id)
     }
 
-    /// Retrieves the dispute record for an escrowed payment, if one exists.
+    /// Adds to a campaign's budget before it ends, reactivating it if the
+    /// budget had been exhausted.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the payment to check for disputes
+    /// * `merchant` - The campaign's merchant (must authorize).
+    /// * `campaign_id` - The campaign to fund.
+    /// * `amount` - The amount to add, in the campaign's reward unit.
     ///
     /// # Returns
-    /// `Some(EscrowedPaymentDispute)` if a dispute record exists, or `None` otherwise.
-    pub fn get_escrowed_payment_dispute(
+    /// The new budget.
+    pub fn fund_campaign(
         env: Env,
-        payment_id: u64,
-    ) -> Option<EscrowedPaymentDispute> {
-        env.storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::EscrowedPaymentDispute(
-                payment_id,
-            )))
-    }
-
-    fn require_no_unresolved_escrowed_payment_dispute(
-        env: &Env,
-        payment_id: u64,
-    ) -> Result<(), Error> {
-        if let Some(dispute) = env
-            .storage()
-            .instance()
-            .get::<DataKey, EscrowedPaymentDispute>(&DataKey::State(
-                StateDataKey::EscrowedPaymentDispute(payment_id),
-            ))
+        merchant: Address,
+        campaign_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        merchant.require_auth();
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let key = DataKey::Feature(FeatureKey::Campaign(campaign_id));
+        let mut campaign: Campaign =
+            record_get(&env, &key).ok_or(Error::Payment(PaymentError::CampaignNotFound))?;
+        if campaign.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if campaign.status == CampaignStatus::Closed
+            || env.ledger().timestamp() >= campaign.terms.ends_at
         {
-            if !dispute.resolved {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
+            return Err(Error::Payment(PaymentError::CampaignClosed));
         }
-        Ok(())
+        if campaign.terms.reward == CampaignReward::Cashback {
+            token::Client::new(&env, &campaign.terms.token).transfer(
+                &merchant,
+                env.current_contract_address(),
+                &amount,
+            );
+        }
+
+        campaign.budget += amount;
+        campaign.total_funded += amount;
+        campaign.status = CampaignStatus::Active;
+        record_set(&env, &key, &campaign);
+
+        (CampaignFunded {
+            campaign_id,
+            amount,
+            budget: campaign.budget,
+        })
+        .publish(&env);
+        Ok(campaign.budget)
     }
 
-    /// Updates the notes field on a payment.
+    /// Closes a campaign that has ended and returns its unused cashback budget
+    /// to the merchant.
     ///
     /// # Arguments
-    /// * `merchant` - The merchant who owns the payment (must authorize)
-    /// * `payment_id` - The ID of the payment to update
-    /// * `notes` - The new notes text to set on the payment
+    /// * `merchant` - The campaign's merchant (must authorize).
+    /// * `campaign_id` - The campaign to close.
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the notes are too large, the payment is not
-    /// found, or the caller is not the payment's merchant.
-    pub fn update_payment_notes(
+    /// The budget reclaimed, or an error if the campaign has not ended yet or is
+    /// already closed.
+    pub fn reclaim_campaign_budget(
         env: Env,
         merchant: Address,
-        payment_id: u64,
-        notes: String,
-    ) -> Result<(), Error> {
+        campaign_id: u64,
+    ) -> Result<i128, Error> {
         merchant.require_auth();
+        let key = DataKey::Feature(FeatureKey::Campaign(campaign_id));
+        let mut campaign: Campaign =
+            record_get(&env, &key).ok_or(Error::Payment(PaymentError::CampaignNotFound))?;
+        if campaign.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if campaign.status == CampaignStatus::Closed {
+            return Err(Error::Payment(PaymentError::CampaignClosed));
+        }
+        if env.ledger().timestamp() < campaign.terms.ends_at {
+            return Err(Error::Payment(PaymentError::CampaignNotEnded));
+        }
 
-        // Validate notes size
-        if notes.len() > MAX_NOTES_SIZE {
-            return Err(Error::Basic(BasicError::NotesTooLarge));
+        let amount = campaign.budget;
+        campaign.budget = 0;
+        campaign.status = CampaignStatus::Closed;
+        record_set(&env, &key, &campaign);
+        if campaign.terms.reward == CampaignReward::Cashback && amount > 0 {
+            token::Client::new(&env, &campaign.terms.token).transfer(
+                &env.current_contract_address(),
+                &merchant,
+                &amount,
+            );
         }
 
-        // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
+        let list_key = DataKey::Feature(FeatureKey::MerchantCampaigns(merchant.clone()));
+        let mut campaigns: Vec<u64> = record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        if let Some(index) = campaigns.first_index_of(campaign_id) {
+            campaigns.remove(index);
+            record_set(&env, &list_key, &campaigns);
         }
 
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        (CampaignBudgetReclaimed {
+            campaign_id,
+            merchant,
+            amount,
+        })
+        .publish(&env);
+        Ok(amount)
+    }
 
-        // Verify caller is the merchant
-        if payment.merchant != merchant {
-            return Err(Error::Basic(BasicError::Unauthorized));
-        }
+    /// Returns a campaign by ID, if it exists.
+    pub fn get_campaign(env: Env, campaign_id: u64) -> Option<Campaign> {
+        record_get(&env, &DataKey::Feature(FeatureKey::Campaign(campaign_id)))
+    }
 
-        // Update notes
-        payment.notes = notes;
+    /// Returns the IDs of the merchant's campaigns that have not been closed.
+    pub fn get_merchant_campaigns(env: Env, merchant: Address) -> Vec<u64> {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::MerchantCampaigns(merchant)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
 
-        // Save updated payment
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+    /// Returns the total a customer has been rewarded by a campaign.
+    pub fn get_campaign_rewards(env: Env, campaign_id: u64, customer: Address) -> i128 {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::CampaignRewards(campaign_id, customer)),
+        )
+        .unwrap_or(0)
+    }
 
-        Ok(())
+    /// Returns the cashback held on a payment until its chargeback window closes.
+    pub fn get_held_cashback(env: Env, payment_id: u64) -> Vec<HeldCashback> {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::HeldCashback(payment_id)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
     }
 
-    /// Checks whether a payment has expired based on its expiration timestamp.
+    /// Releases the cashback campaigns awarded on a payment.
+    ///
+    /// Anyone may call this once the payment's chargeback window has closed, or
+    /// as soon as it is fully refunded. The customer is paid the share of each
+    /// reward matching the part of the payment that was not refunded or lost to
+    /// a chargeback; the rest is forfeited back to the campaign's budget, or to
+    /// the merchant if the campaign has been closed.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the payment to check
+    /// * `payment_id` - The payment whose cashback to release
     ///
     /// # Returns
-    /// `true` if the payment exists, has a non-zero expiration, and the current time
-    /// exceeds the expiration; `false` otherwise.
-    pub fn is_payment_expired(env: &Env, payment_id: u64) -> bool {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return false;
+    /// `Ok(paid)` with the cashback paid to the customer, or an error if nothing
+    /// is held, the chargeback window is still open, a chargeback is undecided,
+    /// or one of the campaigns can no longer be read. Nothing is released on
+    /// error, so the held cashback can be released once the campaign is restored.
+    pub fn release_campaign_cashback(env: Env, payment_id: u64) -> Result<i128, Error> {
+        let held_key = DataKey::Feature(FeatureKey::HeldCashback(payment_id));
+        let held: Vec<HeldCashback> =
+            record_get(&env, &held_key).ok_or(Error::Payment(PaymentError::NothingToSettle))?;
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.status != PaymentStatus::Refunded {
+            let window_ends = payment.created_at + Self::get_chargeback_window(env.clone());
+            if env.ledger().timestamp() <= window_ends {
+                return Err(Error::Payment(PaymentError::NotYetDue));
+            }
+            Self::require_no_pending_chargeback(&env, payment_id)?;
+        }
+
+        let kept = (payment.amount - payment.refunded_amount).max(0);
+        let mut paid = 0;
+        for entry in held.iter() {
+            let key = DataKey::Feature(FeatureKey::Campaign(entry.campaign_id));
+            let mut campaign: Campaign =
+                record_get(&env, &key).ok_or(Error::Payment(PaymentError::CampaignNotFound))?;
+            let payout = entry.amount * kept / payment.amount;
+            let forfeited = entry.amount - payout;
+            if payout > 0 {
+                token::Client::new(&env, &campaign.terms.token).transfer(
+                    &env.current_contract_address(),
+                    &payment.customer,
+                    &payout,
+                );
+                paid += payout;
+                (CampaignRewardPaid {
+                    campaign_id: entry.campaign_id,
+                    payment_id,
+                    customer: payment.customer.clone(),
+                    amount: payout,
+                })
+                .publish(&env);
+            }
+            if forfeited <= 0 {
+                continue;
+            }
+
+            let rewards_key = DataKey::Feature(FeatureKey::CampaignRewards(
+                entry.campaign_id,
+                payment.customer.clone(),
+            ));
+            let rewarded: i128 = record_get(&env, &rewards_key).unwrap_or(0);
+            record_set(&env, &rewards_key, &(rewarded - forfeited));
+            campaign.total_rewarded -= forfeited;
+            if campaign.status == CampaignStatus::Closed {
+                token::Client::new(&env, &campaign.terms.token).transfer(
+                    &env.current_contract_address(),
+                    &campaign.merchant,
+                    &forfeited,
+                );
+            } else {
+                campaign.budget += forfeited;
+                campaign.status = CampaignStatus::Active;
+            }
+            record_set(&env, &key, &campaign);
+            (CampaignCashbackForfeited {
+                campaign_id: entry.campaign_id,
+                payment_id,
+                amount: forfeited,
+            })
+            .publish(&env);
         }
-        let payment = PaymentContract::get_payment(env, payment_id);
-        payment.expires_at > 0 && env.ledger().timestamp() > payment.expires_at
+        record_remove(&env, &held_key);
+        Ok(paid)
     }
 
-    /// Expires a payment that has passed its expiration time, refunding the customer.
+    /// Rewards the customer of a completed payment from each of the merchant's
+    /// campaigns it qualifies for, and counts the purchase for first-purchase
+    /// campaigns. Points are credited at once; cashback is taken from the
+    /// budget but held for `release_campaign_cashback`.
+    fn apply_campaigns(env: &Env, payment: &Payment) {
+        let purchases_key = DataKey::Feature(FeatureKey::CustomerPurchases(
+            payment.merchant.clone(),
+            payment.customer.clone(),
+        ));
+        let previous_purchases: u32 = record_get(env, &purchases_key).unwrap_or(0);
+        record_set(env, &purchases_key, &(previous_purchases + 1));
+
+        let campaigns: Vec<u64> = record_get(
+            env,
+            &DataKey::Feature(FeatureKey::MerchantCampaigns(payment.merchant.clone())),
+        )
+        .unwrap_or_else(|| Vec::new(env));
+        let now = env.ledger().timestamp();
+        let mut held: Vec<HeldCashback> = Vec::new(env);
+        for campaign_id in campaigns.iter() {
+            let key = DataKey::Feature(FeatureKey::Campaign(campaign_id));
+            let mut campaign: Campaign = match record_get(env, &key) {
+                Some(campaign) => campaign,
+                None => continue,
+            };
+            let terms = &campaign.terms;
+            if campaign.status != CampaignStatus::Active
+                || terms.token != payment.token
+                || now < terms.starts_at
+                || now >= terms.ends_at
+                || payment.amount < terms.min_amount
+                || (terms.first_purchase_only && previous_purchases > 0)
+            {
+                continue;
+            }
+
+            let rewards_key = DataKey::Feature(FeatureKey::CampaignRewards(
+                campaign_id,
+                payment.customer.clone(),
+            ));
+            let rewarded: i128 = record_get(env, &rewards_key).unwrap_or(0);
+            let mut reward =
+                terms.fixed_reward + payment.amount * i128::from(terms.reward_bps) / 10_000;
+            if terms.per_customer_cap > 0 {
+                reward = reward.min(terms.per_customer_cap - rewarded);
+            }
+            let reward = reward.min(campaign.budget);
+            if reward <= 0 {
+                continue;
+            }
+
+            match terms.reward {
+                CampaignReward::Cashback => {
+                    held.push_back(HeldCashback {
+                        campaign_id,
+                        amount: reward,
+                    });
+                    (CampaignCashbackHeld {
+                        campaign_id,
+                        payment_id: payment.id,
+                        customer: payment.customer.clone(),
+                        amount: reward,
+                    })
+                    .publish(env);
+                }
+                CampaignReward::Points => {
+                    let expiry_seconds = match PaymentContract::get_loyalty_config(env) {
+                        Some(c) if c.active => c.expiry_seconds,
+                        _ => continue,
+                    };
+                    PaymentContract::credit_loyalty_points(
+                        env,
+                        payment.customer.clone(),
+                        reward as u64,
+                        expiry_seconds,
+                    );
+                    (CampaignRewardPaid {
+                        campaign_id,
+                        payment_id: payment.id,
+                        customer: payment.customer.clone(),
+                        amount: reward,
+                    })
+                    .publish(env);
+                }
+            }
+            record_set(env, &rewards_key, &(rewarded + reward));
+            campaign.budget -= reward;
+            campaign.total_rewarded += reward;
+
+            if campaign.budget == 0 {
+                campaign.status = CampaignStatus::Exhausted;
+                (CampaignExhausted { campaign_id }).publish(env);
+            }
+            record_set(env, &key, &campaign);
+        }
+        if !held.is_empty() {
+            record_set(
+                env,
+                &DataKey::Feature(FeatureKey::HeldCashback(payment.id)),
+                &held,
+            );
+        }
+    }
+
+    /// Pays an installment toward a pending payment.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the payment to expire
+    /// * `customer` - The customer making the installment payment (must authorize)
+    /// * `payment_id` - The ID of the payment to pay toward
+    /// * `amount` - The installment amount in base token units
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
-    /// status, has no expiration set, or has not yet expired.
-    pub fn expire_payment(env: Env, payment_id: u64) -> Result<(), Error> {
-        // Retrieve payment from storage
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
+    /// `Ok(())` on success, or an error if the amount is non-positive, the payment is expired
+    /// or not in Pending status, the installment exceeds the outstanding balance, or the
+    /// contract is paused. Automatically finalizes the payment if fully paid.
+    pub fn pay_installment(
+        env: Env,
+        customer: Address,
+        payment_id: u64,
+        amount: i128,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, "pay_installment")?;
+        customer.require_auth();
+        PaymentContract::do_pay_installment(&env, customer, payment_id, amount)?;
+        Ok(())
+    }
+
+    /// Records one installment and finalizes the payment once nothing is
+    /// outstanding. Returns the remaining balance.
+    fn do_pay_installment(
+        env: &Env,
+        customer: Address,
+        payment_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
         }
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
 
-        // Check payment status allows expiry (only allow Pending)
-        match payment.status {
-            PaymentStatus::Pending => {
-                // Allow expiry
-            }
-            PaymentStatus::Refunded | PaymentStatus::PartialRefunded | PaymentStatus::Cancelled => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-            PaymentStatus::Completed => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
+        let payment = PaymentContract::get_payment(env, payment_id);
+
+        // Check if payment is expired
+        if PaymentContract::is_payment_expired(env, payment_id) {
+            return Err(Error::Payment(PaymentError::Expired));
         }
 
-        // Check payment has expiration set
-        if payment.expires_at == 0 {
-            return Err(Error::Payment(PaymentError::NoExpiration));
+        // Only allow installments on Pending payments
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
         }
 
-        // Check current time is past expires_at
-        if env.ledger().timestamp() <= payment.expires_at {
-            return Err(Error::Payment(PaymentError::NotExpired));
+        // Get current outstanding balance
+        let outstanding_balance = PaymentContract::get_outstanding_balance(env.clone(), payment_id);
+
+        if outstanding_balance <= 0 {
+            return Err(Error::Payment(PaymentError::AlreadyFullyPaid));
         }
 
-        // Transfer token amount back to customer (automatic refund)
-        let token_client = token::Client::new(&env, &payment.token);
-        let contract_address = env.current_contract_address();
+        if amount > outstanding_balance {
+            return Err(Error::Payment(PaymentError::InstallmentExceedsRemaining));
+        }
 
-        // Check if contract has sufficient balance (in case of partial payments)
-        let contract_balance = token_client.balance(&contract_address);
-        let refund_amount = if contract_balance >= payment.amount {
-            payment.amount
-        } else {
-            contract_balance
+        // Get current installment counter
+        let installment_counter: u32 = record_get(
+            env,
+            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+        )
+        .unwrap_or(0);
+        let new_installment_number = installment_counter + 1;
+
+        // Transfer tokens from customer to contract
+        let token_client = token::Client::new(env, &payment.token);
+        token_client.transfer(&customer, &env.current_contract_address(), &amount);
+
+        // Create partial payment record
+        let remaining = outstanding_balance - amount;
+        let partial_payment = PartialPaymentRecord {
+            payment_id,
+            installment_number: new_installment_number,
+            amount_paid: amount,
+            total_amount: payment.amount,
+            remaining,
+            paid_at: env.ledger().timestamp(),
+            payer: customer.clone(),
         };
 
-        if refund_amount > 0 {
-            token_client.transfer(&contract_address, &payment.customer, &refund_amount);
-        }
+        // Store partial payment record
+        record_set(
+            env,
+            &DataKey::State(StateDataKey::PartialPaymentRecord(
+                payment_id,
+                new_installment_number,
+            )),
+            &partial_payment,
+        );
 
-        // Update payment status to Cancelled
-        payment.status = PaymentStatus::Cancelled;
+        // Update installment counter
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+            &new_installment_number,
+        );
 
-        // Store updated payment back to storage
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+        // Update outstanding balance
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
+            &remaining,
+        );
 
-        // Emit PaymentExpired event with refund info
-        (PaymentExpired {
+        // Emit installment paid event
+        (InstallmentPaid {
             payment_id,
-            customer: payment.customer,
-            refunded_amount: refund_amount,
-            expired_at: env.ledger().timestamp(),
+            installment_number: new_installment_number,
+            amount,
+            remaining,
+            payer: customer,
+            paid_at: partial_payment.paid_at,
         })
-        .publish(&env);
+        .publish(env);
 
-        Ok(())
+        // Check if payment is now fully paid
+        if remaining == 0 {
+            PaymentContract::finalize_installment_payment(env.clone(), payment_id)?;
+        }
+
+        Ok(remaining)
     }
 
-    /// Completes a pending payment, transferring funds from the customer to the merchant.
-    ///
-    /// If the payment exceeds the large payment threshold, a multisig proposal is
-    /// automatically created instead of completing immediately.
+    /// Returns the full installment payment history for a given payment.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing the completion (must be in the multisig admin list)
-    /// * `payment_id` - The ID of the payment to complete
+    /// * `payment_id` - The ID of the payment to retrieve installment history for
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
-    /// found, is not in Pending status, is expired, or the multisig threshold is not met.
-    /// Returns `ProposalError::RequiresMultiSig` if a multisig proposal was auto-created.
-    pub fn complete_payment(env: Env, admin: Address, payment_id: u64) -> Result<(), Error> {
-        Self::require_not_paused(&env, "complete_payment")?;
-        admin.require_auth();
+    /// A `Vec<PartialPaymentRecord>` of all installments made toward the payment.
+    pub fn get_installment_history(env: Env, payment_id: u64) -> Vec<PartialPaymentRecord> {
+        let installment_counter: u32 = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+        )
+        .unwrap_or(0);
 
-        // Verify caller is in the multisig admin list
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
+        let mut history = Vec::new(&env);
+        for i in 1..=installment_counter {
+            if let Some(record) = record_get(
+                &env,
+                &DataKey::State(StateDataKey::PartialPaymentRecord(payment_id, i)),
+            ) {
+                history.push_back(record);
+            }
         }
+        history
+    }
 
-        // Check if payment requires multi-sig approval
+    /// Returns the outstanding balance remaining on a payment.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the payment to check
+    ///
+    /// # Returns
+    /// The remaining amount in base token units. Returns the full payment amount if no
+    /// partial payments have been made.
+    pub fn get_outstanding_balance(env: Env, payment_id: u64) -> i128 {
+        // First check if we have an outstanding balance stored
+        if let Some(balance) = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
+        ) {
+            return balance;
+        }
+
+        // If not, calculate from payment amount and partial payments
         let payment = PaymentContract::get_payment(&env, payment_id);
-        let threshold = PaymentContract::get_large_payment_threshold(env.clone());
+        let installment_counter: u32 = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+        )
+        .unwrap_or(0);
 
-        if threshold > 0 && payment.amount > threshold {
-            // Check if there's already a proposal for this payment
-            if env
-                .storage()
-                .instance()
-                .get::<DataKey, LargePaymentProposal>(&DataKey::State(
-                    StateDataKey::LargePaymentProposal(payment_id),
-                ))
-                .is_some()
-            {
-                return Err(Error::Proposal(ProposalError::RequiresMultiSig));
+        let mut total_paid = 0i128;
+        for i in 1..=installment_counter {
+            if let Some(record) = record_get::<DataKey, PartialPaymentRecord>(
+                &env,
+                &DataKey::State(StateDataKey::PartialPaymentRecord(payment_id, i)),
+            ) {
+                total_paid += record.amount_paid;
             }
+        }
 
-            // Auto-create proposal for large payment
-            let now = env.ledger().timestamp();
-            let expires_at = now + config.proposal_ttl;
-
-            let mut approvals = Vec::new(&env);
-            approvals.push_back(admin.clone());
+        let outstanding = payment.amount - total_paid;
 
-            let proposal = LargePaymentProposal {
-                payment_id,
-                approvals,
-                required: config.required_signatures,
-                proposed_at: now,
-                expires_at,
-                executed: false,
-            };
+        // Cache the calculated balance
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
+            &outstanding,
+        );
 
-            env.storage().instance().set(
-                &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
-                &proposal,
-            );
+        outstanding
+    }
 
-            (LargePaymentProposed {
-                payment_id,
-                proposer: admin,
-                required_approvals: config.required_signatures,
-                expires_at,
-            })
-            .publish(&env);
+    /// Finalizes a payment that has been fully paid through installments.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The ID of the payment to finalize
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the payment is not in Pending status or
+    /// the outstanding balance is not zero.
+    pub fn finalize_installment_payment(env: Env, payment_id: u64) -> Result<(), Error> {
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
 
-            return Err(Error::Proposal(ProposalError::RequiresMultiSig));
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
         }
 
-        PaymentContract::do_complete_payment(&env, payment_id)
-    }
-
-    fn do_complete_payment(env: &Env, payment_id: u64) -> Result<(), Error> {
-        // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
+        let outstanding_balance = PaymentContract::get_outstanding_balance(env.clone(), payment_id);
+        if outstanding_balance != 0 {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
         }
 
-        let mut payment = PaymentContract::get_payment(env, payment_id);
+        // Get installment counter for event
+        let installment_counter: u32 = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
+        )
+        .unwrap_or(0);
 
-        // Before updating status, check if payment is expired
-        if PaymentContract::is_payment_expired(env, payment_id) {
-            return Err(Error::Payment(PaymentError::Expired));
-        }
+        // Update payment status to Completed
+        payment.status = PaymentStatus::Completed;
+        Self::mark_completed(&env, payment_id);
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
 
-        match payment.status {
-            PaymentStatus::Pending => {
-                payment.status = PaymentStatus::Completed;
-            }
-            PaymentStatus::Completed => {
-                return Err(Error::Payment(PaymentError::AlreadyProcessed));
-            }
-            PaymentStatus::Refunded | PaymentStatus::PartialRefunded => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-            PaymentStatus::Cancelled => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-        }
+        // Transfer or accumulate all collected funds to merchant, less any application
+        // fee and the rolling reserve
+        let settled = payment.amount
+            - Self::collect_application_fee(
+                &env,
+                &payment,
+                payment.amount,
+                &env.current_contract_address(),
+            );
+        let reserve = Self::withhold_reserve(&env, &payment, settled);
+        Self::settle_or_accumulate(
+            &env,
+            payment.merchant.clone(),
+            payment.token.clone(),
+            settled - reserve,
+        )?;
 
-        // Subtract any loyalty-point discount redeemed against this payment (#490)
-        let discount: i128 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Discount(payment_id)))
-            .unwrap_or(0);
-        let charge_amount = if discount > 0 {
-            env.storage()
-                .instance()
-                .remove(&DataKey::Payment(PaymentKey::Discount(payment_id)));
-            let capped_discount = if discount > payment.amount {
-                payment.amount
-            } else {
-                discount
-            };
-            payment.amount - capped_discount
-        } else {
-            payment.amount
-        };
+        // Emit payment fully paid event
+        (PaymentFullyPaid {
+            payment_id,
+            total_installments: installment_counter,
+            completed_at: env.ledger().timestamp(),
+        })
+        .publish(&env);
 
-        // Deduct platform fee (if configured) and get net amount for merchant
-        let (net_amount, fee_amount) = PaymentContract::deduct_fee(
-            env,
+        // Also emit standard payment completed event for compatibility
+        (PaymentCompleted {
             payment_id,
-            charge_amount,
-            payment.merchant.clone(),
-            &payment.token,
-            &payment.customer,
-            payment.currency.clone(),
-        );
+            merchant: payment.merchant,
+            amount: payment.amount,
+        })
+        .publish(&env);
 
-        // Check finality delay config (#219)
-        let finality: Option<FinalityConfig> = env
+        Ok(())
+    }
+
+    /// Refunds a pending payment in full, returning funds to the customer.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing the refund (must be in the multisig admin list)
+    /// * `payment_id` - The ID of the payment to refund
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
+    /// found, is expired, is not in Pending status, or the contract is paused.
+    pub fn refund_payment(env: Env, admin: Address, payment_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, "refund_payment")?;
+        admin.require_auth();
+
+        // Verify caller is in the multisig admin list
+        let config: MultiSigConfig = env
             .storage()
             .instance()
-            .get(&DataKey::Config(ConfigKey::FinalityConfig));
-        if let Some(ref fc) = finality {
-            if fc.active && payment.amount >= fc.min_amount_threshold {
-                // Hold funds — create PendingSettlement instead of transferring
-                let release_at = env.ledger().timestamp() + fc.delay_seconds;
-                let settlement = PendingSettlement {
-                    payment_id,
-                    merchant: payment.merchant.clone(),
-                    amount: net_amount,
-                    token: payment.token.clone(),
-                    release_at,
-                };
-                env.storage().instance().set(
-                    &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
-                    &settlement,
-                );
-                let idx: u64 = env
-                    .storage()
-                    .instance()
-                    .get(&DataKey::Merchant(MerchantDataKey::PendingSettlementCount(
-                        payment.merchant.clone(),
-                    )))
-                    .unwrap_or(0);
-                env.storage().instance().set(
-                    &DataKey::Merchant(MerchantDataKey::PendingSettlementIndex(
-                        payment.merchant.clone(),
-                        idx,
-                    )),
-                    &payment_id,
-                );
-                env.storage().instance().set(
-                    &DataKey::Merchant(MerchantDataKey::PendingSettlementCount(
-                        payment.merchant.clone(),
-                    )),
-                    &(idx + 1),
-                );
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-                PaymentContract::update_merchant_fee_record_post_completion(
-                    env,
-                    payment.merchant.clone(),
-                    payment.amount,
-                    fee_amount,
-                );
-                let mut analytics: PaymentAnalytics = env
-                    .storage()
-                    .instance()
-                    .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
-                    .unwrap_or(PaymentAnalytics {
-                        total_payments_created: 0,
-                        total_payments_completed: 0,
-                        total_payments_cancelled: 0,
-                        total_payments_refunded: 0,
-                        total_volume: 0,
-                        total_refunded_volume: 0,
-                        unique_customers: 0,
-                        unique_merchants: 0,
-                    });
-                analytics.total_payments_completed += 1;
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
-                (PaymentCompleted {
-                    payment_id,
-                    merchant: payment.merchant.clone(),
-                    amount: payment.amount,
-                })
-                .publish(env);
-                return Ok(());
-            }
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        // Token transfer: net amount from customer to merchant
-        let token_client = token::Client::new(env, &payment.token);
-        let contract_address = env.current_contract_address();
+        PaymentContract::do_refund_payment(&env, payment_id)
+    }
 
-        token_client.transfer_from(
-            &contract_address,
-            &payment.customer,
-            &payment.merchant,
-            &net_amount,
-        );
+    fn do_refund_payment(env: &Env, payment_id: u64) -> Result<(), Error> {
+        // Check if payment exists
+        if !record_has(env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
 
-        // Check if merchant has an active payment forward config
-        if let Ok(forward_config) =
-            PaymentContract::get_forward_config(env.clone(), payment.merchant.clone())
-        {
-            if forward_config.active {
-                // Calculate the forward amount based on forward_bps
-                let forward_amount = (net_amount * (forward_config.forward_bps as i128)) / 10000;
+        let mut payment = PaymentContract::get_payment(env, payment_id);
 
-                // Transfer the forward amount from merchant to forward_to address
-                if forward_amount > 0 {
-                    token_client.transfer(
-                        &payment.merchant,
-                        &forward_config.forward_to,
-                        &forward_amount,
-                    );
+        // Before updating status, check if payment is expired
+        if PaymentContract::is_payment_expired(env, payment_id) {
+            return Err(Error::Payment(PaymentError::Expired));
+        }
 
-                    (PaymentForwarded {
-                        payment_id,
-                        merchant: payment.merchant.clone(),
-                        forward_to: forward_config.forward_to,
-                        forward_amount,
-                    })
-                    .publish(env);
-                }
+        match payment.status {
+            PaymentStatus::Pending => {
+                payment.status = PaymentStatus::Refunded;
+                payment.refunded_amount = payment.amount;
+            }
+            PaymentStatus::Completed | PaymentStatus::PartialRefunded => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+            PaymentStatus::Refunded => {
+                return Err(Error::Payment(PaymentError::AlreadyProcessed));
+            }
+            PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
             }
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-        PaymentContract::update_merchant_fee_record_post_completion(
+        record_set(
             env,
-            payment.merchant.clone(),
-            payment.amount,
-            fee_amount,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
         );
 
         // Update analytics
@@ -3919,7 +7783,8 @@ impl PaymentContract {
                 unique_customers: 0,
                 unique_merchants: 0,
             });
-        analytics.total_payments_completed += 1;
+        analytics.total_payments_refunded += 1;
+        analytics.total_refunded_volume += payment.amount;
         env.storage()
             .instance()
             .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
@@ -3937,635 +7802,899 @@ impl PaymentContract {
                 total_refunded: 0,
                 total_refunded_volume: 0,
             });
-        m_analytics.total_completed += 1;
+        m_analytics.total_refunded += 1;
+        m_analytics.total_refunded_volume += payment.amount;
         env.storage().instance().set(
             &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
             &m_analytics,
         );
-        (PaymentCompleted {
-            payment_id,
-            merchant: payment.merchant.clone(),
-            amount: payment.amount,
-        })
-        .publish(env);
-
-        // Accrue loyalty points for completed payments if loyalty is configured.
-        PaymentContract::maybe_accrue_loyalty_points(
-            &env,
-            payment.customer.clone(),
-            payment.amount,
-        );
-
-        // Accrue fee rebate for merchant if rebate programme is active
-        PaymentContract::maybe_accrue_fee_rebate(
-            env,
-            payment.merchant.clone(),
-            payment.amount,
-            fee_amount,
-        );
-
-        // Attempt to trigger auto-escrow if a rule exists
-        // Ignore errors - if there's no rule, payment is below minimum, or already triggered,
-        // we just skip the auto-escrow (the payment completion still succeeds)
-        let _ = PaymentContract::trigger_auto_escrow(env, payment_id);
-        let now = env.ledger().timestamp();
-        PaymentContract::update_merchant_bucket(env, payment.merchant.clone(), now, 0, 0, 0, 0);
-        PaymentContract::update_platform_daily_bucket(env, now, 0, 0, 0);
-
-        Ok(())
-    }
-
-    /// Configures automatic payment forwarding for a merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant setting up forwarding (must authorize)
-    /// * `forward_to` - The address to forward a portion of payments to
-    /// * `forward_bps` - The percentage to forward in basis points (1-10000)
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the forward_bps is invalid, a forwarding loop
-    /// is detected, or the contract is paused.
-    pub fn set_payment_forward(
-        env: Env,
-        merchant: Address,
-        forward_to: Address,
-        forward_bps: u32,
-    ) -> Result<(), Error> {
-        Self::require_not_paused(&env, "set_payment_forward")?;
-        merchant.require_auth();
-
-        // Validate forward_bps: must be between 1 and 10000
-        if let Err(_) = Self::validate_bps(forward_bps) {
-            return Err(Error::Feature(FeatureError::InvalidForwardBps));
-        }
-
-        // Get configured max depth (default 5)
-        let max_depth: u32 = env
+        let mut c_analytics: CustomerAnalytics = env
             .storage()
             .instance()
-            .get(&DataKey::Config(ConfigKey::MaxForwardDepth))
-            .unwrap_or(5);
-
-        // Detect cycles and enforce depth limit by walking the forward chain
-        {
-            let mut current = forward_to.clone();
-            for _depth in 0..max_depth {
-                if current == merchant {
-                    return Err(Error::Feature(FeatureError::ForwardLoop));
-                }
-                match env
-                    .storage()
-                    .instance()
-                    .get::<DataKey, PaymentForwardConfig>(&DataKey::Feature(
-                        FeatureKey::PaymentForwardConfig(current.clone()),
-                    )) {
-                    Some(next) => current = next.forward_to,
-                    None => break,
-                }
-            }
-            // If we exhausted max_depth iterations, the chain is too long
-            if env
-                .storage()
-                .instance()
-                .has(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
-                    current.clone(),
-                )))
-            {
-                return Err(Error::Feature(FeatureError::ForwardLoop));
-            }
-        }
-
-        // Create and store the forward config
-        let config = PaymentForwardConfig {
-            merchant: merchant.clone(),
-            forward_to: forward_to.clone(),
-            forward_bps,
-            active: true,
-        };
-
+            .get(&DataKey::Customer(CustomerDataKey::Analytics(
+                payment.customer.clone(),
+            )))
+            .unwrap_or(PaymentContract::default_customer_analytics());
+        c_analytics.total_refunds += payment.amount;
         env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::PaymentForwardConfig(merchant.clone())),
-            &config,
+            &DataKey::Customer(CustomerDataKey::Analytics(payment.customer.clone())),
+            &c_analytics,
         );
 
-        (PaymentForwardConfigSet {
-            merchant: merchant.clone(),
-            forward_to,
-            forward_bps,
+        (PaymentRefunded {
+            payment_id,
+            customer: payment.customer,
+            amount: payment.amount,
         })
-        .publish(&env);
+        .publish(env);
+
+        let now = env.ledger().timestamp();
+        PaymentContract::update_merchant_bucket(
+            env,
+            payment.merchant.clone(),
+            now,
+            0,
+            0,
+            payment.amount,
+            0,
+        );
+        PaymentContract::update_platform_daily_bucket(env, now, 0, payment.amount, 0);
 
         Ok(())
     }
 
-    /// Removes the payment forwarding configuration for a merchant.
+    /// Refunds a partial amount of a pending or partially-refunded payment.
     ///
     /// # Arguments
-    /// * `merchant` - The merchant removing forwarding (must authorize)
+    /// * `admin` - The admin authorizing the refund (must be in the multisig admin list)
+    /// * `payment_id` - The ID of the payment to partially refund
+    /// * `refund_amount` - The amount to refund in base token units
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if no forwarding config exists or the contract is paused.
-    pub fn remove_payment_forward(env: Env, merchant: Address) -> Result<(), Error> {
-        Self::require_not_paused(&env, "remove_payment_forward")?;
-        merchant.require_auth();
+    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
+    /// found, is expired, is not in a refundable status, has an undecided chargeback, or the
+    /// refund exceeds the payment amount.
+    pub fn partial_refund(
+        env: Env,
+        admin: Address,
+        payment_id: u64,
+        refund_amount: i128,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, "partial_refund")?;
+        admin.require_auth();
 
-        // Check if the forward config exists
-        if !env
+        let config: MultiSigConfig = env
             .storage()
             .instance()
-            .has(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
-                merchant.clone(),
-            )))
-        {
-            return Err(Error::Feature(FeatureError::ForwardConfigNotFound));
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        // Remove the forward config
-        env.storage()
-            .instance()
-            .remove(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
-                merchant.clone(),
-            )));
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
+        }
 
-        (PaymentForwardConfigRemoved {
-            merchant: merchant.clone(),
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+
+        if PaymentContract::is_payment_expired(&env, payment_id) {
+            return Err(Error::Payment(PaymentError::Expired));
+        }
+
+        Self::require_no_pending_chargeback(&env, payment_id)?;
+
+        match payment.status {
+            PaymentStatus::Pending | PaymentStatus::PartialRefunded => {
+                let new_refunded = payment.refunded_amount + refund_amount;
+                if new_refunded > payment.amount {
+                    return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+                }
+                payment.refunded_amount = new_refunded;
+                payment.status = if new_refunded == payment.amount {
+                    PaymentStatus::Refunded
+                } else {
+                    PaymentStatus::PartialRefunded
+                };
+            }
+            _ => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+        }
+
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+
+        (PaymentRefunded {
+            payment_id,
+            customer: payment.customer,
+            amount: refund_amount,
         })
         .publish(&env);
 
         Ok(())
     }
 
-    /// Retrieves the payment forwarding configuration for a merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
-    ///
-    /// # Returns
-    /// `Ok(PaymentForwardConfig)` if configured, or an error if no config is found.
-    pub fn get_forward_config(env: Env, merchant: Address) -> Result<PaymentForwardConfig, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentForwardConfig(
-                merchant,
-            )))
-            .ok_or(Error::Feature(FeatureError::ForwardConfigNotFound))
-    }
+    // ── Refund contract settlement link ───────────────────────────────────
 
-    /// Configures the loyalty points program settings.
+    /// Registers the refund contract allowed to settle refunds via `settle_refund`.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
-    /// * `config` - The loyalty configuration including points per unit, redemption rate,
-    ///   and expiry duration
+    /// * `admin` - The admin authorizing the change (must be in the multisig admin list)
+    /// * `refund_contract` - The address of the refund contract
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the config values
-    /// are invalid (zero points_per_unit or zero expiry_seconds), or the contract is paused.
-    pub fn configure_loyalty(env: Env, admin: Address, config: LoyaltyConfig) -> Result<(), Error> {
-        Self::require_not_paused(&env, "configure_loyalty")?;
+    /// `Ok(())` on success, or an error if the caller is not an admin or multisig is not initialized.
+    pub fn set_refund_contract(
+        env: Env,
+        admin: Address,
+        refund_contract: Address,
+    ) -> Result<(), Error> {
         admin.require_auth();
-
-        let multisig_config: MultiSigConfig = env
+        let config: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !multisig_config.admins.contains(&admin) {
+        if !config.admins.contains(&admin) {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
+        env.storage().instance().set(
+            &DataKey::Config(ConfigKey::RefundContract),
+            &refund_contract,
+        );
+        Ok(())
+    }
 
-        if config.points_per_unit == 0 || config.expiry_seconds == 0 {
-            return Err(Error::Basic(BasicError::InvalidAmount));
-        }
-
+    /// Returns the registered refund contract, if any.
+    ///
+    /// # Returns
+    /// `Some(Address)` if a refund contract has been registered, `None` otherwise.
+    pub fn get_refund_contract(env: Env) -> Option<Address> {
         env.storage()
             .instance()
-            .set(&DataKey::Config(ConfigKey::LoyaltyConfig), &config);
-        Ok(())
+            .get(&DataKey::Config(ConfigKey::RefundContract))
     }
 
-    /// Returns the loyalty points balance for a customer.
+    /// Settles a refund processed by the registered refund contract against a payment.
+    ///
+    /// Adds `amount` to `refunded_amount` and moves the payment to `PartialRefunded` or
+    /// `Refunded`. The refunded total is shared with `refund_payment` and `partial_refund`,
+    /// so a payment can never be refunded past its amount across both paths. Funds still
+    /// held in custody for the payment (an unreleased finality-delay settlement) are
+    /// transferred to the refund contract, up to `amount`. Any remainder is drawn from
+    /// the merchant's accumulated payout balance in the payment token. The rolling
+    /// reserve is left alone here; the refund contract draws on it through
+    /// `draw_reserve_for_refund` only if its own merchant refund reserve falls short.
     ///
     /// # Arguments
-    /// * `customer` - The customer address to query
+    /// * `refund_contract` - The calling refund contract (must authorize and be registered)
+    /// * `payment_id` - The ID of the payment being refunded
+    /// * `merchant` - The merchant the refund is booked against
+    /// * `token` - The token the refund is paid in
+    /// * `amount` - The refund amount in base token units
     ///
     /// # Returns
-    /// `Some(CustomerLoyaltyBalance)` if the customer has a loyalty balance, or `None` otherwise.
-    pub fn get_loyalty_balance(env: Env, customer: Address) -> Option<CustomerLoyaltyBalance> {
-        env.storage()
+    /// `Ok(released)` with the amount transferred out of custody, or an error if the caller
+    /// is not the registered refund contract, the amount is not positive, the payment is not
+    /// found or never completed, the merchant or token differs from the payment's
+    /// (`RefundMismatch`), the payment has an undecided chargeback, or the refund exceeds
+    /// the remaining refundable amount.
+    pub fn settle_refund(
+        env: Env,
+        refund_contract: Address,
+        payment_id: u64,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        refund_contract.require_auth();
+        let registered: Option<Address> = env
+            .storage()
             .instance()
-            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
-                customer,
-            )))
+            .get(&DataKey::Config(ConfigKey::RefundContract));
+        if registered != Some(refund_contract.clone()) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+
+        let mut payment: Payment =
+            record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)))
+                .ok_or(Error::Payment(PaymentError::NotFound))?;
+        if payment.merchant != merchant || payment.token != token {
+            return Err(Error::Payment(PaymentError::RefundMismatch));
+        }
+        Self::require_no_pending_chargeback(&env, payment_id)?;
+        match payment.status {
+            PaymentStatus::Completed | PaymentStatus::PartialRefunded => {}
+            PaymentStatus::Refunded => {
+                return Err(Error::Payment(PaymentError::AlreadyProcessed));
+            }
+            PaymentStatus::Pending | PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+        }
+        Self::require_completed(&env, payment_id)?;
+        let refunded_amount = payment
+            .refunded_amount
+            .checked_add(amount)
+            .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+        if refunded_amount > payment.amount {
+            return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+        }
+        payment.refunded_amount = refunded_amount;
+        payment.status = if refunded_amount == payment.amount {
+            PaymentStatus::Refunded
+        } else {
+            PaymentStatus::PartialRefunded
+        };
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+
+        // Claw the refund back out of a settlement that has not been released yet
+        let mut released: i128 = 0;
+        if !record_has(
+            &env,
+            &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+        ) {
+            if let Some(mut settlement) = record_get::<DataKey, PendingSettlement>(
+                &env,
+                &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+            ) {
+                released = amount.min(settlement.amount);
+                if released > 0 {
+                    settlement.amount -= released;
+                    record_set(
+                        &env,
+                        &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                        &settlement,
+                    );
+                    token::Client::new(&env, &payment.token).transfer(
+                        &env.current_contract_address(),
+                        &refund_contract,
+                        &released,
+                    );
+                }
+            }
+        }
+        if released < amount {
+            released += Self::draw_payout_balance(
+                &env,
+                &payment.merchant,
+                &payment.token,
+                amount - released,
+                &refund_contract,
+            );
+        }
+
+        (PaymentRefunded {
+            payment_id,
+            customer: payment.customer,
+            amount,
+        })
+        .publish(&env);
+        (RefundSettled {
+            payment_id,
+            refund_contract,
+            amount,
+            refunded_amount,
+            released_from_custody: released,
+        })
+        .publish(&env);
+
+        Ok(released)
     }
 
-    /// Redeems loyalty points as a discount on a specific payment.
+    /// Covers part of a settled refund from the merchant's rolling reserve.
+    ///
+    /// The registered refund contract calls this after `settle_refund` when what was
+    /// released plus the merchant's refund reserve in the refund contract cannot cover
+    /// the refund. Reserve entries are drawn soonest release day first.
     ///
     /// # Arguments
-    /// * `customer` - The customer redeeming points (must authorize)
-    /// * `points` - The number of loyalty points to redeem
-    /// * `payment_id` - The payment to apply the discount to
+    /// * `refund_contract` - The calling refund contract (must authorize and be registered)
+    /// * `payment_id` - The refunded payment
+    /// * `amount` - The shortfall in base token units, at most the payment's refunded amount
     ///
     /// # Returns
-    /// `Ok(discount_amount)` on success, where `discount_amount` is the token amount
-    /// to deduct from the payment (capped at 50% of the payment amount), or an error
-    /// if loyalty is not configured, points are insufficient or expired, the payment is
-    /// not found, or the caller is not the payment's customer.
-    pub fn redeem_points(
+    /// `Ok(drawn)` with the amount transferred to the refund contract, which may be less
+    /// than `amount` if the reserve runs out, or an error if the caller is not the
+    /// registered refund contract, the amount is invalid, or the payment has no refund
+    /// or never completed.
+    pub fn draw_reserve_for_refund(
         env: Env,
-        customer: Address,
-        points: u64,
+        refund_contract: Address,
         payment_id: u64,
+        amount: i128,
     ) -> Result<i128, Error> {
-        Self::require_not_paused(&env, "redeem_points")?;
-        customer.require_auth();
-
-        let config: LoyaltyConfig = env
+        refund_contract.require_auth();
+        let registered: Option<Address> = env
             .storage()
             .instance()
-            .get(&DataKey::Config(ConfigKey::LoyaltyConfig))
-            .ok_or(Error::Feature(FeatureError::LoyaltyNotConfigured))?;
-        if !config.active {
-            return Err(Error::Feature(FeatureError::LoyaltyNotConfigured));
+            .get(&DataKey::Config(ConfigKey::RefundContract));
+        if registered != Some(refund_contract.clone()) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        let payment: Payment = record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)))
+            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        if !matches!(
+            payment.status,
+            PaymentStatus::PartialRefunded | PaymentStatus::Refunded
+        ) {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        Self::require_completed(&env, payment_id)?;
+        if amount <= 0 || amount > payment.refunded_amount {
+            return Err(Error::Basic(BasicError::InvalidAmount));
         }
 
-        let mut balance: CustomerLoyaltyBalance = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
-                customer.clone(),
-            )))
-            .ok_or(Error::Feature(FeatureError::InsufficientPoints))?;
+        Ok(Self::draw_reserve(
+            &env,
+            &payment.merchant,
+            &payment.token,
+            amount,
+            &refund_contract,
+        ))
+    }
+
+    /// Cancels a pending payment.
+    ///
+    /// # Arguments
+    /// * `caller` - The customer or merchant who authorized the payment (must authorize)
+    /// * `payment_id` - The ID of the payment to cancel
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
+    /// status, or the caller is not the customer or merchant.
+    pub fn cancel_payment(env: Env, caller: Address, payment_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, "cancel_payment")?;
+        caller.require_auth();
+        PaymentContract::do_cancel_payment(&env, caller, payment_id)
+    }
 
-        let now = env.ledger().timestamp();
-        if balance.expires_at != 0 && now > balance.expires_at {
-            return Err(Error::Feature(FeatureError::PointsExpired));
+    fn do_cancel_payment(env: &Env, caller: Address, payment_id: u64) -> Result<(), Error> {
+        // Check if payment exists
+        if !record_has(env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
+            return Err(Error::Payment(PaymentError::NotFound));
         }
 
-        if balance.points < points {
-            return Err(Error::Feature(FeatureError::InsufficientPoints));
-        }
+        let mut payment = PaymentContract::get_payment(env, payment_id);
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
-        let payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.customer != customer {
+        // Check authorization: caller must be customer, merchant, or admin
+        let is_authorized = payment.customer == caller || payment.merchant == caller;
+        if !is_authorized {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        let mut discount = (points as i128) * (config.redemption_rate as i128);
-        let max_discount = payment.amount / 2;
-        if discount > max_discount {
-            discount = max_discount;
+        // Check payment status is Pending
+        match payment.status {
+            PaymentStatus::Pending => {
+                payment.status = PaymentStatus::Cancelled;
+            }
+            PaymentStatus::Completed
+            | PaymentStatus::Refunded
+            | PaymentStatus::PartialRefunded
+            | PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
         }
 
-        balance.points -= points;
-        balance.last_updated = now;
-        env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(customer.clone())),
-            &balance,
+        record_set(
+            env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
         );
 
-        let existing_discount: i128 = env
+        // Update analytics
+        let mut analytics: PaymentAnalytics = env
             .storage()
             .instance()
-            .get(&DataKey::Payment(PaymentKey::Discount(payment_id)))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Payment(PaymentKey::Discount(payment_id)),
-            &(existing_discount + discount),
-        );
-
-        Ok(discount)
-    }
-
-    fn get_loyalty_config(env: &Env) -> Option<LoyaltyConfig> {
+            .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
+            .unwrap_or(PaymentAnalytics {
+                total_payments_created: 0,
+                total_payments_completed: 0,
+                total_payments_cancelled: 0,
+                total_payments_refunded: 0,
+                total_volume: 0,
+                total_refunded_volume: 0,
+                unique_customers: 0,
+                unique_merchants: 0,
+            });
+        analytics.total_payments_cancelled += 1;
         env.storage()
             .instance()
-            .get(&DataKey::Config(ConfigKey::LoyaltyConfig))
-    }
-
-    fn maybe_accrue_loyalty_points(env: &Env, customer: Address, amount: i128) {
-        if amount <= 0 {
-            return;
-        }
-        let config = match PaymentContract::get_loyalty_config(env) {
-            Some(c) if c.active && c.points_per_unit > 0 => c,
-            _ => return,
-        };
-
-        let points = (amount / i128::from(config.points_per_unit)) as u64;
-        if points == 0 {
-            return;
-        }
-
-        let now = env.ledger().timestamp();
-        let mut balance: CustomerLoyaltyBalance = env
+            .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
+        let mut m_analytics: MerchantAnalytics = env
             .storage()
             .instance()
-            .get(&DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(
-                customer.clone(),
+            .get(&DataKey::Merchant(MerchantDataKey::Analytics(
+                payment.merchant.clone(),
             )))
-            .unwrap_or(CustomerLoyaltyBalance {
-                customer: customer.clone(),
-                points: 0,
-                last_updated: 0,
-                expires_at: 0,
+            .unwrap_or(MerchantAnalytics {
+                total_payments: 0,
+                total_volume: 0,
+                total_completed: 0,
+                total_cancelled: 0,
+                total_refunded: 0,
+                total_refunded_volume: 0,
             });
-
-        if balance.expires_at != 0 && now > balance.expires_at {
-            balance.points = points;
-        } else {
-            balance.points = balance.points.saturating_add(points);
-        }
-        balance.last_updated = now;
-        balance.expires_at = now + config.expiry_seconds;
-
+        m_analytics.total_cancelled += 1;
         env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::CustomerLoyaltyBalance(customer)),
-            &balance,
+            &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
+            &m_analytics,
+        );
+        let timestamp = env.ledger().timestamp();
+        (PaymentCancelled {
+            payment_id,
+            cancelled_by: caller,
+            timestamp,
+        })
+        .publish(env);
+
+        PaymentContract::update_merchant_bucket(
+            env,
+            payment.merchant.clone(),
+            timestamp,
+            0,
+            0,
+            0,
+            1,
         );
+        PaymentContract::update_platform_daily_bucket(env, timestamp, 0, 0, 1);
+
+        Ok(())
     }
 
-    /// Pays an installment toward a pending payment.
+    /// Returns a paginated list of payments made by a customer.
     ///
     /// # Arguments
-    /// * `customer` - The customer making the installment payment (must authorize)
-    /// * `payment_id` - The ID of the payment to pay toward
-    /// * `amount` - The installment amount in base token units
+    /// * `customer` - The customer address to query
+    /// * `limit` - Maximum number of payments to return
+    /// * `offset` - The number of payments to skip (for pagination)
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the amount is non-positive, the payment is expired
-    /// or not in Pending status, the installment exceeds the outstanding balance, or the
-    /// contract is paused. Automatically finalizes the payment if fully paid.
-    pub fn pay_installment(
+    /// A `Vec<Payment>` containing the requested page of payments.
+    pub fn get_payments_by_customer(
         env: Env,
         customer: Address,
-        payment_id: u64,
-        amount: i128,
-    ) -> Result<(), Error> {
-        Self::require_not_paused(&env, "pay_installment")?;
-        customer.require_auth();
-
-        if amount <= 0 {
-            return Err(Error::Basic(BasicError::InvalidAmount));
-        }
-
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        limit: u64,
+        offset: u64,
+    ) -> Vec<Payment> {
+        let total_count: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer.clone())),
+        )
+        .unwrap_or(0);
 
-        // Check if payment is expired
-        if PaymentContract::is_payment_expired(&env, payment_id) {
-            return Err(Error::Payment(PaymentError::Expired));
-        }
+        let mut payments = Vec::new(&env);
+        let start = offset;
+        let end = (offset + limit).min(total_count);
 
-        // Only allow installments on Pending payments
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
+        for i in start..end {
+            if let Some(payment_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Customer(CustomerDataKey::Payments(customer.clone(), i)),
+            ) {
+                if let Some(payment) = record_get::<DataKey, Payment>(
+                    &env,
+                    &DataKey::Payment(PaymentKey::Data(payment_id)),
+                ) {
+                    payments.push_back(payment);
+                }
+            }
         }
 
-        // Get current outstanding balance
-        let outstanding_balance = PaymentContract::get_outstanding_balance(env.clone(), payment_id);
-
-        if outstanding_balance <= 0 {
-            return Err(Error::Payment(PaymentError::AlreadyFullyPaid));
-        }
+        payments
+    }
 
-        if amount > outstanding_balance {
-            return Err(Error::Payment(PaymentError::InstallmentExceedsRemaining));
-        }
+    /// Returns the total number of payments made by a customer.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer address to query
+    ///
+    /// # Returns
+    /// The total payment count for the customer, or 0 if none.
+    pub fn get_payment_count_by_customer(env: Env, customer: Address) -> u64 {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer)),
+        )
+        .unwrap_or(0)
+    }
 
-        // Get current installment counter
-        let installment_counter: u32 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::PartialPaymentCounter(
-                payment_id,
-            )))
-            .unwrap_or(0);
-        let new_installment_number = installment_counter + 1;
+    /// Returns a paginated list of payments received by a merchant.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant address to query
+    /// * `limit` - Maximum number of payments to return
+    /// * `offset` - The number of payments to skip (for pagination)
+    ///
+    /// # Returns
+    /// A `Vec<Payment>` containing the requested page of payments.
+    pub fn get_payments_by_merchant(
+        env: Env,
+        merchant: Address,
+        limit: u64,
+        offset: u64,
+    ) -> Vec<Payment> {
+        let total_count: u64 = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
+        )
+        .unwrap_or(0);
 
-        // Transfer tokens from customer to contract
-        let token_client = token::Client::new(&env, &payment.token);
-        token_client.transfer(&customer, &env.current_contract_address(), &amount);
+        let mut payments = Vec::new(&env);
+        let start = offset;
+        let end = (offset + limit).min(total_count);
 
-        // Create partial payment record
-        let remaining = outstanding_balance - amount;
-        let partial_payment = PartialPaymentRecord {
-            payment_id,
-            installment_number: new_installment_number,
-            amount_paid: amount,
-            total_amount: payment.amount,
-            remaining,
-            paid_at: env.ledger().timestamp(),
-            payer: customer.clone(),
-        };
+        for i in start..end {
+            if let Some(payment_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Merchant(MerchantDataKey::Payments(merchant.clone(), i)),
+            ) {
+                if let Some(payment) = record_get::<DataKey, Payment>(
+                    &env,
+                    &DataKey::Payment(PaymentKey::Data(payment_id)),
+                ) {
+                    payments.push_back(payment);
+                }
+            }
+        }
 
-        // Store partial payment record
-        env.storage().instance().set(
-            &DataKey::State(StateDataKey::PartialPaymentRecord(
-                payment_id,
-                new_installment_number,
-            )),
-            &partial_payment,
-        );
+        payments
+    }
 
-        // Update installment counter
-        env.storage().instance().set(
-            &DataKey::Payment(PaymentKey::PartialPaymentCounter(payment_id)),
-            &new_installment_number,
-        );
+    /// Returns the total number of payments received by a merchant.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant address to query
+    ///
+    /// # Returns
+    /// The total payment count for the merchant, or 0 if none.
+    pub fn get_payment_count_by_merchant(env: Env, merchant: Address) -> u64 {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant)),
+        )
+        .unwrap_or(0)
+    }
 
-        // Update outstanding balance
-        env.storage().instance().set(
-            &DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
-            &remaining,
-        );
+    /// Returns the payment IDs for the given merchant on the requested page (100 per page).
+    pub fn get_merchant_payments(env: Env, merchant: Address, page: u64) -> Vec<u64> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(merchant, page)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
 
-        // Emit installment paid event
-        (InstallmentPaid {
-            payment_id,
-            installment_number: new_installment_number,
-            amount,
-            remaining,
-            payer: customer,
-            paid_at: partial_payment.paid_at,
-        })
-        .publish(&env);
+    /// Adds a token to the list of allowed tokens for payments.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `token` - The SPL token address to add to the allowed list
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if the admin is unauthorized or multisig is not initialized.
+    pub fn add_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
 
-        // Check if payment is now fully paid
-        if remaining == 0 {
-            PaymentContract::finalize_installment_payment(env.clone(), payment_id)?;
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+
+        let mut tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::AllowedTokens))
+            .unwrap_or_else(|| Vec::new(&env));
+        if !tokens.contains(&token) {
+            tokens.push_back(token);
+            env.storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::AllowedTokens), &tokens);
         }
 
         Ok(())
     }
 
-    /// Returns the full installment payment history for a given payment.
+    /// Removes a token from the list of allowed tokens for payments.
     ///
     /// # Arguments
-    /// * `payment_id` - The ID of the payment to retrieve installment history for
+    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
+    /// * `token` - The SPL token address to remove from the allowed list
     ///
     /// # Returns
-    /// A `Vec<PartialPaymentRecord>` of all installments made toward the payment.
-    pub fn get_installment_history(env: Env, payment_id: u64) -> Vec<PartialPaymentRecord> {
-        let installment_counter: u32 = env
+    /// `Ok(())` on success, or an error if the admin is unauthorized or multisig is not initialized.
+    pub fn remove_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
+        admin.require_auth();
+
+        let config: MultiSigConfig = env
             .storage()
             .instance()
-            .get(&DataKey::Payment(PaymentKey::PartialPaymentCounter(
-                payment_id,
-            )))
-            .unwrap_or(0);
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
 
-        let mut history = Vec::new(&env);
-        for i in 1..=installment_counter {
-            if let Some(record) =
-                env.storage()
-                    .instance()
-                    .get(&DataKey::State(StateDataKey::PartialPaymentRecord(
-                        payment_id, i,
-                    )))
-            {
-                history.push_back(record);
+        let tokens: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::AllowedTokens))
+            .unwrap_or_else(|| Vec::new(&env));
+        let mut updated = Vec::new(&env);
+        for entry in tokens.iter() {
+            if entry != token {
+                updated.push_back(entry);
             }
         }
-        history
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::AllowedTokens), &updated);
+
+        Ok(())
     }
 
-    /// Returns the outstanding balance remaining on a payment.
+    /// Returns the list of allowed payment tokens.
     ///
-    /// # Arguments
-    /// * `payment_id` - The ID of the payment to check
+    /// If no tokens have been configured, returns an empty list, which means all tokens are permitted.
     ///
     /// # Returns
-    /// The remaining amount in base token units. Returns the full payment amount if no
-    /// partial payments have been made.
-    pub fn get_outstanding_balance(env: Env, payment_id: u64) -> i128 {
-        // First check if we have an outstanding balance stored
-        if let Some(balance) =
-            env.storage()
-                .instance()
-                .get(&DataKey::Payment(PaymentKey::OutstandingBalance(
-                    payment_id,
-                )))
-        {
-            return balance;
-        }
+    /// A vector of token addresses that have been explicitly allowed by an admin.
+    /// Returns the list of all allowed tokens for payments.
+    ///
+    /// # Returns
+    /// A `Vec<Address>` of allowed token addresses. Returns an empty vector if none are configured.
+    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::AllowedTokens))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
 
-        // If not, calculate from payment amount and partial payments
-        let payment = PaymentContract::get_payment(&env, payment_id);
-        let installment_counter: u32 = env
+    // Mirrors the refund contract's token-registry bypass (Issue #191): an
+    // empty allowlist means the allowlist hasn't been configured yet, so all
+    // tokens are permitted until an admin opts in by calling
+    // `add_allowed_token`.
+    fn is_token_allowed(env: &Env, token: &Address) -> bool {
+        let tokens: Vec<Address> = env
             .storage()
             .instance()
-            .get(&DataKey::Payment(PaymentKey::PartialPaymentCounter(
-                payment_id,
+            .get(&DataKey::Config(ConfigKey::AllowedTokens))
+            .unwrap_or_else(|| Vec::new(env));
+        tokens.is_empty() || tokens.contains(token)
+    }
+
+    const ACTIVE_SUBSCRIPTION_PAGE_SIZE: u64 = 100;
+
+    fn merchant_active_subscription_count(env: &Env, merchant: &Address) -> u64 {
+        record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptionCount(
+                merchant.clone(),
+            )),
+        )
+        .unwrap_or(0)
+    }
+
+    fn set_active_subscription_at(
+        env: &Env,
+        merchant: &Address,
+        flat_index: u64,
+        subscription_id: u64,
+    ) {
+        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let mut page: Vec<u64> = record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                merchant.clone(),
+                page_num,
             )))
-            .unwrap_or(0);
+            .unwrap_or_else(|| Vec::new(env));
 
-        let mut total_paid = 0i128;
-        for i in 1..=installment_counter {
-            if let Some(record) = env
-                .storage()
-                .instance()
-                .get::<DataKey, PartialPaymentRecord>(&DataKey::State(
-                    StateDataKey::PartialPaymentRecord(payment_id, i),
-                ))
-            {
-                total_paid += record.amount_paid;
+        let page_offset_u32 = page_offset as u32;
+        while page.len() <= page_offset_u32 {
+            page.push_back(0);
+        }
+        page.set(page_offset_u32, subscription_id);
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                merchant.clone(),
+                page_num,
+            )),
+            &page,
+        );
+    }
+
+    fn active_subscription_at(env: &Env, merchant: &Address, flat_index: u64) -> Option<u64> {
+        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let page: Vec<u64> = record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                merchant.clone(),
+                page_num,
+            )))?;
+        let page_offset_u32 = page_offset as u32;
+        if page_offset_u32 < page.len() {
+            Some(page.get(page_offset_u32).unwrap())
+        } else {
+            None
+        }
+    }
+
+    fn remove_active_subscription_page_entry(env: &Env, merchant: &Address, flat_index: u64) {
+        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
+        let page_offset_u32 = page_offset as u32;
+        let page: Vec<u64> = record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                merchant.clone(),
+                page_num,
+            )),
+        )
+        .unwrap_or_else(|| Vec::new(env));
+
+        if page_offset_u32 < page.len() {
+            let mut rebuilt = Vec::new(env);
+            for i in 0..page.len() {
+                if i != page_offset_u32 {
+                    rebuilt.push_back(page.get(i).unwrap());
+                }
+            }
+            if rebuilt.is_empty() {
+                record_remove(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                        merchant.clone(),
+                        page_num,
+                    )),
+                );
+            } else {
+                record_set(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
+                        merchant.clone(),
+                        page_num,
+                    )),
+                    &rebuilt,
+                );
             }
         }
+    }
 
-        let outstanding = payment.amount - total_paid;
-
-        // Cache the calculated balance
-        env.storage().instance().set(
-            &DataKey::Payment(PaymentKey::OutstandingBalance(payment_id)),
-            &outstanding,
+    fn add_to_merchant_active_subscriptions(env: &Env, merchant: &Address, subscription_id: u64) {
+        let count = Self::merchant_active_subscription_count(env, merchant);
+        Self::set_active_subscription_at(env, merchant, count, subscription_id);
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(subscription_id)),
+            &count,
+        );
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptionCount(
+                merchant.clone(),
+            )),
+            &(count + 1),
         );
-
-        outstanding
     }
 
-    /// Finalizes a payment that has been fully paid through installments.
-    ///
-    /// # Arguments
-    /// * `payment_id` - The ID of the payment to finalize
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not in Pending status or
-    /// the outstanding balance is not zero.
-    pub fn finalize_installment_payment(env: Env, payment_id: u64) -> Result<(), Error> {
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
-
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
+    fn remove_from_merchant_active_subscriptions(
+        env: &Env,
+        merchant: &Address,
+        subscription_id: u64,
+    ) {
+        let count = Self::merchant_active_subscription_count(env, merchant);
+        if count == 0 {
+            return;
         }
 
-        let outstanding_balance = PaymentContract::get_outstanding_balance(env.clone(), payment_id);
-        if outstanding_balance != 0 {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
-        }
+        let index: u64 = match record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(subscription_id)),
+        ) {
+            Some(i) => i,
+            None => return,
+        };
+        let last_index = count - 1;
 
-        // Get installment counter for event
-        let installment_counter: u32 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::PartialPaymentCounter(
-                payment_id,
-            )))
-            .unwrap_or(0);
+        if index != last_index {
+            if let Some(last_id) = Self::active_subscription_at(env, merchant, last_index) {
+                Self::set_active_subscription_at(env, merchant, index, last_id);
+                record_set(
+                    env,
+                    &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(last_id)),
+                    &index,
+                );
+            }
+        }
 
-        // Update payment status to Completed
-        payment.status = PaymentStatus::Completed;
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+        Self::remove_active_subscription_page_entry(env, merchant, last_index);
+        record_remove(
+            env,
+            &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(subscription_id)),
+        );
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptionCount(
+                merchant.clone(),
+            )),
+            &last_index,
+        );
+    }
 
-        // Transfer or accumulate all collected funds to merchant
-        Self::settle_or_accumulate(
+    /// Returns active subscription IDs for a merchant (100 per page).
+    pub fn get_merchant_subscriptions(env: Env, merchant: Address, page: u64) -> Vec<u64> {
+        record_get(
             &env,
-            payment.merchant.clone(),
-            payment.token.clone(),
-            payment.amount,
-        )?;
-
-        // Emit payment fully paid event
-        (PaymentFullyPaid {
-            payment_id,
-            total_installments: installment_counter,
-            completed_at: env.ledger().timestamp(),
-        })
-        .publish(&env);
+            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(merchant, page)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
 
-        // Also emit standard payment completed event for compatibility
-        (PaymentCompleted {
-            payment_id,
-            merchant: payment.merchant,
-            amount: payment.amount,
-        })
-        .publish(&env);
+    /// Returns the active subscription IDs on `plan_id` from one page of
+    /// `get_merchant_subscriptions`.
+    pub fn get_merchant_plan_subscriptions(
+        env: Env,
+        merchant: Address,
+        plan_id: u64,
+        page: u64,
+    ) -> Vec<u64> {
+        let mut filtered = Vec::new(&env);
+        for id in Self::get_merchant_subscriptions(env.clone(), merchant, page).iter() {
+            let linked: Option<u64> =
+                record_get(&env, &DataKey::Subscription(SubscriptionKey::PlanLink(id)));
+            if linked == Some(plan_id) {
+                filtered.push_back(id);
+            }
+        }
+        filtered
+    }
 
-        Ok(())
+    fn is_valid_currency(currency: &Currency) -> bool {
+        matches!(
+            currency,
+            Currency::XLM | Currency::USDC | Currency::USDT | Currency::BTC | Currency::ETH
+        )
     }
 
-    /// Refunds a pending payment in full, returning funds to the customer.
+    /// Sets the manual conversion rate for a given currency.
+    ///
+    /// This rate is used when oracle-based rate fetching is disabled or unavailable.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing the refund (must be in the multisig admin list)
-    /// * `payment_id` - The ID of the payment to refund
+    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
+    /// * `currency` - The fiat currency to set a conversion rate for.
+    /// * `rate` - The conversion rate in base units (e.g., 1_0000000 = 1:1).
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
-    /// found, is expired, is not in Pending status, or the contract is paused.
-    pub fn refund_payment(env: Env, admin: Address, payment_id: u64) -> Result<(), Error> {
-        Self::require_not_paused(&env, "refund_payment")?;
+    /// `Ok(())` on success, or an error if unauthorized or the currency is invalid.
+    pub fn set_conversion_rate(
+        env: Env,
+        admin: Address,
+        currency: Currency,
+        rate: i128,
+    ) -> Result<(), Error> {
         admin.require_auth();
 
-        // Verify caller is in the multisig admin list
         let config: MultiSigConfig = env
             .storage()
             .instance()
@@ -4575,1007 +8704,975 @@ impl PaymentContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        PaymentContract::do_refund_payment(&env, payment_id)
-    }
-
-    fn do_refund_payment(env: &Env, payment_id: u64) -> Result<(), Error> {
-        // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
-
-        let mut payment = PaymentContract::get_payment(env, payment_id);
-
-        // Before updating status, check if payment is expired
-        if PaymentContract::is_payment_expired(env, payment_id) {
-            return Err(Error::Payment(PaymentError::Expired));
-        }
-
-        match payment.status {
-            PaymentStatus::Pending => {
-                payment.status = PaymentStatus::Refunded;
-            }
-            PaymentStatus::Completed | PaymentStatus::PartialRefunded => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-            PaymentStatus::Refunded => {
-                return Err(Error::Payment(PaymentError::AlreadyProcessed));
-            }
-            PaymentStatus::Cancelled => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
+        if !PaymentContract::is_valid_currency(&currency) {
+            return Err(Error::Basic(BasicError::InvalidCurrency));
         }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-
-        // Update analytics
-        let mut analytics: PaymentAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
-            .unwrap_or(PaymentAnalytics {
-                total_payments_created: 0,
-                total_payments_completed: 0,
-                total_payments_cancelled: 0,
-                total_payments_refunded: 0,
-                total_volume: 0,
-                total_refunded_volume: 0,
-                unique_customers: 0,
-                unique_merchants: 0,
-            });
-        analytics.total_payments_refunded += 1;
-        analytics.total_refunded_volume += payment.amount;
-        env.storage()
-            .instance()
-            .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
-        let mut m_analytics: MerchantAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::Analytics(
-                payment.merchant.clone(),
-            )))
-            .unwrap_or(MerchantAnalytics {
-                total_payments: 0,
-                total_volume: 0,
-                total_completed: 0,
-                total_cancelled: 0,
-                total_refunded: 0,
-                total_refunded_volume: 0,
-            });
-        m_analytics.total_refunded += 1;
-        m_analytics.total_refunded_volume += payment.amount;
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
-            &m_analytics,
-        );
-        let mut c_analytics: CustomerAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::Analytics(
-                payment.customer.clone(),
-            )))
-            .unwrap_or(PaymentContract::default_customer_analytics());
-        c_analytics.total_refunds += payment.amount;
         env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::Analytics(payment.customer.clone())),
-            &c_analytics,
-        );
-
-        (PaymentRefunded {
-            payment_id,
-            customer: payment.customer,
-            amount: payment.amount,
-        })
-        .publish(env);
-
-        let now = env.ledger().timestamp();
-        PaymentContract::update_merchant_bucket(
-            env,
-            payment.merchant.clone(),
-            now,
-            0,
-            0,
-            payment.amount,
-            0,
+            &DataKey::Feature(FeatureKey::ConversionRate(currency)),
+            &rate,
         );
-        PaymentContract::update_platform_daily_bucket(env, now, 0, payment.amount, 0);
 
         Ok(())
     }
 
-    /// Refunds a partial amount of a pending or partially-refunded payment.
+    /// Returns the stored conversion rate for a given currency.
+    ///
+    /// Defaults to `1_0000000` (1:1) if no rate has been set.
+    ///
+    /// # Arguments
+    /// * `currency` - The fiat currency to query the conversion rate for.
+    ///
+    /// # Returns
+    /// The conversion rate in base units.
+    pub fn get_conversion_rate(env: Env, currency: Currency) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::ConversionRate(currency)))
+            .unwrap_or(1_0000000)
+    }
+
+    /// Configures the oracle-based price feed for a given currency.
+    ///
+    /// When enabled, the contract will fetch live conversion rates from the oracle
+    /// contract instead of using the manual conversion rate.
     ///
     /// # Arguments
-    /// * `admin` - The admin authorizing the refund (must be in the multisig admin list)
-    /// * `payment_id` - The ID of the payment to partially refund
-    /// * `refund_amount` - The amount to refund in base token units
+    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
+    /// * `config` - The oracle rate configuration including oracle address, price feed ID,
+    ///   staleness threshold, and currency.
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized, the payment is not
-    /// found, is expired, is not in a refundable status, or the refund exceeds the payment amount.
-    pub fn partial_refund(
+    /// `Ok(())` on success, or an error if unauthorized.
+    pub fn set_oracle_rate_config(
         env: Env,
         admin: Address,
-        payment_id: u64,
-        refund_amount: i128,
+        config: OracleRateConfig,
     ) -> Result<(), Error> {
         admin.require_auth();
-
-        let config: MultiSigConfig = env
+        let multisig: MultiSigConfig = env
             .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
+        if !multisig.admins.contains(&admin) {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
-
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
-
-        let mut payment = PaymentContract::get_payment(&env, payment_id);
-
-        if PaymentContract::is_payment_expired(&env, payment_id) {
-            return Err(Error::Payment(PaymentError::Expired));
-        }
-
-        match payment.status {
-            PaymentStatus::Pending | PaymentStatus::PartialRefunded => {
-                let new_refunded = payment.refunded_amount + refund_amount;
-                if new_refunded > payment.amount {
-                    return Err(Error::Payment(PaymentError::RefundExceedsPayment));
-                }
-                payment.refunded_amount = new_refunded;
-                payment.status = if new_refunded == payment.amount {
-                    PaymentStatus::Refunded
-                } else {
-                    PaymentStatus::PartialRefunded
-                };
-            }
-            _ => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-        }
-
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
-
-        (PaymentRefunded {
-            payment_id,
-            customer: payment.customer,
-            amount: refund_amount,
-        })
-        .publish(&env);
-
+        env.storage().instance().set(
+            &DataKey::Feature(FeatureKey::OracleRateConfig(config.currency.clone())),
+            &config,
+        );
         Ok(())
     }
 
-    /// Cancels a pending payment.
+    /// Returns the oracle rate configuration for a given currency.
     ///
     /// # Arguments
-    /// * `caller` - The customer or merchant who authorized the payment (must authorize)
-    /// * `payment_id` - The ID of the payment to cancel
+    /// * `currency` - The fiat currency to query the oracle config for.
     ///
     /// # Returns
-    /// `Ok(())` on success, or an error if the payment is not found, is not in Pending
-    /// status, or the caller is not the customer or merchant.
-    pub fn cancel_payment(env: Env, caller: Address, payment_id: u64) -> Result<(), Error> {
-        Self::require_not_paused(&env, "cancel_payment")?;
-        caller.require_auth();
-        PaymentContract::do_cancel_payment(&env, caller, payment_id)
+    /// `Some(OracleRateConfig)` if configured, `None` otherwise.
+    pub fn get_oracle_rate_config(env: Env, currency: Currency) -> Option<OracleRateConfig> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::OracleRateConfig(currency)))
     }
 
-    fn do_cancel_payment(env: &Env, caller: Address, payment_id: u64) -> Result<(), Error> {
-        // Check if payment exists
-        if !env
+    /// Fetches a fresh conversion rate from the oracle and stores it.
+    ///
+    /// If the oracle is disabled for this currency, returns the stored manual rate.
+    /// If the oracle feed data is stale (older than `max_staleness_seconds`), returns an error.
+    ///
+    /// # Arguments
+    /// * `currency` - The fiat currency to refresh the conversion rate for.
+    ///
+    /// # Returns
+    /// The updated conversion rate on success.
+    ///
+    /// # Errors
+    /// - `OracleNotConfigured` if no oracle config exists for the currency.
+    /// - `OracleCallFailed` if the oracle contract call fails.
+    /// - `OracleFeedStale` if the fetched data exceeds the staleness threshold.
+    pub fn refresh_conversion_rate(env: Env, currency: Currency) -> Result<i128, Error> {
+        let cfg: OracleRateConfig = env
             .storage()
             .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::NotFound));
-        }
-
-        let mut payment = PaymentContract::get_payment(env, payment_id);
+            .get(&DataKey::Feature(FeatureKey::OracleRateConfig(
+                currency.clone(),
+            )))
+            .ok_or(Error::Basic(BasicError::OracleNotConfigured))?;
 
-        // Check authorization: caller must be customer, merchant, or admin
-        let is_authorized = payment.customer == caller || payment.merchant == caller;
-        if !is_authorized {
-            return Err(Error::Basic(BasicError::Unauthorized));
+        if !cfg.enabled {
+            return Ok(PaymentContract::get_conversion_rate(env, currency));
         }
 
-        // Check payment status is Pending
-        match payment.status {
-            PaymentStatus::Pending => {
-                payment.status = PaymentStatus::Cancelled;
-            }
-            PaymentStatus::Completed
-            | PaymentStatus::Refunded
-            | PaymentStatus::PartialRefunded
-            | PaymentStatus::Cancelled => {
-                return Err(Error::Payment(PaymentError::InvalidStatus));
-            }
-        }
+        let args = (cfg.price_feed_id.clone(),).into_val(&env);
+        let fetched = env
+            .try_invoke_contract::<(i128, u64), Error>(
+                &cfg.oracle_address,
+                &Symbol::new(&env, "get_price"),
+                args,
+            )
+            .map_err(|_| Error::Basic(BasicError::OracleCallFailed))?
+            .map_err(|_| Error::Basic(BasicError::OracleCallFailed))?;
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+        let now = env.ledger().timestamp();
+        if now.saturating_sub(fetched.1) > cfg.max_staleness_seconds {
+            return Err(Error::Basic(BasicError::OracleFeedStale));
+        }
 
-        // Update analytics
-        let mut analytics: PaymentAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentAnalytics))
-            .unwrap_or(PaymentAnalytics {
-                total_payments_created: 0,
-                total_payments_completed: 0,
-                total_payments_cancelled: 0,
-                total_payments_refunded: 0,
-                total_volume: 0,
-                total_refunded_volume: 0,
-                unique_customers: 0,
-                unique_merchants: 0,
-            });
-        analytics.total_payments_cancelled += 1;
-        env.storage()
-            .instance()
-            .set(&DataKey::Feature(FeatureKey::PaymentAnalytics), &analytics);
-        let mut m_analytics: MerchantAnalytics = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::Analytics(
-                payment.merchant.clone(),
-            )))
-            .unwrap_or(MerchantAnalytics {
-                total_payments: 0,
-                total_volume: 0,
-                total_completed: 0,
-                total_cancelled: 0,
-                total_refunded: 0,
-                total_refunded_volume: 0,
-            });
-        m_analytics.total_cancelled += 1;
         env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::Analytics(payment.merchant.clone())),
-            &m_analytics,
+            &DataKey::Feature(FeatureKey::ConversionRate(currency)),
+            &fetched.0,
         );
-        let timestamp = env.ledger().timestamp();
-        (PaymentCancelled {
-            payment_id,
-            cancelled_by: caller,
-            timestamp,
-        })
-        .publish(env);
+        Ok(fetched.0)
+    }
 
-        PaymentContract::update_merchant_bucket(
-            env,
-            payment.merchant.clone(),
-            timestamp,
-            0,
-            0,
-            0,
-            1,
-        );
-        PaymentContract::update_platform_daily_bucket(env, timestamp, 0, 0, 1);
+    // ── RECURRING / SUBSCRIPTION METHODS ────────────────────────────────────
 
-        Ok(())
+    /// Create a new subscription. The customer authorises the creation.
+    /// `interval`             – seconds between each automatic payment
+    /// `duration`             – total lifetime in seconds (0 = indefinite)
+    /// `max_retries`          – how many times to retry a failed cycle (0 uses DEFAULT)
+    /// `trial_period_seconds` – free trial duration in seconds (0 = no trial)
+    pub fn create_subscription(
+        env: Env,
+        customer: Address,
+        merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        interval: u64,
+        duration: u64,
+        max_retries: u64,
+        metadata: String,
+        trial_period_seconds: u64,
+    ) -> Result<u64, Error> {
+        customer.require_auth();
+        Self::do_create_subscription(
+            &env,
+            customer,
+            merchant,
+            amount,
+            token,
+            currency,
+            interval,
+            duration,
+            max_retries,
+            metadata,
+            trial_period_seconds,
+        )
     }
 
-    /// Returns a paginated list of payments made by a customer.
-    ///
-    /// # Arguments
-    /// * `customer` - The customer address to query
-    /// * `limit` - Maximum number of payments to return
-    /// * `offset` - The number of payments to skip (for pagination)
-    ///
-    /// # Returns
-    /// A `Vec<Payment>` containing the requested page of payments.
-    pub fn get_payments_by_customer(
-        env: Env,
+    #[allow(clippy::too_many_arguments)]
+    fn do_create_subscription(
+        env: &Env,
         customer: Address,
-        limit: u64,
-        offset: u64,
-    ) -> Vec<Payment> {
-        let total_count: u64 = env
+        merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        interval: u64,
+        duration: u64,
+        max_retries: u64,
+        metadata: String,
+        trial_period_seconds: u64,
+    ) -> Result<u64, Error> {
+        if !PaymentContract::is_valid_currency(&currency) {
+            return Err(Error::Basic(BasicError::InvalidCurrency));
+        }
+        if metadata.len() > MAX_METADATA_SIZE {
+            return Err(Error::Basic(BasicError::MetadataTooLarge));
+        }
+        if interval == 0 {
+            // return Err(Error::InvalidInterval);
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+
+        let counter: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::Customer(CustomerDataKey::PaymentCount(
-                customer.clone(),
-            )))
+            .get(&DataKey::Subscription(SubscriptionKey::Counter))
             .unwrap_or(0);
+        let sub_id = counter + 1;
 
-        let mut payments = Vec::new(&env);
-        let start = offset;
-        let end = (offset + limit).min(total_count);
+        let now = env.ledger().timestamp();
+        let ends_at = if duration > 0 { now + duration } else { 0 };
+        let retries = if max_retries == 0 {
+            DEFAULT_MAX_RETRIES
+        } else {
+            max_retries
+        };
 
-        for i in start..end {
-            if let Some(payment_id) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, u64>(&DataKey::Customer(CustomerDataKey::Payments(
-                        customer.clone(),
-                        i,
-                    )))
-            {
-                if let Some(payment) = env
-                    .storage()
-                    .instance()
-                    .get::<DataKey, Payment>(&DataKey::Payment(PaymentKey::Data(payment_id)))
-                {
-                    payments.push_back(payment);
-                }
-            }
-        }
+        let trial_ends_at = if trial_period_seconds > 0 {
+            now + trial_period_seconds
+        } else {
+            0
+        };
 
-        payments
-    }
+        let sub = Subscription {
+            id: sub_id,
+            customer: customer.clone(),
+            merchant: merchant.clone(),
+            amount,
+            token,
+            currency,
+            interval,
+            duration,
+            status: SubscriptionStatus::Active,
+            created_at: now,
+            next_payment_at: now + interval,
+            ends_at,
+            payment_count: 0,
+            retry_count: 0,
+            max_retries: retries,
+            metadata,
+            trial_data: SubscriptionTrialData {
+                period_seconds: trial_period_seconds,
+                ends_at: trial_ends_at,
+                converted: false,
+            },
+            pause_data: SubscriptionPauseData {
+                last_paused_at: 0,
+                total_pause_duration: 0,
+                proration_enabled: false,
+            },
+        };
 
-    /// Returns the total number of payments made by a customer.
-    ///
-    /// # Arguments
-    /// * `customer` - The customer address to query
-    ///
-    /// # Returns
-    /// The total payment count for the customer, or 0 if none.
-    pub fn get_payment_count_by_customer(env: Env, customer: Address) -> u64 {
+        record_set(
+            env,
+            &DataKey::Subscription(SubscriptionKey::Data(sub_id)),
+            &sub,
+        );
         env.storage()
             .instance()
-            .get(&DataKey::Customer(CustomerDataKey::PaymentCount(customer)))
-            .unwrap_or(0)
-    }
+            .set(&DataKey::Subscription(SubscriptionKey::Counter), &sub_id);
 
-    /// Returns a paginated list of payments received by a merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
-    /// * `limit` - Maximum number of payments to return
-    /// * `offset` - The number of payments to skip (for pagination)
-    ///
-    /// # Returns
-    /// A `Vec<Payment>` containing the requested page of payments.
-    pub fn get_payments_by_merchant(
-        env: Env,
-        merchant: Address,
-        limit: u64,
-        offset: u64,
-    ) -> Vec<Payment> {
-        let total_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PaymentCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
+        // Index by customer
+        let c_count: u64 = record_get(
+            env,
+            &DataKey::Customer(CustomerDataKey::SubscriptionCount(customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
+            &DataKey::Customer(CustomerDataKey::Subscriptions(customer.clone(), c_count)),
+            &sub_id,
+        );
+        record_set(
+            env,
+            &DataKey::Customer(CustomerDataKey::SubscriptionCount(customer)),
+            &(c_count + 1),
+        );
 
-        let mut payments = Vec::new(&env);
-        let start = offset;
-        let end = (offset + limit).min(total_count);
+        // Index by merchant
+        let m_count: u64 = record_get(
+            env,
+            &DataKey::Merchant(MerchantDataKey::SubscriptionCount(merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::Subscriptions(merchant.clone(), m_count)),
+            &sub_id,
+        );
+        record_set(
+            env,
+            &DataKey::Merchant(MerchantDataKey::SubscriptionCount(merchant.clone())),
+            &(m_count + 1),
+        );
 
-        for i in start..end {
-            if let Some(payment_id) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, u64>(&DataKey::Merchant(MerchantDataKey::Payments(
-                        merchant.clone(),
-                        i,
-                    )))
-            {
-                if let Some(payment) = env
-                    .storage()
-                    .instance()
-                    .get::<DataKey, Payment>(&DataKey::Payment(PaymentKey::Data(payment_id)))
-                {
-                    payments.push_back(payment);
-                }
-            }
-        }
+        Self::add_to_merchant_active_subscriptions(env, &merchant, sub_id);
 
-        payments
-    }
+        (SubscriptionCreated {
+            subscription_id: sub_id,
+            customer: sub.customer.clone(),
+            merchant: sub.merchant.clone(),
+            amount: sub.amount,
+            interval: sub.interval,
+        })
+        .publish(env);
 
-    /// Returns the total number of payments received by a merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to query
-    ///
-    /// # Returns
-    /// The total payment count for the merchant, or 0 if none.
-    pub fn get_payment_count_by_merchant(env: Env, merchant: Address) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PaymentCount(merchant)))
-            .unwrap_or(0)
-    }
+        // Emit TrialStarted if trial is active
+        if sub.trial_data.ends_at > 0 {
+            (TrialStarted {
+                subscription_id: sub_id,
+                trial_ends_at: sub.trial_data.ends_at,
+            })
+            .publish(env);
+        }
 
-    /// Returns the payment IDs for the given merchant on the requested page (100 per page).
-    pub fn get_merchant_payments(env: Env, merchant: Address, page: u64) -> Vec<u64> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
-                merchant, page,
-            )))
-            .unwrap_or_else(|| Vec::new(&env))
+        Ok(sub_id)
     }
 
-    /// Adds a token to the list of allowed tokens for payments.
-    ///
-    /// # Arguments
-    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
-    /// * `token` - The SPL token address to add to the allowed list
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized or multisig is not initialized.
-    pub fn add_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
-        admin.require_auth();
+    /// Publish a subscription plan. Customers join it with `subscribe_to_plan`.
+    /// `dunning` overrides the contract-wide dunning config for the plan's subscriptions.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_subscription_plan(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        currency: Currency,
+        price: i128,
+        interval: u64,
+        duration: u64,
+        trial_period_seconds: u64,
+        max_retries: u64,
+        dunning: Option<DunningConfig>,
+        metadata: String,
+    ) -> Result<u64, Error> {
+        merchant.require_auth();
 
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
+        if !PaymentContract::is_valid_currency(&currency) {
+            return Err(Error::Basic(BasicError::InvalidCurrency));
+        }
+        if metadata.len() > MAX_METADATA_SIZE {
+            return Err(Error::Basic(BasicError::MetadataTooLarge));
+        }
+        if interval == 0 {
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+        if price <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if trial_period_seconds > MAX_TRIAL_DURATION {
+            return Err(Error::Subscription(
+                SubscriptionError::MaxTrialDurationExceeded,
+            ));
         }
 
-        let mut tokens: Vec<Address> = env
+        let plan_id: u64 = env
             .storage()
             .instance()
-            .get(&DataKey::Config(ConfigKey::AllowedTokens))
-            .unwrap_or_else(|| Vec::new(&env));
-        if !tokens.contains(&token) {
-            tokens.push_back(token);
-            env.storage()
-                .instance()
-                .set(&DataKey::Config(ConfigKey::AllowedTokens), &tokens);
+            .get(&DataKey::Subscription(SubscriptionKey::PlanCounter))
+            .unwrap_or(0u64)
+            + 1;
+        let now = env.ledger().timestamp();
+
+        let mut prices = Vec::new(&env);
+        prices.push_back(PlanPrice {
+            version: 1,
+            amount: price,
+            effective_at: now,
+        });
+        let plan = SubscriptionPlan {
+            id: plan_id,
+            merchant: merchant.clone(),
+            token,
+            currency,
+            prices,
+            interval,
+            duration,
+            trial_period_seconds,
+            max_retries,
+            metadata,
+            archived: false,
+            created_at: now,
+        };
+
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Plan(plan_id)),
+            &plan,
+        );
+        env.storage().instance().set(
+            &DataKey::Subscription(SubscriptionKey::PlanCounter),
+            &plan_id,
+        );
+        if let Some(config) = dunning {
+            record_set(
+                &env,
+                &DataKey::Subscription(SubscriptionKey::PlanDunning(plan_id)),
+                &config,
+            );
         }
 
-        Ok(())
+        let plans_key = DataKey::Subscription(SubscriptionKey::MerchantPlans(merchant.clone()));
+        let mut plans: Vec<u64> = record_get(&env, &plans_key).unwrap_or_else(|| Vec::new(&env));
+        plans.push_back(plan_id);
+        record_set(&env, &plans_key, &plans);
+
+        (SubscriptionPlanCreated {
+            plan_id,
+            merchant,
+            price,
+            interval,
+        })
+        .publish(&env);
+
+        Ok(plan_id)
     }
 
-    /// Removes a token from the list of allowed tokens for payments.
-    ///
-    /// # Arguments
-    /// * `admin` - The admin authorizing this change (must be in the multisig admin list)
-    /// * `token` - The SPL token address to remove from the allowed list
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if the admin is unauthorized or multisig is not initialized.
-    pub fn remove_allowed_token(env: Env, admin: Address, token: Address) -> Result<(), Error> {
-        admin.require_auth();
+    /// Schedule a new plan price. Existing subscribers roll onto it from their first
+    /// charge at or after `effective_at`, which must be at least `PLAN_PRICE_NOTICE_PERIOD`
+    /// away and later than every price already scheduled. Returns the new price version.
+    pub fn schedule_plan_price(
+        env: Env,
+        merchant: Address,
+        plan_id: u64,
+        new_price: i128,
+        effective_at: u64,
+    ) -> Result<u32, Error> {
+        merchant.require_auth();
 
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
+        let mut plan = Self::load_plan(&env, plan_id)?;
+        if plan.merchant != merchant {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
+        if new_price <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if effective_at < env.ledger().timestamp() + PLAN_PRICE_NOTICE_PERIOD {
+            return Err(Error::Subscription(SubscriptionError::PriceNoticeTooShort));
+        }
 
-        let tokens: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::AllowedTokens))
-            .unwrap_or_else(|| Vec::new(&env));
-        let mut updated = Vec::new(&env);
-        for entry in tokens.iter() {
-            if entry != token {
-                updated.push_back(entry);
-            }
+        let latest = plan.prices.last().unwrap();
+        if effective_at <= latest.effective_at {
+            return Err(Error::Subscription(
+                SubscriptionError::PriceScheduleConflict,
+            ));
         }
-        env.storage()
-            .instance()
-            .set(&DataKey::Config(ConfigKey::AllowedTokens), &updated);
 
-        Ok(())
-    }
+        let version = latest.version + 1;
+        plan.prices.push_back(PlanPrice {
+            version,
+            amount: new_price,
+            effective_at,
+        });
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Plan(plan_id)),
+            &plan,
+        );
 
-    /// Returns the list of allowed payment tokens.
-    ///
-    /// If no tokens have been configured, returns an empty list, which means all tokens are permitted.
-    ///
-    /// # Returns
-    /// A vector of token addresses that have been explicitly allowed by an admin.
-    /// Returns the list of all allowed tokens for payments.
-    ///
-    /// # Returns
-    /// A `Vec<Address>` of allowed token addresses. Returns an empty vector if none are configured.
-    pub fn get_allowed_tokens(env: Env) -> Vec<Address> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::AllowedTokens))
-            .unwrap_or_else(|| Vec::new(&env))
-    }
+        (PlanPriceScheduled {
+            plan_id,
+            version,
+            amount: new_price,
+            effective_at,
+        })
+        .publish(&env);
 
-    // Mirrors the refund contract's token-registry bypass (Issue #191): an
-    // empty allowlist means the allowlist hasn't been configured yet, so all
-    // tokens are permitted until an admin opts in by calling
-    // `add_allowed_token`.
-    fn is_token_allowed(env: &Env, token: &Address) -> bool {
-        let tokens: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::AllowedTokens))
-            .unwrap_or_else(|| Vec::new(env));
-        tokens.is_empty() || tokens.contains(token)
+        Ok(version)
     }
 
-    const ACTIVE_SUBSCRIPTION_PAGE_SIZE: u64 = 100;
+    /// Archive a plan so no new customers can subscribe. Existing subscriptions keep billing.
+    pub fn archive_subscription_plan(
+        env: Env,
+        merchant: Address,
+        plan_id: u64,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
 
-    fn merchant_active_subscription_count(env: &Env, merchant: &Address) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(
-                MerchantDataKey::MerchantActiveSubscriptionCount(merchant.clone()),
-            ))
-            .unwrap_or(0)
+        let mut plan = Self::load_plan(&env, plan_id)?;
+        if plan.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if plan.archived {
+            return Err(Error::Subscription(SubscriptionError::PlanArchived));
+        }
+
+        plan.archived = true;
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Plan(plan_id)),
+            &plan,
+        );
+
+        (SubscriptionPlanArchived { plan_id }).publish(&env);
+
+        Ok(())
     }
 
-    fn set_active_subscription_at(
-        env: &Env,
-        merchant: &Address,
-        flat_index: u64,
-        subscription_id: u64,
-    ) {
-        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let mut page: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                merchant.clone(),
-                page_num,
-            )))
-            .unwrap_or_else(|| Vec::new(env));
+    /// Subscribe the customer to a plan on the terms and price currently in effect.
+    pub fn subscribe_to_plan(env: Env, customer: Address, plan_id: u64) -> Result<u64, Error> {
+        customer.require_auth();
 
-        let page_offset_u32 = page_offset as u32;
-        while page.len() <= page_offset_u32 {
-            page.push_back(0);
+        let plan = Self::load_plan(&env, plan_id)?;
+        if plan.archived {
+            return Err(Error::Subscription(SubscriptionError::PlanArchived));
         }
-        page.set(page_offset_u32, subscription_id);
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                merchant.clone(),
-                page_num,
-            )),
-            &page,
+
+        let price = Self::plan_price_at(&plan, env.ledger().timestamp());
+        let sub_id = Self::do_create_subscription(
+            &env,
+            customer,
+            plan.merchant,
+            price.amount,
+            plan.token,
+            plan.currency,
+            plan.interval,
+            plan.duration,
+            plan.max_retries,
+            plan.metadata,
+            plan.trial_period_seconds,
+        )?;
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::PlanLink(sub_id)),
+            &plan_id,
         );
+
+        (SubscribedToPlan {
+            subscription_id: sub_id,
+            plan_id,
+            version: price.version,
+        })
+        .publish(&env);
+
+        Ok(sub_id)
     }
 
-    fn active_subscription_at(env: &Env, merchant: &Address, flat_index: u64) -> Option<u64> {
-        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let page: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                merchant.clone(),
-                page_num,
-            )))?;
-        let page_offset_u32 = page_offset as u32;
-        if page_offset_u32 < page.len() {
-            Some(page.get(page_offset_u32).unwrap())
-        } else {
-            None
-        }
+    /// Returns a subscription plan.
+    pub fn get_subscription_plan(env: Env, plan_id: u64) -> Result<SubscriptionPlan, Error> {
+        Self::load_plan(&env, plan_id)
     }
 
-    fn remove_active_subscription_page_entry(env: &Env, merchant: &Address, flat_index: u64) {
-        let page_num = flat_index / Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let page_offset = flat_index % Self::ACTIVE_SUBSCRIPTION_PAGE_SIZE;
-        let page_offset_u32 = page_offset as u32;
-        let mut page: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                merchant.clone(),
-                page_num,
-            )))
-            .unwrap_or_else(|| Vec::new(env));
+    /// Returns the plan's dunning override, if it was created with one.
+    pub fn get_plan_dunning_config(env: Env, plan_id: u64) -> Option<DunningConfig> {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::PlanDunning(plan_id)),
+        )
+    }
 
-        if page_offset_u32 < page.len() {
-            let mut rebuilt = Vec::new(env);
-            for i in 0..page.len() {
-                if i != page_offset_u32 {
-                    rebuilt.push_back(page.get(i).unwrap());
-                }
-            }
-            if rebuilt.is_empty() {
-                env.storage().instance().remove(&DataKey::Merchant(
-                    MerchantDataKey::MerchantActiveSubscriptions(merchant.clone(), page_num),
-                ));
-            } else {
-                env.storage().instance().set(
-                    &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                        merchant.clone(),
-                        page_num,
-                    )),
-                    &rebuilt,
-                );
-            }
-        }
+    /// Returns the IDs of every plan a merchant has published, archived ones included.
+    pub fn get_merchant_plans(env: Env, merchant: Address) -> Vec<u64> {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::MerchantPlans(merchant)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
     }
 
-    fn add_to_merchant_active_subscriptions(env: &Env, merchant: &Address, subscription_id: u64) {
-        let count = Self::merchant_active_subscription_count(env, merchant);
-        Self::set_active_subscription_at(env, merchant, count, subscription_id);
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(subscription_id)),
-            &count,
-        );
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptionCount(
-                merchant.clone(),
-            )),
-            &(count + 1),
-        );
+    /// Returns the plan a subscription was created from, if any.
+    pub fn get_subscription_plan_id(env: Env, subscription_id: u64) -> Option<u64> {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::PlanLink(subscription_id)),
+        )
     }
 
-    fn remove_from_merchant_active_subscriptions(
-        env: &Env,
-        merchant: &Address,
+    /// Move a subscription to a new amount or plan. The customer authorises the change.
+    ///
+    /// `Immediate` changes credit the unused time of the paid cycle at the old price and
+    /// charge it at the new one, both after any group discount. The difference is pulled
+    /// from the customer straight away, or kept as a credit against later cycles when the
+    /// change is a downgrade. `NextCycle` changes apply when the next cycle is charged.
+    /// `payment_count` and `next_payment_at` are left untouched either way.
+    ///
+    /// Returns the prorated difference: positive when charged, negative when credited.
+    pub fn change_subscription_plan(
+        env: Env,
+        customer: Address,
         subscription_id: u64,
-    ) {
-        let count = Self::merchant_active_subscription_count(env, merchant);
-        if count == 0 {
-            return;
+        change: PlanChange,
+        timing: PlanChangeTiming,
+    ) -> Result<i128, Error> {
+        customer.require_auth();
+
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::NotFound))?;
+        if sub.customer != customer {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if sub.status != SubscriptionStatus::Active {
+            return Err(Error::Subscription(SubscriptionError::NotActive));
         }
 
-        let index: u64 = match env.storage().instance().get(&DataKey::Merchant(
-            MerchantDataKey::ActiveSubscriptionIndex(subscription_id),
-        )) {
-            Some(i) => i,
-            None => return,
-        };
-        let last_index = count - 1;
+        let now = env.ledger().timestamp();
+        let old_amount = sub.amount;
+        let (new_amount, plan_id) = Self::resolve_plan_change(&env, &sub, &change, true)?;
+        let pending_key =
+            DataKey::Subscription(SubscriptionKey::PendingPlanChange(subscription_id));
+
+        if timing == PlanChangeTiming::NextCycle {
+            let effective_at = sub.next_payment_at;
+            record_set(
+                &env,
+                &pending_key,
+                &PendingPlanChange {
+                    change,
+                    effective_at,
+                },
+            );
+            Self::publish_plan_change(
+                &env,
+                subscription_id,
+                old_amount,
+                new_amount,
+                0,
+                effective_at,
+            );
+            return Ok(0);
+        }
+        record_remove(&env, &pending_key);
 
-        if index != last_index {
-            if let Some(last_id) = Self::active_subscription_at(env, merchant, last_index) {
-                Self::set_active_subscription_at(env, merchant, index, last_id);
-                env.storage().instance().set(
-                    &DataKey::Merchant(MerchantDataKey::ActiveSubscriptionIndex(last_id)),
-                    &index,
-                );
+        // The last charge paid for the cycle ending at `next_payment_at`; nothing has
+        // been paid yet before the first charge.
+        let mut difference: i128 = 0;
+        if sub.payment_count > 0 && now < sub.next_payment_at {
+            let remaining = (sub.next_payment_at - now).min(sub.interval) as i128;
+            let old_price = Self::group_discounted_amount(&env, subscription_id, old_amount);
+            let new_price = Self::group_discounted_amount(&env, subscription_id, new_amount);
+            difference = (new_price - old_price) * remaining / sub.interval as i128;
+        }
+
+        if difference > 0 {
+            PaymentContract::check_and_update_spend_limit(&env, &sub.customer, difference)?;
+            if !Self::try_charge_customer(
+                &env,
+                &sub.customer,
+                &sub.token,
+                &sub.merchant,
+                difference,
+            ) {
+                return Err(Error::Payment(PaymentError::TransferFailed));
             }
+        } else if difference < 0 {
+            let credit_key =
+                DataKey::Subscription(SubscriptionKey::ProrationCredit(subscription_id));
+            let credit: i128 = record_get(&env, &credit_key).unwrap_or(0);
+            record_set(&env, &credit_key, &(credit - difference));
         }
 
-        Self::remove_active_subscription_page_entry(env, merchant, last_index);
-        env.storage().instance().remove(&DataKey::Merchant(
-            MerchantDataKey::ActiveSubscriptionIndex(subscription_id),
-        ));
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptionCount(
-                merchant.clone(),
-            )),
-            &last_index,
+        Self::apply_plan_change(&env, &mut sub, new_amount, plan_id);
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+            &sub,
+        );
+        Self::publish_plan_change(
+            &env,
+            subscription_id,
+            old_amount,
+            new_amount,
+            difference.abs(),
+            now,
         );
-    }
-
-    /// Returns active subscription IDs for a merchant (100 per page).
-    pub fn get_merchant_subscriptions(env: Env, merchant: Address, page: u64) -> Vec<u64> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::MerchantActiveSubscriptions(
-                merchant, page,
-            )))
-            .unwrap_or_else(|| Vec::new(&env))
-    }
 
-    fn is_valid_currency(currency: &Currency) -> bool {
-        matches!(
-            currency,
-            Currency::XLM | Currency::USDC | Currency::USDT | Currency::BTC | Currency::ETH
-        )
+        Ok(difference)
     }
 
-    /// Sets the manual conversion rate for a given currency.
-    ///
-    /// This rate is used when oracle-based rate fetching is disabled or unavailable.
-    ///
-    /// # Arguments
-    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
-    /// * `currency` - The fiat currency to set a conversion rate for.
-    /// * `rate` - The conversion rate in base units (e.g., 1_0000000 = 1:1).
+    /// Pin a subscription's billing dates to a `BillingAnchor` instead of a fixed
+    /// `interval`. Only the merchant may set it. The next charge moves to the anchor's
+    /// first due date on or after the one already scheduled, so a cycle never shortens.
+    /// `BillingAnchor::Yearly` bills on the anniversary of `created_at`.
     ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if unauthorized or the currency is invalid.
-    pub fn set_conversion_rate(
+    /// Returns the new `next_payment_at`.
+    pub fn set_subscription_billing_anchor(
         env: Env,
-        admin: Address,
-        currency: Currency,
-        rate: i128,
-    ) -> Result<(), Error> {
-        admin.require_auth();
+        merchant: Address,
+        subscription_id: u64,
+        anchor: BillingAnchor,
+    ) -> Result<u64, Error> {
+        merchant.require_auth();
 
-        let config: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !config.admins.contains(&admin) {
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::NotFound))?;
+        if sub.merchant != merchant {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
-
-        if !PaymentContract::is_valid_currency(&currency) {
-            return Err(Error::Basic(BasicError::InvalidCurrency));
+        if !is_valid_billing_anchor(&anchor) {
+            return Err(Error::Basic(BasicError::InvalidInterval));
         }
 
-        env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::ConversionRate(currency)),
-            &rate,
+        if let BillingAnchor::FixedSeconds(seconds) = anchor {
+            sub.interval = seconds;
+        } else {
+            sub.next_payment_at =
+                next_billing_time(&anchor, sub.created_at, sub.next_payment_at - 1);
+        }
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+            &sub,
+        );
+        record_set(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::BillingAnchor(subscription_id)),
+            &anchor,
         );
 
-        Ok(())
-    }
+        (SubscriptionBillingAnchorSet {
+            subscription_id,
+            anchor,
+            next_payment_at: sub.next_payment_at,
+        })
+        .publish(&env);
 
-    /// Returns the stored conversion rate for a given currency.
-    ///
-    /// Defaults to `1_0000000` (1:1) if no rate has been set.
-    ///
-    /// # Arguments
-    /// * `currency` - The fiat currency to query the conversion rate for.
-    ///
-    /// # Returns
-    /// The conversion rate in base units.
-    pub fn get_conversion_rate(env: Env, currency: Currency) -> i128 {
-        env.storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::ConversionRate(currency)))
-            .unwrap_or(1_0000000)
+        Ok(sub.next_payment_at)
     }
 
-    /// Configures the oracle-based price feed for a given currency.
-    ///
-    /// When enabled, the contract will fetch live conversion rates from the oracle
-    /// contract instead of using the manual conversion rate.
-    ///
-    /// # Arguments
-    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
-    /// * `config` - The oracle rate configuration including oracle address, price feed ID,
-    ///   staleness threshold, and currency.
-    ///
-    /// # Returns
-    /// `Ok(())` on success, or an error if unauthorized.
-    pub fn set_oracle_rate_config(
+    /// Returns the anchor a subscription bills on; `FixedSeconds(interval)` unless one was set.
+    pub fn get_subscription_billing_anchor(
         env: Env,
-        admin: Address,
-        config: OracleRateConfig,
-    ) -> Result<(), Error> {
-        admin.require_auth();
-        let multisig: MultiSigConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
-            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
-        if !multisig.admins.contains(&admin) {
-            return Err(Error::Basic(BasicError::Unauthorized));
-        }
-        env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::OracleRateConfig(config.currency.clone())),
-            &config,
-        );
-        Ok(())
+        subscription_id: u64,
+    ) -> Result<BillingAnchor, Error> {
+        let sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::NotFound))?;
+        Ok(Self::subscription_anchor(&env, &sub))
     }
 
-    /// Returns the oracle rate configuration for a given currency.
-    ///
-    /// # Arguments
-    /// * `currency` - The fiat currency to query the oracle config for.
-    ///
-    /// # Returns
-    /// `Some(OracleRateConfig)` if configured, `None` otherwise.
-    pub fn get_oracle_rate_config(env: Env, currency: Currency) -> Option<OracleRateConfig> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::OracleRateConfig(currency)))
+    fn subscription_anchor(env: &Env, sub: &Subscription) -> BillingAnchor {
+        record_get(
+            env,
+            &DataKey::Subscription(SubscriptionKey::BillingAnchor(sub.id)),
+        )
+        .unwrap_or(BillingAnchor::FixedSeconds(sub.interval))
     }
 
-    /// Fetches a fresh conversion rate from the oracle and stores it.
-    ///
-    /// If the oracle is disabled for this currency, returns the stored manual rate.
-    /// If the oracle feed data is stale (older than `max_staleness_seconds`), returns an error.
-    ///
-    /// # Arguments
-    /// * `currency` - The fiat currency to refresh the conversion rate for.
-    ///
-    /// # Returns
-    /// The updated conversion rate on success.
-    ///
-    /// # Errors
-    /// - `OracleNotConfigured` if no oracle config exists for the currency.
-    /// - `OracleCallFailed` if the oracle contract call fails.
-    /// - `OracleFeedStale` if the fetched data exceeds the staleness threshold.
-    pub fn refresh_conversion_rate(env: Env, currency: Currency) -> Result<i128, Error> {
-        let cfg: OracleRateConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::OracleRateConfig(
-                currency.clone(),
-            )))
-            .ok_or(Error::Basic(BasicError::OracleNotConfigured))?;
+    /// The subscription's first due date strictly after `after`.
+    fn next_subscription_billing(env: &Env, sub: &Subscription, after: u64) -> u64 {
+        next_billing_time(&Self::subscription_anchor(env, sub), sub.created_at, after)
+    }
 
-        if !cfg.enabled {
-            return Ok(PaymentContract::get_conversion_rate(env, currency));
+    /// When the subscription is next actually charged: its next due date, or the
+    /// first due date once a running trial has ended.
+    fn next_billed_at(env: &Env, sub: &Subscription) -> u64 {
+        let mut due = sub.next_payment_at;
+        if sub.trial_data.ends_at <= due {
+            return due;
         }
-
-        let args = (cfg.price_feed_id.clone(),).into_val(&env);
-        let fetched = env
-            .try_invoke_contract::<(i128, u64), Error>(
-                &cfg.oracle_address,
-                &Symbol::new(&env, "get_price"),
-                args,
-            )
-            .map_err(|_| Error::Basic(BasicError::OracleCallFailed))?
-            .map_err(|_| Error::Basic(BasicError::OracleCallFailed))?;
-
-        let now = env.ledger().timestamp();
-        if now.saturating_sub(fetched.1) > cfg.max_staleness_seconds {
-            return Err(Error::Basic(BasicError::OracleFeedStale));
+        match Self::subscription_anchor(env, sub) {
+            BillingAnchor::FixedSeconds(seconds) => {
+                let cycles = (sub.trial_data.ends_at - due).div_ceil(seconds);
+                due + cycles * seconds
+            }
+            anchor => {
+                while due < sub.trial_data.ends_at {
+                    due = next_billing_time(&anchor, sub.created_at, due);
+                }
+                due
+            }
         }
-
-        env.storage().instance().set(
-            &DataKey::Feature(FeatureKey::ConversionRate(currency)),
-            &fetched.0,
-        );
-        Ok(fetched.0)
     }
 
-    // ── RECURRING / SUBSCRIPTION METHODS ────────────────────────────────────
+    /// Returns the plan change waiting for the next cycle, if any.
+    pub fn get_pending_plan_change(env: Env, subscription_id: u64) -> Option<PendingPlanChange> {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::PendingPlanChange(subscription_id)),
+        )
+    }
 
-    /// Create a new subscription. The customer authorises the creation.
-    /// `interval`             – seconds between each automatic payment
-    /// `duration`             – total lifetime in seconds (0 = indefinite)
-    /// `max_retries`          – how many times to retry a failed cycle (0 uses DEFAULT)
-    /// `trial_period_seconds` – free trial duration in seconds (0 = no trial)
-    pub fn create_subscription(
-        env: Env,
-        customer: Address,
-        merchant: Address,
-        amount: i128,
-        token: Address,
-        currency: Currency,
-        interval: u64,
-        duration: u64,
-        max_retries: u64,
-        metadata: String,
-        trial_period_seconds: u64,
-    ) -> Result<u64, Error> {
-        customer.require_auth();
+    /// Returns the unused downgrade credit that will be taken off the next charges.
+    pub fn get_proration_credit(env: Env, subscription_id: u64) -> i128 {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::ProrationCredit(subscription_id)),
+        )
+        .unwrap_or(0)
+    }
 
-        if !PaymentContract::is_valid_currency(&currency) {
-            return Err(Error::Basic(BasicError::InvalidCurrency));
+    /// Resolves a plan change to the new per-cycle amount and the plan to follow.
+    /// `validate` is false when a scheduled change is applied, so a plan archived
+    /// in the meantime still takes its subscribers.
+    fn resolve_plan_change(
+        env: &Env,
+        sub: &Subscription,
+        change: &PlanChange,
+        validate: bool,
+    ) -> Result<(i128, Option<u64>), Error> {
+        match change {
+            PlanChange::Amount(amount) => {
+                if *amount <= 0 {
+                    return Err(Error::Basic(BasicError::InvalidAmount));
+                }
+                Ok((*amount, None))
+            }
+            PlanChange::Plan(plan_id) => {
+                let plan = Self::load_plan(env, *plan_id)?;
+                if validate {
+                    if plan.archived {
+                        return Err(Error::Subscription(SubscriptionError::PlanArchived));
+                    }
+                    if plan.merchant != sub.merchant
+                        || plan.token != sub.token
+                        || plan.interval != sub.interval
+                    {
+                        return Err(Error::Subscription(SubscriptionError::IncompatiblePlan));
+                    }
+                }
+                let price = Self::plan_price_at(&plan, env.ledger().timestamp());
+                Ok((price.amount, Some(*plan_id)))
+            }
         }
-        if metadata.len() > MAX_METADATA_SIZE {
-            return Err(Error::Basic(BasicError::MetadataTooLarge));
+    }
+
+    fn apply_plan_change(env: &Env, sub: &mut Subscription, amount: i128, plan_id: Option<u64>) {
+        sub.amount = amount;
+        let link_key = DataKey::Subscription(SubscriptionKey::PlanLink(sub.id));
+        match plan_id {
+            Some(id) => record_set(env, &link_key, &id),
+            None => record_remove(env, &link_key),
         }
-        if interval == 0 {
-            // return Err(Error::InvalidInterval);
-            return Err(Error::Basic(BasicError::InvalidInterval));
+    }
+
+    fn publish_plan_change(
+        env: &Env,
+        subscription_id: u64,
+        old_amount: i128,
+        new_amount: i128,
+        prorated: i128,
+        effective_at: u64,
+    ) {
+        if new_amount < old_amount {
+            (SubscriptionDowngraded {
+                subscription_id,
+                old_amount,
+                new_amount,
+                prorated_credit: prorated,
+                effective_at,
+            })
+            .publish(env);
+        } else {
+            (SubscriptionUpgraded {
+                subscription_id,
+                old_amount,
+                new_amount,
+                prorated_charge: prorated,
+                effective_at,
+            })
+            .publish(env);
         }
+    }
 
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Counter))
-            .unwrap_or(0);
-        let sub_id = counter + 1;
+    /// Works out what the cycle being charged pulls from the customer: applies a
+    /// scheduled plan change, rolls onto the current plan price, then takes off the
+    /// group discount and any proration credit. Returns the charge and the credit used.
+    fn prepare_cycle_charge(env: &Env, sub: &mut Subscription) -> (i128, i128) {
+        let pending_key = DataKey::Subscription(SubscriptionKey::PendingPlanChange(sub.id));
+        if let Some(pending) = record_get::<DataKey, PendingPlanChange>(env, &pending_key) {
+            if env.ledger().timestamp() >= pending.effective_at {
+                if let Ok((amount, plan_id)) =
+                    Self::resolve_plan_change(env, sub, &pending.change, false)
+                {
+                    Self::apply_plan_change(env, sub, amount, plan_id);
+                    record_set(
+                        env,
+                        &DataKey::Subscription(SubscriptionKey::Data(sub.id)),
+                        &*sub,
+                    );
+                }
+                record_remove(env, &pending_key);
+            }
+        }
+        Self::sync_plan_price(env, sub);
 
-        let now = env.ledger().timestamp();
-        let ends_at = if duration > 0 { now + duration } else { 0 };
-        let retries = if max_retries == 0 {
-            DEFAULT_MAX_RETRIES
-        } else {
-            max_retries
-        };
+        let price = Self::group_discounted_amount(env, sub.id, sub.amount);
+        let credit: i128 = record_get(
+            env,
+            &DataKey::Subscription(SubscriptionKey::ProrationCredit(sub.id)),
+        )
+        .unwrap_or(0);
+        let credit_used = credit.min(price);
+        (price - credit_used, credit_used)
+    }
 
-        let trial_ends_at = if trial_period_seconds > 0 {
-            now + trial_period_seconds
+    fn use_proration_credit(env: &Env, subscription_id: u64, used: i128) {
+        if used == 0 {
+            return;
+        }
+        let key = DataKey::Subscription(SubscriptionKey::ProrationCredit(subscription_id));
+        let credit: i128 = record_get(env, &key).unwrap_or(0);
+        if credit <= used {
+            record_remove(env, &key);
         } else {
-            0
-        };
+            record_set(env, &key, &(credit - used));
+        }
+    }
 
-        let sub = Subscription {
-            id: sub_id,
-            customer: customer.clone(),
-            merchant: merchant.clone(),
-            amount,
-            token,
-            currency,
-            interval,
-            duration,
-            status: SubscriptionStatus::Active,
-            created_at: now,
-            next_payment_at: now + interval,
-            ends_at,
-            payment_count: 0,
-            retry_count: 0,
-            max_retries: retries,
-            metadata,
-            trial_data: SubscriptionTrialData {
-                period_seconds: trial_period_seconds,
-                ends_at: trial_ends_at,
-                converted: false,
-            },
-            pause_data: SubscriptionPauseData {
-                last_paused_at: 0,
-                total_pause_duration: 0,
-                proration_enabled: false,
-            },
-        };
+    /// `amount` less the discount of the active group the subscription belongs to.
+    fn group_discounted_amount(env: &Env, subscription_id: u64, amount: i128) -> i128 {
+        let group: Option<SubscriptionGroup> = record_get::<DataKey, u64>(
+            env,
+            &DataKey::Subscription(SubscriptionKey::GroupMembership(subscription_id)),
+        )
+        .and_then(|group_id| {
+            record_get(
+                env,
+                &DataKey::Subscription(SubscriptionKey::Group(group_id)),
+            )
+        });
+        match group {
+            Some(group) if group.active => {
+                let bps = group.discount_bps.min(10_000) as i128;
+                amount - amount * bps / 10_000
+            }
+            _ => amount,
+        }
+    }
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Subscription(SubscriptionKey::Data(sub_id)), &sub);
-        env.storage()
-            .instance()
-            .set(&DataKey::Subscription(SubscriptionKey::Counter), &sub_id);
+    fn load_plan(env: &Env, plan_id: u64) -> Result<SubscriptionPlan, Error> {
+        record_get(env, &DataKey::Subscription(SubscriptionKey::Plan(plan_id)))
+            .ok_or(Error::Subscription(SubscriptionError::PlanNotFound))
+    }
 
-        // Index by customer
-        let c_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::SubscriptionCount(
-                customer.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::Subscriptions(customer.clone(), c_count)),
-            &sub_id,
-        );
-        env.storage().instance().set(
-            &DataKey::Customer(CustomerDataKey::SubscriptionCount(customer)),
-            &(c_count + 1),
-        );
+    /// The latest plan price whose `effective_at` has been reached.
+    fn plan_price_at(plan: &SubscriptionPlan, at: u64) -> PlanPrice {
+        let mut current = plan.prices.get(0).unwrap();
+        for price in plan.prices.iter() {
+            if price.effective_at > at {
+                break;
+            }
+            current = price;
+        }
+        current
+    }
 
-        // Index by merchant
-        let m_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::SubscriptionCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::Subscriptions(merchant.clone(), m_count)),
-            &sub_id,
-        );
-        env.storage().instance().set(
-            &DataKey::Merchant(MerchantDataKey::SubscriptionCount(merchant.clone())),
-            &(m_count + 1),
-        );
+    /// Moves a plan subscription onto the plan price in effect now. The caller persists `sub`.
+    fn sync_plan_price(env: &Env, sub: &mut Subscription) {
+        let plan_id: u64 = match record_get(
+            env,
+            &DataKey::Subscription(SubscriptionKey::PlanLink(sub.id)),
+        ) {
+            Some(id) => id,
+            None => return,
+        };
+        let plan: SubscriptionPlan =
+            match record_get(env, &DataKey::Subscription(SubscriptionKey::Plan(plan_id))) {
+                Some(plan) => plan,
+                None => return,
+            };
 
-        Self::add_to_merchant_active_subscriptions(&env, &merchant, sub_id);
+        let price = Self::plan_price_at(&plan, env.ledger().timestamp());
+        if price.amount == sub.amount {
+            return;
+        }
+        sub.amount = price.amount;
 
-        (SubscriptionCreated {
-            subscription_id: sub_id,
-            customer: sub.customer.clone(),
-            merchant: sub.merchant.clone(),
-            amount: sub.amount,
-            interval: sub.interval,
+        (SubscriptionPriceUpdated {
+            subscription_id: sub.id,
+            plan_id,
+            version: price.version,
+            amount: price.amount,
         })
-        .publish(&env);
-
-        // Emit TrialStarted if trial is active
-        if sub.trial_data.ends_at > 0 {
-            (TrialStarted {
-                subscription_id: sub_id,
-                trial_ends_at: sub.trial_data.ends_at,
-            })
-            .publish(&env);
-        }
+        .publish(env);
+    }
 
-        Ok(sub_id)
+    /// Dunning config for a subscription: its plan's override, else the contract-wide config.
+    fn subscription_dunning_config(env: &Env, subscription_id: u64) -> DunningConfig {
+        record_get::<DataKey, u64>(
+            env,
+            &DataKey::Subscription(SubscriptionKey::PlanLink(subscription_id)),
+        )
+        .and_then(|plan_id| {
+            record_get(
+                env,
+                &DataKey::Subscription(SubscriptionKey::PlanDunning(plan_id)),
+            )
+        })
+        .unwrap_or_else(|| PaymentContract::get_dunning_config(env.clone()))
     }
 
     /// Extend the trial period for a subscription. Only callable by the merchant before the trial expires.
@@ -5588,13 +9685,11 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         merchant.require_auth();
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .ok_or(Error::Subscription(SubscriptionError::NotFound))?;
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::NotFound))?;
 
         if sub.merchant != merchant {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -5632,7 +9727,8 @@ impl PaymentContract {
             ))?;
         sub.trial_data.period_seconds = new_total;
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
@@ -5644,33 +9740,28 @@ impl PaymentContract {
     /// Anyone (typically an off-chain keeper / cron) may call this once the
     /// payment is due. It handles retry logic internally.
     pub fn execute_recurring_payment(env: Env, subscription_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         let now = env.ledger().timestamp();
 
         // InDunning path: enforce on-chain backoff before retrying
         if sub.status == SubscriptionStatus::InDunning {
-            let mut dunning: DunningState = env
-                .storage()
-                .instance()
-                .get(&DataKey::State(StateDataKey::DunningState(subscription_id)))
-                .ok_or(Error::Subscription(SubscriptionError::DunningNotFound))?;
+            let mut dunning: DunningState = record_get(
+                &env,
+                &DataKey::State(StateDataKey::DunningState(subscription_id)),
+            )
+            .ok_or(Error::Subscription(SubscriptionError::DunningNotFound))?;
 
             if now < dunning.next_retry_at {
                 return Err(Error::Subscription(SubscriptionError::RetryTooEarly));
@@ -5686,36 +9777,45 @@ impl PaymentContract {
                 return Err(Error::Subscription(SubscriptionError::MerchantPaused));
             }
 
+            let (charge, credit_used) = Self::prepare_cycle_charge(&env, &mut sub);
+
             // Check customer spend limit (#282)
             if let Err(_) =
-                PaymentContract::check_and_update_spend_limit(&env, &sub.customer, sub.amount)
+                PaymentContract::check_and_update_spend_limit(&env, &sub.customer, charge)
             {
                 return Err(Error::Feature(FeatureError::SpendLimitExceeded));
             }
 
-            let token_client = token::Client::new(&env, &sub.token);
-            let contract_address = env.current_contract_address();
-            let transfer_ok = token_client
-                .try_transfer_from(&contract_address, &sub.customer, &sub.merchant, &sub.amount)
-                .is_ok();
+            let transfer_ok = charge == 0
+                || Self::try_charge_customer(
+                    &env,
+                    &sub.customer,
+                    &sub.token,
+                    &sub.merchant,
+                    charge,
+                );
 
             if transfer_ok {
+                Self::use_proration_credit(&env, subscription_id, credit_used);
                 sub.payment_count += 1;
                 sub.retry_count = 0;
-                sub.next_payment_at = sub.next_payment_at + sub.interval;
+                sub.next_payment_at =
+                    Self::next_subscription_billing(&env, &sub, sub.next_payment_at);
                 sub.status = SubscriptionStatus::Active;
 
                 if sub.ends_at > 0 && sub.next_payment_at >= sub.ends_at {
                     sub.status = SubscriptionStatus::Expired;
                 }
 
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                     &sub,
                 );
-                env.storage()
-                    .instance()
-                    .remove(&DataKey::State(StateDataKey::DunningState(subscription_id)));
+                record_remove(
+                    &env,
+                    &DataKey::State(StateDataKey::DunningState(subscription_id)),
+                );
 
                 (DunningResolved {
                     subscription_id,
@@ -5726,7 +9826,7 @@ impl PaymentContract {
                 (RecurringPaymentExecuted {
                     subscription_id,
                     payment_count: sub.payment_count,
-                    amount: sub.amount,
+                    amount: charge,
                     next_payment_at: sub.next_payment_at,
                 })
                 .publish(&env);
@@ -5736,11 +9836,13 @@ impl PaymentContract {
 
                 if dunning.retry_count >= dunning.max_retries {
                     sub.status = SubscriptionStatus::Suspended;
-                    env.storage().instance().set(
+                    record_set(
+                        &env,
                         &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                         &sub,
                     );
-                    env.storage().instance().set(
+                    record_set(
+                        &env,
                         &DataKey::State(StateDataKey::DunningState(subscription_id)),
                         &dunning,
                     );
@@ -5756,7 +9858,8 @@ impl PaymentContract {
 
                 // Exponential backoff: backoff_seconds * 2^retry_count
                 dunning.next_retry_at = now + (dunning.backoff_seconds << dunning.retry_count);
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::State(StateDataKey::DunningState(subscription_id)),
                     &dunning,
                 );
@@ -5781,7 +9884,8 @@ impl PaymentContract {
         // Check subscription has not ended
         if sub.ends_at > 0 && now >= sub.ends_at {
             sub.status = SubscriptionStatus::Expired;
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                 &sub,
             );
@@ -5805,30 +9909,28 @@ impl PaymentContract {
 
         // Skip charge if still within trial period
         if sub.trial_data.ends_at > 0 && now < sub.trial_data.ends_at {
-            sub.next_payment_at = sub.next_payment_at + sub.interval;
-            env.storage().instance().set(
+            sub.next_payment_at = Self::next_subscription_billing(&env, &sub, sub.next_payment_at);
+            record_set(
+                &env,
                 &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                 &sub,
             );
             return Ok(());
         }
 
+        let (charge, credit_used) = Self::prepare_cycle_charge(&env, &mut sub);
+
         // Check customer spend limit (#282)
-        if let Err(_) =
-            PaymentContract::check_and_update_spend_limit(&env, &sub.customer, sub.amount)
-        {
+        if let Err(_) = PaymentContract::check_and_update_spend_limit(&env, &sub.customer, charge) {
             return Err(Error::Feature(FeatureError::SpendLimitExceeded));
         }
 
-        // Attempt token transfer
-        let token_client = token::Client::new(&env, &sub.token);
-        let contract_address = env.current_contract_address();
-
-        let transfer_ok = token_client
-            .try_transfer_from(&contract_address, &sub.customer, &sub.merchant, &sub.amount)
-            .is_ok();
+        // Attempt the charge, from the customer's wallet first
+        let transfer_ok = charge == 0
+            || Self::try_charge_customer(&env, &sub.customer, &sub.token, &sub.merchant, charge);
 
         if transfer_ok {
+            Self::use_proration_credit(&env, subscription_id, credit_used);
             // Mark converted on first post-trial charge
             if sub.trial_data.ends_at > 0 && !sub.trial_data.converted {
                 sub.trial_data.converted = true;
@@ -5841,14 +9943,15 @@ impl PaymentContract {
 
             sub.payment_count += 1;
             sub.retry_count = 0;
-            sub.next_payment_at = sub.next_payment_at + sub.interval;
+            sub.next_payment_at = Self::next_subscription_billing(&env, &sub, sub.next_payment_at);
 
             // Auto-expire when duration is reached
             if sub.ends_at > 0 && sub.next_payment_at >= sub.ends_at {
                 sub.status = SubscriptionStatus::Expired;
             }
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                 &sub,
             );
@@ -5856,7 +9959,7 @@ impl PaymentContract {
             (RecurringPaymentExecuted {
                 subscription_id,
                 payment_count: sub.payment_count,
-                amount: sub.amount,
+                amount: charge,
                 next_payment_at: sub.next_payment_at,
             })
             .publish(&env);
@@ -5933,7 +10036,8 @@ impl PaymentContract {
             max_units_per_period,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Metered(sub_id)),
             &sub,
         );
@@ -5969,13 +10073,11 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         merchant.require_auth();
 
-        let mut sub: MeteredSubscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Metered(
-                subscription_id,
-            )))
-            .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
+        let mut sub: MeteredSubscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
 
         if sub.merchant != merchant {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -5989,7 +10091,8 @@ impl PaymentContract {
 
         sub.accumulated_units = sub.accumulated_units.saturating_add(units);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
             &sub,
         );
@@ -6015,12 +10118,11 @@ impl PaymentContract {
     /// # Panics
     /// Panics if the metered subscription is not found.
     pub fn get_current_usage(env: Env, subscription_id: u64) -> MeteredSubscription {
-        env.storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Metered(
-                subscription_id,
-            )))
-            .expect("MeteredSubscription not found")
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
+        )
+        .expect("MeteredSubscription not found")
     }
 
     /// Sets or updates the billing cap for a metered subscription.
@@ -6046,13 +10148,11 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         merchant.require_auth();
 
-        let mut sub: MeteredSubscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Metered(
-                subscription_id,
-            )))
-            .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
+        let mut sub: MeteredSubscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
 
         if sub.merchant != merchant {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -6060,7 +10160,8 @@ impl PaymentContract {
 
         sub.billing_cap = Some(cap);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
             &sub,
         );
@@ -6083,13 +10184,11 @@ impl PaymentContract {
     /// - `MeteredNotFound` if the subscription does not exist.
     /// - `BillingOverflow` if the multiplication of units × price overflows.
     pub fn execute_metered_billing(env: Env, subscription_id: u64) -> Result<i128, Error> {
-        let mut sub: MeteredSubscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Metered(
-                subscription_id,
-            )))
-            .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
+        let mut sub: MeteredSubscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::MeteredNotFound))?;
 
         Self::require_merchant_not_paused(&env, &sub.merchant)?;
 
@@ -6112,14 +10211,14 @@ impl PaymentContract {
             }
         }
 
-        let token_client = token::Client::new(&env, &sub.token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer_from(&contract_address, &sub.customer, &sub.merchant, &amount);
+        PaymentContract::check_and_update_spend_limit(&env, &sub.customer, amount)?;
+        Self::charge_customer(&env, &sub.customer, &sub.token, &sub.merchant, amount);
 
         sub.accumulated_units = 0;
         sub.last_reset_at = env.ledger().timestamp();
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Metered(subscription_id)),
             &sub,
         );
@@ -6150,23 +10249,18 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         caller.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         let config: Option<MultiSigConfig> = env
             .storage()
@@ -6187,7 +10281,8 @@ impl PaymentContract {
         }
 
         sub.status = SubscriptionStatus::Cancelled;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
@@ -6221,23 +10316,18 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         if sub.customer != customer {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -6249,7 +10339,8 @@ impl PaymentContract {
 
         sub.status = SubscriptionStatus::Paused;
         sub.pause_data.last_paused_at = env.ledger().timestamp();
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
@@ -6267,23 +10358,18 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         if sub.customer != customer {
             return Err(Error::Basic(BasicError::Unauthorized));
@@ -6327,7 +10413,8 @@ impl PaymentContract {
             .publish(&env);
         }
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
@@ -6341,14 +10428,13 @@ impl PaymentContract {
         Ok(())
     }
 
-    /// Read a single subscription.
-    pub fn get_subscription(env: Env, subscription_id: u64) -> Subscription {
-        env.storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .expect("Subscription not found")
+    /// Read a single subscription.
+    pub fn get_subscription(env: Env, subscription_id: u64) -> Subscription {
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .expect("Subscription not found")
     }
 
     /// Paginated list of subscriptions for a customer.
@@ -6358,33 +10444,24 @@ impl PaymentContract {
         limit: u64,
         offset: u64,
     ) -> Vec<Subscription> {
-        let total: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::SubscriptionCount(
-                customer.clone(),
-            )))
-            .unwrap_or(0);
+        let total: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::SubscriptionCount(customer.clone())),
+        )
+        .unwrap_or(0);
 
         let mut result = Vec::new(&env);
         let end = (offset + limit).min(total);
 
         for i in offset..end {
-            if let Some(sub_id) = env
-                .storage()
-                .instance()
-                .get::<DataKey, u64>(&DataKey::Customer(CustomerDataKey::Subscriptions(
-                    customer.clone(),
-                    i,
-                )))
-            {
-                if let Some(sub) =
-                    env.storage()
-                        .instance()
-                        .get::<DataKey, Subscription>(&DataKey::Subscription(
-                            SubscriptionKey::Data(sub_id),
-                        ))
-                {
+            if let Some(sub_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Customer(CustomerDataKey::Subscriptions(customer.clone(), i)),
+            ) {
+                if let Some(sub) = record_get::<DataKey, Subscription>(
+                    &env,
+                    &DataKey::Subscription(SubscriptionKey::Data(sub_id)),
+                ) {
                     result.push_back(sub);
                 }
             }
@@ -6400,33 +10477,24 @@ impl PaymentContract {
         limit: u64,
         offset: u64,
     ) -> Vec<Subscription> {
-        let total: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::SubscriptionCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
+        let total: u64 = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::SubscriptionCount(merchant.clone())),
+        )
+        .unwrap_or(0);
 
         let mut result = Vec::new(&env);
         let end = (offset + limit).min(total);
 
         for i in offset..end {
-            if let Some(sub_id) = env
-                .storage()
-                .instance()
-                .get::<DataKey, u64>(&DataKey::Merchant(MerchantDataKey::Subscriptions(
-                    merchant.clone(),
-                    i,
-                )))
-            {
-                if let Some(sub) =
-                    env.storage()
-                        .instance()
-                        .get::<DataKey, Subscription>(&DataKey::Subscription(
-                            SubscriptionKey::Data(sub_id),
-                        ))
-                {
+            if let Some(sub_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Merchant(MerchantDataKey::Subscriptions(merchant.clone(), i)),
+            ) {
+                if let Some(sub) = record_get::<DataKey, Subscription>(
+                    &env,
+                    &DataKey::Subscription(SubscriptionKey::Data(sub_id)),
+                ) {
                     result.push_back(sub);
                 }
             }
@@ -6475,41 +10543,37 @@ impl PaymentContract {
 
     /// Returns the dunning state for a subscription, if any.
     pub fn get_dunning_state(env: Env, subscription_id: u64) -> Option<DunningState> {
-        env.storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::DunningState(subscription_id)))
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::DunningState(subscription_id)),
+        )
     }
 
     /// Retry a failed payment for a subscription in dunning.
     /// Validates that the retry is due before attempting.
     pub fn retry_failed_payment(env: Env, subscription_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         if sub.status != SubscriptionStatus::InDunning {
             return Err(Error::Subscription(SubscriptionError::NotInDunning));
         }
 
-        let mut dunning: DunningState = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::DunningState(subscription_id)))
-            .ok_or(Error::Subscription(SubscriptionError::DunningNotFound))?;
+        let mut dunning: DunningState = record_get(
+            &env,
+            &DataKey::State(StateDataKey::DunningState(subscription_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::DunningNotFound))?;
 
         let now = env.ledger().timestamp();
 
@@ -6518,31 +10582,32 @@ impl PaymentContract {
             return Err(Error::Subscription(SubscriptionError::RetryTooEarly));
         }
 
-        // Attempt the payment
-        let token_client = token::Client::new(&env, &sub.token);
-        let contract_address = env.current_contract_address();
+        let (charge, credit_used) = Self::prepare_cycle_charge(&env, &mut sub);
 
-        let transfer_ok = token_client
-            .try_transfer_from(&contract_address, &sub.customer, &sub.merchant, &sub.amount)
-            .is_ok();
+        // Attempt the payment, from the customer's wallet first
+        let transfer_ok = charge == 0
+            || Self::try_charge_customer(&env, &sub.customer, &sub.token, &sub.merchant, charge);
 
         if transfer_ok {
+            Self::use_proration_credit(&env, subscription_id, credit_used);
             sub.payment_count += 1;
             sub.retry_count = 0;
-            sub.next_payment_at = now + sub.interval;
+            sub.next_payment_at = Self::next_subscription_billing(&env, &sub, now);
             sub.status = SubscriptionStatus::Active;
 
             if sub.ends_at > 0 && sub.next_payment_at >= sub.ends_at {
                 sub.status = SubscriptionStatus::Expired;
             }
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                 &sub,
             );
-            env.storage()
-                .instance()
-                .remove(&DataKey::State(StateDataKey::DunningState(subscription_id)));
+            record_remove(
+                &env,
+                &DataKey::State(StateDataKey::DunningState(subscription_id)),
+            );
 
             (DunningResolved {
                 subscription_id,
@@ -6553,7 +10618,7 @@ impl PaymentContract {
             (RecurringPaymentExecuted {
                 subscription_id,
                 payment_count: sub.payment_count,
-                amount: sub.amount,
+                amount: charge,
                 next_payment_at: sub.next_payment_at,
             })
             .publish(&env);
@@ -6565,11 +10630,13 @@ impl PaymentContract {
 
             if dunning.retry_count >= dunning.max_retries {
                 sub.status = SubscriptionStatus::Suspended;
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                     &sub,
                 );
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::State(StateDataKey::DunningState(subscription_id)),
                     &dunning,
                 );
@@ -6585,7 +10652,8 @@ impl PaymentContract {
 
             // Exponential backoff: backoff_seconds * 2^retry_count
             dunning.next_retry_at = now + (dunning.backoff_seconds << dunning.retry_count);
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::State(StateDataKey::DunningState(subscription_id)),
                 &dunning,
             );
@@ -6619,23 +10687,18 @@ impl PaymentContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         if sub.status != SubscriptionStatus::InDunning
             && sub.status != SubscriptionStatus::Suspended
@@ -6646,17 +10709,19 @@ impl PaymentContract {
         // Reset to active state
         sub.status = SubscriptionStatus::Active;
         sub.retry_count = 0;
-        sub.next_payment_at = env.ledger().timestamp() + sub.interval;
+        sub.next_payment_at = Self::next_subscription_billing(&env, &sub, env.ledger().timestamp());
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
 
         // Remove dunning state
-        env.storage()
-            .instance()
-            .remove(&DataKey::State(StateDataKey::DunningState(subscription_id)));
+        record_remove(
+            &env,
+            &DataKey::State(StateDataKey::DunningState(subscription_id)),
+        );
 
         (DunningResolved {
             subscription_id,
@@ -6669,7 +10734,7 @@ impl PaymentContract {
 
     /// Internal function to enter dunning for a subscription.
     fn enter_dunning(env: &Env, subscription_id: u64, _reason: String) {
-        let config = PaymentContract::get_dunning_config(env.clone());
+        let config = PaymentContract::subscription_dunning_config(env, subscription_id);
         let now = env.ledger().timestamp();
 
         // retry_count = 0: first retry uses backoff * 2^0 = backoff (1x)
@@ -6683,21 +10748,20 @@ impl PaymentContract {
             last_failed_at: now,
         };
 
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::State(StateDataKey::DunningState(subscription_id)),
             &dunning_state,
         );
 
         // Update subscription status
-        if let Some(mut sub) =
-            env.storage()
-                .instance()
-                .get::<DataKey, Subscription>(&DataKey::Subscription(SubscriptionKey::Data(
-                    subscription_id,
-                )))
-        {
+        if let Some(mut sub) = record_get::<DataKey, Subscription>(
+            env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             sub.status = SubscriptionStatus::InDunning;
-            env.storage().instance().set(
+            record_set(
+                env,
                 &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
                 &sub,
             );
@@ -7334,8 +11398,33 @@ impl PaymentContract {
         result
     }
 
+    fn do_upgrade(env: &Env, new_wasm_hash: BytesN<32>) {
+        let previous_wasm_hash: Option<BytesN<32>> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::WasmHash));
+        if let Some(previous) = &previous_wasm_hash {
+            env.storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::PreviousWasmHash), previous);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::WasmHash), &new_wasm_hash);
+
+        (ContractUpgraded {
+            previous_wasm_hash,
+            new_wasm_hash: new_wasm_hash.clone(),
+        })
+        .publish(env);
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
     fn dispatch_action(env: &Env, proposal: &AdminProposal) -> Result<(), Error> {
         match proposal.action_type {
+            ActionType::UpgradeContract(ref new_wasm_hash) => {
+                PaymentContract::do_upgrade(env, new_wasm_hash.clone());
+            }
             ActionType::CompletePayment => {
                 let payment_id = PaymentContract::read_u64_from_bytes(&proposal.data, 0);
                 PaymentContract::do_complete_payment(env, payment_id)?;
@@ -7680,11 +11769,22 @@ impl PaymentContract {
         let net_amount = amount.saturating_sub(fee);
 
         // Transfer fee from customer to contract
-        let token_client = token::Client::new(env, token);
-        let contract_address = env.current_contract_address();
-        token_client.transfer_from(&contract_address, customer, &contract_address, &fee);
+        PaymentContract::charge_customer(
+            env,
+            customer,
+            token,
+            &env.current_contract_address(),
+            fee,
+        );
 
-        // Update accumulated fees
+        // Update accumulated fees, less any referral commissions
+        let commission = PaymentContract::accrue_referral_commissions(
+            env,
+            payment_id,
+            [merchant.clone(), customer.clone()],
+            token,
+            fee,
+        );
         let accumulated: i128 = env
             .storage()
             .instance()
@@ -7692,7 +11792,7 @@ impl PaymentContract {
             .unwrap_or(0);
         env.storage().instance().set(
             &DataKey::Payment(PaymentKey::AccumulatedFees),
-            &(accumulated + fee),
+            &(accumulated + fee - commission),
         );
 
         if risk_surcharge_bps > 0 {
@@ -8241,6 +12341,240 @@ impl PaymentContract {
         );
     }
 
+    // ── REFERRAL PROGRAMME ────────────────────────────────────────────────────
+
+    /// Configures the referral programme.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
+    /// * `config` - The commission share and how long referrals earn it.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an error if unauthorized or `commission_bps` exceeds 10000.
+    pub fn configure_referral_program(
+        env: Env,
+        admin: Address,
+        config: ReferralConfig,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let multisig: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !multisig.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if config.commission_bps > 10000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::ReferralConfig), &config);
+        Ok(())
+    }
+
+    /// Returns the referral programme configuration, if set.
+    pub fn get_referral_config(env: Env) -> Option<ReferralConfig> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::ReferralConfig))
+    }
+
+    /// Attaches a referrer to a merchant or customer at onboarding.
+    ///
+    /// The referee must not have any payments yet. For the programme's
+    /// `duration_seconds` from now, the referrer earns a commission on the
+    /// platform fee of every payment the referee makes or receives.
+    ///
+    /// # Arguments
+    /// * `referee` - The merchant or customer being onboarded (must authorize).
+    /// * `referrer` - The address that referred them.
+    ///
+    /// # Returns
+    /// `Ok(())` on success.
+    ///
+    /// # Errors
+    /// - `ReferralProgramNotFound` if the programme is not configured or inactive.
+    /// - `ReferralNotEligible` if the referee refers themselves, already has a
+    ///   referrer, or already has payments.
+    pub fn attach_referrer(env: Env, referee: Address, referrer: Address) -> Result<(), Error> {
+        referee.require_auth();
+        let config = match Self::get_referral_config(env.clone()) {
+            Some(config) if config.active => config,
+            _ => return Err(Error::Feature(FeatureError::ReferralProgramNotFound)),
+        };
+        let key = DataKey::Feature(FeatureKey::Referral(referee.clone()));
+        let merchant_payments: u64 = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(referee.clone())),
+        )
+        .unwrap_or(0);
+        let customer_payments: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(referee.clone())),
+        )
+        .unwrap_or(0);
+        if referee == referrer
+            || record_has(&env, &key)
+            || merchant_payments > 0
+            || customer_payments > 0
+        {
+            return Err(Error::Feature(FeatureError::ReferralNotEligible));
+        }
+
+        let now = env.ledger().timestamp();
+        let referral = Referral {
+            referrer: referrer.clone(),
+            referee: referee.clone(),
+            attached_at: now,
+            expires_at: now + config.duration_seconds,
+        };
+        record_set(&env, &key, &referral);
+
+        let mut analytics = Self::get_referrer_analytics(env.clone(), referrer.clone());
+        analytics.total_referrals += 1;
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::ReferrerAnalytics(referrer.clone())),
+            &analytics,
+        );
+
+        (ReferralAttached {
+            referee,
+            referrer,
+            expires_at: referral.expires_at,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the referral attached to a merchant or customer, if any.
+    pub fn get_referral(env: Env, referee: Address) -> Option<Referral> {
+        record_get(&env, &DataKey::Feature(FeatureKey::Referral(referee)))
+    }
+
+    /// Returns the referrer's unclaimed commission in `token`.
+    pub fn get_referral_balance(env: Env, referrer: Address, token: Address) -> i128 {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::ReferralBalance(referrer, token)),
+        )
+        .unwrap_or(0)
+    }
+
+    /// Returns a referrer's lifetime referral statistics.
+    pub fn get_referrer_analytics(env: Env, referrer: Address) -> ReferrerAnalytics {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::ReferrerAnalytics(referrer)),
+        )
+        .unwrap_or(ReferrerAnalytics {
+            total_referrals: 0,
+            commissioned_payments: 0,
+            total_earned: 0,
+            total_claimed: 0,
+        })
+    }
+
+    /// Claims the referrer's accrued commission in `token`.
+    ///
+    /// # Arguments
+    /// * `referrer` - The referrer claiming (must authorize).
+    /// * `token` - The token the commission accrued in.
+    ///
+    /// # Returns
+    /// The amount transferred on success, or `NoReferralCommission` if nothing has accrued.
+    pub fn claim_referral_commission(
+        env: Env,
+        referrer: Address,
+        token: Address,
+    ) -> Result<i128, Error> {
+        referrer.require_auth();
+        let key = DataKey::Feature(FeatureKey::ReferralBalance(referrer.clone(), token.clone()));
+        let amount: i128 = record_get(&env, &key).unwrap_or(0);
+        if amount <= 0 {
+            return Err(Error::Feature(FeatureError::NoReferralCommission));
+        }
+        record_remove(&env, &key);
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &referrer,
+            &amount,
+        );
+
+        let mut analytics = Self::get_referrer_analytics(env.clone(), referrer.clone());
+        analytics.total_claimed += amount;
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::ReferrerAnalytics(referrer.clone())),
+            &analytics,
+        );
+
+        (ReferralCommissionClaimed {
+            referrer,
+            token,
+            amount,
+        })
+        .publish(&env);
+        Ok(amount)
+    }
+
+    /// Credits referral commissions on a collected platform `fee` to the
+    /// referrers of the payment's merchant and customer whose referral has not
+    /// expired. Returns the total credited, which never exceeds `fee`.
+    fn accrue_referral_commissions(
+        env: &Env,
+        payment_id: u64,
+        parties: [Address; 2],
+        token: &Address,
+        fee: i128,
+    ) -> i128 {
+        let config = match PaymentContract::get_referral_config(env.clone()) {
+            Some(config) if config.active => config,
+            _ => return 0,
+        };
+
+        let now = env.ledger().timestamp();
+        let mut total = 0;
+        for party in parties {
+            let referral = match PaymentContract::get_referral(env.clone(), party) {
+                Some(referral) if now < referral.expires_at => referral,
+                _ => continue,
+            };
+            let commission = (fee * config.commission_bps as i128 / 10000).min(fee - total);
+            if commission <= 0 {
+                continue;
+            }
+            total += commission;
+
+            let key = DataKey::Feature(FeatureKey::ReferralBalance(
+                referral.referrer.clone(),
+                token.clone(),
+            ));
+            let balance: i128 = record_get(env, &key).unwrap_or(0);
+            record_set(env, &key, &(balance + commission));
+            let mut analytics =
+                PaymentContract::get_referrer_analytics(env.clone(), referral.referrer.clone());
+            analytics.commissioned_payments += 1;
+            analytics.total_earned += commission;
+            record_set(
+                env,
+                &DataKey::Feature(FeatureKey::ReferrerAnalytics(referral.referrer.clone())),
+                &analytics,
+            );
+
+            (ReferralCommissionAccrued {
+                payment_id,
+                referrer: referral.referrer,
+                token: token.clone(),
+                amount: commission,
+            })
+            .publish(env);
+        }
+        total
+    }
+
     // ── BATCH PAYMENT OPERATIONS ──────────────────────────────────────────────
 
     fn validate_batch_size(len: u32) -> Result<(), Error> {
@@ -8576,49 +12910,52 @@ impl PaymentContract {
                 refunded_amount: 0,
             };
 
-            env.storage()
-                .instance()
-                .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+            record_set(
+                &env,
+                &DataKey::Payment(PaymentKey::Data(payment_id)),
+                &payment,
+            );
+            PaymentContract::mark_completed(&env, payment_id);
             env.storage()
                 .instance()
                 .set(&DataKey::Payment(PaymentKey::Counter), &payment_id);
 
             // Index by customer
-            let customer_count: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::Customer(CustomerDataKey::PaymentCount(
-                    entry.customer.clone(),
-                )))
-                .unwrap_or(0);
-            env.storage().instance().set(
+            let customer_count: u64 = record_get(
+                &env,
+                &DataKey::Customer(CustomerDataKey::PaymentCount(entry.customer.clone())),
+            )
+            .unwrap_or(0);
+            record_set(
+                &env,
                 &DataKey::Customer(CustomerDataKey::Payments(
                     entry.customer.clone(),
                     customer_count,
                 )),
                 &payment_id,
             );
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Customer(CustomerDataKey::PaymentCount(entry.customer.clone())),
                 &(customer_count + 1),
             );
 
             // Index by merchant
-            let merchant_count: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::Merchant(MerchantDataKey::PaymentCount(
-                    entry.merchant.clone(),
-                )))
-                .unwrap_or(0);
-            env.storage().instance().set(
+            let merchant_count: u64 = record_get(
+                &env,
+                &DataKey::Merchant(MerchantDataKey::PaymentCount(entry.merchant.clone())),
+            )
+            .unwrap_or(0);
+            record_set(
+                &env,
                 &DataKey::Merchant(MerchantDataKey::Payments(
                     entry.merchant.clone(),
                     merchant_count,
                 )),
                 &payment_id,
             );
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Merchant(MerchantDataKey::PaymentCount(entry.merchant.clone())),
                 &(merchant_count + 1),
             );
@@ -8627,16 +12964,16 @@ impl PaymentContract {
             {
                 const PAGE_SIZE: u64 = 100;
                 let page_num = merchant_count / PAGE_SIZE;
-                let mut page: Vec<u64> = env
-                    .storage()
-                    .instance()
-                    .get(&DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
+                let mut page: Vec<u64> = record_get(
+                    &env,
+                    &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
                         entry.merchant.clone(),
                         page_num,
                     )))
                     .unwrap_or_else(|| Vec::new(&env));
                 page.push_back(payment_id);
-                env.storage().instance().set(
+                record_set(
+                    &env,
                     &DataKey::Merchant(MerchantDataKey::MerchantPaymentsPage(
                         entry.merchant.clone(),
                         page_num,
@@ -8825,7 +13162,8 @@ impl PaymentContract {
             evaluated_at: None,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
             &conditional_payment,
         );
@@ -8863,13 +13201,11 @@ impl PaymentContract {
     /// Returns an error if the conditional payment is not found or if
     /// oracle/cross-contract evaluation fails.
     pub fn evaluate_condition(env: Env, payment_id: u64) -> Result<bool, Error> {
-        let mut conditional_payment: ConditionalPayment = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ConditionalPayment(
-                payment_id,
-            )))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        let mut conditional_payment: ConditionalPayment = record_get(
+            &env,
+            &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
 
         // Return cached result if already evaluated
         if let Some(_evaluated_at) = conditional_payment.evaluated_at {
@@ -8902,7 +13238,8 @@ impl PaymentContract {
         conditional_payment.condition_met = condition_met;
         conditional_payment.evaluated_at = Some(current_timestamp);
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
             &conditional_payment,
         );
@@ -8949,22 +13286,15 @@ impl PaymentContract {
         }
 
         // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
 
         // Check if conditional payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::State(StateDataKey::ConditionalPayment(
-                payment_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
+        ) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
 
@@ -9006,13 +13336,10 @@ impl PaymentContract {
     /// Returns an error if the payment is not found, not in pending status,
     /// or the condition is not met.
     pub fn execute_if_condition_met(env: Env, payment_id: u64) -> Result<(), Error> {
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::State(StateDataKey::ConditionalPayment(
-                payment_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
+        ) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
         let payment = PaymentContract::get_payment(&env, payment_id);
@@ -9041,12 +13368,11 @@ impl PaymentContract {
     /// # Errors
     /// Returns `PaymentError::NotFound` if no conditional payment exists.
     pub fn get_conditional_payment(env: Env, payment_id: u64) -> Result<ConditionalPayment, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::ConditionalPayment(
-                payment_id,
-            )))
-            .ok_or(Error::Payment(PaymentError::NotFound))
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::ConditionalPayment(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))
     }
 
     // ── ANALYTICS FUNCTIONS ────────────────────────────────────────────────
@@ -9133,24 +13459,18 @@ impl PaymentContract {
         customer: Address,
         limit: u32,
     ) -> Vec<(Address, i128)> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::MerchantCount(
-                customer.clone(),
-            )))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::MerchantCount(customer.clone())),
+        )
+        .unwrap_or(0);
 
         let mut pairs: Vec<(Address, i128)> = Vec::new(&env);
         for i in 0..count {
-            if let Some(merchant) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, Address>(&DataKey::Customer(CustomerDataKey::MerchantList(
-                        customer.clone(),
-                        i,
-                    )))
-            {
+            if let Some(merchant) = record_get::<DataKey, Address>(
+                &env,
+                &DataKey::Customer(CustomerDataKey::MerchantList(customer.clone(), i)),
+            ) {
                 let vol: i128 = env
                     .storage()
                     .instance()
@@ -9287,11 +13607,10 @@ impl PaymentContract {
             .unwrap_or(0);
         let mut pairs: Vec<(Address, i128)> = Vec::new(&env);
         for i in 0..count {
-            if let Some(merchant) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Address>(&DataKey::Merchant(MerchantDataKey::GlobalList(i)))
-            {
+            if let Some(merchant) = record_get::<DataKey, Address>(
+                &env,
+                &DataKey::Merchant(MerchantDataKey::GlobalList(i)),
+            ) {
                 let analytics =
                     PaymentContract::get_merchant_analytics(env.clone(), merchant.clone());
                 pairs.push_back((merchant, analytics.total_volume));
@@ -9425,13 +13744,22 @@ impl PaymentContract {
     /// # Arguments
     /// * `admin` - Admin address (must be a multi-sig admin).
     /// * `reason` - Human-readable reason for pausing.
+    /// * `duration_seconds` - How long the pause lasts before it lifts on its
+    ///   own; `None` pauses until `unpause_contract` is called. Re-pausing a
+    ///   paused contract keeps the later expiry, so an indefinite pause stays
+    ///   indefinite.
     ///
     /// # Returns
     /// `Ok(())` on success.
     ///
     /// # Errors
     /// Returns an error if the caller is not an authorized admin.
-    pub fn pause_contract(env: Env, admin: Address, reason: String) -> Result<(), Error> {
+    pub fn pause_contract(
+        env: Env,
+        admin: Address,
+        reason: String,
+        duration_seconds: Option<u64>,
+    ) -> Result<(), Error> {
         admin.require_auth();
         let config: MultiSigConfig = env
             .storage()
@@ -9442,6 +13770,12 @@ impl PaymentContract {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
         let now = env.ledger().timestamp();
+        let already_paused = env
+            .storage()
+            .instance()
+            .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
+            .is_some_and(|state| state.globally_paused)
+            && !Self::global_pause_expired(&env);
         let pause_state = if let Some(mut state) = env
             .storage()
             .instance()
@@ -9464,6 +13798,12 @@ impl PaymentContract {
         env.storage()
             .instance()
             .set(&DataKey::Config(ConfigKey::PauseStateKey), &pause_state);
+        let paused_until = Self::merged_paused_until(
+            &env,
+            already_paused,
+            duration_seconds.map(|duration| now.saturating_add(duration)),
+        );
+        Self::set_paused_until(&env, paused_until);
         let history_count: u64 = env
             .storage()
             .instance()
@@ -9489,6 +13829,7 @@ impl PaymentContract {
             paused_by: admin,
             reason,
             paused_at: now,
+            paused_until,
         })
         .publish(&env);
         Ok(())
@@ -9527,6 +13868,7 @@ impl PaymentContract {
                 .instance()
                 .set(&DataKey::Config(ConfigKey::PauseStateKey), &state);
         }
+        Self::set_paused_until(&env, None);
         let now = env.ledger().timestamp();
         let history_count: u64 = env
             .storage()
@@ -9753,7 +14095,8 @@ impl PaymentContract {
     /// A `PauseState` struct indicating whether the contract is globally paused,
     /// which individual functions are paused, and who/when it was paused.
     pub fn get_pause_state(env: Env) -> PauseState {
-        env.storage()
+        let mut state = env
+            .storage()
             .instance()
             .get(&DataKey::Config(ConfigKey::PauseStateKey))
             .unwrap_or(PauseState {
@@ -9762,7 +14105,24 @@ impl PaymentContract {
                 paused_at: 0,
                 paused_by: env.current_contract_address(),
                 pause_reason: String::from_str(&env, ""),
-            })
+            });
+        if state.globally_paused && Self::global_pause_expired(&env) {
+            state.globally_paused = false;
+        }
+        state
+    }
+
+    /// Returns the timestamp at which the current global pause lifts on its own.
+    ///
+    /// # Returns
+    /// `None` if the contract is not paused or was paused without a duration.
+    pub fn get_paused_until(env: Env) -> Option<u64> {
+        if Self::global_pause_expired(&env) {
+            return None;
+        }
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::PausedUntil))
     }
 
     /// Checks whether a specific function is currently paused.
@@ -9781,7 +14141,7 @@ impl PaymentContract {
             .instance()
             .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
         {
-            if state.globally_paused {
+            if state.globally_paused && !Self::global_pause_expired(&env) {
                 return true;
             }
             for fn_name in state.paused_functions.iter() {
@@ -9925,27 +14285,21 @@ impl PaymentContract {
     ///
     /// # Errors
     /// Returns an error if the payment is not found, escrow was already triggered,
-    /// the rule is not found/inactive, or token/amount requirements are not met.
+    /// the rule is not found/inactive, token/amount requirements are not met, or the
+    /// escrow contract cannot pull the escrow amount from the customer.
     pub fn trigger_auto_escrow(env: &Env, payment_id: u64) -> Result<(), Error> {
         // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
+        if !record_has(env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
 
         let payment = PaymentContract::get_payment(env, payment_id);
 
         // Check if auto-escrow already triggered for this payment
-        if env
-            .storage()
-            .instance()
-            .has(&DataKey::State(StateDataKey::AutoEscrowTriggered(
-                payment_id,
-            )))
-        {
+        if record_has(
+            env,
+            &DataKey::State(StateDataKey::AutoEscrowTriggered(payment_id)),
+        ) {
             return Err(Error::Feature(FeatureError::AutoEscrowAlreadyTriggered));
         }
 
@@ -9982,7 +14336,7 @@ impl PaymentContract {
         let escrow_client = EscrowContractClient::new(&env, &rule.escrow_contract);
         let release_timestamp = env.ledger().timestamp() + 86400 * 30; // 30 days
         let expiry_timestamp = release_timestamp + 86400 * 7;
-        let escrow_id = escrow_client.create_escrow(
+        let escrow_id = match escrow_client.try_create_escrow(
             &payment.customer,
             &payment.merchant,
             &escrow_amount,
@@ -9991,10 +14345,14 @@ impl PaymentContract {
             &0u64, // min_hold_period
             &expiry_timestamp,
             &true,
-        );
+        ) {
+            Ok(Ok(escrow_id)) => escrow_id,
+            _ => return Err(Error::Feature(FeatureError::EscrowBridgeFailed)),
+        };
 
         // Mark escrow as triggered for this payment
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::State(StateDataKey::AutoEscrowTriggered(payment_id)),
             &escrow_id,
         );
@@ -10012,6 +14370,98 @@ impl PaymentContract {
         Ok(())
     }
 
+    fn require_merchant_not_paused(env: &Env, merchant: &Address) -> Result<(), Error> {
+        let paused: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::Merchant(MerchantDataKey::MerchantPaused(
+                merchant.clone(),
+            )))
+            .unwrap_or(false);
+        if paused {
+            return Err(Error::Subscription(SubscriptionError::MerchantPaused));
+        }
+        Ok(())
+    }
+
+    fn set_paused_until(env: &Env, paused_until: Option<u64>) {
+        match paused_until {
+            Some(timestamp) => env
+                .storage()
+                .instance()
+                .set(&DataKey::Config(ConfigKey::PausedUntil), &timestamp),
+            None => env
+                .storage()
+                .instance()
+                .remove(&DataKey::Config(ConfigKey::PausedUntil)),
+        }
+    }
+
+    /// Returns the expiry of a global pause requested while the contract may
+    /// already be paused. A live pause keeps the later of the two expiries, and
+    /// an indefinite pause (`None`) outlasts any timed one.
+    fn merged_paused_until(env: &Env, already_paused: bool, requested: Option<u64>) -> Option<u64> {
+        if !already_paused {
+            return requested;
+        }
+        match (
+            env.storage()
+                .instance()
+                .get::<DataKey, u64>(&DataKey::Config(ConfigKey::PausedUntil)),
+            requested,
+        ) {
+            (Some(current), Some(requested)) => Some(current.max(requested)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` once a timed global pause has reached its `paused_until` time.
+    fn global_pause_expired(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get::<DataKey, u64>(&DataKey::Config(ConfigKey::PausedUntil))
+            .is_some_and(|paused_until| env.ledger().timestamp() >= paused_until)
+    }
+
+    /// Persists the unpause of a global pause whose duration has run out. Called
+    /// lazily from `require_not_paused`; the contract itself is recorded as the
+    /// unpauser.
+    fn lift_expired_pause(env: &Env, mut state: PauseState) {
+        let now = env.ledger().timestamp();
+        let contract = env.current_contract_address();
+        state.globally_paused = false;
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::PauseStateKey), &state);
+        Self::set_paused_until(env, None);
+        let history_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::PauseHistoryCount))
+            .unwrap_or(0);
+        let entry = PauseHistory {
+            index: history_count,
+            function_name: String::from_str(env, "global"),
+            paused: false,
+            changed_by: contract.clone(),
+            changed_at: now,
+            reason: String::from_str(env, "pause duration elapsed"),
+        };
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::PauseHistoryEntry(history_count)),
+            &entry,
+        );
+        env.storage().instance().set(
+            &DataKey::State(StateDataKey::PauseHistoryCount),
+            &(history_count + 1),
+        );
+        (ContractUnpausedEvent {
+            unpaused_by: contract,
+            unpaused_at: now,
+        })
+        .publish(env);
+    }
+
     fn require_not_paused(env: &Env, function_name: &str) -> Result<(), Error> {
         if let Some(state) = env
             .storage()
@@ -10019,7 +14469,10 @@ impl PaymentContract {
             .get::<DataKey, PauseState>(&DataKey::Config(ConfigKey::PauseStateKey))
         {
             if state.globally_paused {
-                return Err(Error::Basic(BasicError::ContractPaused));
+                if !Self::global_pause_expired(env) {
+                    return Err(Error::Basic(BasicError::ContractPaused));
+                }
+                Self::lift_expired_pause(env, state.clone());
             }
             let fn_str = String::from_str(env, function_name);
             for fn_name in state.paused_functions.iter() {
@@ -10110,10 +14563,7 @@ impl PaymentContract {
         merchant.require_auth();
 
         // Verify payment exists and belongs to merchant
-        let payment: Payment = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Data(payment_id)))
+        let payment: Payment = record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)))
             .ok_or(Error::Payment(PaymentError::NotFound))?;
 
         if payment.merchant != merchant {
@@ -10127,13 +14577,11 @@ impl PaymentContract {
         }
 
         // Check if proposal already exists
-        if env
-            .storage()
-            .instance()
-            .get::<DataKey, LargePaymentProposal>(&DataKey::State(
-                StateDataKey::LargePaymentProposal(payment_id),
-            ))
-            .is_some()
+        if record_get::<DataKey, LargePaymentProposal>(
+            &env,
+            &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+        )
+        .is_some()
         {
             return Err(Error::Payment(PaymentError::AlreadyProcessed));
         }
@@ -10159,7 +14607,8 @@ impl PaymentContract {
             executed: false,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
             &proposal,
         );
@@ -10207,13 +14656,11 @@ impl PaymentContract {
             return Err(Error::Basic(BasicError::NotAnAdmin));
         }
 
-        let mut proposal: LargePaymentProposal = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::LargePaymentProposal(
-                payment_id,
-            )))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        let mut proposal: LargePaymentProposal = record_get(
+            &env,
+            &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
 
         if proposal.executed {
             return Err(Error::Payment(PaymentError::AlreadyProcessed));
@@ -10229,7 +14676,8 @@ impl PaymentContract {
 
         proposal.approvals.push_back(approver.clone());
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
             &proposal,
         );
@@ -10266,13 +14714,11 @@ impl PaymentContract {
             .get(&DataKey::Config(ConfigKey::MultiSigConfig))
             .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
 
-        let mut proposal: LargePaymentProposal = env
-            .storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::LargePaymentProposal(
-                payment_id,
-            )))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        let mut proposal: LargePaymentProposal = record_get(
+            &env,
+            &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
 
         if proposal.executed {
             return Err(Error::Payment(PaymentError::AlreadyProcessed));
@@ -10287,11 +14733,9 @@ impl PaymentContract {
         }
 
         // Get the payment and execute it
-        let mut payment: Payment = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Data(payment_id)))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        let mut payment: Payment =
+            record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)))
+                .ok_or(Error::Payment(PaymentError::NotFound))?;
 
         if payment.status != PaymentStatus::Pending {
             return Err(Error::Payment(PaymentError::InvalidStatus));
@@ -10308,12 +14752,16 @@ impl PaymentContract {
         );
 
         payment.status = PaymentStatus::Completed;
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+        Self::mark_completed(&env, payment_id);
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
 
         proposal.executed = true;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
             &proposal,
         );
@@ -10341,12 +14789,11 @@ impl PaymentContract {
     /// # Panics
     /// Panics if no proposal exists for the given payment ID.
     pub fn get_large_payment_proposal(env: Env, payment_id: u64) -> LargePaymentProposal {
-        env.storage()
-            .instance()
-            .get(&DataKey::State(StateDataKey::LargePaymentProposal(
-                payment_id,
-            )))
-            .expect("Large payment proposal not found")
+        record_get(
+            &env,
+            &DataKey::State(StateDataKey::LargePaymentProposal(payment_id)),
+        )
+        .expect("Large payment proposal not found")
     }
 
     /// Set payment metadata with encrypted content reference
@@ -10363,11 +14810,7 @@ impl PaymentContract {
         caller.require_auth();
 
         // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
 
@@ -10384,10 +14827,8 @@ impl PaymentContract {
         }
 
         // Check if metadata already exists
-        let existing_metadata: Option<PaymentMetadata> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Metadata(payment_id)));
+        let existing_metadata: Option<PaymentMetadata> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Metadata(payment_id)));
 
         if let Some(existing) = existing_metadata {
             // Metadata already set - emit update event with new version
@@ -10401,7 +14842,8 @@ impl PaymentContract {
                 version: new_version,
             };
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Payment(PaymentKey::Metadata(payment_id)),
                 &updated_metadata,
             );
@@ -10424,7 +14866,8 @@ impl PaymentContract {
                 version: 1,
             };
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Payment(PaymentKey::Metadata(payment_id)),
                 &metadata,
             );
@@ -10443,9 +14886,7 @@ impl PaymentContract {
 
     /// Get payment metadata
     pub fn get_payment_metadata(env: Env, payment_id: u64) -> Option<PaymentMetadata> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Metadata(payment_id)))
+        record_get(&env, &DataKey::Payment(PaymentKey::Metadata(payment_id)))
     }
 
     /// Verify metadata integrity by comparing provided hash against stored hash
@@ -10455,10 +14896,8 @@ impl PaymentContract {
         payment_id: u64,
         plaintext_hash: BytesN<32>,
     ) -> bool {
-        let metadata: Option<PaymentMetadata> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Metadata(payment_id)));
+        let metadata: Option<PaymentMetadata> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Metadata(payment_id)));
 
         match metadata {
             Some(meta) => meta.content_hash == plaintext_hash,
@@ -10478,11 +14917,7 @@ impl PaymentContract {
         caller.require_auth();
 
         // Check if payment exists
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Payment(PaymentKey::Data(payment_id)))
-        {
+        if !record_has(&env, &DataKey::Payment(PaymentKey::Data(payment_id))) {
             return Err(Error::Payment(PaymentError::NotFound));
         }
 
@@ -10499,20 +14934,16 @@ impl PaymentContract {
         }
 
         // Check if memo already exists
-        let existing_memo: Option<PaymentMemo> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Memo(payment_id)));
+        let existing_memo: Option<PaymentMemo> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Memo(payment_id)));
 
         let current_time = env.ledger().timestamp();
 
         if let Some(existing) = existing_memo {
             // Archive current version into sliding-window history (capped at MAX_MEMO_VERSIONS)
-            let mut history: Vec<PaymentMemo> = env
-                .storage()
-                .instance()
-                .get(&DataKey::Payment(PaymentKey::MemoVersion(payment_id)))
-                .unwrap_or_else(|| Vec::new(&env));
+            let mut history: Vec<PaymentMemo> =
+                record_get(&env, &DataKey::Payment(PaymentKey::MemoVersion(payment_id)))
+                    .unwrap_or_else(|| Vec::new(&env));
             if history.len() >= MAX_MEMO_VERSIONS {
                 // Drop oldest entry by rebuilding without index 0
                 let mut trimmed = Vec::new(&env);
@@ -10522,7 +14953,8 @@ impl PaymentContract {
                 history = trimmed;
             }
             history.push_back(existing.clone());
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Payment(PaymentKey::MemoVersion(payment_id)),
                 &history,
             );
@@ -10548,7 +14980,8 @@ impl PaymentContract {
                 created_by: existing.created_by,
             };
 
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Payment(PaymentKey::Memo(payment_id)),
                 &updated_memo,
             );
@@ -10580,9 +15013,7 @@ impl PaymentContract {
                 created_by: caller.clone(),
             };
 
-            env.storage()
-                .instance()
-                .set(&DataKey::Payment(PaymentKey::Memo(payment_id)), &memo);
+            record_set(&env, &DataKey::Payment(PaymentKey::Memo(payment_id)), &memo);
 
             PaymentMemoSet {
                 payment_id,
@@ -10597,18 +15028,14 @@ impl PaymentContract {
 
     /// Get payment memo
     pub fn get_payment_memo(env: Env, payment_id: u64) -> Option<PaymentMemo> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Memo(payment_id)))
+        record_get(&env, &DataKey::Payment(PaymentKey::Memo(payment_id)))
     }
 
     /// Verify memo integrity by comparing provided hash against stored memo hash
     /// Returns true if hashes match, false otherwise
     pub fn verify_memo_integrity(env: Env, payment_id: u64, plaintext_hash: BytesN<32>) -> bool {
-        let memo: Option<PaymentMemo> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Memo(payment_id)));
+        let memo: Option<PaymentMemo> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Memo(payment_id)));
 
         match memo {
             Some(m) => m.memo_hash == plaintext_hash,
@@ -10622,11 +15049,9 @@ impl PaymentContract {
         env: Env,
         payment_id: u64,
         expected_reference_hash: BytesN<32>,
-    ) -> bool {
-        let memo: Option<PaymentMemo> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Memo(payment_id)));
+    ) -> bool {
+        let memo: Option<PaymentMemo> =
+            record_get(&env, &DataKey::Payment(PaymentKey::Memo(payment_id)));
 
         match memo {
             Some(m) => m.reference_hash == expected_reference_hash,
@@ -10674,11 +15099,11 @@ impl PaymentContract {
         }
 
         // New customer risk (simplified - in production would check payment history)
-        let customer_payment_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::PaymentCount(customer)))
-            .unwrap_or(0);
+        let customer_payment_count: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer)),
+        )
+        .unwrap_or(0);
         if customer_payment_count < 3 {
             risk_score += config.new_customer_surcharge_bps;
         }
@@ -10794,30 +15219,26 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-        {
+        if !record_has(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        ) {
             return Err(Error::Subscription(SubscriptionError::NotFound));
         }
 
-        let mut sub: Subscription = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Data(
-                subscription_id,
-            )))
-            .unwrap();
+        let mut sub: Subscription = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
+        )
+        .unwrap();
 
         if sub.customer != customer {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
 
         sub.pause_data.proration_enabled = enabled;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Data(subscription_id)),
             &sub,
         );
@@ -10839,7 +15260,7 @@ impl PaymentContract {
     /// * `token` - The token address for the channel.
     /// * `amount` - Amount to deposit into the channel.
     /// * `expires_at` - Ledger timestamp when the channel expires (0 for no expiry).
-    /// * `customer_pk` - Customer's Ed25519 public key for signature verification.
+    /// * `customer_key` - Key the customer signs channel states with.
     ///
     /// # Returns
     /// The channel ID on success.
@@ -10854,7 +15275,7 @@ impl PaymentContract {
         token: Address,
         amount: i128,
         expires_at: u64,
-        customer_pk: BytesN<32>,
+        customer_key: SignerKey,
     ) -> Result<u64, Error> {
         customer.require_auth();
         if amount <= 0 {
@@ -10887,10 +15308,11 @@ impl PaymentContract {
             settled_nonce: 0,
             open: true,
             expires_at,
-            customer_pk,
+            customer_key,
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::PaymentChannel(channel_id)),
             &channel,
         );
@@ -10939,10 +15361,7 @@ impl PaymentContract {
             return Err(Error::Basic(BasicError::InvalidAmount));
         }
 
-        let mut channel: PaymentChannel = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentChannel(channel_id)))
+        let mut channel = Self::load_payment_channel(&env, channel_id)
             .ok_or(Error::Feature(FeatureError::ChannelNotFound))?;
 
         if channel.customer != customer {
@@ -10963,7 +15382,8 @@ impl PaymentContract {
 
         channel.deposited += amount;
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::PaymentChannel(channel_id)),
             &channel,
         );
@@ -10988,7 +15408,7 @@ impl PaymentContract {
     /// * `channel_id` - The ID of the channel to settle.
     /// * `merchant_amount` - Amount to send to the merchant.
     /// * `nonce` - Strictly increasing nonce to prevent replay attacks.
-    /// * `signature` - Ed25519 signature from the customer.
+    /// * `signature` - The customer's signature, of the type of their `customer_key`.
     ///
     /// # Returns
     /// `Ok(())` on success.
@@ -11001,12 +15421,9 @@ impl PaymentContract {
         channel_id: u64,
         merchant_amount: i128,
         nonce: u64,
-        signature: BytesN<64>,
+        signature: StateSignature,
     ) -> Result<(), Error> {
-        let mut channel: PaymentChannel = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentChannel(channel_id)))
+        let mut channel = Self::load_payment_channel(&env, channel_id)
             .ok_or(Error::Feature(FeatureError::ChannelNotFound))?;
 
         Self::require_merchant_not_paused(&env, &channel.merchant)?;
@@ -11034,8 +15451,7 @@ impl PaymentContract {
         msg.append(&merchant_amount.to_xdr(&env));
         msg.append(&nonce.to_xdr(&env));
 
-        env.crypto()
-            .ed25519_verify(&channel.customer_pk, &msg, &signature);
+        Self::verify_state_signature(&env, &channel.customer_key, &msg, &signature)?;
 
         let customer_refund = channel.deposited - merchant_amount;
 
@@ -11063,7 +15479,8 @@ impl PaymentContract {
         channel.settled_nonce = nonce;
         channel.open = false;
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::PaymentChannel(channel_id)),
             &channel,
         );
@@ -11096,10 +15513,7 @@ impl PaymentContract {
     pub fn close_channel_expired(env: Env, caller: Address, channel_id: u64) -> Result<(), Error> {
         caller.require_auth();
 
-        let mut channel: PaymentChannel = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentChannel(channel_id)))
+        let mut channel = Self::load_payment_channel(&env, channel_id)
             .ok_or(Error::Feature(FeatureError::ChannelNotFound))?;
 
         if caller != channel.customer && caller != channel.merchant {
@@ -11123,35 +15537,477 @@ impl PaymentContract {
         }
 
         channel.open = false;
-        env.storage().instance().set(
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::PaymentChannel(channel_id)),
+            &channel,
+        );
+
+        (ChannelExpiredClosed {
+            channel_id,
+            refunded_to: channel.customer.clone(),
+        })
+        .publish(&env);
+
+        Ok(())
+    }
+
+    /// Retrieves a payment channel by its ID.
+    ///
+    /// # Arguments
+    /// * `channel_id` - The channel ID.
+    ///
+    /// # Returns
+    /// The `PaymentChannel` record.
+    ///
+    /// # Errors
+    /// Returns an error if the channel is not found.
+    pub fn get_channel(env: Env, channel_id: u64) -> Result<PaymentChannel, Error> {
+        Self::load_payment_channel(&env, channel_id)
+            .ok_or(Error::Feature(FeatureError::ChannelNotFound))
+    }
+
+    /// Loads a payment channel, upgrading records stored before channels carried a
+    /// `SignerKey`: their `customer_pk` is an ed25519 key. The upgraded shape is
+    /// persisted by the next write.
+    fn load_payment_channel(env: &Env, channel_id: u64) -> Option<PaymentChannel> {
+        let mut fields: Map<Symbol, Val> = record_get(
+            env,
             &DataKey::Feature(FeatureKey::PaymentChannel(channel_id)),
+        )?;
+        let legacy_key = Symbol::new(env, "customer_pk");
+        if let Some(customer_pk) = fields.get(legacy_key.clone()) {
+            let customer_pk = BytesN::<32>::from_val(env, &customer_pk);
+            fields.remove(legacy_key);
+            fields.set(
+                Symbol::new(env, "customer_key"),
+                SignerKey::Ed25519(customer_pk).into_val(env),
+            );
+        }
+        Some(PaymentChannel::from_val(env, &fields.to_val()))
+    }
+
+    /// Opens a bidirectional payment channel funded by both parties.
+    ///
+    /// Unlike `open_channel`, either side can pay the other: every off-chain
+    /// `ChannelState` carries both balances and is signed by both parties. The
+    /// channel closes instantly with `close_channel_mutual`, or through
+    /// `close_channel_unilateral` followed by a challenge period.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer (must authorize).
+    /// * `merchant` - The merchant (must authorize).
+    /// * `token` - The token address for the channel.
+    /// * `customer_deposit` - Amount deposited by the customer.
+    /// * `merchant_deposit` - Amount deposited by the merchant, e.g. to cover cashback.
+    /// * `customer_key` - Key the customer signs channel states with.
+    /// * `merchant_key` - Key the merchant signs channel states with.
+    /// * `challenge_period` - Seconds a unilateral close stays open to challenges.
+    ///
+    /// # Returns
+    /// The channel ID on success.
+    ///
+    /// # Errors
+    /// Returns an error if a deposit is negative or both are zero, the merchant is
+    /// the zero address or paused, or a token transfer fails.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_bidirectional_channel(
+        env: Env,
+        customer: Address,
+        merchant: Address,
+        token: Address,
+        customer_deposit: i128,
+        merchant_deposit: i128,
+        customer_key: SignerKey,
+        merchant_key: SignerKey,
+        challenge_period: u64,
+    ) -> Result<u64, Error> {
+        customer.require_auth();
+        merchant.require_auth();
+        if customer_deposit < 0 || merchant_deposit < 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let total = customer_deposit
+            .checked_add(merchant_deposit)
+            .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+        if total == 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if Self::is_zero_address(&env, &merchant) || customer == merchant {
+            return Err(Error::Feature(FeatureError::InvalidCounterparty));
+        }
+        Self::require_merchant_not_paused(&env, &merchant)?;
+
+        let token_client = token::Client::new(&env, &token);
+        let contract_address = env.current_contract_address();
+        if customer_deposit > 0 {
+            token_client.transfer(&customer, &contract_address, &customer_deposit);
+        }
+        if merchant_deposit > 0 {
+            token_client.transfer(&merchant, &contract_address, &merchant_deposit);
+        }
+
+        let channel_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::BidirectionalChannelCounter))
+            .unwrap_or(0)
+            + 1;
+        let channel = BidirectionalChannel {
+            channel_id,
+            customer: customer.clone(),
+            merchant: merchant.clone(),
+            token,
+            customer_key,
+            merchant_key,
+            customer_deposit,
+            merchant_deposit,
+            challenge_period,
+            status: BidirectionalChannelStatus::Open,
+            nonce: 0,
+            customer_balance: customer_deposit,
+            merchant_balance: merchant_deposit,
+            close_deadline: 0,
+        };
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::BidirectionalChannel(channel_id)),
+            &channel,
+        );
+        env.storage().instance().set(
+            &DataKey::Feature(FeatureKey::BidirectionalChannelCounter),
+            &channel_id,
+        );
+
+        (BidirectionalChannelOpened {
+            channel_id,
+            customer,
+            merchant,
+            customer_deposit,
+            merchant_deposit,
+        })
+        .publish(&env);
+
+        Ok(channel_id)
+    }
+
+    /// Cooperatively closes a bidirectional channel and pays out both balances
+    /// immediately.
+    ///
+    /// Can be called by anyone holding a state that both parties signed with
+    /// `is_final` set, so neither party can close on an older, intermediate
+    /// state. It also ends a pending unilateral close early, as long as the
+    /// state is not older than the one already submitted.
+    ///
+    /// # Arguments
+    /// * `channel_id` - The ID of the channel to close.
+    /// * `signed_state` - The final state with both signatures.
+    ///
+    /// # Errors
+    /// Returns an error if the channel is not found or closed, the state is not
+    /// marked final, is for another channel or older than the on-chain state,
+    /// the balances do not add up to the deposits, or a signature is invalid.
+    pub fn close_channel_mutual(
+        env: Env,
+        channel_id: u64,
+        signed_state: SignedChannelState,
+    ) -> Result<(), Error> {
+        let mut channel = Self::get_bidirectional_channel(env.clone(), channel_id)?;
+        if channel.status == BidirectionalChannelStatus::Closed {
+            return Err(Error::Feature(FeatureError::ChannelClosed));
+        }
+        Self::verify_channel_state(&env, &channel, &signed_state)?;
+        if !signed_state.state.is_final {
+            return Err(Error::Feature(FeatureError::ChannelStateNotFinal));
+        }
+        if signed_state.state.nonce < channel.nonce {
+            return Err(Error::Feature(FeatureError::InvalidNonce));
+        }
+
+        Self::apply_channel_state(&mut channel, &signed_state.state);
+        Self::pay_out_bidirectional_channel(&env, &mut channel, true);
+        Ok(())
+    }
+
+    /// Starts a unilateral close of a bidirectional channel.
+    ///
+    /// The submitted state is held for the channel's challenge period, during
+    /// which either party can replace it with a newer signed state through
+    /// `challenge_channel_close`. `finalize_channel_close` pays out afterwards.
+    ///
+    /// # Arguments
+    /// * `caller` - The customer or merchant (must authorize).
+    /// * `channel_id` - The ID of the channel to close.
+    /// * `signed_state` - The latest state the caller holds, or `None` to close on
+    ///   the opening balances.
+    ///
+    /// # Errors
+    /// Returns an error if the caller is not a channel party, the channel is not
+    /// open, or the state is invalid or older than the on-chain state.
+    pub fn close_channel_unilateral(
+        env: Env,
+        caller: Address,
+        channel_id: u64,
+        signed_state: Option<SignedChannelState>,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        let mut channel = Self::get_bidirectional_channel(env.clone(), channel_id)?;
+        if caller != channel.customer && caller != channel.merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if channel.status != BidirectionalChannelStatus::Open {
+            return Err(Error::Feature(FeatureError::ChannelClosed));
+        }
+        if let Some(signed_state) = signed_state {
+            Self::verify_channel_state(&env, &channel, &signed_state)?;
+            if signed_state.state.nonce < channel.nonce {
+                return Err(Error::Feature(FeatureError::InvalidNonce));
+            }
+            Self::apply_channel_state(&mut channel, &signed_state.state);
+        }
+
+        channel.status = BidirectionalChannelStatus::Closing;
+        channel.close_deadline = env
+            .ledger()
+            .timestamp()
+            .saturating_add(channel.challenge_period);
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::BidirectionalChannel(channel_id)),
+            &channel,
+        );
+
+        (ChannelCloseInitiated {
+            channel_id,
+            initiated_by: caller,
+            nonce: channel.nonce,
+            close_deadline: channel.close_deadline,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Replaces the state of a closing bidirectional channel with a newer one.
+    ///
+    /// Permissionless: the two signatures prove both parties agreed to the state.
+    /// The challenge period is not extended.
+    ///
+    /// # Arguments
+    /// * `channel_id` - The ID of the closing channel.
+    /// * `signed_state` - A state with a higher nonce than the submitted one.
+    ///
+    /// # Errors
+    /// Returns an error if the channel is not closing, the challenge period is
+    /// over, or the state is invalid or not newer than the submitted one.
+    pub fn challenge_channel_close(
+        env: Env,
+        channel_id: u64,
+        signed_state: SignedChannelState,
+    ) -> Result<(), Error> {
+        let mut channel = Self::get_bidirectional_channel(env.clone(), channel_id)?;
+        if channel.status != BidirectionalChannelStatus::Closing {
+            return Err(Error::Feature(FeatureError::ChannelNotClosing));
+        }
+        if env.ledger().timestamp() >= channel.close_deadline {
+            return Err(Error::Feature(FeatureError::ChallengePeriodOver));
+        }
+        Self::verify_channel_state(&env, &channel, &signed_state)?;
+        if signed_state.state.nonce <= channel.nonce {
+            return Err(Error::Feature(FeatureError::InvalidNonce));
+        }
+
+        Self::apply_channel_state(&mut channel, &signed_state.state);
+        record_set(
+            &env,
+            &DataKey::Feature(FeatureKey::BidirectionalChannel(channel_id)),
             &channel,
         );
 
-        (ChannelExpiredClosed {
-            channel_id,
-            refunded_to: channel.customer.clone(),
+        (ChannelStateChallenged {
+            channel_id,
+            nonce: channel.nonce,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Pays out a unilaterally closed bidirectional channel once its challenge
+    /// period has passed. Permissionless.
+    ///
+    /// # Arguments
+    /// * `channel_id` - The ID of the closing channel.
+    ///
+    /// # Errors
+    /// Returns an error if the channel is not closing or the challenge period
+    /// has not ended.
+    pub fn finalize_channel_close(env: Env, channel_id: u64) -> Result<(), Error> {
+        let mut channel = Self::get_bidirectional_channel(env.clone(), channel_id)?;
+        if channel.status != BidirectionalChannelStatus::Closing {
+            return Err(Error::Feature(FeatureError::ChannelNotClosing));
+        }
+        if env.ledger().timestamp() < channel.close_deadline {
+            return Err(Error::Feature(FeatureError::ChallengePeriodActive));
+        }
+
+        Self::pay_out_bidirectional_channel(&env, &mut channel, false);
+        Ok(())
+    }
+
+    /// Retrieves a bidirectional payment channel by its ID.
+    ///
+    /// # Errors
+    /// Returns an error if the channel is not found.
+    pub fn get_bidirectional_channel(
+        env: Env,
+        channel_id: u64,
+    ) -> Result<BidirectionalChannel, Error> {
+        record_get(
+            &env,
+            &DataKey::Feature(FeatureKey::BidirectionalChannel(channel_id)),
+        )
+        .ok_or(Error::Feature(FeatureError::ChannelNotFound))
+    }
+
+    /// Checks that `signed_state` belongs to `channel`, conserves its deposits and
+    /// carries valid signatures from both parties. Panics on a bad signature.
+    fn verify_channel_state(
+        env: &Env,
+        channel: &BidirectionalChannel,
+        signed_state: &SignedChannelState,
+    ) -> Result<(), Error> {
+        let state = &signed_state.state;
+        if state.channel_id != channel.channel_id {
+            return Err(Error::Feature(FeatureError::InvalidSignature));
+        }
+        if state.customer_balance < 0 || state.merchant_balance < 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let total = channel.customer_deposit + channel.merchant_deposit;
+        if state.customer_balance.checked_add(state.merchant_balance) != Some(total) {
+            return Err(Error::Feature(FeatureError::BalanceSumMismatch));
+        }
+
+        let mut msg = Bytes::new(env);
+        msg.append(&env.current_contract_address().to_xdr(env));
+        msg.append(&state.clone().to_xdr(env));
+        Self::verify_state_signature(
+            env,
+            &channel.customer_key,
+            &msg,
+            &signed_state.customer_signature,
+        )?;
+        Self::verify_state_signature(
+            env,
+            &channel.merchant_key,
+            &msg,
+            &signed_state.merchant_signature,
+        )
+    }
+
+    /// Verifies `signature` over `msg` against `key`. Panics if the ed25519 or
+    /// secp256r1 signature itself is invalid.
+    fn verify_state_signature(
+        env: &Env,
+        key: &SignerKey,
+        msg: &Bytes,
+        signature: &StateSignature,
+    ) -> Result<(), Error> {
+        match (key, signature) {
+            (SignerKey::Ed25519(public_key), StateSignature::Ed25519(signature)) => {
+                env.crypto().ed25519_verify(public_key, msg, signature);
+                Ok(())
+            }
+            (SignerKey::Secp256r1(public_key), StateSignature::WebAuthn(assertion)) => {
+                Self::verify_webauthn_assertion(env, public_key, msg, assertion)
+            }
+            _ => Err(Error::Signature(SignatureError::KeyTypeMismatch)),
+        }
+    }
+
+    /// Verifies a passkey assertion the way a WebAuthn relying party does: the
+    /// authenticator must report user presence, `client_data_json` must be a
+    /// `webauthn.get` whose challenge is `base64url(sha256(msg))`, and the
+    /// signature must cover `authenticator_data || sha256(client_data_json)`.
+    fn verify_webauthn_assertion(
+        env: &Env,
+        public_key: &BytesN<65>,
+        msg: &Bytes,
+        assertion: &WebAuthnAssertion,
+    ) -> Result<(), Error> {
+        // authenticatorData is rpIdHash (32 bytes) || flags (1) || signCount (4) || ...
+        let authenticator_data = &assertion.authenticator_data;
+        if authenticator_data.len() < 37 || authenticator_data.get_unchecked(32) & 0x01 == 0 {
+            return Err(Error::Signature(SignatureError::InvalidWebAuthnAssertion));
+        }
+
+        let client_data_len = assertion.client_data_json.len();
+        if client_data_len > MAX_CLIENT_DATA_JSON_LEN {
+            return Err(Error::Signature(SignatureError::InvalidWebAuthnAssertion));
+        }
+        let mut buffer = [0u8; MAX_CLIENT_DATA_JSON_LEN as usize];
+        let client_data = &mut buffer[..client_data_len as usize];
+        assertion.client_data_json.copy_into_slice(client_data);
+        if !contains(client_data, b"\"type\":\"webauthn.get\"") {
+            return Err(Error::Signature(SignatureError::InvalidWebAuthnAssertion));
+        }
+
+        let mut challenge = [0u8; 57];
+        challenge[..13].copy_from_slice(b"\"challenge\":\"");
+        base64url_encode_digest(&env.crypto().sha256(msg).to_array(), &mut challenge[13..56]);
+        challenge[56] = b'"';
+        if !contains(client_data, &challenge) {
+            return Err(Error::Signature(SignatureError::ChallengeMismatch));
+        }
+
+        let mut signed_data = authenticator_data.clone();
+        signed_data.append(&env.crypto().sha256(&assertion.client_data_json).into());
+        env.crypto().secp256r1_verify(
+            public_key,
+            &env.crypto().sha256(&signed_data),
+            &assertion.signature,
+        );
+        Ok(())
+    }
+
+    fn apply_channel_state(channel: &mut BidirectionalChannel, state: &ChannelState) {
+        channel.nonce = state.nonce;
+        channel.customer_balance = state.customer_balance;
+        channel.merchant_balance = state.merchant_balance;
+    }
+
+    fn pay_out_bidirectional_channel(env: &Env, channel: &mut BidirectionalChannel, mutual: bool) {
+        let token_client = token::Client::new(env, &channel.token);
+        let contract_address = env.current_contract_address();
+        if channel.customer_balance > 0 {
+            token_client.transfer(
+                &contract_address,
+                &channel.customer,
+                &channel.customer_balance,
+            );
+        }
+        if channel.merchant_balance > 0 {
+            token_client.transfer(
+                &contract_address,
+                &channel.merchant,
+                &channel.merchant_balance,
+            );
+        }
+
+        channel.status = BidirectionalChannelStatus::Closed;
+        record_set(
+            env,
+            &DataKey::Feature(FeatureKey::BidirectionalChannel(channel.channel_id)),
+            &*channel,
+        );
+
+        (BidirectionalChannelClosed {
+            channel_id: channel.channel_id,
+            nonce: channel.nonce,
+            customer_amount: channel.customer_balance,
+            merchant_amount: channel.merchant_balance,
+            mutual,
         })
-        .publish(&env);
-
-        Ok(())
-    }
-
-    /// Retrieves a payment channel by its ID.
-    ///
-    /// # Arguments
-    /// * `channel_id` - The channel ID.
-    ///
-    /// # Returns
-    /// The `PaymentChannel` record.
-    ///
-    /// # Errors
-    /// Returns an error if the channel is not found.
-    pub fn get_channel(env: Env, channel_id: u64) -> Result<PaymentChannel, Error> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::PaymentChannel(channel_id)))
-            .ok_or(Error::Feature(FeatureError::ChannelNotFound))
+        .publish(env);
     }
 
     fn extract_public_key(env: &Env, address: &Address) -> BytesN<32> {
@@ -11269,43 +16125,45 @@ impl PaymentContract {
             refunded_amount: 0,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
         env.storage()
             .instance()
             .set(&DataKey::Payment(PaymentKey::Counter), &payment_id);
 
         // Index by customer
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Customer(CustomerDataKey::PaymentCount(
-                customer.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 = record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::PaymentCount(customer.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Customer(CustomerDataKey::Payments(customer.clone(), customer_count)),
             &payment_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Customer(CustomerDataKey::PaymentCount(customer.clone())),
             &(customer_count + 1),
         );
 
         // Index by merchant
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PaymentCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let merchant_count: u64 = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::Merchant(MerchantDataKey::Payments(merchant.clone(), merchant_count)),
             &payment_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Merchant(MerchantDataKey::PaymentCount(merchant.clone())),
             &(merchant_count + 1),
         );
@@ -11337,7 +16195,8 @@ impl PaymentContract {
                 .instance()
                 .get(&DataKey::Config(ConfigKey::GlobalMerchantCount))
                 .unwrap_or(0);
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Merchant(MerchantDataKey::GlobalList(global_count)),
                 &merchant,
             );
@@ -11417,21 +16276,18 @@ impl PaymentContract {
             &new_merchant_vol,
         );
         if prev_merchant_vol == 0 {
-            let m_count: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::Customer(CustomerDataKey::MerchantCount(
-                    customer.clone(),
-                )))
-                .unwrap_or(0);
-            env.storage().instance().set(
-                &DataKey::Customer(CustomerDataKey::MerchantList(
-                    customer.clone(),
-                    m_count,
-                )),
+            let m_count: u64 = record_get(
+                &env,
+                &DataKey::Customer(CustomerDataKey::MerchantCount(customer.clone())),
+            )
+            .unwrap_or(0);
+            record_set(
+                &env,
+                &DataKey::Customer(CustomerDataKey::MerchantList(customer.clone(), m_count)),
                 &merchant,
             );
-            env.storage().instance().set(
+            record_set(
+                &env,
                 &DataKey::Customer(CustomerDataKey::MerchantCount(customer.clone())),
                 &(m_count + 1),
             );
@@ -11450,7 +16306,8 @@ impl PaymentContract {
             recipients,
             executed: false,
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::SplitConfig(payment_id)),
             &config,
         );
@@ -11488,32 +16345,25 @@ impl PaymentContract {
     ) -> Result<(), Error> {
         admin.require_auth();
 
-        let mut config: PaymentSplitConfig = env
-            .storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::SplitConfig(payment_id)))
-            .ok_or(Error::Feature(FeatureError::SplitConfigNotFound))?;
+        let mut config: PaymentSplitConfig =
+            record_get(&env, &DataKey::Feature(FeatureKey::SplitConfig(payment_id)))
+                .ok_or(Error::Feature(FeatureError::SplitConfigNotFound))?;
 
         if config.executed {
             return Err(Error::Feature(FeatureError::SplitAlreadyExecuted));
         }
 
-        let payment: Payment = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::Data(payment_id)))
+        let payment: Payment = record_get(&env, &DataKey::Payment(PaymentKey::Data(payment_id)))
             .ok_or(Error::Payment(PaymentError::NotFound))?;
 
-        let token_client = token::Client::new(&env, &payment.token);
-        let contract_address = env.current_contract_address();
-
         for recipient in config.recipients.iter() {
             let share = (payment.amount * recipient.share_bps as i128) / 10000;
-            token_client.transfer(&contract_address, &recipient.address, &share);
+            Self::pay_out(&env, &recipient.address, &payment.token, share)?;
         }
 
         config.executed = true;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::SplitConfig(payment_id)),
             &config,
         );
@@ -11529,9 +16379,7 @@ impl PaymentContract {
     /// # Returns
     /// `Some(PaymentSplitConfig)` if a split was configured, `None` otherwise.
     pub fn get_split_config(env: Env, payment_id: u64) -> Option<PaymentSplitConfig> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Feature(FeatureKey::SplitConfig(payment_id)))
+        record_get(&env, &DataKey::Feature(FeatureKey::SplitConfig(payment_id)))
     }
 
     /// Sets the minimum amount per split recipient for split payments.
@@ -11571,6 +16419,384 @@ impl PaymentContract {
             .get(&DataKey::Config(ConfigKey::MinSplitAmount))
     }
 
+    // ── MARKETPLACE PLATFORMS ────────────────────────────────────────────────
+
+    /// Connects a sub-merchant to a marketplace platform, so the platform can
+    /// create payments on its behalf with `create_platform_payment`.
+    ///
+    /// # Arguments
+    /// * `platform` - The platform onboarding the merchant (must authorize)
+    /// * `sub_merchant` - The merchant being connected (must authorize)
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `AlreadyProcessed` if the sub-merchant is already
+    /// connected to a platform.
+    pub fn register_sub_merchant(
+        env: Env,
+        platform: Address,
+        sub_merchant: Address,
+    ) -> Result<(), Error> {
+        platform.require_auth();
+        sub_merchant.require_auth();
+        if platform == sub_merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        let key = DataKey::Merchant(MerchantDataKey::SubMerchant(sub_merchant.clone()));
+        if record_has(&env, &key) {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        }
+
+        record_set(
+            &env,
+            &key,
+            &SubMerchant {
+                platform: platform.clone(),
+                sub_merchant: sub_merchant.clone(),
+                registered_at: env.ledger().timestamp(),
+            },
+        );
+        let list_key = DataKey::Merchant(MerchantDataKey::PlatformSubMerchants(platform.clone()));
+        let mut sub_merchants: Vec<Address> =
+            record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        sub_merchants.push_back(sub_merchant.clone());
+        record_set(&env, &list_key, &sub_merchants);
+
+        (SubMerchantRegistered {
+            platform,
+            sub_merchant,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Disconnects a sub-merchant from its platform. Payments already created
+    /// still route their application fee to the platform.
+    ///
+    /// # Arguments
+    /// * `platform` - The sub-merchant's platform (must authorize)
+    /// * `sub_merchant` - The merchant to disconnect
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `SubMerchantNotFound` if it is not connected to `platform`.
+    pub fn remove_sub_merchant(
+        env: Env,
+        platform: Address,
+        sub_merchant: Address,
+    ) -> Result<(), Error> {
+        platform.require_auth();
+        let key = DataKey::Merchant(MerchantDataKey::SubMerchant(sub_merchant.clone()));
+        match record_get::<DataKey, SubMerchant>(&env, &key) {
+            Some(record) if record.platform == platform => {}
+            _ => return Err(Error::Payment(PaymentError::SubMerchantNotFound)),
+        }
+        record_remove(&env, &key);
+
+        let list_key = DataKey::Merchant(MerchantDataKey::PlatformSubMerchants(platform.clone()));
+        let mut sub_merchants: Vec<Address> =
+            record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        if let Some(index) = sub_merchants.first_index_of(&sub_merchant) {
+            sub_merchants.remove(index);
+        }
+        record_set(&env, &list_key, &sub_merchants);
+
+        (SubMerchantRemoved {
+            platform,
+            sub_merchant,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the platform connection of a sub-merchant, if any.
+    pub fn get_sub_merchant(env: Env, sub_merchant: Address) -> Option<SubMerchant> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::SubMerchant(sub_merchant)),
+        )
+    }
+
+    /// Returns the sub-merchants connected to a platform.
+    pub fn get_platform_sub_merchants(env: Env, platform: Address) -> Vec<Address> {
+        record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PlatformSubMerchants(platform)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Creates a payment to a sub-merchant on behalf of its platform.
+    ///
+    /// The payment is an ordinary non-expiring `Pending` payment whose `merchant`
+    /// is the sub-merchant. When it completes, `application_fee` is paid to the platform
+    /// out of the sub-merchant's settlement, after the protocol fee.
+    ///
+    /// # Arguments
+    /// * `customer` - The paying customer (must authorize)
+    /// * `platform` - The marketplace platform
+    /// * `sub_merchant` - The sub-merchant receiving the payment; must be connected to `platform`
+    /// * `amount` - The payment amount in base token units
+    /// * `token` - The token contract address
+    /// * `currency` - The payment currency
+    /// * `metadata` - Payment metadata
+    /// * `application_fee` - The platform's fee on this payment
+    ///
+    /// # Returns
+    /// `Ok(payment_id)` on success, or an error if the sub-merchant is not connected to
+    /// the platform, the fee is negative, above 10000 bps or larger than `amount`, or
+    /// the payment itself is invalid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_platform_payment(
+        env: Env,
+        customer: Address,
+        platform: Address,
+        sub_merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        metadata: String,
+        application_fee: ApplicationFee,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "create_platform_payment")?;
+        Self::require_merchant_not_paused(&env, &sub_merchant)?;
+        customer.require_auth();
+        match Self::get_sub_merchant(env.clone(), sub_merchant.clone()) {
+            Some(record) if record.platform == platform => {}
+            _ => return Err(Error::Payment(PaymentError::SubMerchantNotFound)),
+        }
+        if application_fee.bps > 10000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        let fee = application_fee.fixed + amount * application_fee.bps as i128 / 10000;
+        if application_fee.fixed < 0 || fee > amount {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+
+        let payment_id = PaymentContract::do_create_payment(
+            &env,
+            customer,
+            sub_merchant.clone(),
+            amount,
+            token,
+            currency,
+            0,
+            metadata,
+        )?;
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::PlatformPayment(payment_id)),
+            &PlatformPayment {
+                payment_id,
+                platform: platform.clone(),
+                application_fee: fee,
+                fee_collected: 0,
+            },
+        );
+
+        let mut analytics = Self::get_merchant_analytics(env.clone(), platform.clone());
+        analytics.total_payments += 1;
+        analytics.total_volume += amount;
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::Analytics(platform.clone())),
+            &analytics,
+        );
+
+        (PlatformPaymentCreated {
+            payment_id,
+            platform,
+            sub_merchant,
+            application_fee: fee,
+        })
+        .publish(&env);
+        Ok(payment_id)
+    }
+
+    /// Returns the marketplace details of a payment, if it was created by a platform.
+    pub fn get_platform_payment(env: Env, payment_id: u64) -> Option<PlatformPayment> {
+        record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::PlatformPayment(payment_id)),
+        )
+    }
+
+    /// Refunds a completed platform payment on behalf of its sub-merchant.
+    ///
+    /// The refund is paid to the customer from the payment's unreleased pending
+    /// settlement first, then from the sub-merchant's rolling reserve, and the
+    /// rest from the platform's own balance. The platform settles with the
+    /// sub-merchant off-chain.
+    ///
+    /// # Arguments
+    /// * `platform` - The payment's platform (must authorize)
+    /// * `payment_id` - The platform payment to refund
+    /// * `amount` - The refund amount in base token units
+    ///
+    /// # Returns
+    /// `Ok(from_platform)` with the part paid from the platform's balance, or an error
+    /// if the payment is not the platform's, not completed, has an undecided
+    /// chargeback, or the refund exceeds the remaining refundable amount.
+    pub fn refund_platform_payment(
+        env: Env,
+        platform: Address,
+        payment_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "refund_platform_payment")?;
+        platform.require_auth();
+        match Self::get_platform_payment(env.clone(), payment_id) {
+            Some(record) if record.platform == platform => {}
+            Some(_) => return Err(Error::Basic(BasicError::Unauthorized)),
+            None => return Err(Error::Payment(PaymentError::NotFound)),
+        }
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let mut payment = PaymentContract::get_payment(&env, payment_id);
+        Self::require_no_pending_chargeback(&env, payment_id)?;
+        match payment.status {
+            PaymentStatus::Completed | PaymentStatus::PartialRefunded => {}
+            PaymentStatus::Refunded => {
+                return Err(Error::Payment(PaymentError::AlreadyProcessed));
+            }
+            PaymentStatus::Pending | PaymentStatus::Cancelled => {
+                return Err(Error::Payment(PaymentError::InvalidStatus));
+            }
+        }
+        if payment.refunded_amount + amount > payment.amount {
+            return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+        }
+        payment.refunded_amount += amount;
+        payment.status = if payment.refunded_amount == payment.amount {
+            PaymentStatus::Refunded
+        } else {
+            PaymentStatus::PartialRefunded
+        };
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::Data(payment_id)),
+            &payment,
+        );
+
+        let token_client = token::Client::new(&env, &payment.token);
+        let contract_address = env.current_contract_address();
+        let mut covered = 0;
+        if !record_has(
+            &env,
+            &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+        ) {
+            if let Some(mut settlement) = record_get::<DataKey, PendingSettlement>(
+                &env,
+                &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+            ) {
+                covered = amount.min(settlement.amount);
+                if covered > 0 {
+                    settlement.amount -= covered;
+                    record_set(
+                        &env,
+                        &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                        &settlement,
+                    );
+                    token_client.transfer(&contract_address, &payment.customer, &covered);
+                }
+            }
+        }
+        if covered < amount {
+            covered += Self::draw_reserve(
+                &env,
+                &payment.merchant,
+                &payment.token,
+                amount - covered,
+                &payment.customer,
+            );
+        }
+        let from_platform = amount - covered;
+        if from_platform > 0 {
+            token_client.transfer(&platform, &payment.customer, &from_platform);
+        }
+
+        for merchant in [payment.merchant.clone(), platform.clone()] {
+            let mut analytics = Self::get_merchant_analytics(env.clone(), merchant.clone());
+            analytics.total_refunded += 1;
+            analytics.total_refunded_volume += amount;
+            env.storage().instance().set(
+                &DataKey::Merchant(MerchantDataKey::Analytics(merchant)),
+                &analytics,
+            );
+        }
+
+        (PaymentRefunded {
+            payment_id,
+            customer: payment.customer,
+            amount,
+        })
+        .publish(&env);
+        (PlatformRefunded {
+            payment_id,
+            platform,
+            amount,
+            from_platform,
+        })
+        .publish(&env);
+        Ok(from_platform)
+    }
+
+    /// Pays a platform payment's application fee, capped at `available`, from
+    /// `payer` (the customer, or this contract when it already holds the funds)
+    /// to the platform and books it in both parties' fee records and the
+    /// platform's analytics. Returns the fee paid, or 0 for other payments.
+    fn collect_application_fee(
+        env: &Env,
+        payment: &Payment,
+        available: i128,
+        payer: &Address,
+    ) -> i128 {
+        let key = DataKey::Payment(PaymentKey::PlatformPayment(payment.id));
+        let mut record: PlatformPayment = match record_get(env, &key) {
+            Some(record) => record,
+            None => return 0,
+        };
+
+        let fee = record.application_fee.min(available).max(0);
+        if fee > 0 {
+            let token_client = token::Client::new(env, &payment.token);
+            let contract_address = env.current_contract_address();
+            if *payer == contract_address {
+                token_client.transfer(&contract_address, &record.platform, &fee);
+            } else {
+                PaymentContract::charge_customer(env, payer, &payment.token, &record.platform, fee);
+            }
+        }
+        record.fee_collected = fee;
+        record_set(env, &key, &record);
+
+        PaymentContract::update_merchant_fee_record_post_completion(
+            env,
+            payment.merchant.clone(),
+            0,
+            fee,
+        );
+        PaymentContract::update_merchant_fee_record_post_completion(
+            env,
+            record.platform.clone(),
+            payment.amount,
+            0,
+        );
+        let mut analytics =
+            PaymentContract::get_merchant_analytics(env.clone(), record.platform.clone());
+        analytics.total_completed += 1;
+        env.storage().instance().set(
+            &DataKey::Merchant(MerchantDataKey::Analytics(record.platform.clone())),
+            &analytics,
+        );
+
+        (ApplicationFeeCollected {
+            payment_id: payment.id,
+            platform: record.platform,
+            amount: fee,
+        })
+        .publish(env);
+        fee
+    }
+
     // ── FEE SWEEP (#216) ─────────────────────────────────────────────────────
 
     /// Sets the recipient address for platform fee sweeps.
@@ -11663,7 +16889,8 @@ impl PaymentContract {
             recipient,
             swept_at: env.ledger().timestamp(),
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Feature(FeatureKey::SweepHistory(sweep_id)),
             &record,
         );
@@ -11690,11 +16917,7 @@ impl PaymentContract {
             1
         };
         for i in start..=total {
-            if let Some(record) = env
-                .storage()
-                .instance()
-                .get(&DataKey::Feature(FeatureKey::SweepHistory(i)))
-            {
+            if let Some(record) = record_get(&env, &DataKey::Feature(FeatureKey::SweepHistory(i))) {
                 result.push_back(record);
             }
         }
@@ -11712,6 +16935,190 @@ impl PaymentContract {
             .unwrap_or(0)
     }
 
+    // ── CUSTOMER WALLET ──────────────────────────────────────────────────────
+
+    /// Tops up the customer's prepaid wallet balance in `token`.
+    ///
+    /// Completed payments, recurring and metered subscription charges, dunning
+    /// retries and plan-change prorations are paid from this balance first; only
+    /// the remainder is pulled under the customer's token allowance.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer depositing (must authorize).
+    /// * `token` - The token to deposit.
+    /// * `amount` - The amount to deposit.
+    ///
+    /// # Returns
+    /// The new wallet balance, or `InvalidAmount` if `amount` is not positive.
+    pub fn wallet_deposit(
+        env: Env,
+        customer: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "wallet_deposit")?;
+        customer.require_auth();
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        token::Client::new(&env, &token).transfer(
+            &customer,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let key = DataKey::Customer(CustomerDataKey::WalletBalance(
+            customer.clone(),
+            token.clone(),
+        ));
+        let balance = record_get::<DataKey, i128>(&env, &key).unwrap_or(0) + amount;
+        record_set(&env, &key, &balance);
+
+        (WalletDeposited {
+            customer,
+            token,
+            amount,
+            balance,
+        })
+        .publish(&env);
+        Ok(balance)
+    }
+
+    /// Withdraws from the customer's prepaid wallet balance.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer withdrawing (must authorize).
+    /// * `token` - The token to withdraw.
+    /// * `amount` - The amount to withdraw.
+    ///
+    /// # Returns
+    /// The remaining wallet balance, or an error if `amount` is not positive or
+    /// exceeds the balance.
+    pub fn wallet_withdraw(
+        env: Env,
+        customer: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        customer.require_auth();
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let key = DataKey::Customer(CustomerDataKey::WalletBalance(
+            customer.clone(),
+            token.clone(),
+        ));
+        let balance: i128 = record_get(&env, &key).unwrap_or(0);
+        if amount > balance {
+            return Err(Error::Payment(PaymentError::InsufficientWalletBalance));
+        }
+
+        let balance = balance - amount;
+        if balance == 0 {
+            record_remove(&env, &key);
+        } else {
+            record_set(&env, &key, &balance);
+        }
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &customer,
+            &amount,
+        );
+
+        (WalletWithdrawn {
+            customer,
+            token,
+            amount,
+            balance,
+        })
+        .publish(&env);
+        Ok(balance)
+    }
+
+    /// Returns the customer's prepaid wallet balance in `token`.
+    pub fn get_wallet_balance(env: Env, customer: Address, token: Address) -> i128 {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::WalletBalance(customer, token)),
+        )
+        .unwrap_or(0)
+    }
+
+    /// Moves `amount` of `token` from the customer to `to`, spending their wallet
+    /// balance first and pulling the rest under their allowance. Panics like
+    /// `transfer_from` if the allowance cannot cover the rest.
+    fn charge_customer(env: &Env, customer: &Address, token: &Address, to: &Address, amount: i128) {
+        let from_wallet =
+            PaymentContract::get_wallet_balance(env.clone(), customer.clone(), token.clone())
+                .min(amount);
+        if amount > from_wallet {
+            let contract_address = env.current_contract_address();
+            token::Client::new(env, token).transfer_from(
+                &contract_address,
+                customer,
+                to,
+                &(amount - from_wallet),
+            );
+        }
+        PaymentContract::debit_wallet(env, customer, token, to, from_wallet);
+    }
+
+    /// Like `charge_customer`, but returns `false` without moving anything if
+    /// the allowance cannot cover what the wallet does not.
+    fn try_charge_customer(
+        env: &Env,
+        customer: &Address,
+        token: &Address,
+        to: &Address,
+        amount: i128,
+    ) -> bool {
+        let from_wallet =
+            PaymentContract::get_wallet_balance(env.clone(), customer.clone(), token.clone())
+                .min(amount);
+        if amount > from_wallet {
+            let contract_address = env.current_contract_address();
+            let pulled = token::Client::new(env, token).try_transfer_from(
+                &contract_address,
+                customer,
+                to,
+                &(amount - from_wallet),
+            );
+            if !matches!(pulled, Ok(Ok(()))) {
+                return false;
+            }
+        }
+        PaymentContract::debit_wallet(env, customer, token, to, from_wallet);
+        true
+    }
+
+    fn debit_wallet(env: &Env, customer: &Address, token: &Address, to: &Address, amount: i128) {
+        if amount <= 0 {
+            return;
+        }
+        let key = DataKey::Customer(CustomerDataKey::WalletBalance(
+            customer.clone(),
+            token.clone(),
+        ));
+        let balance: i128 = record_get::<DataKey, i128>(env, &key).unwrap_or(0) - amount;
+        if balance == 0 {
+            record_remove(env, &key);
+        } else {
+            record_set(env, &key, &balance);
+        }
+        let contract_address = env.current_contract_address();
+        if *to != contract_address {
+            token::Client::new(env, token).transfer(&contract_address, to, &amount);
+        }
+
+        (WalletDebited {
+            customer: customer.clone(),
+            token: token.clone(),
+            amount,
+            to: to.clone(),
+        })
+        .publish(env);
+    }
+
     // ── CUSTOMER SPEND LIMITS (#217) ─────────────────────────────────────────
 
     /// Sets a spending limit for a customer over a rolling time period.
@@ -11863,6 +17270,264 @@ impl PaymentContract {
         Ok(())
     }
 
+    // ── DELEGATED SPENDERS ────────────────────────────────────────────────────
+
+    /// Authorizes `delegate` to create payments on the customer's behalf within
+    /// `scope`. Granting again replaces the scope and starts a new period.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer granting the authority (must authorize).
+    /// * `delegate` - The address allowed to spend, e.g. an employee card or agent.
+    /// * `scope` - The per-period cap, allowed merchants, categories and tokens, and expiry.
+    ///
+    /// # Errors
+    /// Returns an error if the delegate is the customer, the cap is not positive,
+    /// the period is zero, or the expiry has already passed.
+    pub fn grant_delegate(
+        env: Env,
+        customer: Address,
+        delegate: Address,
+        scope: DelegateScope,
+    ) -> Result<(), Error> {
+        customer.require_auth();
+        if delegate == customer {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if scope.period_cap <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if scope.period_seconds == 0 {
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+        let now = env.ledger().timestamp();
+        if scope.expires_at != 0 && scope.expires_at <= now {
+            return Err(Error::Payment(PaymentError::DelegationExpired));
+        }
+
+        let key = DataKey::Customer(CustomerDataKey::Delegation(
+            customer.clone(),
+            delegate.clone(),
+        ));
+        let total_spent = record_get::<DataKey, SpendDelegation>(&env, &key)
+            .map(|d| d.total_spent)
+            .unwrap_or(0);
+        let delegation = SpendDelegation {
+            customer: customer.clone(),
+            delegate: delegate.clone(),
+            scope: scope.clone(),
+            granted_at: now,
+            period_start: now,
+            spent_in_period: 0,
+            total_spent,
+        };
+        record_set(&env, &key, &delegation);
+
+        let list_key = DataKey::Customer(CustomerDataKey::Delegates(customer.clone()));
+        let mut delegates: Vec<Address> =
+            record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        if !delegates.contains(&delegate) {
+            delegates.push_back(delegate.clone());
+            record_set(&env, &list_key, &delegates);
+        }
+
+        (DelegateGranted {
+            customer,
+            delegate,
+            period_cap: scope.period_cap,
+            expires_at: scope.expires_at,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Revokes a delegate's spending authority. Their spend history is kept.
+    ///
+    /// # Arguments
+    /// * `customer` - The customer revoking the authority (must authorize).
+    /// * `delegate` - The delegate to revoke.
+    pub fn revoke_delegate(env: Env, customer: Address, delegate: Address) -> Result<(), Error> {
+        customer.require_auth();
+        let key = DataKey::Customer(CustomerDataKey::Delegation(
+            customer.clone(),
+            delegate.clone(),
+        ));
+        if !record_has(&env, &key) {
+            return Err(Error::Payment(PaymentError::DelegationNotFound));
+        }
+        record_remove(&env, &key);
+
+        let list_key = DataKey::Customer(CustomerDataKey::Delegates(customer.clone()));
+        let mut delegates: Vec<Address> =
+            record_get(&env, &list_key).unwrap_or_else(|| Vec::new(&env));
+        if let Some(index) = delegates.first_index_of(&delegate) {
+            delegates.remove(index);
+            record_set(&env, &list_key, &delegates);
+        }
+
+        (DelegateRevoked { customer, delegate }).publish(&env);
+        Ok(())
+    }
+
+    /// Returns the customer's delegation to `delegate`, if one is active.
+    pub fn get_delegation(
+        env: Env,
+        customer: Address,
+        delegate: Address,
+    ) -> Option<SpendDelegation> {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::Delegation(customer, delegate)),
+        )
+    }
+
+    /// Returns the delegates the customer has authorized.
+    pub fn get_delegates(env: Env, customer: Address) -> Vec<Address> {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::Delegates(customer)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    const DELEGATE_SPEND_PAGE_SIZE: u64 = 100;
+
+    /// Returns the payments `delegate` has created for the customer on the
+    /// requested page (100 per page), oldest first.
+    pub fn get_delegate_spend_history(
+        env: Env,
+        customer: Address,
+        delegate: Address,
+        page: u64,
+    ) -> Vec<DelegateSpend> {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::DelegateSpends(customer, delegate, page)),
+        )
+        .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Returns how many payments `delegate` has created for the customer.
+    pub fn get_delegate_spend_count(env: Env, customer: Address, delegate: Address) -> u64 {
+        record_get(
+            &env,
+            &DataKey::Customer(CustomerDataKey::DelegateSpendCount(customer, delegate)),
+        )
+        .unwrap_or(0)
+    }
+
+    /// Creates a payment on the customer's behalf, authorized by a delegate
+    /// instead of the customer. The payment is otherwise an ordinary `Pending`
+    /// payment from the customer, subject to their own spend limit.
+    ///
+    /// # Arguments
+    /// * `delegate` - The delegate creating the payment (must authorize).
+    /// * `customer` - The customer who granted the delegation and pays.
+    /// * `merchant` - The merchant receiving the payment.
+    /// * `amount` - The payment amount in base token units.
+    /// * `token` - The payment token address.
+    /// * `currency` - The fiat currency associated with the payment.
+    /// * `metadata` - Arbitrary metadata string for the payment.
+    ///
+    /// # Returns
+    /// `Ok(payment_id)` on success, or an error if there is no delegation, it has
+    /// expired, the payment falls outside its scope or would exceed the period cap.
+    /// A scope restricted to categories only admits merchants whose registered
+    /// category is listed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_delegated_payment(
+        env: Env,
+        delegate: Address,
+        customer: Address,
+        merchant: Address,
+        amount: i128,
+        token: Address,
+        currency: Currency,
+        metadata: String,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "create_delegated_payment")?;
+        delegate.require_auth();
+        let key = DataKey::Customer(CustomerDataKey::Delegation(
+            customer.clone(),
+            delegate.clone(),
+        ));
+        let mut delegation: SpendDelegation =
+            record_get(&env, &key).ok_or(Error::Payment(PaymentError::DelegationNotFound))?;
+
+        let now = env.ledger().timestamp();
+        let scope = &delegation.scope;
+        if scope.expires_at != 0 && now >= scope.expires_at {
+            return Err(Error::Payment(PaymentError::DelegationExpired));
+        }
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        let category = Self::get_merchant_category(env.clone(), merchant.clone());
+        let category_allowed = scope.categories.is_empty()
+            || category
+                .as_ref()
+                .is_some_and(|c| scope.categories.contains(c));
+        if (!scope.merchants.is_empty() && !scope.merchants.contains(&merchant))
+            || (!scope.tokens.is_empty() && !scope.tokens.contains(&token))
+            || !category_allowed
+        {
+            return Err(Error::Payment(PaymentError::DelegationScopeViolation));
+        }
+
+        if now > delegation.period_start + scope.period_seconds {
+            delegation.period_start = now;
+            delegation.spent_in_period = 0;
+        }
+        if delegation.spent_in_period + amount > scope.period_cap {
+            return Err(Error::Payment(PaymentError::DelegationCapExceeded));
+        }
+        delegation.spent_in_period += amount;
+        delegation.total_spent += amount;
+
+        let payment_id = PaymentContract::do_create_payment(
+            &env,
+            customer.clone(),
+            merchant.clone(),
+            amount,
+            token.clone(),
+            currency,
+            0,
+            metadata,
+        )?;
+        record_set(&env, &key, &delegation);
+
+        let count_key = DataKey::Customer(CustomerDataKey::DelegateSpendCount(
+            customer.clone(),
+            delegate.clone(),
+        ));
+        let count: u64 = record_get(&env, &count_key).unwrap_or(0);
+        record_set(&env, &count_key, &(count + 1));
+        let history_key = DataKey::Customer(CustomerDataKey::DelegateSpends(
+            customer.clone(),
+            delegate.clone(),
+            count / Self::DELEGATE_SPEND_PAGE_SIZE,
+        ));
+        let mut history: Vec<DelegateSpend> =
+            record_get(&env, &history_key).unwrap_or_else(|| Vec::new(&env));
+        history.push_back(DelegateSpend {
+            payment_id,
+            merchant,
+            token,
+            amount,
+            category,
+            spent_at: now,
+        });
+        record_set(&env, &history_key, &history);
+
+        (DelegatedPaymentCreated {
+            payment_id,
+            customer,
+            delegate,
+            amount,
+        })
+        .publish(&env);
+        Ok(payment_id)
+    }
+
     // ── SUBSCRIPTION GROUPS (#218) ────────────────────────────────────────────
 
     /// Creates a new subscription group with a discount.
@@ -11896,7 +17561,8 @@ impl PaymentContract {
             discount_bps,
             active: true,
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Group(group_id)),
             &group,
         );
@@ -11926,35 +17592,35 @@ impl PaymentContract {
         subscription_id: u64,
     ) -> Result<(), Error> {
         owner.require_auth();
-        let mut group: SubscriptionGroup = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Group(group_id)))
-            .ok_or(Error::Subscription(SubscriptionError::GroupNotFound))?;
+        let mut group: SubscriptionGroup = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Group(group_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::GroupNotFound))?;
         if group.owner != owner {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
-        if group.subscription_ids.len() >= 20 {
-            return Err(Error::Subscription(
-                SubscriptionError::GroupSizeLimitExceeded,
-            ));
-        }
-        if env
-            .storage()
-            .instance()
-            .get::<DataKey, u64>(&DataKey::Subscription(SubscriptionKey::GroupMembership(
-                subscription_id,
-            )))
-            .is_some()
+        if group.subscription_ids.len() >= 20 {
+            return Err(Error::Subscription(
+                SubscriptionError::GroupSizeLimitExceeded,
+            ));
+        }
+        if record_get::<DataKey, u64>(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::GroupMembership(subscription_id)),
+        )
+        .is_some()
         {
             return Err(Error::Subscription(SubscriptionError::AlreadyInGroup));
         }
         group.subscription_ids.push_back(subscription_id);
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Group(group_id)),
             &group,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::GroupMembership(subscription_id)),
             &group_id,
         );
@@ -11980,11 +17646,11 @@ impl PaymentContract {
         subscription_id: u64,
     ) -> Result<(), Error> {
         owner.require_auth();
-        let mut group: SubscriptionGroup = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Group(group_id)))
-            .ok_or(Error::Subscription(SubscriptionError::GroupNotFound))?;
+        let mut group: SubscriptionGroup = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Group(group_id)),
+        )
+        .ok_or(Error::Subscription(SubscriptionError::GroupNotFound))?;
         if group.owner != owner {
             return Err(Error::Basic(BasicError::Unauthorized));
         }
@@ -11995,15 +17661,15 @@ impl PaymentContract {
             }
         }
         group.subscription_ids = new_ids;
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::Subscription(SubscriptionKey::Group(group_id)),
             &group,
         );
-        env.storage()
-            .instance()
-            .remove(&DataKey::Subscription(SubscriptionKey::GroupMembership(
-                subscription_id,
-            )));
+        record_remove(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::GroupMembership(subscription_id)),
+        );
         Ok(())
     }
 
@@ -12015,37 +17681,36 @@ impl PaymentContract {
     /// # Returns
     /// `Some(SubscriptionGroup)` if the group exists, `None` otherwise.
     pub fn get_subscription_group(env: Env, group_id: u64) -> Option<SubscriptionGroup> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Group(group_id)))
+        record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Group(group_id)),
+        )
     }
 
     /// Returns the earliest next billing timestamp across all subscriptions in a group.
+    /// A subscription still in its trial counts from its first due date after the trial.
     ///
     /// # Arguments
     /// * `group_id` - The group ID.
     ///
     /// # Returns
-    /// The earliest `next_payment_at` timestamp, or 0 if the group has no subscriptions.
+    /// The earliest billing timestamp, or 0 if the group has no subscriptions.
     pub fn get_group_next_billing(env: Env, group_id: u64) -> u64 {
-        let group: Option<SubscriptionGroup> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Subscription(SubscriptionKey::Group(group_id)));
+        let group: Option<SubscriptionGroup> = record_get(
+            &env,
+            &DataKey::Subscription(SubscriptionKey::Group(group_id)),
+        );
         let group = match group {
             None => return 0,
             Some(g) => g,
         };
         let mut earliest: u64 = u64::MAX;
         for sub_id in group.subscription_ids.iter() {
-            if let Some(sub) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Subscription>(&DataKey::Subscription(SubscriptionKey::Data(sub_id)))
-            {
-                if sub.next_payment_at < earliest {
-                    earliest = sub.next_payment_at;
-                }
+            if let Some(sub) = record_get::<DataKey, Subscription>(
+                &env,
+                &DataKey::Subscription(SubscriptionKey::Data(sub_id)),
+            ) {
+                earliest = earliest.min(Self::next_billed_at(&env, &sub));
             }
         }
         if earliest == u64::MAX {
@@ -12113,31 +17778,32 @@ impl PaymentContract {
     /// Returns an error if the settlement is already finalized, not found,
     /// or the release time has not yet passed.
     pub fn finalize_pending_settlement(env: Env, payment_id: u64) -> Result<(), Error> {
-        if env
-            .storage()
-            .instance()
-            .has(&DataKey::State(StateDataKey::SettlementFinalized(
-                payment_id,
-            )))
-        {
+        if record_has(
+            &env,
+            &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+        ) {
             return Err(Error::Feature(FeatureError::SettlementAlreadyFinalized));
         }
-        let settlement: PendingSettlement = env
-            .storage()
-            .instance()
-            .get(&DataKey::Payment(PaymentKey::PendingSettlement(payment_id)))
-            .ok_or(Error::Payment(PaymentError::NotFound))?;
+        let settlement: PendingSettlement = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::NotFound))?;
         let now = env.ledger().timestamp();
         if now < settlement.release_at {
             return Err(Error::Feature(FeatureError::SettlementNotReady));
         }
-        let token_client = token::Client::new(&env, &settlement.token);
-        token_client.transfer(
-            &env.current_contract_address(),
+        let amount = Self::recover_chargeback_debts(
+            &env,
             &settlement.merchant,
-            &settlement.amount,
+            &settlement.token,
+            settlement.amount,
         );
-        env.storage().instance().set(
+        if amount > 0 {
+            Self::pay_out(&env, &settlement.merchant, &settlement.token, amount)?;
+        }
+        record_set(
+            &env,
             &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
             &true,
         );
@@ -12152,30 +17818,25 @@ impl PaymentContract {
     /// # Returns
     /// A vector of `PendingSettlement` records that have not yet been finalized.
     pub fn get_pending_settlements(env: Env, merchant: Address) -> Vec<PendingSettlement> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::Merchant(MerchantDataKey::PendingSettlementCount(
-                merchant.clone(),
-            )))
-            .unwrap_or(0);
+        let count: u64 = record_get(
+            &env,
+            &DataKey::Merchant(MerchantDataKey::PendingSettlementCount(merchant.clone())),
+        )
+        .unwrap_or(0);
         let mut result = Vec::new(&env);
         for i in 0..count {
-            if let Some(payment_id) =
-                env.storage()
-                    .instance()
-                    .get::<DataKey, u64>(&DataKey::Merchant(
-                        MerchantDataKey::PendingSettlementIndex(merchant.clone(), i),
-                    ))
-            {
-                if !env.storage().instance().has(&DataKey::State(
-                    StateDataKey::SettlementFinalized(payment_id),
-                )) {
-                    if let Some(s) = env
-                        .storage()
-                        .instance()
-                        .get(&DataKey::Payment(PaymentKey::PendingSettlement(payment_id)))
-                    {
+            if let Some(payment_id) = record_get::<DataKey, u64>(
+                &env,
+                &DataKey::Merchant(MerchantDataKey::PendingSettlementIndex(merchant.clone(), i)),
+            ) {
+                if !record_has(
+                    &env,
+                    &DataKey::State(StateDataKey::SettlementFinalized(payment_id)),
+                ) {
+                    if let Some(s) = record_get(
+                        &env,
+                        &DataKey::Payment(PaymentKey::PendingSettlement(payment_id)),
+                    ) {
                         result.push_back(s);
                     }
                 }
@@ -12206,8 +17867,164 @@ impl PaymentContract {
 
     // ── Issue #118: Payment routing optimization ──────────────────────────
 
+    /// Registers a swap venue that routes can use to convert between two tokens.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin authorizing this operation (must be a multisig admin).
+    /// * `adapter` - Contract implementing the swap interface described on `SwapVenue`.
+    /// * `token_a` / `token_b` - The token pair the venue trades.
+    /// * `currency_a` / `currency_b` - Currencies whose conversion rates price each token.
+    /// * `fee_bps` - Fee the venue charges on each swap.
+    ///
+    /// # Returns
+    /// The new venue ID.
+    ///
+    /// # Errors
+    /// Returns an error if unauthorized, the tokens are the same, or `fee_bps`
+    /// is 10_000 or more.
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_swap_venue(
+        env: Env,
+        admin: Address,
+        adapter: Address,
+        token_a: Address,
+        currency_a: Currency,
+        token_b: Address,
+        currency_b: Currency,
+        fee_bps: u32,
+    ) -> Result<u32, Error> {
+        Self::require_not_paused(&env, "register_swap_venue")?;
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if token_a == token_b {
+            return Err(Error::Routing(RoutingError::InvalidRoute));
+        }
+        if fee_bps >= 10_000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+
+        let mut venues = Self::get_swap_venues(env.clone());
+        let venue = SwapVenue {
+            venue_id: venues.len() + 1,
+            adapter: adapter.clone(),
+            token_a: token_a.clone(),
+            currency_a,
+            token_b: token_b.clone(),
+            currency_b,
+            fee_bps,
+            active: true,
+        };
+        venues.push_back(venue.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::Feature(FeatureKey::SwapVenues), &venues);
+
+        (SwapVenueRegistered {
+            venue_id: venue.venue_id,
+            adapter,
+            token_a,
+            token_b,
+        })
+        .publish(&env);
+        Ok(venue.venue_id)
+    }
+
+    /// Enables or disables a swap venue. Disabled venues are skipped by route
+    /// search and rejected at execution.
+    ///
+    /// # Errors
+    /// Returns an error if unauthorized or the venue does not exist.
+    pub fn set_swap_venue_active(
+        env: Env,
+        admin: Address,
+        venue_id: u32,
+        active: bool,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        let mut venue = Self::get_swap_venue(env.clone(), venue_id)?;
+        venue.active = active;
+        let mut venues = Self::get_swap_venues(env.clone());
+        venues.set(venue_id - 1, venue);
+        env.storage()
+            .instance()
+            .set(&DataKey::Feature(FeatureKey::SwapVenues), &venues);
+        Ok(())
+    }
+
+    /// Returns a registered swap venue.
+    ///
+    /// # Errors
+    /// Returns `SwapVenueNotFound` if no venue has this ID.
+    pub fn get_swap_venue(env: Env, venue_id: u32) -> Result<SwapVenue, Error> {
+        if venue_id == 0 {
+            return Err(Error::Routing(RoutingError::SwapVenueNotFound));
+        }
+        Self::get_swap_venues(env)
+            .get(venue_id - 1)
+            .ok_or(Error::Routing(RoutingError::SwapVenueNotFound))
+    }
+
+    /// Returns all registered swap venues, including disabled ones.
+    pub fn get_swap_venues(env: Env) -> Vec<SwapVenue> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Feature(FeatureKey::SwapVenues))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Admin sets the maximum number of swaps in a route (default: 3, at most 4).
+    ///
+    /// # Errors
+    /// Returns an error if unauthorized or `max_hops` is outside `1..=4`.
+    pub fn set_max_route_hops(env: Env, admin: Address, max_hops: u32) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if max_hops == 0 || max_hops > MAX_ROUTE_HOPS {
+            return Err(Error::Routing(RoutingError::TooManyHops));
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::MaxRouteHops), &max_hops);
+        Ok(())
+    }
+
+    /// Returns the maximum number of swaps in a route.
+    pub fn get_max_route_hops(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MaxRouteHops))
+            .unwrap_or(3)
+    }
+
     /// Returns up to 3 candidate routes sorted by effective_cost (ascending).
-    /// Routes are derived from stored conversion rates for the given token pair.
+    ///
+    /// Paths are searched over active swap venues up to `get_max_route_hops()`
+    /// swaps, never revisiting a token. Each hop is priced with the conversion
+    /// rates of the venue's currencies, read live from the oracle when an
+    /// `OracleRateConfig` is enabled, less the venue fee. Venues whose oracle
+    /// feed is stale or failing are skipped.
     pub fn get_optimal_route(
         env: Env,
         input_token: Address,
@@ -12215,45 +18032,43 @@ impl PaymentContract {
         amount: i128,
     ) -> Vec<RouteOption> {
         let mut routes: Vec<RouteOption> = Vec::new(&env);
-
-        // Direct route: input → output at 1:1 with no fee
-        let direct = RouteOption {
-            input_token: input_token.clone(),
-            output_token: output_token.clone(),
-            input_amount: amount,
-            output_amount: amount,
-            fee_bps: 0,
-            effective_cost: 0,
-        };
-        routes.push_back(direct);
-
-        // Fee-bearing route using configured fee (if any)
-        let fee_config: Option<FeeConfig> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::FeeConfig));
-        if let Some(fc) = fee_config {
-            if fc.active && fc.fee_bps > 0 {
-                let fee = (amount * fc.fee_bps as i128) / 10000;
-                let route_with_fee = RouteOption {
-                    input_token: input_token.clone(),
-                    output_token: output_token.clone(),
-                    input_amount: amount,
-                    output_amount: amount - fee,
-                    fee_bps: fc.fee_bps,
-                    effective_cost: fee,
-                };
-                routes.push_back(route_with_fee);
-            }
+        if amount <= 0 {
+            return routes;
+        }
+        if input_token == output_token {
+            routes.push_back(RouteOption {
+                input_token: input_token.clone(),
+                output_token,
+                input_amount: amount,
+                output_amount: amount,
+                fee_bps: 0,
+                effective_cost: 0,
+                hops: Vec::new(&env),
+            });
+            return routes;
         }
 
-        // Sort by effective_cost ascending (bubble sort, max 3 elements)
+        let venues = Self::get_swap_venues(env.clone());
+        let mut visited = Vec::new(&env);
+        visited.push_back(input_token.clone());
+        Self::search_routes(
+            &env,
+            &venues,
+            &output_token,
+            Self::get_max_route_hops(env.clone()),
+            &mut visited,
+            &mut Vec::new(&env),
+            (amount, amount, 0),
+            &mut routes,
+        );
+
+        // Sort by effective_cost ascending, then by fewer hops
         let len = routes.len();
         for i in 0..len {
             for j in 0..(len.saturating_sub(i + 1)) {
                 let a = routes.get(j).unwrap();
                 let b = routes.get(j + 1).unwrap();
-                if a.effective_cost > b.effective_cost {
+                if (a.effective_cost, a.hops.len()) > (b.effective_cost, b.hops.len()) {
                     routes.set(j, b);
                     routes.set(j + 1, a);
                 }
@@ -12264,59 +18079,356 @@ impl PaymentContract {
         let mut result: Vec<RouteOption> = Vec::new(&env);
         let cap = core::cmp::min(routes.len(), 3u32);
         for i in 0..cap {
-            result.push_back(routes.get(i).unwrap());
+            let mut route = routes.get(i).unwrap();
+            route.input_token = input_token.clone();
+            route.input_amount = amount;
+            result.push_back(route);
         }
         result
     }
 
-    /// Executes a payment using the provided route.
-    /// Validates the route is still valid (fee_bps matches current config) before executing.
+    /// Executes a pending payment along `route` and delivers `output_token` to
+    /// the merchant, completing the payment.
+    ///
+    /// The payment is completed like any other, so fees, reserves and rewards
+    /// apply. The customer's share is pulled into the contract (the customer must
+    /// have approved it), the merchant's share is swapped hop by hop through each
+    /// venue's adapter, and the output is sent to the merchant. Each hop's output
+    /// is measured from the contract's balance change rather than trusted from
+    /// the adapter. If a finality delay holds the payment, nothing is swapped and
+    /// the held settlement is released in the payment token.
+    ///
+    /// # Arguments
+    /// * `customer` - The payment's customer (must authorize).
+    /// * `merchant` - The payment's merchant.
+    /// * `route` - A route from `get_optimal_route()`.
+    /// * `payment_id` - The pending payment to execute.
+    /// * `max_slippage_bps` - How far the delivered amount may fall below the
+    ///   route re-quoted at current rates; `route.output_amount` is not trusted.
+    ///
+    /// # Returns
+    /// The amount of `output_token` delivered to the merchant.
+    ///
+    /// # Errors
+    /// Returns an error if the payment does not match the route, a hop uses an
+    /// unknown or disabled venue, a rate for the re-quote is unavailable or the
+    /// quote overflows, an adapter call fails, or the delivered amount is below
+    /// the slippage bound. Any error reverts all swaps.
     pub fn execute_routed_payment(
         env: Env,
         customer: Address,
         merchant: Address,
         route: RouteOption,
         payment_id: u64,
-    ) -> Result<(), Error> {
+        max_slippage_bps: u32,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "execute_routed_payment")?;
         customer.require_auth();
+        if max_slippage_bps > 10_000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
 
-        // Validate route is still valid at execution time
-        let fee_config: Option<FeeConfig> = env
-            .storage()
-            .instance()
-            .get(&DataKey::Config(ConfigKey::FeeConfig));
-        let current_fee_bps = fee_config
-            .filter(|fc| fc.active)
-            .map(|fc| fc.fee_bps)
-            .unwrap_or(0);
+        // Verify payment exists and belongs to customer
+        let payment = PaymentContract::get_payment(&env, payment_id);
+        if payment.customer != customer || payment.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if payment.status != PaymentStatus::Pending {
+            return Err(Error::Payment(PaymentError::InvalidStatus));
+        }
+        if PaymentContract::is_payment_expired(&env, payment_id) {
+            return Err(Error::Payment(PaymentError::Expired));
+        }
+        if payment.amount != route.input_amount {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if payment.token != route.input_token {
+            return Err(Error::Routing(RoutingError::InvalidRoute));
+        }
+        let venues = Self::validate_route(&env, &route)?;
+
+        let delivered = PaymentContract::settle_payment(
+            &env,
+            payment_id,
+            Some((&route, &venues, max_slippage_bps)),
+        )?;
+        (RoutedPaymentExecuted {
+            payment_id,
+            input_token: route.input_token,
+            output_token: route.output_token,
+            input_amount: route.input_amount,
+            output_amount: delivered,
+            hops: route.hops.len(),
+        })
+        .publish(&env);
+
+        Ok(delivered)
+    }
+
+    /// Depth-first search for paths from the last token in `visited` to
+    /// `output_token`. `quote` carries the running (amount, fee-free amount,
+    /// summed fee bps) along the path.
+    #[allow(clippy::too_many_arguments)]
+    fn search_routes(
+        env: &Env,
+        venues: &Vec<SwapVenue>,
+        output_token: &Address,
+        hops_left: u32,
+        visited: &mut Vec<Address>,
+        path: &mut Vec<RouteHop>,
+        quote: (i128, i128, u32),
+        routes: &mut Vec<RouteOption>,
+    ) {
+        if hops_left == 0 {
+            return;
+        }
+        let token_in = visited.last().unwrap();
+        for venue in venues.iter() {
+            if !venue.active {
+                continue;
+            }
+            let (token_out, currency_in, currency_out) = if venue.token_a == token_in {
+                (
+                    venue.token_b.clone(),
+                    venue.currency_a.clone(),
+                    venue.currency_b.clone(),
+                )
+            } else if venue.token_b == token_in {
+                (
+                    venue.token_a.clone(),
+                    venue.currency_b.clone(),
+                    venue.currency_a.clone(),
+                )
+            } else {
+                continue;
+            };
+            if visited.contains(&token_out) {
+                continue;
+            }
+            let (rate_in, rate_out) = match (
+                Self::route_rate(env, currency_in),
+                Self::route_rate(env, currency_out),
+            ) {
+                (Some(rate_in), Some(rate_out)) if rate_in > 0 && rate_out > 0 => {
+                    (rate_in, rate_out)
+                }
+                _ => continue,
+            };
+            let (Some(ideal), Some(amount)) = (
+                Self::convert_at_rate(quote.1, rate_in, rate_out, 0),
+                Self::convert_at_rate(quote.0, rate_in, rate_out, venue.fee_bps),
+            ) else {
+                continue;
+            };
+            if amount <= 0 {
+                continue;
+            }
+
+            path.push_back(RouteHop {
+                venue_id: venue.venue_id,
+                token_in: token_in.clone(),
+                token_out: token_out.clone(),
+            });
+            let fee_bps = quote.2 + venue.fee_bps;
+            if token_out == *output_token {
+                // Input fields are filled in by `get_optimal_route`.
+                routes.push_back(RouteOption {
+                    input_token: token_in.clone(),
+                    output_token: token_out,
+                    input_amount: 0,
+                    output_amount: amount,
+                    fee_bps,
+                    effective_cost: ideal - amount,
+                    hops: path.clone(),
+                });
+            } else {
+                visited.push_back(token_out);
+                Self::search_routes(
+                    env,
+                    venues,
+                    output_token,
+                    hops_left - 1,
+                    visited,
+                    path,
+                    (amount, ideal, fee_bps),
+                    routes,
+                );
+                visited.pop_back();
+            }
+            path.pop_back();
+        }
+    }
+
+    /// Converts `amount` at `rate_in / rate_out` less `fee_bps`, or `None` if a
+    /// product overflows.
+    fn convert_at_rate(amount: i128, rate_in: i128, rate_out: i128, fee_bps: u32) -> Option<i128> {
+        amount
+            .checked_mul(rate_in)?
+            .checked_div(rate_out)?
+            .checked_mul(10_000 - fee_bps as i128)
+            .map(|amount| amount / 10_000)
+    }
+
+    /// Prices `amount` along `hops` at current rates, the same way
+    /// `get_optimal_route` quotes it. `venues` holds each hop's venue.
+    fn quote_route(
+        env: &Env,
+        venues: &Vec<SwapVenue>,
+        hops: &Vec<RouteHop>,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        let mut quote = amount;
+        for (venue, hop) in venues.iter().zip(hops.iter()) {
+            let (currency_in, currency_out) = if venue.token_a == hop.token_in {
+                (venue.currency_a, venue.currency_b)
+            } else {
+                (venue.currency_b, venue.currency_a)
+            };
+            let (rate_in, rate_out) = match (
+                Self::route_rate(env, currency_in),
+                Self::route_rate(env, currency_out),
+            ) {
+                (Some(rate_in), Some(rate_out)) if rate_in > 0 && rate_out > 0 => {
+                    (rate_in, rate_out)
+                }
+                _ => return Err(Error::Routing(RoutingError::RateUnavailable)),
+            };
+            quote = Self::convert_at_rate(quote, rate_in, rate_out, venue.fee_bps)
+                .ok_or(Error::Routing(RoutingError::QuoteOverflow))?;
+        }
+        Ok(quote)
+    }
+
+    /// Swaps `amount` of the route's input token, held by the contract, through
+    /// each hop's venue and pays the output to `payee`. The output must be within
+    /// `max_slippage_bps` of the route re-quoted at current rates.
+    fn pay_out_along_route(
+        env: &Env,
+        payee: &Address,
+        route: &RouteOption,
+        venues: &Vec<SwapVenue>,
+        amount: i128,
+        max_slippage_bps: u32,
+    ) -> Result<(Address, i128), Error> {
+        let quote = Self::quote_route(env, venues, &route.hops, amount)?;
+        let min_output = quote
+            .checked_mul(10_000 - max_slippage_bps as i128)
+            .ok_or(Error::Routing(RoutingError::QuoteOverflow))?
+            / 10_000;
+        let mut delivered = amount;
+        for (venue, hop) in venues.iter().zip(route.hops.iter()) {
+            delivered = Self::swap_through_venue(env, &venue, &hop, delivered, 0)?;
+        }
+        if delivered < min_output {
+            return Err(Error::Routing(RoutingError::SlippageExceeded));
+        }
+        token::Client::new(env, &route.output_token).transfer(
+            &env.current_contract_address(),
+            payee,
+            &delivered,
+        );
+        Ok((route.output_token.clone(), delivered))
+    }
 
-        if route.fee_bps != current_fee_bps {
-            return Err(Error::Feature(FeatureError::InvalidFeeConfig));
+    /// Conversion rate used to price a route hop: live from the oracle when one
+    /// is enabled for `currency`, otherwise the stored rate.
+    fn route_rate(env: &Env, currency: Currency) -> Option<i128> {
+        let cfg: Option<OracleRateConfig> =
+            env.storage()
+                .instance()
+                .get(&DataKey::Feature(FeatureKey::OracleRateConfig(
+                    currency.clone(),
+                )));
+        match cfg {
+            Some(cfg) if cfg.enabled => {
+                let (rate, updated_at) = env
+                    .try_invoke_contract::<(i128, u64), Error>(
+                        &cfg.oracle_address,
+                        &Symbol::new(env, "get_price"),
+                        (cfg.price_feed_id.clone(),).into_val(env),
+                    )
+                    .ok()?
+                    .ok()?;
+                if env.ledger().timestamp().saturating_sub(updated_at) > cfg.max_staleness_seconds {
+                    return None;
+                }
+                Some(rate)
+            }
+            _ => Some(PaymentContract::get_conversion_rate(env.clone(), currency)),
         }
+    }
 
-        // Verify payment exists and belongs to customer
-        let payment = PaymentContract::get_payment(&env, payment_id);
-        if payment.customer != customer {
-            return Err(Error::Basic(BasicError::Unauthorized));
+    /// Checks that `route` is a connected path through active venues from its
+    /// input to its output token, and returns the venue of each hop.
+    fn validate_route(env: &Env, route: &RouteOption) -> Result<Vec<SwapVenue>, Error> {
+        let mut venues = Vec::new(env);
+        if route.hops.len() > Self::get_max_route_hops(env.clone()) {
+            return Err(Error::Routing(RoutingError::TooManyHops));
         }
-        if payment.status != PaymentStatus::Pending {
-            return Err(Error::Payment(PaymentError::InvalidStatus));
+        let mut token = route.input_token.clone();
+        for hop in route.hops.iter() {
+            let venue = Self::get_swap_venue(env.clone(), hop.venue_id)?;
+            let serves_pair = (venue.token_a == hop.token_in && venue.token_b == hop.token_out)
+                || (venue.token_b == hop.token_in && venue.token_a == hop.token_out);
+            if !venue.active || !serves_pair || hop.token_in != token {
+                return Err(Error::Routing(RoutingError::InvalidRoute));
+            }
+            token = hop.token_out.clone();
+            venues.push_back(venue);
         }
-        if payment.amount != route.input_amount {
-            return Err(Error::Basic(BasicError::InvalidAmount));
+        if token != route.output_token {
+            return Err(Error::Routing(RoutingError::InvalidRoute));
         }
+        Ok(venues)
+    }
 
-        // Execute the transfer
-        let token_client = token::Client::new(&env, &route.input_token);
+    /// Approves the venue's adapter for `amount_in`, calls its `swap`, and returns
+    /// the amount of `hop.token_out` the contract actually received.
+    ///
+    /// A failed adapter call is rolled back, so `SwapFailed` leaves the contract's
+    /// balances untouched; `SlippageExceeded` means the input was spent.
+    fn swap_through_venue(
+        env: &Env,
+        venue: &SwapVenue,
+        hop: &RouteHop,
+        amount_in: i128,
+        min_amount_out: i128,
+    ) -> Result<i128, Error> {
         let contract_address = env.current_contract_address();
-        token_client.transfer_from(
+        let token_in = token::Client::new(env, &hop.token_in);
+        let token_out = token::Client::new(env, &hop.token_out);
+        let balance_before = token_out.balance(&contract_address);
+
+        let expiration_ledger = env.ledger().sequence();
+        token_in.approve(
             &contract_address,
-            &customer,
-            &merchant,
-            &route.output_amount,
+            &venue.adapter,
+            &amount_in,
+            &expiration_ledger,
         );
+        let args = (
+            contract_address.clone(),
+            hop.token_in.clone(),
+            hop.token_out.clone(),
+            amount_in,
+            min_amount_out,
+        )
+            .into_val(env);
+        let result =
+            env.try_invoke_contract::<i128, Error>(&venue.adapter, &Symbol::new(env, "swap"), args);
+        // Revoke whatever the adapter did not pull.
+        token_in.approve(&contract_address, &venue.adapter, &0, &expiration_ledger);
+        if !matches!(result, Ok(Ok(_))) {
+            return Err(Error::Routing(RoutingError::SwapFailed));
+        }
 
-        Ok(())
+        // The input has been spent at this point, so a short delivery cannot fall
+        // back to the original token.
+        let received = token_out.balance(&contract_address) - balance_before;
+        if received <= 0 || received < min_amount_out {
+            return Err(Error::Routing(RoutingError::SlippageExceeded));
+        }
+        Ok(received)
     }
 
     /// Tags a payment with up to 10 arbitrary tag hashes.
@@ -12467,23 +18579,369 @@ impl PaymentContract {
         tax: i128,
     ) -> Result<u64, Error> {
         merchant.require_auth();
+        Self::do_attach_invoice(&env, merchant, payment_id, items, tax)
+    }
+
+    /// Attaches an invoice whose tax is computed from the registered tax rules
+    /// (see `set_tax_rule`) rather than supplied by the caller.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant who owns the payment (must authorize).
+    /// * `payment_id` - The payment to attach the invoice to.
+    /// * `items` - Line items; exclusive taxes are added on top of their amounts.
+    /// * `jurisdiction` - The jurisdiction code to look rules up under.
+    /// * `categories` - The product category hash of each line item.
+    ///
+    /// # Returns
+    /// The invoice ID on success. The per-line taxes are available from
+    /// `get_tax_breakdown`.
+    ///
+    /// # Errors
+    /// As `attach_invoice`, plus `TaxRuleNotFound` if a category has no rule
+    /// and `InvalidLineItem` if `categories` does not match `items`.
+    pub fn attach_invoice_with_tax(
+        env: Env,
+        merchant: Address,
+        payment_id: u64,
+        items: Vec<LineItem>,
+        jurisdiction: Symbol,
+        categories: Vec<BytesN<32>>,
+    ) -> Result<u64, Error> {
+        merchant.require_auth();
+        let (tax, breakdown) =
+            Self::compute_invoice_tax(&env, &merchant, &items, &jurisdiction, &categories)?;
+        let invoice_id = Self::do_attach_invoice(&env, merchant, payment_id, items, tax)?;
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::InvoiceTaxBreakdown(invoice_id)),
+            &breakdown,
+        );
+        Ok(invoice_id)
+    }
+
+    fn do_attach_invoice(
+        env: &Env,
+        merchant: Address,
+        payment_id: u64,
+        items: Vec<LineItem>,
+        tax: i128,
+    ) -> Result<u64, Error> {
+        // Check if payment exists and merchant is authorized
+        let payment = Self::get_payment(env, payment_id);
+        if payment.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+
+        // Check if invoice already attached
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
+        {
+            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        }
+
+        // Validate line items and calculate subtotal
+        let mut subtotal: i128 = 0;
+        for item in items.iter() {
+            if item.quantity == 0 || item.unit_price <= 0 || item.amount <= 0 {
+                return Err(Error::Payment(PaymentError::InvalidLineItem));
+            }
+            subtotal = subtotal
+                .checked_add(item.amount)
+                .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+        }
+
+        // Verify total
+        let total = subtotal
+            .checked_add(tax)
+            .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+        if total != payment.amount {
+            return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+        }
+
+        // Get next invoice ID
+        let invoice_id: u64 = env
+            .storage()
+            .persistent()
+            .get::<_, u64>(&DataKey::Payment(PaymentKey::InvoiceCounter))
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+
+        // Create and store invoice
+        let invoice = PaymentInvoice {
+            invoice_id,
+            payment_id,
+            items,
+            subtotal,
+            tax,
+            total,
+            issued_at: env.ledger().timestamp(),
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Payment(PaymentKey::Invoice(payment_id)), &invoice);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Payment(PaymentKey::InvoiceCounter), &invoice_id);
+        env.storage().persistent().set(
+            &DataKey::Payment(PaymentKey::InvoicePaymentId(invoice_id)),
+            &payment_id,
+        );
+
+        Ok(invoice_id)
+    }
+
+    /// Returns an invoice by its invoice ID.
+    ///
+    /// # Arguments
+    /// * `invoice_id` - The invoice ID.
+    ///
+    /// # Returns
+    /// `Some(PaymentInvoice)` if the invoice exists, `None` otherwise.
+    pub fn get_invoice(env: Env, invoice_id: u64) -> Option<PaymentInvoice> {
+        let payment_id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Payment(PaymentKey::InvoicePaymentId(invoice_id)))?;
+        env.storage()
+            .persistent()
+            .get(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
+    }
+
+    /// Returns the invoice attached to a specific payment.
+    ///
+    /// # Arguments
+    /// * `payment_id` - The payment ID.
+    ///
+    /// # Returns
+    /// `Some(PaymentInvoice)` if an invoice is attached, `None` otherwise.
+    pub fn get_payment_invoice(env: Env, payment_id: u64) -> Option<PaymentInvoice> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
+    }
+
+    /// Verifies that an invoice's stored total matches a recalculation of its line items.
+    ///
+    /// Useful for detecting tampering or data corruption.
+    ///
+    /// # Arguments
+    /// * `invoice_id` - The invoice ID.
+    ///
+    /// # Returns
+    /// `true` if the recalculation matches the stored total, `false` if
+    /// the invoice is not found or the totals do not match.
+    pub fn verify_invoice_total(env: Env, invoice_id: u64) -> bool {
+        if let Some(invoice) = Self::get_invoice(env, invoice_id) {
+            let mut calculated_subtotal: i128 = 0;
+            for item in invoice.items.iter() {
+                calculated_subtotal = calculated_subtotal.saturating_add(item.amount);
+            }
+            let calculated_total = calculated_subtotal.saturating_add(invoice.tax);
+            calculated_total == invoice.total
+        } else {
+            false
+        }
+    }
+
+    /// Registers the platform-wide tax rule for a product category in a
+    /// jurisdiction. Merchants can override it with `set_merchant_tax_rule`.
+    ///
+    /// # Errors
+    /// Returns `Unauthorized` if the caller is not an admin, or `InvalidTaxRule`
+    /// if a rate exceeds 10000 bps or the rule has more than `MAX_TAX_RATES` rates.
+    pub fn set_tax_rule(
+        env: Env,
+        admin: Address,
+        jurisdiction: Symbol,
+        category: BytesN<32>,
+        rule: TaxRule,
+    ) -> Result<(), Error> {
+        admin.require_auth();
+        let config: MultiSigConfig = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::MultiSigConfig))
+            .ok_or(Error::Basic(BasicError::MultiSigNotInitialized))?;
+        if !config.admins.contains(&admin) {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        Self::validate_tax_rule(&rule)?;
+
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::TaxRule(jurisdiction.clone(), category.clone())),
+            &rule,
+        );
+        (TaxRuleSet {
+            merchant: None,
+            jurisdiction,
+            category,
+            inclusive: rule.inclusive,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Registers a merchant's own tax rule for a product category in a
+    /// jurisdiction, taking precedence over the platform rule for the
+    /// merchant's invoices.
+    ///
+    /// # Errors
+    /// Returns `InvalidTaxRule` if a rate exceeds 10000 bps or the rule has more
+    /// than `MAX_TAX_RATES` rates.
+    pub fn set_merchant_tax_rule(
+        env: Env,
+        merchant: Address,
+        jurisdiction: Symbol,
+        category: BytesN<32>,
+        rule: TaxRule,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        Self::validate_tax_rule(&rule)?;
+
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::MerchantTaxRule(
+                merchant.clone(),
+                jurisdiction.clone(),
+                category.clone(),
+            )),
+            &rule,
+        );
+        (TaxRuleSet {
+            merchant: Some(merchant),
+            jurisdiction,
+            category,
+            inclusive: rule.inclusive,
+        })
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the tax rule that applies to `merchant`'s invoices for a product
+    /// category in a jurisdiction: the merchant's own rule, else the platform's.
+    pub fn get_tax_rule(
+        env: Env,
+        merchant: Address,
+        jurisdiction: Symbol,
+        category: BytesN<32>,
+    ) -> Option<TaxRule> {
+        record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::MerchantTaxRule(
+                merchant,
+                jurisdiction.clone(),
+                category.clone(),
+            )),
+        )
+        .or_else(|| {
+            record_get(
+                &env,
+                &DataKey::Payment(PaymentKey::TaxRule(jurisdiction, category)),
+            )
+        })
+    }
+
+    /// Returns the per-line taxes of an invoice: for `Attached`, those computed
+    /// by `attach_invoice_with_tax`; for `Issued`, those computed when an invoice
+    /// with a tax context was issued. Empty for invoices with a caller-supplied
+    /// tax or no tax context.
+    pub fn get_tax_breakdown(env: Env, kind: InvoiceKind, invoice_id: u64) -> Vec<TaxLine> {
+        let key = match kind {
+            InvoiceKind::Attached => PaymentKey::InvoiceTaxBreakdown(invoice_id),
+            InvoiceKind::Issued => PaymentKey::IssuedInvoiceTaxBreakdown(invoice_id),
+        };
+        record_get(&env, &DataKey::Payment(key)).unwrap_or_else(|| Vec::new(&env))
+    }
+
+    fn validate_tax_rule(rule: &TaxRule) -> Result<(), Error> {
+        if rule.rates.len() > MAX_TAX_RATES || rule.rates.iter().any(|r| r.rate_bps > 10_000) {
+            return Err(Error::Payment(PaymentError::InvalidTaxRule));
+        }
+        Ok(())
+    }
+
+    /// Computes the tax on `items` from the rules for `jurisdiction`. Returns the
+    /// exclusive tax to add to the line items and the per-line breakdown.
+    fn compute_invoice_tax(
+        env: &Env,
+        merchant: &Address,
+        items: &Vec<LineItem>,
+        jurisdiction: &Symbol,
+        categories: &Vec<BytesN<32>>,
+    ) -> Result<(i128, Vec<TaxLine>), Error> {
+        if categories.len() != items.len() {
+            return Err(Error::Payment(PaymentError::InvalidLineItem));
+        }
+        let mut tax: i128 = 0;
+        let mut breakdown = Vec::new(env);
+        for (line, (item, category)) in items.iter().zip(categories.iter()).enumerate() {
+            let rule = Self::get_tax_rule(
+                env.clone(),
+                merchant.clone(),
+                jurisdiction.clone(),
+                category.clone(),
+            )
+            .ok_or(Error::Payment(PaymentError::TaxRuleNotFound))?;
+            let line_tax = apply_tax_rule(
+                line as u32,
+                item.amount,
+                jurisdiction,
+                &category,
+                &rule,
+                &mut breakdown,
+            )?;
+            tax = tax
+                .checked_add(line_tax)
+                .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+        }
+        Ok((tax, breakdown))
+    }
+
+    /// Creates a draft invoice the merchant can issue to a customer with
+    /// `issue_invoice`.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant issuing the invoice (must authorize).
+    /// * `customer` - The customer expected to pay the invoice.
+    /// * `currency` - The invoice currency.
+    /// * `items` - Line items; at least one is required.
+    /// * `tax` - Total tax amount.
+    /// * `accepted_tokens` - Tokens the invoice can be paid in; empty accepts any allowed token.
+    /// * `due_date` - Timestamp after which late fees accrue.
+    /// * `late_fee` - The late fee policy.
+    ///
+    /// # Returns
+    /// The invoice ID on success.
+    ///
+    /// # Errors
+    /// Returns an error if a line item or the tax is invalid, the due date is
+    /// not in the future, or the late fee policy is invalid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_invoice(
+        env: Env,
+        merchant: Address,
+        customer: Address,
+        currency: Currency,
+        items: Vec<LineItem>,
+        tax: i128,
+        accepted_tokens: Vec<Address>,
+        due_date: u64,
+        late_fee: LateFeePolicy,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, "create_invoice")?;
+        merchant.require_auth();
 
-        // Check if payment exists and merchant is authorized
-        let payment = Self::get_payment(&env, payment_id);
-        if payment.merchant != merchant {
-            return Err(Error::Basic(BasicError::Unauthorized));
+        if !PaymentContract::is_valid_currency(&currency) {
+            return Err(Error::Basic(BasicError::InvalidCurrency));
         }
-
-        // Check if invoice already attached
-        if env
-            .storage()
-            .persistent()
-            .has(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
-        {
-            return Err(Error::Payment(PaymentError::AlreadyProcessed));
+        if items.is_empty() {
+            return Err(Error::Payment(PaymentError::InvalidLineItem));
         }
-
-        // Validate line items and calculate subtotal
         let mut subtotal: i128 = 0;
         for item in items.iter() {
             if item.quantity == 0 || item.unit_price <= 0 || item.amount <= 0 {
@@ -12493,99 +18951,393 @@ impl PaymentContract {
                 .checked_add(item.amount)
                 .ok_or(Error::Basic(BasicError::InvalidAmount))?;
         }
-
-        // Verify total
+        if tax < 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
         let total = subtotal
             .checked_add(tax)
             .ok_or(Error::Basic(BasicError::InvalidAmount))?;
-        if total != payment.amount {
-            return Err(Error::Payment(PaymentError::RefundExceedsPayment));
+
+        let now = env.ledger().timestamp();
+        if due_date <= now {
+            return Err(Error::Payment(PaymentError::InvalidDueDate));
+        }
+        if late_fee.bps_per_period > 10_000 {
+            return Err(Error::Basic(BasicError::InvalidBps));
+        }
+        if late_fee.bps_per_period > 0 && late_fee.period_seconds == 0 {
+            return Err(Error::Basic(BasicError::InvalidInterval));
+        }
+        if late_fee.max_fee < 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
         }
 
-        // Get next invoice ID
         let invoice_id: u64 = env
             .storage()
-            .persistent()
-            .get::<_, u64>(&DataKey::Payment(PaymentKey::InvoiceCounter))
-            .unwrap_or(0)
-            .checked_add(1)
-            .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+            .instance()
+            .get(&DataKey::Payment(PaymentKey::IssuedInvoiceCounter))
+            .unwrap_or(0u64)
+            + 1;
+        env.storage().instance().set(
+            &DataKey::Payment(PaymentKey::IssuedInvoiceCounter),
+            &invoice_id,
+        );
 
-        // Create and store invoice
-        let invoice = PaymentInvoice {
-            invoice_id,
-            payment_id,
+        let invoice = Invoice {
+            id: invoice_id,
+            merchant: merchant.clone(),
+            customer: customer.clone(),
+            currency,
             items,
             subtotal,
             tax,
             total,
-            issued_at: env.ledger().timestamp(),
+            accepted_tokens,
+            due_date,
+            late_fee,
+            status: InvoiceStatus::Draft,
+            created_at: now,
+            issued_at: 0,
+            payment_id: 0,
+            late_fee_charged: 0,
+            paid_at: 0,
         };
-
-        env.storage()
-            .persistent()
-            .set(&DataKey::Payment(PaymentKey::Invoice(payment_id)), &invoice);
-        env.storage()
-            .persistent()
-            .set(&DataKey::Payment(PaymentKey::InvoiceCounter), &invoice_id);
-        env.storage().persistent().set(
-            &DataKey::Payment(PaymentKey::InvoicePaymentId(invoice_id)),
-            &payment_id,
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoice(invoice_id)),
+            &invoice,
         );
 
+        (InvoiceCreated {
+            invoice_id,
+            merchant,
+            customer,
+            total,
+            due_date,
+        })
+        .publish(&env);
+
         Ok(invoice_id)
     }
 
-    /// Returns an invoice by its invoice ID.
+    /// Has `issue_invoice` compute a draft invoice's tax from the registered
+    /// tax rules, replacing the tax given to `create_invoice`.
     ///
     /// # Arguments
-    /// * `invoice_id` - The invoice ID.
+    /// * `merchant` - The invoice's merchant (must authorize).
+    /// * `invoice_id` - The draft invoice.
+    /// * `jurisdiction` - The jurisdiction code to look rules up under.
+    /// * `categories` - The product category hash of each line item.
     ///
-    /// # Returns
-    /// `Some(PaymentInvoice)` if the invoice exists, `None` otherwise.
-    pub fn get_invoice(env: Env, invoice_id: u64) -> Option<PaymentInvoice> {
-        let payment_id: u64 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Payment(PaymentKey::InvoicePaymentId(invoice_id)))?;
-        env.storage()
-            .persistent()
-            .get(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
+    /// # Errors
+    /// Returns `Unauthorized` if the caller is not the invoice's merchant,
+    /// `InvoiceNotOpen` if the invoice is not a draft, or `InvalidLineItem` if
+    /// `categories` does not match the line items.
+    pub fn set_invoice_tax_context(
+        env: Env,
+        merchant: Address,
+        invoice_id: u64,
+        jurisdiction: Symbol,
+        categories: Vec<BytesN<32>>,
+    ) -> Result<(), Error> {
+        merchant.require_auth();
+        let invoice = Self::load_invoice(&env, invoice_id)?;
+        if invoice.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if invoice.status != InvoiceStatus::Draft {
+            return Err(Error::Payment(PaymentError::InvoiceNotOpen));
+        }
+        if categories.len() != invoice.items.len() {
+            return Err(Error::Payment(PaymentError::InvalidLineItem));
+        }
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoiceTaxContext(invoice_id)),
+            &InvoiceTaxContext {
+                jurisdiction,
+                categories,
+            },
+        );
+        Ok(())
     }
 
-    /// Returns the invoice attached to a specific payment.
-    ///
-    /// # Arguments
-    /// * `payment_id` - The payment ID.
+    /// Issues a draft invoice, making it payable by its customer. If a tax
+    /// context was set, the tax is computed from the rules in force now.
     ///
-    /// # Returns
-    /// `Some(PaymentInvoice)` if an invoice is attached, `None` otherwise.
-    pub fn get_payment_invoice(env: Env, payment_id: u64) -> Option<PaymentInvoice> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Payment(PaymentKey::Invoice(payment_id)))
+    /// # Errors
+    /// Returns `Unauthorized` if the caller is not the invoice's merchant,
+    /// `InvoiceNotOpen` if the invoice is not a draft, or `InvalidDueDate` if
+    /// the due date has already passed.
+    pub fn issue_invoice(env: Env, merchant: Address, invoice_id: u64) -> Result<(), Error> {
+        merchant.require_auth();
+        let mut invoice = Self::load_invoice(&env, invoice_id)?;
+        if invoice.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if invoice.status != InvoiceStatus::Draft {
+            return Err(Error::Payment(PaymentError::InvoiceNotOpen));
+        }
+        let now = env.ledger().timestamp();
+        if invoice.due_date <= now {
+            return Err(Error::Payment(PaymentError::InvalidDueDate));
+        }
+
+        let context: Option<InvoiceTaxContext> = record_get(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoiceTaxContext(invoice_id)),
+        );
+        if let Some(context) = context {
+            let (tax, breakdown) = Self::compute_invoice_tax(
+                &env,
+                &merchant,
+                &invoice.items,
+                &context.jurisdiction,
+                &context.categories,
+            )?;
+            invoice.tax = tax;
+            invoice.total = invoice
+                .subtotal
+                .checked_add(tax)
+                .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+            record_set(
+                &env,
+                &DataKey::Payment(PaymentKey::IssuedInvoiceTaxBreakdown(invoice_id)),
+                &breakdown,
+            );
+        }
+
+        invoice.status = InvoiceStatus::Open;
+        invoice.issued_at = now;
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoice(invoice_id)),
+            &invoice,
+        );
+
+        (InvoiceIssued {
+            invoice_id,
+            customer: invoice.customer,
+            total: invoice.total,
+            due_date: invoice.due_date,
+        })
+        .publish(&env);
+        Ok(())
     }
 
-    /// Verifies that an invoice's stored total matches a recalculation of its line items.
+    /// Pays all or part of an open invoice.
     ///
-    /// Useful for detecting tampering or data corruption.
+    /// The first payment creates a pending payment for the invoice total plus
+    /// any accrued late fee; every payment is recorded as an installment on it
+    /// (see `pay_installment`), and later payments must use the same token.
+    /// Late fees accrued since the previous payment are added to the amount
+    /// owed before this payment is applied.
     ///
     /// # Arguments
-    /// * `invoice_id` - The invoice ID.
+    /// * `customer` - The invoice's customer (must authorize).
+    /// * `invoice_id` - The invoice to pay.
+    /// * `token` - The token to pay with; must be accepted by the invoice.
+    /// * `amount` - The amount to pay, at most the amount due.
     ///
     /// # Returns
-    /// `true` if the recalculation matches the stored total, `false` if
-    /// the invoice is not found or the totals do not match.
-    pub fn verify_invoice_total(env: Env, invoice_id: u64) -> bool {
-        if let Some(invoice) = Self::get_invoice(env, invoice_id) {
-            let mut calculated_subtotal: i128 = 0;
-            for item in invoice.items.iter() {
-                calculated_subtotal = calculated_subtotal.saturating_add(item.amount);
+    /// The amount still due after this payment. The invoice becomes `Paid`
+    /// and the payment settles once this reaches zero.
+    pub fn pay_invoice(
+        env: Env,
+        customer: Address,
+        invoice_id: u64,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "pay_invoice")?;
+        customer.require_auth();
+
+        let mut invoice = Self::load_invoice(&env, invoice_id)?;
+        if invoice.customer != customer {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if invoice.status != InvoiceStatus::Open {
+            return Err(Error::Payment(PaymentError::InvoiceNotOpen));
+        }
+        if amount <= 0 {
+            return Err(Error::Basic(BasicError::InvalidAmount));
+        }
+        if !invoice.accepted_tokens.is_empty() && !invoice.accepted_tokens.contains(&token) {
+            return Err(Error::Payment(PaymentError::TokenNotAccepted));
+        }
+
+        let late_fee = Self::invoice_late_fee(&env, &invoice);
+        if invoice.payment_id == 0 {
+            let amount_due = invoice
+                .total
+                .checked_add(late_fee)
+                .ok_or(Error::Basic(BasicError::InvalidAmount))?;
+            invoice.payment_id = PaymentContract::do_create_payment(
+                &env,
+                customer.clone(),
+                invoice.merchant.clone(),
+                amount_due,
+                token,
+                invoice.currency.clone(),
+                0,
+                String::from_str(&env, ""),
+            )?;
+        } else {
+            let mut payment = PaymentContract::get_payment(&env, invoice.payment_id);
+            if payment.token != token {
+                return Err(Error::Payment(PaymentError::TokenNotAccepted));
             }
-            let calculated_total = calculated_subtotal.saturating_add(invoice.tax);
-            calculated_total == invoice.total
+            let accrued = late_fee - invoice.late_fee_charged;
+            if accrued > 0 {
+                let outstanding =
+                    PaymentContract::get_outstanding_balance(env.clone(), invoice.payment_id);
+                payment.amount += accrued;
+                record_set(
+                    &env,
+                    &DataKey::Payment(PaymentKey::Data(invoice.payment_id)),
+                    &payment,
+                );
+                record_set(
+                    &env,
+                    &DataKey::Payment(PaymentKey::OutstandingBalance(invoice.payment_id)),
+                    &(outstanding + accrued),
+                );
+            }
+        }
+        invoice.late_fee_charged = late_fee;
+
+        let remaining =
+            PaymentContract::do_pay_installment(&env, customer, invoice.payment_id, amount)?;
+        (InvoicePaymentReceived {
+            invoice_id,
+            payment_id: invoice.payment_id,
+            amount,
+            late_fee,
+            remaining,
+        })
+        .publish(&env);
+
+        if remaining == 0 {
+            invoice.status = InvoiceStatus::Paid;
+            invoice.paid_at = env.ledger().timestamp();
+            (InvoicePaid {
+                invoice_id,
+                payment_id: invoice.payment_id,
+                amount_paid: invoice.total + late_fee,
+                paid_at: invoice.paid_at,
+            })
+            .publish(&env);
+        }
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoice(invoice_id)),
+            &invoice,
+        );
+        Ok(remaining)
+    }
+
+    /// Voids a draft or open invoice. Any partial payments already collected
+    /// are returned to the customer and the backing payment is cancelled.
+    ///
+    /// # Returns
+    /// The amount refunded to the customer.
+    ///
+    /// # Errors
+    /// Returns `Unauthorized` if the caller is not the invoice's merchant, or
+    /// `InvoiceNotOpen` if the invoice is already paid or void.
+    pub fn void_invoice(env: Env, merchant: Address, invoice_id: u64) -> Result<i128, Error> {
+        merchant.require_auth();
+        let mut invoice = Self::load_invoice(&env, invoice_id)?;
+        if invoice.merchant != merchant {
+            return Err(Error::Basic(BasicError::Unauthorized));
+        }
+        if !matches!(invoice.status, InvoiceStatus::Draft | InvoiceStatus::Open) {
+            return Err(Error::Payment(PaymentError::InvoiceNotOpen));
+        }
+
+        let mut refunded = 0;
+        if invoice.payment_id != 0 {
+            let payment = PaymentContract::get_payment(&env, invoice.payment_id);
+            refunded = payment.amount
+                - PaymentContract::get_outstanding_balance(env.clone(), invoice.payment_id);
+            if refunded > 0 {
+                token::Client::new(&env, &payment.token).transfer(
+                    &env.current_contract_address(),
+                    &invoice.customer,
+                    &refunded,
+                );
+            }
+            PaymentContract::do_cancel_payment(&env, merchant, invoice.payment_id)?;
+        }
+
+        invoice.status = InvoiceStatus::Void;
+        record_set(
+            &env,
+            &DataKey::Payment(PaymentKey::IssuedInvoice(invoice_id)),
+            &invoice,
+        );
+
+        (InvoiceVoided {
+            invoice_id,
+            refunded,
+            voided_at: env.ledger().timestamp(),
+        })
+        .publish(&env);
+        Ok(refunded)
+    }
+
+    /// Returns an invoice created with `create_invoice`, reporting an open
+    /// invoice past its due date as `Overdue`.
+    pub fn get_issued_invoice(env: Env, invoice_id: u64) -> Result<Invoice, Error> {
+        let mut invoice = Self::load_invoice(&env, invoice_id)?;
+        if invoice.status == InvoiceStatus::Open && env.ledger().timestamp() > invoice.due_date {
+            invoice.status = InvoiceStatus::Overdue;
+        }
+        Ok(invoice)
+    }
+
+    /// Returns the amount currently due on an invoice, including late fees
+    /// accrued up to now. Paid and void invoices owe nothing.
+    pub fn get_invoice_amount_due(env: Env, invoice_id: u64) -> Result<i128, Error> {
+        let invoice = Self::load_invoice(&env, invoice_id)?;
+        if matches!(invoice.status, InvoiceStatus::Paid | InvoiceStatus::Void) {
+            return Ok(0);
+        }
+        let owed = invoice.total + Self::invoice_late_fee(&env, &invoice);
+        if invoice.payment_id == 0 {
+            return Ok(owed);
+        }
+        let payment = PaymentContract::get_payment(&env, invoice.payment_id);
+        let paid = payment.amount
+            - PaymentContract::get_outstanding_balance(env.clone(), invoice.payment_id);
+        Ok(owed - paid)
+    }
+
+    fn load_invoice(env: &Env, invoice_id: u64) -> Result<Invoice, Error> {
+        record_get(
+            env,
+            &DataKey::Payment(PaymentKey::IssuedInvoice(invoice_id)),
+        )
+        .ok_or(Error::Payment(PaymentError::InvoiceNotFound))
+    }
+
+    /// Late fee accrued on `invoice` as of now.
+    fn invoice_late_fee(env: &Env, invoice: &Invoice) -> i128 {
+        let now = env.ledger().timestamp();
+        let policy = &invoice.late_fee;
+        if policy.bps_per_period == 0 || now <= invoice.due_date {
+            return 0;
+        }
+        let periods = (now - invoice.due_date).div_ceil(policy.period_seconds) as i128;
+        let fee = invoice
+            .total
+            .saturating_mul(policy.bps_per_period as i128)
+            .saturating_mul(periods)
+            / 10_000;
+        if policy.max_fee > 0 {
+            fee.min(policy.max_fee)
         } else {
-            false
+            fee
         }
     }
 
@@ -12643,3 +19395,66 @@ mod test_scheduled_payment;
 
 #[cfg(test)]
 mod schema_version_test;
+
+#[cfg(test)]
+mod test_record_storage;
+
+#[cfg(test)]
+mod test_refund_link;
+
+#[cfg(test)]
+mod test_upgrade;
+
+#[cfg(test)]
+mod test_timed_pause;
+
+#[cfg(test)]
+mod test_bidirectional_channel;
+
+#[cfg(test)]
+mod test_routing;
+
+#[cfg(test)]
+mod test_payment_intent;
+
+#[cfg(test)]
+mod test_passkey_channel;
+
+#[cfg(test)]
+mod test_fixture;
+
+#[cfg(test)]
+mod test_subscription_plans;
+
+#[cfg(test)]
+mod test_subscription_plan_change;
+
+#[cfg(test)]
+mod test_billing_anchor;
+
+#[cfg(test)]
+mod test_invoices;
+
+#[cfg(test)]
+mod test_tax;
+
+#[cfg(test)]
+mod test_chargeback;
+
+#[cfg(test)]
+mod test_rolling_reserve;
+
+#[cfg(test)]
+mod test_marketplace;
+
+#[cfg(test)]
+mod test_referrals;
+
+#[cfg(test)]
+mod test_wallet;
+
+#[cfg(test)]
+mod test_delegation;
+
+#[cfg(test)]
+mod test_campaigns;
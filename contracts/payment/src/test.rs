@@ -5078,6 +5078,20 @@ fn setup_fee_contract(
     (client, admin, contract_id, token_contract_id, token_admin)
 }
 
+/// Zeroes the risk surcharges so a payment is charged the flat `fee_bps` only.
+fn disable_risk_surcharges(client: &PaymentContractClient<'_>, admin: &Address) {
+    client.set_risk_fee_config(
+        admin,
+        &RiskFeeConfig {
+            base_fee_bps: 0,
+            large_amount_threshold: i128::MAX,
+            large_amount_surcharge_bps: 0,
+            new_customer_surcharge_bps: 0,
+            high_risk_currency_surcharge: 0,
+        },
+    );
+}
+
 #[test]
 fn test_set_and_get_fee_config() {
     let env = Env::default();
@@ -5126,6 +5140,7 @@ fn test_fee_deducted_from_payment_amount() {
         active: true,
     };
     client.set_fee_config(&admin, &fee_config);
+    disable_risk_surcharges(&client, &admin);
 
     token_client.mint(&customer, &amount);
     token_user_client.approve(&customer, &contract_id, &amount, &200);
@@ -5601,6 +5616,7 @@ fn test_withdraw_fees_to_treasury() {
         active: true,
     };
     client.set_fee_config(&admin, &fee_config);
+    disable_risk_surcharges(&client, &admin);
 
     token_client.mint(&customer, &amount);
     token_user_client.approve(&customer, &contract_id, &amount, &200);
@@ -0,0 +1,260 @@
+#![cfg(test)]
+
+extern crate alloc;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::rngs::OsRng;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Bytes, BytesN, Env,
+};
+
+const CHALLENGE_PERIOD: u64 = 3_600;
+
+struct Setup<'a> {
+    env: Env,
+    client: PaymentContractClient<'a>,
+    customer: Address,
+    merchant: Address,
+    token: token::Client<'a>,
+    customer_key: SigningKey,
+    merchant_key: SigningKey,
+    channel_id: u64,
+}
+
+/// Opens a channel with 1_000 from the customer and 200 from the merchant.
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_admin = token::StellarAssetClient::new(&env, &token_id);
+    token_admin.mint(&customer, &1_000);
+    token_admin.mint(&merchant, &200);
+
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+
+    let customer_key = SigningKey::generate(&mut OsRng);
+    let merchant_key = SigningKey::generate(&mut OsRng);
+    let channel_id = client.open_bidirectional_channel(
+        &customer,
+        &merchant,
+        &token_id,
+        &1_000,
+        &200,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &customer_key.verifying_key().to_bytes(),
+        )),
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &merchant_key.verifying_key().to_bytes(),
+        )),
+        &CHALLENGE_PERIOD,
+    );
+
+    Setup {
+        token: token::Client::new(&env, &token_id),
+        env,
+        client,
+        customer,
+        merchant,
+        customer_key,
+        merchant_key,
+        channel_id,
+    }
+}
+
+/// Signs a state with both keys, building the message the same way the
+/// contract does: `contract_address.to_xdr() || state.to_xdr()`.
+fn sign(
+    s: &Setup,
+    nonce: u64,
+    customer_balance: i128,
+    merchant_balance: i128,
+) -> SignedChannelState {
+    sign_state(s, nonce, customer_balance, merchant_balance, false)
+}
+
+fn sign_final(
+    s: &Setup,
+    nonce: u64,
+    customer_balance: i128,
+    merchant_balance: i128,
+) -> SignedChannelState {
+    sign_state(s, nonce, customer_balance, merchant_balance, true)
+}
+
+fn sign_state(
+    s: &Setup,
+    nonce: u64,
+    customer_balance: i128,
+    merchant_balance: i128,
+    is_final: bool,
+) -> SignedChannelState {
+    let state = ChannelState {
+        channel_id: s.channel_id,
+        nonce,
+        customer_balance,
+        merchant_balance,
+        is_final,
+    };
+    let mut msg = Bytes::new(&s.env);
+    msg.append(&s.client.address.clone().to_xdr(&s.env));
+    msg.append(&state.clone().to_xdr(&s.env));
+    let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
+
+    SignedChannelState {
+        state,
+        customer_signature: StateSignature::Ed25519(BytesN::from_array(
+            &s.env,
+            &s.customer_key.sign(&msg_vec).to_bytes(),
+        )),
+        merchant_signature: StateSignature::Ed25519(BytesN::from_array(
+            &s.env,
+            &s.merchant_key.sign(&msg_vec).to_bytes(),
+        )),
+    }
+}
+
+#[test]
+fn test_mutual_close_pays_out_both_directions() {
+    let s = setup();
+    assert_eq!(s.token.balance(&s.client.address), 1_200);
+
+    // The merchant paid 50 back to the customer after the customer spent 300.
+    s.client
+        .close_channel_mutual(&s.channel_id, &sign_final(&s, 2, 750, 450));
+
+    assert_eq!(s.token.balance(&s.customer), 750);
+    assert_eq!(s.token.balance(&s.merchant), 450);
+    let channel = s.client.get_bidirectional_channel(&s.channel_id);
+    assert_eq!(channel.status, BidirectionalChannelStatus::Closed);
+    assert_eq!(channel.nonce, 2);
+    assert_eq!(
+        s.client
+            .try_close_channel_mutual(&s.channel_id, &sign_final(&s, 3, 0, 1_200)),
+        Err(Ok(Error::Feature(FeatureError::ChannelClosed)))
+    );
+}
+
+#[test]
+fn test_mutual_close_requires_final_state() {
+    let s = setup();
+    // An intermediate state both parties signed while the channel was in use.
+    assert_eq!(
+        s.client
+            .try_close_channel_mutual(&s.channel_id, &sign(&s, 2, 750, 450)),
+        Err(Ok(Error::Feature(FeatureError::ChannelStateNotFinal)))
+    );
+    assert_eq!(
+        s.client.get_bidirectional_channel(&s.channel_id).status,
+        BidirectionalChannelStatus::Open
+    );
+
+    s.client
+        .close_channel_mutual(&s.channel_id, &sign_final(&s, 2, 750, 450));
+    assert_eq!(s.token.balance(&s.customer), 750);
+}
+
+#[test]
+fn test_newer_state_supersedes_unilateral_close() {
+    let s = setup();
+    // The customer submits a stale state that favours them.
+    s.client
+        .close_channel_unilateral(&s.customer, &s.channel_id, &Some(sign(&s, 1, 900, 300)));
+    let channel = s.client.get_bidirectional_channel(&s.channel_id);
+    assert_eq!(channel.status, BidirectionalChannelStatus::Closing);
+    assert_eq!(channel.close_deadline, 1_000 + CHALLENGE_PERIOD);
+    assert_eq!(
+        s.client.try_finalize_channel_close(&s.channel_id),
+        Err(Ok(Error::Feature(FeatureError::ChallengePeriodActive)))
+    );
+
+    assert_eq!(
+        s.client
+            .try_challenge_channel_close(&s.channel_id, &sign(&s, 1, 600, 600)),
+        Err(Ok(Error::Feature(FeatureError::InvalidNonce)))
+    );
+    s.client
+        .challenge_channel_close(&s.channel_id, &sign(&s, 4, 500, 700));
+
+    s.env.ledger().set_timestamp(1_000 + CHALLENGE_PERIOD);
+    assert_eq!(
+        s.client
+            .try_challenge_channel_close(&s.channel_id, &sign(&s, 5, 400, 800)),
+        Err(Ok(Error::Feature(FeatureError::ChallengePeriodOver)))
+    );
+    s.client.finalize_channel_close(&s.channel_id);
+
+    assert_eq!(s.token.balance(&s.customer), 500);
+    assert_eq!(s.token.balance(&s.merchant), 700);
+    assert_eq!(
+        s.client.get_bidirectional_channel(&s.channel_id).status,
+        BidirectionalChannelStatus::Closed
+    );
+}
+
+#[test]
+fn test_unilateral_close_without_state_returns_deposits() {
+    let s = setup();
+    s.client
+        .close_channel_unilateral(&s.merchant, &s.channel_id, &None);
+    s.env.ledger().set_timestamp(1_000 + CHALLENGE_PERIOD);
+    s.client.finalize_channel_close(&s.channel_id);
+
+    assert_eq!(s.token.balance(&s.customer), 1_000);
+    assert_eq!(s.token.balance(&s.merchant), 200);
+}
+
+#[test]
+fn test_mutual_close_ends_pending_unilateral_close() {
+    let s = setup();
+    s.client
+        .close_channel_unilateral(&s.customer, &s.channel_id, &Some(sign(&s, 3, 800, 400)));
+
+    assert_eq!(
+        s.client
+            .try_close_channel_mutual(&s.channel_id, &sign_final(&s, 2, 1_000, 200)),
+        Err(Ok(Error::Feature(FeatureError::InvalidNonce)))
+    );
+    s.client
+        .close_channel_mutual(&s.channel_id, &sign_final(&s, 3, 800, 400));
+    assert_eq!(s.token.balance(&s.customer), 800);
+    assert_eq!(
+        s.client.try_finalize_channel_close(&s.channel_id),
+        Err(Ok(Error::Feature(FeatureError::ChannelNotClosing)))
+    );
+}
+
+#[test]
+fn test_state_must_conserve_deposits_and_come_from_a_party() {
+    let s = setup();
+    assert_eq!(
+        s.client
+            .try_close_channel_mutual(&s.channel_id, &sign_final(&s, 1, 1_000, 1_000)),
+        Err(Ok(Error::Feature(FeatureError::BalanceSumMismatch)))
+    );
+    assert_eq!(
+        s.client
+            .try_close_channel_unilateral(&Address::generate(&s.env), &s.channel_id, &None),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_state_signed_by_one_party_is_rejected() {
+    let s = setup();
+    let mut signed = sign_final(&s, 1, 0, 1_200);
+    signed.merchant_signature = signed.customer_signature.clone();
+    s.client.close_channel_mutual(&s.channel_id, &signed);
+}
@@ -0,0 +1,388 @@
+#![cfg(test)]
+
+extern crate std;
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Env, String,
+};
+
+fn at(year: i64, month: u32, day: u32) -> u64 {
+    midnight_on(year, month, day)
+}
+
+fn date_of(timestamp: u64) -> (i64, u32, u32) {
+    civil_from_days((timestamp / SECONDS_PER_DAY) as i64)
+}
+
+// ── Calendar ────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_leap_year_rules() {
+    assert!(!is_leap_year(1900));
+    assert!(!is_leap_year(1970));
+    assert!(is_leap_year(1972));
+    assert!(is_leap_year(2000));
+    assert!(!is_leap_year(2023));
+    assert!(is_leap_year(2024));
+    assert!(!is_leap_year(2100));
+    assert!(is_leap_year(2400));
+}
+
+#[test]
+fn test_every_day_from_1970_to_2400_round_trips() {
+    let last = days_from_civil(2400, 12, 31);
+    let (mut year, mut month, mut day) = (1970, 1, 1);
+    let mut days_in_year = 0;
+    for days in 0..=last {
+        assert_eq!(civil_from_days(days), (year, month, day));
+        assert_eq!(days_from_civil(year, month, day), days);
+        days_in_year += 1;
+
+        if day < days_in_month(year, month) {
+            day += 1;
+        } else if month < 12 {
+            month += 1;
+            day = 1;
+        } else {
+            let expected = if is_leap_year(year) { 366 } else { 365 };
+            assert_eq!(days_in_year, expected, "year {}", year);
+            days_in_year = 0;
+            year += 1;
+            month = 1;
+            day = 1;
+        }
+    }
+    assert_eq!(year, 2401);
+}
+
+#[test]
+fn test_february_length_follows_leap_years() {
+    for year in 1970..=2400 {
+        let expected = if is_leap_year(year) { 29 } else { 28 };
+        assert_eq!(days_in_month(year, 2), expected, "year {}", year);
+        assert_eq!(
+            days_from_civil(year, 3, 1) - days_from_civil(year, 2, 1),
+            expected as i64
+        );
+    }
+}
+
+#[test]
+fn test_monthly_on_day_clamps_to_month_end() {
+    let anchor = BillingAnchor::MonthlyOnDay(31);
+    let mut due = at(2023, 1, 31);
+    let mut seen = std::vec::Vec::new();
+    for _ in 0..14 {
+        due = next_billing_time(&anchor, 0, due);
+        seen.push(date_of(due));
+    }
+    assert_eq!(
+        seen,
+        std::vec![
+            (2023, 2, 28),
+            (2023, 3, 31),
+            (2023, 4, 30),
+            (2023, 5, 31),
+            (2023, 6, 30),
+            (2023, 7, 31),
+            (2023, 8, 31),
+            (2023, 9, 30),
+            (2023, 10, 31),
+            (2023, 11, 30),
+            (2023, 12, 31),
+            (2024, 1, 31),
+            (2024, 2, 29),
+            (2024, 3, 31),
+        ]
+    );
+}
+
+#[test]
+fn test_monthly_on_day_within_month_and_at_midnight() {
+    let anchor = BillingAnchor::MonthlyOnDay(1);
+    // Mid-month, the 1st of the next month; on the 1st itself, the month after.
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2024, 1, 15) + 3_600),
+        at(2024, 2, 1)
+    );
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2024, 2, 1)),
+        at(2024, 3, 1)
+    );
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2024, 12, 1) + 1),
+        at(2025, 1, 1)
+    );
+    // Day 30 in February of leap and common years.
+    let anchor = BillingAnchor::MonthlyOnDay(30);
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2024, 2, 1)),
+        at(2024, 2, 29)
+    );
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2100, 2, 1)),
+        at(2100, 2, 28)
+    );
+    assert_eq!(
+        next_billing_time(&anchor, 0, at(2100, 2, 28)),
+        at(2100, 3, 30)
+    );
+}
+
+#[test]
+fn test_yearly_from_leap_day_returns_to_leap_day() {
+    let origin = at(2024, 2, 29) + 45_000;
+    let mut due = origin;
+    for year in 2025..=2404 {
+        due = next_billing_time(&BillingAnchor::Yearly, origin, due);
+        let expected_day = if is_leap_year(year) { 29 } else { 28 };
+        assert_eq!(date_of(due), (year, 2, expected_day));
+        assert_eq!(due % SECONDS_PER_DAY, 0);
+    }
+}
+
+#[test]
+fn test_yearly_keeps_ordinary_anniversary() {
+    let origin = at(2023, 7, 4);
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Yearly, origin, origin),
+        at(2024, 7, 4)
+    );
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Yearly, origin, at(2024, 1, 1)),
+        at(2024, 7, 4)
+    );
+}
+
+#[test]
+fn test_weekly_on_weekday() {
+    // 1970-01-01 was a Thursday.
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Weekly(3), 0, 0),
+        at(1970, 1, 8)
+    );
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Weekly(0), 0, 0),
+        at(1970, 1, 5)
+    );
+    // 2024-02-29 was a Thursday, so Friday is the next day across the leap day.
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Weekly(4), 0, at(2024, 2, 28) + 1),
+        at(2024, 3, 1)
+    );
+    assert_eq!(
+        next_billing_time(&BillingAnchor::Weekly(6), 0, at(2024, 2, 29)),
+        at(2024, 3, 3)
+    );
+}
+
+// ── Contract ────────────────────────────────────────────────────────────────
+
+fn setup<'a>(now: u64) -> (Env, PaymentContractClient<'a>, Address, Address, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(now);
+
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token).mint(&customer, &10_000);
+
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&Address::generate(&env));
+    token::Client::new(&env, &token).approve(&customer, &client.address, &10_000, &1_000);
+    (env, client, customer, merchant, token)
+}
+
+fn subscribe(
+    env: &Env,
+    client: &PaymentContractClient,
+    customer: &Address,
+    merchant: &Address,
+    token: &Address,
+    interval: u64,
+    trial: u64,
+) -> u64 {
+    client.create_subscription(
+        customer,
+        merchant,
+        &100,
+        token,
+        &Currency::USDC,
+        &interval,
+        &0,
+        &0,
+        &String::from_str(env, ""),
+        &trial,
+    )
+}
+
+#[test]
+fn test_monthly_subscription_bills_on_the_first() {
+    let (env, client, customer, merchant, token) = setup(at(2024, 1, 15) + 43_200);
+    let sub_id = subscribe(
+        &env,
+        &client,
+        &customer,
+        &merchant,
+        &token,
+        30 * SECONDS_PER_DAY,
+        0,
+    );
+
+    // The first charge was due on 14 February; it moves on to 1 March, never earlier.
+    let next =
+        client.set_subscription_billing_anchor(&merchant, &sub_id, &BillingAnchor::MonthlyOnDay(1));
+    assert_eq!(next, at(2024, 3, 1));
+    assert_eq!(
+        client.get_subscription_billing_anchor(&sub_id),
+        BillingAnchor::MonthlyOnDay(1)
+    );
+
+    for expected in [at(2024, 4, 1), at(2024, 5, 1), at(2024, 6, 1)] {
+        env.ledger()
+            .set_timestamp(client.get_subscription(&sub_id).next_payment_at);
+        client.execute_recurring_payment(&sub_id);
+        assert_eq!(client.get_subscription(&sub_id).next_payment_at, expected);
+    }
+    assert_eq!(token::Client::new(&env, &token).balance(&merchant), 300);
+}
+
+#[test]
+fn test_yearly_subscription_renews_on_leap_day_anniversary() {
+    let (env, client, customer, merchant, token) = setup(at(2024, 2, 29));
+    let sub_id = subscribe(
+        &env,
+        &client,
+        &customer,
+        &merchant,
+        &token,
+        365 * SECONDS_PER_DAY,
+        0,
+    );
+    client.set_subscription_billing_anchor(&merchant, &sub_id, &BillingAnchor::Yearly);
+
+    let mut renewals = std::vec::Vec::new();
+    for _ in 0..4 {
+        let due = client.get_subscription(&sub_id).next_payment_at;
+        renewals.push(date_of(due));
+        env.ledger().set_timestamp(due);
+        client.execute_recurring_payment(&sub_id);
+    }
+    assert_eq!(
+        renewals,
+        std::vec![(2025, 2, 28), (2026, 2, 28), (2027, 2, 28), (2028, 2, 29)]
+    );
+}
+
+#[test]
+fn test_billing_anchor_validation() {
+    let (env, client, customer, merchant, token) = setup(at(2024, 1, 1));
+    let sub_id = subscribe(
+        &env,
+        &client,
+        &customer,
+        &merchant,
+        &token,
+        SECONDS_PER_DAY,
+        0,
+    );
+
+    for anchor in [
+        BillingAnchor::MonthlyOnDay(0),
+        BillingAnchor::MonthlyOnDay(32),
+        BillingAnchor::Weekly(7),
+        BillingAnchor::FixedSeconds(0),
+    ] {
+        assert_eq!(
+            client.try_set_subscription_billing_anchor(&merchant, &sub_id, &anchor),
+            Err(Ok(Error::Basic(BasicError::InvalidInterval)))
+        );
+    }
+    assert_eq!(
+        client.try_set_subscription_billing_anchor(&customer, &sub_id, &BillingAnchor::Yearly),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+}
+
+#[test]
+fn test_group_next_billing_skips_trial_cycles() {
+    let (env, client, customer, merchant, token) = setup(at(2024, 1, 10));
+    let trialing = subscribe(
+        &env,
+        &client,
+        &customer,
+        &merchant,
+        &token,
+        SECONDS_PER_DAY,
+        10 * SECONDS_PER_DAY,
+    );
+    client.set_subscription_billing_anchor(&merchant, &trialing, &BillingAnchor::Weekly(0));
+    let group_id = client.create_subscription_group(&merchant, &0);
+    client.add_to_group(&merchant, &group_id, &trialing);
+
+    // Due Monday 15 January, but the trial runs to the 20th: billed Monday the 22nd.
+    assert_eq!(
+        client.get_subscription(&trialing).next_payment_at,
+        at(2024, 1, 15)
+    );
+    assert_eq!(client.get_group_next_billing(&group_id), at(2024, 1, 22));
+
+    let paying = subscribe(
+        &env,
+        &client,
+        &customer,
+        &merchant,
+        &token,
+        3 * SECONDS_PER_DAY,
+        0,
+    );
+    client.add_to_group(&merchant, &group_id, &paying);
+    assert_eq!(client.get_group_next_billing(&group_id), at(2024, 1, 13));
+}
+
+#[test]
+fn test_monthly_payouts_follow_the_calendar() {
+    let (env, client, customer, merchant, token) = setup(at(2024, 1, 31) + 600);
+    client.set_payout_schedule(&merchant, &PayoutFrequency::Monthly, &token);
+    assert_eq!(
+        client
+            .get_payout_schedule(&merchant)
+            .unwrap()
+            .next_payout_at,
+        at(2024, 2, 29)
+    );
+
+    let mut payouts = std::vec::Vec::new();
+    for _ in 0..3 {
+        let now = env.ledger().timestamp();
+        let pid = client.schedule_payment(&customer, &merchant, &token, &10, &(now + 10));
+        env.ledger().set_timestamp(now + 10);
+        client.execute_scheduled_payment(&pid);
+
+        let due = client
+            .get_payout_schedule(&merchant)
+            .unwrap()
+            .next_payout_at;
+        env.ledger().set_timestamp(due);
+        client.trigger_scheduled_payout(&merchant);
+        payouts.push(date_of(
+            client
+                .get_payout_schedule(&merchant)
+                .unwrap()
+                .next_payout_at,
+        ));
+    }
+    assert_eq!(
+        payouts,
+        std::vec![(2024, 3, 31), (2024, 4, 30), (2024, 5, 31)]
+    );
+
+    // Pinning to the 1st moves the payout due on 31 May to the next 1st after now.
+    let next = client.set_payout_anchor(&merchant, &BillingAnchor::MonthlyOnDay(1));
+    assert_eq!(next, at(2024, 5, 1));
+}
@@ -0,0 +1,355 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Campaign, CampaignReward, CampaignStatus, CampaignTerms, Currency, DataKey, Error,
+    FeatureError, FeatureKey, LoyaltyConfig, PaymentError, DEFAULT_CHARGEBACK_WINDOW,
+    SECONDS_PER_DAY,
+};
+
+const END: u64 = START + 30 * SECONDS_PER_DAY;
+
+fn terms(s: &Setup, reward_bps: u32, fixed_reward: i128) -> CampaignTerms {
+    CampaignTerms {
+        token: s.token.address.clone(),
+        reward: CampaignReward::Cashback,
+        starts_at: START,
+        ends_at: END,
+        reward_bps,
+        fixed_reward,
+        min_amount: 0,
+        per_customer_cap: 0,
+        first_purchase_only: false,
+    }
+}
+
+fn pay(s: &Setup, amount: i128) -> u64 {
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.complete_payment(&s.admin, &payment_id);
+    payment_id
+}
+
+/// The total cashback held on a payment.
+fn held(s: &Setup, payment_id: u64) -> i128 {
+    s.client
+        .get_held_cashback(&payment_id)
+        .iter()
+        .map(|h| h.amount)
+        .sum()
+}
+
+#[test]
+fn test_percentage_cashback_capped_per_customer() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let mut cashback = terms(&s, 500, 0);
+    cashback.per_customer_cap = 30;
+    let campaign_id = s.client.create_campaign(&s.merchant, &cashback, &500);
+    assert_eq!(s.token.balance(&s.merchant), 500);
+    assert_eq!(s.token.balance(&s.client.address), 500);
+    assert_eq!(
+        s.client.get_merchant_campaigns(&s.merchant),
+        soroban_sdk::vec![&s.env, campaign_id]
+    );
+
+    // 5% of 400 is 20, then only 10 is left under the cap, then nothing.
+    let first = pay(&s, 400);
+    assert_eq!(held(&s, first), 20);
+    let second = pay(&s, 400);
+    assert_eq!(held(&s, second), 10);
+    let third = pay(&s, 400);
+    assert_eq!(held(&s, third), 0);
+    assert_eq!(s.token.balance(&s.customer), 8_800);
+
+    assert_eq!(s.client.get_campaign_rewards(&campaign_id, &s.customer), 30);
+    let campaign = s.client.get_campaign(&campaign_id).unwrap();
+    assert_eq!(campaign.budget, 470);
+    assert_eq!(campaign.total_rewarded, 30);
+    assert_eq!(campaign.status, CampaignStatus::Active);
+}
+
+#[test]
+fn test_first_purchase_bonus_and_spend_threshold() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let mut first_purchase = terms(&s, 0, 50);
+    first_purchase.first_purchase_only = true;
+    s.client.create_campaign(&s.merchant, &first_purchase, &200);
+    let mut threshold = terms(&s, 0, 25);
+    threshold.min_amount = 500;
+    s.client.create_campaign(&s.merchant, &threshold, &200);
+
+    let first = pay(&s, 600);
+    assert_eq!(held(&s, first), 75);
+    let second = pay(&s, 600);
+    assert_eq!(held(&s, second), 25);
+    let third = pay(&s, 100);
+    assert_eq!(held(&s, third), 0);
+}
+
+#[test]
+fn test_campaign_stops_on_exhaustion_and_budget_is_reclaimed() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let campaign_id = s
+        .client
+        .create_campaign(&s.merchant, &terms(&s, 1_000, 0), &30);
+
+    let first = pay(&s, 200);
+    let second = pay(&s, 200);
+    let campaign = s.client.get_campaign(&campaign_id).unwrap();
+    assert_eq!(campaign.budget, 0);
+    assert_eq!(campaign.status, CampaignStatus::Exhausted);
+    assert_eq!(held(&s, first) + held(&s, second), 30);
+    let third = pay(&s, 200);
+    assert_eq!(held(&s, third), 0);
+
+    assert_eq!(s.client.fund_campaign(&s.merchant, &campaign_id, &50), 50);
+    assert_eq!(
+        s.client.get_campaign(&campaign_id).unwrap().status,
+        CampaignStatus::Active
+    );
+    let fourth = pay(&s, 100);
+    assert_eq!(held(&s, fourth), 10);
+
+    assert_eq!(
+        s.client
+            .try_reclaim_campaign_budget(&s.merchant, &campaign_id),
+        Err(Ok(Error::Payment(PaymentError::CampaignNotEnded)))
+    );
+    s.env.ledger().set_timestamp(END);
+    let fifth = pay(&s, 100);
+    assert_eq!(held(&s, fifth), 0);
+    assert_eq!(
+        s.client
+            .try_reclaim_campaign_budget(&Address::generate(&s.env), &campaign_id),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+
+    let merchant_before = s.token.balance(&s.merchant);
+    assert_eq!(
+        s.client.reclaim_campaign_budget(&s.merchant, &campaign_id),
+        40
+    );
+    assert_eq!(s.token.balance(&s.merchant), merchant_before + 40);
+    assert_eq!(
+        s.client.get_campaign(&campaign_id).unwrap().status,
+        CampaignStatus::Closed
+    );
+    assert!(s.client.get_merchant_campaigns(&s.merchant).is_empty());
+    assert_eq!(
+        s.client
+            .try_reclaim_campaign_budget(&s.merchant, &campaign_id),
+        Err(Ok(Error::Payment(PaymentError::CampaignClosed)))
+    );
+    assert_eq!(
+        s.client.try_fund_campaign(&s.merchant, &campaign_id, &10),
+        Err(Ok(Error::Payment(PaymentError::CampaignClosed)))
+    );
+}
+
+#[test]
+fn test_points_campaign_credits_loyalty_points() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let mut points = terms(&s, 0, 40);
+    points.reward = CampaignReward::Points;
+    assert_eq!(
+        s.client.try_create_campaign(&s.merchant, &points, &100),
+        Err(Ok(Error::Feature(FeatureError::LoyaltyNotConfigured)))
+    );
+
+    s.client.configure_loyalty(
+        &s.admin,
+        &LoyaltyConfig {
+            points_per_unit: 1_000_000,
+            redemption_rate: 1,
+            expiry_seconds: SECONDS_PER_DAY,
+            active: true,
+        },
+    );
+    let campaign_id = s.client.create_campaign(&s.merchant, &points, &100);
+    // A points budget is an allowance of points, so no tokens are deposited.
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+
+    pay(&s, 300);
+    assert_eq!(
+        s.client.get_loyalty_balance(&s.customer).unwrap().points,
+        40
+    );
+    assert_eq!(s.token.balance(&s.customer), 9_700);
+    assert_eq!(s.client.get_campaign(&campaign_id).unwrap().budget, 60);
+}
+
+#[test]
+fn test_create_campaign_validation() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let mut backwards = terms(&s, 500, 0);
+    backwards.ends_at = START;
+    assert_eq!(
+        s.client.try_create_campaign(&s.merchant, &backwards, &100),
+        Err(Ok(Error::Basic(BasicError::InvalidInterval)))
+    );
+    assert_eq!(
+        s.client
+            .try_create_campaign(&s.merchant, &terms(&s, 10_001, 0), &100),
+        Err(Ok(Error::Basic(BasicError::InvalidBps)))
+    );
+    assert_eq!(
+        s.client
+            .try_create_campaign(&s.merchant, &terms(&s, 0, 0), &100),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    assert_eq!(
+        s.client
+            .try_create_campaign(&s.merchant, &terms(&s, 500, 0), &0),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    assert_eq!(
+        s.client.try_fund_campaign(&s.merchant, &7, &10),
+        Err(Ok(Error::Payment(PaymentError::CampaignNotFound)))
+    );
+}
+
+#[test]
+fn test_cashback_released_after_chargeback_window() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    s.client
+        .create_campaign(&s.merchant, &terms(&s, 500, 0), &500);
+
+    let payment_id = pay(&s, 400);
+    assert_eq!(held(&s, payment_id), 20);
+    assert_eq!(s.token.balance(&s.customer), 9_600);
+    assert_eq!(
+        s.client.try_release_campaign_cashback(&payment_id),
+        Err(Ok(Error::Payment(PaymentError::NotYetDue)))
+    );
+
+    s.env
+        .ledger()
+        .set_timestamp(START + DEFAULT_CHARGEBACK_WINDOW + 1);
+    assert_eq!(s.client.release_campaign_cashback(&payment_id), 20);
+    assert_eq!(s.token.balance(&s.customer), 9_620);
+    assert!(s.client.get_held_cashback(&payment_id).is_empty());
+    assert_eq!(
+        s.client.try_release_campaign_cashback(&payment_id),
+        Err(Ok(Error::Payment(PaymentError::NothingToSettle)))
+    );
+}
+
+#[test]
+fn test_cashback_kept_while_campaign_unreadable() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let campaign_id = s
+        .client
+        .create_campaign(&s.merchant, &terms(&s, 500, 0), &500);
+    let payment_id = pay(&s, 400);
+    s.env
+        .ledger()
+        .set_timestamp(START + DEFAULT_CHARGEBACK_WINDOW + 1);
+
+    // An archived campaign record cannot be settled against.
+    let key = DataKey::Feature(FeatureKey::Campaign(campaign_id));
+    let campaign: Campaign = s.env.as_contract(&s.client.address, || {
+        let campaign = s.env.storage().persistent().get(&key).unwrap();
+        s.env.storage().persistent().remove(&key);
+        campaign
+    });
+    assert_eq!(
+        s.client.try_release_campaign_cashback(&payment_id),
+        Err(Ok(Error::Payment(PaymentError::CampaignNotFound)))
+    );
+    assert_eq!(held(&s, payment_id), 20);
+
+    s.env.as_contract(&s.client.address, || {
+        s.env.storage().persistent().set(&key, &campaign);
+    });
+    assert_eq!(s.client.release_campaign_cashback(&payment_id), 20);
+    assert_eq!(s.token.balance(&s.customer), 9_620);
+}
+
+#[test]
+fn test_refunds_and_chargebacks_forfeit_cashback() {
+    let s = setup(10_000);
+    s.mint(&s.merchant, 1_000);
+    s.approve(10_000);
+    let refund_contract = Address::generate(&s.env);
+    s.client.set_refund_contract(&s.admin, &refund_contract);
+    let campaign_id = s
+        .client
+        .create_campaign(&s.merchant, &terms(&s, 500, 0), &500);
+
+    // A fully refunded payment forfeits its cashback straight away.
+    let refunded = pay(&s, 400);
+    s.client.settle_refund(
+        &refund_contract,
+        &refunded,
+        &s.merchant,
+        &s.token.address,
+        &400,
+    );
+    assert_eq!(s.client.release_campaign_cashback(&refunded), 0);
+    let campaign = s.client.get_campaign(&campaign_id).unwrap();
+    assert_eq!(campaign.budget, 500);
+    assert_eq!(campaign.total_rewarded, 0);
+    assert_eq!(s.client.get_campaign_rewards(&campaign_id, &s.customer), 0);
+
+    // A partial refund and a lost chargeback each forfeit their share.
+    let partly_refunded = pay(&s, 400);
+    s.client.settle_refund(
+        &refund_contract,
+        &partly_refunded,
+        &s.merchant,
+        &s.token.address,
+        &100,
+    );
+    let disputed = pay(&s, 400);
+    s.client.open_chargeback(
+        &s.customer,
+        &disputed,
+        &200,
+        &String::from_str(&s.env, "item not received"),
+    );
+    assert_eq!(s.client.get_campaign(&campaign_id).unwrap().budget, 460);
+
+    s.env
+        .ledger()
+        .set_timestamp(START + DEFAULT_CHARGEBACK_WINDOW + 1);
+    assert_eq!(
+        s.client.try_release_campaign_cashback(&disputed),
+        Err(Ok(Error::Payment(PaymentError::ChargebackPending)))
+    );
+    s.client.resolve_chargeback(&s.admin, &disputed, &true);
+
+    let customer_before = s.token.balance(&s.customer);
+    assert_eq!(s.client.release_campaign_cashback(&partly_refunded), 15);
+    assert_eq!(s.client.release_campaign_cashback(&disputed), 10);
+    assert_eq!(s.token.balance(&s.customer), customer_before + 25);
+    let campaign = s.client.get_campaign(&campaign_id).unwrap();
+    assert_eq!(campaign.budget, 475);
+    assert_eq!(campaign.total_rewarded, 25);
+    assert_eq!(s.client.get_campaign_rewards(&campaign_id, &s.customer), 25);
+}
@@ -0,0 +1,343 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, BytesN, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, ChargebackDebt, ChargebackStatus, Currency, DataKey, Error, FinalityConfig,
+    PaymentError, PaymentKey, PaymentStatus, PayoutFrequency, CHARGEBACK_RESPONSE_PERIOD,
+    DEFAULT_CHARGEBACK_WINDOW,
+};
+
+/// Creates a payment and pays it in full as a single installment, so the
+/// contract settles it through the merchant's payout schedule.
+fn paid_payment(s: &Setup, customer: &Address, amount: i128) -> u64 {
+    let payment_id = s.client.create_payment(
+        customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.pay_installment(customer, &payment_id, &amount);
+    payment_id
+}
+
+fn reason(s: &Setup) -> String {
+    String::from_str(&s.env, "item not received")
+}
+
+#[test]
+fn test_customer_win_refunds_funds_held_from_balance() {
+    let s = setup(10_000);
+    s.client
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Daily, &s.token.address);
+    let payment_id = paid_payment(&s, &s.customer, 300);
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 300);
+
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &200, &reason(&s));
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 100);
+    let chargeback = s.client.get_chargeback(&payment_id).unwrap();
+    assert_eq!(chargeback.status, ChargebackStatus::Open);
+    assert_eq!(chargeback.held_from_balance, 200);
+
+    let evidence = BytesN::from_array(&s.env, &[7; 32]);
+    s.client
+        .submit_chargeback_evidence(&s.merchant, &payment_id, &evidence);
+    let chargeback = s.client.get_chargeback(&payment_id).unwrap();
+    assert_eq!(chargeback.status, ChargebackStatus::Represented);
+    assert_eq!(chargeback.evidence_hash, Some(evidence));
+
+    s.client.resolve_chargeback(&s.admin, &payment_id, &true);
+    assert_eq!(s.token.balance(&s.customer), 9_900);
+    let payment = s.client.get_payment(&payment_id);
+    assert_eq!(payment.status, PaymentStatus::PartialRefunded);
+    assert_eq!(payment.refunded_amount, 200);
+    assert_eq!(
+        s.client.get_chargeback(&payment_id).unwrap().status,
+        ChargebackStatus::CustomerWon
+    );
+    assert_eq!(
+        s.client
+            .try_resolve_chargeback(&s.admin, &payment_id, &false),
+        Err(Ok(Error::Payment(PaymentError::AlreadyProcessed)))
+    );
+}
+
+#[test]
+fn test_merchant_win_restores_held_balance() {
+    let s = setup(10_000);
+    s.client
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Daily, &s.token.address);
+    let payment_id = paid_payment(&s, &s.customer, 300);
+
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &300, &reason(&s));
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 0);
+
+    s.client.resolve_chargeback(&s.admin, &payment_id, &false);
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 300);
+    assert_eq!(
+        s.client.get_payment(&payment_id).status,
+        PaymentStatus::Completed
+    );
+    assert_eq!(
+        s.client.get_chargeback(&payment_id).unwrap().status,
+        ChargebackStatus::MerchantWon
+    );
+}
+
+#[test]
+fn test_merchant_win_restores_pending_settlement() {
+    let s = setup(10_000);
+    token::StellarAssetClient::new(&s.env, &s.token.address).mint(&s.client.address, &1_000);
+    s.client.configure_finality_delay(
+        &s.admin,
+        &FinalityConfig {
+            delay_seconds: 3_600,
+            min_amount_threshold: 100,
+            active: true,
+        },
+    );
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &500,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &200, &reason(&s));
+    let settlement = s
+        .client
+        .get_pending_settlements(&s.merchant)
+        .get(0)
+        .unwrap();
+    assert_eq!(settlement.amount, 300);
+    assert_eq!(
+        s.client
+            .get_chargeback(&payment_id)
+            .unwrap()
+            .held_from_settlement,
+        200
+    );
+
+    s.client.resolve_chargeback(&s.admin, &payment_id, &false);
+    s.env.ledger().set_timestamp(START + 3_600);
+    s.client.finalize_pending_settlement(&payment_id);
+    assert_eq!(s.token.balance(&s.merchant), 500);
+}
+
+#[test]
+fn test_unheld_loss_is_recovered_from_later_settlements() {
+    let s = setup(10_000);
+    // Without a payout schedule the merchant is paid at once, so nothing can be held.
+    let payment_id = paid_payment(&s, &s.customer, 300);
+    assert_eq!(s.token.balance(&s.merchant), 300);
+
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &300, &reason(&s));
+    assert_eq!(
+        s.client
+            .get_chargeback(&payment_id)
+            .unwrap()
+            .held_from_balance,
+        0
+    );
+    s.client.resolve_chargeback(&s.admin, &payment_id, &true);
+    assert_eq!(s.token.balance(&s.customer), 9_700);
+    assert_eq!(
+        s.client.get_chargeback_debts(&s.merchant),
+        soroban_sdk::vec![
+            &s.env,
+            ChargebackDebt {
+                payment_id,
+                customer: s.customer.clone(),
+                token: s.token.address.clone(),
+                amount: 300,
+            }
+        ]
+    );
+
+    let other = Address::generate(&s.env);
+    token::StellarAssetClient::new(&s.env, &s.token.address).mint(&other, &1_000);
+    paid_payment(&s, &other, 200);
+    assert_eq!(s.token.balance(&s.customer), 9_900);
+    assert_eq!(s.token.balance(&s.merchant), 300);
+    assert_eq!(
+        s.client
+            .get_chargeback_debts(&s.merchant)
+            .get(0)
+            .unwrap()
+            .amount,
+        100
+    );
+
+    paid_payment(&s, &other, 500);
+    assert_eq!(s.token.balance(&s.customer), 10_000);
+    assert_eq!(s.token.balance(&s.merchant), 700);
+    assert!(s.client.get_chargeback_debts(&s.merchant).is_empty());
+}
+
+#[test]
+fn test_chargeback_eligibility() {
+    let s = setup(10_000);
+    let pending = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &300,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.customer, &pending, &100, &reason(&s)),
+        Err(Ok(Error::Payment(PaymentError::InvalidStatus)))
+    );
+    // A partial refund moves the payment to PartialRefunded, but its funds
+    // were never collected, so there is nothing to charge back.
+    s.client.partial_refund(&s.admin, &pending, &100);
+    assert_eq!(
+        s.client.get_payment(&pending).status,
+        PaymentStatus::PartialRefunded
+    );
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.customer, &pending, &100, &reason(&s)),
+        Err(Ok(Error::Payment(PaymentError::InvalidStatus)))
+    );
+
+    let payment_id = paid_payment(&s, &s.customer, 300);
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.merchant, &payment_id, &100, &reason(&s)),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.customer, &payment_id, &301, &reason(&s)),
+        Err(Ok(Error::Payment(PaymentError::RefundExceedsPayment)))
+    );
+
+    s.env
+        .ledger()
+        .set_timestamp(START + DEFAULT_CHARGEBACK_WINDOW + 1);
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.customer, &payment_id, &100, &reason(&s)),
+        Err(Ok(Error::Payment(PaymentError::ChargebackWindowClosed)))
+    );
+    s.client
+        .set_chargeback_window(&s.admin, &(DEFAULT_CHARGEBACK_WINDOW + 10));
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &100, &reason(&s));
+    assert_eq!(
+        s.client
+            .try_open_chargeback(&s.customer, &payment_id, &100, &reason(&s)),
+        Err(Ok(Error::Payment(PaymentError::AlreadyProcessed)))
+    );
+
+    s.env
+        .ledger()
+        .set_timestamp(START + DEFAULT_CHARGEBACK_WINDOW + 2 + CHARGEBACK_RESPONSE_PERIOD);
+    assert_eq!(
+        s.client.try_submit_chargeback_evidence(
+            &s.merchant,
+            &payment_id,
+            &BytesN::from_array(&s.env, &[7; 32])
+        ),
+        Err(Ok(Error::Payment(PaymentError::Expired)))
+    );
+}
+
+#[test]
+fn test_refunds_blocked_while_chargeback_pending() {
+    let s = setup(10_000);
+    s.client
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Daily, &s.token.address);
+    let refund_contract = Address::generate(&s.env);
+    s.client.set_refund_contract(&s.admin, &refund_contract);
+    let payment_id = paid_payment(&s, &s.customer, 300);
+
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &200, &reason(&s));
+    assert_eq!(
+        s.client.try_settle_refund(
+            &refund_contract,
+            &payment_id,
+            &s.merchant,
+            &s.token.address,
+            &100,
+        ),
+        Err(Ok(Error::Payment(PaymentError::ChargebackPending)))
+    );
+
+    s.client.submit_chargeback_evidence(
+        &s.merchant,
+        &payment_id,
+        &BytesN::from_array(&s.env, &[7; 32]),
+    );
+    assert_eq!(
+        s.client.try_settle_refund(
+            &refund_contract,
+            &payment_id,
+            &s.merchant,
+            &s.token.address,
+            &100,
+        ),
+        Err(Ok(Error::Payment(PaymentError::ChargebackPending)))
+    );
+
+    s.client.resolve_chargeback(&s.admin, &payment_id, &false);
+    s.client.settle_refund(
+        &refund_contract,
+        &payment_id,
+        &s.merchant,
+        &s.token.address,
+        &100,
+    );
+    assert_eq!(s.client.get_payment(&payment_id).refunded_amount, 100);
+}
+
+#[test]
+fn test_customer_win_is_capped_at_unrefunded_amount() {
+    let s = setup(10_000);
+    s.client
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Daily, &s.token.address);
+    let payment_id = paid_payment(&s, &s.customer, 300);
+    s.client
+        .open_chargeback(&s.customer, &payment_id, &200, &reason(&s));
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 100);
+
+    // A refund booked while the chargeback was open, before refunds were
+    // blocked during disputes.
+    let mut payment = s.client.get_payment(&payment_id);
+    payment.refunded_amount = 200;
+    payment.status = PaymentStatus::PartialRefunded;
+    s.env.as_contract(&s.client.address, || {
+        s.env
+            .storage()
+            .persistent()
+            .set(&DataKey::Payment(PaymentKey::Data(payment_id)), &payment);
+    });
+
+    s.client.resolve_chargeback(&s.admin, &payment_id, &true);
+    assert_eq!(s.token.balance(&s.customer), 9_800);
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 200);
+    let payment = s.client.get_payment(&payment_id);
+    assert_eq!(payment.refunded_amount, 300);
+    assert_eq!(payment.status, PaymentStatus::Refunded);
+    assert!(s.client.get_chargeback_debts(&s.merchant).is_empty());
+}
@@ -0,0 +1,286 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, BytesN, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, DelegateScope, DelegateSpend, Error, PaymentError, PaymentStatus,
+};
+
+const DAY: u64 = 86_400;
+
+fn scope(s: &Setup, period_cap: i128) -> DelegateScope {
+    DelegateScope {
+        period_cap,
+        period_seconds: DAY,
+        merchants: vec![&s.env],
+        categories: vec![&s.env],
+        tokens: vec![&s.env],
+        expires_at: 0,
+    }
+}
+
+fn category(s: &Setup, n: u8) -> BytesN<32> {
+    BytesN::from_array(&s.env, &[n; 32])
+}
+
+fn spend(
+    s: &Setup,
+    delegate: &Address,
+    merchant: &Address,
+    amount: i128,
+) -> Result<u64, Result<Error, soroban_sdk::InvokeError>> {
+    s.client
+        .try_create_delegated_payment(
+            delegate,
+            &s.customer,
+            merchant,
+            &amount,
+            &s.token.address,
+            &Currency::USDC,
+            &String::from_str(&s.env, ""),
+        )
+        .map(|r| r.unwrap())
+}
+
+#[test]
+fn test_delegate_creates_payment_for_customer() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    assert_eq!(
+        spend(&s, &delegate, &s.merchant, 100),
+        Err(Ok(Error::Payment(PaymentError::DelegationNotFound)))
+    );
+
+    s.client
+        .grant_delegate(&s.customer, &delegate, &scope(&s, 1_000));
+    assert_eq!(
+        s.client.get_delegates(&s.customer),
+        vec![&s.env, delegate.clone()]
+    );
+
+    let payment_id = spend(&s, &delegate, &s.merchant, 400).unwrap();
+    let payment = s.client.get_payment(&payment_id);
+    assert_eq!(payment.customer, s.customer);
+    assert_eq!(payment.amount, 400);
+    assert_eq!(payment.status, PaymentStatus::Pending);
+
+    // The payment completes against the customer's own allowance.
+    s.token
+        .approve(&s.customer, &s.client.address, &400, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+    assert_eq!(s.token.balance(&s.merchant), 400);
+    assert_eq!(s.token.balance(&s.customer), 9_600);
+
+    let delegation = s.client.get_delegation(&s.customer, &delegate).unwrap();
+    assert_eq!(delegation.spent_in_period, 400);
+    assert_eq!(delegation.total_spent, 400);
+    assert_eq!(
+        s.client
+            .get_delegate_spend_history(&s.customer, &delegate, &0),
+        vec![
+            &s.env,
+            DelegateSpend {
+                payment_id,
+                merchant: s.merchant.clone(),
+                token: s.token.address.clone(),
+                amount: 400,
+                category: None,
+                spent_at: START,
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_payment_outside_scope_is_rejected() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    let mut limited = scope(&s, 1_000);
+    let other_merchant = Address::generate(&s.env);
+    limited.merchants = vec![&s.env, s.merchant.clone(), other_merchant.clone()];
+    limited.categories = vec![&s.env, category(&s, 1)];
+    limited.tokens = vec![&s.env, s.token.address.clone()];
+    s.client.grant_delegate(&s.customer, &delegate, &limited);
+
+    let violation = Err(Ok(Error::Payment(PaymentError::DelegationScopeViolation)));
+    assert_eq!(
+        spend(&s, &delegate, &Address::generate(&s.env), 100),
+        violation
+    );
+    // Merchants without a registered category are outside a category scope.
+    assert_eq!(spend(&s, &delegate, &s.merchant, 100), violation);
+    s.client
+        .set_merchant_category(&s.admin, &s.merchant, &Some(category(&s, 1)));
+    s.client
+        .set_merchant_category(&s.admin, &other_merchant, &Some(category(&s, 2)));
+    assert_eq!(spend(&s, &delegate, &other_merchant, 100), violation);
+
+    let other_token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    assert_eq!(
+        s.client
+            .try_create_delegated_payment(
+                &delegate,
+                &s.customer,
+                &s.merchant,
+                &100,
+                &other_token,
+                &Currency::USDC,
+                &String::from_str(&s.env, ""),
+            )
+            .map(|r| r.unwrap()),
+        violation
+    );
+
+    let payment_id = spend(&s, &delegate, &s.merchant, 100).unwrap();
+    let recorded = s
+        .client
+        .get_delegate_spend_history(&s.customer, &delegate, &0)
+        .get(0)
+        .unwrap();
+    assert_eq!(recorded.payment_id, payment_id);
+    assert_eq!(recorded.category, Some(category(&s, 1)));
+}
+
+#[test]
+fn test_set_merchant_category() {
+    let s = setup(10_000);
+    assert_eq!(
+        s.client
+            .try_set_merchant_category(&s.merchant, &s.merchant, &Some(category(&s, 1))),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    s.client
+        .set_merchant_category(&s.admin, &s.merchant, &Some(category(&s, 1)));
+    assert_eq!(
+        s.client.get_merchant_category(&s.merchant),
+        Some(category(&s, 1))
+    );
+    s.client.set_merchant_category(&s.admin, &s.merchant, &None);
+    assert_eq!(s.client.get_merchant_category(&s.merchant), None);
+}
+
+#[test]
+fn test_spend_history_is_paged() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    s.client
+        .grant_delegate(&s.customer, &delegate, &scope(&s, 10_000));
+    for _ in 0..101 {
+        spend(&s, &delegate, &s.merchant, 1).unwrap();
+    }
+
+    assert_eq!(
+        s.client.get_delegate_spend_count(&s.customer, &delegate),
+        101
+    );
+    assert_eq!(
+        s.client
+            .get_delegate_spend_history(&s.customer, &delegate, &0)
+            .len(),
+        100
+    );
+    let last = s
+        .client
+        .get_delegate_spend_history(&s.customer, &delegate, &1);
+    assert_eq!(last.len(), 1);
+    assert_eq!(last.get(0).unwrap().payment_id, 101);
+}
+
+#[test]
+fn test_period_cap_resets_each_period() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    s.client
+        .grant_delegate(&s.customer, &delegate, &scope(&s, 500));
+
+    spend(&s, &delegate, &s.merchant, 300).unwrap();
+    assert_eq!(
+        spend(&s, &delegate, &s.merchant, 201),
+        Err(Ok(Error::Payment(PaymentError::DelegationCapExceeded)))
+    );
+    spend(&s, &delegate, &s.merchant, 200).unwrap();
+
+    s.env.ledger().set_timestamp(START + DAY + 1);
+    spend(&s, &delegate, &s.merchant, 500).unwrap();
+    let delegation = s.client.get_delegation(&s.customer, &delegate).unwrap();
+    assert_eq!(delegation.spent_in_period, 500);
+    assert_eq!(delegation.total_spent, 1_000);
+    assert_eq!(
+        s.client
+            .get_delegate_spend_history(&s.customer, &delegate, &0)
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_expiry_and_revocation() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    let mut expiring = scope(&s, 1_000);
+    expiring.expires_at = START + DAY;
+    s.client.grant_delegate(&s.customer, &delegate, &expiring);
+    spend(&s, &delegate, &s.merchant, 100).unwrap();
+
+    s.env.ledger().set_timestamp(START + DAY);
+    assert_eq!(
+        spend(&s, &delegate, &s.merchant, 100),
+        Err(Ok(Error::Payment(PaymentError::DelegationExpired)))
+    );
+
+    s.client.revoke_delegate(&s.customer, &delegate);
+    assert!(s.client.get_delegation(&s.customer, &delegate).is_none());
+    assert!(s.client.get_delegates(&s.customer).is_empty());
+    assert_eq!(
+        spend(&s, &delegate, &s.merchant, 100),
+        Err(Ok(Error::Payment(PaymentError::DelegationNotFound)))
+    );
+    assert_eq!(
+        s.client.try_revoke_delegate(&s.customer, &delegate),
+        Err(Ok(Error::Payment(PaymentError::DelegationNotFound)))
+    );
+    // The spend history outlives the delegation.
+    assert_eq!(
+        s.client
+            .get_delegate_spend_history(&s.customer, &delegate, &0)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_grant_delegate_validation() {
+    let s = setup(10_000);
+    let delegate = Address::generate(&s.env);
+    assert_eq!(
+        s.client
+            .try_grant_delegate(&s.customer, &s.customer, &scope(&s, 100)),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client
+            .try_grant_delegate(&s.customer, &delegate, &scope(&s, 0)),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    let mut no_period = scope(&s, 100);
+    no_period.period_seconds = 0;
+    assert_eq!(
+        s.client
+            .try_grant_delegate(&s.customer, &delegate, &no_period),
+        Err(Ok(Error::Basic(BasicError::InvalidInterval)))
+    );
+    let mut expired = scope(&s, 100);
+    expired.expires_at = START;
+    assert_eq!(
+        s.client
+            .try_grant_delegate(&s.customer, &delegate, &expired),
+        Err(Ok(Error::Payment(PaymentError::DelegationExpired)))
+    );
+}
@@ -0,0 +1,62 @@
+#![cfg(test)]
+//! Shared setup for the payment contract tests.
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Env,
+};
+
+use crate::{PaymentContract, PaymentContractClient};
+
+/// The ledger timestamp `setup` starts at.
+pub const START: u64 = 1_000;
+
+pub struct Setup<'a> {
+    pub env: Env,
+    pub client: PaymentContractClient<'a>,
+    pub admin: Address,
+    pub customer: Address,
+    pub merchant: Address,
+    pub token: token::Client<'a>,
+}
+
+/// Registers and initializes the payment contract at `START` with all auths
+/// mocked, and mints `customer_balance` of a fresh token to the customer.
+pub fn setup<'a>(customer_balance: i128) -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(START);
+
+    let admin = Address::generate(&env);
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let s = Setup {
+        token: token::Client::new(&env, &token_id),
+        customer: Address::generate(&env),
+        merchant: Address::generate(&env),
+        env,
+        client,
+        admin,
+    };
+    if customer_balance > 0 {
+        s.mint(&s.customer, customer_balance);
+    }
+    s
+}
+
+impl Setup<'_> {
+    /// Mints `amount` of the setup token to `to`.
+    pub fn mint(&self, to: &Address, amount: i128) {
+        token::StellarAssetClient::new(&self.env, &self.token.address).mint(to, &amount);
+    }
+
+    /// Lets the contract pull up to `amount` of the setup token from the customer.
+    pub fn approve(&self, amount: i128) {
+        self.token
+            .approve(&self.customer, &self.client.address, &amount, &1_000);
+    }
+}
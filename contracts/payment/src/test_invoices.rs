@@ -0,0 +1,291 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, BytesN, Vec,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, Error, InvoiceStatus, LateFeePolicy, LineItem, PaymentError,
+    PaymentStatus, SECONDS_PER_DAY,
+};
+
+const DUE: u64 = START + 10 * SECONDS_PER_DAY;
+
+fn no_late_fee() -> LateFeePolicy {
+    LateFeePolicy {
+        bps_per_period: 0,
+        period_seconds: 0,
+        max_fee: 0,
+    }
+}
+
+/// Creates and issues an invoice for 900 plus 100 tax, payable in the setup token.
+fn issue(s: &Setup, late_fee: LateFeePolicy) -> u64 {
+    let items = vec![
+        &s.env,
+        LineItem {
+            description_hash: BytesN::from_array(&s.env, &[1; 32]),
+            quantity: 3,
+            unit_price: 300,
+            amount: 900,
+        },
+    ];
+    let invoice_id = s.client.create_invoice(
+        &s.merchant,
+        &s.customer,
+        &Currency::USDC,
+        &items,
+        &100,
+        &vec![&s.env, s.token.address.clone()],
+        &DUE,
+        &late_fee,
+    );
+    s.client.issue_invoice(&s.merchant, &invoice_id);
+    invoice_id
+}
+
+#[test]
+fn test_invoice_paid_in_installments() {
+    let s = setup(10_000);
+    let items = vec![
+        &s.env,
+        LineItem {
+            description_hash: BytesN::from_array(&s.env, &[1; 32]),
+            quantity: 1,
+            unit_price: 1_000,
+            amount: 1_000,
+        },
+    ];
+    let invoice_id = s.client.create_invoice(
+        &s.merchant,
+        &s.customer,
+        &Currency::USDC,
+        &items,
+        &0,
+        &vec![&s.env],
+        &DUE,
+        &no_late_fee(),
+    );
+    assert_eq!(
+        s.client.get_issued_invoice(&invoice_id).status,
+        InvoiceStatus::Draft
+    );
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &invoice_id, &s.token.address, &400),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotOpen)))
+    );
+
+    s.client.issue_invoice(&s.merchant, &invoice_id);
+    assert_eq!(
+        s.client
+            .pay_invoice(&s.customer, &invoice_id, &s.token.address, &400),
+        600
+    );
+    let invoice = s.client.get_issued_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Open);
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 600);
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &invoice_id, &s.token.address, &700),
+        Err(Ok(Error::Payment(
+            PaymentError::InstallmentExceedsRemaining
+        )))
+    );
+
+    assert_eq!(
+        s.client
+            .pay_invoice(&s.customer, &invoice_id, &s.token.address, &600),
+        0
+    );
+    let invoice = s.client.get_issued_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Paid);
+    assert_eq!(invoice.paid_at, START);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+    assert_eq!(
+        s.client.get_payment(&invoice.payment_id).status,
+        PaymentStatus::Completed
+    );
+    assert_eq!(
+        s.client.get_installment_history(&invoice.payment_id).len(),
+        2
+    );
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &invoice_id, &s.token.address, &1),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotOpen)))
+    );
+}
+
+#[test]
+fn test_late_fee_accrues_per_period_up_to_cap() {
+    let s = setup(10_000);
+    let invoice_id = issue(
+        &s,
+        LateFeePolicy {
+            bps_per_period: 100,
+            period_seconds: SECONDS_PER_DAY,
+            max_fee: 25,
+        },
+    );
+
+    s.env.ledger().set_timestamp(DUE);
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 1_000);
+    assert_eq!(
+        s.client.get_issued_invoice(&invoice_id).status,
+        InvoiceStatus::Open
+    );
+
+    // One started day late: 1% of the 1_000 total.
+    s.env.ledger().set_timestamp(DUE + 1);
+    assert_eq!(
+        s.client.get_issued_invoice(&invoice_id).status,
+        InvoiceStatus::Overdue
+    );
+    assert_eq!(
+        s.client
+            .pay_invoice(&s.customer, &invoice_id, &s.token.address, &500),
+        510
+    );
+
+    // Two days late: the fee grows to 20 and the extra 10 is added to what is owed.
+    s.env.ledger().set_timestamp(DUE + SECONDS_PER_DAY + 1);
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 520);
+
+    // Five days late: capped at 25.
+    s.env.ledger().set_timestamp(DUE + 4 * SECONDS_PER_DAY + 1);
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 525);
+    assert_eq!(
+        s.client
+            .pay_invoice(&s.customer, &invoice_id, &s.token.address, &525),
+        0
+    );
+
+    let invoice = s.client.get_issued_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Paid);
+    assert_eq!(invoice.late_fee_charged, 25);
+    assert_eq!(s.token.balance(&s.merchant), 1_025);
+}
+
+#[test]
+fn test_pay_invoice_checks_customer_and_token() {
+    let s = setup(10_000);
+    let invoice_id = issue(&s, no_late_fee());
+
+    let other_token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &invoice_id, &other_token, &100),
+        Err(Ok(Error::Payment(PaymentError::TokenNotAccepted)))
+    );
+    assert_eq!(
+        s.client.try_pay_invoice(
+            &Address::generate(&s.env),
+            &invoice_id,
+            &s.token.address,
+            &100
+        ),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &99, &s.token.address, &100),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotFound)))
+    );
+}
+
+#[test]
+fn test_void_invoice_refunds_partial_payments() {
+    let s = setup(10_000);
+    let invoice_id = issue(&s, no_late_fee());
+    s.client
+        .pay_invoice(&s.customer, &invoice_id, &s.token.address, &300);
+    assert_eq!(s.token.balance(&s.customer), 9_700);
+
+    assert_eq!(
+        s.client
+            .try_void_invoice(&Address::generate(&s.env), &invoice_id),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(s.client.void_invoice(&s.merchant, &invoice_id), 300);
+    assert_eq!(s.token.balance(&s.customer), 10_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+
+    let invoice = s.client.get_issued_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::Void);
+    assert_eq!(
+        s.client.get_payment(&invoice.payment_id).status,
+        PaymentStatus::Cancelled
+    );
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 0);
+    assert_eq!(
+        s.client
+            .try_pay_invoice(&s.customer, &invoice_id, &s.token.address, &700),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotOpen)))
+    );
+    assert_eq!(
+        s.client.try_void_invoice(&s.merchant, &invoice_id),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotOpen)))
+    );
+}
+
+#[test]
+fn test_create_invoice_validation() {
+    let s = setup(10_000);
+    let create = |items: Vec<LineItem>, due_date: u64, late_fee: LateFeePolicy| {
+        s.client.try_create_invoice(
+            &s.merchant,
+            &s.customer,
+            &Currency::USDC,
+            &items,
+            &0,
+            &vec![&s.env],
+            &due_date,
+            &late_fee,
+        )
+    };
+    let items = vec![
+        &s.env,
+        LineItem {
+            description_hash: BytesN::from_array(&s.env, &[1; 32]),
+            quantity: 1,
+            unit_price: 100,
+            amount: 100,
+        },
+    ];
+
+    assert_eq!(
+        create(vec![&s.env], DUE, no_late_fee()),
+        Err(Ok(Error::Payment(PaymentError::InvalidLineItem)))
+    );
+    assert_eq!(
+        create(items.clone(), START, no_late_fee()),
+        Err(Ok(Error::Payment(PaymentError::InvalidDueDate)))
+    );
+    assert_eq!(
+        create(
+            items.clone(),
+            DUE,
+            LateFeePolicy {
+                bps_per_period: 100,
+                period_seconds: 0,
+                max_fee: 0,
+            }
+        ),
+        Err(Ok(Error::Basic(BasicError::InvalidInterval)))
+    );
+
+    // A draft whose due date passes before it is issued cannot be issued.
+    let invoice_id = create(items, DUE, no_late_fee()).unwrap().unwrap();
+    s.env.ledger().set_timestamp(DUE);
+    assert_eq!(
+        s.client.try_issue_invoice(&s.merchant, &invoice_id),
+        Err(Ok(Error::Payment(PaymentError::InvalidDueDate)))
+    );
+}
@@ -0,0 +1,248 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, token, vec, Address, String};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    ApplicationFee, BasicError, Currency, Error, FinalityConfig, PaymentError, PaymentStatus,
+    PlatformPayment, SubMerchant,
+};
+
+/// Registers the setup merchant as a sub-merchant of a new platform.
+fn connect(s: &Setup) -> Address {
+    let platform = Address::generate(&s.env);
+    s.client.register_sub_merchant(&platform, &s.merchant);
+    platform
+}
+
+/// 10 fixed plus 5% of the payment amount.
+fn fee() -> ApplicationFee {
+    ApplicationFee {
+        fixed: 10,
+        bps: 500,
+    }
+}
+
+fn platform_payment(s: &Setup, platform: &Address, amount: i128) -> u64 {
+    s.client.create_platform_payment(
+        &s.customer,
+        platform,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &String::from_str(&s.env, ""),
+        &fee(),
+    )
+}
+
+#[test]
+fn test_sub_merchant_registration() {
+    let s = setup(10_000);
+    let platform = connect(&s);
+    assert_eq!(
+        s.client.get_sub_merchant(&s.merchant),
+        Some(SubMerchant {
+            platform: platform.clone(),
+            sub_merchant: s.merchant.clone(),
+            registered_at: START,
+        })
+    );
+    assert_eq!(
+        s.client.get_platform_sub_merchants(&platform),
+        vec![&s.env, s.merchant.clone()]
+    );
+
+    let other_platform = Address::generate(&s.env);
+    assert_eq!(
+        s.client
+            .try_register_sub_merchant(&other_platform, &s.merchant),
+        Err(Ok(Error::Payment(PaymentError::AlreadyProcessed)))
+    );
+    assert_eq!(
+        s.client
+            .try_remove_sub_merchant(&other_platform, &s.merchant),
+        Err(Ok(Error::Payment(PaymentError::SubMerchantNotFound)))
+    );
+
+    s.client.remove_sub_merchant(&platform, &s.merchant);
+    assert_eq!(s.client.get_sub_merchant(&s.merchant), None);
+    assert!(s.client.get_platform_sub_merchants(&platform).is_empty());
+    assert_eq!(
+        s.client.try_create_platform_payment(
+            &s.customer,
+            &platform,
+            &s.merchant,
+            &1_000,
+            &s.token.address,
+            &Currency::USDC,
+            &String::from_str(&s.env, ""),
+            &fee(),
+        ),
+        Err(Ok(Error::Payment(PaymentError::SubMerchantNotFound)))
+    );
+}
+
+#[test]
+fn test_application_fee_routed_on_completion() {
+    let s = setup(10_000);
+    let platform = connect(&s);
+    let payment_id = platform_payment(&s, &platform, 1_000);
+    assert_eq!(
+        s.client.get_platform_payment(&payment_id).unwrap(),
+        PlatformPayment {
+            payment_id,
+            platform: platform.clone(),
+            application_fee: 60,
+            fee_collected: 0,
+        }
+    );
+    assert_eq!(s.client.get_payment(&payment_id).merchant, s.merchant);
+
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+    assert_eq!(s.token.balance(&platform), 60);
+    assert_eq!(s.token.balance(&s.merchant), 940);
+    assert_eq!(
+        s.client
+            .get_platform_payment(&payment_id)
+            .unwrap()
+            .fee_collected,
+        60
+    );
+
+    let sub_fees = s.client.get_merchant_fee_record(&s.merchant);
+    assert_eq!(sub_fees.total_fees_paid, 60);
+    assert_eq!(sub_fees.total_volume, 1_000);
+    let platform_fees = s.client.get_merchant_fee_record(&platform);
+    assert_eq!(platform_fees.total_fees_paid, 0);
+    assert_eq!(platform_fees.total_volume, 1_000);
+
+    let platform_analytics = s.client.get_merchant_analytics(&platform);
+    assert_eq!(platform_analytics.total_payments, 1);
+    assert_eq!(platform_analytics.total_volume, 1_000);
+    assert_eq!(platform_analytics.total_completed, 1);
+    assert_eq!(
+        s.client.get_merchant_analytics(&s.merchant).total_completed,
+        1
+    );
+}
+
+#[test]
+fn test_application_fee_routed_from_installments() {
+    let s = setup(10_000);
+    let platform = connect(&s);
+    let payment_id = platform_payment(&s, &platform, 500);
+    s.client.pay_installment(&s.customer, &payment_id, &200);
+    s.client.pay_installment(&s.customer, &payment_id, &300);
+
+    assert_eq!(s.token.balance(&platform), 35);
+    assert_eq!(s.token.balance(&s.merchant), 465);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+    assert_eq!(
+        s.client
+            .get_merchant_fee_record(&s.merchant)
+            .total_fees_paid,
+        35
+    );
+}
+
+#[test]
+fn test_platform_refunds_on_behalf_of_sub_merchant() {
+    let s = setup(10_000);
+    let platform = connect(&s);
+    token::StellarAssetClient::new(&s.env, &s.token.address).mint(&s.client.address, &1_000);
+    s.client.configure_finality_delay(
+        &s.admin,
+        &FinalityConfig {
+            delay_seconds: 3_600,
+            min_amount_threshold: 100,
+            active: true,
+        },
+    );
+    let payment_id = platform_payment(&s, &platform, 1_000);
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+    assert_eq!(s.token.balance(&platform), 60);
+    let customer_balance = s.token.balance(&s.customer);
+
+    assert_eq!(
+        s.client
+            .try_refund_platform_payment(&Address::generate(&s.env), &payment_id, &100),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+
+    // The held settlement covers 940; the platform pays the last 60.
+    assert_eq!(
+        s.client
+            .refund_platform_payment(&platform, &payment_id, &1_000),
+        60
+    );
+    assert_eq!(s.token.balance(&platform), 0);
+    assert_eq!(s.token.balance(&s.customer), customer_balance + 1_000);
+    assert_eq!(
+        s.client.get_payment(&payment_id).status,
+        PaymentStatus::Refunded
+    );
+    for merchant in [&platform, &s.merchant] {
+        let analytics = s.client.get_merchant_analytics(merchant);
+        assert_eq!(analytics.total_refunded, 1);
+        assert_eq!(analytics.total_refunded_volume, 1_000);
+    }
+    assert_eq!(
+        s.client
+            .try_refund_platform_payment(&platform, &payment_id, &1),
+        Err(Ok(Error::Payment(PaymentError::AlreadyProcessed)))
+    );
+}
+
+#[test]
+fn test_application_fee_validation() {
+    let s = setup(10_000);
+    let platform = connect(&s);
+    let create = |application_fee: ApplicationFee| {
+        s.client.try_create_platform_payment(
+            &s.customer,
+            &platform,
+            &s.merchant,
+            &100,
+            &s.token.address,
+            &Currency::USDC,
+            &String::from_str(&s.env, ""),
+            &application_fee,
+        )
+    };
+    assert_eq!(
+        create(ApplicationFee {
+            fixed: 0,
+            bps: 10_001
+        }),
+        Err(Ok(Error::Basic(BasicError::InvalidBps)))
+    );
+    assert_eq!(
+        create(ApplicationFee {
+            fixed: 60,
+            bps: 5_000
+        }),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    assert_eq!(
+        create(ApplicationFee { fixed: -1, bps: 0 }),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    assert_eq!(
+        s.client.try_create_platform_payment(
+            &s.customer,
+            &Address::generate(&s.env),
+            &s.merchant,
+            &100,
+            &s.token.address,
+            &Currency::USDC,
+            &String::from_str(&s.env, ""),
+            &fee(),
+        ),
+        Err(Ok(Error::Payment(PaymentError::SubMerchantNotFound)))
+    );
+}
@@ -0,0 +1,367 @@
+#![cfg(test)]
+
+extern crate alloc;
+
+use super::*;
+use ed25519_dalek::Signer as _;
+use p256::ecdsa::Signature;
+use rand::rngs::OsRng;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Bytes, BytesN, Env,
+};
+
+// WebAuthn assertion produced offline by a P-256 key (private scalar
+// sha256("facilpay passkey test vector")) for the `settle_channel` message of
+// channel 1, merchant_amount 600, nonce 1 on the rpId `pay.facilpay.io`.
+const PASSKEY_PUBLIC_KEY: [u8; 65] = [
+    0x04, 0x9d, 0x84, 0x36, 0xdc, 0x13, 0xe5, 0xdb, 0xc8, 0xfa, 0xcf, 0xde, 0x23, 0xc8, 0xc6, 0x71,
+    0x3f, 0x2d, 0x21, 0x56, 0xf0, 0x84, 0xd9, 0xc8, 0x97, 0x46, 0xa7, 0x87, 0x37, 0x8f, 0x1b, 0xa4,
+    0xe8, 0xae, 0x2f, 0xce, 0x7f, 0x6b, 0x59, 0xe9, 0x67, 0xc0, 0x5b, 0x70, 0x42, 0x99, 0x85, 0xca,
+    0x12, 0x75, 0x87, 0x22, 0x35, 0x96, 0xcb, 0x33, 0xc0, 0x72, 0x36, 0x0a, 0x18, 0x2a, 0x9d, 0xe5,
+    0x5d,
+];
+const PASSKEY_PRIVATE_KEY: [u8; 32] = [
+    0x99, 0xb9, 0xcf, 0xc9, 0x9e, 0xa4, 0xce, 0x21, 0x7b, 0x9f, 0xd3, 0xe8, 0xb2, 0xbd, 0x2c, 0x27,
+    0x0b, 0x68, 0x81, 0x64, 0x55, 0xbe, 0xd0, 0xf3, 0x82, 0xa4, 0xb2, 0x2a, 0x52, 0xa7, 0x7b, 0x9d,
+];
+const AUTHENTICATOR_DATA: [u8; 37] = [
+    0xf1, 0x38, 0xe2, 0xd5, 0x0f, 0x29, 0x6e, 0x4c, 0xd5, 0x27, 0x80, 0x9b, 0x07, 0x5f, 0x24, 0x49,
+    0xa8, 0x05, 0x25, 0x88, 0xda, 0x11, 0x7b, 0x45, 0xe4, 0xdf, 0xac, 0x02, 0x1f, 0xb6, 0xdd, 0x3d,
+    0x05, 0x00, 0x00, 0x00, 0x01,
+];
+const CLIENT_DATA_JSON: &str = r#"{"type":"webauthn.get","challenge":"kXWtcgekQ88FaZ6XpCjGt3h3P3kjf9WOqSMb3VpFrKU","origin":"https://pay.facilpay.io","crossOrigin":false}"#;
+const ASSERTION_SIGNATURE: [u8; 64] = [
+    0xfa, 0x08, 0xc3, 0x0f, 0xed, 0x43, 0x57, 0x13, 0x26, 0xd2, 0x0e, 0x12, 0x68, 0x0d, 0xf7, 0x0e,
+    0x90, 0xa9, 0x92, 0x7d, 0x75, 0xec, 0x19, 0x8b, 0x45, 0xbd, 0x28, 0xd8, 0x42, 0xdc, 0x08, 0x58,
+    0x15, 0xf1, 0x80, 0xc5, 0x1e, 0xaa, 0x6a, 0x97, 0xf7, 0x0d, 0x2e, 0xb8, 0xe1, 0xc2, 0x9f, 0x49,
+    0x48, 0x60, 0x97, 0xe8, 0xe8, 0x80, 0xf9, 0x48, 0xa2, 0xc2, 0x5a, 0xb5, 0x53, 0x73, 0xdc, 0xb7,
+];
+
+struct Setup<'a> {
+    env: Env,
+    client: PaymentContractClient<'a>,
+    customer: Address,
+    merchant: Address,
+    token: token::Client<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let token_admin = token::StellarAssetClient::new(&env, &token_id);
+    token_admin.mint(&customer, &1_000);
+    token_admin.mint(&merchant, &200);
+
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&Address::generate(&env));
+
+    Setup {
+        token: token::Client::new(&env, &token_id),
+        env,
+        client,
+        customer,
+        merchant,
+    }
+}
+
+fn passkey(env: &Env) -> SignerKey {
+    SignerKey::Secp256r1(BytesN::from_array(env, &PASSKEY_PUBLIC_KEY))
+}
+
+fn open_passkey_channel(s: &Setup) -> u64 {
+    s.client.open_channel(
+        &s.customer,
+        &s.merchant,
+        &s.token.address,
+        &1_000,
+        &0,
+        &passkey(&s.env),
+    )
+}
+
+fn vector_assertion(env: &Env) -> WebAuthnAssertion {
+    WebAuthnAssertion {
+        authenticator_data: Bytes::from_array(env, &AUTHENTICATOR_DATA),
+        client_data_json: Bytes::from_slice(env, CLIENT_DATA_JSON.as_bytes()),
+        signature: BytesN::from_array(env, &ASSERTION_SIGNATURE),
+    }
+}
+
+/// Produces an assertion over `msg` from the test vector's passkey, as a wallet would.
+fn webauthn_sign(env: &Env, msg: &Bytes) -> StateSignature {
+    let mut challenge = [0u8; 43];
+    base64url_encode_digest(&env.crypto().sha256(msg).to_array(), &mut challenge);
+    let client_data_json = alloc::format!(
+        r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://pay.facilpay.io"}}"#,
+        core::str::from_utf8(&challenge).unwrap()
+    );
+    let mut signed_data = AUTHENTICATOR_DATA.to_vec();
+    signed_data.extend_from_slice(
+        &env.crypto()
+            .sha256(&Bytes::from_slice(env, client_data_json.as_bytes()))
+            .to_array(),
+    );
+
+    let key = p256::ecdsa::SigningKey::from_slice(&PASSKEY_PRIVATE_KEY).unwrap();
+    let signature: Signature = key.sign(&signed_data);
+    let signature = signature.normalize_s().unwrap_or(signature);
+    StateSignature::WebAuthn(WebAuthnAssertion {
+        authenticator_data: Bytes::from_array(env, &AUTHENTICATOR_DATA),
+        client_data_json: Bytes::from_slice(env, client_data_json.as_bytes()),
+        signature: BytesN::from_array(env, &signature.to_bytes().into()),
+    })
+}
+
+#[test]
+fn test_passkey_settles_channel_with_test_vector() {
+    let s = setup();
+    let channel_id = open_passkey_channel(&s);
+    assert_eq!(
+        s.client.get_channel(&channel_id).customer_key,
+        passkey(&s.env)
+    );
+
+    s.client.settle_channel(
+        &channel_id,
+        &600,
+        &1,
+        &StateSignature::WebAuthn(vector_assertion(&s.env)),
+    );
+    assert_eq!(s.token.balance(&s.merchant), 800);
+    assert_eq!(s.token.balance(&s.customer), 400);
+    assert!(!s.client.get_channel(&channel_id).open);
+}
+
+#[test]
+fn test_webauthn_challenge_binds_the_settled_state() {
+    let s = setup();
+    let channel_id = open_passkey_channel(&s);
+
+    // The assertion signs 600; claiming 700 changes the expected challenge.
+    assert_eq!(
+        s.client.try_settle_channel(
+            &channel_id,
+            &700,
+            &1,
+            &StateSignature::WebAuthn(vector_assertion(&s.env)),
+        ),
+        Err(Ok(Error::Signature(SignatureError::ChallengeMismatch)))
+    );
+}
+
+#[test]
+fn test_webauthn_requires_user_presence_and_get_ceremony() {
+    let s = setup();
+    let channel_id = open_passkey_channel(&s);
+
+    let mut no_presence = vector_assertion(&s.env);
+    no_presence.authenticator_data.set(32, 0x04);
+    assert_eq!(
+        s.client.try_settle_channel(
+            &channel_id,
+            &600,
+            &1,
+            &StateSignature::WebAuthn(no_presence),
+        ),
+        Err(Ok(Error::Signature(
+            SignatureError::InvalidWebAuthnAssertion
+        )))
+    );
+
+    let mut registration = vector_assertion(&s.env);
+    registration.client_data_json = Bytes::from_slice(
+        &s.env,
+        CLIENT_DATA_JSON
+            .replace("webauthn.get", "webauthn.create")
+            .as_bytes(),
+    );
+    assert_eq!(
+        s.client.try_settle_channel(
+            &channel_id,
+            &600,
+            &1,
+            &StateSignature::WebAuthn(registration),
+        ),
+        Err(Ok(Error::Signature(
+            SignatureError::InvalidWebAuthnAssertion
+        )))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_webauthn_rejects_a_forged_signature() {
+    let s = setup();
+    let channel_id = open_passkey_channel(&s);
+
+    let mut forged = vector_assertion(&s.env);
+    let mut signature = ASSERTION_SIGNATURE;
+    signature[10] ^= 0x01;
+    forged.signature = BytesN::from_array(&s.env, &signature);
+    s.client
+        .settle_channel(&channel_id, &600, &1, &StateSignature::WebAuthn(forged));
+}
+
+#[test]
+fn test_signature_must_match_key_type() {
+    let s = setup();
+    let channel_id = open_passkey_channel(&s);
+
+    assert_eq!(
+        s.client.try_settle_channel(
+            &channel_id,
+            &600,
+            &1,
+            &StateSignature::Ed25519(BytesN::from_array(&s.env, &[0; 64])),
+        ),
+        Err(Ok(Error::Signature(SignatureError::KeyTypeMismatch)))
+    );
+}
+
+#[test]
+fn test_bidirectional_channel_with_passkey_customer() {
+    let s = setup();
+    let merchant_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+    let channel_id = s.client.open_bidirectional_channel(
+        &s.customer,
+        &s.merchant,
+        &s.token.address,
+        &1_000,
+        &200,
+        &passkey(&s.env),
+        &SignerKey::Ed25519(BytesN::from_array(
+            &s.env,
+            &merchant_key.verifying_key().to_bytes(),
+        )),
+        &3_600,
+    );
+
+    let state = ChannelState {
+        channel_id,
+        nonce: 1,
+        customer_balance: 700,
+        merchant_balance: 500,
+        is_final: true,
+    };
+    let mut msg = Bytes::new(&s.env);
+    msg.append(&s.client.address.clone().to_xdr(&s.env));
+    msg.append(&state.clone().to_xdr(&s.env));
+    let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
+
+    s.client.close_channel_mutual(
+        &channel_id,
+        &SignedChannelState {
+            state,
+            customer_signature: webauthn_sign(&s.env, &msg),
+            merchant_signature: StateSignature::Ed25519(BytesN::from_array(
+                &s.env,
+                &merchant_key.sign(&msg_vec).to_bytes(),
+            )),
+        },
+    );
+    assert_eq!(s.token.balance(&s.customer), 700);
+    assert_eq!(s.token.balance(&s.merchant), 500);
+}
+
+#[test]
+fn test_passkey_signs_payment_intent() {
+    let s = setup();
+    s.client.register_intent_key(&s.customer, &passkey(&s.env));
+    let intent = PaymentIntent {
+        customer: s.customer.clone(),
+        merchant: s.merchant.clone(),
+        token: s.token.address.clone(),
+        amount: 500,
+        currency: Currency::USDC,
+        expiry: 2_000,
+        nonce: 1,
+        invoice_hash: None,
+    };
+    let mut msg = Bytes::from_array(&s.env, &s.env.ledger().network_id().to_array());
+    msg.append(&s.client.address.clone().to_xdr(&s.env));
+    msg.append(&intent.clone().to_xdr(&s.env));
+
+    let ed25519 = StateSignature::Ed25519(BytesN::from_array(&s.env, &[0; 64]));
+    assert_eq!(
+        s.client.try_execute_payment_intent(&intent, &ed25519),
+        Err(Ok(Error::Signature(SignatureError::KeyTypeMismatch)))
+    );
+
+    let payment_id = s
+        .client
+        .execute_payment_intent(&intent, &webauthn_sign(&s.env, &msg));
+    assert_eq!(s.client.get_payment(&payment_id).amount, 500);
+    assert!(s.client.is_intent_nonce_used(&s.customer, &1));
+}
+
+/// `PaymentChannel` as stored before channels carried a tagged `SignerKey`.
+#[contracttype]
+#[derive(Clone)]
+struct LegacyPaymentChannel {
+    channel_id: u64,
+    customer: Address,
+    merchant: Address,
+    token: Address,
+    deposited: i128,
+    settled: i128,
+    settled_nonce: u64,
+    open: bool,
+    expires_at: u64,
+    customer_pk: BytesN<32>,
+}
+
+#[test]
+fn test_legacy_ed25519_channel_still_settles() {
+    let s = setup();
+    let customer_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+    s.token.transfer(&s.customer, &s.client.address, &1_000);
+    s.env.as_contract(&s.client.address, || {
+        s.env.storage().persistent().set(
+            &DataKey::Feature(FeatureKey::PaymentChannel(1)),
+            &LegacyPaymentChannel {
+                channel_id: 1,
+                customer: s.customer.clone(),
+                merchant: s.merchant.clone(),
+                token: s.token.address.clone(),
+                deposited: 1_000,
+                settled: 0,
+                settled_nonce: 0,
+                open: true,
+                expires_at: 0,
+                customer_pk: BytesN::from_array(&s.env, &customer_key.verifying_key().to_bytes()),
+            },
+        );
+    });
+    assert_eq!(
+        s.client.get_channel(&1).customer_key,
+        SignerKey::Ed25519(BytesN::from_array(
+            &s.env,
+            &customer_key.verifying_key().to_bytes()
+        ))
+    );
+
+    let mut msg = Bytes::new(&s.env);
+    msg.append(&1u64.to_xdr(&s.env));
+    msg.append(&250i128.to_xdr(&s.env));
+    msg.append(&1u64.to_xdr(&s.env));
+    let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
+    s.client.settle_channel(
+        &1,
+        &250,
+        &1,
+        &StateSignature::Ed25519(BytesN::from_array(
+            &s.env,
+            &customer_key.sign(&msg_vec).to_bytes(),
+        )),
+    );
+    assert_eq!(s.token.balance(&s.merchant), 450);
+    assert!(!s.client.get_channel(&1).open);
+}
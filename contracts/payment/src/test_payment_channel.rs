@@ -33,7 +33,7 @@ fn test_close_expired_channel() {
     let expires_at = 1000u64;
     env.ledger().set_timestamp(expires_at - 10);
 
-    let dummy_pk = BytesN::<32>::from_array(&env, &[0u8; 32]);
+    let dummy_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &[0u8; 32]));
     let channel_id = client.open_channel(
         &customer,
         &merchant,
@@ -83,7 +83,7 @@ fn test_payment_channel_full_lifecycle() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     // Open channel
     let channel_id = client.open_channel(
@@ -111,7 +111,7 @@ fn test_payment_channel_full_lifecycle() {
     // Collect message bytes and sign
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let signature = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &signature.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &signature.to_bytes()));
 
     // Settle the channel
     client.settle_channel(&channel_id, &merchant_amount, &nonce, &sig_bn);
@@ -154,7 +154,7 @@ fn test_settle_channel_invalid_nonce() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     let channel_id = client.open_channel(
         &customer,
@@ -175,7 +175,7 @@ fn test_settle_channel_invalid_nonce() {
 
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let signature = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &signature.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &signature.to_bytes()));
 
     // Should fail: nonce 0 is not > channel settled_nonce 0
     let result = client.try_settle_channel(&channel_id, &merchant_amount, &bad_nonce, &sig_bn);
@@ -212,7 +212,7 @@ fn test_settle_channel_rejects_negative_merchant_amount() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     let channel_id = client.open_channel(
         &customer,
@@ -232,7 +232,7 @@ fn test_settle_channel_rejects_negative_merchant_amount() {
 
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let signature = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &signature.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &signature.to_bytes()));
 
     let result = client.try_settle_channel(&channel_id, &merchant_amount, &nonce, &sig_bn);
     assert!(
@@ -241,7 +241,10 @@ fn test_settle_channel_rejects_negative_merchant_amount() {
     );
 
     let channel = client.get_channel(&channel_id);
-    assert!(channel.open, "channel must remain open on rejected settlement");
+    assert!(
+        channel.open,
+        "channel must remain open on rejected settlement"
+    );
 }
 
 #[test]
@@ -273,7 +276,7 @@ fn test_stale_nonce_replay_rejected() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     let channel_id = client.open_channel(
         &customer,
@@ -287,14 +290,14 @@ fn test_stale_nonce_replay_rejected() {
     // Customer signed two off-chain states:
     //   stale: nonce=1, merchant gets 100
     //   latest: nonce=5, merchant gets 800
-    let sign_state = |nonce: u64, amount: i128| -> BytesN<64> {
+    let sign_state = |nonce: u64, amount: i128| -> StateSignature {
         let mut msg = Bytes::new(&env);
         msg.append(&channel_id.to_xdr(&env));
         msg.append(&amount.to_xdr(&env));
         msg.append(&nonce.to_xdr(&env));
         let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
         let sig = signing_key.sign(&msg_vec);
-        BytesN::<64>::from_array(&env, &sig.to_bytes())
+        StateSignature::Ed25519(BytesN::<64>::from_array(&env, &sig.to_bytes()))
     };
 
     let stale_sig = sign_state(1, 100);
@@ -340,7 +343,7 @@ fn test_equal_nonce_replay_rejected() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     let channel_id = client.open_channel(
         &customer,
@@ -358,7 +361,7 @@ fn test_equal_nonce_replay_rejected() {
     msg.append(&0u64.to_xdr(&env));
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let sig = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &sig.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &sig.to_bytes()));
 
     let result = client.try_settle_channel(&channel_id, &500i128, &0u64, &sig_bn);
     assert!(
@@ -395,7 +398,7 @@ fn setup_channel_env() -> (
     let expires_at = 5000u64;
     env.ledger().set_timestamp(expires_at - 100);
 
-    let dummy_pk = BytesN::<32>::from_array(&env, &[0u8; 32]);
+    let dummy_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &[0u8; 32]));
     let channel_id = client.open_channel(
         &customer,
         &merchant,
@@ -469,7 +472,7 @@ fn counterparty_can_close_immediately_on_agreement() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     // Channel with no expiry — counterparty (merchant) can settle immediately via signed state
     let channel_id = client.open_channel(
@@ -489,7 +492,7 @@ fn counterparty_can_close_immediately_on_agreement() {
     msg.append(&nonce.to_xdr(&env));
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let sig = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &sig.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &sig.to_bytes()));
 
     // Merchant submits valid signed state — channel closes immediately
     client.settle_channel(&channel_id, &merchant_amount, &nonce, &sig_bn);
@@ -524,7 +527,7 @@ fn close_transfers_correct_balances_to_each_party() {
     let mut rng = OsRng;
     let signing_key = SigningKey::generate(&mut rng);
     let pk_bytes = signing_key.verifying_key().to_bytes();
-    let customer_pk = BytesN::<32>::from_array(&env, &pk_bytes);
+    let customer_pk = SignerKey::Ed25519(BytesN::<32>::from_array(&env, &pk_bytes));
 
     let channel_id = client.open_channel(
         &customer,
@@ -543,7 +546,7 @@ fn close_transfers_correct_balances_to_each_party() {
     msg.append(&nonce.to_xdr(&env));
     let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
     let sig = signing_key.sign(&msg_vec);
-    let sig_bn = BytesN::<64>::from_array(&env, &sig.to_bytes());
+    let sig_bn = StateSignature::Ed25519(BytesN::<64>::from_array(&env, &sig.to_bytes()));
 
     client.settle_channel(&channel_id, &merchant_amount, &nonce, &sig_bn);
 
@@ -0,0 +1,207 @@
+#![cfg(test)]
+
+extern crate alloc;
+
+use super::*;
+use ed25519_dalek::{Signer, SigningKey};
+use rand::rngs::OsRng;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token, Address, Bytes, BytesN, Env,
+};
+
+struct Setup<'a> {
+    env: Env,
+    client: PaymentContractClient<'a>,
+    admin: Address,
+    customer: Address,
+    merchant: Address,
+    token: token::Client<'a>,
+    customer_key: SigningKey,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    token::StellarAssetClient::new(&env, &token_id).mint(&customer, &1_000);
+
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+
+    let customer_key = SigningKey::generate(&mut OsRng);
+    client.register_intent_key(
+        &customer,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &customer_key.verifying_key().to_bytes(),
+        )),
+    );
+
+    Setup {
+        token: token::Client::new(&env, &token_id),
+        env,
+        client,
+        admin,
+        customer,
+        merchant,
+        customer_key,
+    }
+}
+
+fn intent(s: &Setup, nonce: u64) -> PaymentIntent {
+    PaymentIntent {
+        customer: s.customer.clone(),
+        merchant: s.merchant.clone(),
+        token: s.token.address.clone(),
+        amount: 500,
+        currency: Currency::USDC,
+        expiry: 2_000,
+        nonce,
+        invoice_hash: None,
+    }
+}
+
+/// Signs the intent the same way the contract verifies it:
+/// `network_id || contract_address.to_xdr() || intent.to_xdr()`.
+fn sign_for_network(s: &Setup, intent: &PaymentIntent, network_id: [u8; 32]) -> StateSignature {
+    let mut msg = Bytes::from_array(&s.env, &network_id);
+    msg.append(&s.client.address.clone().to_xdr(&s.env));
+    msg.append(&intent.clone().to_xdr(&s.env));
+    let msg_vec: alloc::vec::Vec<u8> = msg.iter().collect();
+    StateSignature::Ed25519(BytesN::from_array(
+        &s.env,
+        &s.customer_key.sign(&msg_vec).to_bytes(),
+    ))
+}
+
+fn sign(s: &Setup, intent: &PaymentIntent) -> StateSignature {
+    sign_for_network(s, intent, s.env.ledger().network_id().to_array())
+}
+
+#[test]
+fn test_intent_creates_payment_without_customer_auth() {
+    let s = setup();
+    let mut signed = intent(&s, 7);
+    signed.invoice_hash = Some(BytesN::from_array(&s.env, &[9; 32]));
+
+    let payment_id = s.client.execute_payment_intent(&signed, &sign(&s, &signed));
+    assert!(s.env.auths().is_empty());
+
+    let payment = s.client.get_payment(&payment_id);
+    assert_eq!(payment.customer, s.customer);
+    assert_eq!(payment.merchant, s.merchant);
+    assert_eq!(payment.amount, 500);
+    assert_eq!(payment.status, PaymentStatus::Pending);
+    assert_eq!(
+        s.client.get_payment_invoice_hash(&payment_id),
+        signed.invoice_hash
+    );
+    assert!(s.client.is_intent_nonce_used(&s.customer, &7));
+    assert!(!s.client.is_intent_nonce_used(&s.customer, &6));
+
+    // The payment settles against the customer's allowance like any other.
+    s.token
+        .approve(&s.customer, &s.client.address, &500, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+    assert_eq!(s.token.balance(&s.merchant), 500);
+}
+
+#[test]
+fn test_intent_cannot_be_replayed() {
+    let s = setup();
+    let signed = intent(&s, 1);
+    let signature = sign(&s, &signed);
+    s.client.execute_payment_intent(&signed, &signature);
+
+    assert_eq!(
+        s.client.try_execute_payment_intent(&signed, &signature),
+        Err(Ok(Error::Signature(SignatureError::IntentNonceUsed)))
+    );
+}
+
+#[test]
+fn test_cancelled_intent_cannot_be_executed() {
+    let s = setup();
+    let signed = intent(&s, 3);
+    s.client.cancel_payment_intent(&s.customer, &3);
+
+    assert_eq!(
+        s.client
+            .try_execute_payment_intent(&signed, &sign(&s, &signed)),
+        Err(Ok(Error::Signature(SignatureError::IntentNonceUsed)))
+    );
+    assert_eq!(
+        s.client.try_cancel_payment_intent(&s.customer, &3),
+        Err(Ok(Error::Signature(SignatureError::IntentNonceUsed)))
+    );
+}
+
+#[test]
+fn test_expired_or_unregistered_intent_is_rejected() {
+    let s = setup();
+    let signed = intent(&s, 1);
+    let signature = sign(&s, &signed);
+    s.env.ledger().set_timestamp(2_001);
+    assert_eq!(
+        s.client.try_execute_payment_intent(&signed, &signature),
+        Err(Ok(Error::Signature(SignatureError::IntentExpired)))
+    );
+
+    let mut stranger = intent(&s, 1);
+    stranger.customer = Address::generate(&s.env);
+    stranger.expiry = 3_000;
+    assert_eq!(
+        s.client
+            .try_execute_payment_intent(&stranger, &sign(&s, &stranger)),
+        Err(Ok(Error::Signature(
+            SignatureError::SigningKeyNotRegistered
+        )))
+    );
+}
+
+#[test]
+fn test_legacy_ed25519_intent_key_still_verifies() {
+    let s = setup();
+    let public_key = BytesN::from_array(&s.env, &s.customer_key.verifying_key().to_bytes());
+    s.env.as_contract(&s.client.address, || {
+        s.env.storage().persistent().set(
+            &DataKey::Customer(CustomerDataKey::IntentKey(s.customer.clone())),
+            &public_key,
+        );
+    });
+    assert_eq!(
+        s.client.get_intent_key(&s.customer),
+        Some(SignerKey::Ed25519(public_key))
+    );
+
+    let signed = intent(&s, 2);
+    s.client.execute_payment_intent(&signed, &sign(&s, &signed));
+    assert!(s.client.is_intent_nonce_used(&s.customer, &2));
+}
+
+#[test]
+#[should_panic]
+fn test_intent_signed_for_another_network_is_rejected() {
+    let s = setup();
+    let signed = intent(&s, 1);
+    let signature = sign_for_network(&s, &signed, [7; 32]);
+    s.client.execute_payment_intent(&signed, &signature);
+}
+
+#[test]
+#[should_panic]
+fn test_tampered_intent_is_rejected() {
+    let s = setup();
+    let mut signed = intent(&s, 1);
+    let signature = sign(&s, &signed);
+    signed.amount = 900;
+    s.client.execute_payment_intent(&signed, &signature);
+}
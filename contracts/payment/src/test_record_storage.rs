@@ -0,0 +1,112 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Ledger},
+    token, Address, Env, String,
+};
+
+fn create_token_contract<'a>(
+    env: &Env,
+    admin: &Address,
+) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
+    let contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let contract_address = contract.address();
+    (
+        token::Client::new(env, &contract_address),
+        token::StellarAssetClient::new(env, &contract_address),
+    )
+}
+
+fn setup(env: &Env) -> (PaymentContractClient<'_>, Address, Address, u64) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let merchant = Address::generate(env);
+    let customer = Address::generate(env);
+    let (token_client, token_admin) = create_token_contract(env, &admin);
+    token_admin.mint(&customer, &10_000);
+
+    let contract_id = env.register(PaymentContract, ());
+    let client = PaymentContractClient::new(env, &contract_id);
+    client.initialize(&admin);
+
+    let payment_id = client.create_payment(
+        &customer,
+        &merchant,
+        &1000i128,
+        &token_client.address,
+        &Currency::USDC,
+        &3600u64,
+        &String::from_str(env, "Test payment"),
+    );
+    (client, admin, customer, payment_id)
+}
+
+#[test]
+fn test_payment_and_indexes_stored_in_persistent_storage() {
+    let env = Env::default();
+    let (client, _, customer, payment_id) = setup(&env);
+
+    env.as_contract(&client.address, || {
+        let data_key = DataKey::Payment(PaymentKey::Data(payment_id));
+        assert!(env.storage().persistent().has(&data_key));
+        assert!(!env.storage().instance().has(&data_key));
+
+        let index_key = DataKey::Customer(CustomerDataKey::PaymentCount(customer.clone()));
+        assert!(env.storage().persistent().has(&index_key));
+        assert!(!env.storage().instance().has(&index_key));
+    });
+}
+
+#[test]
+fn test_extend_record_ttl_refreshes_payment_record() {
+    let env = Env::default();
+    let (client, _, _, payment_id) = setup(&env);
+    let data_key = DataKey::Payment(PaymentKey::Data(payment_id));
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += RECORD_TTL_EXTEND_TO - RECORD_TTL_THRESHOLD + 1);
+    let before = env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&data_key)
+    });
+    assert!(before < RECORD_TTL_THRESHOLD);
+
+    client.extend_record_ttl(&payment_id);
+
+    let after = env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&data_key)
+    });
+    assert_eq!(after, RECORD_TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_extend_record_ttl_unknown_payment_fails() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+
+    let result = client.try_extend_record_ttl(&999);
+    assert_eq!(result, Err(Ok(Error::Payment(PaymentError::NotFound))));
+}
+
+#[test]
+fn test_legacy_instance_record_is_promoted_on_touch() {
+    let env = Env::default();
+    let (client, _, _, payment_id) = setup(&env);
+    let data_key = DataKey::Payment(PaymentKey::Data(payment_id));
+
+    // Simulate a payment written by a pre-v2 release.
+    env.as_contract(&client.address, || {
+        let payment: Payment = env.storage().persistent().get(&data_key).unwrap();
+        env.storage().persistent().remove(&data_key);
+        env.storage().instance().set(&data_key, &payment);
+    });
+    assert_eq!(client.get_payment(&payment_id).id, payment_id);
+
+    client.extend_record_ttl(&payment_id);
+
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().has(&data_key));
+        assert!(!env.storage().instance().has(&data_key));
+    });
+    assert_eq!(client.get_payment(&payment_id).id, payment_id);
+}
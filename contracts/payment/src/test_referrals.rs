@@ -0,0 +1,197 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, Error, FeatureError, FeeConfig, Referral, ReferralConfig,
+    ReferrerAnalytics, SECONDS_PER_DAY,
+};
+
+const DURATION: u64 = 90 * SECONDS_PER_DAY;
+
+/// Sets a flat 10% fee and a 20% referral commission, and returns a referrer.
+fn configure(s: &Setup) -> Address {
+    // The risk surcharge is capped away.
+    s.client.set_fee_config(
+        &s.admin,
+        &FeeConfig {
+            fee_bps: 1_000,
+            min_fee: 0,
+            max_fee: 1_000_000,
+            treasury: Address::generate(&s.env),
+            fee_token: s.token.address.clone(),
+            active: true,
+        },
+    );
+    s.client.configure_referral_program(
+        &s.admin,
+        &ReferralConfig {
+            commission_bps: 2_000,
+            duration_seconds: DURATION,
+            active: true,
+        },
+    );
+    Address::generate(&s.env)
+}
+
+fn complete(s: &Setup, amount: i128) -> u64 {
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.token
+        .approve(&s.customer, &s.client.address, &amount, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+    payment_id
+}
+
+#[test]
+fn test_commission_deducted_before_sweep_and_claimed() {
+    let s = setup(100_000);
+    let referrer = configure(&s);
+    s.client.attach_referrer(&s.merchant, &referrer);
+    assert_eq!(
+        s.client.get_referral(&s.merchant),
+        Some(Referral {
+            referrer: referrer.clone(),
+            referee: s.merchant.clone(),
+            attached_at: START,
+            expires_at: START + DURATION,
+        })
+    );
+
+    complete(&s, 1_000);
+    complete(&s, 2_000);
+    assert_eq!(s.token.balance(&s.merchant), 2_700);
+    assert_eq!(
+        s.client.get_referral_balance(&referrer, &s.token.address),
+        60
+    );
+    assert_eq!(s.client.get_sweepable_balance(), 240);
+
+    let recipient = Address::generate(&s.env);
+    s.client.set_sweep_recipient(&s.admin, &recipient);
+    assert_eq!(s.client.sweep_platform_fees(&s.admin), 240);
+    assert_eq!(s.token.balance(&s.client.address), 60);
+
+    assert_eq!(
+        s.client
+            .claim_referral_commission(&referrer, &s.token.address),
+        60
+    );
+    assert_eq!(s.token.balance(&referrer), 60);
+    assert_eq!(
+        s.client.get_referrer_analytics(&referrer),
+        ReferrerAnalytics {
+            total_referrals: 1,
+            commissioned_payments: 2,
+            total_earned: 60,
+            total_claimed: 60,
+        }
+    );
+    assert_eq!(
+        s.client
+            .try_claim_referral_commission(&referrer, &s.token.address),
+        Err(Ok(Error::Feature(FeatureError::NoReferralCommission)))
+    );
+}
+
+#[test]
+fn test_commission_stops_when_referral_expires() {
+    let s = setup(100_000);
+    let referrer = configure(&s);
+    s.client.attach_referrer(&s.customer, &referrer);
+
+    s.env.ledger().set_timestamp(START + DURATION - 1);
+    complete(&s, 1_000);
+    assert_eq!(
+        s.client.get_referral_balance(&referrer, &s.token.address),
+        20
+    );
+
+    s.env.ledger().set_timestamp(START + DURATION);
+    complete(&s, 1_000);
+    assert_eq!(
+        s.client.get_referral_balance(&referrer, &s.token.address),
+        20
+    );
+    assert_eq!(s.client.get_sweepable_balance(), 180);
+}
+
+#[test]
+fn test_merchant_and_customer_referrers_both_earn() {
+    let s = setup(100_000);
+    let referrer = configure(&s);
+    let other_referrer = Address::generate(&s.env);
+    s.client.attach_referrer(&s.merchant, &referrer);
+    s.client.attach_referrer(&s.customer, &other_referrer);
+
+    complete(&s, 1_000);
+    assert_eq!(
+        s.client.get_referral_balance(&referrer, &s.token.address),
+        20
+    );
+    assert_eq!(
+        s.client
+            .get_referral_balance(&other_referrer, &s.token.address),
+        20
+    );
+    assert_eq!(s.client.get_sweepable_balance(), 60);
+}
+
+#[test]
+fn test_referrer_attachment_eligibility() {
+    let s = setup(100_000);
+    let referrer = configure(&s);
+    assert_eq!(
+        s.client.try_attach_referrer(&s.merchant, &s.merchant),
+        Err(Ok(Error::Feature(FeatureError::ReferralNotEligible)))
+    );
+    s.client.attach_referrer(&s.merchant, &referrer);
+    assert_eq!(
+        s.client
+            .try_attach_referrer(&s.merchant, &Address::generate(&s.env)),
+        Err(Ok(Error::Feature(FeatureError::ReferralNotEligible)))
+    );
+
+    // Referrers can only be attached at onboarding, before any payments.
+    complete(&s, 1_000);
+    assert_eq!(
+        s.client.try_attach_referrer(&s.customer, &referrer),
+        Err(Ok(Error::Feature(FeatureError::ReferralNotEligible)))
+    );
+
+    assert_eq!(
+        s.client.try_configure_referral_program(
+            &s.admin,
+            &ReferralConfig {
+                commission_bps: 10_001,
+                duration_seconds: DURATION,
+                active: true,
+            }
+        ),
+        Err(Ok(Error::Basic(BasicError::InvalidBps)))
+    );
+    s.client.configure_referral_program(
+        &s.admin,
+        &ReferralConfig {
+            commission_bps: 2_000,
+            duration_seconds: DURATION,
+            active: false,
+        },
+    );
+    assert_eq!(
+        s.client
+            .try_attach_referrer(&Address::generate(&s.env), &referrer),
+        Err(Ok(Error::Feature(FeatureError::ReferralProgramNotFound)))
+    );
+}
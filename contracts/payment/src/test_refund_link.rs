@@ -0,0 +1,387 @@
+#![cfg(test)]
+
+use super::*;
+use refund::{RefundContract, RefundContractClient, RefundReasonCode};
+use soroban_sdk::{testutils::Address as _, token, Address, Env, String};
+
+struct Setup<'a> {
+    env: Env,
+    payments: PaymentContractClient<'a>,
+    refunds: RefundContractClient<'a>,
+    admin: Address,
+    customer: Address,
+    merchant: Address,
+    token: token::Client<'a>,
+    token_admin: token::StellarAssetClient<'a>,
+}
+
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+
+    let token_address = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+    let token = token::Client::new(&env, &token_address);
+    let token_admin = token::StellarAssetClient::new(&env, &token_address);
+    token_admin.mint(&customer, &10_000);
+
+    let payments = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    payments.initialize(&admin);
+    let refunds = RefundContractClient::new(&env, &env.register(RefundContract, ()));
+    refunds.initialize(&admin);
+
+    payments.set_refund_contract(&admin, &refunds.address);
+    refunds.set_payment_contract_address(&admin, &payments.address);
+
+    Setup {
+        env,
+        payments,
+        refunds,
+        admin,
+        customer,
+        merchant,
+        token,
+        token_admin,
+    }
+}
+
+/// Creates and completes a payment of `amount`. With `hold_in_custody` the payment
+/// contract keeps the funds in a finality-delay settlement instead of paying out.
+fn completed_payment(s: &Setup, amount: i128, hold_in_custody: bool) -> u64 {
+    if hold_in_custody {
+        s.payments.configure_finality_delay(
+            &s.admin,
+            &FinalityConfig {
+                delay_seconds: 3600,
+                min_amount_threshold: 1,
+                active: true,
+            },
+        );
+        s.token_admin.mint(&s.payments.address, &amount);
+    } else {
+        s.token
+            .approve(&s.customer, &s.payments.address, &amount, &1_000);
+    }
+    let payment_id = s.payments.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.payments.complete_payment(&s.admin, &payment_id);
+    payment_id
+}
+
+fn approved_refund(s: &Setup, payment_id: u64, amount: i128, original: i128) -> u64 {
+    let refund_id = s.refunds.request_refund(
+        &s.merchant,
+        &payment_id,
+        &s.customer,
+        &amount,
+        &original,
+        &s.token.address,
+        &String::from_str(&s.env, "damaged"),
+        &RefundReasonCode::ProductDefect,
+        &0,
+    );
+    s.refunds.approve_refund(&s.admin, &refund_id);
+    refund_id
+}
+
+#[test]
+fn test_processed_refund_updates_payment_and_pays_from_custody() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, true);
+
+    let refund_id = approved_refund(&s, payment_id, 400, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    let payment = s.payments.get_payment(&payment_id);
+    assert_eq!(payment.refunded_amount, 400);
+    assert_eq!(payment.status, PaymentStatus::PartialRefunded);
+    assert_eq!(s.token.balance(&s.customer), 10_000 + 400);
+    assert_eq!(
+        s.payments
+            .get_pending_settlements(&s.merchant)
+            .get(0)
+            .unwrap()
+            .amount,
+        600
+    );
+    assert_eq!(
+        s.refunds.get_refund_reserve(&s.merchant, &s.token.address),
+        0
+    );
+}
+
+#[test]
+fn test_refund_falls_back_to_reserve_when_funds_released() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, false);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+    s.refunds
+        .deposit_refund_reserve(&s.merchant, &s.token.address, &1_000);
+
+    let refund_id = approved_refund(&s, payment_id, 1_000, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    let payment = s.payments.get_payment(&payment_id);
+    assert_eq!(payment.refunded_amount, 1_000);
+    assert_eq!(payment.status, PaymentStatus::Refunded);
+    assert_eq!(s.token.balance(&s.customer), 10_000);
+    assert_eq!(
+        s.refunds.get_refund_reserve(&s.merchant, &s.token.address),
+        0
+    );
+}
+
+#[test]
+fn test_refund_draws_from_accumulated_payout_balance() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, false);
+    // Earlier settlements the contract still holds for the merchant's weekly payout
+    s.payments
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Weekly, &s.token.address);
+    s.token_admin.mint(&s.payments.address, &1_000);
+    s.env.as_contract(&s.payments.address, || {
+        PaymentContract::settle_or_accumulate(
+            &s.env,
+            s.merchant.clone(),
+            s.token.address.clone(),
+            1_000,
+        )
+        .unwrap();
+    });
+    assert_eq!(s.payments.get_accumulated_balance(&s.merchant), 1_000);
+
+    let refund_id = approved_refund(&s, payment_id, 400, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    assert_eq!(s.token.balance(&s.customer), 10_000 - 1_000 + 400);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+    assert_eq!(s.payments.get_accumulated_balance(&s.merchant), 600);
+    assert_eq!(
+        s.refunds.get_refund_reserve(&s.merchant, &s.token.address),
+        0
+    );
+}
+
+#[test]
+fn test_refund_contract_cannot_refund_past_direct_refunds() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, true);
+
+    let first = approved_refund(&s, payment_id, 400, 1_000);
+    s.refunds.process_refund(&s.admin, &first);
+    let second = approved_refund(&s, payment_id, 100, 1_000);
+
+    // The remainder is refunded directly before the second request is processed.
+    s.payments.partial_refund(&s.admin, &payment_id, &600);
+    assert_eq!(
+        s.payments.get_payment(&payment_id).status,
+        PaymentStatus::Refunded
+    );
+
+    let result = s.refunds.try_process_refund(&s.admin, &second);
+    assert_eq!(
+        result,
+        Err(Ok(refund::Error::Ext(
+            refund::ExtError::PaymentSettlementFailed
+        )))
+    );
+    assert_eq!(s.payments.get_payment(&payment_id).refunded_amount, 1_000);
+    assert_eq!(
+        s.refunds.get_refund(&second).status,
+        refund::RefundStatus::Approved
+    );
+}
+
+#[test]
+fn test_direct_refund_cannot_exceed_settled_refunds() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, true);
+
+    let refund_id = approved_refund(&s, payment_id, 700, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    let result = s.payments.try_partial_refund(&s.admin, &payment_id, &400);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Payment(PaymentError::RefundExceedsPayment)))
+    );
+}
+
+#[test]
+fn test_settle_refund_rejects_unregistered_caller() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, true);
+    let impostor = Address::generate(&s.env);
+
+    let result =
+        s.payments
+            .try_settle_refund(&impostor, &payment_id, &s.merchant, &s.token.address, &100);
+    assert_eq!(result, Err(Ok(Error::Basic(BasicError::Unauthorized))));
+    assert_eq!(s.payments.get_payment(&payment_id).refunded_amount, 0);
+}
+
+#[test]
+fn test_settle_refund_rejects_payment_that_never_completed() {
+    let s = setup();
+    let payment_id = s.payments.create_payment(
+        &s.customer,
+        &s.merchant,
+        &1_000,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    // A direct partial refund moves the pending payment to PartialRefunded
+    // without any funds having been collected.
+    s.payments.partial_refund(&s.admin, &payment_id, &100);
+    assert_eq!(
+        s.payments.get_payment(&payment_id).status,
+        PaymentStatus::PartialRefunded
+    );
+    assert_eq!(s.payments.get_payment_completed_at(&payment_id), None);
+    assert!(!s.payments.check_payment_customer(&payment_id, &s.customer));
+
+    let result = s.payments.try_settle_refund(
+        &s.refunds.address,
+        &payment_id,
+        &s.merchant,
+        &s.token.address,
+        &100,
+    );
+    assert_eq!(result, Err(Ok(Error::Payment(PaymentError::InvalidStatus))));
+    assert_eq!(s.payments.get_payment(&payment_id).refunded_amount, 100);
+}
+
+#[test]
+fn test_settle_refund_rejects_other_merchant_or_token() {
+    let s = setup();
+    let payment_id = completed_payment(&s, 1_000, true);
+    assert_eq!(
+        s.payments.get_payment_completed_at(&payment_id),
+        Some(s.env.ledger().timestamp())
+    );
+    let other_merchant = Address::generate(&s.env);
+    let other_token = s
+        .env
+        .register_stellar_asset_contract_v2(s.admin.clone())
+        .address();
+
+    let result = s.payments.try_settle_refund(
+        &s.refunds.address,
+        &payment_id,
+        &other_merchant,
+        &s.token.address,
+        &100,
+    );
+    assert_eq!(
+        result,
+        Err(Ok(Error::Payment(PaymentError::RefundMismatch)))
+    );
+    let result = s.payments.try_settle_refund(
+        &s.refunds.address,
+        &payment_id,
+        &s.merchant,
+        &other_token,
+        &100,
+    );
+    assert_eq!(
+        result,
+        Err(Ok(Error::Payment(PaymentError::RefundMismatch)))
+    );
+
+    // A refund booked by another merchant cannot claw back this payment's funds.
+    let refund_id = s.refunds.request_refund(
+        &other_merchant,
+        &payment_id,
+        &s.customer,
+        &100,
+        &1_000,
+        &s.token.address,
+        &String::from_str(&s.env, "damaged"),
+        &RefundReasonCode::ProductDefect,
+        &0,
+    );
+    s.refunds.approve_refund(&s.admin, &refund_id);
+    assert_eq!(
+        s.refunds.try_process_refund(&s.admin, &refund_id),
+        Err(Ok(refund::Error::Ext(
+            refund::ExtError::PaymentSettlementFailed
+        )))
+    );
+    assert_eq!(s.payments.get_payment(&payment_id).refunded_amount, 0);
+}
+
+fn withhold_tenth_as_reserve(s: &Setup) {
+    s.payments.set_reserve_tier(
+        &s.admin,
+        &ReserveTier {
+            level: MerchantVerificationLevel::Unverified,
+            reserve_bps: 1_000,
+            risk_weight_pct: 0,
+            hold_seconds: 30 * SECONDS_PER_DAY,
+        },
+    );
+}
+
+#[test]
+fn test_refund_reserve_is_used_before_rolling_reserve() {
+    let s = setup();
+    withhold_tenth_as_reserve(&s);
+    let payment_id = completed_payment(&s, 1_000, false);
+    assert_eq!(
+        s.payments
+            .get_reserve_balance(&s.merchant, &s.token.address),
+        100
+    );
+    s.refunds
+        .deposit_refund_reserve(&s.merchant, &s.token.address, &400);
+
+    let refund_id = approved_refund(&s, payment_id, 400, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    assert_eq!(s.token.balance(&s.customer), 10_000 - 1_000 + 400);
+    assert_eq!(
+        s.refunds.get_refund_reserve(&s.merchant, &s.token.address),
+        0
+    );
+    assert_eq!(
+        s.payments
+            .get_reserve_balance(&s.merchant, &s.token.address),
+        100
+    );
+}
+
+#[test]
+fn test_rolling_reserve_covers_refund_reserve_shortfall() {
+    let s = setup();
+    withhold_tenth_as_reserve(&s);
+    let payment_id = completed_payment(&s, 1_000, false);
+    s.refunds
+        .deposit_refund_reserve(&s.merchant, &s.token.address, &300);
+
+    let refund_id = approved_refund(&s, payment_id, 400, 1_000);
+    s.refunds.process_refund(&s.admin, &refund_id);
+
+    assert_eq!(s.token.balance(&s.customer), 10_000 - 1_000 + 400);
+    assert_eq!(
+        s.refunds.get_refund_reserve(&s.merchant, &s.token.address),
+        0
+    );
+    assert_eq!(
+        s.payments
+            .get_reserve_balance(&s.merchant, &s.token.address),
+        0
+    );
+}
@@ -0,0 +1,369 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, Error, FinalityConfig, MerchantVerificationLevel, PaymentError,
+    ReserveEntry, ReserveTier, SECONDS_PER_DAY,
+};
+
+const HOLD: u64 = 30 * SECONDS_PER_DAY;
+const RELEASE_DAY: u64 = (START + HOLD) / SECONDS_PER_DAY;
+
+fn tier(level: MerchantVerificationLevel, reserve_bps: u32, risk_weight_pct: u32) -> ReserveTier {
+    ReserveTier {
+        level,
+        reserve_bps,
+        risk_weight_pct,
+        hold_seconds: HOLD,
+    }
+}
+
+/// Creates a payment and pays it in full as a single installment.
+fn paid_payment(s: &Setup, amount: i128) -> u64 {
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.pay_installment(&s.customer, &payment_id, &amount);
+    payment_id
+}
+
+#[test]
+fn test_reserve_withheld_on_completion_and_released_by_keeper() {
+    let s = setup(10_000);
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 1_000, 0),
+    );
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &1_000,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    assert_eq!(s.token.balance(&s.merchant), 900);
+    assert_eq!(s.token.balance(&s.client.address), 100);
+    assert_eq!(
+        s.client.get_reserve_release_days(&s.merchant),
+        soroban_sdk::vec![&s.env, RELEASE_DAY]
+    );
+    assert_eq!(
+        s.client.get_reserve_entries(&s.merchant, &RELEASE_DAY),
+        soroban_sdk::vec![
+            &s.env,
+            ReserveEntry {
+                payment_id,
+                token: s.token.address.clone(),
+                amount: 100,
+                release_at: START + HOLD,
+            }
+        ]
+    );
+
+    s.env.ledger().set_timestamp(START + HOLD - 1);
+    assert_eq!(s.client.release_reserves(&s.merchant), 0);
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        100
+    );
+
+    s.env.ledger().set_timestamp(START + HOLD);
+    assert_eq!(s.client.release_reserves(&s.merchant), 100);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+    assert!(s
+        .client
+        .get_reserve_entries(&s.merchant, &RELEASE_DAY)
+        .is_empty());
+    assert!(s.client.get_reserve_release_days(&s.merchant).is_empty());
+}
+
+#[test]
+fn test_reserve_collected_when_settlement_is_deferred() {
+    let s = setup(10_000);
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 1_000, 0),
+    );
+    s.client.configure_finality_delay(
+        &s.admin,
+        &FinalityConfig {
+            delay_seconds: 3_600,
+            min_amount_threshold: 100,
+            active: true,
+        },
+    );
+    // The deferred share is held in the contract's custody until finalization.
+    s.mint(&s.client.address, 900);
+    s.approve(1_000);
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &1_000,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    assert_eq!(s.token.balance(&s.customer), 9_900);
+    assert_eq!(s.token.balance(&s.client.address), 1_000);
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        100
+    );
+    assert_eq!(
+        s.client
+            .get_pending_settlements(&s.merchant)
+            .get(0)
+            .unwrap()
+            .amount,
+        900
+    );
+
+    s.env.ledger().set_timestamp(START + 3_600);
+    s.client.finalize_pending_settlement(&payment_id);
+    assert_eq!(s.token.balance(&s.merchant), 900);
+    s.env.ledger().set_timestamp(START + HOLD);
+    assert_eq!(s.client.release_reserves(&s.merchant), 100);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+}
+
+#[test]
+fn test_reserve_rate_follows_verification_level_and_risk() {
+    let s = setup(10_000);
+    // A new customer scores 100 bps of risk; half of it is added to the 5% base.
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 500, 50),
+    );
+    s.client
+        .set_reserve_tier(&s.admin, &tier(MerchantVerificationLevel::Premium, 0, 0));
+
+    paid_payment(&s, 1_000);
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        55
+    );
+    assert_eq!(s.token.balance(&s.merchant), 945);
+
+    s.client.set_merchant_verification_level(
+        &s.admin,
+        &s.merchant,
+        &MerchantVerificationLevel::Premium,
+    );
+    paid_payment(&s, 1_000);
+    assert_eq!(s.token.balance(&s.merchant), 1_945);
+    assert_eq!(
+        s.client
+            .get_reserve_entries(&s.merchant, &RELEASE_DAY)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_reserve_covers_lost_chargeback() {
+    let s = setup(10_000);
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 2_000, 0),
+    );
+    let first = paid_payment(&s, 500);
+    paid_payment(&s, 1_000);
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        300
+    );
+
+    // Nothing is held from the paid-out payment, so the reserve covers it, oldest first.
+    s.client.open_chargeback(
+        &s.customer,
+        &first,
+        &250,
+        &String::from_str(&s.env, "item not received"),
+    );
+    s.client.resolve_chargeback(&s.admin, &first, &true);
+    assert_eq!(s.token.balance(&s.customer), 8_750);
+    assert!(s.client.get_chargeback_debts(&s.merchant).is_empty());
+
+    let entries = s.client.get_reserve_entries(&s.merchant, &RELEASE_DAY);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries.get(0).unwrap().amount, 50);
+
+    // Once the reserve runs dry the rest becomes chargeback debt.
+    let second = entries.get(0).unwrap().payment_id;
+    s.client.open_chargeback(
+        &s.customer,
+        &second,
+        &200,
+        &String::from_str(&s.env, "item not received"),
+    );
+    s.client.resolve_chargeback(&s.admin, &second, &true);
+    assert_eq!(s.token.balance(&s.customer), 8_800);
+    assert!(s.client.get_reserve_release_days(&s.merchant).is_empty());
+    assert_eq!(
+        s.client
+            .get_chargeback_debts(&s.merchant)
+            .get(0)
+            .unwrap()
+            .amount,
+        150
+    );
+}
+
+#[test]
+fn test_reserve_covers_refund_only_when_drawn() {
+    let s = setup(10_000);
+    let refund_contract = Address::generate(&s.env);
+    s.client.set_refund_contract(&s.admin, &refund_contract);
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 1_000, 0),
+    );
+    let payment_id = paid_payment(&s, 1_000);
+
+    assert_eq!(
+        s.client
+            .try_draw_reserve_for_refund(&refund_contract, &payment_id, &100),
+        Err(Ok(Error::Payment(PaymentError::InvalidStatus)))
+    );
+    // Settling the refund leaves the rolling reserve to the refund contract.
+    assert_eq!(
+        s.client.settle_refund(
+            &refund_contract,
+            &payment_id,
+            &s.merchant,
+            &s.token.address,
+            &300,
+        ),
+        0
+    );
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        100
+    );
+
+    assert_eq!(
+        s.client
+            .try_draw_reserve_for_refund(&s.merchant, &payment_id, &100),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client
+            .try_draw_reserve_for_refund(&refund_contract, &payment_id, &301),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+    assert_eq!(
+        s.client
+            .draw_reserve_for_refund(&refund_contract, &payment_id, &300),
+        100
+    );
+    assert_eq!(s.token.balance(&refund_contract), 100);
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        0
+    );
+    assert!(s.client.get_reserve_release_days(&s.merchant).is_empty());
+}
+
+#[test]
+fn test_reserve_entries_bucketed_by_release_day() {
+    let s = setup(10_000);
+    s.client.set_reserve_tier(
+        &s.admin,
+        &tier(MerchantVerificationLevel::Unverified, 1_000, 0),
+    );
+    paid_payment(&s, 1_000);
+    paid_payment(&s, 500);
+    s.env.ledger().set_timestamp(START + SECONDS_PER_DAY);
+    let later = paid_payment(&s, 2_000);
+
+    assert_eq!(
+        s.client.get_reserve_release_days(&s.merchant),
+        soroban_sdk::vec![&s.env, RELEASE_DAY, RELEASE_DAY + 1]
+    );
+    assert_eq!(
+        s.client
+            .get_reserve_entries(&s.merchant, &RELEASE_DAY)
+            .len(),
+        2
+    );
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        350
+    );
+
+    // Only the first day's bucket has matured.
+    s.env.ledger().set_timestamp(START + HOLD);
+    assert_eq!(s.client.release_reserves(&s.merchant), 150);
+    assert_eq!(
+        s.client.get_reserve_release_days(&s.merchant),
+        soroban_sdk::vec![&s.env, RELEASE_DAY + 1]
+    );
+    assert_eq!(
+        s.client
+            .get_reserve_entries(&s.merchant, &(RELEASE_DAY + 1))
+            .get(0)
+            .unwrap()
+            .payment_id,
+        later
+    );
+    assert_eq!(
+        s.client.get_reserve_balance(&s.merchant, &s.token.address),
+        200
+    );
+}
+
+#[test]
+fn test_set_reserve_tier_validation() {
+    let s = setup(10_000);
+    assert_eq!(
+        s.client
+            .try_set_reserve_tier(&s.merchant, &tier(MerchantVerificationLevel::Basic, 500, 0)),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client
+            .try_set_reserve_tier(&s.admin, &tier(MerchantVerificationLevel::Basic, 10_001, 0)),
+        Err(Ok(Error::Basic(BasicError::InvalidBps)))
+    );
+    let mut no_hold = tier(MerchantVerificationLevel::Basic, 500, 0);
+    no_hold.hold_seconds = 0;
+    assert_eq!(
+        s.client.try_set_reserve_tier(&s.admin, &no_hold),
+        Err(Ok(Error::Basic(BasicError::InvalidInterval)))
+    );
+
+    assert_eq!(
+        s.client.get_reserve_tier(&MerchantVerificationLevel::Basic),
+        None
+    );
+    s.client
+        .set_reserve_tier(&s.admin, &tier(MerchantVerificationLevel::Basic, 500, 0));
+    assert_eq!(
+        s.client.get_reserve_tier(&MerchantVerificationLevel::Basic),
+        Some(tier(MerchantVerificationLevel::Basic, 500, 0))
+    );
+    // Without a tier for their level, merchants have no reserve.
+    paid_payment(&s, 1_000);
+    assert_eq!(s.token.balance(&s.merchant), 1_000);
+}
@@ -0,0 +1,479 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    contract, contractimpl,
+    testutils::{Address as _, Ledger},
+    token, vec, Address, Env, String,
+};
+
+const RATE_SCALE: i128 = 1_0000000;
+
+/// Constant-rate AMM implementing the swap venue interface. It pays out
+/// `amount_in * rate / RATE_SCALE`, minus an optional shortfall it does not
+/// report in its return value.
+#[contract]
+pub struct MockAmm;
+
+#[contractimpl]
+impl MockAmm {
+    pub fn set_rate(env: Env, token_in: Address, token_out: Address, rate: i128) {
+        env.storage().instance().set(&(token_in, token_out), &rate);
+    }
+
+    pub fn set_shortfall(env: Env, shortfall: i128) {
+        env.storage().instance().set(&0u32, &shortfall);
+    }
+
+    pub fn swap(
+        env: Env,
+        sender: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+    ) -> i128 {
+        let rate: i128 = env
+            .storage()
+            .instance()
+            .get(&(token_in.clone(), token_out.clone()))
+            .unwrap();
+        let amount_out = amount_in * rate / RATE_SCALE;
+        assert!(amount_out >= min_amount_out);
+
+        let amm = env.current_contract_address();
+        token::Client::new(&env, &token_in).transfer_from(&amm, &sender, &amm, &amount_in);
+        let shortfall: i128 = env.storage().instance().get(&0u32).unwrap_or(0);
+        token::Client::new(&env, &token_out).transfer(&amm, &sender, &(amount_out - shortfall));
+        amount_out
+    }
+}
+
+struct Setup<'a> {
+    env: Env,
+    client: PaymentContractClient<'a>,
+    admin: Address,
+    customer: Address,
+    merchant: Address,
+    xlm: token::Client<'a>,
+    usdc: token::Client<'a>,
+    usdt: token::Client<'a>,
+    amm: MockAmmClient<'a>,
+}
+
+/// XLM trades at 0.1 USD. The AMM quotes XLM→USDC at 0.0997 (0.1 less 30 bps),
+/// USDC→USDT at 0.999 (10 bps) and XLM→USDT at 0.099 (100 bps).
+fn setup<'a>() -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let client = PaymentContractClient::new(&env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+
+    let token = |env: &Env| {
+        let address = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        token::Client::new(env, &address)
+    };
+    let (xlm, usdc, usdt) = (token(&env), token(&env), token(&env));
+    let amm = MockAmmClient::new(&env, &env.register(MockAmm, ()));
+    token::StellarAssetClient::new(&env, &xlm.address).mint(&customer, &100_000);
+    for t in [&usdc, &usdt] {
+        token::StellarAssetClient::new(&env, &t.address).mint(&amm.address, &100_000);
+    }
+
+    client.set_conversion_rate(&admin, &Currency::XLM, &(RATE_SCALE / 10));
+    client.set_conversion_rate(&admin, &Currency::USDC, &RATE_SCALE);
+    client.set_conversion_rate(&admin, &Currency::USDT, &RATE_SCALE);
+
+    amm.set_rate(&xlm.address, &usdc.address, &997_000);
+    amm.set_rate(&usdc.address, &usdt.address, &9_990_000);
+    amm.set_rate(&xlm.address, &usdt.address, &990_000);
+    client.register_swap_venue(
+        &admin,
+        &amm.address,
+        &xlm.address,
+        &Currency::XLM,
+        &usdc.address,
+        &Currency::USDC,
+        &30,
+    );
+    client.register_swap_venue(
+        &admin,
+        &amm.address,
+        &usdc.address,
+        &Currency::USDC,
+        &usdt.address,
+        &Currency::USDT,
+        &10,
+    );
+    client.register_swap_venue(
+        &admin,
+        &amm.address,
+        &xlm.address,
+        &Currency::XLM,
+        &usdt.address,
+        &Currency::USDT,
+        &100,
+    );
+
+    Setup {
+        env,
+        client,
+        admin,
+        customer,
+        merchant,
+        xlm,
+        usdc,
+        usdt,
+        amm,
+    }
+}
+
+fn xlm_payment(s: &Setup, amount: i128) -> u64 {
+    s.xlm
+        .approve(&s.customer, &s.client.address, &amount, &1_000);
+    s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.xlm.address,
+        &Currency::XLM,
+        &0,
+        &String::from_str(&s.env, ""),
+    )
+}
+
+#[test]
+fn test_two_hop_route_beats_expensive_direct_venue() {
+    let s = setup();
+    let routes = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000);
+    assert_eq!(routes.len(), 2);
+
+    let best = routes.get(0).unwrap();
+    assert_eq!(best.hops.len(), 2);
+    assert_eq!(best.hops.get(0).unwrap().token_out, s.usdc.address);
+    assert_eq!(best.output_amount, 996);
+    assert_eq!(best.fee_bps, 40);
+    assert_eq!(best.effective_cost, 4);
+
+    let direct = routes.get(1).unwrap();
+    assert_eq!(direct.hops.len(), 1);
+    assert_eq!(direct.output_amount, 990);
+    assert_eq!(direct.effective_cost, 10);
+}
+
+#[test]
+fn test_routed_payment_delivers_output_token() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 10_000);
+    let route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(0)
+        .unwrap();
+
+    let delivered =
+        s.client
+            .execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &50);
+
+    assert_eq!(delivered, 996);
+    assert_eq!(s.usdt.balance(&s.merchant), 996);
+    assert_eq!(s.xlm.balance(&s.merchant), 0);
+    assert_eq!(s.xlm.balance(&s.customer), 90_000);
+    assert_eq!(s.xlm.balance(&s.amm.address), 10_000);
+    assert_eq!(s.usdc.balance(&s.client.address), 0);
+    assert_eq!(
+        s.client.get_payment(&payment_id).status,
+        PaymentStatus::Completed
+    );
+}
+
+#[test]
+fn test_slippage_bound_reverts_routed_payment() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 10_000);
+    let route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(0)
+        .unwrap();
+
+    // The pool moves after the quote: USDC→USDT now pays 0.95.
+    s.amm.set_rate(&s.usdc.address, &s.usdt.address, &9_500_000);
+    assert_eq!(
+        s.client
+            .try_execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &100),
+        Err(Ok(Error::Routing(RoutingError::SlippageExceeded)))
+    );
+    assert_eq!(s.xlm.balance(&s.customer), 100_000);
+    assert_eq!(
+        s.client.get_payment(&payment_id).status,
+        PaymentStatus::Pending
+    );
+
+    // A looser bound accepts the worse fill.
+    let delivered =
+        s.client
+            .execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &600);
+    assert_eq!(delivered, 947);
+}
+
+#[test]
+fn test_slippage_is_measured_against_on_chain_quote() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 10_000);
+    let mut route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(0)
+        .unwrap();
+
+    // A caller-supplied quote of 1 cannot loosen the bound.
+    route.output_amount = 1;
+    s.amm.set_rate(&s.usdc.address, &s.usdt.address, &9_500_000);
+    assert_eq!(
+        s.client
+            .try_execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &100),
+        Err(Ok(Error::Routing(RoutingError::SlippageExceeded)))
+    );
+
+    // The quote overflows once the input currency is priced absurdly high.
+    s.client
+        .set_conversion_rate(&s.admin, &Currency::XLM, &(i128::MAX / 100));
+    assert_eq!(
+        s.client
+            .try_execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &100),
+        Err(Ok(Error::Routing(RoutingError::QuoteOverflow)))
+    );
+}
+
+#[test]
+fn test_routed_payment_charges_platform_fee() {
+    let s = setup();
+    s.client.set_fee_config(
+        &s.admin,
+        &FeeConfig {
+            fee_bps: 100,
+            min_fee: 0,
+            max_fee: 0,
+            treasury: s.admin.clone(),
+            fee_token: s.xlm.address.clone(),
+            active: true,
+        },
+    );
+    let payment_id = xlm_payment(&s, 10_000);
+    let route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(0)
+        .unwrap();
+
+    let delivered =
+        s.client
+            .execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &50);
+
+    // The fee stays in the contract until it is swept to the treasury.
+    let fee = s.xlm.balance(&s.client.address);
+    assert!(fee > 0);
+    assert_eq!(s.xlm.balance(&s.amm.address), 10_000 - fee);
+    assert_eq!(s.xlm.balance(&s.customer), 90_000);
+    assert_eq!(s.usdt.balance(&s.merchant), delivered);
+    assert!(delivered < 996);
+    assert_eq!(
+        s.client.get_payment(&payment_id).status,
+        PaymentStatus::Completed
+    );
+}
+
+#[test]
+fn test_hop_output_is_measured_from_balance() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 10_000);
+    let route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(0)
+        .unwrap();
+
+    // Each hop's adapter reports 10 more than it sends.
+    s.amm.set_shortfall(&10);
+    let delivered =
+        s.client
+            .execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &10_000);
+
+    assert_eq!(delivered, s.usdt.balance(&s.merchant));
+    assert_eq!(delivered, 987 * 9_990_000 / RATE_SCALE - 10);
+}
+
+#[test]
+fn test_disabled_venue_and_hop_limit_constrain_routes() {
+    let s = setup();
+    s.client.set_swap_venue_active(&s.admin, &3, &false);
+    let routes = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000);
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes.get(0).unwrap().hops.len(), 2);
+
+    s.client.set_max_route_hops(&s.admin, &1);
+    assert!(s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .is_empty());
+    assert_eq!(
+        s.client.try_set_max_route_hops(&s.admin, &5),
+        Err(Ok(Error::Routing(RoutingError::TooManyHops)))
+    );
+}
+
+#[test]
+fn test_route_through_disabled_venue_is_rejected() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 10_000);
+    let route = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.usdt.address, &10_000)
+        .get(1)
+        .unwrap();
+
+    s.client.set_swap_venue_active(&s.admin, &3, &false);
+    assert_eq!(
+        s.client
+            .try_execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &100),
+        Err(Ok(Error::Routing(RoutingError::InvalidRoute)))
+    );
+
+    let mut forged = route.clone();
+    forged.output_token = s.usdc.address.clone();
+    s.client.set_swap_venue_active(&s.admin, &3, &true);
+    assert_eq!(
+        s.client
+            .try_execute_routed_payment(&s.customer, &s.merchant, &forged, &payment_id, &100),
+        Err(Ok(Error::Routing(RoutingError::InvalidRoute)))
+    );
+}
+
+#[test]
+fn test_same_token_route_pays_directly() {
+    let s = setup();
+    let payment_id = xlm_payment(&s, 500);
+    let routes = s
+        .client
+        .get_optimal_route(&s.xlm.address, &s.xlm.address, &500);
+    assert_eq!(routes.len(), 1);
+    let route = routes.get(0).unwrap();
+    assert!(route.hops.is_empty());
+
+    s.client
+        .execute_routed_payment(&s.customer, &s.merchant, &route, &payment_id, &0);
+    assert_eq!(s.xlm.balance(&s.merchant), 500);
+}
+
+// ── Settlement in the merchant's preferred token ─────────────────────────
+
+#[test]
+fn test_complete_payment_settles_in_preferred_token() {
+    let s = setup();
+    s.client
+        .set_settlement_preference(&s.merchant, &s.usdc.address, &100);
+    let payment_id = xlm_payment(&s, 10_000);
+
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    assert_eq!(s.usdc.balance(&s.merchant), 997);
+    assert_eq!(s.xlm.balance(&s.merchant), 0);
+    assert_eq!(s.xlm.balance(&s.amm.address), 10_000);
+    assert_eq!(s.xlm.balance(&s.client.address), 0);
+}
+
+#[test]
+fn test_failed_conversion_falls_back_to_payment_token() {
+    let s = setup();
+    s.client
+        .set_settlement_preference(&s.merchant, &s.usdc.address, &100);
+    // The pool now pays half the quoted rate, so the adapter rejects the swap.
+    s.amm.set_rate(&s.xlm.address, &s.usdc.address, &500_000);
+    let payment_id = xlm_payment(&s, 10_000);
+
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    assert_eq!(s.xlm.balance(&s.merchant), 10_000);
+    assert_eq!(s.usdc.balance(&s.merchant), 0);
+    assert_eq!(s.xlm.balance(&s.amm.address), 0);
+    assert_eq!(s.xlm.allowance(&s.client.address, &s.amm.address), 0);
+}
+
+#[test]
+fn test_conversion_uses_a_single_venue() {
+    let s = setup();
+    s.client
+        .set_settlement_preference(&s.merchant, &s.usdt.address, &100);
+    s.client.set_swap_venue_active(&s.admin, &3, &false);
+    let payment_id = xlm_payment(&s, 10_000);
+
+    // XLM→USDC→USDT is still routable, but settlement does not chain swaps.
+    s.client.complete_payment(&s.admin, &payment_id);
+    assert_eq!(s.xlm.balance(&s.merchant), 10_000);
+
+    s.client.clear_settlement_preference(&s.merchant);
+    assert_eq!(s.client.get_settlement_preference(&s.merchant), None);
+}
+
+#[test]
+fn test_split_settlement_converts_per_recipient() {
+    let s = setup();
+    let partner = Address::generate(&s.env);
+    s.client
+        .set_settlement_preference(&s.merchant, &s.usdc.address, &100);
+    let recipients = vec![
+        &s.env,
+        SplitRecipient {
+            address: s.merchant.clone(),
+            share_bps: 7_000,
+        },
+        SplitRecipient {
+            address: partner.clone(),
+            share_bps: 3_000,
+        },
+    ];
+    let payment_id = s.client.create_split_payment(
+        &s.customer,
+        &s.merchant,
+        &1_000,
+        &s.xlm.address,
+        &recipients,
+    );
+
+    s.client.execute_split_settlement(&s.admin, &payment_id);
+
+    assert_eq!(s.usdc.balance(&s.merchant), 69);
+    assert_eq!(s.xlm.balance(&partner), 300);
+}
+
+#[test]
+fn test_scheduled_payout_converts_accumulated_balance() {
+    let s = setup();
+    s.client
+        .set_payout_schedule(&s.merchant, &PayoutFrequency::Daily, &s.xlm.address);
+    s.client
+        .set_settlement_preference(&s.merchant, &s.usdc.address, &100);
+    s.xlm
+        .approve(&s.customer, &s.client.address, &10_000, &1_000);
+    let payment_id =
+        s.client
+            .schedule_payment(&s.customer, &s.merchant, &s.xlm.address, &10_000, &10);
+    s.env.ledger().set_timestamp(10);
+    s.client.execute_scheduled_payment(&payment_id);
+    assert_eq!(s.client.get_accumulated_balance(&s.merchant), 10_000);
+
+    s.env.ledger().set_timestamp(SECONDS_PER_DAY);
+    s.client.trigger_scheduled_payout(&s.merchant);
+    assert_eq!(s.usdc.balance(&s.merchant), 997);
+}
@@ -0,0 +1,231 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, Error, PendingPlanChange, PlanChange, PlanChangeTiming, SubscriptionError,
+};
+
+const INTERVAL: u64 = 1_000;
+
+fn subscribe(s: &Setup, amount: i128) -> u64 {
+    s.client.create_subscription(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &INTERVAL,
+        &0,
+        &0,
+        &String::from_str(&s.env, ""),
+        &0,
+    )
+}
+
+/// Charges the first cycle, which pays for `[START + INTERVAL, START + 2 * INTERVAL)`.
+fn charge_first_cycle(s: &Setup, sub_id: u64) {
+    s.env.ledger().set_timestamp(START + INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+}
+
+#[test]
+fn test_immediate_upgrade_charges_prorated_difference() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let sub_id = subscribe(&s, 300);
+    charge_first_cycle(&s, sub_id);
+
+    // Half the paid cycle is left: (500 - 300) * 500 / 1_000.
+    s.env.ledger().set_timestamp(START + INTERVAL + 500);
+    let charged = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(500),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(charged, 100);
+    assert_eq!(s.token.balance(&s.merchant), 400);
+
+    let sub = s.client.get_subscription(&sub_id);
+    assert_eq!(sub.amount, 500);
+    assert_eq!(sub.payment_count, 1);
+    assert_eq!(sub.next_payment_at, START + 2 * INTERVAL);
+
+    s.env.ledger().set_timestamp(START + 2 * INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 900);
+}
+
+#[test]
+fn test_immediate_downgrade_credits_later_cycles() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let sub_id = subscribe(&s, 300);
+    charge_first_cycle(&s, sub_id);
+
+    // (100 - 300) * 750 / 1_000 = -150, more than one cycle of the new price.
+    s.env.ledger().set_timestamp(START + INTERVAL + 250);
+    let credited = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(100),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(credited, -150);
+    assert_eq!(s.client.get_proration_credit(&sub_id), 150);
+    assert_eq!(s.token.balance(&s.merchant), 300);
+
+    s.env.ledger().set_timestamp(START + 2 * INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 300);
+    assert_eq!(s.client.get_proration_credit(&sub_id), 50);
+    assert_eq!(s.client.get_subscription(&sub_id).payment_count, 2);
+
+    s.env.ledger().set_timestamp(START + 3 * INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 350);
+    assert_eq!(s.client.get_proration_credit(&sub_id), 0);
+}
+
+#[test]
+fn test_next_cycle_change_applies_at_next_charge() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let sub_id = subscribe(&s, 300);
+    charge_first_cycle(&s, sub_id);
+
+    s.env.ledger().set_timestamp(START + INTERVAL + 500);
+    let charged = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(500),
+        &PlanChangeTiming::NextCycle,
+    );
+    assert_eq!(charged, 0);
+    assert_eq!(s.client.get_subscription(&sub_id).amount, 300);
+    assert_eq!(
+        s.client.get_pending_plan_change(&sub_id),
+        Some(PendingPlanChange {
+            change: PlanChange::Amount(500),
+            effective_at: START + 2 * INTERVAL,
+        })
+    );
+
+    s.env.ledger().set_timestamp(START + 2 * INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 800);
+    assert_eq!(s.client.get_subscription(&sub_id).amount, 500);
+    assert_eq!(s.client.get_pending_plan_change(&sub_id), None);
+}
+
+#[test]
+fn test_proration_uses_group_discount() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let sub_id = subscribe(&s, 300);
+    let group_id = s.client.create_subscription_group(&s.merchant, &1_000);
+    s.client.add_to_group(&s.merchant, &group_id, &sub_id);
+
+    charge_first_cycle(&s, sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 270);
+
+    // (450 - 270) * 500 / 1_000 after the 10% group discount.
+    s.env.ledger().set_timestamp(START + INTERVAL + 500);
+    let charged = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(500),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(charged, 90);
+    assert_eq!(s.token.balance(&s.merchant), 360);
+}
+
+#[test]
+fn test_change_to_another_plan() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let plan = |price: i128, interval: u64| {
+        s.client.create_subscription_plan(
+            &s.merchant,
+            &s.token.address,
+            &Currency::USDC,
+            &price,
+            &interval,
+            &0,
+            &0,
+            &0,
+            &None,
+            &String::from_str(&s.env, ""),
+        )
+    };
+    let basic = plan(100, INTERVAL);
+    let pro = plan(300, INTERVAL);
+    let yearly = plan(1_000, INTERVAL * 12);
+
+    let sub_id = s.client.subscribe_to_plan(&s.customer, &basic);
+    charge_first_cycle(&s, sub_id);
+
+    assert_eq!(
+        s.client.try_change_subscription_plan(
+            &s.customer,
+            &sub_id,
+            &PlanChange::Plan(yearly),
+            &PlanChangeTiming::Immediate,
+        ),
+        Err(Ok(Error::Subscription(SubscriptionError::IncompatiblePlan)))
+    );
+
+    s.env.ledger().set_timestamp(START + INTERVAL + 500);
+    let charged = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Plan(pro),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(charged, 100);
+    assert_eq!(s.client.get_subscription(&sub_id).amount, 300);
+    assert_eq!(s.client.get_subscription_plan_id(&sub_id), Some(pro));
+
+    // A custom amount detaches the subscription from plan pricing.
+    s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(300),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(s.client.get_subscription_plan_id(&sub_id), None);
+}
+
+#[test]
+fn test_change_before_first_charge_is_not_prorated() {
+    let s = setup(10_000);
+    s.approve(10_000);
+    let sub_id = subscribe(&s, 300);
+
+    assert_eq!(
+        s.client.try_change_subscription_plan(
+            &Address::generate(&s.env),
+            &sub_id,
+            &PlanChange::Amount(500),
+            &PlanChangeTiming::Immediate,
+        ),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+
+    s.env.ledger().set_timestamp(START + 500);
+    let charged = s.client.change_subscription_plan(
+        &s.customer,
+        &sub_id,
+        &PlanChange::Amount(500),
+        &PlanChangeTiming::Immediate,
+    );
+    assert_eq!(charged, 0);
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert_eq!(s.client.get_subscription(&sub_id).amount, 500);
+}
@@ -0,0 +1,193 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, String,
+};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, DunningConfig, Error, SubscriptionError, PLAN_PRICE_NOTICE_PERIOD,
+    SECONDS_PER_DAY,
+};
+
+const INTERVAL: u64 = 10 * SECONDS_PER_DAY;
+
+fn create_plan(s: &Setup, price: i128, dunning: Option<DunningConfig>) -> u64 {
+    s.client.create_subscription_plan(
+        &s.merchant,
+        &s.token.address,
+        &Currency::USDC,
+        &price,
+        &INTERVAL,
+        &0u64,
+        &0u64,
+        &2u64,
+        &dunning,
+        &String::from_str(&s.env, "pro"),
+    )
+}
+
+#[test]
+fn test_subscription_takes_plan_terms() {
+    let s = setup(1_000);
+    let plan_id = create_plan(&s, 100, None);
+    assert_eq!(
+        s.client.get_merchant_plans(&s.merchant),
+        vec![&s.env, plan_id]
+    );
+
+    let sub_id = s.client.subscribe_to_plan(&s.customer, &plan_id);
+    let sub = s.client.get_subscription(&sub_id);
+    assert_eq!(sub.merchant, s.merchant);
+    assert_eq!(sub.amount, 100);
+    assert_eq!(sub.token, s.token.address);
+    assert_eq!(sub.interval, INTERVAL);
+    assert_eq!(sub.max_retries, 2);
+    assert_eq!(sub.metadata, String::from_str(&s.env, "pro"));
+    assert_eq!(s.client.get_subscription_plan_id(&sub_id), Some(plan_id));
+
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+    s.env.ledger().set_timestamp(START + INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 100);
+}
+
+#[test]
+fn test_price_change_applies_after_notice_period() {
+    let s = setup(1_000);
+    let plan_id = create_plan(&s, 100, None);
+    let sub_id = s.client.subscribe_to_plan(&s.customer, &plan_id);
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+
+    let effective_at = START + PLAN_PRICE_NOTICE_PERIOD;
+    assert_eq!(
+        s.client
+            .try_schedule_plan_price(&s.merchant, &plan_id, &150, &(effective_at - 1)),
+        Err(Ok(Error::Subscription(
+            SubscriptionError::PriceNoticeTooShort
+        )))
+    );
+    assert_eq!(
+        s.client
+            .schedule_plan_price(&s.merchant, &plan_id, &150, &effective_at),
+        2
+    );
+    assert_eq!(
+        s.client
+            .try_schedule_plan_price(&s.merchant, &plan_id, &200, &effective_at),
+        Err(Ok(Error::Subscription(
+            SubscriptionError::PriceScheduleConflict
+        )))
+    );
+
+    // Cycles before the new price takes effect still charge the old one.
+    s.env.ledger().set_timestamp(START + INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    s.env.ledger().set_timestamp(START + 2 * INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 200);
+
+    s.env.ledger().set_timestamp(effective_at);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 350);
+    assert_eq!(s.client.get_subscription(&sub_id).amount, 150);
+
+    // New subscribers join at the price in effect.
+    let late_sub = s.client.subscribe_to_plan(&s.customer, &plan_id);
+    assert_eq!(s.client.get_subscription(&late_sub).amount, 150);
+}
+
+#[test]
+fn test_archived_plan_rejects_new_subscribers_only() {
+    let s = setup(1_000);
+    let plan_id = create_plan(&s, 100, None);
+    let sub_id = s.client.subscribe_to_plan(&s.customer, &plan_id);
+
+    assert_eq!(
+        s.client
+            .try_archive_subscription_plan(&Address::generate(&s.env), &plan_id),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    s.client.archive_subscription_plan(&s.merchant, &plan_id);
+    assert!(s.client.get_subscription_plan(&plan_id).archived);
+    assert_eq!(
+        s.client.try_subscribe_to_plan(&s.customer, &plan_id),
+        Err(Ok(Error::Subscription(SubscriptionError::PlanArchived)))
+    );
+    assert_eq!(
+        s.client
+            .try_archive_subscription_plan(&s.merchant, &plan_id),
+        Err(Ok(Error::Subscription(SubscriptionError::PlanArchived)))
+    );
+
+    s.token
+        .approve(&s.customer, &s.client.address, &1_000, &1_000);
+    s.env.ledger().set_timestamp(START + INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(s.token.balance(&s.merchant), 100);
+}
+
+#[test]
+fn test_plan_dunning_config_overrides_global() {
+    let s = setup(1_000);
+    let plan_id = create_plan(
+        &s,
+        100,
+        Some(DunningConfig {
+            initial_backoff_seconds: 60,
+            max_retries: 1,
+        }),
+    );
+    let sub_id = s.client.subscribe_to_plan(&s.customer, &plan_id);
+
+    // No allowance, so the first charge fails and enters dunning.
+    s.env.ledger().set_timestamp(START + INTERVAL);
+    s.client.execute_recurring_payment(&sub_id);
+
+    let dunning = s.client.get_dunning_state(&sub_id).unwrap();
+    assert_eq!(dunning.backoff_seconds, 60);
+    assert_eq!(dunning.max_retries, 1);
+}
+
+#[test]
+fn test_merchant_subscriptions_filter_by_plan() {
+    let s = setup(1_000);
+    let plan_a = create_plan(&s, 100, None);
+    let plan_b = create_plan(&s, 300, None);
+    let sub_a = s.client.subscribe_to_plan(&s.customer, &plan_a);
+    let sub_b = s.client.subscribe_to_plan(&s.customer, &plan_b);
+    let direct = s.client.create_subscription(
+        &s.customer,
+        &s.merchant,
+        &50,
+        &s.token.address,
+        &Currency::USDC,
+        &INTERVAL,
+        &0,
+        &0,
+        &String::from_str(&s.env, ""),
+        &0,
+    );
+
+    assert_eq!(
+        s.client.get_merchant_subscriptions(&s.merchant, &0),
+        vec![&s.env, sub_a, sub_b, direct]
+    );
+    assert_eq!(
+        s.client
+            .get_merchant_plan_subscriptions(&s.merchant, &plan_a, &0),
+        vec![&s.env, sub_a]
+    );
+    assert_eq!(
+        s.client
+            .get_merchant_plan_subscriptions(&s.merchant, &plan_b, &0),
+        vec![&s.env, sub_b]
+    );
+    assert_eq!(
+        s.client.try_subscribe_to_plan(&s.customer, &99),
+        Err(Ok(Error::Subscription(SubscriptionError::PlanNotFound)))
+    );
+}
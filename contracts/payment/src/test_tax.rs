@@ -0,0 +1,299 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, vec, Address, BytesN, String, Symbol, Vec,
+};
+
+use crate::test_fixture::{setup, Setup};
+use crate::{
+    BasicError, Currency, Error, InvoiceKind, LateFeePolicy, LineItem, PaymentError, TaxLine,
+    TaxRate, TaxRule,
+};
+
+fn category(s: &Setup, n: u8) -> BytesN<32> {
+    BytesN::from_array(&s.env, &[n; 32])
+}
+
+fn item(s: &Setup, amount: i128) -> LineItem {
+    LineItem {
+        description_hash: BytesN::from_array(&s.env, &[0; 32]),
+        quantity: 1,
+        unit_price: amount,
+        amount,
+    }
+}
+
+fn rule(s: &Setup, inclusive: bool, rates: &[(u32, bool)]) -> TaxRule {
+    let mut list = Vec::new(&s.env);
+    for (rate_bps, compound) in rates {
+        list.push_back(TaxRate {
+            rate_bps: *rate_bps,
+            compound: *compound,
+        });
+    }
+    TaxRule {
+        inclusive,
+        rates: list,
+    }
+}
+
+fn pay(s: &Setup, amount: i128) -> u64 {
+    s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    )
+}
+
+fn jurisdiction() -> Symbol {
+    symbol_short!("CA_ON")
+}
+
+#[test]
+fn test_exclusive_tax_uses_bankers_rounding() {
+    let s = setup(0);
+    s.client.set_tax_rule(
+        &s.admin,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, false, &[(500, false)]),
+    );
+
+    // 5% of 50 is 2.5 and of 70 is 3.5; both round to the even neighbour.
+    let payment_id = pay(&s, 126);
+    let invoice_id = s.client.attach_invoice_with_tax(
+        &s.merchant,
+        &payment_id,
+        &vec![&s.env, item(&s, 50), item(&s, 70)],
+        &jurisdiction(),
+        &vec![&s.env, category(&s, 1), category(&s, 1)],
+    );
+
+    let invoice = s.client.get_invoice(&invoice_id).unwrap();
+    assert_eq!(invoice.subtotal, 120);
+    assert_eq!(invoice.tax, 6);
+    assert_eq!(invoice.total, 126);
+    assert!(s.client.verify_invoice_total(&invoice_id));
+
+    let breakdown = s
+        .client
+        .get_tax_breakdown(&InvoiceKind::Attached, &invoice_id);
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown.get(0).unwrap().tax, 2);
+    assert_eq!(breakdown.get(1).unwrap().tax, 4);
+    assert_eq!(breakdown.get(1).unwrap().line, 1);
+}
+
+#[test]
+fn test_compounding_rate_taxes_earlier_taxes() {
+    let s = setup(0);
+    s.client.set_tax_rule(
+        &s.admin,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, false, &[(500, false), (1_000, true)]),
+    );
+
+    let payment_id = pay(&s, 1_155);
+    let invoice_id = s.client.attach_invoice_with_tax(
+        &s.merchant,
+        &payment_id,
+        &vec![&s.env, item(&s, 1_000)],
+        &jurisdiction(),
+        &vec![&s.env, category(&s, 1)],
+    );
+
+    let breakdown = s
+        .client
+        .get_tax_breakdown(&InvoiceKind::Attached, &invoice_id);
+    assert_eq!(
+        breakdown.get(1).unwrap(),
+        TaxLine {
+            line: 0,
+            jurisdiction: jurisdiction(),
+            category: category(&s, 1),
+            rate_bps: 1_000,
+            compound: true,
+            inclusive: false,
+            taxable_amount: 1_050,
+            tax: 105,
+        }
+    );
+    assert_eq!(s.client.get_invoice(&invoice_id).unwrap().tax, 155);
+}
+
+#[test]
+fn test_inclusive_tax_is_carved_out_of_line_amount() {
+    let s = setup(0);
+    s.client.set_tax_rule(
+        &s.admin,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, true, &[(2_000, false)]),
+    );
+    s.client.set_tax_rule(
+        &s.admin,
+        &jurisdiction(),
+        &category(&s, 2),
+        &rule(&s, true, &[(500, false), (500, false)]),
+    );
+
+    let payment_id = pay(&s, 221);
+    let invoice_id = s.client.attach_invoice_with_tax(
+        &s.merchant,
+        &payment_id,
+        &vec![&s.env, item(&s, 120), item(&s, 101)],
+        &jurisdiction(),
+        &vec![&s.env, category(&s, 1), category(&s, 2)],
+    );
+
+    // Inclusive tax is already in the line amounts, so nothing is added.
+    assert_eq!(s.client.get_invoice(&invoice_id).unwrap().tax, 0);
+
+    let breakdown = s
+        .client
+        .get_tax_breakdown(&InvoiceKind::Attached, &invoice_id);
+    assert_eq!(breakdown.get(0).unwrap().taxable_amount, 100);
+    assert_eq!(breakdown.get(0).unwrap().tax, 20);
+    // 101 / 1.1 rounds to a net of 92; 5 + 5 would overshoot by one, so the
+    // last rate absorbs it.
+    assert_eq!(breakdown.get(1).unwrap().taxable_amount, 92);
+    assert_eq!(breakdown.get(1).unwrap().tax, 5);
+    assert_eq!(breakdown.get(2).unwrap().tax, 4);
+}
+
+#[test]
+fn test_merchant_rule_overrides_platform_rule() {
+    let s = setup(0);
+    assert_eq!(
+        s.client.try_set_tax_rule(
+            &s.merchant,
+            &jurisdiction(),
+            &category(&s, 1),
+            &rule(&s, false, &[(500, false)]),
+        ),
+        Err(Ok(Error::Basic(BasicError::Unauthorized)))
+    );
+    assert_eq!(
+        s.client.try_set_merchant_tax_rule(
+            &s.merchant,
+            &jurisdiction(),
+            &category(&s, 1),
+            &rule(&s, false, &[(10_001, false)]),
+        ),
+        Err(Ok(Error::Payment(PaymentError::InvalidTaxRule)))
+    );
+
+    s.client.set_tax_rule(
+        &s.admin,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, false, &[(500, false)]),
+    );
+    s.client.set_merchant_tax_rule(
+        &s.merchant,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, false, &[]),
+    );
+    assert_eq!(
+        s.client.get_tax_rule(
+            &Address::generate(&s.env),
+            &jurisdiction(),
+            &category(&s, 1)
+        ),
+        Some(rule(&s, false, &[(500, false)]))
+    );
+
+    // The merchant's exemption wins over the platform's 5%.
+    let payment_id = pay(&s, 100);
+    let invoice_id = s.client.attach_invoice_with_tax(
+        &s.merchant,
+        &payment_id,
+        &vec![&s.env, item(&s, 100)],
+        &jurisdiction(),
+        &vec![&s.env, category(&s, 1)],
+    );
+    assert_eq!(s.client.get_invoice(&invoice_id).unwrap().tax, 0);
+    assert!(s
+        .client
+        .get_tax_breakdown(&InvoiceKind::Attached, &invoice_id)
+        .is_empty());
+
+    let payment_id = pay(&s, 100);
+    assert_eq!(
+        s.client.try_attach_invoice_with_tax(
+            &s.merchant,
+            &payment_id,
+            &vec![&s.env, item(&s, 100)],
+            &jurisdiction(),
+            &vec![&s.env, category(&s, 2)],
+        ),
+        Err(Ok(Error::Payment(PaymentError::TaxRuleNotFound)))
+    );
+}
+
+#[test]
+fn test_issued_invoice_tax_computed_at_issuance() {
+    let s = setup(0);
+    let invoice_id = s.client.create_invoice(
+        &s.merchant,
+        &s.customer,
+        &Currency::USDC,
+        &vec![&s.env, item(&s, 900)],
+        &0,
+        &vec![&s.env],
+        &10_000,
+        &LateFeePolicy {
+            bps_per_period: 0,
+            period_seconds: 0,
+            max_fee: 0,
+        },
+    );
+    s.client.set_invoice_tax_context(
+        &s.merchant,
+        &invoice_id,
+        &jurisdiction(),
+        &vec![&s.env, category(&s, 1)],
+    );
+
+    // Rules registered after the draft was created still apply at issuance.
+    s.client.set_merchant_tax_rule(
+        &s.merchant,
+        &jurisdiction(),
+        &category(&s, 1),
+        &rule(&s, false, &[(1_000, false)]),
+    );
+    s.client.issue_invoice(&s.merchant, &invoice_id);
+
+    let invoice = s.client.get_issued_invoice(&invoice_id);
+    assert_eq!(invoice.tax, 90);
+    assert_eq!(invoice.total, 990);
+    assert_eq!(s.client.get_invoice_amount_due(&invoice_id), 990);
+    assert_eq!(
+        s.client
+            .get_tax_breakdown(&InvoiceKind::Issued, &invoice_id)
+            .get(0)
+            .unwrap()
+            .tax,
+        90
+    );
+    // The attached-invoice ID space is separate.
+    assert!(s
+        .client
+        .get_tax_breakdown(&InvoiceKind::Attached, &invoice_id)
+        .is_empty());
+    assert_eq!(
+        s.client.try_set_invoice_tax_context(
+            &s.merchant,
+            &invoice_id,
+            &jurisdiction(),
+            &vec![&s.env, category(&s, 1)],
+        ),
+        Err(Ok(Error::Payment(PaymentError::InvoiceNotOpen)))
+    );
+}
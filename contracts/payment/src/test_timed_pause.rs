@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env, String,
+};
+
+fn setup(env: &Env) -> (PaymentContractClient<'_>, Address) {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_000);
+    let admin = Address::generate(env);
+    let client = PaymentContractClient::new(env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+    (client, admin)
+}
+
+fn reason(env: &Env) -> String {
+    String::from_str(env, "mistaken pause")
+}
+
+#[test]
+fn test_timed_pause_lifts_after_duration() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    assert_eq!(client.get_paused_until(), Some(1_600));
+    assert!(client.get_pause_state().globally_paused);
+    assert_eq!(
+        client.try_set_max_forward_depth(&admin, &3),
+        Err(Ok(Error::Basic(BasicError::ContractPaused)))
+    );
+
+    env.ledger().set_timestamp(1_600);
+    assert!(!client.get_pause_state().globally_paused);
+    assert!(!client.is_function_paused(&String::from_str(&env, "create_payment")));
+    assert_eq!(client.get_paused_until(), None);
+
+    client.set_max_forward_depth(&admin, &3);
+    assert_eq!(client.get_max_forward_depth(), 3);
+}
+
+#[test]
+fn test_expired_pause_is_persisted_on_first_guarded_call() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    env.ledger().set_timestamp(2_000);
+    client.set_max_forward_depth(&admin, &3);
+
+    env.as_contract(&client.address, || {
+        let state: PauseState = env
+            .storage()
+            .instance()
+            .get(&DataKey::Config(ConfigKey::PauseStateKey))
+            .unwrap();
+        assert!(!state.globally_paused);
+        assert!(!env
+            .storage()
+            .instance()
+            .has(&DataKey::Config(ConfigKey::PausedUntil)));
+
+        let entry: PauseHistory = env
+            .storage()
+            .instance()
+            .get(&DataKey::State(StateDataKey::PauseHistoryEntry(1)))
+            .unwrap();
+        assert!(!entry.paused);
+        assert_eq!(entry.changed_by, client.address);
+        assert_eq!(entry.changed_at, 2_000);
+    });
+}
+
+#[test]
+fn test_indefinite_pause_replaces_timed_pause() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    client.pause_contract(&admin, &reason(&env), &None);
+    assert_eq!(client.get_paused_until(), None);
+
+    env.ledger().set_timestamp(10_000);
+    assert!(client.get_pause_state().globally_paused);
+    assert_eq!(
+        client.try_set_max_forward_depth(&admin, &3),
+        Err(Ok(Error::Basic(BasicError::ContractPaused)))
+    );
+}
+
+#[test]
+fn test_shorter_repause_keeps_later_expiry() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    client.pause_contract(&admin, &reason(&env), &Some(100));
+    assert_eq!(client.get_paused_until(), Some(1_600));
+
+    client.pause_contract(&admin, &reason(&env), &Some(1_000));
+    assert_eq!(client.get_paused_until(), Some(2_000));
+}
+
+#[test]
+fn test_timed_repause_keeps_indefinite_pause() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &None);
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    assert_eq!(client.get_paused_until(), None);
+
+    env.ledger().set_timestamp(10_000);
+    assert!(client.get_pause_state().globally_paused);
+}
+
+#[test]
+fn test_unpause_clears_pause_duration() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    client.pause_contract(&admin, &reason(&env), &Some(600));
+    client.unpause_contract(&admin);
+    assert_eq!(client.get_paused_until(), None);
+
+    // A later indefinite pause must not inherit the old expiry.
+    client.pause_contract(&admin, &reason(&env), &None);
+    env.ledger().set_timestamp(5_000);
+    assert!(client.get_pause_state().globally_paused);
+}
@@ -0,0 +1,123 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol};
+
+const UPGRADE_WASM: &[u8] = include_bytes!("../../test_fixtures/add_u64.wasm");
+
+fn setup(env: &Env) -> (PaymentContractClient<'_>, Address, BytesN<32>) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let client = PaymentContractClient::new(env, &env.register(PaymentContract, ()));
+    client.initialize(&admin);
+    let wasm_hash = env.deployer().upload_contract_wasm(UPGRADE_WASM);
+    (client, admin, wasm_hash)
+}
+
+/// Calls `add` from the fixture WASM, which only succeeds once the code was replaced.
+fn runs_upgraded_code(env: &Env, contract: &Address) -> bool {
+    let sum: u64 = env.invoke_contract(
+        contract,
+        &Symbol::new(env, "add"),
+        vec![env, 2u64.into_val(env), 3u64.into_val(env)],
+    );
+    sum == 5
+}
+
+fn stored_hash(env: &Env, contract: &Address, key: ConfigKey) -> Option<BytesN<32>> {
+    env.as_contract(contract, || {
+        env.storage().instance().get(&DataKey::Config(key))
+    })
+}
+
+#[test]
+fn test_upgrade_replaces_code_and_records_hash() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+    assert_eq!(client.get_wasm_hash(), None);
+
+    client.upgrade(&admin, &wasm_hash);
+
+    assert!(runs_upgraded_code(&env, &client.address));
+    assert_eq!(
+        stored_hash(&env, &client.address, ConfigKey::WasmHash),
+        Some(wasm_hash)
+    );
+    assert_eq!(
+        stored_hash(&env, &client.address, ConfigKey::PreviousWasmHash),
+        None
+    );
+}
+
+#[test]
+fn test_upgrade_keeps_replaced_hash_for_rollback() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+    let earlier = BytesN::from_array(&env, &[7; 32]);
+    env.as_contract(&client.address, || {
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::WasmHash), &earlier);
+    });
+    assert_eq!(client.get_wasm_hash(), Some(earlier.clone()));
+
+    client.upgrade(&admin, &wasm_hash);
+
+    assert_eq!(
+        stored_hash(&env, &client.address, ConfigKey::PreviousWasmHash),
+        Some(earlier)
+    );
+    assert_eq!(
+        stored_hash(&env, &client.address, ConfigKey::WasmHash),
+        Some(wasm_hash)
+    );
+}
+
+#[test]
+fn test_upgrade_rejects_non_admin() {
+    let env = Env::default();
+    let (client, _admin, wasm_hash) = setup(&env);
+
+    let result = client.try_upgrade(&Address::generate(&env), &wasm_hash);
+    assert_eq!(result, Err(Ok(Error::Basic(BasicError::NotAnAdmin))));
+    assert_eq!(client.get_wasm_hash(), None);
+}
+
+#[test]
+fn test_upgrade_goes_through_multisig_when_threshold_above_one() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+    let admin2 = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        let config = MultiSigConfig {
+            admins: vec![&env, admin.clone(), admin2.clone()],
+            required_signatures: 2,
+            total_admins: 2,
+            proposal_ttl: 604800,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Config(ConfigKey::MultiSigConfig), &config);
+    });
+
+    let result = client.try_upgrade(&admin, &wasm_hash);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Proposal(ProposalError::RequiresMultiSig)))
+    );
+
+    let proposal_id = client.propose_action(
+        &admin,
+        &ActionType::UpgradeContract(wasm_hash.clone()),
+        &client.address,
+        &Bytes::new(&env),
+    );
+    client.approve_action(&admin2, &proposal_id);
+    client.execute_action(&proposal_id);
+
+    assert!(runs_upgraded_code(&env, &client.address));
+    assert_eq!(
+        stored_hash(&env, &client.address, ConfigKey::WasmHash),
+        Some(wasm_hash)
+    );
+}
@@ -0,0 +1,188 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Ledger, String};
+
+use crate::test_fixture::{setup, Setup, START};
+use crate::{
+    BasicError, Currency, DunningConfig, Error, FeatureError, PaymentError, SubscriptionStatus,
+};
+
+const DAY: u64 = 86_400;
+
+fn subscribe(s: &Setup, amount: i128) -> u64 {
+    s.client.create_subscription(
+        &s.customer,
+        &s.merchant,
+        &amount,
+        &s.token.address,
+        &Currency::USDC,
+        &DAY,
+        &0,
+        &3,
+        &String::from_str(&s.env, ""),
+        &0,
+    )
+}
+
+#[test]
+fn test_deposit_and_withdraw() {
+    let s = setup(10_000);
+    assert_eq!(
+        s.client
+            .try_wallet_deposit(&s.customer, &s.token.address, &0),
+        Err(Ok(Error::Basic(BasicError::InvalidAmount)))
+    );
+
+    assert_eq!(
+        s.client.wallet_deposit(&s.customer, &s.token.address, &700),
+        700
+    );
+    assert_eq!(
+        s.client.wallet_deposit(&s.customer, &s.token.address, &300),
+        1_000
+    );
+    assert_eq!(s.token.balance(&s.customer), 9_000);
+    assert_eq!(s.token.balance(&s.client.address), 1_000);
+
+    assert_eq!(
+        s.client
+            .try_wallet_withdraw(&s.customer, &s.token.address, &1_001),
+        Err(Ok(Error::Payment(PaymentError::InsufficientWalletBalance)))
+    );
+    assert_eq!(
+        s.client
+            .wallet_withdraw(&s.customer, &s.token.address, &400),
+        600
+    );
+    assert_eq!(s.token.balance(&s.customer), 9_400);
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        600
+    );
+}
+
+#[test]
+fn test_complete_payment_spends_wallet_before_allowance() {
+    let s = setup(10_000);
+    s.client.wallet_deposit(&s.customer, &s.token.address, &300);
+    s.token
+        .approve(&s.customer, &s.client.address, &200, &1_000);
+
+    let payment_id = s.client.create_payment(
+        &s.customer,
+        &s.merchant,
+        &500,
+        &s.token.address,
+        &Currency::USDC,
+        &0,
+        &String::from_str(&s.env, ""),
+    );
+    s.client.complete_payment(&s.admin, &payment_id);
+
+    assert_eq!(s.token.balance(&s.merchant), 500);
+    assert_eq!(s.token.balance(&s.customer), 9_500);
+    assert_eq!(s.token.balance(&s.client.address), 0);
+    assert_eq!(s.token.allowance(&s.customer, &s.client.address), 0);
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        0
+    );
+}
+
+#[test]
+fn test_recurring_charge_paid_from_wallet_without_allowance() {
+    let s = setup(10_000);
+    s.client
+        .wallet_deposit(&s.customer, &s.token.address, &1_000);
+    let sub_id = subscribe(&s, 400);
+
+    s.env.ledger().set_timestamp(START + DAY);
+    s.client.execute_recurring_payment(&sub_id);
+
+    assert_eq!(s.token.balance(&s.merchant), 400);
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        600
+    );
+    assert_eq!(s.client.get_subscription(&sub_id).payment_count, 1);
+}
+
+#[test]
+fn test_dunning_retry_succeeds_after_top_up() {
+    let s = setup(10_000);
+    s.client.set_dunning_config(
+        &s.admin,
+        &DunningConfig {
+            initial_backoff_seconds: 3_600,
+            max_retries: 4,
+        },
+    );
+    s.client.wallet_deposit(&s.customer, &s.token.address, &100);
+    let sub_id = subscribe(&s, 400);
+
+    // The wallet alone cannot cover the charge and there is no allowance.
+    s.env.ledger().set_timestamp(START + DAY);
+    s.client.execute_recurring_payment(&sub_id);
+    assert_eq!(
+        s.client.get_subscription(&sub_id).status,
+        SubscriptionStatus::InDunning
+    );
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        100
+    );
+
+    s.client.wallet_deposit(&s.customer, &s.token.address, &300);
+    let next_retry = s.client.get_dunning_state(&sub_id).unwrap().next_retry_at;
+    s.env.ledger().set_timestamp(next_retry);
+    s.client.execute_recurring_payment(&sub_id);
+
+    assert_eq!(
+        s.client.get_subscription(&sub_id).status,
+        SubscriptionStatus::Active
+    );
+    assert!(s.client.get_dunning_state(&sub_id).is_none());
+    assert_eq!(s.token.balance(&s.merchant), 400);
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        0
+    );
+}
+
+#[test]
+fn test_metered_billing_from_wallet_respects_spend_limit() {
+    let s = setup(10_000);
+    s.client
+        .wallet_deposit(&s.customer, &s.token.address, &1_000);
+    s.client
+        .set_customer_spend_limit(&s.admin, &s.customer, &500, &DAY);
+    let sub_id = s.client.create_metered_subscription(
+        &s.merchant,
+        &s.customer,
+        &100,
+        &String::from_str(&s.env, "gb"),
+        &s.token.address,
+        &None,
+        &None,
+    );
+
+    s.client.report_usage(&s.merchant, &sub_id, &6);
+    assert_eq!(
+        s.client.try_execute_metered_billing(&sub_id),
+        Err(Ok(Error::Feature(FeatureError::SpendLimitExceeded)))
+    );
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        1_000
+    );
+
+    s.env.ledger().set_timestamp(START + DAY + 1);
+    s.client
+        .set_customer_spend_limit(&s.admin, &s.customer, &600, &DAY);
+    assert_eq!(s.client.execute_metered_billing(&sub_id), 600);
+    assert_eq!(s.token.balance(&s.merchant), 600);
+    assert_eq!(
+        s.client.get_wallet_balance(&s.customer, &s.token.address),
+        400
+    );
+}
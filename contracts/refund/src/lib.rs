@@ -131,6 +131,7 @@ const CUSTOMER_HISTORY_HOT_CAP: u64 = 50;
 #[contracttype]
 pub enum SystemKey {
     PauseStateKey,
+    PausedUntil,
     PauseHistoryEntry(u64),
     PauseHistoryCount,
     CircuitBreakerConfigKey,
@@ -168,6 +169,9 @@ pub enum SystemKey {
     // Tracks the distinct (window_start, window_end) pairs cached above, so a newly
     // processed refund can invalidate only the windows it actually falls within.
     AnalyticsCacheWindows,
+    // Code installed by the last upgrade and the one it replaced (rollback target)
+    WasmHash,
+    PreviousWasmHash,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -195,6 +199,14 @@ pub enum TokenKey {
     TokenByIndex(u64),
 }
 
+// Merchant-funded refund reserves that `process_refund` pays customers from,
+// keyed by (merchant, token).
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum ReserveKey {
+    RefundReserve(Address, Address),
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[contracttype]
 pub enum RefundStatus {
@@ -299,6 +311,10 @@ pub enum ExtError {
     // Issue #389: two-step admin rotation errors
     NoPendingAdmin = 59,
     NotPendingAdmin = 60,
+    // Merchant refund reserve errors
+    InsufficientRefundReserve = 61,
+    // Linked payment contract rejected the refund settlement
+    PaymentSettlementFailed = 62,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -385,6 +401,24 @@ pub struct RefundProcessed {
     pub processed_at: u64,
 }
 
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundReserveDeposited {
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundReserveWithdrawn {
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub balance: i128,
+}
+
 #[contractevent]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AutoRefundTriggered {
@@ -1078,6 +1112,7 @@ pub struct ContractPausedEvent {
     pub paused_by: Address,
     pub reason: String,
     pub paused_at: u64,
+    pub paused_until: Option<u64>,
 }
 
 #[contractevent]
@@ -1338,6 +1373,83 @@ pub struct AdminRotationAccepted {
     pub new_admin: Address,
 }
 
+/// Event emitted when the contract code is upgraded.
+#[contractevent]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContractUpgraded {
+    pub previous_wasm_hash: Option<BytesN<32>>,
+    pub new_wasm_hash: BytesN<32>,
+}
+
+// ── Per-record persistent storage ───────────────────────────────────────────
+//
+// Refund requests, evidence, appeals, arbitration cases, vouchers and the
+// per-customer/merchant/payment refund indexes are stored in persistent
+// storage. Reads and writes bump the entry's TTL; entries that an earlier
+// release wrote to instance storage are moved across on first touch.
+
+const LEDGERS_PER_DAY: u32 = 17_280;
+const RECORD_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+const RECORD_TTL_EXTEND_TO: u32 = 120 * LEDGERS_PER_DAY;
+
+fn bump_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+}
+
+/// Moves a record written by a pre-persistent release out of instance storage.
+/// Returns `true` when the record now lives in persistent storage.
+fn promote_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if env.storage().persistent().has(key) {
+        return true;
+    }
+    match env.storage().instance().get::<K, Val>(key) {
+        Some(value) => {
+            env.storage().instance().remove(key);
+            env.storage().persistent().set(key, &value);
+            true
+        }
+        None => false,
+    }
+}
+
+fn record_get<K: IntoVal<Env, Val>, V: TryFromVal<Env, Val>>(env: &Env, key: &K) -> Option<V> {
+    if !promote_record(env, key) {
+        return None;
+    }
+    let value = env.storage().persistent().get(key);
+    bump_record(env, key);
+    value
+}
+
+fn record_set<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(env: &Env, key: &K, value: &V) {
+    if env.storage().instance().has(key) {
+        env.storage().instance().remove(key);
+    }
+    env.storage().persistent().set(key, value);
+    bump_record(env, key);
+}
+
+fn record_has<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    env.storage().persistent().has(key) || env.storage().instance().has(key)
+}
+
+fn record_remove<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage().persistent().remove(key);
+    env.storage().instance().remove(key);
+}
+
+/// Bumps a record's TTL, promoting it from instance storage first if needed.
+/// Returns `false` when no such record exists.
+fn touch_record<K: IntoVal<Env, Val>>(env: &Env, key: &K) -> bool {
+    if !promote_record(env, key) {
+        return false;
+    }
+    bump_record(env, key);
+    true
+}
+
 #[contract]
 pub struct RefundContract;
 
@@ -1432,6 +1544,88 @@ impl RefundContract {
         Ok(())
     }
 
+    /// Replace the contract code with the uploaded WASM identified by `new_wasm_hash`.
+    ///
+    /// The hash being replaced is kept as the rollback target. Storage is left
+    /// untouched; call [`Self::migrate_schema`] afterwards if the new code expects
+    /// a newer schema.
+    ///
+    /// # Arguments
+    /// * `admin` - The contract admin authorizing the upgrade.
+    /// * `new_wasm_hash` - Hash of WASM already uploaded to the network.
+    ///
+    /// # Errors
+    /// Returns `Unauthorized` if the caller is not the contract admin.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        admin.require_auth();
+        let stored_admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::Core(CoreError::Unauthorized))?;
+        if admin != stored_admin {
+            return Err(Error::Core(CoreError::Unauthorized));
+        }
+
+        let previous_wasm_hash: Option<BytesN<32>> =
+            env.storage().instance().get(&SystemKey::WasmHash);
+        if let Some(previous) = &previous_wasm_hash {
+            env.storage()
+                .instance()
+                .set(&SystemKey::PreviousWasmHash, previous);
+        }
+        env.storage()
+            .instance()
+            .set(&SystemKey::WasmHash, &new_wasm_hash);
+
+        (ContractUpgraded {
+            previous_wasm_hash,
+            new_wasm_hash: new_wasm_hash.clone(),
+        })
+        .publish(&env);
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+        Ok(())
+    }
+
+    /// Get the WASM hash installed by the last upgrade, or `None` if the contract
+    /// still runs the code it was deployed with.
+    pub fn get_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&SystemKey::WasmHash)
+    }
+
+    /// Get the WASM hash replaced by the last upgrade (the rollback target).
+    /// `None` until the contract has been upgraded twice, since the hash it was
+    /// deployed with is not known on-chain.
+    pub fn get_previous_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage().instance().get(&SystemKey::PreviousWasmHash)
+    }
+
+    /// Extend the TTL of a refund record and the side records stored under its id.
+    ///
+    /// Anyone may call this so keepers can keep old refunds readable. A refund
+    /// still held in instance storage by an earlier release is moved to persistent
+    /// storage first.
+    ///
+    /// # Arguments
+    /// * `refund_id` - The refund whose records should be kept alive.
+    ///
+    /// # Errors
+    /// Returns `RefundNotFound` if the refund does not exist.
+    pub fn extend_record_ttl(env: Env, refund_id: u64) -> Result<(), Error> {
+        if !touch_record(&env, &DataKey::Refund(refund_id)) {
+            return Err(Error::Core(CoreError::RefundNotFound));
+        }
+        touch_record(&env, &DataKey::RefundStatusIndex(refund_id));
+        touch_record(&env, &EvidenceKey::EvidenceCount(refund_id));
+        touch_record(&env, &SystemKey::RefundRejectedAt(refund_id));
+        touch_record(&env, &SystemKey::AppealByRefund(refund_id));
+
+        env.storage()
+            .instance()
+            .extend_ttl(RECORD_TTL_THRESHOLD, RECORD_TTL_EXTEND_TO);
+        Ok(())
+    }
+
     /// Propose a new admin, starting a two-step rotation (Issue #389).
     ///
     /// The current admin designates `new_admin` as pending. The rotation only
@@ -1594,10 +1788,7 @@ impl RefundContract {
     /// Returns `RefundNotFound` if no refund exists with the given ID.
     pub fn get_refund(env: &Env, refund_id: u64) -> Result<Refund, Error> {
         // Retrieve refund from storage by ID
-        env.storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
-            .ok_or(Error::Core(CoreError::RefundNotFound))
+        record_get(env, &DataKey::Refund(refund_id)).ok_or(Error::Core(CoreError::RefundNotFound))
     }
 
     /// Approve a pending refund request.
@@ -1659,10 +1850,7 @@ impl RefundContract {
     /// Returns `RefundNotRejected` if the refund is not in `PendingAppeal` status.
     /// Returns `InvalidStatus` if the appeal window has not yet expired.
     pub fn finalize_denial(env: Env, refund_id: u64) -> Result<(), Error> {
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let mut refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.status != RefundStatus::PendingAppeal {
@@ -1681,13 +1869,9 @@ impl RefundContract {
 
         refund.status = RefundStatus::Rejected;
         refund.rejected_at = Some(now);
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(&env, &DataKey::Refund(refund_id), &refund);
         Self::add_to_status_index(&env, RefundStatus::Rejected, refund_id);
-        env.storage()
-            .instance()
-            .set(&SystemKey::RefundRejectedAt(refund_id), &now);
+        record_set(&env, &SystemKey::RefundRejectedAt(refund_id), &now);
 
         let rejected_by = refund
             .rejected_by
@@ -1713,10 +1897,7 @@ impl RefundContract {
         refund_id: u64,
         rejection_reason: String,
     ) -> Result<(), Error> {
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let mut refund: Refund = record_get(env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.status != RefundStatus::Requested {
@@ -1736,9 +1917,7 @@ impl RefundContract {
         refund.rejected_by = Some(admin.clone());
         refund.appeal_deadline = Some(now.saturating_add(appeal_window));
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(env, &DataKey::Refund(refund_id), &refund);
         Self::add_to_status_index(env, RefundStatus::PendingAppeal, refund_id);
 
         (RefundRejected {
@@ -1779,10 +1958,7 @@ impl RefundContract {
         Self::require_not_paused(&env, "file_appeal")?;
         customer.require_auth();
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.customer != customer {
@@ -1793,11 +1969,7 @@ impl RefundContract {
         {
             return Err(Error::Core(CoreError::RefundNotRejected));
         }
-        if env
-            .storage()
-            .instance()
-            .has(&SystemKey::AppealByRefund(refund_id))
-        {
+        if record_has(&env, &SystemKey::AppealByRefund(refund_id)) {
             return Err(Error::Core(CoreError::AppealAlreadyFiled));
         }
 
@@ -1810,10 +1982,7 @@ impl RefundContract {
                 return Err(Error::Core(CoreError::AppealWindowExpired));
             }
         } else {
-            let rejected_at: u64 = env
-                .storage()
-                .instance()
-                .get(&SystemKey::RefundRejectedAt(refund_id))
+            let rejected_at: u64 = record_get(&env, &SystemKey::RefundRejectedAt(refund_id))
                 .ok_or(Error::Core(CoreError::RefundNotRejected))?;
             let appeal_window: u64 = env
                 .storage()
@@ -1840,26 +2009,21 @@ impl RefundContract {
             resolved: false,
             outcome: None,
         };
-        env.storage()
-            .instance()
-            .set(&SystemKey::Appeal(appeal_id), &appeal);
+        record_set(&env, &SystemKey::Appeal(appeal_id), &appeal);
         env.storage()
             .instance()
             .set(&SystemKey::AppealCounter, &appeal_id);
-        env.storage()
-            .instance()
-            .set(&SystemKey::AppealByRefund(refund_id), &appeal_id);
+        record_set(&env, &SystemKey::AppealByRefund(refund_id), &appeal_id);
 
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&SystemKey::AppealByCustomerCount(customer.clone()))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 =
+            record_get(&env, &SystemKey::AppealByCustomerCount(customer.clone())).unwrap_or(0);
+        record_set(
+            &env,
             &SystemKey::AppealByCustomer(customer.clone(), customer_count),
             &appeal_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &SystemKey::AppealByCustomerCount(customer.clone()),
             &(customer_count + 1),
         );
@@ -1905,20 +2069,14 @@ impl RefundContract {
             return Err(Error::Core(CoreError::Unauthorized));
         }
 
-        let mut appeal: RefundAppeal = env
-            .storage()
-            .instance()
-            .get(&SystemKey::Appeal(appeal_id))
+        let mut appeal: RefundAppeal = record_get(&env, &SystemKey::Appeal(appeal_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
         if appeal.resolved {
             return Err(Error::Core(CoreError::AlreadyProcessed));
         }
 
         if uphold {
-            let mut refund: Refund = env
-                .storage()
-                .instance()
-                .get(&DataKey::Refund(appeal.refund_id))
+            let mut refund: Refund = record_get(&env, &DataKey::Refund(appeal.refund_id))
                 .ok_or(Error::Core(CoreError::RefundNotFound))?;
             if refund.status != RefundStatus::Rejected
                 && refund.status != RefundStatus::PendingAppeal
@@ -1929,17 +2087,12 @@ impl RefundContract {
             let prior_status = refund.status.clone();
             Self::remove_from_status_index(&env, prior_status, refund.id)?;
             refund.status = RefundStatus::Approved;
-            env.storage()
-                .instance()
-                .set(&DataKey::Refund(refund.id), &refund);
+            record_set(&env, &DataKey::Refund(refund.id), &refund);
             Self::add_to_status_index(&env, RefundStatus::Approved, refund.id);
 
             Self::process_refund_internal(&env, admin.clone(), refund.id)?;
         } else {
-            let mut refund: Refund = env
-                .storage()
-                .instance()
-                .get(&DataKey::Refund(appeal.refund_id))
+            let mut refund: Refund = record_get(&env, &DataKey::Refund(appeal.refund_id))
                 .ok_or(Error::Core(CoreError::RefundNotFound))?;
             if refund.status != RefundStatus::Rejected
                 && refund.status != RefundStatus::PendingAppeal
@@ -1953,18 +2106,14 @@ impl RefundContract {
                 Self::remove_from_status_index(&env, RefundStatus::PendingAppeal, refund.id)?;
                 refund.status = RefundStatus::Rejected;
                 refund.rejected_at = Some(env.ledger().timestamp());
-                env.storage()
-                    .instance()
-                    .set(&DataKey::Refund(refund.id), &refund);
+                record_set(&env, &DataKey::Refund(refund.id), &refund);
                 Self::add_to_status_index(&env, RefundStatus::Rejected, refund.id);
             }
         }
 
         appeal.resolved = true;
         appeal.outcome = Some(uphold);
-        env.storage()
-            .instance()
-            .set(&SystemKey::Appeal(appeal_id), &appeal);
+        record_set(&env, &SystemKey::Appeal(appeal_id), &appeal);
 
         (AppealResolved {
             appeal_id,
@@ -1987,9 +2136,7 @@ impl RefundContract {
     /// # Errors
     /// Returns `RefundNotFound` if no appeal exists with the given ID.
     pub fn get_appeal(env: Env, appeal_id: u64) -> Result<RefundAppeal, Error> {
-        env.storage()
-            .instance()
-            .get(&SystemKey::Appeal(appeal_id))
+        record_get(&env, &SystemKey::Appeal(appeal_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))
     }
 
@@ -2002,23 +2149,16 @@ impl RefundContract {
     /// A vector of `RefundAppeal` records filed by the customer.
     pub fn get_appeals_by_customer(env: Env, customer: Address) -> Vec<RefundAppeal> {
         let mut appeals = Vec::new(&env);
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&SystemKey::AppealByCustomerCount(customer.clone()))
-            .unwrap_or(0);
+        let count: u64 =
+            record_get(&env, &SystemKey::AppealByCustomerCount(customer.clone())).unwrap_or(0);
 
         let mut index = 0u64;
         while index < count {
-            if let Some(appeal_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&SystemKey::AppealByCustomer(customer.clone(), index))
+            if let Some(appeal_id) =
+                record_get::<_, u64>(&env, &SystemKey::AppealByCustomer(customer.clone(), index))
             {
-                if let Some(appeal) = env
-                    .storage()
-                    .instance()
-                    .get::<_, RefundAppeal>(&SystemKey::Appeal(appeal_id))
+                if let Some(appeal) =
+                    record_get::<_, RefundAppeal>(&env, &SystemKey::Appeal(appeal_id))
                 {
                     appeals.push_back(appeal);
                 }
@@ -2032,7 +2172,15 @@ impl RefundContract {
     /// Process an approved refund for payout.
     ///
     /// Changes the refund status from `Approved` to `Processed`, deducts platform fees,
-    /// enforces merchant refund quota, and emits a `RefundProcessed` event.
+    /// transfers the net amount to the customer, enforces merchant refund quota, and
+    /// emits a `RefundProcessed` event.
+    ///
+    /// When a payment contract is linked, the refund is first settled against it: the
+    /// payment's `refunded_amount` and status are updated in the same transaction, and
+    /// any funds the payment contract still holds for the payment are released here.
+    /// The merchant's refund reserve covers whatever the payment contract did not release;
+    /// only if it falls short is the rest drawn from the merchant's rolling reserve in the
+    /// payment contract.
     ///
     /// # Arguments
     /// * `admin` - The admin address (must be authorized).
@@ -2042,6 +2190,11 @@ impl RefundContract {
     /// Returns `InvalidStatus` if the refund is not in `Approved` status.
     /// Returns `RefundExceedsPolicy` if the merchant quota is exceeded.
     /// Returns `TotalRefundsExceedPayment` if processing would exceed the original payment.
+    /// Returns `InsufficientRefundReserve` if the merchant's reserve in the refund
+    /// token and its rolling reserve cannot cover the part of the refund not released
+    /// by the payment contract.
+    /// Returns `PaymentSettlementFailed` if the linked payment contract rejects the
+    /// settlement, e.g. because the payment was already refunded through it.
     pub fn process_refund(env: Env, admin: Address, refund_id: u64) -> Result<(), Error> {
         Self::require_not_paused(&env, "process_refund")?;
         admin.require_auth();
@@ -2546,19 +2699,13 @@ impl RefundContract {
             return Err(Error::Core(CoreError::Unauthorized));
         }
 
-        let mut case: ArbitrationCase = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        let mut case: ArbitrationCase = record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
         if case.status != ArbitrationStatus::Open {
             return Err(Error::Core(CoreError::InvalidStatus));
         }
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(case.refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(case.refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if let Some(denied_by) = refund.rejected_by.clone() {
@@ -2579,9 +2726,7 @@ impl RefundContract {
 
         if !case.arbitrators.contains(&arbitrator) {
             case.arbitrators.push_back(arbitrator);
-            env.storage()
-                .instance()
-                .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
+            record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
         }
 
         Ok(())
@@ -2615,10 +2760,7 @@ impl RefundContract {
     ) -> Result<u64, Error> {
         caller.require_auth();
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
         // Matches `file_appeal`'s dual-status check: a rejection sits in
         // `PendingAppeal` during the appeal window before finalizing to
@@ -2686,9 +2828,7 @@ impl RefundContract {
                     deposited_at: env.ledger().timestamp(),
                     returned: false,
                 };
-                env.storage()
-                    .instance()
-                    .set(&ArbitrationKey::ArbitrationStake(case_id), &stake);
+                record_set(&env, &ArbitrationKey::ArbitrationStake(case_id), &stake);
 
                 StakeDeposited {
                     case_id,
@@ -2726,9 +2866,7 @@ impl RefundContract {
             default_favor_customer: true,
         };
 
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
+        record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
         env.storage()
             .instance()
             .set(&ArbitrationKey::ArbitrationCounter, &case_id);
@@ -2768,10 +2906,7 @@ impl RefundContract {
     ) -> Result<(), Error> {
         arbitrator.require_auth();
 
-        let mut case: ArbitrationCase = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        let mut case: ArbitrationCase = record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
         if case.status != ArbitrationStatus::Open {
             return Err(Error::Core(CoreError::InvalidStatus));
@@ -2782,19 +2917,14 @@ impl RefundContract {
         if !case.arbitrators.contains(&arbitrator) {
             return Err(Error::Core(CoreError::NotArbitrator));
         }
-        if env
-            .storage()
-            .instance()
-            .has(&ArbitrationKey::ArbitratorVote(case_id, arbitrator.clone()))
-        {
+        if record_has(
+            &env,
+            &ArbitrationKey::ArbitratorVote(case_id, arbitrator.clone()),
+        ) {
             return Err(Error::Core(CoreError::AlreadyProcessed));
         }
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(case.refund_id))
-            .unwrap();
+        let refund: Refund = record_get(&env, &DataKey::Refund(case.refund_id)).unwrap();
         if arbitrator == refund.merchant || arbitrator == refund.customer {
             return Err(Error::Core(CoreError::Unauthorized));
         }
@@ -2805,21 +2935,17 @@ impl RefundContract {
             reasoning_hash,
             voted_at: env.ledger().timestamp(),
         };
-        env.storage().instance().set(
+        record_set(
+            &env,
             &ArbitrationKey::ArbitratorVote(case_id, arbitrator.clone()),
             &vote,
         );
 
-        let mut voted: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitratorsVoted(case_id))
+        let mut voted: Vec<Address> = record_get(&env, &ArbitrationKey::ArbitratorsVoted(case_id))
             .unwrap_or_else(|| Vec::new(&env));
         if !voted.contains(&arbitrator) {
             voted.push_back(arbitrator.clone());
-            env.storage()
-                .instance()
-                .set(&ArbitrationKey::ArbitratorsVoted(case_id), &voted);
+            record_set(&env, &ArbitrationKey::ArbitratorsVoted(case_id), &voted);
         }
 
         if vote_for_refund {
@@ -2827,9 +2953,7 @@ impl RefundContract {
         } else {
             case.votes_against_refund += 1;
         }
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
+        record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
 
         ArbitrationVoteCast {
             case_id,
@@ -2854,10 +2978,7 @@ impl RefundContract {
     /// Returns `RefundNotFound` if the case does not exist.
     /// Returns `InvalidStatus` if the case is not open or quorum (3 votes) has not been reached.
     pub fn close_arbitration_case(env: Env, case_id: u64) -> Result<(), Error> {
-        let mut case: ArbitrationCase = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        let mut case: ArbitrationCase = record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
         if case.status != ArbitrationStatus::Open {
             return Err(Error::Core(CoreError::InvalidStatus));
@@ -2871,30 +2992,20 @@ impl RefundContract {
         let approved = case.votes_for_refund > case.votes_against_refund;
 
         case.status = ArbitrationStatus::Decided;
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
+        record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
 
         // Update refund status based on the arbitration outcome
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(case.refund_id))
-            .unwrap();
+        let mut refund: Refund = record_get(&env, &DataKey::Refund(case.refund_id)).unwrap();
         if approved {
             refund.status = RefundStatus::Approved;
-            env.storage()
-                .instance()
-                .set(&DataKey::Refund(case.refund_id), &refund);
+            record_set(&env, &DataKey::Refund(case.refund_id), &refund);
         } else if refund.status == RefundStatus::PendingAppeal {
             // The arbitration panel upheld the rejection, so it's final now
             // — no need to wait out the rest of the appeal window.
             Self::remove_from_status_index(&env, RefundStatus::PendingAppeal, refund.id)?;
             refund.status = RefundStatus::Rejected;
             refund.rejected_at = Some(env.ledger().timestamp());
-            env.storage()
-                .instance()
-                .set(&DataKey::Refund(case.refund_id), &refund);
+            record_set(&env, &DataKey::Refund(case.refund_id), &refund);
             Self::add_to_status_index(&env, RefundStatus::Rejected, refund.id);
         }
 
@@ -2902,10 +3013,7 @@ impl RefundContract {
         let num_voters = total_votes as i128;
 
         // Get all arbitrators who voted (needed for both fee distribution and reputation updates)
-        let all_voters: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitratorsVoted(case_id))
+        let all_voters: Vec<Address> = record_get(&env, &ArbitrationKey::ArbitratorsVoted(case_id))
             .unwrap_or_else(|| Vec::new(&env));
 
         if num_voters > 0 {
@@ -2942,11 +3050,11 @@ impl RefundContract {
             // Filter to only majority voters
             let mut majority_voters = Vec::new(&env);
             for voter in all_voters.iter() {
-                let vote: ArbitratorVote = env
-                    .storage()
-                    .instance()
-                    .get(&ArbitrationKey::ArbitratorVote(case_id, voter.clone()))
-                    .unwrap();
+                let vote: ArbitratorVote = record_get(
+                    &env,
+                    &ArbitrationKey::ArbitratorVote(case_id, voter.clone()),
+                )
+                .unwrap();
 
                 // Check if this voter was in the majority
                 let in_majority = if approved {
@@ -3009,11 +3117,11 @@ impl RefundContract {
         let current_time = env.ledger().timestamp();
 
         for voter in all_voters.iter() {
-            let vote: ArbitratorVote = env
-                .storage()
-                .instance()
-                .get(&ArbitrationKey::ArbitratorVote(case_id, voter.clone()))
-                .unwrap();
+            let vote: ArbitratorVote = record_get(
+                &env,
+                &ArbitrationKey::ArbitratorVote(case_id, voter.clone()),
+            )
+            .unwrap();
 
             // Check if this voter was in the majority
             let in_majority = if approved {
@@ -3094,10 +3202,8 @@ impl RefundContract {
     ///   (refund approved). The staker is the party that escalated the case
     ///   (the merchant), so they "won" iff the outcome is NOT approved.
     fn settle_arbitration_stake(env: &Env, case_id: u64, approved: bool) {
-        let stake_opt: Option<ArbitrationStake> = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationStake(case_id));
+        let stake_opt: Option<ArbitrationStake> =
+            record_get(env, &ArbitrationKey::ArbitrationStake(case_id));
 
         let mut stake = match stake_opt {
             Some(s) if !s.returned => s,
@@ -3167,9 +3273,7 @@ impl RefundContract {
 
         // Mark stake as returned
         stake.returned = true;
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationStake(case_id), &stake);
+        record_set(env, &ArbitrationKey::ArbitrationStake(case_id), &stake);
     }
 
     /// Set the default timeout duration for arbitration cases.
@@ -3224,10 +3328,7 @@ impl RefundContract {
     /// Returns `InvalidStatus` if the case is not open or quorum was already reached.
     /// Returns `CaseNotTimedOut` if the timeout has not yet elapsed.
     pub fn trigger_arbitration_timeout(env: Env, case_id: u64) -> Result<(), Error> {
-        let mut case: ArbitrationCase = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        let mut case: ArbitrationCase = record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if case.status != ArbitrationStatus::Open {
@@ -3246,20 +3347,12 @@ impl RefundContract {
 
         let approved = case.default_favor_customer;
         case.status = ArbitrationStatus::Decided;
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
+        record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
 
         if approved {
-            let mut refund: Refund = env
-                .storage()
-                .instance()
-                .get(&DataKey::Refund(case.refund_id))
-                .unwrap();
+            let mut refund: Refund = record_get(&env, &DataKey::Refund(case.refund_id)).unwrap();
             refund.status = RefundStatus::Approved;
-            env.storage()
-                .instance()
-                .set(&DataKey::Refund(case.refund_id), &refund);
+            record_set(&env, &DataKey::Refund(case.refund_id), &refund);
         }
 
         // Handle stake return or forfeiture, same as the quorum-vote path —
@@ -3676,9 +3769,7 @@ impl RefundContract {
     /// # Errors
     /// Returns `RefundNotFound` if the case does not exist.
     pub fn get_arbitration_case(env: Env, case_id: u64) -> Result<ArbitrationCase, Error> {
-        env.storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))
     }
 
@@ -3733,19 +3824,15 @@ impl RefundContract {
         amount: i128,
         token: &Address,
     ) -> Result<(i128, i128), Error> {
-        let config: RefundFeeConfig =
-            match env.storage().instance().get(&SystemKey::RefundFeeConfig) {
-                Some(c) => c,
-                None => return Ok((amount, 0)),
-            };
-        if !config.active {
-            return Ok((amount, 0));
-        }
-        let raw_fee = amount
-            .saturating_mul(config.fee_bps as i128)
-            .checked_div(10_000)
-            .unwrap_or(0);
-        let fee = raw_fee.max(config.min_fee).min(config.max_fee);
+        let config = match env
+            .storage()
+            .instance()
+            .get::<_, RefundFeeConfig>(&SystemKey::RefundFeeConfig)
+        {
+            Some(c) if c.active => c,
+            _ => return Ok((amount, 0)),
+        };
+        let fee = Self::refund_fee(&config, amount);
         let net = amount.saturating_sub(fee);
         if fee > 0 {
             token::Client::new(env, token).transfer(
@@ -3773,6 +3860,28 @@ impl RefundContract {
         Ok((net, fee))
     }
 
+    /// Platform fee charged on a refund of `amount` under `config`. `min_fee` may
+    /// exceed small refund amounts.
+    fn refund_fee(config: &RefundFeeConfig, amount: i128) -> i128 {
+        let raw_fee = amount
+            .saturating_mul(config.fee_bps as i128)
+            .checked_div(10_000)
+            .unwrap_or(0);
+        raw_fee.max(config.min_fee).min(config.max_fee)
+    }
+
+    /// Platform fee that processing a refund of `amount` will route to the treasury.
+    fn pending_refund_fee(env: &Env, amount: i128) -> i128 {
+        match env
+            .storage()
+            .instance()
+            .get::<_, RefundFeeConfig>(&SystemKey::RefundFeeConfig)
+        {
+            Some(config) if config.active => Self::refund_fee(&config, amount),
+            _ => 0,
+        }
+    }
+
     pub fn withdraw_treasury_fees(env: Env, admin: Address) -> Result<i128, Error> {
         admin.require_auth();
 
@@ -3834,9 +3943,7 @@ impl RefundContract {
 
     /// Get the stake information for a specific arbitration case
     pub fn get_arbitration_stake(env: Env, case_id: u64) -> Option<ArbitrationStake> {
-        env.storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationStake(case_id))
+        record_get(&env, &ArbitrationKey::ArbitrationStake(case_id))
     }
 
     /// Get a paginated list of refunds filtered by status.
@@ -3864,16 +3971,10 @@ impl RefundContract {
         let end = core::cmp::min(total, offset.saturating_add(limit));
         let mut index = offset;
         while index < end {
-            if let Some(refund_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&DataKey::RefundsByStatus(status.clone(), index))
+            if let Some(refund_id) =
+                record_get::<_, u64>(env, &DataKey::RefundsByStatus(status.clone(), index))
             {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(env, &DataKey::Refund(refund_id)) {
                     results.push_back(refund);
                 }
             }
@@ -3908,16 +4009,10 @@ impl RefundContract {
         let end = core::cmp::min(total, offset.saturating_add(limit));
         let mut index = offset;
         while index < end {
-            if let Some(refund_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&DataKey::MerchantRefunds(merchant.clone(), index))
+            if let Some(refund_id) =
+                record_get::<_, u64>(&env, &DataKey::MerchantRefunds(merchant.clone(), index))
             {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(&env, &DataKey::Refund(refund_id)) {
                     results.push_back(refund);
                 }
             }
@@ -3983,16 +4078,10 @@ impl RefundContract {
 
         let mut index = 0u64;
         while index < total_requests {
-            if let Some(refund_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&DataKey::MerchantRefunds(merchant.clone(), index))
+            if let Some(refund_id) =
+                record_get::<_, u64>(&env, &DataKey::MerchantRefunds(merchant.clone(), index))
             {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(&env, &DataKey::Refund(refund_id)) {
                     match refund.status {
                         RefundStatus::Approved => {
                             total_approved += 1;
@@ -4057,11 +4146,7 @@ impl RefundContract {
         let mut collected: u64 = 0;
         let mut id: u64 = 1;
         while id <= total_refunds && collected < limit {
-            if let Some(refund) = env
-                .storage()
-                .instance()
-                .get::<_, Refund>(&DataKey::Refund(id))
-            {
+            if let Some(refund) = record_get::<_, Refund>(env, &DataKey::Refund(id)) {
                 if refund.reason_code == code {
                     if matched >= offset {
                         results.push_back(refund);
@@ -4115,11 +4200,7 @@ impl RefundContract {
 
         let mut id: u64 = 1;
         while id <= total_refunds {
-            if let Some(refund) = env
-                .storage()
-                .instance()
-                .get::<_, Refund>(&DataKey::Refund(id))
-            {
+            if let Some(refund) = record_get::<_, Refund>(&env, &DataKey::Refund(id)) {
                 if refund.requested_at >= window_start && refund.requested_at <= window_end {
                     match refund.reason_code {
                         RefundReasonCode::ProductDefect => product_defect += 1,
@@ -4226,11 +4307,7 @@ impl RefundContract {
 
         let mut id: u64 = 1;
         while id <= total_refunds {
-            if let Some(refund) = env
-                .storage()
-                .instance()
-                .get::<_, Refund>(&DataKey::Refund(id))
-            {
+            if let Some(refund) = record_get::<_, Refund>(env, &DataKey::Refund(id)) {
                 if refund.payment_id == payment_id && refund.status == RefundStatus::Processed {
                     total += refund.amount;
                 }
@@ -4712,49 +4789,6 @@ impl RefundContract {
         Ok(())
     }
 
-    /// Set whether a merchant inherits its refund policy from its parent merchant.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant address to configure (must authenticate).
-    /// * `inherit` - `true` to enable inheritance, `false` to disable it.
-    pub fn set_inherit_from_parent(env: Env, merchant: Address, inherit: bool) {
-        merchant.require_auth();
-        Self::set_inherit_from_parent_inner(&env, &merchant, inherit);
-    }
-
-    /// Deactivate a merchant's refund policy so it is no longer enforced.
-    ///
-    /// # Arguments
-    /// * `merchant` - The merchant whose policy should be deactivated (must authenticate).
-    ///
-    /// # Errors
-    /// Returns `PolicyNotFound` if no policy exists for the merchant.
-    /// Returns `PolicyInactive` if the policy is already inactive.
-    pub fn deactivate_refund_policy(env: Env, merchant: Address) -> Result<(), Error> {
-        // Require merchant authentication
-        merchant.require_auth();
-
-        let mut policy: RefundPolicy = env
-            .storage()
-            .instance()
-            .get(&DataKey::RefundPolicy(merchant.clone()))
-            .ok_or(Error::Core(CoreError::PolicyNotFound))?;
-
-        if !policy.active {
-            return Err(Error::Core(CoreError::PolicyInactive));
-        }
-
-        policy.active = false;
-        env.storage()
-            .instance()
-            .set(&DataKey::RefundPolicy(merchant.clone()), &policy);
-
-        // Emit RefundPolicyDeactivated event
-        (RefundPolicyDeactivated { merchant }).publish(&env);
-
-        Ok(())
-    }
-
     /// Override a refund decision as an admin and create an immutable audit log entry.
     ///
     /// Records the override with a SHA-256 transaction hash for integrity verification.
@@ -4788,10 +4822,7 @@ impl RefundContract {
         }
 
         // Verify refund exists
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         // Generate immutable audit log entry
@@ -4822,9 +4853,11 @@ impl RefundContract {
         };
 
         // Store immutable audit log entry
-        env.storage()
-            .instance()
-            .set(&DataKey::AdminOverrideHistory(override_id), &audit_entry);
+        record_set(
+            &env,
+            &DataKey::AdminOverrideHistory(override_id),
+            &audit_entry,
+        );
 
         // Increment counter
         env.storage()
@@ -4856,9 +4889,7 @@ impl RefundContract {
 
     /// Retrieve admin override audit log entry by override_id
     pub fn get_admin_override_history(env: Env, override_id: u64) -> Option<AdminOverrideHistory> {
-        env.storage()
-            .instance()
-            .get(&DataKey::AdminOverrideHistory(override_id))
+        record_get(&env, &DataKey::AdminOverrideHistory(override_id))
     }
 
     /// Get total count of admin override audit log entries
@@ -5159,15 +5190,15 @@ impl RefundContract {
 
     fn add_to_status_index(env: &Env, status: RefundStatus, refund_id: u64) {
         let count = Self::get_refund_count_by_status(env, status.clone());
-        env.storage()
-            .instance()
-            .set(&DataKey::RefundsByStatus(status.clone(), count), &refund_id);
+        record_set(
+            env,
+            &DataKey::RefundsByStatus(status.clone(), count),
+            &refund_id,
+        );
         env.storage()
             .instance()
             .set(&DataKey::RefundStatusCount(status.clone()), &(count + 1));
-        env.storage()
-            .instance()
-            .set(&DataKey::RefundStatusIndex(refund_id), &count);
+        record_set(env, &DataKey::RefundStatusIndex(refund_id), &count);
     }
 
     fn remove_from_status_index(
@@ -5180,34 +5211,24 @@ impl RefundContract {
             return Err(Error::Core(CoreError::InvalidStatus));
         }
 
-        let index: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::RefundStatusIndex(refund_id))
+        let index: u64 = record_get(env, &DataKey::RefundStatusIndex(refund_id))
             .ok_or(Error::Core(CoreError::InvalidStatus))?;
         let last_index = count - 1;
 
         if index != last_index {
-            let last_refund_id: u64 = env
-                .storage()
-                .instance()
-                .get(&DataKey::RefundsByStatus(status.clone(), last_index))
-                .ok_or(Error::Core(CoreError::InvalidStatus))?;
-            env.storage().instance().set(
+            let last_refund_id: u64 =
+                record_get(env, &DataKey::RefundsByStatus(status.clone(), last_index))
+                    .ok_or(Error::Core(CoreError::InvalidStatus))?;
+            record_set(
+                env,
                 &DataKey::RefundsByStatus(status.clone(), index),
                 &last_refund_id,
             );
-            env.storage()
-                .instance()
-                .set(&DataKey::RefundStatusIndex(last_refund_id), &index);
+            record_set(env, &DataKey::RefundStatusIndex(last_refund_id), &index);
         }
 
-        env.storage()
-            .instance()
-            .remove(&DataKey::RefundsByStatus(status.clone(), last_index));
-        env.storage()
-            .instance()
-            .remove(&DataKey::RefundStatusIndex(refund_id));
+        record_remove(env, &DataKey::RefundsByStatus(status.clone(), last_index));
+        record_remove(env, &DataKey::RefundStatusIndex(refund_id));
         env.storage()
             .instance()
             .set(&DataKey::RefundStatusCount(status), &last_index);
@@ -5292,10 +5313,7 @@ impl RefundContract {
             let result = Self::approve_refund_internal(&env, admin.clone(), refund_id);
             match result {
                 Ok(()) => {
-                    let amount = env
-                        .storage()
-                        .instance()
-                        .get::<DataKey, Refund>(&DataKey::Refund(refund_id))
+                    let amount = record_get::<DataKey, Refund>(&env, &DataKey::Refund(refund_id))
                         .map(|r| r.amount)
                         .unwrap_or(0);
                     results.push_back(BatchRefundResult {
@@ -5337,13 +5355,20 @@ impl RefundContract {
     ) -> Vec<BatchRefundResult> {
         admin.require_auth();
         let limit = Self::get_batch_refund_limit(env.clone());
-        if refund_ids.len() > limit {
+        let batch_error = if let Err(e) = Self::require_not_paused(&env, "process_refund_batch") {
+            Some(e)
+        } else if refund_ids.len() > limit {
+            Some(Error::Core(CoreError::BatchRefundTooLarge))
+        } else {
+            None
+        };
+        if let Some(e) = batch_error {
             // Batch-level validation failure: reject the entire batch without processing.
             let mut results = Vec::new(&env);
             results.push_back(BatchRefundResult {
                 refund_id: 0,
                 success: false,
-                error_code: Error::Core(CoreError::BatchRefundTooLarge).to_u32(),
+                error_code: e.to_u32(),
                 amount_refunded: 0,
             });
             return results;
@@ -5353,10 +5378,7 @@ impl RefundContract {
         // return an error entry in the results vector without blocking other items.
         let mut results = Vec::new(&env);
         for refund_id in refund_ids.iter() {
-            let amount = env
-                .storage()
-                .instance()
-                .get::<DataKey, Refund>(&DataKey::Refund(refund_id))
+            let amount = record_get::<DataKey, Refund>(&env, &DataKey::Refund(refund_id))
                 .map(|r| r.amount)
                 .unwrap_or(0);
             let result = Self::process_refund_internal(&env, admin.clone(), refund_id);
@@ -5429,8 +5451,8 @@ impl RefundContract {
     /// * `customer` - The customer address to verify ownership for.
     ///
     /// # Returns
-    /// `true` if the payment exists, belongs to the customer, and is completed.
-    /// Returns `false` if no payment contract is set or verification fails.
+    /// `true` if the payment exists, belongs to the customer, and was completed and not
+    /// fully refunded. Returns `false` if no payment contract is set or verification fails.
     pub fn verify_payment_ownership(env: Env, payment_id: u64, customer: Address) -> bool {
         let payment_contract: Address = match env
             .storage()
@@ -5441,7 +5463,8 @@ impl RefundContract {
             None => return false, // no contract set → skip verification
         };
         // Cross-contract call to payment_contract.check_payment_customer(payment_id, customer).
-        // That function returns bool: true if payment exists, belongs to customer, and is Completed.
+        // That function returns bool: true if payment exists, belongs to customer, is Completed
+        // or PartialRefunded, and its funds were collected.
         let func = Symbol::new(&env, "check_payment_customer");
         let args = (payment_id, customer).into_val(&env);
         match env.try_invoke_contract::<bool, soroban_sdk::InvokeError>(
@@ -5593,40 +5616,36 @@ impl RefundContract {
             expires_at: ttl_expires_at,
         };
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(&env, &DataKey::Refund(refund_id), &refund);
         env.storage()
             .instance()
             .set(&DataKey::RefundCounter, &refund_id);
         Self::add_to_status_index(&env, initial_status.clone(), refund_id);
 
-        let merchant_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::MerchantRefundCount(merchant.clone()))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let merchant_count: u64 =
+            record_get(&env, &DataKey::MerchantRefundCount(merchant.clone())).unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::MerchantRefunds(merchant.clone(), merchant_count),
             &refund_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::MerchantRefundCount(merchant.clone()),
             &(merchant_count + 1),
         );
 
         Self::append_customer_refund_history(&env, &customer, refund_id);
 
-        let payment_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::PaymentRefundCount(payment_id))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let payment_count: u64 =
+            record_get(&env, &DataKey::PaymentRefundCount(payment_id)).unwrap_or(0);
+        record_set(
+            &env,
             &DataKey::PaymentRefunds(payment_id, payment_count),
             &refund_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &DataKey::PaymentRefundCount(payment_id),
             &(payment_count + 1),
         );
@@ -5662,10 +5681,7 @@ impl RefundContract {
         approved_by: Address,
         refund_id: u64,
     ) -> Result<(), Error> {
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let mut refund: Refund = record_get(env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.status != RefundStatus::Requested {
@@ -5683,9 +5699,7 @@ impl RefundContract {
         refund.status = RefundStatus::Approved;
         // Issue #147: Set approved_at timestamp
         refund.approved_at = Some(env.ledger().timestamp());
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(env, &DataKey::Refund(refund_id), &refund);
         Self::add_to_status_index(env, RefundStatus::Approved, refund_id);
 
         (RefundApproved {
@@ -5708,10 +5722,7 @@ impl RefundContract {
         processed_by: Address,
         refund_id: u64,
     ) -> Result<(), Error> {
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let mut refund: Refund = record_get(env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.status != RefundStatus::Approved {
@@ -5725,10 +5736,6 @@ impl RefundContract {
             refund.original_payment_amount,
         )?;
 
-        // Deduct platform fee from refund amount
-        let (net_refund_amount, _fee_amount) =
-            Self::deduct_refund_fee(env, refund_id, refund.amount, &refund.token)?;
-
         // Enforce merchant refund quota if configured
         if let Some(mut quota) = env
             .storage()
@@ -5755,13 +5762,46 @@ impl RefundContract {
             );
         }
 
+        // Funds released by the linked payment contract and the merchant's reserve
+        // together cover both the customer payout and the platform fee, which can
+        // exceed the refund amount when `min_fee` is larger than it
+        let outflow = refund
+            .amount
+            .max(Self::pending_refund_fee(env, refund.amount));
+        let released = Self::settle_with_payment_contract(env, &refund)?;
+        let mut from_reserve = outflow - released;
+        let reserve =
+            Self::get_refund_reserve(env.clone(), refund.merchant.clone(), refund.token.clone());
+        if reserve < from_reserve {
+            // Only a shortfall in the merchant's own reserve taps its rolling
+            // reserve in the payment contract
+            from_reserve -= Self::draw_payment_reserve(env, &refund, from_reserve - reserve)?;
+        }
+        if reserve < from_reserve {
+            return Err(Error::Ext(ExtError::InsufficientRefundReserve));
+        }
+        record_set(
+            env,
+            &ReserveKey::RefundReserve(refund.merchant.clone(), refund.token.clone()),
+            &(reserve - from_reserve),
+        );
+
+        // Deduct platform fee from refund amount
+        let (net_refund_amount, _fee_amount) =
+            Self::deduct_refund_fee(env, refund_id, refund.amount, &refund.token)?;
+        if net_refund_amount > 0 {
+            token::Client::new(env, &refund.token).transfer(
+                &env.current_contract_address(),
+                &refund.customer,
+                &net_refund_amount,
+            );
+        }
+
         Self::remove_from_status_index(env, RefundStatus::Approved, refund_id)?;
         refund.status = RefundStatus::Processed;
         // Issue #147: Set processed_at timestamp
         refund.processed_at = Some(env.ledger().timestamp());
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(env, &DataKey::Refund(refund_id), &refund);
         Self::add_to_status_index(env, RefundStatus::Processed, refund_id);
         // Issue #382: this refund's requested_at may fall within a previously
         // cached analytics window, so drop that cache entry.
@@ -5783,6 +5823,66 @@ impl RefundContract {
         Ok(())
     }
 
+    /// Books a refund against the linked payment contract, if one is configured, and
+    /// returns the amount it released from custody to this contract. The payment
+    /// contract rejects the refund unless its merchant and token match the payment.
+    fn settle_with_payment_contract(env: &Env, refund: &Refund) -> Result<i128, Error> {
+        let payment_contract: Address = match env
+            .storage()
+            .instance()
+            .get(&DataKey::PaymentContractAddress)
+        {
+            Some(addr) => addr,
+            None => return Ok(0),
+        };
+        let func = Symbol::new(env, "settle_refund");
+        let args = (
+            env.current_contract_address(),
+            refund.payment_id,
+            refund.merchant.clone(),
+            refund.token.clone(),
+            refund.amount,
+        )
+            .into_val(env);
+        match env.try_invoke_contract::<i128, soroban_sdk::InvokeError>(
+            &payment_contract,
+            &func,
+            args,
+        ) {
+            Ok(Ok(released)) if (0..=refund.amount).contains(&released) => Ok(released),
+            _ => Err(Error::Ext(ExtError::PaymentSettlementFailed)),
+        }
+    }
+
+    /// Draws up to `shortfall` for a settled refund from the merchant's rolling reserve
+    /// in the linked payment contract, if one is configured, and returns the amount
+    /// it transferred to this contract.
+    fn draw_payment_reserve(env: &Env, refund: &Refund, shortfall: i128) -> Result<i128, Error> {
+        let payment_contract: Address = match env
+            .storage()
+            .instance()
+            .get(&DataKey::PaymentContractAddress)
+        {
+            Some(addr) => addr,
+            None => return Ok(0),
+        };
+        let func = Symbol::new(env, "draw_reserve_for_refund");
+        let args = (
+            env.current_contract_address(),
+            refund.payment_id,
+            shortfall.min(refund.amount),
+        )
+            .into_val(env);
+        match env.try_invoke_contract::<i128, soroban_sdk::InvokeError>(
+            &payment_contract,
+            &func,
+            args,
+        ) {
+            Ok(Ok(drawn)) if (0..=shortfall).contains(&drawn) => Ok(drawn),
+            _ => Err(Error::Ext(ExtError::PaymentSettlementFailed)),
+        }
+    }
+
     fn get_external_payment(env: &Env, payment_id: u64) -> Result<ExternalPayment, Error> {
         let payment_contract: Address = env
             .storage()
@@ -5824,6 +5924,126 @@ impl RefundContract {
         }
     }
 
+    // ── Merchant refund reserve ────────────────────────────────────────────
+
+    /// Deposit tokens into a merchant's refund reserve.
+    ///
+    /// Processed refunds are paid to the customer out of this reserve, in the
+    /// refund's token.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant funding the reserve (must authenticate).
+    /// * `token` - The token being deposited.
+    /// * `amount` - The amount to deposit (must be positive).
+    ///
+    /// # Returns
+    /// The merchant's reserve balance in `token` after the deposit.
+    ///
+    /// # Errors
+    /// Returns `InvalidAmount` if `amount` is not positive.
+    pub fn deposit_refund_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "deposit_refund_reserve")?;
+        merchant.require_auth();
+        if amount <= 0 {
+            return Err(Error::Core(CoreError::InvalidAmount));
+        }
+
+        token::Client::new(&env, &token).transfer(
+            &merchant,
+            env.current_contract_address(),
+            &amount,
+        );
+
+        let balance = Self::get_refund_reserve(env.clone(), merchant.clone(), token.clone())
+            .checked_add(amount)
+            .ok_or(Error::Core(CoreError::InvalidAmount))?;
+        record_set(
+            &env,
+            &ReserveKey::RefundReserve(merchant.clone(), token.clone()),
+            &balance,
+        );
+
+        (RefundReserveDeposited {
+            merchant,
+            token,
+            amount,
+            balance,
+        })
+        .publish(&env);
+
+        Ok(balance)
+    }
+
+    /// Withdraw tokens from a merchant's refund reserve back to the merchant.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant owning the reserve (must authenticate).
+    /// * `token` - The token to withdraw.
+    /// * `amount` - The amount to withdraw (must be positive).
+    ///
+    /// # Returns
+    /// The merchant's reserve balance in `token` after the withdrawal.
+    ///
+    /// # Errors
+    /// Returns `InvalidAmount` if `amount` is not positive.
+    /// Returns `InsufficientRefundReserve` if the reserve holds less than `amount`.
+    pub fn withdraw_refund_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env, "withdraw_refund_reserve")?;
+        merchant.require_auth();
+        if amount <= 0 {
+            return Err(Error::Core(CoreError::InvalidAmount));
+        }
+
+        let reserve = Self::get_refund_reserve(env.clone(), merchant.clone(), token.clone());
+        if reserve < amount {
+            return Err(Error::Ext(ExtError::InsufficientRefundReserve));
+        }
+        let balance = reserve - amount;
+        record_set(
+            &env,
+            &ReserveKey::RefundReserve(merchant.clone(), token.clone()),
+            &balance,
+        );
+
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &merchant,
+            &amount,
+        );
+
+        (RefundReserveWithdrawn {
+            merchant,
+            token,
+            amount,
+            balance,
+        })
+        .publish(&env);
+
+        Ok(balance)
+    }
+
+    /// Get a merchant's refund reserve balance for a token.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant to query.
+    /// * `token` - The reserve token.
+    ///
+    /// # Returns
+    /// The reserve balance, or `0` if the merchant has never funded it.
+    pub fn get_refund_reserve(env: Env, merchant: Address, token: Address) -> i128 {
+        record_get(&env, &ReserveKey::RefundReserve(merchant, token)).unwrap_or(0)
+    }
+
     // ── ANALYTICS FUNCTIONS ────────────────────────────────────────────────
 
     /// Get overall refund analytics for the contract.
@@ -5854,10 +6074,19 @@ impl RefundContract {
     /// # Arguments
     /// * `admin` - The contract admin pausing the contract.
     /// * `reason` - A human-readable reason for the pause.
+    /// * `duration_seconds` - How long the pause lasts before it lifts on its
+    ///   own; `None` pauses until `unpause_contract` is called. Re-pausing a
+    ///   paused contract keeps the later expiry, so an indefinite pause stays
+    ///   indefinite.
     ///
     /// # Errors
     /// Returns `Unauthorized` if the caller is not the contract admin.
-    pub fn pause_contract(env: Env, admin: Address, reason: String) -> Result<(), Error> {
+    pub fn pause_contract(
+        env: Env,
+        admin: Address,
+        reason: String,
+        duration_seconds: Option<u64>,
+    ) -> Result<(), Error> {
         admin.require_auth();
         let stored_admin = env
             .storage()
@@ -5868,6 +6097,12 @@ impl RefundContract {
             return Err(Error::Core(CoreError::Unauthorized));
         }
         let now = env.ledger().timestamp();
+        let already_paused = env
+            .storage()
+            .instance()
+            .get::<SystemKey, PauseState>(&SystemKey::PauseStateKey)
+            .is_some_and(|state| state.globally_paused)
+            && !Self::global_pause_expired(&env);
         let pause_state = if let Some(mut state) = env
             .storage()
             .instance()
@@ -5890,6 +6125,12 @@ impl RefundContract {
         env.storage()
             .instance()
             .set(&SystemKey::PauseStateKey, &pause_state);
+        let paused_until = Self::merged_paused_until(
+            &env,
+            already_paused,
+            duration_seconds.map(|duration| now.saturating_add(duration)),
+        );
+        Self::set_paused_until(&env, paused_until);
         let history_count: u64 = env
             .storage()
             .instance()
@@ -5913,6 +6154,7 @@ impl RefundContract {
             paused_by: admin,
             reason,
             paused_at: now,
+            paused_until,
         })
         .publish(&env);
         Ok(())
@@ -5945,6 +6187,7 @@ impl RefundContract {
                 .instance()
                 .set(&SystemKey::PauseStateKey, &state);
         }
+        Self::set_paused_until(&env, None);
         let now = env.ledger().timestamp();
         let history_count: u64 = env
             .storage()
@@ -6117,7 +6360,8 @@ impl RefundContract {
     /// A `PauseState` struct indicating whether the contract is globally paused,
     /// which functions are individually paused, and who initiated the pause.
     pub fn get_pause_state(env: Env) -> PauseState {
-        env.storage()
+        let mut state = env
+            .storage()
             .instance()
             .get(&SystemKey::PauseStateKey)
             .unwrap_or(PauseState {
@@ -6126,7 +6370,22 @@ impl RefundContract {
                 paused_at: 0,
                 paused_by: env.current_contract_address(),
                 pause_reason: String::from_str(&env, ""),
-            })
+            });
+        if state.globally_paused && Self::global_pause_expired(&env) {
+            state.globally_paused = false;
+        }
+        state
+    }
+
+    /// Get the timestamp at which the current global pause lifts on its own.
+    ///
+    /// # Returns
+    /// `None` if the contract is not paused or was paused without a duration.
+    pub fn get_paused_until(env: Env) -> Option<u64> {
+        if Self::global_pause_expired(&env) {
+            return None;
+        }
+        env.storage().instance().get(&SystemKey::PausedUntil)
     }
 
     /// Check whether a specific function is currently paused.
@@ -6142,7 +6401,7 @@ impl RefundContract {
             .instance()
             .get::<SystemKey, PauseState>(&SystemKey::PauseStateKey)
         {
-            if state.globally_paused {
+            if state.globally_paused && !Self::global_pause_expired(&env) {
                 return true;
             }
             for fn_name in state.paused_functions.iter() {
@@ -6165,6 +6424,79 @@ impl RefundContract {
         }
     }
 
+    fn set_paused_until(env: &Env, paused_until: Option<u64>) {
+        match paused_until {
+            Some(timestamp) => env
+                .storage()
+                .instance()
+                .set(&SystemKey::PausedUntil, &timestamp),
+            None => env.storage().instance().remove(&SystemKey::PausedUntil),
+        }
+    }
+
+    /// Returns the expiry of a global pause requested while the contract may
+    /// already be paused. A live pause keeps the later of the two expiries, and
+    /// an indefinite pause (`None`) outlasts any timed one.
+    fn merged_paused_until(env: &Env, already_paused: bool, requested: Option<u64>) -> Option<u64> {
+        if !already_paused {
+            return requested;
+        }
+        match (
+            env.storage()
+                .instance()
+                .get::<SystemKey, u64>(&SystemKey::PausedUntil),
+            requested,
+        ) {
+            (Some(current), Some(requested)) => Some(current.max(requested)),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` once a timed global pause has reached its `paused_until` time.
+    fn global_pause_expired(env: &Env) -> bool {
+        env.storage()
+            .instance()
+            .get::<SystemKey, u64>(&SystemKey::PausedUntil)
+            .is_some_and(|paused_until| env.ledger().timestamp() >= paused_until)
+    }
+
+    /// Persists the unpause of a global pause whose duration has run out. Called
+    /// lazily from `require_not_paused`; the contract itself is recorded as the
+    /// unpauser.
+    fn lift_expired_pause(env: &Env, mut state: PauseState) {
+        let now = env.ledger().timestamp();
+        let contract = env.current_contract_address();
+        state.globally_paused = false;
+        env.storage()
+            .instance()
+            .set(&SystemKey::PauseStateKey, &state);
+        Self::set_paused_until(env, None);
+        let history_count: u64 = env
+            .storage()
+            .instance()
+            .get(&SystemKey::PauseHistoryCount)
+            .unwrap_or(0);
+        let entry = PauseHistory {
+            index: history_count,
+            function_name: String::from_str(env, "global"),
+            paused: false,
+            changed_by: contract.clone(),
+            changed_at: now,
+            reason: String::from_str(env, "pause duration elapsed"),
+        };
+        env.storage()
+            .instance()
+            .set(&SystemKey::PauseHistoryEntry(history_count), &entry);
+        env.storage()
+            .instance()
+            .set(&SystemKey::PauseHistoryCount, &(history_count + 1));
+        (ContractUnpausedEvent {
+            unpaused_by: contract,
+            unpaused_at: now,
+        })
+        .publish(env);
+    }
+
     fn require_not_paused(env: &Env, function_name: &str) -> Result<(), Error> {
         if let Some(state) = env
             .storage()
@@ -6172,7 +6504,10 @@ impl RefundContract {
             .get::<SystemKey, PauseState>(&SystemKey::PauseStateKey)
         {
             if state.globally_paused {
-                return Err(Error::Core(CoreError::ContractPaused));
+                if !Self::global_pause_expired(env) {
+                    return Err(Error::Core(CoreError::ContractPaused));
+                }
+                Self::lift_expired_pause(env, state.clone());
             }
             let fn_str = String::from_str(env, function_name);
             for fn_name in state.paused_functions.iter() {
@@ -6713,11 +7048,8 @@ impl RefundContract {
 
     fn get_customer_refund_count(env: &Env, address: &Address) -> u64 {
         // Count refunds for this address
-        let refund_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::CustomerRefundCount(address.clone()))
-            .unwrap_or(0);
+        let refund_count: u64 =
+            record_get(env, &DataKey::CustomerRefundCount(address.clone())).unwrap_or(0);
         refund_count
     }
 
@@ -6727,42 +7059,35 @@ impl RefundContract {
     /// history can grow without bound while the instance storage footprint
     /// (loaded on every invocation) stays fixed.
     fn append_customer_refund_history(env: &Env, customer: &Address, refund_id: u64) {
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::CustomerRefundCount(customer.clone()))
-            .unwrap_or(0);
+        let customer_count: u64 =
+            record_get(env, &DataKey::CustomerRefundCount(customer.clone())).unwrap_or(0);
 
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::CustomerRefunds(customer.clone(), customer_count),
             &refund_id,
         );
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::CustomerRefundCount(customer.clone()),
             &(customer_count + 1),
         );
 
-        let start: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::CustomerRefundHistoryStart(customer.clone()))
-            .unwrap_or(0);
+        let start: u64 =
+            record_get(env, &DataKey::CustomerRefundHistoryStart(customer.clone())).unwrap_or(0);
         let hot_len = customer_count + 1 - start;
         if hot_len > CUSTOMER_HISTORY_HOT_CAP {
-            if let Some(archived_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&DataKey::CustomerRefunds(customer.clone(), start))
+            if let Some(archived_id) =
+                record_get::<_, u64>(env, &DataKey::CustomerRefunds(customer.clone(), start))
             {
                 env.storage().persistent().set(
                     &DataKey::CustomerRefundsArchive(customer.clone(), start),
                     &archived_id,
                 );
             }
-            env.storage()
-                .instance()
-                .remove(&DataKey::CustomerRefunds(customer.clone(), start));
-            env.storage().instance().set(
+            record_remove(env, &DataKey::CustomerRefunds(customer.clone(), start));
+            record_set(
+                env,
                 &DataKey::CustomerRefundHistoryStart(customer.clone()),
                 &(start + 1),
             );
@@ -6772,19 +7097,14 @@ impl RefundContract {
     /// Reads a customer's refund id at a given history index, transparently
     /// falling back to the archive when the entry has aged out of hot storage.
     fn get_customer_refund_id_at(env: &Env, customer: &Address, index: u64) -> Option<u64> {
-        let start: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::CustomerRefundHistoryStart(customer.clone()))
-            .unwrap_or(0);
+        let start: u64 =
+            record_get(env, &DataKey::CustomerRefundHistoryStart(customer.clone())).unwrap_or(0);
         if index < start {
             env.storage()
                 .persistent()
                 .get(&DataKey::CustomerRefundsArchive(customer.clone(), index))
         } else {
-            env.storage()
-                .instance()
-                .get(&DataKey::CustomerRefunds(customer.clone(), index))
+            record_get(env, &DataKey::CustomerRefunds(customer.clone(), index))
         }
     }
 
@@ -6845,11 +7165,7 @@ impl RefundContract {
             }
 
             if let Some(refund_id) = Self::get_customer_refund_id_at(&env, &customer, index) {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(&env, &DataKey::Refund(refund_id)) {
                     results.push_back(refund);
                     collected += 1;
                 }
@@ -6875,11 +7191,7 @@ impl RefundContract {
         let mut index = 0u64;
         while index < total_requested {
             if let Some(refund_id) = Self::get_customer_refund_id_at(&env, &customer, index) {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(&env, &DataKey::Refund(refund_id)) {
                     match refund.status {
                         RefundStatus::Approved | RefundStatus::Processed => {
                             total_approved += 1;
@@ -6918,10 +7230,7 @@ impl RefundContract {
     }
 
     fn get_merchant_refund_count(env: &Env, merchant: &Address) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::MerchantRefundCount(merchant.clone()))
-            .unwrap_or(0)
+        record_get(env, &DataKey::MerchantRefundCount(merchant.clone())).unwrap_or(0)
     }
 
     // Issue #144: Notification hook functions
@@ -7376,16 +7685,10 @@ impl RefundContract {
         let mut index = 0u64;
 
         while index < total && collected < limit {
-            if let Some(refund_id) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&DataKey::MerchantRefunds(merchant.clone(), index))
+            if let Some(refund_id) =
+                record_get::<_, u64>(env, &DataKey::MerchantRefunds(merchant.clone(), index))
             {
-                if let Some(refund) = env
-                    .storage()
-                    .instance()
-                    .get::<_, Refund>(&DataKey::Refund(refund_id))
-                {
+                if let Some(refund) = record_get::<_, Refund>(env, &DataKey::Refund(refund_id)) {
                     if refund.status == status {
                         if matched >= offset {
                             results.push_back(refund);
@@ -7820,10 +8123,7 @@ impl RefundContract {
     /// Returns `InvalidStatus` if the refund is not in `Requested` status.
     /// Returns `RefundWindowExpired` if the refund's TTL has not yet elapsed.
     pub fn expire_stale_refund(env: Env, refund_id: u64) -> Result<(), Error> {
-        let mut refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let mut refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if refund.status != RefundStatus::Requested {
@@ -7839,9 +8139,7 @@ impl RefundContract {
         Self::remove_from_status_index(&env, RefundStatus::Requested, refund_id)?;
         refund.status = RefundStatus::Rejected;
         refund.rejected_at = Some(env.ledger().timestamp());
-        env.storage()
-            .instance()
-            .set(&DataKey::Refund(refund_id), &refund);
+        record_set(&env, &DataKey::Refund(refund_id), &refund);
         Self::add_to_status_index(&env, RefundStatus::Rejected, refund_id);
 
         (RefundRejected {
@@ -7875,11 +8173,7 @@ impl RefundContract {
         let mut id = 1u64;
 
         while id <= total && collected < limit {
-            if let Some(refund) = env
-                .storage()
-                .instance()
-                .get::<DataKey, Refund>(&DataKey::Refund(id))
-            {
+            if let Some(refund) = record_get::<DataKey, Refund>(&env, &DataKey::Refund(id)) {
                 if refund.status == RefundStatus::Requested {
                     if let Some(expires_at) = refund.expires_at {
                         if now >= expires_at {
@@ -7918,29 +8212,18 @@ impl RefundContract {
     ) -> Result<(), Error> {
         submitter.require_auth();
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if submitter != refund.customer && submitter != refund.merchant {
             return Err(Error::Core(CoreError::Unauthorized));
         }
 
-        if env
-            .storage()
-            .instance()
-            .has(&EvidenceKey::Evidence(refund_id, submitter.clone()))
-        {
+        if record_has(&env, &EvidenceKey::Evidence(refund_id, submitter.clone())) {
             return Err(Error::Ext(ExtError::EvidenceAlreadySubmitted));
         }
 
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&EvidenceKey::EvidenceCount(refund_id))
-            .unwrap_or(0);
+        let count: u64 = record_get(&env, &EvidenceKey::EvidenceCount(refund_id)).unwrap_or(0);
 
         let evidence = RefundEvidence {
             refund_id,
@@ -7949,16 +8232,17 @@ impl RefundContract {
             submitted_at: env.ledger().timestamp(),
         };
 
-        env.storage().instance().set(
+        record_set(
+            &env,
             &EvidenceKey::Evidence(refund_id, submitter.clone()),
             &evidence,
         );
-        env.storage()
-            .instance()
-            .set(&EvidenceKey::EvidenceIndex(refund_id, count), &submitter);
-        env.storage()
-            .instance()
-            .set(&EvidenceKey::EvidenceCount(refund_id), &(count + 1));
+        record_set(
+            &env,
+            &EvidenceKey::EvidenceIndex(refund_id, count),
+            &submitter,
+        );
+        record_set(&env, &EvidenceKey::EvidenceCount(refund_id), &(count + 1));
 
         Ok(())
     }
@@ -7976,9 +8260,7 @@ impl RefundContract {
         refund_id: u64,
         submitter: Address,
     ) -> Option<RefundEvidence> {
-        env.storage()
-            .instance()
-            .get(&EvidenceKey::Evidence(refund_id, submitter))
+        record_get(&env, &EvidenceKey::Evidence(refund_id, submitter))
     }
 
     /// Get all evidence entries submitted for a refund dispute.
@@ -7989,24 +8271,17 @@ impl RefundContract {
     /// # Returns
     /// A vector of all `RefundEvidence` entries for the refund.
     pub fn get_all_refund_evidence(env: Env, refund_id: u64) -> Vec<RefundEvidence> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&EvidenceKey::EvidenceCount(refund_id))
-            .unwrap_or(0);
+        let count: u64 = record_get(&env, &EvidenceKey::EvidenceCount(refund_id)).unwrap_or(0);
         let mut results = Vec::new(&env);
         let mut i = 0u64;
         while i < count {
-            if let Some(submitter) = env
-                .storage()
-                .instance()
-                .get::<_, Address>(&EvidenceKey::EvidenceIndex(refund_id, i))
+            if let Some(submitter) =
+                record_get::<_, Address>(&env, &EvidenceKey::EvidenceIndex(refund_id, i))
             {
-                if let Some(ev) = env
-                    .storage()
-                    .instance()
-                    .get::<_, RefundEvidence>(&EvidenceKey::Evidence(refund_id, submitter))
-                {
+                if let Some(ev) = record_get::<_, RefundEvidence>(
+                    &env,
+                    &EvidenceKey::Evidence(refund_id, submitter),
+                ) {
                     results.push_back(ev);
                 }
             }
@@ -8170,10 +8445,7 @@ impl RefundContract {
             return Err(Error::Core(CoreError::Unauthorized));
         }
 
-        let refund: Refund = env
-            .storage()
-            .instance()
-            .get(&DataKey::Refund(refund_id))
+        let refund: Refund = record_get(&env, &DataKey::Refund(refund_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         let counter: u64 = env
@@ -8196,23 +8468,23 @@ impl RefundContract {
             redeemed: false,
         };
 
-        env.storage()
-            .instance()
-            .set(&VoucherKey::Voucher(voucher_id), &voucher);
+        record_set(&env, &VoucherKey::Voucher(voucher_id), &voucher);
         env.storage()
             .instance()
             .set(&VoucherKey::VoucherCounter, &voucher_id);
 
-        let customer_count: u64 = env
-            .storage()
-            .instance()
-            .get(&VoucherKey::CustomerVoucherCount(refund.customer.clone()))
-            .unwrap_or(0);
-        env.storage().instance().set(
+        let customer_count: u64 = record_get(
+            &env,
+            &VoucherKey::CustomerVoucherCount(refund.customer.clone()),
+        )
+        .unwrap_or(0);
+        record_set(
+            &env,
             &VoucherKey::CustomerVoucher(refund.customer.clone(), customer_count),
             &voucher_id,
         );
-        env.storage().instance().set(
+        record_set(
+            &env,
             &VoucherKey::CustomerVoucherCount(refund.customer.clone()),
             &(customer_count + 1),
         );
@@ -8240,10 +8512,7 @@ impl RefundContract {
     ) -> Result<(), Error> {
         customer.require_auth();
 
-        let mut voucher: RefundVoucher = env
-            .storage()
-            .instance()
-            .get(&VoucherKey::Voucher(voucher_id))
+        let mut voucher: RefundVoucher = record_get(&env, &VoucherKey::Voucher(voucher_id))
             .ok_or(Error::Ext(ExtError::VoucherNotFound))?;
 
         if voucher.customer != customer {
@@ -8263,9 +8532,7 @@ impl RefundContract {
         );
 
         voucher.redeemed = true;
-        env.storage()
-            .instance()
-            .set(&VoucherKey::Voucher(voucher_id), &voucher);
+        record_set(&env, &VoucherKey::Voucher(voucher_id), &voucher);
 
         Ok(())
     }
@@ -8278,9 +8545,7 @@ impl RefundContract {
     /// # Returns
     /// The `RefundVoucher` if found, `None` otherwise.
     pub fn get_voucher(env: Env, voucher_id: u64) -> Option<RefundVoucher> {
-        env.storage()
-            .instance()
-            .get(&VoucherKey::Voucher(voucher_id))
+        record_get(&env, &VoucherKey::Voucher(voucher_id))
     }
 
     /// Get all refund vouchers issued to a customer.
@@ -8291,24 +8556,15 @@ impl RefundContract {
     /// # Returns
     /// A vector of `RefundVoucher` entries for the customer.
     pub fn get_customer_vouchers(env: Env, customer: Address) -> Vec<RefundVoucher> {
-        let count: u64 = env
-            .storage()
-            .instance()
-            .get(&VoucherKey::CustomerVoucherCount(customer.clone()))
-            .unwrap_or(0);
+        let count: u64 =
+            record_get(&env, &VoucherKey::CustomerVoucherCount(customer.clone())).unwrap_or(0);
         let mut results = Vec::new(&env);
         let mut i = 0u64;
         while i < count {
-            if let Some(vid) = env
-                .storage()
-                .instance()
-                .get::<_, u64>(&VoucherKey::CustomerVoucher(customer.clone(), i))
+            if let Some(vid) =
+                record_get::<_, u64>(&env, &VoucherKey::CustomerVoucher(customer.clone(), i))
             {
-                if let Some(v) = env
-                    .storage()
-                    .instance()
-                    .get::<_, RefundVoucher>(&VoucherKey::Voucher(vid))
-                {
+                if let Some(v) = record_get::<_, RefundVoucher>(&env, &VoucherKey::Voucher(vid)) {
                     results.push_back(v);
                 }
             }
@@ -8401,18 +8657,11 @@ impl RefundContract {
     /// Returns `CaseNotTimedOut` if the escalation timeout has not elapsed.
     /// Returns `ArbitratorNotFound` if no senior arbitrators are registered.
     pub fn escalate_arbitration_case(env: Env, case_id: u64) -> Result<(), Error> {
-        if env
-            .storage()
-            .instance()
-            .has(&ArbitrationKey::CaseEscalated(case_id))
-        {
+        if record_has(&env, &ArbitrationKey::CaseEscalated(case_id)) {
             return Err(Error::Ext(ExtError::CaseAlreadyEscalated));
         }
 
-        let mut case: ArbitrationCase = env
-            .storage()
-            .instance()
-            .get(&ArbitrationKey::ArbitrationCase(case_id))
+        let mut case: ArbitrationCase = record_get(&env, &ArbitrationKey::ArbitrationCase(case_id))
             .ok_or(Error::Core(CoreError::RefundNotFound))?;
 
         if case.status != ArbitrationStatus::Open {
@@ -8446,12 +8695,8 @@ impl RefundContract {
         case.arbitrators = senior_list;
         case.votes_for_refund = 0;
         case.votes_against_refund = 0;
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::ArbitrationCase(case_id), &case);
-        env.storage()
-            .instance()
-            .set(&ArbitrationKey::CaseEscalated(case_id), &true);
+        record_set(&env, &ArbitrationKey::ArbitrationCase(case_id), &case);
+        record_set(&env, &ArbitrationKey::CaseEscalated(case_id), &true);
 
         Ok(())
     }
@@ -8464,11 +8709,7 @@ impl RefundContract {
     /// # Returns
     /// `ArbitratorTier::Senior` if the case has been escalated, `ArbitratorTier::Junior` otherwise.
     pub fn get_arbitration_tier(env: Env, case_id: u64) -> ArbitratorTier {
-        if env
-            .storage()
-            .instance()
-            .has(&ArbitrationKey::CaseEscalated(case_id))
-        {
+        if record_has(&env, &ArbitrationKey::CaseEscalated(case_id)) {
             ArbitratorTier::Senior
         } else {
             ArbitratorTier::Junior
@@ -8533,10 +8774,7 @@ impl RefundContract {
     /// A tuple of `(refund_count, total_refunded_amount)` representing how many
     /// refunds have been made and the cumulative amount refunded.
     pub fn get_payment_refund_usage(env: Env, payment_id: u64) -> (u32, i128) {
-        let usage: Option<(u32, i128)> = env
-            .storage()
-            .instance()
-            .get(&DataKey::PaymentRefundUsage(payment_id));
+        let usage: Option<(u32, i128)> = record_get(&env, &DataKey::PaymentRefundUsage(payment_id));
         usage.unwrap_or((0, 0))
     }
 
@@ -8555,11 +8793,8 @@ impl RefundContract {
             None => return Ok(()),
         };
 
-        let (current_count, current_amount): (u32, i128) = env
-            .storage()
-            .instance()
-            .get(&DataKey::PaymentRefundUsage(payment_id))
-            .unwrap_or((0u32, 0i128));
+        let (current_count, current_amount): (u32, i128) =
+            record_get(env, &DataKey::PaymentRefundUsage(payment_id)).unwrap_or((0u32, 0i128));
 
         // Check count cap (only for Requested and Approved statuses)
         if current_count >= cap.max_refund_count {
@@ -8576,16 +8811,14 @@ impl RefundContract {
     }
 
     fn update_payment_refund_usage(env: &Env, payment_id: u64, refund_amount: i128) {
-        let (current_count, current_amount): (u32, i128) = env
-            .storage()
-            .instance()
-            .get(&DataKey::PaymentRefundUsage(payment_id))
-            .unwrap_or((0u32, 0i128));
+        let (current_count, current_amount): (u32, i128) =
+            record_get(env, &DataKey::PaymentRefundUsage(payment_id)).unwrap_or((0u32, 0i128));
 
         let new_count = current_count.saturating_add(1u32);
         let new_amount = current_amount.saturating_add(refund_amount);
 
-        env.storage().instance().set(
+        record_set(
+            env,
             &DataKey::PaymentRefundUsage(payment_id),
             &(new_count, new_amount),
         );
@@ -8687,4 +8920,91 @@ impl RefundContract {
     /// Enable or disable strict tier policy enforcement for a merchant.
     ///
     /// When strict mode is enabled, customers without an assigned tier are
-    /// denied refunds inst
\ No newline at end of file
+    /// denied refunds instead of falling back to the default policy.
+    ///
+    /// # Arguments
+    /// * `merchant` - The merchant configuring strict mode (must authenticate).
+    /// * `strict` - `true` to enable strict mode, `false` to disable it.
+    pub fn set_strict_tier_policy(env: Env, merchant: Address, strict: bool) -> Result<(), Error> {
+        merchant.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::StrictTierPolicy(merchant), &strict);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod schema_version_test;
+
+#[cfg(test)]
+mod test;
+
+#[cfg(test)]
+mod test_admin_rotation;
+
+#[cfg(test)]
+mod test_arbitration_fees;
+
+#[cfg(test)]
+mod test_arbitration_stake;
+
+#[cfg(test)]
+mod test_arbitration_timeout;
+
+#[cfg(test)]
+mod test_arbitrator_reputation;
+
+#[cfg(test)]
+mod test_auto_refund;
+
+#[cfg(test)]
+mod test_batch;
+
+#[cfg(test)]
+mod test_circuit_breaker;
+
+#[cfg(test)]
+mod test_cross_contract;
+
+#[cfg(test)]
+mod test_customer_history;
+
+#[cfg(test)]
+mod test_customer_tier_policy;
+
+#[cfg(test)]
+mod test_inheritance;
+
+#[cfg(test)]
+mod test_merchant_eligibility;
+
+#[cfg(test)]
+mod test_merchant_refunds;
+
+#[cfg(test)]
+mod test_notification_hooks;
+
+#[cfg(test)]
+mod test_payment_refund_cap;
+
+#[cfg(test)]
+mod test_policy;
+
+#[cfg(test)]
+mod test_process;
+
+#[cfg(test)]
+mod test_rate_limit;
+
+#[cfg(test)]
+mod test_voucher_expiry;
+
+#[cfg(test)]
+mod test_refund_reserve;
+
+#[cfg(test)]
+mod test_record_storage;
+
+#[cfg(test)]
+mod test_upgrade;
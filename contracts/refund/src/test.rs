@@ -5,6 +5,23 @@ use soroban_sdk::{
     testutils::Address as _, testutils::Events, testutils::Ledger, Address, Env, String,
 };
 
+/// Register a token and fund `merchant`'s refund reserve with `amount` of it,
+/// so approved refunds in that token can be processed.
+fn fund_refund_reserve(
+    env: &Env,
+    client: &RefundContractClient,
+    merchant: &Address,
+    amount: i128,
+) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(merchant, &amount);
+    client.deposit_refund_reserve(merchant, &token, &amount);
+    token
+}
+
 #[test]
 fn test_request_refund_with_valid_data() {
     let env = Env::default();
@@ -897,13 +914,13 @@ fn test_multiple_partial_refunds_for_same_payment() {
 
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
     let admin = Address::generate(&env);
     let payment_id = 7u64;
     let original_payment_amount = 1500i128;
     let reason = String::from_str(&env, "Partial refund");
 
-    env.mock_all_auths();
     let refund_id1 = client.request_refund(
         &merchant,
         &payment_id,
@@ -1016,13 +1033,13 @@ fn test_status_queries_and_counts() {
 
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
     let admin = Address::generate(&env);
     let payment_id = 10u64;
     let amount = 500i128;
     let reason = String::from_str(&env, "Test");
 
-    env.mock_all_auths();
     let r1 = client.request_refund(
         &merchant,
         &payment_id,
@@ -1376,10 +1393,10 @@ fn test_reason_code_analytics_cache_invalidated_on_process_within_window() {
 
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
     let reason = String::from_str(&env, "cache-test");
 
-    env.mock_all_auths();
     client.set_fraud_config(
         &admin,
         &FraudConfig {
@@ -1610,13 +1627,13 @@ fn test_can_refund_payment_helper() {
 
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
     let admin = Address::generate(&env);
     let payment_id = 77u64;
     let original_payment_amount = 1000i128;
     let reason = String::from_str(&env, "Helper");
 
-    env.mock_all_auths();
     let refund_id = client.request_refund(
         &merchant,
         &payment_id,
@@ -2072,7 +2089,7 @@ fn test_file_and_resolve_appeal_upheld_processes_refund() {
 
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
 
     let refund_id = client.request_refund(
         &merchant,
@@ -215,7 +215,7 @@ fn test_trigger_arbitration_timeout_settles_stake() {
             treasury_share_bps: 3000,
             treasury_address: treasury.clone(),
             fee_token: token_client.address.clone(),
-            fee_per_case: 1000,
+            fee_per_case: 300,
         },
     );
 
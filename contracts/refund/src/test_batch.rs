@@ -3,6 +3,23 @@
 use super::*;
 use soroban_sdk::{testutils::Address as _, Address, Env, String, Vec};
 
+/// Register a token and fund `merchant`'s refund reserve with `amount` of it,
+/// so approved refunds in that token can be processed.
+fn fund_refund_reserve(
+    env: &Env,
+    client: &RefundContractClient,
+    merchant: &Address,
+    amount: i128,
+) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(merchant, &amount);
+    client.deposit_refund_reserve(merchant, &token, &amount);
+    token
+}
+
 fn setup(env: &Env) -> (RefundContractClient, Address) {
     let id = env.register(RefundContract, ());
     let client = RefundContractClient::new(env, &id);
@@ -19,7 +36,7 @@ fn make_refund(
     payment_id: u64,
 ) -> u64 {
     let customer = Address::generate(env);
-    let token = Address::generate(env);
+    let token = fund_refund_reserve(env, client, merchant, 500);
     client.request_refund(
         merchant,
         &payment_id,
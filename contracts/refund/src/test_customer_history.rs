@@ -5,9 +5,26 @@ use crate::{
 };
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    Address, Env, String,
+    token, Address, Env, String,
 };
 
+/// Register a token and fund `merchant`'s refund reserve with `amount` of it,
+/// so approved refunds in that token can be processed.
+fn fund_refund_reserve(
+    env: &Env,
+    client: &RefundContractClient,
+    merchant: &Address,
+    amount: i128,
+) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(merchant, &amount);
+    client.deposit_refund_reserve(merchant, &token, &amount);
+    token
+}
+
 fn setup_test_env<'a>() -> (Env, RefundContractClient<'a>, Address, Address, Address) {
     let env = Env::default();
     env.mock_all_auths();
@@ -135,7 +152,7 @@ fn test_lifecycle_timestamps_on_reject() {
 #[test]
 fn test_lifecycle_timestamps_on_process() {
     let (env, client, admin, merchant, customer) = setup_test_env();
-    let token = Address::generate(&env);
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
 
     let refund_id = client.request_refund(
         &merchant,
@@ -379,7 +396,7 @@ fn test_get_customer_refund_summary_empty() {
 #[test]
 fn test_get_customer_refund_summary_with_data() {
     let (env, client, admin, merchant, customer) = setup_test_env();
-    let token = Address::generate(&env);
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
 
     // Create refund 1 - will be processed
     env.ledger().set_timestamp(100);
@@ -1,7 +1,27 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{ testutils::Address as _, Address, Env, String };
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env, String,
+};
+
+/// Register a token and fund `merchant`'s refund reserve with `amount` of it,
+/// so approved refunds in that token can be processed.
+fn fund_refund_reserve(
+    env: &Env,
+    client: &RefundContractClient,
+    merchant: &Address,
+    amount: i128,
+) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(merchant, &amount);
+    client.deposit_refund_reserve(merchant, &token, &amount);
+    token
+}
 
 fn request_refund_for_merchant(
     client: &RefundContractClient<'_>,
@@ -25,6 +45,15 @@ fn request_refund_for_merchant(
     )
 }
 
+/// Let the appeal window of the given rejected refunds lapse and finalize them,
+/// moving them from `PendingAppeal` to `Rejected`.
+fn finalize_denials(env: &Env, client: &RefundContractClient<'_>, refund_ids: &[u64]) {
+    env.ledger().with_mut(|li| li.timestamp += 604_800);
+    for refund_id in refund_ids {
+        client.finalize_denial(refund_id);
+    }
+}
+
 fn disable_fraud_checks(client: &RefundContractClient<'_>, admin: &Address) {
     client.set_fraud_config(
         admin,
@@ -92,9 +121,9 @@ fn test_get_merchant_refunds_by_status_filters_matches_and_offset() {
     let merchant_a = Address::generate(&env);
     let merchant_b = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
-
     env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant_a, 10_000);
+
     disable_fraud_checks(&client, &admin);
 
     let approved_one = request_refund_for_merchant(&client, &env, &merchant_a, &customer, &token, 11, 110);
@@ -112,6 +141,7 @@ fn test_get_merchant_refunds_by_status_filters_matches_and_offset() {
     client.process_refund(&admin, &processed_one);
     client.approve_refund(&admin, &other_approved);
     client.reject_refund(&admin, &other_rejected, &String::from_str(&env, "No"));
+    finalize_denials(&env, &client, &[rejected_one, other_rejected]);
 
     let approved = client.get_merchant_refunds_by_status(
         &merchant_a,
@@ -171,9 +201,9 @@ fn test_get_merchant_pending_refunds_returns_only_requested() {
     let merchant_a = Address::generate(&env);
     let merchant_b = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
-
     env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant_a, 10_000);
+
     disable_fraud_checks(&client, &admin);
 
     let pending_one = request_refund_for_merchant(&client, &env, &merchant_a, &customer, &token, 21, 210);
@@ -207,9 +237,9 @@ fn test_get_merchant_refund_summary_counts_current_statuses_and_amounts() {
     let merchant = Address::generate(&env);
     let empty_merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
-
     env.mock_all_auths();
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
+
     disable_fraud_checks(&client, &admin);
 
     let pending_one = request_refund_for_merchant(&client, &env, &merchant, &customer, &token, 31, 50);
@@ -225,6 +255,7 @@ fn test_get_merchant_refund_summary_counts_current_statuses_and_amounts() {
     client.reject_refund(&admin, &rejected_one, &String::from_str(&env, "No"));
     client.approve_refund(&admin, &processed_one);
     client.process_refund(&admin, &processed_one);
+    finalize_denials(&env, &client, &[rejected_one]);
 
     let summary = client.get_merchant_refund_summary(&merchant);
     assert_eq!(summary.total_requests, 5);
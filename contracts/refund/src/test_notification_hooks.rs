@@ -5,9 +5,26 @@ use crate::{
     RefundStatus,
 };
 use soroban_sdk::{
-    contract, contractimpl, symbol_short, testutils::Address as _, Address, Env, String, Vec,
+    contract, contractimpl, symbol_short, testutils::Address as _, token, Address, Env, String, Vec,
 };
 
+/// Register a token and fund `merchant`'s refund reserve with `amount` of it,
+/// so approved refunds in that token can be processed.
+fn fund_refund_reserve(
+    env: &Env,
+    client: &RefundContractClient,
+    merchant: &Address,
+    amount: i128,
+) -> Address {
+    let token_admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(merchant, &amount);
+    client.deposit_refund_reserve(merchant, &token, &amount);
+    token
+}
+
 // Mock subscriber contract for testing
 #[contract]
 pub struct MockSubscriber;
@@ -304,7 +321,7 @@ fn test_hook_invocation_on_refund_processed() {
     // Create, approve, and process a refund
     let merchant = Address::generate(&env);
     let customer = Address::generate(&env);
-    let token = Address::generate(&env);
+    let token = fund_refund_reserve(&env, &client, &merchant, 10_000);
 
     let refund_id = client.request_refund(
         &merchant,
@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Ledger},
+    Address, Env, String,
+};
+
+fn setup(env: &Env) -> (RefundContractClient<'_>, Address, u64) {
+    let id = env.register(RefundContract, ());
+    let client = RefundContractClient::new(env, &id);
+    let admin = Address::generate(env);
+    env.mock_all_auths();
+    client.initialize(&admin);
+
+    let merchant = Address::generate(env);
+    let customer = Address::generate(env);
+    let refund_id = client.request_refund(
+        &merchant,
+        &1u64,
+        &customer,
+        &100i128,
+        &1000i128,
+        &Address::generate(env),
+        &String::from_str(env, "reason"),
+        &RefundReasonCode::Other,
+        &0u64,
+    );
+    (client, customer, refund_id)
+}
+
+#[test]
+fn test_refund_and_indexes_stored_in_persistent_storage() {
+    let env = Env::default();
+    let (client, customer, refund_id) = setup(&env);
+
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().has(&DataKey::Refund(refund_id)));
+        assert!(!env.storage().instance().has(&DataKey::Refund(refund_id)));
+        assert!(env
+            .storage()
+            .persistent()
+            .has(&DataKey::CustomerRefundCount(customer.clone())));
+    });
+}
+
+#[test]
+fn test_extend_record_ttl_refreshes_refund_record() {
+    let env = Env::default();
+    let (client, _, refund_id) = setup(&env);
+
+    env.ledger()
+        .with_mut(|li| li.sequence_number += RECORD_TTL_EXTEND_TO - RECORD_TTL_THRESHOLD + 1);
+    client.extend_record_ttl(&refund_id);
+
+    let ttl = env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&DataKey::Refund(refund_id))
+    });
+    assert_eq!(ttl, RECORD_TTL_EXTEND_TO);
+}
+
+#[test]
+fn test_extend_record_ttl_unknown_refund_fails() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    let result = client.try_extend_record_ttl(&77);
+    assert_eq!(result, Err(Ok(Error::Core(CoreError::RefundNotFound))));
+}
+
+#[test]
+fn test_legacy_instance_refund_promoted_on_touch() {
+    let env = Env::default();
+    let (client, _, refund_id) = setup(&env);
+
+    // Simulate a refund written by a release that kept records in instance storage.
+    env.as_contract(&client.address, || {
+        let refund: Refund = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Refund(refund_id))
+            .unwrap();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Refund(refund_id));
+        env.storage()
+            .instance()
+            .set(&DataKey::Refund(refund_id), &refund);
+    });
+
+    client.extend_record_ttl(&refund_id);
+
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().has(&DataKey::Refund(refund_id)));
+        assert!(!env.storage().instance().has(&DataKey::Refund(refund_id)));
+    });
+    assert_eq!(client.get_refund(&refund_id).id, refund_id);
+}
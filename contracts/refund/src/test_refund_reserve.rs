@@ -0,0 +1,279 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, token, Address, Env, String};
+
+fn create_token_contract<'a>(
+    env: &Env,
+    admin: &Address,
+) -> (token::Client<'a>, token::StellarAssetClient<'a>) {
+    let contract = env.register_stellar_asset_contract_v2(admin.clone());
+    let contract_address = contract.address();
+    (
+        token::Client::new(env, &contract_address),
+        token::StellarAssetClient::new(env, &contract_address),
+    )
+}
+
+fn setup(env: &Env) -> (RefundContractClient<'_>, Address) {
+    let id = env.register(RefundContract, ());
+    let client = RefundContractClient::new(env, &id);
+    let admin = Address::generate(env);
+    env.mock_all_auths();
+    client.initialize(&admin);
+    (client, admin)
+}
+
+fn approved_refund(
+    client: &RefundContractClient,
+    env: &Env,
+    admin: &Address,
+    merchant: &Address,
+    customer: &Address,
+    token: &Address,
+    amount: i128,
+) -> u64 {
+    let refund_id = client.request_refund(
+        merchant,
+        &1u64,
+        customer,
+        &amount,
+        &1000i128,
+        token,
+        &String::from_str(env, "reason"),
+        &RefundReasonCode::Other,
+        &0u64,
+    );
+    client.approve_refund(admin, &refund_id);
+    refund_id
+}
+
+#[test]
+fn test_deposit_and_withdraw_refund_reserve() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+
+    let balance = client.deposit_refund_reserve(&merchant, &token_client.address, &700);
+    assert_eq!(balance, 700);
+    assert_eq!(
+        client.get_refund_reserve(&merchant, &token_client.address),
+        700
+    );
+    assert_eq!(token_client.balance(&merchant), 300);
+    assert_eq!(token_client.balance(&client.address), 700);
+
+    let balance = client.withdraw_refund_reserve(&merchant, &token_client.address, &200);
+    assert_eq!(balance, 500);
+    assert_eq!(token_client.balance(&merchant), 500);
+    assert_eq!(token_client.balance(&client.address), 500);
+}
+
+#[test]
+fn test_withdraw_more_than_reserve_fails() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+    client.deposit_refund_reserve(&merchant, &token_client.address, &100);
+
+    let result = client.try_withdraw_refund_reserve(&merchant, &token_client.address, &101);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Ext(ExtError::InsufficientRefundReserve)))
+    );
+}
+
+#[test]
+fn test_deposit_non_positive_amount_fails() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let (token_client, _) = create_token_contract(&env, &admin);
+
+    let result = client.try_deposit_refund_reserve(&merchant, &token_client.address, &0);
+    assert_eq!(result, Err(Ok(Error::Core(CoreError::InvalidAmount))));
+}
+
+#[test]
+fn test_process_refund_pays_customer_from_reserve() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+    client.deposit_refund_reserve(&merchant, &token_client.address, &1000);
+
+    let refund_id = approved_refund(
+        &client,
+        &env,
+        &admin,
+        &merchant,
+        &customer,
+        &token_client.address,
+        400,
+    );
+    client.process_refund(&admin, &refund_id);
+
+    assert_eq!(
+        client.get_refund(&refund_id).status,
+        RefundStatus::Processed
+    );
+    assert_eq!(token_client.balance(&customer), 400);
+    assert_eq!(
+        client.get_refund_reserve(&merchant, &token_client.address),
+        600
+    );
+}
+
+#[test]
+fn test_process_refund_pays_net_of_fee() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+    client.deposit_refund_reserve(&merchant, &token_client.address, &1000);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(
+            &SystemKey::RefundFeeConfig,
+            &RefundFeeConfig {
+                fee_bps: 100,
+                min_fee: 0,
+                max_fee: 1000,
+                treasury: treasury.clone(),
+                fee_token: token_client.address.clone(),
+                active: true,
+            },
+        );
+    });
+
+    let refund_id = approved_refund(
+        &client,
+        &env,
+        &admin,
+        &merchant,
+        &customer,
+        &token_client.address,
+        500,
+    );
+    client.process_refund(&admin, &refund_id);
+
+    assert_eq!(token_client.balance(&customer), 495);
+    assert_eq!(token_client.balance(&treasury), 5);
+    assert_eq!(
+        client.get_refund_reserve(&merchant, &token_client.address),
+        500
+    );
+}
+
+#[test]
+fn test_process_refund_debits_min_fee_above_amount() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+    client.deposit_refund_reserve(&merchant, &token_client.address, &1000);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(
+            &SystemKey::RefundFeeConfig,
+            &RefundFeeConfig {
+                fee_bps: 100,
+                min_fee: 50,
+                max_fee: 1000,
+                treasury: treasury.clone(),
+                fee_token: token_client.address.clone(),
+                active: true,
+            },
+        );
+    });
+
+    // The 50 minimum fee is larger than the 30 refund, so the reserve covers it
+    let refund_id = approved_refund(
+        &client,
+        &env,
+        &admin,
+        &merchant,
+        &customer,
+        &token_client.address,
+        30,
+    );
+    client.process_refund(&admin, &refund_id);
+
+    assert_eq!(token_client.balance(&customer), 0);
+    assert_eq!(token_client.balance(&treasury), 50);
+    assert_eq!(
+        client.get_refund_reserve(&merchant, &token_client.address),
+        950
+    );
+    assert_eq!(token_client.balance(&client.address), 950);
+}
+
+#[test]
+fn test_process_refund_fails_when_reserve_short() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&merchant, &1000);
+    client.deposit_refund_reserve(&merchant, &token_client.address, &100);
+
+    let refund_id = approved_refund(
+        &client,
+        &env,
+        &admin,
+        &merchant,
+        &customer,
+        &token_client.address,
+        400,
+    );
+    let result = client.try_process_refund(&admin, &refund_id);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Ext(ExtError::InsufficientRefundReserve)))
+    );
+
+    assert_eq!(client.get_refund(&refund_id).status, RefundStatus::Approved);
+    assert_eq!(token_client.balance(&customer), 0);
+    assert_eq!(
+        client.get_refund_reserve(&merchant, &token_client.address),
+        100
+    );
+}
+
+#[test]
+fn test_reserve_is_isolated_per_merchant() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let merchant = Address::generate(&env);
+    let other_merchant = Address::generate(&env);
+    let customer = Address::generate(&env);
+    let (token_client, token_admin) = create_token_contract(&env, &admin);
+    token_admin.mint(&other_merchant, &1000);
+    client.deposit_refund_reserve(&other_merchant, &token_client.address, &1000);
+
+    let refund_id = approved_refund(
+        &client,
+        &env,
+        &admin,
+        &merchant,
+        &customer,
+        &token_client.address,
+        400,
+    );
+    let result = client.try_process_refund(&admin, &refund_id);
+    assert_eq!(
+        result,
+        Err(Ok(Error::Ext(ExtError::InsufficientRefundReserve)))
+    );
+}
@@ -0,0 +1,57 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as _, vec, Address, BytesN, Env, IntoVal, Symbol};
+
+const UPGRADE_WASM: &[u8] = include_bytes!("../../test_fixtures/add_u64.wasm");
+
+fn setup(env: &Env) -> (RefundContractClient<'_>, Address, BytesN<32>) {
+    env.mock_all_auths();
+    let admin = Address::generate(env);
+    let client = RefundContractClient::new(env, &env.register(RefundContract, ()));
+    client.initialize(&admin);
+    let wasm_hash = env.deployer().upload_contract_wasm(UPGRADE_WASM);
+    (client, admin, wasm_hash)
+}
+
+fn stored_hash(env: &Env, contract: &Address, key: SystemKey) -> Option<BytesN<32>> {
+    env.as_contract(contract, || env.storage().instance().get(&key))
+}
+
+#[test]
+fn test_upgrade_replaces_code_and_keeps_previous_hash() {
+    let env = Env::default();
+    let (client, admin, wasm_hash) = setup(&env);
+    let earlier = BytesN::from_array(&env, &[3; 32]);
+    env.as_contract(&client.address, || {
+        env.storage().instance().set(&SystemKey::WasmHash, &earlier);
+    });
+
+    client.upgrade(&admin, &wasm_hash);
+
+    let sum: u64 = env.invoke_contract(
+        &client.address,
+        &Symbol::new(&env, "add"),
+        vec![&env, 1u64.into_val(&env), 1u64.into_val(&env)],
+    );
+    assert_eq!(sum, 2);
+    assert_eq!(
+        stored_hash(&env, &client.address, SystemKey::WasmHash),
+        Some(wasm_hash)
+    );
+    assert_eq!(
+        stored_hash(&env, &client.address, SystemKey::PreviousWasmHash),
+        Some(earlier)
+    );
+}
+
+#[test]
+fn test_upgrade_rejects_non_admin() {
+    let env = Env::default();
+    let (client, _admin, wasm_hash) = setup(&env);
+
+    let result = client.try_upgrade(&Address::generate(&env), &wasm_hash);
+    assert_eq!(result, Err(Ok(Error::Core(CoreError::Unauthorized))));
+    assert_eq!(client.get_wasm_hash(), None);
+    assert_eq!(client.get_previous_wasm_hash(), None);
+}
@@ -20,8 +20,12 @@ fn create_refund_and_issue_voucher(
 ) -> (u64, u64) {
     let merchant = Address::generate(env);
     let customer = Address::generate(env);
-    let token = Address::generate(env);
     let amount = 1000_i128;
+    // Vouchers are paid out of the refund funds the contract holds.
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    token::StellarAssetClient::new(env, &token).mint(&client.address, &amount);
     let payment_id = 1_u64;
     let reason = String::from_str(env, "defective product");
 